@@ -0,0 +1,96 @@
+//! Admin endpoints for the anonymized usage analytics of the backend services, i.e. the hourly
+//! counts of the subscriptions, endpoint hits and notifications. The counts are recorded by the
+//! services only when analytics is enabled, see `subvt-service-common` for details.
+use crate::{check_admin_key, ResultResponse, ServiceState};
+use actix_web::{get, web, HttpRequest};
+use serde::Deserialize;
+use subvt_service_common::response;
+use subvt_types::err::ServiceErrorCode;
+
+/// Analytics cover the last 24 hours by default.
+const DEFAULT_ANALYTICS_HOUR_COUNT: u32 = 24;
+/// Upper limit for the number of hours covered, i.e. 90 days.
+const MAX_ANALYTICS_HOUR_COUNT: u32 = 90 * 24;
+
+#[derive(Deserialize)]
+struct AnalyticsQueryParameters {
+    /// Number of most recent hours, including the current hour.
+    #[serde(rename(deserialize = "hour_count"))]
+    pub maybe_hour_count: Option<u32>,
+    #[serde(rename(deserialize = "service_name"))]
+    pub maybe_service_name: Option<String>,
+    /// Required only for the hourly counts.
+    #[serde(rename(deserialize = "metric"))]
+    pub maybe_metric: Option<String>,
+}
+
+impl AnalyticsQueryParameters {
+    /// Gets the validated hour count, or the error message.
+    fn get_hour_count(&self) -> Result<u32, String> {
+        let hour_count = self
+            .maybe_hour_count
+            .unwrap_or(DEFAULT_ANALYTICS_HOUR_COUNT);
+        if hour_count == 0 || hour_count > MAX_ANALYTICS_HOUR_COUNT {
+            return Err(format!(
+                "Hour count should be between 1 and {}.",
+                MAX_ANALYTICS_HOUR_COUNT
+            ));
+        }
+        Ok(hour_count)
+    }
+}
+
+/// Admin endpoint. `GET`s the total and peak hourly counts of each metric of each service in
+/// the given number of most recent hours, optionally filtered by service name.
+#[get("/analytics/summary")]
+pub(crate) async fn get_analytics_summaries(
+    request: HttpRequest,
+    query: web::Query<AnalyticsQueryParameters>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let hour_count = match query.get_hour_count() {
+        Ok(hour_count) => hour_count,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    Ok(response::ok(
+        state
+            .postgres
+            .get_analytics_summaries(hour_count, query.maybe_service_name.as_deref())
+            .await?,
+    ))
+}
+
+/// Admin endpoint. `GET`s the hourly counts of a metric in the given number of most recent
+/// hours, oldest hour first, optionally filtered by service name.
+#[get("/analytics/hourly")]
+pub(crate) async fn get_hourly_analytics_counts(
+    request: HttpRequest,
+    query: web::Query<AnalyticsQueryParameters>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let metric = match &query.maybe_metric {
+        Some(metric) => metric,
+        None => {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidRequest,
+                "Metric is required.".to_string(),
+            ))
+        }
+    };
+    let hour_count = match query.get_hour_count() {
+        Ok(hour_count) => hour_count,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    Ok(response::ok(
+        state
+            .postgres
+            .get_hourly_analytics_counts(metric, hour_count, query.maybe_service_name.as_deref())
+            .await?,
+    ))
+}
@@ -0,0 +1,216 @@
+//! API keys of the users for the public API access, and the API usage reports. The requests
+//! to the report service are counted per API key, see `subvt-report-service` for details.
+use crate::{auth, check_admin_key, check_user_exists_by_id, ResultResponse, ServiceState, CONFIG};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use subvt_service_common::response;
+use subvt_types::app::UserApiUsageReport;
+use subvt_types::err::ServiceErrorCode;
+
+/// Upper limit for the number of non-deleted API keys of a user.
+const MAX_USER_API_KEY_COUNT: usize = 5;
+/// Usage reports cover the last 30 days by default.
+const DEFAULT_API_USAGE_DAY_COUNT: u32 = 30;
+
+#[derive(Deserialize)]
+struct UserIdPathParameter {
+    pub user_id: u32,
+}
+
+#[derive(Deserialize)]
+struct ApiKeyIdPathParameter {
+    pub user_id: u32,
+    pub api_key_id: u32,
+}
+
+#[derive(Deserialize)]
+struct ApiUsageQueryParameters {
+    /// Number of most recent days, including the current day.
+    #[serde(rename(deserialize = "day_count"))]
+    pub maybe_day_count: Option<u32>,
+}
+
+impl ApiUsageQueryParameters {
+    /// Gets the validated day count, or the error message.
+    fn get_day_count(&self) -> Result<u32, String> {
+        let day_count = self.maybe_day_count.unwrap_or(DEFAULT_API_USAGE_DAY_COUNT);
+        if day_count == 0 || day_count > CONFIG.report.max_api_usage_day_count {
+            return Err(format!(
+                "Day count should be between 1 and {}.",
+                CONFIG.report.max_api_usage_day_count
+            ));
+        }
+        Ok(day_count)
+    }
+}
+
+/// `GET`s the non-deleted API keys of the user. The keys themselves are not returned, only
+/// their prefixes.
+#[get("/user/{user_id}/api-key")]
+pub(crate) async fn get_user_api_keys(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_api_keys(path_params.user_id)
+            .await?,
+    ))
+}
+
+/// Creates a new API key for the user. The response contains the key, which cannot be
+/// retrieved again.
+#[post("/user/{user_id}/api-key")]
+pub(crate) async fn create_user_api_key(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .get_user_api_keys(path_params.user_id)
+        .await?
+        .len()
+        >= MAX_USER_API_KEY_COUNT
+    {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "A user cannot have more than {} API keys.",
+                MAX_USER_API_KEY_COUNT
+            ),
+        ));
+    }
+    Ok(response::created(
+        state
+            .postgres
+            .save_user_api_key(path_params.user_id)
+            .await?,
+    ))
+}
+
+/// `DELETE`s the API key of the user. A soft delete, the usage history of the key is kept.
+#[delete("/user/{user_id}/api-key/{api_key_id}")]
+pub(crate) async fn delete_user_api_key(
+    path_params: web::Path<ApiKeyIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .delete_user_api_key(path_params.user_id, path_params.api_key_id)
+        .await?
+    {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "API key not found.".to_string(),
+        ))
+    }
+}
+
+/// `GET`s the API usage of the user in the given number of most recent days, along with the
+/// usage of the current day against the daily quota. See `UserApiUsageReport` in `subvt-types`
+/// for details.
+#[get("/user/{user_id}/usage")]
+pub(crate) async fn get_user_api_usage(
+    path_params: web::Path<UserIdPathParameter>,
+    query: web::Query<ApiUsageQueryParameters>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    let day_count = match query.get_day_count() {
+        Ok(day_count) => day_count,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    let daily_request_quota = CONFIG.report.api_daily_request_quota;
+    let today_request_count = state
+        .postgres
+        .get_user_today_api_request_count(path_params.user_id)
+        .await?;
+    Ok(response::ok(UserApiUsageReport {
+        user_id: path_params.user_id,
+        daily_request_quota,
+        today_request_count,
+        today_remaining_request_count: daily_request_quota.saturating_sub(today_request_count),
+        usage: state
+            .postgres
+            .get_user_api_usage(path_params.user_id, day_count)
+            .await?,
+    }))
+}
+
+/// Admin endpoint. `GET`s the total API usage of each user in the given number of most recent
+/// days, busiest user first.
+#[get("/api-usage/user")]
+pub(crate) async fn get_user_api_usage_summaries(
+    request: HttpRequest,
+    query: web::Query<ApiUsageQueryParameters>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let day_count = match query.get_day_count() {
+        Ok(day_count) => day_count,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_api_usage_summaries(day_count)
+            .await?,
+    ))
+}
+
+/// Admin endpoint. `GET`s the total API usage of all users for each of the given number of
+/// most recent days, most recent day first.
+#[get("/api-usage/daily")]
+pub(crate) async fn get_daily_api_usage(
+    request: HttpRequest,
+    query: web::Query<ApiUsageQueryParameters>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let day_count = match query.get_day_count() {
+        Ok(day_count) => day_count,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    Ok(response::ok(
+        state.postgres.get_daily_api_usage(day_count).await?,
+    ))
+}
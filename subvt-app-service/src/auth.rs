@@ -0,0 +1,166 @@
+//! Wallet sign-in flow for the clients that cannot store long-term secrets, such as browser
+//! frontends. The client requests a challenge for the user's public key, signs the challenge
+//! message with the wallet key, and exchanges the signature for a short-lived session token.
+//! The token is then sent in the `Authorization: Bearer` header of the REST requests, and can
+//! be used to subscribe to the user events instead of a signed timestamp.
+use crate::{ResultResponse, ServiceState, CONFIG};
+use actix_web::{delete, post, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use subvt_service_common::response;
+use subvt_types::app::UserAuthChallenge;
+use subvt_types::err::ServiceErrorCode;
+
+/// A challenge has to be signed and exchanged for a session within 5 minutes.
+const CHALLENGE_TTL_SECONDS: u64 = 5 * 60;
+
+/// Public keys are persisted as `0x`-prefixed upper-case hex strings.
+fn normalize_public_key_hex(public_key_hex: &str) -> String {
+    format!(
+        "0x{}",
+        public_key_hex.trim_start_matches("0x").to_uppercase()
+    )
+}
+
+/// Gets the token in the `Authorization: Bearer` header of the request.
+pub(crate) fn get_bearer_token(request: &HttpRequest) -> Option<&str> {
+    request
+        .headers()
+        .get("Authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(|token| token.trim())
+        .filter(|token| !token.is_empty())
+}
+
+/// Checks that the request has a session token of the user, if the sessions are required
+/// by the configuration.
+pub(crate) async fn check_user_session(
+    request: &HttpRequest,
+    state: &web::Data<ServiceState>,
+    user_id: u32,
+) -> anyhow::Result<Option<HttpResponse>> {
+    if !CONFIG.http.app_service_session_required {
+        return Ok(None);
+    }
+    let maybe_session_user_id = match get_bearer_token(request) {
+        Some(token) => state.postgres.get_session_user_id(token).await?,
+        None => None,
+    };
+    match maybe_session_user_id {
+        Some(session_user_id) if session_user_id == user_id => Ok(None),
+        Some(_) => Ok(Some(response::error(
+            ServiceErrorCode::Forbidden,
+            "Not authorized.".to_string(),
+        ))),
+        None => Ok(Some(response::error(
+            ServiceErrorCode::Unauthorized,
+            "Valid session token required.".to_string(),
+        ))),
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateAuthChallengeRequest {
+    pub public_key_hex: String,
+}
+
+/// Issues a sign-in challenge for the user with the given public key.
+/// See `UserAuthChallenge` in `subvt-types` for details.
+#[post("/auth/challenge")]
+pub(crate) async fn create_auth_challenge(
+    input: web::Json<CreateAuthChallengeRequest>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    let user = match state
+        .postgres
+        .get_user_by_public_key_hex(&normalize_public_key_hex(&input.public_key_hex))
+        .await?
+    {
+        Some(user) => user,
+        None => {
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "User not found.".to_string(),
+            ))
+        }
+    };
+    Ok(response::created(
+        state
+            .postgres
+            .save_user_auth_challenge(user.id, CHALLENGE_TTL_SECONDS)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct CreateSessionRequest {
+    pub public_key_hex: String,
+    pub nonce: String,
+    /// 0x-prefixed hex-encoded sr25519 signature of the challenge message.
+    pub signature_hex: String,
+}
+
+/// Exchanges a signed challenge for a session. A challenge can only be used once, even if
+/// the signature is invalid. See `UserSession` in `subvt-types` for details.
+#[post("/auth/session")]
+pub(crate) async fn create_session(
+    input: web::Json<CreateSessionRequest>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    let unauthorized_response = response::error(
+        ServiceErrorCode::Unauthorized,
+        "Invalid or expired challenge signature.".to_string(),
+    );
+    let user = match state
+        .postgres
+        .get_user_by_public_key_hex(&normalize_public_key_hex(&input.public_key_hex))
+        .await?
+    {
+        Some(user) => user,
+        None => return Ok(unauthorized_response),
+    };
+    if !state
+        .postgres
+        .consume_user_auth_challenge(user.id, &input.nonce)
+        .await?
+    {
+        return Ok(unauthorized_response);
+    }
+    if !user.verify_wallet_signature(
+        &UserAuthChallenge::get_message(&input.nonce),
+        &input.signature_hex,
+    ) {
+        return Ok(unauthorized_response);
+    }
+    Ok(response::created(
+        state
+            .postgres
+            .save_user_session(user.id, CONFIG.http.app_service_session_ttl_seconds)
+            .await?,
+    ))
+}
+
+/// Ends the session of the token in the `Authorization: Bearer` header, i.e. signs out.
+#[delete("/auth/session")]
+pub(crate) async fn delete_session(
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    let token = match get_bearer_token(&request) {
+        Some(token) => token,
+        None => {
+            return Ok(response::error(
+                ServiceErrorCode::Unauthorized,
+                "Session token required.".to_string(),
+            ))
+        }
+    };
+    if state.postgres.delete_user_session(token).await? {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Session not found.".to_string(),
+        ))
+    }
+}
@@ -1,22 +1,33 @@
 //! Application REST interface. Contains services such as user registration, network list,
 //! notification channels, user validator registration, user notification rules persistence
-//! and deletion, etc.
+//! and deletion, shared validator groups, API keys and usage reports, etc.
+use actix_web::dev::Service as _;
 use actix_web::web::Data;
-use actix_web::{delete, get, post, web, App, HttpResponse, HttpServer};
+use actix_web::{delete, get, post, put, web, App, HttpRequest, HttpResponse, HttpServer};
 use async_trait::async_trait;
 use lazy_static::lazy_static;
 use log::debug;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::sync::Arc;
 use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
-use subvt_service_common::{err::InternalServerError, Service};
+use subvt_service_common::{
+    analytics as service_analytics, err::InternalServerError, response, Service,
+};
 use subvt_types::app::{
-    NotificationPeriodType, User, UserNotificationChannel, UserNotificationRuleParameter,
-    UserValidator, PUBLIC_KEY_HEX_LENGTH,
+    NetworkDefaultNotificationRule, NotificationPeriodType, User, UserNominator,
+    UserNotificationChannel, UserNotificationRuleParameter, UserValidator, PUBLIC_KEY_HEX_LENGTH,
 };
-use subvt_types::err::ServiceError;
+use subvt_types::err::ServiceErrorCode;
+use subvt_types::local_time;
+
+pub mod analytics;
+pub mod api_usage;
+pub mod auth;
+pub mod user_event;
+pub mod validator_claim;
+pub mod validator_group;
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -24,6 +35,9 @@ lazy_static! {
 
 type ResultResponse = Result<HttpResponse, InternalServerError>;
 
+/// A mute can be at most 30 days long.
+const MAX_MUTE_DURATION_SECONDS: u64 = 30 * 24 * 60 * 60;
+
 #[derive(Clone)]
 pub struct ServiceState {
     pub postgres: Arc<PostgreSQLAppStorage>,
@@ -34,29 +48,54 @@ async fn check_user_exists_by_id(
     user_id: u32,
 ) -> anyhow::Result<Option<HttpResponse>> {
     if !state.postgres.user_exists_by_id(user_id).await? {
-        return Ok(Some(
-            HttpResponse::NotFound().json(ServiceError::from("User not found.".to_string())),
-        ));
+        return Ok(Some(response::error(
+            ServiceErrorCode::NotFound,
+            "User not found.".to_string(),
+        )));
     }
     Ok(None)
 }
 
+/// Checks the admin key header of a request to an admin endpoint. Admin endpoints are disabled
+/// when there is no admin key in the configuration.
+fn check_admin_key(request: &HttpRequest) -> Option<HttpResponse> {
+    let admin_key = &CONFIG.http.app_service_admin_key;
+    let is_authorized = !admin_key.is_empty()
+        && request
+            .headers()
+            .get("X-Admin-Key")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value == admin_key)
+            .unwrap_or(false);
+    if is_authorized {
+        None
+    } else {
+        Some(response::error(
+            ServiceErrorCode::Forbidden,
+            "Not authorized.".to_string(),
+        ))
+    }
+}
+
 /// `GET`s the list of networks supported by SubVT.
 #[get("/network")]
 pub async fn get_networks(state: web::Data<ServiceState>) -> ResultResponse {
-    Ok(HttpResponse::Ok().json(state.postgres.get_networks().await?))
+    Ok(response::ok(state.postgres.get_networks().await?))
 }
 
 /// `GET`s the list of supported notification channels, such as email, push notification, SMS, etc.
 #[get("/notification/channel")]
 async fn get_notification_channels(state: web::Data<ServiceState>) -> ResultResponse {
-    Ok(HttpResponse::Ok().json(state.postgres.get_notification_channels().await?))
+    Ok(response::ok(
+        state.postgres.get_notification_channels().await?,
+    ))
 }
 
-/// `GET`s the list of notification types and their parameters supported by SubVT.
+/// `GET`s the list of notification types supported by SubVT, with their severity, supported
+/// channels, parameters and the JSON schema of the parameters object.
 #[get("/notification/type")]
 async fn get_notification_types(state: web::Data<ServiceState>) -> ResultResponse {
-    Ok(HttpResponse::Ok().json(state.postgres.get_notification_types().await?))
+    Ok(response::ok(state.postgres.get_notification_types().await?))
 }
 
 /// Validates and creates a new user.
@@ -65,16 +104,20 @@ async fn create_user(state: web::Data<ServiceState>, mut user: web::Json<User>)
     let public_key_hex = user.public_key_hex.trim_start_matches("0x").to_uppercase();
     // validate public key hex length
     if public_key_hex.len() != PUBLIC_KEY_HEX_LENGTH {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(format!(
-            "Public key should be {} characters long hexadecimal string.",
-            PUBLIC_KEY_HEX_LENGTH
-        ))));
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Public key should be {} characters long hexadecimal string.",
+                PUBLIC_KEY_HEX_LENGTH
+            ),
+        ));
     }
     // validate hex format
     if hex::decode(&public_key_hex).is_err() {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
             "Public key should be valid hexadecimal string.".to_string(),
-        )));
+        ));
     }
     user.public_key_hex = format!("0x{}", public_key_hex);
     // check duplicate public key
@@ -83,12 +126,14 @@ async fn create_user(state: web::Data<ServiceState>, mut user: web::Json<User>)
         .user_exists_with_public_key(&user.public_key_hex)
         .await?
     {
-        return Ok(HttpResponse::Conflict().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::Conflict,
             "A user exists with the given public key.".to_string(),
-        )));
+        ));
     }
+    user.is_verified_operator = false;
     user.id = state.postgres.save_user(&user).await?;
-    Ok(HttpResponse::Created().json(user))
+    Ok(response::created(user))
 }
 
 #[derive(Deserialize)]
@@ -100,12 +145,18 @@ struct UserIdPathParameter {
 #[get("/user/{user_id}/notification/channel")]
 async fn get_user_notification_channels(
     path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
         return Ok(error_response);
     }
-    Ok(HttpResponse::Ok().json(
+    Ok(response::ok(
         state
             .postgres
             .get_user_notification_channels(path_params.user_id)
@@ -118,8 +169,14 @@ async fn get_user_notification_channels(
 async fn add_user_notification_channel(
     path_params: web::Path<UserIdPathParameter>,
     mut input: web::Json<UserNotificationChannel>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     input.user_id = path_params.user_id as u32;
     if let Some(error_response) = check_user_exists_by_id(&state, input.user_id).await? {
         return Ok(error_response);
@@ -130,30 +187,38 @@ async fn add_user_notification_channel(
         .notification_channel_exists(&input.channel_code)
         .await?
     {
-        return Ok(HttpResponse::NotFound().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
             "Notification channel not found.".to_string(),
-        )));
+        ));
     }
     if state
         .postgres
         .user_notification_channel_target_exists(&input)
         .await?
     {
-        return Ok(HttpResponse::Conflict().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::Conflict,
             "This target exists for the user.".to_string(),
-        )));
+        ));
     }
     // validate input
     if input.target.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
             "Invalid notification target.".to_string(),
-        )));
+        ));
+    }
+    if let Some(error_response) =
+        validate_channel_locale(input.timezone.as_deref(), input.locale.as_deref())
+    {
+        return Ok(error_response);
     }
     input.id = state
         .postgres
         .save_user_notification_channel(&input)
         .await?;
-    Ok(HttpResponse::Created().json(input))
+    Ok(response::created(input))
 }
 
 #[derive(Deserialize)]
@@ -162,21 +227,99 @@ struct UserNotificationChannelIdPathParameter {
     pub channel_id: u32,
 }
 
+/// Error response if the timezone is not an IANA timezone name or the locale is not a BCP 47
+/// language tag.
+fn validate_channel_locale(timezone: Option<&str>, locale: Option<&str>) -> Option<HttpResponse> {
+    if let Some(timezone) = timezone {
+        if !local_time::is_valid_timezone(timezone) {
+            return Some(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Invalid timezone {}.", timezone),
+            ));
+        }
+    }
+    if let Some(locale) = locale {
+        if !local_time::is_valid_locale(locale) {
+            return Some(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Invalid locale {}.", locale),
+            ));
+        }
+    }
+    None
+}
+
+#[derive(Deserialize, Serialize)]
+struct UserNotificationChannelLocale {
+    /// IANA timezone name, e.g. `Europe/Istanbul`.
+    pub timezone: Option<String>,
+    /// BCP 47 language tag, e.g. `en-US`.
+    pub locale: Option<String>,
+}
+
+/// Sets the timezone and the locale of the notification channel, e.g. when the device of a push
+/// notification channel changes its timezone. The era and epoch boundaries in the notifications
+/// sent to the channel get accompanied with their local times. `null` clears the value.
+#[put("/user/{user_id}/notification/channel/{channel_id}/locale")]
+async fn set_user_notification_channel_locale(
+    path_params: web::Path<UserNotificationChannelIdPathParameter>,
+    input: web::Json<UserNotificationChannelLocale>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if !state
+        .postgres
+        .user_notification_channel_exists(path_params.user_id, path_params.channel_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "User notification channel not found.".to_string(),
+        ));
+    }
+    if let Some(error_response) =
+        validate_channel_locale(input.timezone.as_deref(), input.locale.as_deref())
+    {
+        return Ok(error_response);
+    }
+    state
+        .postgres
+        .save_user_notification_channel_locale(
+            path_params.channel_id,
+            input.timezone.as_deref(),
+            input.locale.as_deref(),
+        )
+        .await?;
+    Ok(response::ok(input.into_inner()))
+}
+
 /// `DELETE`s the notification channel from the user's list of notification channels.
 /// A soft delete, but the user will no longer receive notifications on this channel.
 #[delete("/user/{user_id}/notification/channel/{channel_id}")]
 async fn delete_user_notification_channel(
     path_params: web::Path<UserNotificationChannelIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     let channel_exists = state
         .postgres
         .user_notification_channel_exists(path_params.user_id, path_params.channel_id)
         .await?;
     if !channel_exists {
-        return Ok(HttpResponse::NotFound().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
             "User notification channel not found.".to_string(),
-        )));
+        ));
     }
     match state
         .postgres
@@ -184,9 +327,10 @@ async fn delete_user_notification_channel(
         .await?
     {
         true => Ok(HttpResponse::NoContent().finish()),
-        false => Ok(HttpResponse::InternalServerError().json(ServiceError::from(
+        false => Ok(response::error(
+            ServiceErrorCode::Internal,
             "There was an error deleting the notification channel.".to_string(),
-        ))),
+        )),
     }
 }
 
@@ -194,12 +338,18 @@ async fn delete_user_notification_channel(
 #[get("/user/{user_id}/validator")]
 async fn get_user_validators(
     path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
         return Ok(error_response);
     }
-    Ok(HttpResponse::Ok().json(
+    Ok(response::ok(
         state
             .postgres
             .get_user_validators(path_params.user_id)
@@ -207,13 +357,29 @@ async fn get_user_validators(
     ))
 }
 
-/// Adds a new validator to the user's list of validators.
+#[derive(Deserialize)]
+struct AddUserValidatorQueryParameters {
+    /// Network's default notification rules get created for the validator unless this is `false`.
+    #[serde(rename(deserialize = "apply_default_rules"))]
+    maybe_apply_default_rules: Option<bool>,
+}
+
+/// Adds a new validator to the user's list of validators, then creates the default notification
+/// rules of the network for the validator with all the notification channels of the user,
+/// unless the user opts out. Default rules don't get created if the user has no channels.
 #[post("/user/{user_id}/validator")]
 async fn add_user_validator(
     path_params: web::Path<UserIdPathParameter>,
+    query: web::Query<AddUserValidatorQueryParameters>,
     mut input: web::Json<UserValidator>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     input.user_id = path_params.user_id;
     if let Some(error_response) = check_user_exists_by_id(&state, input.user_id).await? {
         return Ok(error_response);
@@ -224,18 +390,85 @@ async fn add_user_validator(
         .network_exists_by_id(input.network_id)
         .await?
     {
-        return Ok(
-            HttpResponse::NotFound().json(ServiceError::from("Network not found.".to_string()))
-        );
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
     }
     // check user validator exists
     if state.postgres.user_validator_exists(&input).await? {
-        return Ok(
-            HttpResponse::Conflict().json(ServiceError::from("User validator exists.".to_string()))
-        );
+        return Ok(response::error(
+            ServiceErrorCode::Conflict,
+            "User validator exists.".to_string(),
+        ));
     }
     input.id = state.postgres.save_user_validator(&input).await?;
-    Ok(HttpResponse::Created().json(input))
+    if query.maybe_apply_default_rules.unwrap_or(true) {
+        apply_network_default_notification_rules(&state, &input).await?;
+    }
+    Ok(response::created(input))
+}
+
+async fn apply_network_default_notification_rules(
+    state: &web::Data<ServiceState>,
+    user_validator: &UserValidator,
+) -> anyhow::Result<()> {
+    let default_rules = state
+        .postgres
+        .get_network_default_notification_rules(user_validator.network_id)
+        .await?;
+    if default_rules.is_empty() {
+        return Ok(());
+    }
+    let user_notification_channels = state
+        .postgres
+        .get_user_notification_channels(user_validator.user_id)
+        .await?;
+    if user_notification_channels.is_empty() {
+        debug!(
+            "User {} has no notification channels. Skip default rules.",
+            user_validator.user_id
+        );
+        return Ok(());
+    }
+    let user_validator_ids: HashSet<u32> = [user_validator.id].into_iter().collect();
+    let mut rule_count = 0;
+    for default_rule in &default_rules {
+        // only the channels that the notification type can be delivered to
+        let supported_channel_codes = state
+            .postgres
+            .get_notification_type_channel_codes(&default_rule.notification_type_code)
+            .await?;
+        let user_notification_channel_ids: HashSet<u32> = user_notification_channels
+            .iter()
+            .filter(|channel| supported_channel_codes.contains(&channel.channel_code))
+            .map(|channel| channel.id)
+            .collect();
+        if user_notification_channel_ids.is_empty() {
+            continue;
+        }
+        rule_count += 1;
+        state
+            .postgres
+            .save_user_notification_rule(
+                user_validator.user_id,
+                &default_rule.notification_type_code,
+                (default_rule.name.as_deref(), None),
+                (Some(user_validator.network_id), false, None),
+                (&default_rule.period_type, default_rule.period),
+                (
+                    &user_validator_ids,
+                    &user_notification_channel_ids,
+                    &default_rule.parameters,
+                ),
+            )
+            .await?;
+    }
+    debug!(
+        "Created {} default rule(s) for user validator {}.",
+        rule_count, user_validator.id
+    );
+    Ok(())
 }
 
 #[derive(Deserialize)]
@@ -249,16 +482,24 @@ struct UserValidatorIdPathParameter {
 #[delete("/user/{user_id}/validator/{user_validator_id}")]
 async fn delete_user_validator(
     path_params: web::Path<UserValidatorIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     // check validator exists
     if !state
         .postgres
         .user_validator_exists_by_id(path_params.user_id, path_params.user_validator_id)
         .await?
     {
-        return Ok(HttpResponse::NotFound()
-            .json(ServiceError::from("User validator not found.".to_string())));
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "User validator not found.".to_string(),
+        ));
     }
     match state
         .postgres
@@ -266,10 +507,187 @@ async fn delete_user_validator(
         .await?
     {
         true => Ok(HttpResponse::NoContent().finish()),
-        false => Ok(HttpResponse::InternalServerError().json(ServiceError::from(
+        false => Ok(response::error(
+            ServiceErrorCode::Internal,
             "There was an error deleting the user's validator.".to_string(),
-        ))),
+        )),
+    }
+}
+
+/// `GET`s the list of all nominators watched by the user.
+#[get("/user/{user_id}/nominator")]
+async fn get_user_nominators(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_nominators(path_params.user_id)
+            .await?,
+    ))
+}
+
+/// Adds a nominator to the user's list of watched nominators. Nominator notification rules,
+/// such as `chain_nominator_reward_below_expectation`, apply to all the watched nominators
+/// of the user.
+#[post("/user/{user_id}/nominator")]
+async fn add_user_nominator(
+    path_params: web::Path<UserIdPathParameter>,
+    mut input: web::Json<UserNominator>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    input.user_id = path_params.user_id;
+    if let Some(error_response) = check_user_exists_by_id(&state, input.user_id).await? {
+        return Ok(error_response);
+    }
+    // check network exists
+    if !state
+        .postgres
+        .network_exists_by_id(input.network_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
+    }
+    // check user nominator exists
+    if state.postgres.user_nominator_exists(&input).await? {
+        return Ok(response::error(
+            ServiceErrorCode::Conflict,
+            "User nominator exists.".to_string(),
+        ));
+    }
+    input.id = state.postgres.save_user_nominator(&input).await?;
+    Ok(response::created(input))
+}
+
+#[derive(Deserialize)]
+struct UserNominatorIdPathParameter {
+    pub user_id: u32,
+    pub user_nominator_id: u32,
+}
+
+/// `DELETE`s a nominator from the user's list of watched nominators.
+/// A soft delete, i.e. only marks the nominator as deleted.
+#[delete("/user/{user_id}/nominator/{user_nominator_id}")]
+async fn delete_user_nominator(
+    path_params: web::Path<UserNominatorIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    // check nominator exists
+    if !state
+        .postgres
+        .user_nominator_exists_by_id(path_params.user_id, path_params.user_nominator_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "User nominator not found.".to_string(),
+        ));
+    }
+    match state
+        .postgres
+        .delete_user_nominator(path_params.user_nominator_id)
+        .await?
+    {
+        true => Ok(HttpResponse::NoContent().finish()),
+        false => Ok(response::error(
+            ServiceErrorCode::Internal,
+            "There was an error deleting the user's nominator.".to_string(),
+        )),
+    }
+}
+
+/// Validates the rule parameters against the parameter types of the notification type.
+/// Returns the error response if the parameters are not valid.
+async fn validate_notification_rule_parameters(
+    state: &web::Data<ServiceState>,
+    notification_type_code: &str,
+    parameters: &[UserNotificationRuleParameter],
+) -> anyhow::Result<Option<HttpResponse>> {
+    let notification_parameter_types = state
+        .postgres
+        .get_notification_parameter_types(notification_type_code)
+        .await?;
+    let notification_parameter_type_ids: Vec<u32> = notification_parameter_types
+        .iter()
+        .map(|parameter_type| parameter_type.id)
+        .collect();
+    let irrelevant_parameter_type_ids: Vec<u32> = parameters
+        .iter()
+        .map(|parameter| parameter.parameter_type_id)
+        .filter(|id| !notification_parameter_type_ids.contains(id))
+        .collect();
+    if !irrelevant_parameter_type_ids.is_empty() {
+        return Ok(Some(response::error(
+            ServiceErrorCode::NotFound,
+            format!(
+                "Posted parameter(s) with id(s) {:?} not found for notification type '{}'.",
+                irrelevant_parameter_type_ids, notification_type_code
+            ),
+        )));
     }
+    let posted_parameter_type_ids: Vec<u32> = parameters
+        .iter()
+        .map(|parameter| parameter.parameter_type_id)
+        .collect();
+    // check if all non-optional parameters are sent
+    let missing_non_optional_parameter_type_ids: Vec<u32> = notification_parameter_types
+        .iter()
+        .filter(|parameter_type| {
+            !parameter_type.is_optional && !posted_parameter_type_ids.contains(&parameter_type.id)
+        })
+        .map(|parameter_type| parameter_type.id)
+        .collect();
+    if !missing_non_optional_parameter_type_ids.is_empty() {
+        return Ok(Some(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Missing non-optional parameter type ids: {:?}",
+                missing_non_optional_parameter_type_ids
+            ),
+        )));
+    }
+    // validate parameters
+    for parameter in parameters {
+        let parameter_type = notification_parameter_types
+            .iter()
+            .find(|parameter_type| parameter_type.id == parameter.parameter_type_id)
+            .unwrap();
+        if let (false, Some(validation_error_message)) = parameter.validate(parameter_type) {
+            return Ok(Some(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!(
+                    "Invalid '{}': {}",
+                    parameter_type.code, validation_error_message
+                ),
+            )));
+        }
+    }
+    Ok(None)
 }
 
 #[derive(Deserialize)]
@@ -279,6 +697,9 @@ struct CreateUserNotificationRuleRequest {
     pub network_id: Option<u32>,
     pub is_for_all_validators: bool,
     pub user_validator_ids: HashSet<u32>,
+    /// Applies the rule to the validators of a group that the user owns or has subscribed to,
+    /// instead of the user's own validators.
+    pub validator_group_id: Option<u32>,
     pub period_type: NotificationPeriodType,
     pub period: u16,
     pub user_notification_channel_ids: HashSet<u32>,
@@ -290,12 +711,18 @@ struct CreateUserNotificationRuleRequest {
 #[get("/user/{user_id}/notification/rule")]
 async fn get_user_notification_rules(
     path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
         return Ok(error_response);
     }
-    Ok(HttpResponse::Ok().json(
+    Ok(response::ok(
         state
             .postgres
             .get_user_notification_rules(path_params.user_id)
@@ -309,8 +736,14 @@ async fn get_user_notification_rules(
 async fn create_user_notification_rule(
     path_params: web::Path<UserIdPathParameter>,
     mut input: web::Json<CreateUserNotificationRuleRequest>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
         return Ok(error_response);
     }
@@ -320,25 +753,76 @@ async fn create_user_notification_rule(
         .notification_type_exists_by_code(&input.notification_type_code)
         .await?
     {
-        return Ok(HttpResponse::NotFound().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
             "Notification type not found.".to_string(),
-        )));
+        ));
+    }
+    // operator-only notification types require a validator claim
+    if state
+        .postgres
+        .notification_type_is_operator_only(&input.notification_type_code)
+        .await?
+    {
+        let is_verified_operator = match state.postgres.get_user_by_id(path_params.user_id).await? {
+            Some(user) => user.is_verified_operator,
+            None => false,
+        };
+        if !is_verified_operator {
+            return Ok(response::error(
+                ServiceErrorCode::Forbidden,
+                "Notification type is only available to verified validator operators.".to_string(),
+            ));
+        }
     }
     // check network exists
     if let Some(network_id) = input.network_id {
         if !state.postgres.network_exists_by_id(network_id).await? {
-            return Ok(
-                HttpResponse::NotFound().json(ServiceError::from("Network not found.".to_string()))
-            );
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "Network not found.".to_string(),
+            ));
+        }
+    }
+    // check validator group
+    if let Some(validator_group_id) = input.validator_group_id {
+        let validator_group = match state
+            .postgres
+            .get_validator_group_by_id(validator_group_id)
+            .await?
+        {
+            Some(validator_group)
+                if state
+                    .postgres
+                    .user_has_access_to_validator_group(path_params.user_id, validator_group_id)
+                    .await? =>
+            {
+                validator_group
+            }
+            _ => {
+                return Ok(response::error(
+                    ServiceErrorCode::NotFound,
+                    "Validator group not found.".to_string(),
+                ))
+            }
+        };
+        if input.network_id.unwrap_or(validator_group.network_id) != validator_group.network_id {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidRequest,
+                "Rule network should be the network of the validator group.".to_string(),
+            ));
         }
+        input.network_id = Some(validator_group.network_id);
+        input.is_for_all_validators = false;
     }
     // check validators
-    if input.is_for_all_validators {
+    if input.is_for_all_validators || input.validator_group_id.is_some() {
         input.user_validator_ids.clear();
     } else if input.user_validator_ids.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
             "At least 1 user validator should be selected.".to_string(),
-        )));
+        ));
     }
     for user_validator_id in &input.user_validator_ids {
         if !state
@@ -346,15 +830,18 @@ async fn create_user_notification_rule(
             .user_validator_exists_by_id(path_params.user_id, *user_validator_id)
             .await?
         {
-            return Ok(HttpResponse::NotFound()
-                .json(ServiceError::from("User validator not found.".to_string())));
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "User validator not found.".to_string(),
+            ));
         }
     }
     // check if there is at least one notification channel
     if input.user_notification_channel_ids.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
             "There should be at least 1 notification channel selected.".to_string(),
-        )));
+        ));
     }
     // check user notification channel ids
     for user_notification_channel_id in &input.user_notification_channel_ids {
@@ -363,70 +850,45 @@ async fn create_user_notification_rule(
             .user_notification_channel_exists(path_params.user_id, *user_notification_channel_id)
             .await?
         {
-            return Ok(HttpResponse::NotFound().json(ServiceError::from(
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
                 "User notification channel not found.".to_string(),
-            )));
+            ));
         }
-    }
-    let notification_parameter_types = state
-        .postgres
-        .get_notification_parameter_types(&input.notification_type_code)
-        .await?;
-    let notification_parameter_type_ids: Vec<u32> = notification_parameter_types
-        .iter()
-        .map(|parameter_type| parameter_type.id)
-        .collect();
-    let irrelevant_parameter_type_ids: Vec<u32> = input
-        .parameters
-        .iter()
-        .map(|parameter| parameter.parameter_type_id)
-        .filter(|id| !notification_parameter_type_ids.contains(id))
-        .collect();
-    if !irrelevant_parameter_type_ids.is_empty() {
-        return Ok(HttpResponse::NotFound().json(ServiceError::from(format!(
-            "Posted parameter(s) with id(s) {:?} not found for notification type '{}'.",
-            irrelevant_parameter_type_ids, input.notification_type_code
-        ))));
-    }
-    let posted_parameter_type_ids: Vec<u32> = input
-        .parameters
-        .iter()
-        .map(|parameter| parameter.parameter_type_id)
-        .collect();
-    // check if all non-optional parameters are sent
-    let missing_non_optional_parameter_type_ids: Vec<u32> = notification_parameter_types
-        .iter()
-        .filter(|parameter_type| {
-            !parameter_type.is_optional && !posted_parameter_type_ids.contains(&parameter_type.id)
-        })
-        .map(|parameter_type| parameter_type.id)
-        .collect();
-    if !missing_non_optional_parameter_type_ids.is_empty() {
-        return Ok(HttpResponse::BadRequest().json(ServiceError::from(format!(
-            "Missing non-optional parameter type ids: {:?}",
-            missing_non_optional_parameter_type_ids
-        ))));
-    }
-    // validate parameters
-    for parameter in &input.parameters {
-        let parameter_type = notification_parameter_types
-            .iter()
-            .find(|parameter_type| parameter_type.id == parameter.parameter_type_id)
-            .unwrap();
-        if let (false, Some(validation_error_message)) = parameter.validate(parameter_type) {
-            return Ok(HttpResponse::BadRequest().json(ServiceError::from(format!(
-                "Invalid '{}': {}",
-                parameter_type.code, validation_error_message
-            ))));
+        if !state
+            .postgres
+            .notification_type_supports_user_notification_channel(
+                &input.notification_type_code,
+                *user_notification_channel_id,
+            )
+            .await?
+        {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidRequest,
+                "Notification type is not supported on the notification channel.".to_string(),
+            ));
         }
     }
+    if let Some(error_response) = validate_notification_rule_parameters(
+        &state,
+        &input.notification_type_code,
+        &input.parameters,
+    )
+    .await?
+    {
+        return Ok(error_response);
+    }
     let rule_id = state
         .postgres
         .save_user_notification_rule(
             path_params.user_id,
             &input.notification_type_code,
             (input.name.as_deref(), input.notes.as_deref()),
-            (input.network_id, input.is_for_all_validators),
+            (
+                input.network_id,
+                input.is_for_all_validators,
+                input.validator_group_id,
+            ),
             (&input.period_type, input.period),
             (
                 &input.user_validator_ids,
@@ -436,7 +898,7 @@ async fn create_user_notification_rule(
         )
         .await?;
     // get rule
-    Ok(HttpResponse::Created().json(
+    Ok(response::created(
         state
             .postgres
             .get_user_notification_rule_by_id(rule_id)
@@ -456,8 +918,14 @@ struct UserNotificationRuleIdPathParameter {
 #[delete("/user/{user_id}/notification/rule/{user_notification_rule_id}")]
 async fn delete_user_notification_rule(
     path_params: web::Path<UserNotificationRuleIdPathParameter>,
+    request: HttpRequest,
     state: web::Data<ServiceState>,
 ) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
     // check rule exists
     if !state
         .postgres
@@ -467,9 +935,10 @@ async fn delete_user_notification_rule(
         )
         .await?
     {
-        return Ok(HttpResponse::NotFound().json(ServiceError::from(
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
             "User notification rule not found.".to_string(),
-        )));
+        ));
     }
     match state
         .postgres
@@ -477,9 +946,381 @@ async fn delete_user_notification_rule(
         .await?
     {
         true => Ok(HttpResponse::NoContent().finish()),
-        false => Ok(HttpResponse::InternalServerError().json(ServiceError::from(
+        false => Ok(response::error(
+            ServiceErrorCode::Internal,
             "There was an error deleting the user notification rule.".to_string(),
-        ))),
+        )),
+    }
+}
+
+/// `GET`s the user's notification mutes that haven't expired yet.
+#[get("/user/{user_id}/notification/mute")]
+async fn get_user_notification_mutes(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_notification_mutes(path_params.user_id)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct CreateUserNotificationMuteRequest {
+    /// Mutes the notifications for all the user's validators if not given.
+    pub user_validator_id: Option<u32>,
+    /// Mutes all notification types if not given.
+    pub notification_type_code: Option<String>,
+    pub duration_seconds: u64,
+}
+
+/// Temporarily mutes the user's notifications for a validator and/or a notification type,
+/// e.g. the offline notifications of a validator for 24 hours during maintenance. The rules
+/// stay as they are, and the muted notifications don't get sent after the mute either.
+#[post("/user/{user_id}/notification/mute")]
+async fn create_user_notification_mute(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<CreateUserNotificationMuteRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    if input.duration_seconds == 0 || input.duration_seconds > MAX_MUTE_DURATION_SECONDS {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Mute duration should be between 1 and {} seconds.",
+                MAX_MUTE_DURATION_SECONDS
+            ),
+        ));
+    }
+    if let Some(user_validator_id) = input.user_validator_id {
+        if !state
+            .postgres
+            .user_validator_exists_by_id(path_params.user_id, user_validator_id)
+            .await?
+        {
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "User validator not found.".to_string(),
+            ));
+        }
+    }
+    if let Some(notification_type_code) = &input.notification_type_code {
+        if !state
+            .postgres
+            .notification_type_exists_by_code(notification_type_code)
+            .await?
+        {
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "Notification type not found.".to_string(),
+            ));
+        }
+    }
+    Ok(response::created(
+        state
+            .postgres
+            .save_user_notification_mute(
+                path_params.user_id,
+                input.user_validator_id,
+                input.notification_type_code.as_deref(),
+                input.duration_seconds,
+            )
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct UserNotificationMuteIdPathParameter {
+    pub user_id: u32,
+    pub user_notification_mute_id: u32,
+}
+
+/// `DELETE`s a mute, i.e. ends it before its expiry.
+#[delete("/user/{user_id}/notification/mute/{user_notification_mute_id}")]
+async fn delete_user_notification_mute(
+    path_params: web::Path<UserNotificationMuteIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .delete_user_notification_mute(path_params.user_id, path_params.user_notification_mute_id)
+        .await?
+    {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "User notification mute not found.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct UserCurrency {
+    /// Lower-case fiat currency code, e.g. `usd`. No fiat equivalents are displayed if `None`.
+    pub currency_code: Option<String>,
+}
+
+/// `GET`s the fiat currency that the user prefers the amounts in the notifications to be
+/// accompanied with.
+#[get("/user/{user_id}/currency")]
+async fn get_user_currency(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(UserCurrency {
+        currency_code: state
+            .postgres
+            .get_user_currency_code(path_params.user_id)
+            .await?,
+    }))
+}
+
+/// Sets the fiat currency preference of the user, must be one of the currencies tracked
+/// by `subvt-price-updater`. A `null` currency code clears the preference.
+#[put("/user/{user_id}/currency")]
+async fn set_user_currency(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<UserCurrency>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    let maybe_currency_code = input
+        .currency_code
+        .as_ref()
+        .map(|currency_code| currency_code.to_lowercase());
+    if let Some(currency_code) = &maybe_currency_code {
+        if !CONFIG.price.currencies.contains(currency_code) {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Currency {} is not supported.", currency_code),
+            ));
+        }
+    }
+    state
+        .postgres
+        .save_user_currency_code(path_params.user_id, maybe_currency_code.as_deref())
+        .await?;
+    Ok(response::ok(UserCurrency {
+        currency_code: maybe_currency_code,
+    }))
+}
+
+#[derive(Deserialize)]
+struct NetworkIdPathParameter {
+    pub network_id: u32,
+}
+
+/// `GET`s the default notification rules of the network, which get created for the validators
+/// that the users add on the network.
+#[get("/network/{network_id}/notification/default-rule")]
+async fn get_network_default_notification_rules(
+    path_params: web::Path<NetworkIdPathParameter>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if !state
+        .postgres
+        .network_exists_by_id(path_params.network_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_network_default_notification_rules(path_params.network_id)
+            .await?,
+    ))
+}
+
+/// Admin endpoint. Creates the default rule of the network for the notification type,
+/// or replaces the existing one. Doesn't affect the rules created before.
+#[post("/network/{network_id}/notification/default-rule")]
+async fn save_network_default_notification_rule(
+    request: HttpRequest,
+    path_params: web::Path<NetworkIdPathParameter>,
+    mut input: web::Json<NetworkDefaultNotificationRule>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    input.network_id = path_params.network_id;
+    if !state
+        .postgres
+        .network_exists_by_id(input.network_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
+    }
+    if !state
+        .postgres
+        .notification_type_exists_by_code(&input.notification_type_code)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Notification type not found.".to_string(),
+        ));
+    }
+    if let Some(error_response) = validate_notification_rule_parameters(
+        &state,
+        &input.notification_type_code,
+        &input.parameters,
+    )
+    .await?
+    {
+        return Ok(error_response);
+    }
+    input.id = state
+        .postgres
+        .save_network_default_notification_rule(&input)
+        .await?;
+    Ok(response::created(input))
+}
+
+#[derive(Deserialize)]
+struct NetworkDefaultNotificationRulePathParameter {
+    pub network_id: u32,
+    pub notification_type_code: String,
+}
+
+/// Admin endpoint. `DELETE`s the default rule of the network for the notification type.
+/// Doesn't affect the rules created before.
+#[delete("/network/{network_id}/notification/default-rule/{notification_type_code}")]
+async fn delete_network_default_notification_rule(
+    request: HttpRequest,
+    path_params: web::Path<NetworkDefaultNotificationRulePathParameter>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .delete_network_default_notification_rule(
+            path_params.network_id,
+            &path_params.notification_type_code,
+        )
+        .await?
+    {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Default notification rule not found.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct UnsubscribeTokenPathParameter {
+    pub unsubscribe_token: String,
+}
+
+const UNSUBSCRIBE_CONFIRMATION_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head><meta charset="utf-8"><title>Unsubscribe</title></head>
+<body>
+<p>Do you want to stop receiving these notifications by email?</p>
+<form method="post"><button type="submit" name="List-Unsubscribe" value="One-Click">Unsubscribe</button></form>
+</body>
+</html>"#;
+
+fn get_invalid_unsubscribe_token_response() -> HttpResponse {
+    HttpResponse::NotFound()
+        .content_type("text/plain; charset=utf-8")
+        .body("Invalid or expired unsubscribe link.")
+}
+
+/// Confirmation page of the unsubscribe link in the footer of the notification emails (see
+/// `subvt-notification-sender`). Doesn't unsubscribe, since the links get opened by the link
+/// scanners of the mail servers too. The page's form posts to the same URL.
+#[get("/notification/unsubscribe/{unsubscribe_token}")]
+async fn get_unsubscribe_confirmation_page(
+    path_params: web::Path<UnsubscribeTokenPathParameter>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if state
+        .postgres
+        .user_notification_rule_channel_exists_by_unsubscribe_token(&path_params.unsubscribe_token)
+        .await?
+    {
+        Ok(HttpResponse::Ok()
+            .content_type("text/html; charset=utf-8")
+            .body(UNSUBSCRIBE_CONFIRMATION_PAGE))
+    } else {
+        Ok(get_invalid_unsubscribe_token_response())
+    }
+}
+
+/// Removes a channel from a notification rule. Posted by the confirmation page, or by the mail
+/// clients as the RFC 8058 one-click unsubscribe of the `List-Unsubscribe` header of the
+/// notification emails, so responds with plain text.
+#[post("/notification/unsubscribe/{unsubscribe_token}")]
+async fn unsubscribe_user_notification_rule_channel(
+    path_params: web::Path<UnsubscribeTokenPathParameter>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if state
+        .postgres
+        .delete_user_notification_rule_channel_by_unsubscribe_token(&path_params.unsubscribe_token)
+        .await?
+    {
+        Ok(HttpResponse::Ok()
+            .content_type("text/plain; charset=utf-8")
+            .body("You have been unsubscribed from these notifications."))
+    } else {
+        Ok(get_invalid_unsubscribe_token_response())
     }
 }
 
@@ -497,32 +1338,85 @@ impl Service for AppService {
         // persistence instance
         let postgres =
             Arc::new(PostgreSQLAppStorage::new(&CONFIG, CONFIG.get_app_postgres_url()).await?);
+        let (user_event_sender, _) = tokio::sync::broadcast::channel(100);
+        debug!("Starting user event WS server.");
+        let user_event_server_handle = user_event::run_user_event_server(
+            &CONFIG.rpc.host,
+            CONFIG.rpc.app_user_event_port,
+            postgres.clone(),
+            user_event_sender.clone(),
+        )
+        .await?;
+        let user_event_listener =
+            user_event::listen_user_events(postgres.clone(), user_event_sender);
         debug!("Starting HTTP service.");
         let server = HttpServer::new(move || {
             App::new()
                 .app_data(Data::new(ServiceState {
                     postgres: postgres.clone(),
                 }))
-                .app_data(web::JsonConfig::default().error_handler(|err, _req| {
-                    actix_web::error::InternalError::from_response(
-                        "",
-                        HttpResponse::BadRequest().json(ServiceError::from(format!("{}", err))),
-                    )
-                    .into()
-                }))
+                .wrap_fn(|request, service| {
+                    let response = service.call(request);
+                    async move {
+                        let response = response.await?;
+                        if let Some(pattern) = response.request().match_pattern() {
+                            service_analytics::record(&format!("endpoint:{}", pattern), 1);
+                        }
+                        Ok(response)
+                    }
+                })
+                .app_data(web::JsonConfig::default().error_handler(response::handle_json_error))
+                .app_data(web::QueryConfig::default().error_handler(response::handle_query_error))
+                .app_data(web::PathConfig::default().error_handler(response::handle_path_error))
                 .service(get_networks)
+                .service(get_network_default_notification_rules)
+                .service(save_network_default_notification_rule)
+                .service(delete_network_default_notification_rule)
                 .service(get_notification_channels)
                 .service(get_notification_types)
                 .service(create_user)
+                .service(auth::create_auth_challenge)
+                .service(auth::create_session)
+                .service(auth::delete_session)
                 .service(add_user_notification_channel)
                 .service(get_user_notification_channels)
                 .service(delete_user_notification_channel)
+                .service(set_user_notification_channel_locale)
                 .service(get_user_validators)
                 .service(add_user_validator)
                 .service(delete_user_validator)
+                .service(get_user_nominators)
+                .service(add_user_nominator)
+                .service(delete_user_nominator)
                 .service(create_user_notification_rule)
                 .service(get_user_notification_rules)
                 .service(delete_user_notification_rule)
+                .service(create_user_notification_mute)
+                .service(get_user_notification_mutes)
+                .service(delete_user_notification_mute)
+                .service(get_user_currency)
+                .service(set_user_currency)
+                .service(validator_group::get_shared_validator_group)
+                .service(validator_group::get_user_validator_groups)
+                .service(validator_group::create_validator_group)
+                .service(validator_group::subscribe_validator_group)
+                .service(validator_group::unsubscribe_validator_group)
+                .service(validator_group::update_validator_group)
+                .service(validator_group::delete_validator_group)
+                .service(api_usage::get_user_api_keys)
+                .service(api_usage::create_user_api_key)
+                .service(api_usage::delete_user_api_key)
+                .service(api_usage::get_user_api_usage)
+                .service(api_usage::get_user_api_usage_summaries)
+                .service(api_usage::get_daily_api_usage)
+                .service(validator_claim::create_validator_claim_challenge)
+                .service(validator_claim::create_validator_claim)
+                .service(validator_claim::get_user_operator_validators)
+                .service(validator_claim::delete_user_operator_validator)
+                .service(analytics::get_analytics_summaries)
+                .service(analytics::get_hourly_analytics_counts)
+                .service(get_unsubscribe_confirmation_page)
+                .service(unsubscribe_user_notification_rule_channel)
         })
         .workers(10)
         .disable_signals()
@@ -531,7 +1425,16 @@ impl Service for AppService {
             CONFIG.http.host, CONFIG.http.app_service_port,
         ))?
         .run();
-        let (server_result, _) = tokio::join!(server, on_server_ready());
-        Ok(server_result?)
+        let (result, _) = tokio::join!(
+            async {
+                tokio::select! {
+                    server_result = server => server_result.map_err(anyhow::Error::from),
+                    listener_result = user_event_listener => listener_result,
+                }
+            },
+            on_server_ready()
+        );
+        user_event_server_handle.stop()?;
+        result
     }
 }
@@ -0,0 +1,242 @@
+//! User event WebSocket server. Pushes changes to the authenticated user's own resources
+//! (newly persisted notifications, user validators added or deleted from another device).
+//!
+//! Supports two RPC methods: `subscribe_user_events` and `unsubscribe_user_events`.
+//! `subscribe_user_events` accepts three parameters: user id, current UNIX timestamp in
+//! milliseconds, and the 0x-prefixed hex-encoded sr25519 signature of the message
+//! `subvt_user_events:{user_id}:{timestamp}` signed with the user's key. Alternatively, it
+//! accepts two parameters: user id and a session token of the user from the wallet sign-in flow.
+//! A signed timestamp can be used only once per user, so a captured subscription request cannot
+//! be replayed within the timestamp tolerance.
+use jsonrpsee::ws_server::{RpcModule, WsServerBuilder, WsServerHandle};
+use log::{debug, error};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_types::app::UserEvent;
+use tokio::sync::broadcast;
+
+/// Signed timestamps older or newer than this are rejected. Signed timestamps within the
+/// tolerance are recorded until they expire, and a repeated one is rejected.
+const AUTH_TIMESTAMP_TOLERANCE_MILLIS: u64 = 60_000;
+
+struct UserEventContext {
+    postgres: Arc<PostgreSQLAppStorage>,
+    event_sender: broadcast::Sender<UserEvent>,
+    /// `(user_id, timestamp)` pairs of the accepted signatures that haven't expired yet.
+    /// Kept in memory, since there's a single user event server.
+    used_signed_timestamps: Arc<Mutex<HashSet<(u32, u64)>>>,
+}
+
+/// Subscription credentials.
+enum Credentials {
+    Signature {
+        timestamp: u64,
+        signature_hex: String,
+    },
+    Session {
+        token: String,
+    },
+}
+
+/// Records the signed timestamp of the user after pruning the expired ones. Returns `false` if
+/// the timestamp has already been used by the user.
+fn record_signed_timestamp(
+    used_signed_timestamps: &Mutex<HashSet<(u32, u64)>>,
+    user_id: u32,
+    timestamp: u64,
+    now: u64,
+) -> bool {
+    let mut used_signed_timestamps = used_signed_timestamps.lock().unwrap();
+    used_signed_timestamps
+        .retain(|(_, used_timestamp)| used_timestamp + AUTH_TIMESTAMP_TOLERANCE_MILLIS >= now);
+    used_signed_timestamps.insert((user_id, timestamp))
+}
+
+async fn authenticate(
+    postgres: &PostgreSQLAppStorage,
+    used_signed_timestamps: &Mutex<HashSet<(u32, u64)>>,
+    user_id: u32,
+    credentials: &Credentials,
+) -> anyhow::Result<bool> {
+    let (timestamp, signature_hex) = match credentials {
+        Credentials::Signature {
+            timestamp,
+            signature_hex,
+        } => (*timestamp, signature_hex),
+        Credentials::Session { token } => {
+            return Ok(postgres.get_session_user_id(token).await? == Some(user_id));
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)?
+        .as_millis() as u64;
+    let difference = if now > timestamp {
+        now - timestamp
+    } else {
+        timestamp - now
+    };
+    if difference > AUTH_TIMESTAMP_TOLERANCE_MILLIS {
+        return Ok(false);
+    }
+    let user = match postgres.get_user_by_id(user_id).await? {
+        Some(user) => user,
+        None => return Ok(false),
+    };
+    let message = format!("subvt_user_events:{}:{}", user_id, timestamp);
+    if !user.verify_signature(message.as_bytes(), signature_hex) {
+        return Ok(false);
+    }
+    // record only after verification, so that unsigned requests cannot use up timestamps
+    Ok(record_signed_timestamp(
+        used_signed_timestamps,
+        user_id,
+        timestamp,
+        now,
+    ))
+}
+
+/// Forwards the user events received from the application database to the broadcast channel.
+/// Returns only on error.
+pub async fn listen_user_events(
+    postgres: Arc<PostgreSQLAppStorage>,
+    event_sender: broadcast::Sender<UserEvent>,
+) -> anyhow::Result<()> {
+    let mut listener = postgres.get_user_event_listener().await?;
+    debug!("Listening to user events.");
+    loop {
+        let notification = listener.recv().await?;
+        match serde_json::from_str::<UserEvent>(notification.payload()) {
+            Ok(user_event) => {
+                // error only means that there are no subscribers at the moment
+                let _ = event_sender.send(user_event);
+            }
+            Err(error) => error!(
+                "Cannot deserialize user event {}: {:?}",
+                notification.payload(),
+                error
+            ),
+        }
+    }
+}
+
+pub async fn run_user_event_server(
+    host: &str,
+    port: u16,
+    postgres: Arc<PostgreSQLAppStorage>,
+    event_sender: broadcast::Sender<UserEvent>,
+) -> anyhow::Result<WsServerHandle> {
+    let rpc_ws_server = WsServerBuilder::default()
+        .build(format!("{}:{}", host, port))
+        .await?;
+    let mut rpc_module = RpcModule::new(UserEventContext {
+        postgres,
+        event_sender,
+        used_signed_timestamps: Default::default(),
+    });
+    rpc_module.register_subscription(
+        "subscribe_user_events",
+        "subscribe_user_events",
+        "unsubscribe_user_events",
+        |params, mut sink, context| {
+            let mut params = params.sequence();
+            let user_id: u32 = params.next()?;
+            // a timestamp and a signature, or a session token
+            let credentials = match params.next::<serde_json::Value>()? {
+                serde_json::Value::String(token) => Credentials::Session { token },
+                value => Credentials::Signature {
+                    timestamp: value.as_u64().unwrap_or_default(),
+                    signature_hex: params.next()?,
+                },
+            };
+            let postgres = context.postgres.clone();
+            let used_signed_timestamps = context.used_signed_timestamps.clone();
+            let mut event_receiver = context.event_sender.subscribe();
+            tokio::spawn(async move {
+                match authenticate(&postgres, &used_signed_timestamps, user_id, &credentials).await
+                {
+                    Ok(true) => debug!("New user event subscription for user {}.", user_id),
+                    Ok(false) => {
+                        let _ = sink.send(&"Authentication failed.".to_string());
+                        return;
+                    }
+                    Err(error) => {
+                        error!("Error while authenticating user {}: {:?}", user_id, error);
+                        let _ = sink.send(&"Error while authenticating.".to_string());
+                        return;
+                    }
+                }
+                loop {
+                    match event_receiver.recv().await {
+                        Ok(user_event) => {
+                            if user_event.user_id != user_id {
+                                continue;
+                            }
+                            if let Err(error) = sink.send(&user_event) {
+                                debug!("Subscription closed. {:?}", error);
+                                return;
+                            }
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped_count)) => {
+                            error!(
+                                "User {} subscription lagged, skipped {} events.",
+                                user_id, skipped_count
+                            );
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return,
+                    }
+                }
+            });
+            Ok(())
+        },
+    )?;
+    Ok(rpc_ws_server.start(rpc_module)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_timestamp_cannot_be_reused() {
+        let used_signed_timestamps = Mutex::new(HashSet::new());
+        assert!(record_signed_timestamp(
+            &used_signed_timestamps,
+            1,
+            1_000,
+            1_000
+        ));
+        assert!(!record_signed_timestamp(
+            &used_signed_timestamps,
+            1,
+            1_000,
+            2_000
+        ));
+        // same timestamp of another user
+        assert!(record_signed_timestamp(
+            &used_signed_timestamps,
+            2,
+            1_000,
+            2_000
+        ));
+    }
+
+    #[test]
+    fn test_expired_signed_timestamps_get_pruned() {
+        let used_signed_timestamps = Mutex::new(HashSet::new());
+        assert!(record_signed_timestamp(
+            &used_signed_timestamps,
+            1,
+            1_000,
+            1_000
+        ));
+        let now = 1_000 + AUTH_TIMESTAMP_TOLERANCE_MILLIS + 1;
+        assert!(record_signed_timestamp(
+            &used_signed_timestamps,
+            1,
+            now,
+            now
+        ));
+        assert!(!used_signed_timestamps.lock().unwrap().contains(&(1, 1_000)));
+    }
+}
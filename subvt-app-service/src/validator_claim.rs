@@ -0,0 +1,283 @@
+//! Validator claim flow, in which a user proves to be the operator of a validator. The user
+//! requests a claim challenge for the validator stash, signs the challenge message with the
+//! stash key or one of the validator's session keys declared on chain, and submits the
+//! signature. A successful claim flags the user as a verified operator, which unlocks the
+//! operator-only notification types.
+use crate::{auth, check_user_exists_by_id, ResultResponse, ServiceState, CONFIG};
+use actix_web::{delete, get, post, web, HttpRequest, HttpResponse};
+use anyhow::Context;
+use serde::Deserialize;
+use subvt_persistence::realtime::get_realtime_store;
+use subvt_service_common::response;
+use subvt_types::app::{
+    verify_ed25519_signature, verify_sr25519_signature, verify_wallet_signature,
+    ValidatorClaimChallenge,
+};
+use subvt_types::crypto::AccountId;
+use subvt_types::err::ServiceErrorCode;
+use subvt_types::subvt::ValidatorDetails;
+
+/// A claim challenge has to be signed and submitted within 10 minutes, allowing some time
+/// to sign it with a session key on the validator node.
+const CLAIM_CHALLENGE_TTL_SECONDS: u64 = 10 * 60;
+/// Length of each session key in the concatenated next session keys of a validator.
+const SESSION_KEY_LENGTH: usize = 32;
+
+#[derive(Deserialize)]
+struct UserIdPathParameter {
+    pub user_id: u32,
+}
+
+#[derive(Deserialize)]
+struct UserOperatorValidatorIdPathParameter {
+    pub user_id: u32,
+    pub operator_validator_id: u32,
+}
+
+#[derive(Deserialize)]
+struct CreateValidatorClaimChallengeRequest {
+    pub network_id: u32,
+    pub validator_account_id: AccountId,
+}
+
+/// Issues a claim challenge for the validator stash. See `ValidatorClaimChallenge` in
+/// `subvt-types` for details.
+#[post("/user/{user_id}/validator-claim/challenge")]
+pub(crate) async fn create_validator_claim_challenge(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<CreateValidatorClaimChallengeRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    if !state
+        .postgres
+        .network_exists_by_id(input.network_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
+    }
+    Ok(response::created(
+        state
+            .postgres
+            .save_validator_claim_challenge(
+                path_params.user_id,
+                input.network_id,
+                &input.validator_account_id,
+                CLAIM_CHALLENGE_TTL_SECONDS,
+            )
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct CreateValidatorClaimRequest {
+    pub network_id: u32,
+    pub validator_account_id: AccountId,
+    pub nonce: String,
+    /// 0x-prefixed hex-encoded public key of the signer, required only when the challenge
+    /// is signed with a session key.
+    #[serde(rename(deserialize = "signer_public_key_hex"))]
+    pub maybe_signer_public_key_hex: Option<String>,
+    /// 0x-prefixed hex-encoded signature of the challenge message. sr25519 for the stash key,
+    /// sr25519 or ed25519 for the session keys.
+    pub signature_hex: String,
+}
+
+/// Gets the next session keys of the validator from the real-time store, i.e. the session
+/// keys declared on chain. The real-time store is only needed for the rare session key
+/// claims, so it gets connected on demand.
+fn get_validator_session_keys(validator_account_id: &AccountId) -> anyhow::Result<Vec<[u8; 32]>> {
+    let realtime_store = get_realtime_store(&CONFIG)?;
+    let finalized_block_number = realtime_store
+        .get(&format!(
+            "subvt:{}:validators:finalized_block_number",
+            CONFIG.substrate.chain
+        ))?
+        .context("Can't read the finalized block number from the real-time store.")?;
+    let mut maybe_validator_json_string = None;
+    for list in ["active", "inactive"] {
+        maybe_validator_json_string = realtime_store.get(&format!(
+            "subvt:{}:validators:{}:{}:validator:{}",
+            CONFIG.substrate.chain, finalized_block_number, list, validator_account_id
+        ))?;
+        if maybe_validator_json_string.is_some() {
+            break;
+        }
+    }
+    let validator: ValidatorDetails = match maybe_validator_json_string {
+        Some(validator_json_string) => serde_json::from_str(&validator_json_string)?,
+        None => return Ok(Vec::new()),
+    };
+    let session_key_bytes = hex::decode(validator.next_session_keys.trim_start_matches("0x"))?;
+    Ok(session_key_bytes
+        .chunks_exact(SESSION_KEY_LENGTH)
+        .map(|chunk| chunk.try_into().unwrap())
+        .collect())
+}
+
+/// Submits the signed claim challenge. A challenge can only be used once, even if the
+/// signature is invalid. Session key claims are only supported for the network of this
+/// deployment, since the session keys are read from its real-time store.
+#[post("/user/{user_id}/validator-claim")]
+pub(crate) async fn create_validator_claim(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<CreateValidatorClaimRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    let unauthorized_response = response::error(
+        ServiceErrorCode::Unauthorized,
+        "Invalid or expired claim challenge signature.".to_string(),
+    );
+    if !state
+        .postgres
+        .consume_validator_claim_challenge(
+            path_params.user_id,
+            input.network_id,
+            &input.validator_account_id,
+            &input.nonce,
+        )
+        .await?
+    {
+        return Ok(unauthorized_response);
+    }
+    let message = ValidatorClaimChallenge::get_message(
+        path_params.user_id,
+        &input.validator_account_id,
+        &input.nonce,
+    );
+    let mut stash_public_key = [0u8; 32];
+    stash_public_key.copy_from_slice(input.validator_account_id.as_ref());
+    let maybe_signer_public_key: Option<[u8; 32]> = match &input.maybe_signer_public_key_hex {
+        Some(signer_public_key_hex) => {
+            match hex::decode(signer_public_key_hex.trim_start_matches("0x"))
+                .ok()
+                .and_then(|bytes| bytes.try_into().ok())
+            {
+                Some(signer_public_key) => Some(signer_public_key),
+                None => {
+                    return Ok(response::error(
+                        ServiceErrorCode::InvalidRequest,
+                        "Invalid signer public key.".to_string(),
+                    ))
+                }
+            }
+        }
+        None => None,
+    };
+    let is_session_key_claim = match maybe_signer_public_key {
+        Some(signer_public_key) if signer_public_key != stash_public_key => {
+            if input.network_id != CONFIG.substrate.network_id {
+                return Ok(response::error(
+                    ServiceErrorCode::InvalidRequest,
+                    "Session key claims are not supported for the network.".to_string(),
+                ));
+            }
+            let session_keys = get_validator_session_keys(&input.validator_account_id)?;
+            if !session_keys.contains(&signer_public_key)
+                || !(verify_sr25519_signature(
+                    &signer_public_key,
+                    message.as_bytes(),
+                    &input.signature_hex,
+                ) || verify_ed25519_signature(
+                    &signer_public_key,
+                    message.as_bytes(),
+                    &input.signature_hex,
+                ))
+            {
+                return Ok(unauthorized_response);
+            }
+            true
+        }
+        _ => {
+            if !verify_wallet_signature(&stash_public_key, &message, &input.signature_hex) {
+                return Ok(unauthorized_response);
+            }
+            false
+        }
+    };
+    let operator_validator_id = state
+        .postgres
+        .save_user_operator_validator(
+            path_params.user_id,
+            input.network_id,
+            &input.validator_account_id,
+            is_session_key_claim,
+        )
+        .await?;
+    Ok(response::created(
+        state
+            .postgres
+            .get_user_operator_validator_by_id(path_params.user_id, operator_validator_id)
+            .await?,
+    ))
+}
+
+/// `GET`s the validators that the user has claimed.
+#[get("/user/{user_id}/operator-validator")]
+pub(crate) async fn get_user_operator_validators(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_operator_validators(path_params.user_id)
+            .await?,
+    ))
+}
+
+/// `DELETE`s a validator claim of the user. The user stops being a verified operator with
+/// the last claim, but the existing notification rules of operator-only types are kept.
+#[delete("/user/{user_id}/operator-validator/{operator_validator_id}")]
+pub(crate) async fn delete_user_operator_validator(
+    path_params: web::Path<UserOperatorValidatorIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .delete_user_operator_validator(path_params.user_id, path_params.operator_validator_id)
+        .await?
+    {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Operator validator not found.".to_string(),
+        ))
+    }
+}
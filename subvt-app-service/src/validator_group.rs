@@ -0,0 +1,324 @@
+//! Validator groups, i.e. named validator sets such as the curated validator lists of staking
+//! communities. The owner shares a group by its share code, other users subscribe to the group
+//! with the code, and the owner and the subscribers can attach notification rules to the group
+//! with the `validator_group_id` of the notification rule.
+use crate::{auth, check_user_exists_by_id, ResultResponse, ServiceState};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use serde::Deserialize;
+use subvt_service_common::response;
+use subvt_types::crypto::AccountId;
+use subvt_types::err::ServiceErrorCode;
+
+/// Upper limit for the number of validators in a group.
+const MAX_VALIDATOR_GROUP_SIZE: usize = 500;
+
+#[derive(Deserialize)]
+struct ValidatorGroupRequest {
+    pub name: String,
+    pub validator_account_ids: Vec<AccountId>,
+}
+
+impl ValidatorGroupRequest {
+    /// Validates the request, returns the error message on failure.
+    fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Validator group name cannot be empty.".to_string());
+        }
+        if self.validator_account_ids.is_empty() {
+            return Err("At least 1 validator should be in the group.".to_string());
+        }
+        if self.validator_account_ids.len() > MAX_VALIDATOR_GROUP_SIZE {
+            return Err(format!(
+                "Validator group cannot have more than {} validators.",
+                MAX_VALIDATOR_GROUP_SIZE
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct CreateValidatorGroupRequest {
+    pub network_id: u32,
+    #[serde(flatten)]
+    pub group: ValidatorGroupRequest,
+}
+
+#[derive(Deserialize)]
+struct UserIdPathParameter {
+    pub user_id: u32,
+}
+
+#[derive(Deserialize)]
+struct ValidatorGroupIdPathParameter {
+    pub user_id: u32,
+    pub validator_group_id: u32,
+}
+
+#[derive(Deserialize)]
+struct ShareCodePathParameter {
+    pub share_code: String,
+}
+
+/// `GET`s the validator group with the given share code, so that a user can see the group
+/// before subscribing to it.
+#[get("/validator-group/{share_code}")]
+pub(crate) async fn get_shared_validator_group(
+    path_params: web::Path<ShareCodePathParameter>,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    match state
+        .postgres
+        .get_validator_group_by_share_code(&path_params.share_code)
+        .await?
+    {
+        Some(validator_group) => Ok(response::ok(validator_group)),
+        None => Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Validator group not found.".to_string(),
+        )),
+    }
+}
+
+/// `GET`s the validator groups that the user owns or has subscribed to.
+#[get("/user/{user_id}/validator-group")]
+pub(crate) async fn get_user_validator_groups(
+    path_params: web::Path<UserIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        state
+            .postgres
+            .get_user_validator_groups(path_params.user_id)
+            .await?,
+    ))
+}
+
+/// Creates a new validator group owned by the user. The response contains the share code of
+/// the group.
+#[post("/user/{user_id}/validator-group")]
+pub(crate) async fn create_validator_group(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<CreateValidatorGroupRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    if !state
+        .postgres
+        .network_exists_by_id(input.network_id)
+        .await?
+    {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network not found.".to_string(),
+        ));
+    }
+    if let Err(message) = input.group.validate() {
+        return Ok(response::error(ServiceErrorCode::InvalidRequest, message));
+    }
+    let validator_group_id = state
+        .postgres
+        .save_validator_group(
+            path_params.user_id,
+            input.network_id,
+            input.group.name.trim(),
+            &input.group.validator_account_ids,
+        )
+        .await?;
+    Ok(response::created(
+        state
+            .postgres
+            .get_validator_group_by_id(validator_group_id)
+            .await?,
+    ))
+}
+
+/// Checks that the user is the owner of the validator group.
+async fn check_user_owns_validator_group(
+    state: &web::Data<ServiceState>,
+    user_id: u32,
+    validator_group_id: u32,
+) -> anyhow::Result<Option<HttpResponse>> {
+    match state
+        .postgres
+        .get_validator_group_by_id(validator_group_id)
+        .await?
+    {
+        Some(validator_group) if validator_group.user_id == user_id => Ok(None),
+        _ => Ok(Some(response::error(
+            ServiceErrorCode::NotFound,
+            "Validator group not found.".to_string(),
+        ))),
+    }
+}
+
+/// Updates the name and replaces the validators of a group owned by the user. The changes
+/// apply to the notification rules of all the subscribers.
+#[put("/user/{user_id}/validator-group/{validator_group_id}")]
+pub(crate) async fn update_validator_group(
+    path_params: web::Path<ValidatorGroupIdPathParameter>,
+    input: web::Json<ValidatorGroupRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) =
+        check_user_owns_validator_group(&state, path_params.user_id, path_params.validator_group_id)
+            .await?
+    {
+        return Ok(error_response);
+    }
+    if let Err(message) = input.validate() {
+        return Ok(response::error(ServiceErrorCode::InvalidRequest, message));
+    }
+    state
+        .postgres
+        .update_validator_group(
+            path_params.validator_group_id,
+            input.name.trim(),
+            &input.validator_account_ids,
+        )
+        .await?;
+    Ok(response::ok(
+        state
+            .postgres
+            .get_validator_group_by_id(path_params.validator_group_id)
+            .await?,
+    ))
+}
+
+/// `DELETE`s a validator group owned by the user. A soft delete, the notification rules of all
+/// the users attached to the group get deleted too.
+#[delete("/user/{user_id}/validator-group/{validator_group_id}")]
+pub(crate) async fn delete_validator_group(
+    path_params: web::Path<ValidatorGroupIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) =
+        check_user_owns_validator_group(&state, path_params.user_id, path_params.validator_group_id)
+            .await?
+    {
+        return Ok(error_response);
+    }
+    match state
+        .postgres
+        .delete_validator_group(path_params.validator_group_id)
+        .await?
+    {
+        true => Ok(HttpResponse::NoContent().finish()),
+        false => Ok(response::error(
+            ServiceErrorCode::Internal,
+            "There was an error deleting the validator group.".to_string(),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct SubscribeValidatorGroupRequest {
+    pub share_code: String,
+}
+
+/// Subscribes the user to the validator group with the given share code. Subscribing to a
+/// group is idempotent.
+#[post("/user/{user_id}/validator-group/subscription")]
+pub(crate) async fn subscribe_validator_group(
+    path_params: web::Path<UserIdPathParameter>,
+    input: web::Json<SubscribeValidatorGroupRequest>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if let Some(error_response) = check_user_exists_by_id(&state, path_params.user_id).await? {
+        return Ok(error_response);
+    }
+    let validator_group = match state
+        .postgres
+        .get_validator_group_by_share_code(&input.share_code)
+        .await?
+    {
+        Some(validator_group) => validator_group,
+        None => {
+            return Ok(response::error(
+                ServiceErrorCode::NotFound,
+                "Validator group not found.".to_string(),
+            ))
+        }
+    };
+    if validator_group.user_id == path_params.user_id {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "Cannot subscribe to an owned validator group.".to_string(),
+        ));
+    }
+    state
+        .postgres
+        .save_user_validator_group_subscription(path_params.user_id, validator_group.id)
+        .await?;
+    Ok(response::created(
+        state
+            .postgres
+            .get_validator_group_by_id(validator_group.id)
+            .await?,
+    ))
+}
+
+/// Unsubscribes the user from the validator group. The user's notification rules attached to
+/// the group get deleted.
+#[delete("/user/{user_id}/validator-group/subscription/{validator_group_id}")]
+pub(crate) async fn unsubscribe_validator_group(
+    path_params: web::Path<ValidatorGroupIdPathParameter>,
+    request: HttpRequest,
+    state: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) =
+        auth::check_user_session(&request, &state, path_params.user_id).await?
+    {
+        return Ok(error_response);
+    }
+    if state
+        .postgres
+        .delete_user_validator_group_subscription(
+            path_params.user_id,
+            path_params.validator_group_id,
+        )
+        .await?
+    {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Validator group subscription not found.".to_string(),
+        ))
+    }
+}
@@ -4,6 +4,7 @@ use std::sync::Arc;
 use subvt_app_service::{get_networks, ServiceState};
 use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_service_common::response::ResponseEnvelope;
 use subvt_types::app::Network;
 
 #[actix_rt::test]
@@ -25,6 +26,7 @@ async fn test_get_networks() {
     let request = test::TestRequest::get().uri("/network").to_request();
     let response = test::call_service(&app, request).await;
     println!("RESPONSE {:?}", response);
-    let networks: Vec<Network> = test::read_body_json(response).await;
-    assert!(!networks.is_empty());
+    let envelope: ResponseEnvelope<Vec<Network>> = test::read_body_json(response).await;
+    assert!(envelope.errors.is_empty());
+    assert!(!envelope.data.unwrap().is_empty());
 }
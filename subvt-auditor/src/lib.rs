@@ -0,0 +1,263 @@
+//! Cross-checks the indexed data of each ended era against fresh chain queries, so that silent
+//! indexing bugs get caught before they corrupt the reports. Checks the total and the validator
+//! reward points, the validator reward pot, the active validator count, the total stake, and
+//! the total of the deferred slashes applied at the start of the era. Each era is audited once,
+//! the discrepancies get recorded in the network database and sent to the configured alert
+//! webhooks. Only the most recent ended era is audited, eras that have ended while the service
+//! was down are skipped. The watchdog in `./watchdog.rs` runs alongside the era audits.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use subvt_config::Config;
+use subvt_persistence::postgres::network::audit::AuditDiscrepancy;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_service_common::Service;
+use subvt_substrate_client::SubstrateClient;
+use subvt_types::substrate::Era;
+
+mod watchdog;
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+pub struct Auditor {
+    http_client: reqwest::Client,
+}
+
+impl Default for Auditor {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.auditor.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self { http_client }
+    }
+}
+
+/// Adds a discrepancy to the list if the indexed and the chain values differ.
+fn check<T: PartialEq + ToString>(
+    discrepancies: &mut Vec<AuditDiscrepancy>,
+    check_code: &str,
+    indexed_value: Option<T>,
+    chain_value: Option<T>,
+) {
+    if indexed_value != chain_value {
+        discrepancies.push(AuditDiscrepancy {
+            check_code: check_code.to_string(),
+            indexed_value: indexed_value.map(|value| value.to_string()),
+            chain_value: chain_value.map(|value| value.to_string()),
+        });
+    }
+}
+
+impl Auditor {
+    async fn audit_era(
+        postgres: &PostgreSQLNetworkStorage,
+        substrate_client: &SubstrateClient,
+        era_index: u32,
+    ) -> anyhow::Result<Vec<AuditDiscrepancy>> {
+        let mut discrepancies = Vec::new();
+        let indexed = postgres
+            .get_indexed_era_totals(era_index)
+            .await?
+            .context(format!("Era #{} is not indexed.", era_index))?;
+        // era history is kept on chain for the history depth, so the finalized block works
+        let block_hash = substrate_client.get_finalized_block_hash().await?;
+        // the client is long-lived, the queries are decoded with the metadata of the queried
+        // blocks in case of runtime upgrades
+        let substrate_client = substrate_client.at_block(&block_hash).await?;
+        let reward_points = substrate_client
+            .get_era_reward_points(era_index, &block_hash)
+            .await?;
+        check(
+            &mut discrepancies,
+            "total_reward_points",
+            indexed.total_reward_points,
+            Some(reward_points.total),
+        );
+        check(
+            &mut discrepancies,
+            "validator_reward_points_sum",
+            Some(indexed.validator_reward_points_sum),
+            Some(
+                reward_points
+                    .individual
+                    .values()
+                    .map(|points| *points as u64)
+                    .sum(),
+            ),
+        );
+        check(
+            &mut discrepancies,
+            "total_validator_reward",
+            indexed.total_validator_reward,
+            Some(
+                substrate_client
+                    .get_era_total_validator_reward(era_index, &block_hash)
+                    .await?,
+            ),
+        );
+        check(
+            &mut discrepancies,
+            "total_stake",
+            Some(indexed.total_stake),
+            Some(
+                substrate_client
+                    .get_era_total_stake(era_index, &block_hash)
+                    .await?,
+            ),
+        );
+        let era = Era {
+            index: era_index,
+            ..Default::default()
+        };
+        check(
+            &mut discrepancies,
+            "active_validator_count",
+            Some(indexed.active_validator_count),
+            Some(
+                substrate_client
+                    .get_era_stakers(&era, false, &block_hash)
+                    .await?
+                    .stakers
+                    .len() as u32,
+            ),
+        );
+        // deferred slashes get applied and removed from the storage in the first block
+        // of the era, so they are read at the parent block
+        match indexed.first_block_number {
+            Some(first_block_number) if first_block_number > 0 => {
+                let parent_block_hash = substrate_client
+                    .get_block_hash(first_block_number - 1)
+                    .await?;
+                let parent_block_substrate_client =
+                    substrate_client.at_block(&parent_block_hash).await?;
+                check(
+                    &mut discrepancies,
+                    "era_start_slashed_amount",
+                    Some(
+                        postgres
+                            .get_block_slashed_amount(first_block_number)
+                            .await?,
+                    ),
+                    Some(
+                        parent_block_substrate_client
+                            .get_era_unapplied_slashes(era_index, &parent_block_hash)
+                            .await?
+                            .total_amount,
+                    ),
+                );
+            }
+            _ => warn!(
+                "No indexed blocks in era #{}. Skip the slash check.",
+                era_index
+            ),
+        }
+        Ok(discrepancies)
+    }
+
+    async fn send_alerts(&self, era_index: u32, discrepancies: &[AuditDiscrepancy]) {
+        let payload = serde_json::json!({
+            "chain": CONFIG.substrate.chain,
+            "era_index": era_index,
+            "discrepancies": discrepancies
+                .iter()
+                .map(|discrepancy| serde_json::json!({
+                    "check_code": discrepancy.check_code,
+                    "indexed_value": discrepancy.indexed_value,
+                    "chain_value": discrepancy.chain_value,
+                }))
+                .collect::<Vec<serde_json::Value>>(),
+        });
+        for url in &CONFIG.auditor.alert_webhook_urls {
+            let result = self
+                .http_client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(error) = result {
+                error!(
+                    "Error while sending era #{} audit alert to {}: {:?}",
+                    era_index, url, error
+                );
+            }
+        }
+    }
+
+    async fn audit_last_ended_era(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        substrate_client: &SubstrateClient,
+    ) -> anyhow::Result<()> {
+        let era_index = match postgres.get_last_ended_era_index().await? {
+            Some(era_index) => era_index,
+            None => {
+                debug!("No ended era yet.");
+                return Ok(());
+            }
+        };
+        if postgres.era_is_audited(era_index).await? {
+            debug!("Era #{} has been audited.", era_index);
+            return Ok(());
+        }
+        info!("Audit era #{}.", era_index);
+        let discrepancies = Auditor::audit_era(postgres, substrate_client, era_index).await?;
+        postgres.save_era_audit(era_index, &discrepancies).await?;
+        if discrepancies.is_empty() {
+            info!("No discrepancies in era #{}.", era_index);
+            return Ok(());
+        }
+        for discrepancy in &discrepancies {
+            error!(
+                "Era #{} {} discrepancy. Indexed: {:?}, chain: {:?}.",
+                era_index,
+                discrepancy.check_code,
+                discrepancy.indexed_value,
+                discrepancy.chain_value,
+            );
+        }
+        self.send_alerts(era_index, &discrepancies).await;
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Service for Auditor {
+    async fn run(&'static self) -> anyhow::Result<()> {
+        if CONFIG.auditor.alert_webhook_urls.is_empty() {
+            warn!("No alert webhooks are configured, discrepancies will only be recorded.");
+        }
+        info!(
+            "Auditor has started with {} seconds refresh wait period.",
+            CONFIG.auditor.refresh_seconds
+        );
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        let substrate_client = SubstrateClient::new(&CONFIG).await?;
+        let era_audits = async {
+            loop {
+                if let Err(error) = self
+                    .audit_last_ended_era(&postgres, &substrate_client)
+                    .await
+                {
+                    error!("Era audit has failed: {:?}", error);
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(
+                    CONFIG.auditor.refresh_seconds,
+                ))
+                .await;
+            }
+        };
+        tokio::join!(era_audits, self.run_watchdog(&substrate_client));
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+//! Watchdog that checks that the validator list published in the real-time store keeps up with
+//! the chain. The finalized block number of the published list is compared against the
+//! finalized head of the configured node, which in turn is compared against the finalized head
+//! of an independent node (`auditor.watchdog_rpc_url`). A node that falls behind the
+//! independent one is reported as a stuck node, a published list that falls behind a healthy
+//! node is reported as a stuck updater. Alerts get sent to the audit webhooks when the status
+//! changes, including the recovery.
+use crate::{Auditor, CONFIG};
+use log::{debug, error, info, warn};
+use std::fmt::{Display, Formatter};
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore};
+use subvt_service_common::analytics;
+use subvt_substrate_client::SubstrateClient;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum WatchdogStatus {
+    Healthy,
+    /// Configured node doesn't respond or its finalized head lags the independent node.
+    NodeStuck,
+    /// Published validator list lags the finalized head of the configured node.
+    UpdaterStuck,
+}
+
+impl Display for WatchdogStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            Self::Healthy => "healthy",
+            Self::NodeStuck => "node_stuck",
+            Self::UpdaterStuck => "updater_stuck",
+        };
+        write!(f, "{}", display)
+    }
+}
+
+#[derive(Debug)]
+struct WatchdogCheck {
+    published_block_number: Option<u64>,
+    node_block_number: Option<u64>,
+    independent_block_number: u64,
+}
+
+impl WatchdogCheck {
+    fn get_status(&self, max_lag_blocks: u64) -> WatchdogStatus {
+        let node_block_number = match self.node_block_number {
+            Some(node_block_number)
+                if self
+                    .independent_block_number
+                    .saturating_sub(node_block_number)
+                    <= max_lag_blocks =>
+            {
+                node_block_number
+            }
+            _ => return WatchdogStatus::NodeStuck,
+        };
+        match self.published_block_number {
+            Some(published_block_number)
+                if node_block_number.saturating_sub(published_block_number) <= max_lag_blocks =>
+            {
+                WatchdogStatus::Healthy
+            }
+            _ => WatchdogStatus::UpdaterStuck,
+        }
+    }
+}
+
+async fn get_node_finalized_block_number(
+    substrate_client: &SubstrateClient,
+) -> anyhow::Result<u64> {
+    let block_hash = substrate_client.get_finalized_block_hash().await?;
+    substrate_client
+        .get_block_header(&block_hash)
+        .await?
+        .get_number()
+}
+
+impl Auditor {
+    async fn check_watchdog(
+        realtime_store: &dyn RealtimeStore,
+        substrate_client: &SubstrateClient,
+    ) -> anyhow::Result<WatchdogCheck> {
+        let published_block_number = match realtime_store.get(&format!(
+            "subvt:{}:validators:finalized_block_number",
+            CONFIG.substrate.chain
+        ))? {
+            Some(block_number) => Some(block_number.parse()?),
+            None => None,
+        };
+        let node_block_number = match get_node_finalized_block_number(substrate_client).await {
+            Ok(block_number) => Some(block_number),
+            Err(error) => {
+                warn!("Cannot get the finalized block of the node: {:?}", error);
+                None
+            }
+        };
+        // the check is inconclusive without the reference, so the error is propagated
+        let independent_block_number = SubstrateClient::fetch_finalized_block_number(
+            &CONFIG,
+            &CONFIG.auditor.watchdog_rpc_url,
+        )
+        .await?;
+        Ok(WatchdogCheck {
+            published_block_number,
+            node_block_number,
+            independent_block_number,
+        })
+    }
+
+    async fn send_watchdog_alert(&self, status: WatchdogStatus, check: &WatchdogCheck) {
+        let payload = serde_json::json!({
+            "chain": CONFIG.substrate.chain,
+            "watchdog_status": status.to_string(),
+            "published_block_number": check.published_block_number,
+            "node_block_number": check.node_block_number,
+            "independent_block_number": check.independent_block_number,
+            "max_lag_blocks": CONFIG.auditor.watchdog_max_lag_blocks,
+        });
+        for url in &CONFIG.auditor.alert_webhook_urls {
+            let result = self
+                .http_client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(error) = result {
+                error!("Error while sending watchdog alert to {}: {:?}", url, error);
+            }
+        }
+    }
+
+    pub(crate) async fn run_watchdog(&self, substrate_client: &SubstrateClient) {
+        if CONFIG.auditor.watchdog_rpc_url.is_empty() {
+            debug!("Watchdog is disabled.");
+            return std::future::pending().await;
+        }
+        let realtime_store = match get_realtime_store(&CONFIG) {
+            Ok(realtime_store) => realtime_store,
+            Err(error) => {
+                error!("Watchdog cannot access the real-time store: {:?}", error);
+                return std::future::pending().await;
+            }
+        };
+        info!(
+            "Watchdog has started with {} seconds check period.",
+            CONFIG.auditor.watchdog_check_seconds
+        );
+        let mut last_status = WatchdogStatus::Healthy;
+        loop {
+            match Auditor::check_watchdog(realtime_store.as_ref(), substrate_client).await {
+                Ok(check) => {
+                    let status = check.get_status(CONFIG.auditor.watchdog_max_lag_blocks);
+                    analytics::record(&format!("watchdog:{}", status), 1);
+                    debug!("Watchdog check {:?} is {}.", check, status);
+                    if status != last_status {
+                        if status == WatchdogStatus::Healthy {
+                            info!("Watchdog has recovered from {}. {:?}", last_status, check);
+                        } else {
+                            error!("Watchdog status is {}. {:?}", status, check);
+                        }
+                        self.send_watchdog_alert(status, &check).await;
+                        last_status = status;
+                    }
+                }
+                Err(error) => {
+                    analytics::record("watchdog:error", 1);
+                    error!("Watchdog check has failed: {:?}", error);
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(
+                CONFIG.auditor.watchdog_check_seconds,
+            ))
+            .await;
+        }
+    }
+}
@@ -3,9 +3,11 @@
 use async_lock::Mutex;
 use async_recursion::async_recursion;
 use async_trait::async_trait;
+use frame_support::weights::Pays;
+use futures::StreamExt;
 use lazy_static::lazy_static;
 use log::{debug, error, trace};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc, RwLock,
@@ -14,16 +16,26 @@ use subvt_config::Config;
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
 use subvt_service_common::Service;
 use subvt_substrate_client::SubstrateClient;
+use subvt_types::app::extrinsic::ExtrinsicFee;
+use subvt_types::domain_event::DomainEventType;
+use subvt_types::report::RewardLedgerEntry;
 use subvt_types::substrate::metadata::MetadataVersion;
+use subvt_types::subvt::AnnouncedCommissionChange;
 use subvt_types::{
     crypto::AccountId,
     substrate::{
-        event::{ImOnlineEvent, StakingEvent, SubstrateEvent, SystemEvent, UtilityEvent},
+        event::{
+            BalancesEvent, DemocracyEvent, ElectionProviderMultiPhaseEvent, IdentityEvent,
+            ImOnlineEvent, OffencesEvent, ReferendaEvent, SessionEvent, StakingEvent,
+            SubstrateEvent, SystemEvent, TransactionPaymentEvent, TreasuryEvent, UtilityEvent,
+        },
         extrinsic::{
+            AuctionsExtrinsic, CrowdloanExtrinsic, DemocracyExtrinsic, IdentityExtrinsic,
             ImOnlineExtrinsic, MultisigExtrinsic, ProxyExtrinsic, StakingExtrinsic,
-            SubstrateExtrinsic, TimestampExtrinsic, UtilityExtrinsic,
+            SubstrateExtrinsic, SystemExtrinsic, TimestampExtrinsic, UtilityExtrinsic,
         },
-        Era, EraStakers, MultiAddress, ValidatorStake,
+        BlockHeader, Era, EraStakers, IdentityChange, IdentityChangeType, MultiAddress,
+        RewardDestination, ValidatorStake,
     },
 };
 
@@ -40,6 +52,105 @@ struct RuntimeInformation {
     pub epoch_index: u64,
 }
 
+/// Collects the fee of each signed extrinsic from the block events. The fee and the tip are
+/// read from the `TransactionPayment.TransactionFeePaid` event if the runtime emits it. Otherwise
+/// the fee is the sum of the fee deposits to the block author and the treasury in the extrinsic,
+/// and the tip is read from the extrinsic signature.
+fn get_extrinsic_fees(
+    block_hash: &str,
+    maybe_author_account_id: &Option<AccountId>,
+    events: &[SubstrateEvent],
+    extrinsics: &[SubstrateExtrinsic],
+) -> Vec<ExtrinsicFee> {
+    let mut extrinsic_fees: HashMap<u32, ExtrinsicFee> = HashMap::new();
+    for (index, extrinsic) in extrinsics.iter().enumerate() {
+        if let Some(signature) = extrinsic.get_signature() {
+            extrinsic_fees.insert(
+                index as u32,
+                ExtrinsicFee {
+                    block_hash: block_hash.to_string(),
+                    extrinsic_index: index as u32,
+                    signer_account_id: signature.get_signer_account_id(),
+                    block_author_account_id: maybe_author_account_id.clone(),
+                    tip: signature.tip.unwrap_or(0) as u128,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    let mut fee_paid_extrinsic_indices: HashSet<u32> = HashSet::new();
+    for event in events {
+        match event {
+            SubstrateEvent::Balances(BalancesEvent::Deposit {
+                extrinsic_index: Some(extrinsic_index),
+                account_id,
+                amount,
+            }) => {
+                // the fee deposit is the last deposit to the author in the extrinsic
+                if maybe_author_account_id.as_ref() == Some(account_id) {
+                    if let Some(extrinsic_fee) = extrinsic_fees.get_mut(extrinsic_index) {
+                        extrinsic_fee.author_fee_share = *amount;
+                    }
+                }
+            }
+            SubstrateEvent::Treasury(TreasuryEvent::Deposit {
+                extrinsic_index: Some(extrinsic_index),
+                amount,
+            }) => {
+                if let Some(extrinsic_fee) = extrinsic_fees.get_mut(extrinsic_index) {
+                    extrinsic_fee.treasury_fee_share = *amount;
+                }
+            }
+            SubstrateEvent::TransactionPayment(TransactionPaymentEvent::TransactionFeePaid {
+                extrinsic_index: Some(extrinsic_index),
+                actual_fee,
+                tip,
+                ..
+            }) => {
+                if let Some(extrinsic_fee) = extrinsic_fees.get_mut(extrinsic_index) {
+                    extrinsic_fee.fee = *actual_fee;
+                    extrinsic_fee.tip = *tip;
+                    fee_paid_extrinsic_indices.insert(*extrinsic_index);
+                }
+            }
+            SubstrateEvent::System(SystemEvent::ExtrinsicSuccess {
+                extrinsic_index: Some(extrinsic_index),
+                dispatch_info,
+            }) => {
+                if let Some(extrinsic_fee) = extrinsic_fees.get_mut(extrinsic_index) {
+                    extrinsic_fee.weight = dispatch_info.weight;
+                    extrinsic_fee.pays_fee = dispatch_info.pays_fee == Pays::Yes;
+                    extrinsic_fee.is_successful = true;
+                }
+            }
+            SubstrateEvent::System(SystemEvent::ExtrinsicFailed {
+                extrinsic_index: Some(extrinsic_index),
+                dispatch_info,
+                ..
+            }) => {
+                if let Some(extrinsic_fee) = extrinsic_fees.get_mut(extrinsic_index) {
+                    extrinsic_fee.weight = dispatch_info.weight;
+                    extrinsic_fee.pays_fee = dispatch_info.pays_fee == Pays::Yes;
+                    extrinsic_fee.is_successful = false;
+                }
+            }
+            _ => (),
+        }
+    }
+    let mut extrinsic_fees: Vec<ExtrinsicFee> = extrinsic_fees
+        .into_iter()
+        .map(|(extrinsic_index, mut extrinsic_fee)| {
+            if !fee_paid_extrinsic_indices.contains(&extrinsic_index) {
+                extrinsic_fee.fee =
+                    extrinsic_fee.author_fee_share + extrinsic_fee.treasury_fee_share;
+            }
+            extrinsic_fee
+        })
+        .collect();
+    extrinsic_fees.sort_by_key(|extrinsic_fee| extrinsic_fee.extrinsic_index);
+    extrinsic_fees
+}
+
 impl BlockProcessor {
     async fn persist_era_validators_and_stakers(
         &self,
@@ -78,11 +189,93 @@ impl BlockProcessor {
                 &validator_prefs_map,
             )
             .await?;
+        postgres
+            .save_era_validator_commissions(era.index, &validator_prefs_map)
+            .await?;
         postgres.save_era_stakers(era_stakers).await?;
         debug!("Persisted era #{} validators and stakers.", era.index);
         Ok(())
     }
 
+    /// Persists the validator commissions of the past eras in the staking history that don't
+    /// have them yet, i.e. the eras before the processing has started.
+    async fn persist_past_era_validator_commissions(
+        &self,
+        substrate_client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        active_era_index: u32,
+        block_hash: &str,
+    ) -> anyhow::Result<()> {
+        if active_era_index == 0 {
+            return Ok(());
+        }
+        let history_depth = substrate_client.get_history_depth(block_hash).await?;
+        let start_era_index = active_era_index.saturating_sub(history_depth);
+        let end_era_index = active_era_index - 1;
+        let persisted_era_indices = postgres
+            .get_validator_commission_era_indices(start_era_index, end_era_index)
+            .await?;
+        let missing_era_indices: Vec<u32> = (start_era_index..=end_era_index)
+            .filter(|era_index| !persisted_era_indices.contains(era_index))
+            .collect();
+        if missing_era_indices.is_empty() {
+            return Ok(());
+        }
+        debug!(
+            "Persist validator commissions of {} past era(s).",
+            missing_era_indices.len()
+        );
+        for (era_index, validator_prefs_map) in substrate_client
+            .get_past_era_validator_prefs(&missing_era_indices, block_hash)
+            .await?
+        {
+            postgres
+                .save_era_validator_commissions(era_index, &validator_prefs_map)
+                .await?;
+        }
+        debug!("Persisted past era validator commissions.");
+        Ok(())
+    }
+
+    /// Saves a `commission_changed` event if the successful `validate` extrinsic of the stash
+    /// in the block has changed the commission of the validator.
+    async fn save_commission_changed_event(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        (block_hash, block_number): (&str, u64),
+        stash_account_id: &AccountId,
+    ) -> anyhow::Result<()> {
+        let preference_change = match postgres
+            .get_validator_preference_changes(stash_account_id, 1)
+            .await?
+            .pop()
+        {
+            Some(preference_change) => preference_change,
+            None => return Ok(()),
+        };
+        if preference_change.block_number != block_number {
+            return Ok(());
+        }
+        if let Some(previous_commission_per_billion) =
+            preference_change.previous_commission_per_billion
+        {
+            if previous_commission_per_billion != preference_change.commission_per_billion {
+                postgres
+                    .save_domain_event(
+                        DomainEventType::CommissionChanged,
+                        block_hash,
+                        Some(stash_account_id),
+                        &serde_json::json!({
+                            "previous_commission_per_billion": previous_commission_per_billion,
+                            "commission_per_billion": preference_change.commission_per_billion,
+                        }),
+                    )
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
     async fn persist_era_reward_points(
         &self,
         substrate_client: &SubstrateClient,
@@ -119,6 +312,110 @@ impl BlockProcessor {
         Ok(())
     }
 
+    /// Saves the staking rewards of the block to the reward ledger. Each `Rewarded` event
+    /// belongs to the payout of the last `PayoutStarted` event before it in the same extrinsic,
+    /// so the rewards of a batch of payouts get their own era and validator.
+    async fn process_reward_ledger(
+        &self,
+        substrate_client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        (block_hash, block_number, block_timestamp): (&str, u64, Option<u64>),
+        events: &[SubstrateEvent],
+    ) -> anyhow::Result<()> {
+        let mut maybe_payout: Option<(Option<u32>, u32, AccountId)> = None;
+        let mut entries = Vec::new();
+        for (event_index, event) in events.iter().enumerate() {
+            match event {
+                SubstrateEvent::Staking(StakingEvent::PayoutStarted {
+                    extrinsic_index,
+                    era_index,
+                    validator_account_id,
+                }) => {
+                    maybe_payout =
+                        Some((*extrinsic_index, *era_index, validator_account_id.clone()));
+                }
+                SubstrateEvent::Staking(StakingEvent::Rewarded {
+                    extrinsic_index,
+                    rewardee_account_id,
+                    amount,
+                }) => {
+                    let maybe_era_validator = match &maybe_payout {
+                        Some((payout_extrinsic_index, era_index, validator_account_id))
+                            if payout_extrinsic_index == extrinsic_index =>
+                        {
+                            Some((*era_index, validator_account_id.clone()))
+                        }
+                        _ => None,
+                    };
+                    entries.push(RewardLedgerEntry {
+                        account_id: rewardee_account_id.clone(),
+                        block_number,
+                        timestamp: block_timestamp,
+                        event_index: event_index as u32,
+                        era_index: maybe_era_validator
+                            .as_ref()
+                            .map(|(era_index, _)| *era_index),
+                        validator_account_id: maybe_era_validator
+                            .map(|(_, validator_account_id)| validator_account_id),
+                        amount: *amount,
+                        reward_destination: None,
+                        destination_account_id: None,
+                        is_restaked: false,
+                        token_price: None,
+                    });
+                }
+                _ => (),
+            }
+        }
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let account_ids: Vec<AccountId> = entries
+            .iter()
+            .map(|entry| entry.account_id.clone())
+            .collect::<HashSet<AccountId>>()
+            .into_iter()
+            .collect();
+        let reward_destinations = substrate_client
+            .get_reward_destinations(&account_ids, block_hash)
+            .await?;
+        let controller_account_ids: Vec<AccountId> = reward_destinations
+            .iter()
+            .filter(|(_, reward_destination)| **reward_destination == RewardDestination::Controller)
+            .map(|(account_id, _)| account_id.clone())
+            .collect();
+        let controller_account_id_map = substrate_client
+            .get_bonded_account_id_map(&controller_account_ids, block_hash)
+            .await?;
+        for entry in entries.iter_mut() {
+            let reward_destination = match reward_destinations.get(&entry.account_id) {
+                Some(reward_destination) => reward_destination.clone(),
+                None => continue,
+            };
+            entry.destination_account_id = match &reward_destination {
+                RewardDestination::Staked | RewardDestination::Stash => {
+                    Some(entry.account_id.clone())
+                }
+                RewardDestination::Controller => {
+                    controller_account_id_map.get(&entry.account_id).cloned()
+                }
+                RewardDestination::Account(account_id) => Some(account_id.clone()),
+                RewardDestination::None => None,
+            };
+            entry.is_restaked = reward_destination == RewardDestination::Staked;
+            entry.reward_destination = Some(reward_destination);
+        }
+        postgres
+            .save_reward_ledger_entries(block_hash, &entries)
+            .await?;
+        debug!(
+            "Saved {} reward ledger entries for block #{}.",
+            entries.len(),
+            block_number
+        );
+        Ok(())
+    }
+
     async fn process_event(
         &self,
         substrate_client: &SubstrateClient,
@@ -130,6 +427,215 @@ impl BlockProcessor {
     ) -> anyhow::Result<()> {
         let (block_hash, epoch_index) = block_hash_epoch_index;
         match event {
+            SubstrateEvent::Democracy(democracy_event) => match democracy_event {
+                DemocracyEvent::Cancelled {
+                    extrinsic_index,
+                    referendum_index,
+                } => {
+                    let extrinsic_index =
+                        extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                    postgres
+                        .save_democracy_cancelled_event(
+                            block_hash,
+                            extrinsic_index,
+                            event_index as i32,
+                            *referendum_index,
+                        )
+                        .await?;
+                }
+                DemocracyEvent::Started {
+                    extrinsic_index,
+                    referendum_index,
+                    vote_threshold,
+                } => {
+                    match substrate_client
+                        .get_democracy_referendum_info(*referendum_index, block_hash)
+                        .await?
+                    {
+                        Some(referendum_info) => {
+                            let extrinsic_index =
+                                extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                            postgres
+                                .save_democracy_started_event(
+                                    block_hash,
+                                    extrinsic_index,
+                                    event_index as i32,
+                                    (*referendum_index, &format!("{:?}", vote_threshold)),
+                                    referendum_info.end_block_number as u64,
+                                )
+                                .await?;
+                        }
+                        None => {
+                            error!(
+                                "Cannot get info for referendum #{}. Cannot persist started event.",
+                                referendum_index
+                            );
+                        }
+                    }
+                }
+                _ => (),
+            },
+            SubstrateEvent::Referenda(ReferendaEvent::Submitted {
+                extrinsic_index,
+                referendum_index,
+                track_id,
+            }) => {
+                let extrinsic_index = extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                postgres
+                    .save_referenda_submitted_event(
+                        block_hash,
+                        extrinsic_index,
+                        event_index as i32,
+                        *referendum_index,
+                        *track_id,
+                    )
+                    .await?;
+            }
+            SubstrateEvent::ElectionProviderMultiPhase(election_event) => match election_event {
+                ElectionProviderMultiPhaseEvent::ElectionFinalized { .. } => {
+                    // the snapshot gets cleared in the finalization block, so read it
+                    // from the parent block
+                    let block_header = substrate_client.get_block_header(block_hash).await?;
+                    let active_era = substrate_client.get_active_era(block_hash).await?;
+                    // the elected set is going to be active in the next era
+                    let era_index = active_era.index + 1;
+                    match substrate_client
+                        .get_election_snapshot(&block_header.parent_hash)
+                        .await?
+                    {
+                        Some(snapshot) => {
+                            debug!(
+                                "Persist era #{} election snapshot with {} voters and {} targets.",
+                                era_index,
+                                snapshot.voters.len(),
+                                snapshot.targets.len(),
+                            );
+                            postgres
+                                .save_era_election_snapshot(era_index, &snapshot)
+                                .await?;
+                        }
+                        None => {
+                            error!(
+                                "Cannot get election snapshot for era #{}. Cannot persist.",
+                                era_index
+                            );
+                        }
+                    }
+                }
+            },
+            SubstrateEvent::Identity(identity_event) => {
+                let (extrinsic_index, account_id, change) = match identity_event {
+                    IdentityEvent::IdentitySet {
+                        extrinsic_index,
+                        account_id,
+                    } => {
+                        let mut change = IdentityChange::new(IdentityChangeType::IdentitySet);
+                        change.identity = substrate_client
+                            .get_identities(&[account_id.clone()], block_hash)
+                            .await?
+                            .remove(account_id);
+                        (extrinsic_index, account_id, change)
+                    }
+                    IdentityEvent::IdentityCleared {
+                        extrinsic_index,
+                        account_id,
+                        ..
+                    } => (
+                        extrinsic_index,
+                        account_id,
+                        IdentityChange::new(IdentityChangeType::IdentityCleared),
+                    ),
+                    IdentityEvent::IdentityKilled {
+                        extrinsic_index,
+                        account_id,
+                        ..
+                    } => (
+                        extrinsic_index,
+                        account_id,
+                        IdentityChange::new(IdentityChangeType::IdentityKilled),
+                    ),
+                    IdentityEvent::JudgementRequested {
+                        extrinsic_index,
+                        target_account_id,
+                        registrar_index,
+                    } => {
+                        let mut change =
+                            IdentityChange::new(IdentityChangeType::JudgementRequested);
+                        change.registrar_index = Some(*registrar_index);
+                        (extrinsic_index, target_account_id, change)
+                    }
+                    IdentityEvent::JudgementUnrequested {
+                        extrinsic_index,
+                        target_account_id,
+                        registrar_index,
+                    } => {
+                        let mut change =
+                            IdentityChange::new(IdentityChangeType::JudgementUnrequested);
+                        change.registrar_index = Some(*registrar_index);
+                        (extrinsic_index, target_account_id, change)
+                    }
+                    IdentityEvent::JudgementGiven {
+                        extrinsic_index,
+                        target_account_id,
+                        registrar_index,
+                    } => {
+                        let mut change = IdentityChange::new(IdentityChangeType::JudgementGiven);
+                        change.registrar_index = Some(*registrar_index);
+                        change.identity = substrate_client
+                            .get_identities(&[target_account_id.clone()], block_hash)
+                            .await?
+                            .remove(target_account_id);
+                        (extrinsic_index, target_account_id, change)
+                    }
+                    IdentityEvent::SubIdentityAdded {
+                        extrinsic_index,
+                        sub_account_id,
+                        main_account_id,
+                        ..
+                    } => {
+                        let mut change = IdentityChange::new(IdentityChangeType::SubIdentityAdded);
+                        change.related_account_id = Some(main_account_id.clone());
+                        change.sub_display = substrate_client
+                            .get_parent_account_ids(&[sub_account_id.clone()], block_hash)
+                            .await?
+                            .remove(sub_account_id)
+                            .and_then(|(_, sub_display)| sub_display);
+                        (extrinsic_index, sub_account_id, change)
+                    }
+                    IdentityEvent::SubIdentityRemoved {
+                        extrinsic_index,
+                        sub_account_id,
+                        main_account_id,
+                        ..
+                    } => {
+                        let mut change =
+                            IdentityChange::new(IdentityChangeType::SubIdentityRemoved);
+                        change.related_account_id = Some(main_account_id.clone());
+                        (extrinsic_index, sub_account_id, change)
+                    }
+                    IdentityEvent::SubIdentityRevoked {
+                        extrinsic_index,
+                        sub_account_id,
+                        main_account_id,
+                        ..
+                    } => {
+                        let mut change =
+                            IdentityChange::new(IdentityChangeType::SubIdentityRevoked);
+                        change.related_account_id = Some(main_account_id.clone());
+                        (extrinsic_index, sub_account_id, change)
+                    }
+                };
+                let extrinsic_index = extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                postgres
+                    .save_identity_change_event(
+                        block_hash,
+                        extrinsic_index,
+                        event_index as i32,
+                        account_id,
+                        &change,
+                    )
+                    .await?;
+            }
             SubstrateEvent::ImOnline(im_online_event) => match im_online_event {
                 ImOnlineEvent::HeartbeatReceived {
                     extrinsic_index,
@@ -171,6 +677,39 @@ impl BlockProcessor {
                 }
                 _ => (),
             },
+            SubstrateEvent::Offences(offences_event) => match offences_event {
+                OffencesEvent::Offence {
+                    extrinsic_index,
+                    offence_kind,
+                    time_slot,
+                } => {
+                    let extrinsic_index =
+                        extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                    postgres
+                        .save_offence_event(
+                            block_hash,
+                            extrinsic_index,
+                            event_index as i32,
+                            offence_kind,
+                            time_slot,
+                        )
+                        .await?;
+                }
+            },
+            SubstrateEvent::Session(SessionEvent::NewSession {
+                extrinsic_index,
+                session_index,
+            }) => {
+                let extrinsic_index = extrinsic_index.map(|extrinsic_index| extrinsic_index as i32);
+                postgres
+                    .save_new_session_event(
+                        block_hash,
+                        extrinsic_index,
+                        event_index as i32,
+                        *session_index,
+                    )
+                    .await?;
+            }
             SubstrateEvent::Staking(staking_event) => match staking_event {
                 StakingEvent::Chilled {
                     extrinsic_index,
@@ -256,6 +795,14 @@ impl BlockProcessor {
                             *amount,
                         )
                         .await?;
+                    postgres
+                        .save_domain_event(
+                            DomainEventType::Slashed,
+                            block_hash,
+                            Some(validator_account_id),
+                            &serde_json::json!({ "amount": amount.to_string() }),
+                        )
+                        .await?;
                 }
                 _ => (),
             },
@@ -349,7 +896,10 @@ impl BlockProcessor {
         substrate_client: &SubstrateClient,
         postgres: &PostgreSQLNetworkStorage,
         (block_hash, block_number): (String, u64),
-        active_validator_account_ids: &[AccountId],
+        (active_validator_account_ids, batch_interrupted_item_indices): (
+            &[AccountId],
+            &HashMap<u32, u32>,
+        ),
         (index, is_nested_call, maybe_multisig_account_id, maybe_real_account_id, is_successful): (
             usize,
             bool,
@@ -360,6 +910,196 @@ impl BlockProcessor {
         extrinsic: &SubstrateExtrinsic,
     ) -> anyhow::Result<()> {
         match extrinsic {
+            SubstrateExtrinsic::Auctions(AuctionsExtrinsic::Bid {
+                maybe_signature: signature,
+                parachain_id,
+                auction_index,
+                first_slot,
+                last_slot,
+                amount,
+            }) => {
+                let maybe_bidder_account_id = if let Some(real_account_id) = maybe_real_account_id {
+                    Some(real_account_id)
+                } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                    Some(multisig_account_id)
+                } else {
+                    match signature {
+                        Some(signature) => signature.get_signer_account_id(),
+                        _ => None,
+                    }
+                };
+                if let Some(bidder_account_id) = maybe_bidder_account_id {
+                    postgres
+                        .save_auction_bid_extrinsic(
+                            &block_hash,
+                            index as i32,
+                            is_nested_call,
+                            is_successful,
+                            (&bidder_account_id, u32::from(*parachain_id), *auction_index),
+                            (*first_slot, *last_slot),
+                            *amount,
+                        )
+                        .await?;
+                } else {
+                    error!(
+                        "Cannot get bidder account id from signature for extrinsic #{} Auctions.bid.",
+                        index
+                    );
+                }
+            }
+            SubstrateExtrinsic::Crowdloan(crowdloan_extrinsic) => {
+                let (signature, parachain_id, maybe_amount) = match crowdloan_extrinsic {
+                    CrowdloanExtrinsic::Contribute {
+                        maybe_signature,
+                        parachain_id,
+                        amount,
+                        ..
+                    } => (maybe_signature, parachain_id, Some(*amount)),
+                    CrowdloanExtrinsic::ContributeAll {
+                        maybe_signature,
+                        parachain_id,
+                        ..
+                    } => (maybe_signature, parachain_id, None),
+                };
+                let maybe_contributor_account_id =
+                    if let Some(real_account_id) = maybe_real_account_id {
+                        Some(real_account_id)
+                    } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                        Some(multisig_account_id)
+                    } else {
+                        match signature {
+                            Some(signature) => signature.get_signer_account_id(),
+                            _ => None,
+                        }
+                    };
+                if let Some(contributor_account_id) = maybe_contributor_account_id {
+                    postgres
+                        .save_crowdloan_contribute_extrinsic(
+                            &block_hash,
+                            index as i32,
+                            is_nested_call,
+                            is_successful,
+                            (&contributor_account_id, u32::from(*parachain_id)),
+                            maybe_amount,
+                        )
+                        .await?;
+                } else {
+                    error!(
+                        "Cannot get contributor account id from signature for extrinsic #{} Crowdloan.contribute.",
+                        index
+                    );
+                }
+            }
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Vote {
+                maybe_signature: signature,
+                referendum_index,
+                vote,
+            }) => {
+                let maybe_voter_account_id = if let Some(real_account_id) = maybe_real_account_id {
+                    Some(real_account_id)
+                } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                    Some(multisig_account_id)
+                } else {
+                    match signature {
+                        Some(signature) => signature.get_signer_account_id(),
+                        _ => None,
+                    }
+                };
+                if let Some(voter_account_id) = maybe_voter_account_id {
+                    postgres
+                        .save_democracy_vote_extrinsic(
+                            &block_hash,
+                            index as i32,
+                            is_nested_call,
+                            is_successful,
+                            (&voter_account_id, *referendum_index),
+                            vote,
+                        )
+                        .await?;
+                } else {
+                    error!("Cannot get voter account id from signature for extrinsic #{} Democracy.vote.", index);
+                }
+            }
+            SubstrateExtrinsic::ConvictionVoting(_)
+            | SubstrateExtrinsic::Democracy(_)
+            | SubstrateExtrinsic::Referenda(_) => {
+                let governance_call = match extrinsic.get_governance_call() {
+                    Some(governance_call) => governance_call,
+                    None => return Ok(()),
+                };
+                let maybe_caller_account_id = if let Some(real_account_id) = maybe_real_account_id {
+                    Some(real_account_id)
+                } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                    Some(multisig_account_id)
+                } else {
+                    match extrinsic.get_signature() {
+                        Some(signature) => signature.get_signer_account_id(),
+                        _ => None,
+                    }
+                };
+                if let Some(caller_account_id) = maybe_caller_account_id {
+                    postgres
+                        .save_governance_call_extrinsic(
+                            &block_hash,
+                            index as i32,
+                            is_nested_call,
+                            is_successful,
+                            &caller_account_id,
+                            &governance_call,
+                        )
+                        .await?;
+                } else {
+                    error!(
+                        "Cannot get caller account id from signature for extrinsic #{} {}.{}.",
+                        index, governance_call.pallet_name, governance_call.call_name,
+                    );
+                }
+            }
+            SubstrateExtrinsic::Identity(identity_extrinsic) => {
+                let signature = match identity_extrinsic {
+                    IdentityExtrinsic::SetIdentity {
+                        maybe_signature, ..
+                    }
+                    | IdentityExtrinsic::SetSubs {
+                        maybe_signature, ..
+                    }
+                    | IdentityExtrinsic::ClearIdentity { maybe_signature }
+                    | IdentityExtrinsic::ProvideJudgement {
+                        maybe_signature, ..
+                    }
+                    | IdentityExtrinsic::KillIdentity {
+                        maybe_signature, ..
+                    } => maybe_signature,
+                };
+                let maybe_caller_account_id = if let Some(real_account_id) = maybe_real_account_id {
+                    Some(real_account_id)
+                } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                    Some(multisig_account_id)
+                } else {
+                    match signature {
+                        Some(signature) => signature.get_signer_account_id(),
+                        _ => None,
+                    }
+                };
+                if let Some(caller_account_id) = maybe_caller_account_id {
+                    postgres
+                        .save_identity_call_extrinsic(
+                            &block_hash,
+                            index as i32,
+                            is_nested_call,
+                            is_successful,
+                            &caller_account_id,
+                            identity_extrinsic,
+                        )
+                        .await?;
+                } else {
+                    error!(
+                        "Cannot get caller account id from signature for extrinsic #{} Identity.{}.",
+                        index,
+                        identity_extrinsic.get_call_name()
+                    );
+                }
+            }
             SubstrateExtrinsic::ImOnline(imonline_extrinsic) => match imonline_extrinsic {
                 ImOnlineExtrinsic::Hearbeat {
                     maybe_signature: _,
@@ -424,7 +1164,7 @@ impl BlockProcessor {
                         substrate_client,
                         postgres,
                         (block_hash, block_number),
-                        active_validator_account_ids,
+                        (active_validator_account_ids, batch_interrupted_item_indices),
                         (index, true, Some(multisig_account_id), None, is_successful),
                         call,
                     )
@@ -453,7 +1193,7 @@ impl BlockProcessor {
                         substrate_client,
                         postgres,
                         (block_hash, block_number),
-                        active_validator_account_ids,
+                        (active_validator_account_ids, batch_interrupted_item_indices),
                         (index, true, Some(multisig_account_id), None, is_successful),
                         call,
                     )
@@ -471,7 +1211,7 @@ impl BlockProcessor {
                         substrate_client,
                         postgres,
                         (block_hash, block_number),
-                        active_validator_account_ids,
+                        (active_validator_account_ids, batch_interrupted_item_indices),
                         (
                             index,
                             true,
@@ -494,7 +1234,7 @@ impl BlockProcessor {
                         substrate_client,
                         postgres,
                         (block_hash, block_number),
-                        active_validator_account_ids,
+                        (active_validator_account_ids, batch_interrupted_item_indices),
                         (
                             index,
                             true,
@@ -621,6 +1361,19 @@ impl BlockProcessor {
                                 *era_index,
                             )
                             .await;
+                        if is_successful {
+                            postgres
+                                .save_domain_event(
+                                    DomainEventType::PayoutMade,
+                                    &block_hash,
+                                    Some(validator_account_id),
+                                    &serde_json::json!({
+                                        "era_index": era_index,
+                                        "caller_account_id": caller_account_id.to_string(),
+                                    }),
+                                )
+                                .await?;
+                        }
                     } else {
                         error!("Cannot get caller account id from signature for extrinsic #{} Staking.payout_stakers.", index);
                     }
@@ -694,6 +1447,14 @@ impl BlockProcessor {
                                     preferences,
                                 )
                                 .await?;
+                            if is_successful {
+                                self.save_commission_changed_event(
+                                    postgres,
+                                    (&block_hash, block_number),
+                                    &stash_account_id,
+                                )
+                                .await?;
+                            }
                         } else {
                             error!(
                                 "Cannot get stash account id for controller {}.",
@@ -704,24 +1465,145 @@ impl BlockProcessor {
                         error!("Cannot get controller account id from signature for extrinsic #{} Staking.validate.", index);
                     }
                 }
+                StakingExtrinsic::BondExtra {
+                    maybe_signature: signature,
+                    ..
+                }
+                | StakingExtrinsic::Chill {
+                    maybe_signature: signature,
+                }
+                | StakingExtrinsic::Kick {
+                    maybe_signature: signature,
+                    ..
+                }
+                | StakingExtrinsic::Rebond {
+                    maybe_signature: signature,
+                    ..
+                }
+                | StakingExtrinsic::SetPayee {
+                    maybe_signature: signature,
+                    ..
+                }
+                | StakingExtrinsic::Unbond {
+                    maybe_signature: signature,
+                    ..
+                }
+                | StakingExtrinsic::WithdrawUnbonded {
+                    maybe_signature: signature,
+                    ..
+                } => {
+                    let (call_name, maybe_amount) = match staking_extrinsic {
+                        StakingExtrinsic::BondExtra { max_additional, .. } => {
+                            ("bond_extra", Some(*max_additional))
+                        }
+                        StakingExtrinsic::Chill { .. } => ("chill", None),
+                        StakingExtrinsic::Kick { .. } => ("kick", None),
+                        StakingExtrinsic::Rebond { amount, .. } => ("rebond", Some(*amount)),
+                        StakingExtrinsic::SetPayee { .. } => ("set_payee", None),
+                        StakingExtrinsic::Unbond { amount, .. } => ("unbond", Some(*amount)),
+                        _ => ("withdraw_unbonded", None),
+                    };
+                    let maybe_caller_account_id =
+                        if let Some(real_account_id) = maybe_real_account_id {
+                            Some(real_account_id)
+                        } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                            Some(multisig_account_id)
+                        } else {
+                            match signature {
+                                Some(signature) => signature.get_signer_account_id(),
+                                _ => None,
+                            }
+                        };
+                    if let Some(caller_account_id) = maybe_caller_account_id {
+                        postgres
+                            .save_staking_call_extrinsic(
+                                &block_hash,
+                                index as i32,
+                                is_nested_call,
+                                is_successful,
+                                (&caller_account_id, call_name, maybe_amount),
+                            )
+                            .await?;
+                    } else {
+                        error!("Cannot get caller account id from signature for extrinsic #{} Staking.{}.", index, call_name);
+                    }
+                }
+            },
+            SubstrateExtrinsic::System(system_extrinsic) => match system_extrinsic {
+                SystemExtrinsic::Remark {
+                    maybe_signature: signature,
+                    remark,
+                } => {
+                    let announcement = match AnnouncedCommissionChange::parse_remark(remark) {
+                        Some(announcement) => announcement,
+                        None => return Ok(()),
+                    };
+                    if !is_successful {
+                        return Ok(());
+                    }
+                    let maybe_signer_account_id =
+                        if let Some(real_account_id) = maybe_real_account_id {
+                            Some(real_account_id)
+                        } else if let Some(multisig_account_id) = maybe_multisig_account_id {
+                            Some(multisig_account_id)
+                        } else {
+                            match signature {
+                                Some(signature) => signature.get_signer_account_id(),
+                                _ => None,
+                            }
+                        };
+                    if let Some(signer_account_id) = maybe_signer_account_id {
+                        // the announcement can be signed by the stash or the controller
+                        let validator_account_id = substrate_client
+                            .get_stash_account_id(&signer_account_id, &block_hash)
+                            .await?
+                            .unwrap_or(signer_account_id);
+                        debug!(
+                            "Commission change announcement by {}: {} per billion, effective era {:?}.",
+                            validator_account_id.to_ss58_check(),
+                            announcement.0,
+                            announcement.1,
+                        );
+                        postgres
+                            .save_remark_commission_change_announcement(
+                                &block_hash,
+                                index as i32,
+                                &validator_account_id,
+                                announcement,
+                            )
+                            .await?;
+                    } else {
+                        error!("Cannot get signer account id from signature for extrinsic #{} System.remark.", index);
+                    }
+                }
             },
             SubstrateExtrinsic::Utility(utility_extrinsic) => match utility_extrinsic {
                 UtilityExtrinsic::Batch {
                     maybe_signature: _,
                     calls,
                 } => {
-                    for call in calls {
+                    // a batch is successful even if it gets interrupted, the interrupted item
+                    // and the ones after it are not executed
+                    let maybe_interrupted_item_index =
+                        batch_interrupted_item_indices.get(&(index as u32));
+                    for (item_index, call) in calls.iter().enumerate() {
+                        let is_item_successful = is_successful
+                            && maybe_interrupted_item_index
+                                .map(|interrupted_item_index| {
+                                    (item_index as u32) < *interrupted_item_index
+                                })
+                                .unwrap_or(true);
                         self.process_extrinsic(
                             substrate_client,
                             postgres,
                             (block_hash.clone(), block_number),
-                            active_validator_account_ids,
+                            (active_validator_account_ids, batch_interrupted_item_indices),
                             (
                                 index,
                                 true,
                                 maybe_multisig_account_id.clone(),
-                                None,
-                                is_successful,
+                                maybe_real_account_id.clone(),
+                                is_item_successful,
                             ),
                             call,
                         )
@@ -737,12 +1619,12 @@ impl BlockProcessor {
                             substrate_client,
                             postgres,
                             (block_hash.clone(), block_number),
-                            active_validator_account_ids,
+                            (active_validator_account_ids, batch_interrupted_item_indices),
                             (
                                 index,
                                 true,
                                 maybe_multisig_account_id.clone(),
-                                None,
+                                maybe_real_account_id.clone(),
                                 is_successful,
                             ),
                             call,
@@ -763,39 +1645,44 @@ impl BlockProcessor {
         postgres: &PostgreSQLNetworkStorage,
         block_number: u64,
     ) -> anyhow::Result<()> {
-        debug!("Process block #{}.", block_number);
         let block_hash = substrate_client.get_block_hash(block_number).await?;
         let block_header = substrate_client.get_block_header(&block_hash).await?;
-        let maybe_validator_index = block_header.get_validator_index();
-        let runtime_upgrade_info = substrate_client
-            .get_last_runtime_upgrade_info(&block_hash)
+        self.process_block_with_header(
+            substrate_client,
+            runtime_information,
+            postgres,
+            block_number,
+            block_hash,
+            block_header,
+        )
+        .await
+    }
+
+    /// Gets a client with the metadata at the past block, see `SubstrateClient::at_block`.
+    async fn get_past_block_substrate_client(
+        substrate_client: &SubstrateClient,
+        block_number: u64,
+    ) -> anyhow::Result<SubstrateClient> {
+        let block_hash = substrate_client.get_block_hash(block_number).await?;
+        substrate_client.at_block(&block_hash).await
+    }
+
+    /// Processes the block with its already fetched hash and header, e.g. from the header
+    /// stream of the past blocks.
+    async fn process_block_with_header(
+        &self,
+        substrate_client: &mut SubstrateClient,
+        runtime_information: &Arc<RwLock<RuntimeInformation>>,
+        postgres: &PostgreSQLNetworkStorage,
+        block_number: u64,
+        block_hash: String,
+        block_header: BlockHeader,
+    ) -> anyhow::Result<()> {
+        debug!("Process block #{}.", block_number);
+        let author_digest = block_header.get_author_digest();
+        substrate_client
+            .update_metadata_on_runtime_upgrade(&block_hash)
             .await?;
-        // check metadata version
-        if substrate_client
-            .metadata
-            .last_runtime_upgrade_info
-            .spec_version
-            != runtime_upgrade_info.spec_version
-        {
-            debug!(
-                "Different runtime version #{} than client's #{}. Will reset metadata.",
-                runtime_upgrade_info.spec_version,
-                substrate_client
-                    .metadata
-                    .last_runtime_upgrade_info
-                    .spec_version
-            );
-            substrate_client.set_metadata_at_block(&block_hash).await?;
-            debug!(
-                "Runtime {} metadata fetched.",
-                substrate_client
-                    .metadata
-                    .last_runtime_upgrade_info
-                    .spec_version
-            );
-            //substrate_client.metadata.log_all_calls();
-            //substrate_client.metadata.log_all_events();
-        }
         let metadata_version = match substrate_client.metadata.version {
             MetadataVersion::V12 => 12,
             MetadataVersion::V13 => 13,
@@ -816,6 +1703,7 @@ impl BlockProcessor {
             .get_active_validator_account_ids(&block_hash)
             .await?;
 
+        let mut is_new_era = false;
         if last_epoch_index != current_epoch_index || last_era_index != active_era.index {
             let era_stakers = substrate_client
                 .get_era_stakers(&active_era, true, &block_hash)
@@ -830,6 +1718,7 @@ impl BlockProcessor {
                     .await?;
             }
             if last_era_index != active_era.index {
+                is_new_era = true;
                 let era_stakers = substrate_client
                     .get_era_stakers(&active_era, true, &block_hash)
                     .await?;
@@ -842,6 +1731,25 @@ impl BlockProcessor {
                     &era_stakers,
                 )
                 .await?;
+                self.persist_past_era_validator_commissions(
+                    substrate_client,
+                    postgres,
+                    active_era.index,
+                    &block_hash,
+                )
+                .await?;
+                // the clipped exposures leave out the smallest nominations
+                let min_active_nominator_stake = substrate_client
+                    .get_era_stakers(&active_era, false, &block_hash)
+                    .await?
+                    .min_nominator_stake();
+                postgres
+                    .update_era_staking_minimums(
+                        active_era.index,
+                        substrate_client.get_minimum_bonds(&block_hash).await?,
+                        min_active_nominator_stake,
+                    )
+                    .await?;
                 // update last era
                 let last_era_total_validator_reward = substrate_client
                     .get_era_total_validator_reward(active_era.index - 1, &block_hash)
@@ -903,6 +1811,21 @@ impl BlockProcessor {
                 }
             }
         }
+        if !author_digest.unsupported_consensus_engines.is_empty() {
+            trace!(
+                "Skipped unsupported consensus engine(s) {:?} in block #{}.",
+                author_digest.unsupported_consensus_engines,
+                block_number
+            );
+        }
+        if !author_digest.decode_errors.is_empty() {
+            debug!(
+                "Digest log decode error(s) in block #{}: {:?}",
+                block_number, author_digest.decode_errors
+            );
+        }
+        let maybe_validator_index =
+            author_digest.get_validator_index(active_validator_account_ids.len());
         let maybe_author_account_id = if let Some(validator_index) = maybe_validator_index {
             active_validator_account_ids
                 .get(validator_index)
@@ -919,11 +1842,20 @@ impl BlockProcessor {
                 &block_hash,
                 &block_header,
                 block_timestamp,
-                maybe_author_account_id,
+                maybe_author_account_id.clone(),
                 (active_era.index, current_epoch_index as u32),
                 (metadata_version, runtime_version),
             )
             .await?;
+        if is_new_era {
+            let event_count = postgres
+                .save_validator_joined_active_set_events(active_era.index, &block_hash)
+                .await?;
+            debug!(
+                "Saved {} validator joined active set event(s) for era #{}.",
+                event_count, active_era.index
+            );
+        }
         // process/persist events
         let mut successful_extrinsic_indices: Vec<u32> = Vec::new();
         let mut failed_extrinsic_indices: Vec<u32> = Vec::new();
@@ -938,7 +1870,25 @@ impl BlockProcessor {
             )
             .await?;
         }
+        self.process_reward_ledger(
+            substrate_client,
+            postgres,
+            (&block_hash, block_number, block_timestamp),
+            &events,
+        )
+        .await?;
         // persist extrinsics
+        let batch_interrupted_item_indices: HashMap<u32, u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                SubstrateEvent::Utility(UtilityEvent::BatchInterrupted {
+                    extrinsic_index: Some(extrinsic_index),
+                    item_index,
+                    ..
+                }) => Some((*extrinsic_index, *item_index)),
+                _ => None,
+            })
+            .collect();
         for (index, extrinsic) in extrinsics.iter().enumerate() {
             // check events for batch & batch_all
             let is_successful = successful_extrinsic_indices.contains(&(index as u32));
@@ -946,13 +1896,43 @@ impl BlockProcessor {
                 substrate_client,
                 postgres,
                 (block_hash.clone(), block_number),
-                &active_validator_account_ids,
+                (
+                    &active_validator_account_ids,
+                    &batch_interrupted_item_indices,
+                ),
                 (index, false, None, None, is_successful),
                 extrinsic,
             )
             .await?
         }
+        // persist fees
+        for extrinsic_fee in
+            get_extrinsic_fees(&block_hash, &maybe_author_account_id, &events, &extrinsics)
+        {
+            postgres.save_extrinsic_fee(&extrinsic_fee).await?;
+        }
+        // payouts of past eras and the end of an era alter the reports of the completed eras
+        let mut invalidated_era_indices: BTreeSet<u32> = events
+            .iter()
+            .filter_map(|event| match event {
+                SubstrateEvent::Staking(StakingEvent::PayoutStarted { era_index, .. }) => {
+                    Some(*era_index)
+                }
+                _ => None,
+            })
+            .collect();
+        if is_new_era && active_era.index > 0 {
+            invalidated_era_indices.insert(active_era.index - 1);
+        }
         // notify
+        if !invalidated_era_indices.is_empty() {
+            postgres
+                .notify_report_invalidation(
+                    block_number,
+                    invalidated_era_indices.into_iter().collect(),
+                )
+                .await?;
+        }
         postgres
             .notify_block_processed(block_number, block_hash)
             .await?;
@@ -966,6 +1946,8 @@ impl Service for BlockProcessor {
     async fn run(&'static self) -> anyhow::Result<()> {
         loop {
             let block_subscription_substrate_client = SubstrateClient::new(&CONFIG).await?;
+            // fetches the headers of the past blocks ahead of their processing
+            let block_header_substrate_client = Arc::new(SubstrateClient::new(&CONFIG).await?);
             let block_processor_substrate_client =
                 Arc::new(Mutex::new(SubstrateClient::new(&CONFIG).await?));
             let runtime_information = Arc::new(RwLock::new(RuntimeInformation::default()));
@@ -980,6 +1962,7 @@ impl Service for BlockProcessor {
                     Err(_) => return error!("Cannot get block number for header: {:?}", finalized_block_header)
                 };
                 let block_processor_substrate_client = block_processor_substrate_client.clone();
+                let block_header_substrate_client = block_header_substrate_client.clone();
                 let runtime_information = runtime_information.clone();
                 let postgres = postgres.clone();
                 if is_indexing_past_blocks.load(Ordering::SeqCst) {
@@ -999,17 +1982,41 @@ impl Service for BlockProcessor {
                     };
                     if ((processed_block_height + 1) as u64) < finalized_block_number {
                         is_indexing_past_blocks.store(true, Ordering::SeqCst);
-                        let mut block_number = std::cmp::max(
+                        let start_block_number = std::cmp::max(
                             (processed_block_height + 1) as u64,
                             CONFIG.block_processor.start_block_number
                         );
-                        while block_number <= finalized_block_number {
-                            let update_result = self.process_block(
-                                &mut block_processor_substrate_client,
-                                &runtime_information,
-                                &postgres,
-                                block_number,
-                            ).await;
+                        // past blocks are decoded with the metadata of their runtime, without
+                        // changing the metadata of the client of the finalized blocks
+                        let mut past_block_substrate_client = match BlockProcessor::get_past_block_substrate_client(
+                            &block_processor_substrate_client,
+                            start_block_number,
+                        ).await {
+                            Ok(substrate_client) => substrate_client,
+                            Err(error) => {
+                                error!("{:?}", error);
+                                error!("Cannot get the client for the past block #{}.", start_block_number);
+                                is_indexing_past_blocks.store(false, Ordering::SeqCst);
+                                return;
+                            }
+                        };
+                        let mut block_header_stream = Box::pin(
+                            block_header_substrate_client
+                                .get_block_header_stream(start_block_number, finalized_block_number)
+                        );
+                        let mut block_number = start_block_number;
+                        while let Some(block_header_result) = block_header_stream.next().await {
+                            let update_result = match block_header_result {
+                                Ok((block_hash, block_header)) => self.process_block_with_header(
+                                    &mut past_block_substrate_client,
+                                    &runtime_information,
+                                    &postgres,
+                                    block_number,
+                                    block_hash,
+                                    block_header,
+                                ).await,
+                                Err(error) => Err(error),
+                            };
                             match update_result {
                                 Ok(_) => block_number += 1,
                                 Err(error) => {
@@ -0,0 +1,108 @@
+//! Bootstrapping of a deployment for a new network. Fetches the genesis hash, the system
+//! properties and the runtime constants from a node, registers the network in the application
+//! database, and prints the `[substrate]` configuration section for the network's services.
+use anyhow::Context;
+use clap::ArgMatches;
+use log::info;
+use std::str::FromStr;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_substrate_client::SubstrateClient;
+use subvt_types::substrate::{Chain, ChainInfo};
+
+fn print_chain_info(chain_info: &ChainInfo) {
+    println!("Chain: {}", chain_info.name);
+    println!("Genesis hash: {}", chain_info.genesis_hash);
+    println!(
+        "Runtime: {} v{}",
+        chain_info.spec_name, chain_info.spec_version
+    );
+    println!("SS58 prefix: {}", chain_info.system_properties.ss_58_format);
+    println!(
+        "Token: {} ({} decimals)",
+        chain_info.system_properties.token_symbol, chain_info.system_properties.token_decimals
+    );
+    println!(
+        "Expected block time: {}ms",
+        chain_info.constants.expected_block_time_millis
+    );
+    println!(
+        "Epoch duration: {} blocks",
+        chain_info.constants.epoch_duration_blocks
+    );
+    println!(
+        "Era duration: {} blocks ({} sessions)",
+        chain_info.constants.era_duration_blocks, chain_info.constants.sessions_per_era
+    );
+}
+
+fn print_substrate_config(chain_info: &ChainInfo, rpc_url: &str, network_id: u32) {
+    println!();
+    println!("[substrate]");
+    println!("chain = \"{}\"", chain_info.name.to_lowercase());
+    println!("chain_display = \"{}\"", chain_info.name);
+    println!("chain_genesis_hash = \"{}\"", chain_info.genesis_hash);
+    println!("rpc_url = \"{}\"", rpc_url);
+    println!("network_id = {}", network_id);
+    if Chain::from_str(&chain_info.name).unwrap() == Chain::Other {
+        println!(
+            "ss58_prefix = {}",
+            chain_info.system_properties.ss_58_format
+        );
+    }
+}
+
+pub(crate) async fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let rpc_url = matches.value_of("rpc_url").unwrap();
+    let maybe_network_id = match matches.value_of("network_id") {
+        Some(network_id) => Some(network_id.parse::<u32>().context("Invalid network id.")?),
+        None => None,
+    };
+    let dry_run = matches.is_present("dry_run");
+    info!("Fetch chain information from {}.", rpc_url);
+    let chain_info = SubstrateClient::fetch_chain_info(config, rpc_url)
+        .await
+        .context("Cannot fetch the chain information from the node.")?;
+    print_chain_info(&chain_info);
+    let name = matches.value_of("name").unwrap_or(&chain_info.name);
+    let postgres = PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?;
+    let network_id = match postgres
+        .get_network_id_by_hash(&chain_info.genesis_hash)
+        .await?
+    {
+        Some(network_id) => {
+            if let Some(requested_network_id) = maybe_network_id {
+                if requested_network_id != network_id {
+                    anyhow::bail!("Network is already registered with id {}.", network_id);
+                }
+            }
+            info!("Network is already registered with id {}.", network_id);
+            network_id
+        }
+        None => {
+            let network_id = match maybe_network_id {
+                Some(network_id) => network_id,
+                None => postgres.get_next_network_id().await?,
+            };
+            if dry_run {
+                info!(
+                    "Dry run, network {} would be registered with id {}.",
+                    name, network_id
+                );
+            } else {
+                postgres
+                    .save_network(
+                        network_id,
+                        &chain_info.genesis_hash,
+                        name,
+                        chain_info.system_properties.ss_58_format as u32,
+                    )
+                    .await?;
+                info!("Network {} registered with id {}.", name, network_id);
+            }
+            network_id
+        }
+    };
+    print_substrate_config(&chain_info, rpc_url, network_id);
+    Ok(())
+}
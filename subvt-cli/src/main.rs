@@ -0,0 +1,181 @@
+//! SubVT administration command-line tool. Covers the maintenance operations that would
+//! otherwise require ad-hoc scripts against the real-time store and the databases:
+//!
+//! - `snapshot`: inspect, export and import the live network status and validator list snapshots.
+//! - `validator-list refresh`: force the validator list updater to rebuild its cached data.
+//! - `notification replay-era`: make the notification generator process an era again.
+//! - `notification send-test`: send a test notification through the channels of a rule.
+//! - `migrate`: run the pending application and/or network database migrations.
+//! - `bootstrap`: register a new network from its node and print its configuration.
+//! - `retention`: manage the data retention policies, prune old data and report the space usage.
+use clap::{App, AppSettings, Arg};
+use lazy_static::lazy_static;
+use subvt_config::Config;
+
+mod bootstrap;
+mod migrate;
+mod notification;
+mod retention;
+mod snapshot;
+mod validator_list;
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    subvt_logging::init(&CONFIG);
+    let matches = App::new("SubVT CLI")
+        .version("0.1.0")
+        .author("Kutsal Kaan Bilgin <kutsal@helikon.io>")
+        .about("Administration tool for the SubVT backend.")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            App::new("snapshot")
+                .about("Inspects the real-time store snapshots.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("network-status").about("Prints the live network status."),
+                )
+                .subcommand(
+                    App::new("validators")
+                        .about("Prints the summary of the latest validator list snapshot."),
+                )
+                .subcommand(
+                    App::new("validator")
+                        .about("Prints the details of a validator in the latest snapshot.")
+                        .arg(
+                            Arg::new("account_id")
+                                .required(true)
+                                .help("0x-prefixed hex-encoded account id of the validator."),
+                        ),
+                )
+                .subcommand(
+                    App::new("export")
+                        .about("Exports the latest snapshots to a gzip-compressed JSON file.")
+                        .arg(Arg::new("file").required(true).help("Output file path.")),
+                )
+                .subcommand(
+                    App::new("import")
+                        .about("Imports the snapshots from a file created by the export command.")
+                        .arg(Arg::new("file").required(true).help("Input file path.")),
+                ),
+        )
+        .subcommand(
+            App::new("validator-list")
+                .about("Controls the validator list updater.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(App::new("refresh").about(
+                    "Makes the updater rebuild its cached data with the next finalized block.",
+                )),
+        )
+        .subcommand(
+            App::new("notification")
+                .about("Notification generation and delivery operations.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(
+                    App::new("replay-era")
+                        .about("Makes the generator process the era again while it is active.")
+                        .arg(Arg::new("era_index").required(true).help("Era index.")),
+                )
+                .subcommand(
+                    App::new("send-test")
+                        .about("Sends a test notification through the channels of a rule.")
+                        .arg(
+                            Arg::new("rule_id")
+                                .long("rule-id")
+                                .takes_value(true)
+                                .required(true)
+                                .help("User notification rule id."),
+                        )
+                        .arg(Arg::new("validator").long("validator").takes_value(true).help(
+                            "0x-prefixed hex-encoded validator account id. Defaults to the first validator of the rule.",
+                        )),
+                ),
+        )
+        .subcommand(
+            App::new("migrate")
+                .about("Runs the pending database migrations.")
+                .arg(
+                    Arg::new("database")
+                        .possible_values(["app", "network"])
+                        .help("Database to migrate. Both are migrated by default."),
+                ),
+        )
+        .subcommand(
+            App::new("bootstrap")
+                .about("Registers the network of a node and prints its configuration.")
+                .arg(
+                    Arg::new("rpc_url")
+                        .required(true)
+                        .help("WebSocket RPC URL of the node, e.g. ws://127.0.0.1:9944."),
+                )
+                .arg(
+                    Arg::new("network_id")
+                        .long("network-id")
+                        .takes_value(true)
+                        .help("Network id. Defaults to the next available id."),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("Network name. Defaults to the chain name reported by the node."),
+                )
+                .arg(
+                    Arg::new("dry_run")
+                        .long("dry-run")
+                        .help("Prints the network information without registering the network."),
+                ),
+        )
+        .subcommand(
+            App::new("retention")
+                .about("Manages the data retention policies.")
+                .setting(AppSettings::SubcommandRequiredElseHelp)
+                .subcommand(App::new("list").about("Prints the retention policy of each category."))
+                .subcommand(
+                    App::new("set")
+                        .about("Sets the retention period of a category. Doesn't prune the data.")
+                        .arg(
+                            Arg::new("category")
+                                .required(true)
+                                .possible_values(retention::CATEGORIES)
+                                .help("Data category."),
+                        )
+                        .arg(Arg::new("days").required(true).help("Retention period in days.")),
+                )
+                .subcommand(
+                    App::new("unset")
+                        .about("Removes the retention policy of a category, i.e. keeps its data.")
+                        .arg(
+                            Arg::new("category")
+                                .required(true)
+                                .possible_values(retention::CATEGORIES)
+                                .help("Data category."),
+                        ),
+                )
+                .subcommand(
+                    App::new("prune")
+                        .about("Deletes the data older than the retention periods.")
+                        .arg(
+                            Arg::new("category")
+                                .possible_values(retention::CATEGORIES)
+                                .help("Data category. All categories with a policy by default."),
+                        ),
+                )
+                .subcommand(
+                    App::new("usage").about("Prints the disk space usage of the database tables."),
+                ),
+        )
+        .get_matches();
+    match matches.subcommand() {
+        Some(("snapshot", matches)) => snapshot::run(&CONFIG, matches),
+        Some(("validator-list", matches)) => validator_list::run(&CONFIG, matches),
+        Some(("notification", matches)) => notification::run(&CONFIG, matches).await,
+        Some(("migrate", matches)) => migrate::run(&CONFIG, matches).await,
+        Some(("bootstrap", matches)) => bootstrap::run(&CONFIG, matches).await,
+        Some(("retention", matches)) => retention::run(&CONFIG, matches).await,
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,27 @@
+//! Database migrations.
+use clap::ArgMatches;
+use log::info;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+
+pub(crate) async fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let database = matches.value_of("database");
+    if database.is_none() || database == Some("app") {
+        info!("Run application database migrations.");
+        PostgreSQLAppStorage::new(config, config.get_app_postgres_url())
+            .await?
+            .run_migrations()
+            .await?;
+        info!("Application database is up to date.");
+    }
+    if database.is_none() || database == Some("network") {
+        info!("Run network database migrations.");
+        PostgreSQLNetworkStorage::new(config, config.get_network_postgres_url())
+            .await?
+            .run_migrations()
+            .await?;
+        info!("Network database is up to date.");
+    }
+    Ok(())
+}
@@ -0,0 +1,131 @@
+//! Notification generation and delivery operations.
+use anyhow::Context;
+use clap::ArgMatches;
+use log::info;
+use std::str::FromStr;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::app::{Block, Notification, NotificationPeriodType, NotificationTypeCode};
+use subvt_types::crypto::AccountId;
+
+/// Deletes the evaluation records of the era-cadence checks for the era, so that
+/// `subvt-notification-generator` generates the era notifications (e.g. unclaimed payouts)
+/// again while the era is still active.
+async fn replay_era(config: &Config, era_index: u32) -> anyhow::Result<()> {
+    let postgres = PostgreSQLNetworkStorage::new(config, config.get_network_postgres_url()).await?;
+    if postgres
+        .delete_notification_generator_era_checks(era_index)
+        .await?
+    {
+        info!(
+            "Era #{} will be processed again by the notification generator.",
+            era_index
+        );
+    } else {
+        info!(
+            "Era #{} has not been processed by the notification generator yet.",
+            era_index
+        );
+    }
+    Ok(())
+}
+
+/// Saves an immediate block authorship notification with dummy block data for each channel
+/// of the rule, which then gets delivered by `subvt-notification-sender`. Block authorship
+/// is used regardless of the rule's notification type, as it has templates for all channels.
+async fn send_test(
+    config: &Config,
+    rule_id: u32,
+    maybe_validator_account_id: Option<AccountId>,
+) -> anyhow::Result<()> {
+    let postgres = PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?;
+    let rule = postgres
+        .get_user_notification_rule_by_id(rule_id)
+        .await?
+        .context(format!("Notification rule #{} not found.", rule_id))?;
+    let validator_account_id = match maybe_validator_account_id {
+        Some(validator_account_id) => validator_account_id,
+        None => rule
+            .validators
+            .first()
+            .map(|validator| validator.validator_account_id.clone())
+            .context("Rule has no validators. Please specify a validator.")?,
+    };
+    if rule.notification_channels.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Notification rule #{} has no channels.",
+            rule_id
+        ));
+    }
+    let block = Block {
+        hash: format!("0x{}", "0".repeat(64)),
+        number: 0,
+        timestamp: Some(chrono::Utc::now().timestamp_millis() as u64),
+        author_account_id: Some(validator_account_id.clone()),
+        era_index: 0,
+        epoch_index: 0,
+        is_finalized: true,
+        metadata_version: 0,
+        runtime_version: 0,
+    };
+    let data_json = serde_json::to_string(&block)?;
+    for channel in &rule.notification_channels {
+        let notification = Notification {
+            id: 0,
+            user_id: rule.user_id,
+            user_notification_rule_id: rule.id,
+            network_id: config.substrate.network_id,
+            period_type: NotificationPeriodType::Immediate,
+            period: 0,
+            validator_account_id: validator_account_id.clone(),
+            validator_account_json: None,
+            notification_type_code: NotificationTypeCode::ChainValidatorBlockAuthorship.to_string(),
+            user_notification_channel_id: channel.id,
+            notification_channel_code: channel.channel_code.clone(),
+            notification_target: channel.target.clone(),
+            data_json: Some(data_json.clone()),
+            deep_link_json: None,
+            log: Some("Test notification.".to_string()),
+            created_at: None,
+            sent_at: None,
+            delivered_at: None,
+            read_at: None,
+        };
+        let notification_id = postgres.save_notification(&notification).await?;
+        info!(
+            "Saved test notification #{} for {} channel {}.",
+            notification_id, channel.channel_code, channel.target,
+        );
+    }
+    Ok(())
+}
+
+pub(crate) async fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        Some(("replay-era", matches)) => {
+            let era_index: u32 = matches
+                .value_of("era_index")
+                .unwrap()
+                .parse()
+                .context("Invalid era index.")?;
+            replay_era(config, era_index).await
+        }
+        Some(("send-test", matches)) => {
+            let rule_id: u32 = matches
+                .value_of("rule_id")
+                .unwrap()
+                .parse()
+                .context("Invalid rule id.")?;
+            let maybe_validator_account_id = match matches.value_of("validator") {
+                Some(account_id_hex_string) => Some(
+                    AccountId::from_str(account_id_hex_string)
+                        .context("Invalid validator account id.")?,
+                ),
+                None => None,
+            };
+            send_test(config, rule_id, maybe_validator_account_id).await
+        }
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,133 @@
+//! Data retention policies, pruning runs and disk space usage.
+use anyhow::Context;
+use clap::ArgMatches;
+use log::info;
+use std::str::FromStr;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::rdb::{DataRetentionCategory, TableSpaceUsage};
+
+/// Possible values of the category argument, see `DataRetentionCategory` in `subvt-types`.
+pub(crate) const CATEGORIES: [&str; 4] = ["extrinsics", "events", "telemetry", "notifications"];
+
+fn parse_category(matches: &ArgMatches) -> anyhow::Result<Option<DataRetentionCategory>> {
+    match matches.value_of("category") {
+        Some(category) => Ok(Some(DataRetentionCategory::from_str(category)?)),
+        None => Ok(None),
+    }
+}
+
+async fn list(network_postgres: &PostgreSQLNetworkStorage) -> anyhow::Result<()> {
+    let policies = network_postgres.get_data_retention_policies().await?;
+    for category in DataRetentionCategory::all() {
+        match policies.iter().find(|policy| policy.category == category) {
+            Some(policy) => println!(
+                "{:<14} {:>5} days    last pruned: {}",
+                category.to_string(),
+                policy.retention_days,
+                match (&policy.last_pruned_at, policy.last_pruned_row_count) {
+                    (Some(last_pruned_at), Some(count)) =>
+                        format!("{} ({})", last_pruned_at, count),
+                    _ => "never".to_string(),
+                }
+            ),
+            None => println!("{:<14}  kept indefinitely", category.to_string()),
+        }
+    }
+    Ok(())
+}
+
+fn print_space_usages(database: &str, usages: &[TableSpaceUsage]) {
+    let total_bytes: u64 = usages.iter().map(|usage| usage.total_bytes).sum();
+    println!(
+        "{} database: {:.1} MB",
+        database,
+        total_bytes as f64 / (1024.0 * 1024.0)
+    );
+    for usage in usages {
+        println!(
+            "    {:<48} {:>12.1} MB {:>14} rows",
+            usage.table_name,
+            usage.total_bytes as f64 / (1024.0 * 1024.0),
+            usage.row_count_estimate
+        );
+    }
+}
+
+pub(crate) async fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let network_postgres =
+        PostgreSQLNetworkStorage::new(config, config.get_network_postgres_url()).await?;
+    match matches.subcommand() {
+        Some(("list", _)) => list(&network_postgres).await,
+        Some(("set", matches)) => {
+            let category = parse_category(matches)?.unwrap();
+            let retention_days: u32 = matches
+                .value_of("days")
+                .unwrap()
+                .parse()
+                .context("Invalid retention period.")?;
+            if retention_days == 0 {
+                return Err(anyhow::anyhow!(
+                    "Retention period should be at least 1 day."
+                ));
+            }
+            network_postgres
+                .save_data_retention_policy(category, retention_days)
+                .await?;
+            info!(
+                "Data of category {} will be kept for {} days.",
+                category, retention_days
+            );
+            Ok(())
+        }
+        Some(("unset", matches)) => {
+            let category = parse_category(matches)?.unwrap();
+            if network_postgres
+                .delete_data_retention_policy(category)
+                .await?
+            {
+                info!("Data of category {} will be kept indefinitely.", category);
+            } else {
+                info!("Category {} has no retention policy.", category);
+            }
+            Ok(())
+        }
+        Some(("prune", matches)) => {
+            let app_postgres =
+                PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?;
+            let results = network_postgres
+                .prune_data_by_policies(
+                    &app_postgres,
+                    config.substrate.network_id,
+                    parse_category(matches)?,
+                )
+                .await?;
+            if results.is_empty() {
+                info!("No retention policy to apply.");
+            }
+            for result in results {
+                info!(
+                    "Pruned {} older than {} days: {} {} deleted.",
+                    result.category,
+                    result.retention_days,
+                    result.deleted_count,
+                    if result.category == DataRetentionCategory::Telemetry {
+                        "chunks"
+                    } else {
+                        "rows"
+                    }
+                );
+            }
+            Ok(())
+        }
+        Some(("usage", _)) => {
+            let app_postgres =
+                PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?;
+            print_space_usages("Network", &network_postgres.get_table_space_usages().await?);
+            print_space_usages("Application", &app_postgres.get_table_space_usages().await?);
+            Ok(())
+        }
+        _ => unreachable!(),
+    }
+}
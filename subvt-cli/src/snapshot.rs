@@ -0,0 +1,264 @@
+//! Real-time store snapshot inspection, export and import. Exported files let a fresh
+//! deployment warm-start its servers without waiting for the updaters, and can be attached
+//! to bug reports.
+use anyhow::Context;
+use clap::ArgMatches;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::str::FromStr;
+use subvt_config::Config;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore, RealtimeWriteBatch};
+use subvt_types::crypto::AccountId;
+
+/// Exported real-time store state. Validator list keys are relative to the key prefix of the
+/// snapshot block, `subvt:<chain>:validators:<finalized block number>`.
+#[derive(Deserialize, Serialize)]
+struct RealtimeSnapshot {
+    chain: String,
+    finalized_block_number: u64,
+    live_network_status: Option<String>,
+    validator_list_values: BTreeMap<String, String>,
+    validator_list_sets: BTreeMap<String, Vec<String>>,
+}
+
+fn print_json(json_string: &str) -> anyhow::Result<()> {
+    let value: serde_json::Value = serde_json::from_str(json_string)?;
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+/// Gets the key prefix of the validator list snapshot for the latest processed block.
+fn get_validators_prefix(
+    config: &Config,
+    realtime_store: &dyn RealtimeStore,
+) -> anyhow::Result<(u64, String)> {
+    let finalized_block_number: u64 = realtime_store
+        .get(&format!(
+            "subvt:{}:validators:finalized_block_number",
+            config.substrate.chain
+        ))?
+        .context("No validator list snapshot. Is the validator list updater running?")?
+        .parse()?;
+    Ok((
+        finalized_block_number,
+        format!(
+            "subvt:{}:validators:{}",
+            config.substrate.chain, finalized_block_number
+        ),
+    ))
+}
+
+fn print_network_status(config: &Config, realtime_store: &dyn RealtimeStore) -> anyhow::Result<()> {
+    let status_json_string = realtime_store
+        .get(&format!(
+            "subvt:{}:live_network_status",
+            config.substrate.chain
+        ))?
+        .context("No live network status. Is the live network status updater running?")?;
+    print_json(&status_json_string)
+}
+
+fn print_validators(config: &Config, realtime_store: &dyn RealtimeStore) -> anyhow::Result<()> {
+    let (finalized_block_number, prefix) = get_validators_prefix(config, realtime_store)?;
+    let active_account_ids =
+        realtime_store.get_set_members(&format!("{}:active:account_id_set", prefix))?;
+    let inactive_account_ids =
+        realtime_store.get_set_members(&format!("{}:inactive:account_id_set", prefix))?;
+    println!("Finalized block: #{}", finalized_block_number);
+    println!("Active validators: {}", active_account_ids.len());
+    println!("Inactive validators: {}", inactive_account_ids.len());
+    if let Some(active_era_json_string) = realtime_store.get(&format!("{}:active_era", prefix))? {
+        println!("Active era:");
+        print_json(&active_era_json_string)?;
+    }
+    Ok(())
+}
+
+fn print_validator(
+    config: &Config,
+    realtime_store: &dyn RealtimeStore,
+    account_id_hex_string: &str,
+) -> anyhow::Result<()> {
+    let account_id = AccountId::from_str(account_id_hex_string)
+        .context("Invalid account id. Please provide a 0x-prefixed hex string.")?;
+    let (_, prefix) = get_validators_prefix(config, realtime_store)?;
+    for list in ["active", "inactive"] {
+        if let Some(validator_json_string) =
+            realtime_store.get(&format!("{}:{}:validator:{}", prefix, list, account_id))?
+        {
+            println!("Found in the {} list.", list);
+            return print_json(&validator_json_string);
+        }
+    }
+    Err(anyhow::anyhow!(
+        "Validator {} not found in the snapshot.",
+        account_id
+    ))
+}
+
+fn export_snapshot(
+    config: &Config,
+    realtime_store: &dyn RealtimeStore,
+    file_path: &str,
+) -> anyhow::Result<()> {
+    let (finalized_block_number, prefix) = get_validators_prefix(config, realtime_store)?;
+    let mut snapshot = RealtimeSnapshot {
+        chain: config.substrate.chain.clone(),
+        finalized_block_number,
+        live_network_status: realtime_store.get(&format!(
+            "subvt:{}:live_network_status",
+            config.substrate.chain
+        ))?,
+        validator_list_values: BTreeMap::new(),
+        validator_list_sets: BTreeMap::new(),
+    };
+    if let Some(active_era_json_string) = realtime_store.get(&format!("{}:active_era", prefix))? {
+        snapshot
+            .validator_list_values
+            .insert(":active_era".to_string(), active_era_json_string);
+    }
+    for list in ["active", "inactive"] {
+        let set_key = format!(":{}:account_id_set", list);
+        let mut account_id_hex_strings: Vec<String> = realtime_store
+            .get_set_members(&format!("{}{}", prefix, set_key))?
+            .into_iter()
+            .collect();
+        account_id_hex_strings.sort();
+        for account_id_hex_string in &account_id_hex_strings {
+            let validator_key = format!(":{}:validator:{}", list, account_id_hex_string);
+            for key in [
+                validator_key.clone(),
+                format!("{}:hash", validator_key),
+                format!("{}:summary_hash", validator_key),
+            ] {
+                let value = realtime_store
+                    .get(&format!("{}{}", prefix, key))?
+                    .context(format!(
+                        "Snapshot of block #{} has changed during the export. Please retry.",
+                        finalized_block_number
+                    ))?;
+                snapshot.validator_list_values.insert(key, value);
+            }
+        }
+        snapshot
+            .validator_list_sets
+            .insert(set_key, account_id_hex_strings);
+    }
+    let mut writer = GzEncoder::new(
+        BufWriter::new(File::create(file_path)?),
+        Compression::default(),
+    );
+    serde_json::to_writer(&mut writer, &snapshot)?;
+    writer.finish()?.flush()?;
+    info!(
+        "Exported the snapshot of block #{} with {} validator list key(s) to {}.",
+        finalized_block_number,
+        snapshot.validator_list_values.len() + snapshot.validator_list_sets.len(),
+        file_path,
+    );
+    Ok(())
+}
+
+/// Writes the snapshot to the real-time store and publishes it, so that the running servers
+/// pick it up. The validator list updater replaces the imported state once it processes its
+/// first finalized block.
+fn import_snapshot(
+    config: &Config,
+    realtime_store: &dyn RealtimeStore,
+    file_path: &str,
+) -> anyhow::Result<()> {
+    let reader = GzDecoder::new(BufReader::new(File::open(file_path)?));
+    let snapshot: RealtimeSnapshot =
+        serde_json::from_reader(reader).context("Invalid snapshot file.")?;
+    if snapshot.chain != config.substrate.chain {
+        return Err(anyhow::anyhow!(
+            "Snapshot is for chain {}, configured chain is {}.",
+            snapshot.chain,
+            config.substrate.chain
+        ));
+    }
+    let prefix = format!(
+        "subvt:{}:validators:{}",
+        config.substrate.chain, snapshot.finalized_block_number
+    );
+    let mut batch = RealtimeWriteBatch::default();
+    batch.delete_with_prefix(&format!("{}:", prefix));
+    for (key, members) in &snapshot.validator_list_sets {
+        batch.add_to_set(
+            &format!("{}{}", prefix, key),
+            members.iter().cloned().collect(),
+        );
+    }
+    for (key, value) in &snapshot.validator_list_values {
+        batch.set(&format!("{}{}", prefix, key), value);
+    }
+    batch
+        .set(
+            &format!(
+                "subvt:{}:validators:finalized_block_number",
+                config.substrate.chain
+            ),
+            snapshot.finalized_block_number,
+        )
+        .publish_block_number(
+            &format!(
+                "subvt:{}:validators:publish:finalized_block_number",
+                config.substrate.chain
+            ),
+            snapshot.finalized_block_number,
+        );
+    if let Some(status_json_string) = &snapshot.live_network_status {
+        let status: serde_json::Value = serde_json::from_str(status_json_string)?;
+        let best_block_number = status["best_block_number"]
+            .as_u64()
+            .context("Invalid live network status in the snapshot.")?;
+        batch
+            .set(
+                &format!("subvt:{}:live_network_status", config.substrate.chain),
+                status_json_string,
+            )
+            .publish_block_number(
+                &format!(
+                    "subvt:{}:live_network_status:publish:best_block_number",
+                    config.substrate.chain
+                ),
+                best_block_number,
+            );
+    }
+    realtime_store.write(&batch)?;
+    info!(
+        "Imported the snapshot of block #{} from {}.",
+        snapshot.finalized_block_number, file_path,
+    );
+    Ok(())
+}
+
+pub(crate) fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    let realtime_store = get_realtime_store(config)?;
+    match matches.subcommand() {
+        Some(("network-status", _)) => print_network_status(config, realtime_store.as_ref()),
+        Some(("validators", _)) => print_validators(config, realtime_store.as_ref()),
+        Some(("validator", matches)) => print_validator(
+            config,
+            realtime_store.as_ref(),
+            matches.value_of("account_id").unwrap(),
+        ),
+        Some(("export", matches)) => export_snapshot(
+            config,
+            realtime_store.as_ref(),
+            matches.value_of("file").unwrap(),
+        ),
+        Some(("import", matches)) => import_snapshot(
+            config,
+            realtime_store.as_ref(),
+            matches.value_of("file").unwrap(),
+        ),
+        _ => unreachable!(),
+    }
+}
@@ -0,0 +1,38 @@
+//! Validator list updater control.
+use clap::ArgMatches;
+use log::info;
+use subvt_config::Config;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeWriteBatch};
+
+/// Publishes a refresh request to `subvt-validator-list-updater`, which resets its cached
+/// data before processing the next finalized block. The published block number is the
+/// latest processed one, only informative.
+fn refresh(config: &Config) -> anyhow::Result<()> {
+    let realtime_store = get_realtime_store(config)?;
+    let finalized_block_number: u64 = realtime_store
+        .get(&format!(
+            "subvt:{}:validators:finalized_block_number",
+            config.substrate.chain
+        ))?
+        .map(|block_number| block_number.parse())
+        .transpose()?
+        .unwrap_or_default();
+    let mut batch = RealtimeWriteBatch::default();
+    batch.publish_block_number(
+        &format!(
+            "subvt:{}:validators:command:refresh",
+            config.substrate.chain
+        ),
+        finalized_block_number,
+    );
+    realtime_store.write(&batch)?;
+    info!("Validator list refresh requested.");
+    Ok(())
+}
+
+pub(crate) fn run(config: &Config, matches: &ArgMatches) -> anyhow::Result<()> {
+    match matches.subcommand() {
+        Some(("refresh", _)) => refresh(config),
+        _ => unreachable!(),
+    }
+}
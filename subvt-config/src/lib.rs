@@ -1,6 +1,7 @@
 //! SubVT runtime configuration.
 
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fmt;
 
 /// Default development configuration file relative path for other SubVT crates/modules.
@@ -61,12 +62,31 @@ pub struct SubstrateConfig {
     pub chain_genesis_hash: String,
     /// Node WebSocket RPC URL (e.g. `wss://kusama-rpc.polkadot.io` for Kusama).
     pub rpc_url: String,
+    /// Node WebSocket RPC URLs to fail over to, in order, when the node at `rpc_url` is
+    /// unreachable or unhealthy.
+    pub fallback_rpc_urls: Vec<String>,
     /// RPC connection timeout in seconds.
     pub connection_timeout_seconds: u64,
-    /// RPC request timeout in seconds.
+    /// RPC request timeout in seconds. A timed-out request gets retried.
     pub request_timeout_seconds: u64,
+    /// Maximum number of retries of an RPC request that has failed with a retryable error, i.e.
+    /// a connection error, a timeout or a full request queue. 0 disables the retries.
+    pub request_retry_count: u32,
+    /// Delay before the first retry of a failed RPC request in milliseconds. The delay doubles
+    /// with each retry.
+    pub request_retry_initial_delay_millis: u64,
+    /// Maximum delay between the retries of a failed RPC request in milliseconds.
+    pub request_retry_max_delay_millis: u64,
+    /// Number of the RPC connections of a Substrate client. The requests get dispatched to the
+    /// connections in turns, so that the concurrent storage queries, e.g. of the validator list
+    /// updater, don't queue up on a single connection.
+    pub connection_pool_size: u16,
     /// Substrate network id for internal use.
     pub network_id: u32,
+    /// SS58 prefix of the addresses, needed only for the chains other than Kusama and Polkadot
+    /// in the services that don't connect to a node. The services that connect to a node use
+    /// the prefix in the system properties of the node.
+    pub ss58_prefix: Option<u16>,
 }
 
 /// Log configuration.
@@ -91,6 +111,21 @@ pub struct RPCConfig {
     pub inactive_validator_list_port: u16,
     /// Validator details WS RPC server TCP port.
     pub validator_details_port: u16,
+    /// Application user event WS RPC server TCP port.
+    pub app_user_event_port: u16,
+    /// Subscriptions to the WS RPC servers can be authenticated with the session tokens of
+    /// the application REST service when enabled. Otherwise all subscriptions get the full data.
+    pub subscription_auth_enabled: bool,
+    /// Subscriptions without a session token get rejected when enabled.
+    pub subscription_auth_required: bool,
+    /// `summary` or `full`, plan of the subscriptions without a session token. Authenticated
+    /// subscriptions get the `full` plan.
+    pub anonymous_subscription_plan: String,
+    /// Upper limit for the number of concurrent subscriptions of a user on a server.
+    pub max_user_subscription_count: u32,
+    /// Upper limit for the `min_push_interval_blocks` parameter of the validator details
+    /// subscriptions.
+    pub max_push_interval_blocks: u64,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -98,8 +133,20 @@ pub struct HTTPConfig {
     pub host: String,
     /// Report REST service TCP port.
     pub report_service_port: u16,
+    /// Expected value of the `X-Admin-Key` header in the requests to the admin endpoints of the
+    /// report REST service, i.e. data retention and space usage. Admin endpoints are disabled
+    /// when empty.
+    pub report_service_admin_key: String,
     /// Application REST service TCP port.
     pub app_service_port: u16,
+    /// Expected value of the `X-Admin-Key` header in the requests to the admin endpoints of the
+    /// application REST service. Admin endpoints are disabled when empty.
+    pub app_service_admin_key: String,
+    /// Lifetime of the sessions created through the wallet sign-in flow.
+    pub app_service_session_ttl_seconds: u64,
+    /// Whether the user endpoints of the application REST service require a session token of
+    /// the user in the `Authorization: Bearer` header.
+    pub app_service_session_required: bool,
 }
 
 /// Redis configuration. Redis is utilized as in-memory buffer storage for real-time
@@ -109,6 +156,34 @@ pub struct RedisConfig {
     pub url: String,
 }
 
+/// Backend of the real-time buffer between the updaters and the WebSocket servers.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RealtimeStoreType {
+    Redis,
+    /// For tests only. The store is not shared between processes, so the services, which run
+    /// as separate processes, can't communicate through it.
+    Memory,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RealtimeConfig {
+    pub store_type: RealtimeStoreType,
+    /// When true, the servers skip the queued block notifications and process only the newest
+    /// one, so that they don't fall behind under load.
+    pub coalesce_block_notifications: bool,
+    /// Key format versions the updaters write. Writing more than one version (dual-write)
+    /// lets the updaters and the servers get deployed independently during key format changes.
+    /// Version 1 keys are prefixed with `subvt:<chain>:`, and the later versions with
+    /// `subvt:v<version>:<chain>:`.
+    pub write_key_versions: Vec<u32>,
+    /// Key format version the servers read.
+    pub read_key_version: u32,
+    /// Number of most recent finalized blocks for which the validator list is kept in the store.
+    /// Reconnecting validator list subscribers receive the diffs they missed within this depth.
+    pub history_block_depth: u64,
+}
+
 /// PostgreSQL configuration. PostgreSQL is used for historical indexed blockchain data storage.
 #[derive(Clone, Debug, Deserialize)]
 pub struct PostgreSQLConfig {
@@ -129,9 +204,13 @@ pub struct BlockProcessorConfig {
     pub start_block_number: u64,
 }
 
-/// 1KV configuration - only used for Polkadot and Kusama.
+/// 1KV configuration - only used for Polkadot and Kusama. The 1KV subsystem is disabled
+/// when the section is missing or not `enabled`, see `Config::get_onekv_config`.
 #[derive(Clone, Debug, Deserialize)]
 pub struct OneKVConfig {
+    pub enabled: bool,
+    /// Number of most recent candidate records, and rank and score history records, kept
+    /// for each candidate. Backfilled history records are not counted.
     pub candidate_history_record_count: u64,
     pub candidate_list_endpoint: String,
     pub candidate_details_endpoint: String,
@@ -139,22 +218,210 @@ pub struct OneKVConfig {
     pub request_timeout_seconds: u64,
 }
 
+/// Precision of the validator locations. Region-level locations are coarsened to the country
+/// level when the precision is `country`.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GeolocationPrecision {
+    Country,
+    Region,
+}
+
+/// Geolocation updater configuration. Node locations get resolved into country and region
+/// codes through a Nominatim-compatible (OpenStreetMap) geocoding service.
+#[derive(Clone, Debug, Deserialize)]
+pub struct GeolocationConfig {
+    pub geocoding_base_url: String,
+    pub precision: GeolocationPrecision,
+    /// Regions with fewer active validators are only counted at the country level
+    /// in the decentralization report, so that single validators cannot be pinpointed.
+    pub min_report_region_validator_count: u32,
+    pub refresh_seconds: u64,
+    /// Wait period between the geocoding requests to comply with the service usage policy.
+    pub request_interval_millis: u64,
+    pub request_timeout_seconds: u64,
+}
+
+/// Era summary publisher configuration. Publication targets with empty settings are disabled.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EraSummaryPublisherConfig {
+    pub refresh_seconds: u64,
+    pub points_leader_count: u32,
+    pub request_timeout_seconds: u64,
+    /// Summary gets `POST`ed as JSON to each of these URLs.
+    pub webhook_urls: Vec<String>,
+    pub matrix_homeserver_url: String,
+    pub matrix_room_id: String,
+    pub matrix_access_token: String,
+    /// OAuth 2.0 user context access token of the Twitter account, with tweet write scope.
+    pub twitter_access_token: String,
+}
+
+/// Era report archiver configuration. Archive targets with empty settings are disabled.
+#[derive(Clone, Debug, Deserialize)]
+pub struct EraReportArchiverConfig {
+    pub refresh_seconds: u64,
+    pub request_timeout_seconds: u64,
+    /// Upper limit for the number of eras archived to a target in a single run, so that the
+    /// archival of the history of a network gets spread over multiple runs.
+    pub max_era_count_per_run: u32,
+    /// Endpoint of the S3-compatible storage, objects are addressed in path style,
+    /// i.e. `{endpoint}/{bucket}/{key}`.
+    pub s3_endpoint_url: String,
+    pub s3_region: String,
+    pub s3_bucket: String,
+    /// Prefix of the object keys, e.g. `kusama/`.
+    pub s3_key_prefix: String,
+    pub s3_access_key_id: String,
+    pub s3_secret_access_key: String,
+    /// URL of the IPFS node's HTTP RPC API, e.g. `http://127.0.0.1:5001`. Archived files
+    /// get pinned on the node.
+    pub ipfs_api_url: String,
+}
+
+/// Era-boundary consistency checker configuration.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AuditorConfig {
+    pub refresh_seconds: u64,
+    pub request_timeout_seconds: u64,
+    /// Discrepancies get `POST`ed as JSON to each of these URLs.
+    pub alert_webhook_urls: Vec<String>,
+    /// RPC URL of a node independent of `substrate.rpc_url`, used by the watchdog to check
+    /// that the published validator list keeps up with the chain. Watchdog is disabled when
+    /// empty.
+    pub watchdog_rpc_url: String,
+    pub watchdog_check_seconds: u64,
+    /// Alert gets sent when the published block or the node falls behind by more blocks.
+    pub watchdog_max_lag_blocks: u64,
+}
+
+/// Live network status updater configuration.
+#[derive(Clone, Debug, Deserialize)]
+pub struct LiveNetworkStatusUpdaterConfig {
+    /// Status gets updated with every best (non-finalized) block when enabled, and with every
+    /// finalized block otherwise.
+    pub follow_best_blocks: bool,
+    /// Finality is considered stalled when the best block is at least this many blocks ahead
+    /// of the finalized block. Stall detection is disabled when zero.
+    pub finality_stall_block_gap: u64,
+    pub request_timeout_seconds: u64,
+    /// Finality stalls and recoveries get `POST`ed as JSON to each of these URLs.
+    pub alert_webhook_urls: Vec<String>,
+}
+
+/// Token price updater configuration.
+#[derive(Clone, Debug, Deserialize)]
+pub struct PriceConfig {
+    /// Base URL of a CoinGecko-compatible API with the `/simple/price` endpoint.
+    pub api_base_url: String,
+    /// API id of the network token (`kusama`, `polkadot`, etc.).
+    pub token_id: String,
+    /// Lower-case codes of the fiat currencies to be tracked, e.g. `usd`.
+    pub currencies: Vec<String>,
+    pub refresh_seconds: u64,
+    pub request_timeout_seconds: u64,
+}
+
 /// Report service configuration.
 #[derive(Clone, Debug, Deserialize)]
 pub struct ReportConfig {
     pub max_era_index_range: u32,
+    /// Maximum era range of the streaming exports, which are not materialized in memory.
+    pub max_export_era_index_range: u32,
+    /// Number of eras fetched from the database at once in the streaming exports.
+    pub export_chunk_era_count: u32,
+    /// Default number of recent eras the reward projection is based on.
+    pub projection_era_count: u32,
+    /// Maximum number of candidate validators in a reward projection request.
+    pub max_projection_validator_count: u32,
+    /// Maximum number of stash accounts in a portfolio request.
+    pub max_portfolio_account_count: u32,
+    /// Pending rewards are checked for this many most recent eras, the staking history depth.
+    pub portfolio_pending_reward_era_count: u32,
+    pub portfolio_recent_event_count: u32,
+    /// Default number of recent eras the commission comparison is based on.
+    pub commission_comparison_era_count: u32,
+    /// Ascending lower bounds of the commission comparison bands, in percent.
+    /// The last band extends to 100%.
+    pub commission_comparison_band_lower_bounds_percent: Vec<u32>,
+    /// Default page size of the history endpoints.
+    pub history_page_size: u32,
+    pub max_history_page_size: u32,
+    /// Request counts of the API keys are kept in the real-time store and get persisted in the
+    /// application database with this period.
+    pub api_usage_flush_period_seconds: u64,
+    /// Number of requests allowed per user per (UTC) day, for all the API keys of the user.
+    pub api_daily_request_quota: u64,
+    /// Maximum number of recent days in the API usage reports.
+    pub max_api_usage_day_count: u32,
+    /// Expensive endpoints get rejected with `503 Service Unavailable` while the network
+    /// database connection pool is under pressure.
+    pub load_shedding_enabled: bool,
+    /// Period of the network database connection pool wait time measurement.
+    pub load_shedding_probe_period_millis: u64,
+    /// The pool is under pressure while acquiring a connection takes at least this long.
+    pub load_shedding_wait_threshold_millis: u64,
+    /// Sent in the `Retry-After` header of the rejected requests.
+    pub load_shedding_retry_after_seconds: u64,
+    /// Era and validator reports of the ended eras get cached in the real-time store until
+    /// the block processor invalidates them, e.g. with a late payout.
+    pub cache_enabled: bool,
+    /// Recommended commission band of the validator onboarding checklist, in percent.
+    pub onboarding_min_recommended_commission_percent: u32,
+    pub onboarding_max_recommended_commission_percent: u32,
 }
 
-/// Telemetry processor configuration.
+/// Telemetry processor configuration. The Telemetry subsystem is disabled when the section
+/// is missing or not `enabled`, see `Config::get_telemetry_config`.
 #[derive(Clone, Debug, Deserialize)]
 pub struct TelemetryConfig {
+    pub enabled: bool,
     pub websocket_url: String,
 }
 
+/// Evaluation cadence of a periodic notification check. A check gets evaluated once in every
+/// finalized block, session, era or clock hour.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationCheckCadence {
+    Block,
+    Session,
+    Era,
+    Hour,
+}
+
+impl fmt::Display for NotificationCheckCadence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotificationCheckCadence::Block => write!(f, "block"),
+            NotificationCheckCadence::Session => write!(f, "session"),
+            NotificationCheckCadence::Era => write!(f, "era"),
+            NotificationCheckCadence::Hour => write!(f, "hour"),
+        }
+    }
+}
+
 /// Notification generator configuration.
 #[derive(Clone, Debug, Deserialize)]
 pub struct NotificationGeneratorConfig {
     pub unclaimed_payout_check_delay_hours: u32,
+    /// Validators that haven't voted on an ongoing referendum get notified
+    /// when the referendum is this close to its end.
+    pub referendum_vote_missing_window_hours: u32,
+    /// Rewards of the watched nominators are checked for the era this many eras before the
+    /// active era, so that the validators have had time to pay out.
+    pub nominator_reward_check_era_delay: u32,
+    /// Unique id of the generator instance, defaults to the host name. Users are partitioned
+    /// among the instances that are alive, so that multiple instances can run concurrently.
+    pub instance_id: Option<String>,
+    pub instance_heartbeat_seconds: u64,
+    /// Instance is dropped from the partitioning, and its users are distributed among the
+    /// remaining instances, if it doesn't send a heartbeat for this long.
+    pub instance_lease_seconds: u64,
+    /// Evaluation cadences of the periodic checks, keyed by the notification type code, e.g.
+    /// `chain_validator_unclaimed_payout = "era"`. A periodic check without a cadence doesn't
+    /// get evaluated.
+    pub check_cadences: HashMap<String, NotificationCheckCadence>,
 }
 
 /// Notification sender configuration.
@@ -167,6 +434,11 @@ pub struct NotificationSenderConfig {
     pub email_password: String,
     pub email_smtp_server_url: String,
     pub email_smtp_server_tls_port: u16,
+    /// Base URL of the unsubscribe endpoint of `subvt-app-service`. The rule channel's
+    /// unsubscribe token gets appended to this URL in the email footer.
+    pub email_unsubscribe_url: String,
+    /// Number of most recent eras to be included in the era points chart embedded in emails.
+    pub email_chart_era_count: u32,
     // Apple Push Notification Service
     pub apns_key_location: String,
     pub apns_key_id: String,
@@ -175,6 +447,83 @@ pub struct NotificationSenderConfig {
     pub apns_is_production: bool,
     // Firebase Cloud Messaging
     pub fcm_api_key: String,
+    // Dry-run mode for staging environments. Notifications of a dry-run channel don't get
+    // delivered to their targets, but get logged in the `app_notification_dry_run` table.
+    // They get delivered to the channel's override recipient instead, if one is set.
+    pub email_dry_run: bool,
+    pub email_dry_run_recipient: Option<String>,
+    pub apns_dry_run: bool,
+    /// APNS device token.
+    pub apns_dry_run_recipient: Option<String>,
+    pub fcm_dry_run: bool,
+    /// FCM registration token.
+    pub fcm_dry_run_recipient: Option<String>,
+}
+
+/// Validator list updater configuration.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ValidatorListUpdaterConfig {
+    /// Number of most recent preference (commission, nomination blocking) changes
+    /// to be included in the validator details.
+    pub preference_change_history_size: u32,
+    /// Number of most recent on-chain activities (payouts, new nominations, preference
+    /// changes, offences) to be included in the validator details.
+    pub recent_activity_size: u32,
+    /// A validator gets flagged as recently slashed if it has been slashed
+    /// within this many eras.
+    pub recent_slash_era_count: u32,
+    /// BABE `c` constant of the chain: probability of a slot having at least one primary claim.
+    pub babe_primary_slot_probability: f64,
+    /// A validator without any blocks in the session is flagged as likely offline when
+    /// the probability of its primary block shortfall being bad luck is below this value.
+    pub missed_primary_slot_bad_luck_probability_threshold: f64,
+    /// A validator gets flagged for controller low balance when the part of the controller
+    /// balance that can be spent on fees is below this many existential deposits.
+    pub controller_low_balance_existential_deposit_multiple: u32,
+    /// Commission change announcements older than this many days are ignored.
+    pub commission_change_announcement_max_age_days: u32,
+    /// Average payout lag of a validator is computed over the eras paid out within this many
+    /// most recent eras.
+    pub payout_lag_era_count: u32,
+    /// Finalized blocks skipped while an update is in progress get processed in order after
+    /// the update, if there are at most this many of them. Otherwise the updater skips to the
+    /// latest finalized block, since the validator lists of the older blocks are already stale.
+    pub max_catch_up_block_count: u64,
+}
+
+/// Refresh cadence of a validator list field group. The group gets refreshed when either
+/// `block_count` blocks or `seconds` seconds have passed since its last refresh, a limit of
+/// `0` is disabled.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FieldGroupCadenceConfig {
+    pub block_count: u32,
+    pub seconds: u64,
+}
+
+/// Refresh cadences of the validator list field groups that are expensive to compute. The
+/// on-chain fields get refreshed with every block, and the last values of a field group get
+/// carried over until its next refresh. All groups get refreshed at the start of a new era.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ValidatorListUpdaterCadenceConfig {
+    /// Relational database fields, i.e. the era and reward statistics, the 1KV fields
+    /// and the location.
+    pub db_info: FieldGroupCadenceConfig,
+    pub announced_commission_changes: FieldGroupCadenceConfig,
+    /// Recent slash and inactive controller flags. The flags derived from the on-chain
+    /// fields are refreshed with every block.
+    pub risk_flags: FieldGroupCadenceConfig,
+    pub missed_primary_slots: FieldGroupCadenceConfig,
+    pub recent_activity: FieldGroupCadenceConfig,
+}
+
+/// Analytics configuration, see `analytics` in `subvt-service-common`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct AnalyticsConfig {
+    /// Analytics are opt-in, nothing gets recorded when disabled.
+    pub enabled: bool,
+    /// Counters are accumulated in memory and get persisted in the application database with
+    /// this period.
+    pub flush_period_seconds: u64,
 }
 
 /// Whole configuration.
@@ -184,17 +533,27 @@ pub struct Config {
     pub env: Environment,
     pub common: CommonConfig,
     pub http: HTTPConfig,
+    pub live_network_status_updater: LiveNetworkStatusUpdaterConfig,
     pub log: LogConfig,
-    pub onekv: OneKVConfig,
+    pub onekv: Option<OneKVConfig>,
+    pub geolocation: GeolocationConfig,
+    pub era_summary_publisher: EraSummaryPublisherConfig,
+    pub era_report_archiver: EraReportArchiverConfig,
+    pub price: PriceConfig,
+    pub auditor: AuditorConfig,
+    pub analytics: AnalyticsConfig,
     pub app_postgres: PostgreSQLConfig,
     pub network_postgres: PostgreSQLConfig,
     pub redis: RedisConfig,
+    pub realtime: RealtimeConfig,
     pub rpc: RPCConfig,
     pub substrate: SubstrateConfig,
     pub report: ReportConfig,
-    pub telemetry: TelemetryConfig,
+    pub telemetry: Option<TelemetryConfig>,
     pub notification_generator: NotificationGeneratorConfig,
     pub notification_sender: NotificationSenderConfig,
+    pub validator_list_updater: ValidatorListUpdaterConfig,
+    pub validator_list_updater_cadence: ValidatorListUpdaterCadenceConfig,
 }
 
 impl Config {
@@ -259,6 +618,26 @@ impl Config {
             self.network_postgres.database_name,
         )
     }
+
+    /// 1KV configuration, `None` if 1KV is not enabled for the deployment.
+    pub fn get_onekv_config(&self) -> Option<&OneKVConfig> {
+        self.onekv.as_ref().filter(|onekv| onekv.enabled)
+    }
+
+    pub fn is_onekv_enabled(&self) -> bool {
+        self.get_onekv_config().is_some()
+    }
+
+    /// Telemetry configuration, `None` if Telemetry is not enabled for the deployment.
+    pub fn get_telemetry_config(&self) -> Option<&TelemetryConfig> {
+        self.telemetry
+            .as_ref()
+            .filter(|telemetry| telemetry.enabled)
+    }
+
+    pub fn is_telemetry_enabled(&self) -> bool {
+        self.get_telemetry_config().is_some()
+    }
 }
 
 impl Default for Config {
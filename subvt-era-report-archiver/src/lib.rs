@@ -0,0 +1,423 @@
+//! Archives the reports of the ended eras (the era report, the offences and the report of each
+//! validator) as JSON and CSV files to the configured S3-compatible storage and IPFS node, so
+//! that long-term analytics don't require keeping the full history in the network database.
+//! After every archived era, a manifest that indexes all the archived eras of the target gets
+//! uploaded. Archival to each target is recorded, so that a failed target gets retried in the
+//! next run without repeating the others.
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac, NewMac};
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use subvt_config::Config;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_service_common::Service;
+use subvt_types::report::{
+    ArchivedEraReportFile, ArchivedEraValidatorReport, EraReportArchive, EraReportArchiveManifest,
+};
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+const MANIFEST_FILE_NAME: &str = "manifest.json";
+const CSV_HEADER: &str = "era_index,validator_account_id,is_active,commission_per_billion,self_stake,total_stake,block_count,primary_block_count,secondary_plain_block_count,secondary_vrf_block_count,reward_points,self_reward,staker_reward,offline_offence_count,slashed_amount,chilling_count";
+
+enum ArchiveTarget {
+    S3Storage,
+    Ipfs,
+}
+
+impl ArchiveTarget {
+    /// Key of the target in the archive records.
+    fn get_key(&self) -> String {
+        match self {
+            ArchiveTarget::S3Storage => format!("s3:{}", CONFIG.era_report_archiver.s3_bucket),
+            ArchiveTarget::Ipfs => "ipfs".to_string(),
+        }
+    }
+
+    fn get_configured_targets() -> Vec<ArchiveTarget> {
+        let config = &CONFIG.era_report_archiver;
+        let mut targets = Vec::new();
+        if !config.s3_endpoint_url.is_empty()
+            && !config.s3_bucket.is_empty()
+            && !config.s3_access_key_id.is_empty()
+            && !config.s3_secret_access_key.is_empty()
+        {
+            targets.push(ArchiveTarget::S3Storage);
+        }
+        if !config.ipfs_api_url.is_empty() {
+            targets.push(ArchiveTarget::Ipfs);
+        }
+        targets
+    }
+}
+
+struct ArchiveFile {
+    name: String,
+    content_type: &'static str,
+    content: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct IPFSAddResponse {
+    #[serde(rename = "Hash")]
+    hash: String,
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key)
+        .map_err(|_| anyhow::anyhow!("Invalid HMAC key length."))?;
+    mac.update(data);
+    Ok(mac.finalize().into_bytes().to_vec())
+}
+
+fn format_maybe<T: ToString>(maybe_value: &Option<T>) -> String {
+    maybe_value
+        .as_ref()
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+pub struct EraReportArchiver {
+    http_client: reqwest::Client,
+}
+
+impl Default for EraReportArchiver {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.era_report_archiver.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self { http_client }
+    }
+}
+
+impl EraReportArchiver {
+    async fn get_era_report_archive(
+        postgres: &PostgreSQLNetworkStorage,
+        era_index: u32,
+    ) -> anyhow::Result<Option<EraReportArchive>> {
+        let report = match postgres
+            .get_era_report(era_index, era_index)
+            .await?
+            .into_iter()
+            .next()
+        {
+            Some(report) => report,
+            None => return Ok(None),
+        };
+        let offences = postgres
+            .get_offence_report(era_index, era_index)
+            .await?
+            .eras
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        let mut validators = Vec::new();
+        for account_id in postgres.get_era_validator_account_ids(era_index).await? {
+            if let Some(validator_report) = postgres
+                .get_era_validator_report(era_index, era_index, &account_id.to_string())
+                .await?
+                .into_iter()
+                .next()
+            {
+                validators.push(ArchivedEraValidatorReport {
+                    account_id,
+                    report: validator_report,
+                });
+            }
+        }
+        Ok(Some(EraReportArchive {
+            report,
+            offences,
+            validators,
+        }))
+    }
+
+    /// Renders the validator reports of the era as CSV, one row per validator.
+    fn render_csv(archive: &EraReportArchive) -> String {
+        let mut lines = vec![CSV_HEADER.to_string()];
+        for validator in &archive.validators {
+            let report = &validator.report;
+            lines.push(
+                [
+                    archive.report.era.index.to_string(),
+                    validator.account_id.to_string(),
+                    format_maybe(&report.is_active),
+                    format_maybe(&report.commission_per_billion),
+                    format_maybe(&report.self_stake),
+                    format_maybe(&report.total_stake),
+                    report.block_count.to_string(),
+                    report.primary_block_count.to_string(),
+                    report.secondary_plain_block_count.to_string(),
+                    report.secondary_vrf_block_count.to_string(),
+                    format_maybe(&report.reward_points),
+                    report.self_reward.to_string(),
+                    report.staker_reward.to_string(),
+                    report.offline_offence_count.to_string(),
+                    report.slashed_amount.to_string(),
+                    report.chilling_count.to_string(),
+                ]
+                .join(","),
+            );
+        }
+        let mut csv = lines.join("\n");
+        csv.push('\n');
+        csv
+    }
+
+    fn get_archive_files(archive: &EraReportArchive) -> anyhow::Result<Vec<ArchiveFile>> {
+        let era_index = archive.report.era.index;
+        Ok(vec![
+            ArchiveFile {
+                name: format!("era-{}.json", era_index),
+                content_type: "application/json",
+                content: serde_json::to_vec(archive)?,
+            },
+            ArchiveFile {
+                name: format!("era-{}-validators.csv", era_index),
+                content_type: "text/csv",
+                content: EraReportArchiver::render_csv(archive).into_bytes(),
+            },
+        ])
+    }
+
+    /// `PUT`s the object with an AWS Signature Version 4 signed request, returns the object
+    /// URL. Object keys consist of URL-safe characters, so the path needs no further encoding.
+    async fn upload_to_s3(&self, key: &str, file: &ArchiveFile) -> anyhow::Result<String> {
+        let config = &CONFIG.era_report_archiver;
+        let url = reqwest::Url::parse(&format!(
+            "{}/{}/{}",
+            config.s3_endpoint_url.trim_end_matches('/'),
+            config.s3_bucket,
+            key
+        ))?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => anyhow::bail!("Invalid S3 endpoint URL."),
+        };
+        let now = chrono::Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let payload_hash = hex::encode(Sha256::digest(&file.content));
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+        let canonical_request = format!(
+            "PUT\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            url.path(),
+            host,
+            payload_hash,
+            amz_date,
+            signed_headers,
+            payload_hash
+        );
+        let scope = format!("{}/{}/s3/aws4_request", date, config.s3_region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+        let signing_key = {
+            let key = hmac_sha256(
+                format!("AWS4{}", config.s3_secret_access_key).as_bytes(),
+                date.as_bytes(),
+            )?;
+            let key = hmac_sha256(&key, config.s3_region.as_bytes())?;
+            let key = hmac_sha256(&key, b"s3")?;
+            hmac_sha256(&key, b"aws4_request")?
+        };
+        let signature = hex::encode(hmac_sha256(&signing_key, string_to_sign.as_bytes())?);
+        self.http_client
+            .put(url.clone())
+            .header("x-amz-content-sha256", payload_hash)
+            .header("x-amz-date", amz_date)
+            .header(
+                "Authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    config.s3_access_key_id, scope, signed_headers, signature
+                ),
+            )
+            .header("Content-Type", file.content_type)
+            .body(file.content.clone())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(url.to_string())
+    }
+
+    /// Adds and pins the file on the IPFS node, returns the `ipfs://` URI of the file.
+    async fn upload_to_ipfs(&self, file: &ArchiveFile) -> anyhow::Result<String> {
+        let url = format!(
+            "{}/api/v0/add?pin=true&cid-version=1",
+            CONFIG
+                .era_report_archiver
+                .ipfs_api_url
+                .trim_end_matches('/')
+        );
+        let part = reqwest::multipart::Part::bytes(file.content.clone())
+            .file_name(file.name.clone())
+            .mime_str(file.content_type)?;
+        let response: IPFSAddResponse = self
+            .http_client
+            .post(url)
+            .multipart(reqwest::multipart::Form::new().part("file", part))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        Ok(format!("ipfs://{}", response.hash))
+    }
+
+    async fn upload(
+        &self,
+        target: &ArchiveTarget,
+        key: &str,
+        file: &ArchiveFile,
+    ) -> anyhow::Result<String> {
+        match target {
+            ArchiveTarget::S3Storage => {
+                let key = format!("{}{}", CONFIG.era_report_archiver.s3_key_prefix, key);
+                self.upload_to_s3(&key, file).await
+            }
+            ArchiveTarget::Ipfs => self.upload_to_ipfs(file).await,
+        }
+    }
+
+    /// Uploads the manifest of all the archived eras of the target. The manifest has a fixed
+    /// key in S3-compatible storage, whereas its IPFS URI changes with every upload. The last
+    /// location gets saved for both.
+    async fn upload_manifest(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        target: &ArchiveTarget,
+    ) -> anyhow::Result<()> {
+        let target_key = target.get_key();
+        let manifest = EraReportArchiveManifest {
+            chain: CONFIG.substrate.chain.clone(),
+            updated_at: chrono::Utc::now().timestamp_millis() as u64,
+            eras: postgres.get_archived_era_reports(&target_key).await?,
+        };
+        let file = ArchiveFile {
+            name: MANIFEST_FILE_NAME.to_string(),
+            content_type: "application/json",
+            content: serde_json::to_vec(&manifest)?,
+        };
+        let location = self.upload(target, MANIFEST_FILE_NAME, &file).await?;
+        postgres
+            .save_era_report_archive_manifest_location(&target_key, &location)
+            .await?;
+        info!("Uploaded {} manifest to {}.", target_key, location);
+        Ok(())
+    }
+
+    async fn archive_era(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        target: &ArchiveTarget,
+        files: &[ArchiveFile],
+        era_index: u32,
+    ) -> anyhow::Result<()> {
+        let target_key = target.get_key();
+        let mut archived_files = Vec::new();
+        for file in files {
+            let location = self
+                .upload(target, &format!("eras/{}", file.name), file)
+                .await?;
+            archived_files.push(ArchivedEraReportFile {
+                file_name: file.name.clone(),
+                location,
+            });
+        }
+        postgres
+            .save_era_report_archive(era_index, &target_key, &archived_files)
+            .await?;
+        info!("Archived era #{} reports to {}.", era_index, target_key);
+        Ok(())
+    }
+
+    async fn archive_ended_eras(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        targets: &[ArchiveTarget],
+    ) -> anyhow::Result<()> {
+        for target in targets {
+            let target_key = target.get_key();
+            let era_indices = postgres
+                .get_unarchived_ended_era_indices(
+                    &target_key,
+                    CONFIG.era_report_archiver.max_era_count_per_run,
+                )
+                .await?;
+            if era_indices.is_empty() {
+                debug!("No era to archive to {}.", target_key);
+                continue;
+            }
+            let mut archived_era_count = 0;
+            for era_index in era_indices {
+                let archive =
+                    match EraReportArchiver::get_era_report_archive(postgres, era_index).await? {
+                        Some(archive) => archive,
+                        None => {
+                            warn!("Cannot get the reports of era #{}.", era_index);
+                            continue;
+                        }
+                    };
+                let files = EraReportArchiver::get_archive_files(&archive)?;
+                match self.archive_era(postgres, target, &files, era_index).await {
+                    Ok(()) => archived_era_count += 1,
+                    Err(error) => {
+                        // will be retried in the next run
+                        error!(
+                            "Error while archiving era #{} reports to {}: {:?}",
+                            era_index, target_key, error
+                        );
+                        break;
+                    }
+                }
+            }
+            if archived_era_count > 0 {
+                if let Err(error) = self.upload_manifest(postgres, target).await {
+                    error!("Error while uploading {} manifest: {:?}", target_key, error);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Service for EraReportArchiver {
+    async fn run(&'static self) -> anyhow::Result<()> {
+        let targets = ArchiveTarget::get_configured_targets();
+        if targets.is_empty() {
+            warn!("No era report archive targets are configured.");
+        }
+        info!(
+            "Era report archiver has started with {} target(s) and {} seconds refresh wait period.",
+            targets.len(),
+            CONFIG.era_report_archiver.refresh_seconds
+        );
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        loop {
+            if let Err(error) = self.archive_ended_eras(&postgres, &targets).await {
+                error!("Era report archival has failed: {:?}", error);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(
+                CONFIG.era_report_archiver.refresh_seconds,
+            ))
+            .await;
+        }
+    }
+}
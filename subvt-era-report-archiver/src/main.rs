@@ -0,0 +1,14 @@
+//! See `./lib.rs` for details.
+
+use lazy_static::lazy_static;
+use subvt_era_report_archiver::EraReportArchiver;
+use subvt_service_common::Service;
+
+lazy_static! {
+    static ref SERVICE: EraReportArchiver = EraReportArchiver::default();
+}
+
+#[tokio::main]
+async fn main() {
+    SERVICE.start().await;
+}
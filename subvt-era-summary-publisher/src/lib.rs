@@ -0,0 +1,306 @@
+//! Publishes a network summary of each ended era (new active validators, reward points leaders,
+//! slashes and offences, total stake change) to the configured webhooks, Matrix room and
+//! Twitter account. The summary is composed from the report queries of the network database.
+//! Publication to each target is recorded, so that a failed target gets retried in the next
+//! run without repeating the others. Only the most recent ended era is published, eras that
+//! have ended while the service was down are skipped.
+
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use subvt_config::Config;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_service_common::Service;
+use subvt_substrate_client::SubstrateClient;
+use subvt_types::report::EraSummary;
+use subvt_types::substrate::SystemProperties;
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+const TWITTER_API_URL: &str = "https://api.twitter.com/2/tweets";
+const TWEET_MAX_CHAR_COUNT: usize = 280;
+
+enum PublicationTarget {
+    Webhook(String),
+    Matrix,
+    Twitter,
+}
+
+impl PublicationTarget {
+    /// Key of the target in the publication records.
+    fn get_key(&self) -> String {
+        match self {
+            PublicationTarget::Webhook(url) => format!("webhook:{}", url),
+            PublicationTarget::Matrix => "matrix".to_string(),
+            PublicationTarget::Twitter => "twitter".to_string(),
+        }
+    }
+
+    fn get_configured_targets() -> Vec<PublicationTarget> {
+        let config = &CONFIG.era_summary_publisher;
+        let mut targets: Vec<PublicationTarget> = config
+            .webhook_urls
+            .iter()
+            .map(|url| PublicationTarget::Webhook(url.clone()))
+            .collect();
+        if !config.matrix_homeserver_url.is_empty()
+            && !config.matrix_room_id.is_empty()
+            && !config.matrix_access_token.is_empty()
+        {
+            targets.push(PublicationTarget::Matrix);
+        }
+        if !config.twitter_access_token.is_empty() {
+            targets.push(PublicationTarget::Twitter);
+        }
+        targets
+    }
+}
+
+#[derive(Serialize)]
+struct WebhookPayload<'a> {
+    chain: &'a str,
+    text: &'a str,
+    summary: &'a EraSummary,
+}
+
+pub struct EraSummaryPublisher {
+    http_client: reqwest::Client,
+}
+
+impl Default for EraSummaryPublisher {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.era_summary_publisher.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self { http_client }
+    }
+}
+
+/// Formats the amount in token units with two decimals, e.g. `1234.56 KSM`.
+fn format_amount(amount: u128, system_properties: &SystemProperties) -> String {
+    format!(
+        "{:.2} {}",
+        amount as f64 / 10f64.powi(system_properties.token_decimals as i32),
+        system_properties.token_symbol
+    )
+}
+
+impl EraSummaryPublisher {
+    fn compose_text(summary: &EraSummary, system_properties: &SystemProperties) -> String {
+        let report = &summary.report;
+        let mut lines = vec![format!(
+            "{} era #{} has ended.",
+            CONFIG.substrate.chain_display, report.era.index
+        )];
+        if let Some(total_stake) = report.total_stake {
+            let mut line = format!(
+                "Total stake: {}",
+                format_amount(total_stake, system_properties)
+            );
+            if let Some(previous_total_stake) = summary.previous_total_stake {
+                if previous_total_stake > 0 {
+                    let change_percent = (total_stake as f64 - previous_total_stake as f64)
+                        / previous_total_stake as f64
+                        * 100.0;
+                    line.push_str(&format!(" ({:+.2}%)", change_percent));
+                }
+            }
+            lines.push(line);
+        }
+        lines.push(format!(
+            "Active validators: {} ({} new)",
+            summary.active_validator_count,
+            summary.new_active_validator_account_ids.len()
+        ));
+        if !summary.points_leaders.is_empty() {
+            lines.push(format!(
+                "Points leaders: {}",
+                summary
+                    .points_leaders
+                    .iter()
+                    .map(|leader| format!(
+                        "{} ({})",
+                        leader.account_id.to_ss58_check(),
+                        leader.reward_points
+                    ))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        lines.push(format!(
+            "Slashed: {}, offline offences: {}, chills: {}",
+            format_amount(report.slashed_amount, system_properties),
+            report.offline_offence_count,
+            report.chilling_count,
+        ));
+        if summary.offences.offence_count > 0 {
+            lines.push(format!(
+                "Offences: {} ({})",
+                summary.offences.offence_count,
+                summary
+                    .offences
+                    .offence_counts_by_kind
+                    .iter()
+                    .map(|(offence_kind, count)| format!("{}: {}", offence_kind, count))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ));
+        }
+        lines.join("\n")
+    }
+
+    async fn publish(
+        &self,
+        target: &PublicationTarget,
+        summary: &EraSummary,
+        text: &str,
+    ) -> anyhow::Result<()> {
+        let config = &CONFIG.era_summary_publisher;
+        let request = match target {
+            PublicationTarget::Webhook(url) => {
+                self.http_client.post(url).json(&WebhookPayload {
+                    chain: &CONFIG.substrate.chain,
+                    text,
+                    summary,
+                })
+            }
+            PublicationTarget::Matrix => {
+                // transaction id makes the retries idempotent
+                let transaction_id = format!("subvt-era-summary-{}", summary.report.era.index);
+                let mut url = reqwest::Url::parse(&config.matrix_homeserver_url)?;
+                url.path_segments_mut()
+                    .map_err(|_| anyhow::anyhow!("Invalid Matrix homeserver URL."))?
+                    .pop_if_empty()
+                    .extend(&[
+                        "_matrix",
+                        "client",
+                        "r0",
+                        "rooms",
+                        config.matrix_room_id.as_str(),
+                        "send",
+                        "m.room.message",
+                        transaction_id.as_str(),
+                    ]);
+                self.http_client
+                    .put(url)
+                    .bearer_auth(&config.matrix_access_token)
+                    .json(&serde_json::json!({
+                        "msgtype": "m.text",
+                        "body": text,
+                    }))
+            }
+            PublicationTarget::Twitter => {
+                let tweet_text: String = if text.chars().count() > TWEET_MAX_CHAR_COUNT {
+                    let mut tweet_text: String =
+                        text.chars().take(TWEET_MAX_CHAR_COUNT - 1).collect();
+                    tweet_text.push('…');
+                    tweet_text
+                } else {
+                    text.to_string()
+                };
+                self.http_client
+                    .post(TWITTER_API_URL)
+                    .bearer_auth(&config.twitter_access_token)
+                    .json(&serde_json::json!({ "text": tweet_text }))
+            }
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    async fn publish_last_ended_era(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        system_properties: &SystemProperties,
+        targets: &[PublicationTarget],
+    ) -> anyhow::Result<()> {
+        let era_index = match postgres.get_last_ended_era_index().await? {
+            Some(era_index) => era_index,
+            None => {
+                debug!("No ended era yet.");
+                return Ok(());
+            }
+        };
+        let mut pending_targets = Vec::new();
+        for target in targets {
+            if !postgres
+                .era_summary_is_published(era_index, &target.get_key())
+                .await?
+            {
+                pending_targets.push(target);
+            }
+        }
+        if pending_targets.is_empty() {
+            debug!("Era #{} summary has been published.", era_index);
+            return Ok(());
+        }
+        let summary = match postgres
+            .get_era_summary(era_index, CONFIG.era_summary_publisher.points_leader_count)
+            .await?
+        {
+            Some(summary) => summary,
+            None => {
+                warn!("Cannot get the summary of era #{}.", era_index);
+                return Ok(());
+            }
+        };
+        let text = EraSummaryPublisher::compose_text(&summary, system_properties);
+        for target in pending_targets {
+            let target_key = target.get_key();
+            match self.publish(target, &summary, &text).await {
+                Ok(()) => {
+                    postgres
+                        .save_era_summary_publication(era_index, &target_key)
+                        .await?;
+                    info!("Published era #{} summary to {}.", era_index, target_key);
+                }
+                Err(error) => {
+                    // will be retried in the next run
+                    error!(
+                        "Error while publishing era #{} summary to {}: {:?}",
+                        era_index, target_key, error
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Service for EraSummaryPublisher {
+    async fn run(&'static self) -> anyhow::Result<()> {
+        let targets = PublicationTarget::get_configured_targets();
+        if targets.is_empty() {
+            warn!("No era summary publication targets are configured.");
+        }
+        info!(
+            "Era summary publisher has started with {} target(s) and {} seconds refresh wait period.",
+            targets.len(),
+            CONFIG.era_summary_publisher.refresh_seconds
+        );
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        let system_properties = SubstrateClient::new(&CONFIG).await?.system_properties;
+        loop {
+            if let Err(error) = self
+                .publish_last_ended_era(&postgres, &system_properties, &targets)
+                .await
+            {
+                error!("Era summary publication has failed: {:?}", error);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(
+                CONFIG.era_summary_publisher.refresh_seconds,
+            ))
+            .await;
+        }
+    }
+}
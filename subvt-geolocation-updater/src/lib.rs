@@ -0,0 +1,242 @@
+//! Resolves the Telemetry node coordinates and the 1KV candidate locations into country and
+//! region codes using a Nominatim-compatible geocoding service, then assigns a location to
+//! each validator in the latest validator list. Telemetry node locations take precedence
+//! over 1KV locations. Region codes are dropped when the configured precision is `country`.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use log::{debug, error, info};
+use serde::Deserialize;
+use subvt_config::{Config, GeolocationPrecision};
+use subvt_persistence::postgres::network::geolocation::GeolocationQuery;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore};
+use subvt_service_common::Service;
+use subvt_types::subvt::ValidatorDetails;
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+#[derive(Debug, Deserialize)]
+struct GeocodingAddress {
+    country_code: Option<String>,
+    #[serde(rename = "ISO3166-2-lvl4")]
+    iso_3166_2_lvl4: Option<String>,
+    #[serde(rename = "ISO3166-2-lvl6")]
+    iso_3166_2_lvl6: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeocodingPlace {
+    address: Option<GeocodingAddress>,
+}
+
+pub struct GeolocationUpdater {
+    http_client: reqwest::Client,
+}
+
+impl Default for GeolocationUpdater {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            // required by the Nominatim usage policy
+            .user_agent("subvt-geolocation-updater")
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.geolocation.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self { http_client }
+    }
+}
+
+impl GeolocationUpdater {
+    /// Returns the upper-case country code and the region code of the resolved place,
+    /// `None` if the place cannot be resolved.
+    async fn geocode(
+        &self,
+        query: &GeolocationQuery,
+    ) -> anyhow::Result<Option<(String, Option<String>)>> {
+        let base_url = &CONFIG.geolocation.geocoding_base_url;
+        let maybe_place: Option<GeocodingPlace> = match query {
+            GeolocationQuery::Coordinates {
+                latitude,
+                longitude,
+                ..
+            } => {
+                let response = self
+                    .http_client
+                    .get(&format!("{}/reverse", base_url))
+                    .query(&[
+                        ("format", "jsonv2"),
+                        ("zoom", "5"),
+                        ("addressdetails", "1"),
+                        ("lat", latitude.to_string().as_str()),
+                        ("lon", longitude.to_string().as_str()),
+                    ])
+                    .send()
+                    .await?;
+                // response has an error field and no address if the coordinates are unresolvable
+                Some(response.json().await?)
+            }
+            GeolocationQuery::Place { place, .. } => {
+                let response = self
+                    .http_client
+                    .get(&format!("{}/search", base_url))
+                    .query(&[
+                        ("format", "jsonv2"),
+                        ("limit", "1"),
+                        ("addressdetails", "1"),
+                        ("q", place.as_str()),
+                    ])
+                    .send()
+                    .await?;
+                let places: Vec<GeocodingPlace> = response.json().await?;
+                places.into_iter().next()
+            }
+        };
+        let address = match maybe_place.and_then(|place| place.address) {
+            Some(address) => address,
+            None => return Ok(None),
+        };
+        let country_code = match address.country_code {
+            Some(country_code) => country_code.to_uppercase(),
+            None => return Ok(None),
+        };
+        Ok(Some((
+            country_code,
+            address.iso_3166_2_lvl4.or(address.iso_3166_2_lvl6),
+        )))
+    }
+
+    async fn resolve_geolocations(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+    ) -> anyhow::Result<()> {
+        let queries = postgres.get_unresolved_geolocation_queries().await?;
+        info!("Resolve {} location(s).", queries.len());
+        for query in &queries {
+            match self.geocode(query).await {
+                Ok(maybe_result) => {
+                    debug!("Resolved {} :: {:?}.", query.get_query(), maybe_result);
+                    let (country_code, region_code) = match &maybe_result {
+                        Some((country_code, region_code)) => {
+                            (Some(country_code.as_str()), region_code.as_deref())
+                        }
+                        None => (None, None),
+                    };
+                    postgres
+                        .save_geolocation(query.get_query(), country_code, region_code)
+                        .await?;
+                }
+                Err(error) => {
+                    // will be retried in the next run
+                    error!(
+                        "Error while resolving location {}: {:?}",
+                        query.get_query(),
+                        error
+                    );
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(
+                CONFIG.geolocation.request_interval_millis,
+            ))
+            .await;
+        }
+        Ok(())
+    }
+
+    /// Reads the validators of the latest validator list from the real-time store.
+    fn get_validators(realtime_store: &dyn RealtimeStore) -> anyhow::Result<Vec<ValidatorDetails>> {
+        let finalized_block_number: u64 = realtime_store
+            .get(&format!(
+                "subvt:{}:validators:finalized_block_number",
+                CONFIG.substrate.chain
+            ))?
+            .context("No validator list in the real-time store.")?
+            .parse()?;
+        let prefix = format!(
+            "subvt:{}:validators:{}",
+            CONFIG.substrate.chain, finalized_block_number
+        );
+        let mut validators = Vec::new();
+        for list in ["active", "inactive"] {
+            let account_id_hex_strings =
+                realtime_store.get_set_members(&format!("{}:{}:account_id_set", prefix, list))?;
+            for account_id_hex_string in account_id_hex_strings {
+                if let Some(validator_json_string) = realtime_store.get(&format!(
+                    "{}:{}:validator:{}",
+                    prefix, list, account_id_hex_string
+                ))? {
+                    validators.push(serde_json::from_str(&validator_json_string)?);
+                }
+            }
+        }
+        Ok(validators)
+    }
+
+    async fn update_validator_locations(
+        postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
+    ) -> anyhow::Result<()> {
+        let validators = GeolocationUpdater::get_validators(realtime_store)?;
+        info!("Update the locations of {} validators.", validators.len());
+        for validator in &validators {
+            let mut maybe_location = postgres
+                .get_resolved_validator_location(
+                    &validator.account.id,
+                    &validator.controller_account_id,
+                )
+                .await?;
+            if CONFIG.geolocation.precision == GeolocationPrecision::Country {
+                if let Some(location) = maybe_location.as_mut() {
+                    location.region_code = None;
+                }
+            }
+            postgres
+                .save_validator_location(&validator.account.id, maybe_location.as_ref())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
+    ) -> anyhow::Result<()> {
+        info!("Update validator locations.");
+        self.resolve_geolocations(postgres).await?;
+        GeolocationUpdater::update_validator_locations(postgres, realtime_store).await?;
+        info!("Validator location update completed.");
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Service for GeolocationUpdater {
+    async fn run(&'static self) -> anyhow::Result<()> {
+        info!(
+            "Geolocation updater has started with {} seconds refresh wait period.",
+            CONFIG.geolocation.refresh_seconds
+        );
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        loop {
+            if let Err(error) = self.update(&postgres, realtime_store.as_ref()).await {
+                error!("Geolocation update has failed: {:?}", error);
+                error!(
+                    "Will retry in {} seconds.",
+                    CONFIG.geolocation.refresh_seconds
+                );
+            }
+            std::thread::sleep(std::time::Duration::from_secs(
+                CONFIG.geolocation.refresh_seconds,
+            ));
+        }
+    }
+}
@@ -0,0 +1,14 @@
+//! See `./lib.rs` for details.
+
+use lazy_static::lazy_static;
+use subvt_geolocation_updater::GeolocationUpdater;
+use subvt_service_common::Service;
+
+lazy_static! {
+    static ref SERVICE: GeolocationUpdater = GeolocationUpdater::default();
+}
+
+#[tokio::main]
+async fn main() {
+    SERVICE.start().await;
+}
@@ -1,5 +1,7 @@
-//! Subscribes to the live network status data on Redis and publishes the data through
-//! websocket pub/sub.
+//! Subscribes to the live network status data on the real-time store and publishes the data through
+//! websocket pub/sub. When subscription authentication is enabled, `subscribe_live_network_status`
+//! accepts a session token of the user as an optional parameter, see `subscription` in
+//! `subvt-service-common`.
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -7,10 +9,13 @@ use bus::Bus;
 use jsonrpsee::ws_server::{RpcModule, WsServerBuilder, WsServerHandle};
 use lazy_static::lazy_static;
 use log::{debug, error, warn};
-use redis::Connection;
 use std::sync::{Arc, Mutex, RwLock};
 use subvt_config::Config;
-use subvt_service_common::Service;
+use subvt_persistence::realtime::{
+    get_realtime_store, get_skipped_block_counter_key, RealtimeStore,
+};
+use subvt_service_common::subscription::SubscriptionAuthorizer;
+use subvt_service_common::{analytics, Service};
 use subvt_types::subvt::{LiveNetworkStatus, LiveNetworkStatusDiff, LiveNetworkStatusUpdate};
 
 lazy_static! {
@@ -28,13 +33,13 @@ pub struct LiveNetworkStatusServer;
 
 impl LiveNetworkStatusServer {
     async fn read_current_network_status(
-        connection: &mut Connection,
+        realtime_store: &dyn RealtimeStore,
     ) -> anyhow::Result<LiveNetworkStatus> {
         let key = format!("subvt:{}:live_network_status", CONFIG.substrate.chain);
-        let status_json_string: String = redis::cmd("GET")
-            .arg(key)
-            .query(connection)
-            .context("Can't read network status from Redis.")?;
+        let status_json_string = realtime_store
+            .get(&key)
+            .context("Can't read network status from the real-time store.")?
+            .context("Network status not found in the real-time store.")?;
         let status: LiveNetworkStatus = serde_json::from_str(&status_json_string)
             .context("Can't deserialize network status json.")?;
         Ok(status)
@@ -43,6 +48,7 @@ impl LiveNetworkStatusServer {
     async fn run_rpc_server(
         current_status: &Arc<RwLock<LiveNetworkStatus>>,
         bus: &Arc<Mutex<Bus<BusEvent>>>,
+        authorizer: &Arc<SubscriptionAuthorizer>,
     ) -> anyhow::Result<WsServerHandle> {
         let rpc_ws_server = WsServerBuilder::default()
             .build(format!(
@@ -53,12 +59,22 @@ impl LiveNetworkStatusServer {
         let mut rpc_module = RpcModule::new(());
         let current_status = current_status.clone();
         let bus = bus.clone();
+        let authorizer = authorizer.clone();
         rpc_module.register_subscription(
             "subscribe_live_network_status",
             "subscribe_live_network_status",
             "unsubscribe_live_network_status",
-            move |_params, mut sink, _| {
+            move |params, mut sink, _| {
+                let maybe_token: Option<String> = params.sequence().optional_next()?;
+                let permit = match authorizer.authorize_blocking(maybe_token.as_deref()) {
+                    Ok(permit) => permit,
+                    Err(message) => {
+                        let _ = sink.send(&message);
+                        return Err(jsonrpsee_core::error::Error::Custom(message));
+                    }
+                };
                 debug!("New subscription.");
+                analytics::record("subscription:live_network_status", 1);
                 let mut bus_receiver = bus.lock().unwrap().add_rx();
                 {
                     let current_status = current_status.read().unwrap();
@@ -72,27 +88,31 @@ impl LiveNetworkStatusServer {
                         let _ = sink.send(&update);
                     }
                 }
-                std::thread::spawn(move || loop {
-                    if let Ok(status_diff) = bus_receiver.recv() {
-                        match status_diff {
-                            BusEvent::NewBlock(status_diff) => {
-                                let update = LiveNetworkStatusUpdate {
-                                    network: CONFIG.substrate.chain.clone(),
-                                    status: None,
-                                    diff_base_block_number: None,
-                                    diff: Some(*status_diff.clone()),
-                                };
-                                let send_result = sink.send(&update);
-                                if let Err(error) = send_result {
-                                    debug!("Subscription closed. {:?}", error);
+                std::thread::spawn(move || {
+                    // keep the permit for the lifetime of the subscription
+                    let _permit = permit;
+                    loop {
+                        if let Ok(status_diff) = bus_receiver.recv() {
+                            match status_diff {
+                                BusEvent::NewBlock(status_diff) => {
+                                    let update = LiveNetworkStatusUpdate {
+                                        network: CONFIG.substrate.chain.clone(),
+                                        status: None,
+                                        diff_base_block_number: None,
+                                        diff: Some(*status_diff.clone()),
+                                    };
+                                    let send_result = sink.send(&update);
+                                    if let Err(error) = send_result {
+                                        debug!("Subscription closed. {:?}", error);
+                                        return;
+                                    } else {
+                                        debug!("Published diff.");
+                                    }
+                                }
+                                BusEvent::Error => {
                                     return;
-                                } else {
-                                    debug!("Published diff.");
                                 }
                             }
-                            BusEvent::Error => {
-                                return;
-                            }
                         }
                     }
                 });
@@ -109,31 +129,35 @@ impl Service for LiveNetworkStatusServer {
     async fn run(&'static self) -> anyhow::Result<()> {
         let bus = Arc::new(Mutex::new(Bus::new(100)));
         let current_status = Arc::new(RwLock::new(LiveNetworkStatus::default()));
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str()).context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
-
-        let mut pub_sub_connection = redis_client.get_connection()?;
-        let mut pub_sub = pub_sub_connection.as_pubsub();
-        pub_sub.subscribe(format!(
-            "subvt:{}:live_network_status:publish:best_block_number",
-            CONFIG.substrate.chain
-        ))?;
-        let mut data_connection = redis_client.get_connection()?;
+        let authorizer = Arc::new(SubscriptionAuthorizer::new(&CONFIG).await?);
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        let subscription = realtime_store
+            .subscribe(&format!(
+                "subvt:{}:live_network_status:publish:best_block_number",
+                CONFIG.substrate.chain
+            ))?
+            .coalesce(CONFIG.realtime.coalesce_block_notifications);
         let server_stop_handle =
-            LiveNetworkStatusServer::run_rpc_server(&current_status, &bus).await?;
+            LiveNetworkStatusServer::run_rpc_server(&current_status, &bus, &authorizer).await?;
 
         let error: anyhow::Error = loop {
-            let message = pub_sub.get_message();
-            if let Err(error) = message {
-                break error.into();
-            }
-            let payload = message.unwrap().get_payload();
-            if let Err(error) = payload {
-                break error.into();
+            let best_block_number = match subscription.next_block_number() {
+                Ok(best_block_number) => best_block_number,
+                Err(error) => break error,
+            };
+            let skipped_block_count = subscription.take_skipped_block_count();
+            if skipped_block_count > 0 {
+                analytics::record("realtime:skipped_blocks", skipped_block_count);
+                if let Err(error) = realtime_store.increment(
+                    &get_skipped_block_counter_key(
+                        &CONFIG.substrate.chain,
+                        "live_network_status_server",
+                    ),
+                    skipped_block_count,
+                ) {
+                    warn!("Cannot count the skipped blocks: {:?}", error);
+                }
             }
-            let best_block_number: u64 = payload.unwrap();
             {
                 let current_status = current_status.read().unwrap();
                 if current_status.best_block_number == best_block_number {
@@ -142,7 +166,9 @@ impl Service for LiveNetworkStatusServer {
                 }
             }
             debug!("New best block #{}.", best_block_number);
-            match LiveNetworkStatusServer::read_current_network_status(&mut data_connection).await {
+            match LiveNetworkStatusServer::read_current_network_status(realtime_store.as_ref())
+                .await
+            {
                 Ok(new_status) => {
                     {
                         let current_status = current_status.read().unwrap();
@@ -1,58 +1,182 @@
-//! Updates the Redis database after every block with live network status data.
-//! Subscribes to the new blocks using the Substrate client in `subvt-substrate-client`.
+//! Updates the real-time store (Redis by default) after every block with live network status data.
+//! Subscribes to the best (non-finalized) or the finalized blocks using the Substrate client in
+//! `subvt-substrate-client`, see `live_network_status_updater.follow_best_blocks`. Detects the
+//! finality stalls from the gap between the best and the finalized block numbers, and alerts
+//! through the configured webhooks. Reconnects with the new metadata after a runtime upgrade,
+//! and publishes the upgrade on the `runtime_upgraded` channel.
 
 use anyhow::Context;
 use async_trait::async_trait;
 use chrono::Utc;
 use lazy_static::lazy_static;
-use log::{debug, error};
-use redis::Pipeline;
+use log::{debug, error, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use subvt_config::Config;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore, RealtimeWriteBatch};
 use subvt_service_common::Service;
 use subvt_substrate_client::SubstrateClient;
-use subvt_types::{substrate::BlockHeader, subvt::LiveNetworkStatus};
+use subvt_types::substrate::{BlockHeader, RuntimeVersion};
+use subvt_types::subvt::LiveNetworkStatus;
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
 }
 
-#[derive(Default)]
 pub struct LiveNetworkStatusUpdater {
+    http_client: reqwest::Client,
     last_network_status: Mutex<LiveNetworkStatus>,
+    is_finality_stalled: AtomicBool,
+}
+
+impl Default for LiveNetworkStatusUpdater {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.live_network_status_updater.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self {
+            http_client,
+            last_network_status: Default::default(),
+            is_finality_stalled: AtomicBool::new(false),
+        }
+    }
 }
 
 impl LiveNetworkStatusUpdater {
-    /// Updates the Redis database with the given live network status data.
-    fn update_redis(status: &LiveNetworkStatus) -> anyhow::Result<()> {
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str())?;
-        let mut redis_connection = redis_client.get_connection().context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
+    async fn send_finality_alert(&self, is_stalled: bool, status: &LiveNetworkStatus) {
+        let payload = serde_json::json!({
+            "chain": CONFIG.substrate.chain,
+            "event": if is_stalled { "finality_stalled" } else { "finality_recovered" },
+            "best_block_number": status.best_block_number,
+            "finalized_block_number": status.finalized_block_number,
+        });
+        for url in &CONFIG.live_network_status_updater.alert_webhook_urls {
+            let result = self
+                .http_client
+                .post(url)
+                .json(&payload)
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+            if let Err(error) = result {
+                error!("Error while sending finality alert to {}: {:?}", url, error);
+            }
+        }
+    }
+
+    /// Alerts once when the finalized block falls behind the best block by the configured gap,
+    /// and once when it catches up.
+    async fn check_finality(&self, status: &LiveNetworkStatus) {
+        let stall_block_gap = CONFIG.live_network_status_updater.finality_stall_block_gap;
+        if stall_block_gap == 0 {
+            return;
+        }
+        let gap = status
+            .best_block_number
+            .saturating_sub(status.finalized_block_number);
+        let is_stalled = gap >= stall_block_gap;
+        if self
+            .is_finality_stalled
+            .compare_exchange(!is_stalled, is_stalled, Ordering::SeqCst, Ordering::SeqCst)
+            .is_err()
+        {
+            return;
+        }
+        if is_stalled {
+            warn!(
+                "Finality stalled. Best block #{} is {} blocks ahead of finalized block #{}.",
+                status.best_block_number, gap, status.finalized_block_number
+            );
+        } else {
+            info!(
+                "Finality recovered. Finalized block #{}, best block #{}.",
+                status.finalized_block_number, status.best_block_number
+            );
+        }
+        self.send_finality_alert(is_stalled, status).await;
+    }
+
+    /// Updates the real-time store with the given live network status data.
+    fn update_realtime_store(
+        realtime_store: &dyn RealtimeStore,
+        status: &LiveNetworkStatus,
+    ) -> anyhow::Result<()> {
         let status_json_string = serde_json::to_string(status)?;
-        let mut redis_cmd_pipeline = Pipeline::new();
-        redis_cmd_pipeline
-            .cmd("SET")
-            .arg(format!(
-                "subvt:{}:live_network_status",
-                CONFIG.substrate.chain
-            ))
-            .arg(status_json_string)
-            .cmd("PUBLISH")
-            .arg(format!(
-                "subvt:{}:live_network_status:publish:best_block_number",
-                CONFIG.substrate.chain
-            ))
-            .arg(status.best_block_number)
-            .query(&mut redis_connection)
-            .context("Error while publishing Redis pub/sub event.")?;
+        let mut batch = RealtimeWriteBatch::default();
+        batch
+            .set(
+                &format!("subvt:{}:live_network_status", CONFIG.substrate.chain),
+                status_json_string,
+            )
+            .publish_block_number(
+                &format!(
+                    "subvt:{}:live_network_status:publish:best_block_number",
+                    CONFIG.substrate.chain
+                ),
+                status.best_block_number,
+            );
+        realtime_store
+            .write(&batch)
+            .context("Error while publishing live network status.")?;
         Ok(())
     }
 
+    /// Saves the new runtime version and publishes its spec version, so that the servers can
+    /// invalidate the data that depends on the runtime metadata.
+    fn publish_runtime_upgrade(
+        realtime_store: &dyn RealtimeStore,
+        runtime_version: &RuntimeVersion,
+    ) -> anyhow::Result<()> {
+        let mut batch = RealtimeWriteBatch::default();
+        batch
+            .set(
+                &format!("subvt:{}:runtime_version", CONFIG.substrate.chain),
+                serde_json::to_string(runtime_version)?,
+            )
+            .publish_block_number(
+                &format!("subvt:{}:runtime_upgraded", CONFIG.substrate.chain),
+                runtime_version.spec_version as u64,
+            );
+        realtime_store
+            .write(&batch)
+            .context("Error while publishing runtime upgrade.")?;
+        Ok(())
+    }
+
+    /// Updates the status with a block from the subscription, which is the best block when
+    /// following the best blocks, and the finalized block otherwise.
+    async fn update_with_block(
+        &self,
+        client: &SubstrateClient,
+        realtime_store: &dyn RealtimeStore,
+        block_header: &BlockHeader,
+    ) -> anyhow::Result<LiveNetworkStatus> {
+        if CONFIG.live_network_status_updater.follow_best_blocks {
+            return self
+                .fetch_and_update_live_network_status(client, realtime_store, block_header)
+                .await;
+        }
+        let best_block_hash = client
+            .get_current_block_hash()
+            .await
+            .context("Error while fetching best block hash.")?;
+        let best_block_header = client
+            .get_block_header(&best_block_hash)
+            .await
+            .context("Error while fetching best block header.")?;
+        self.fetch_and_update_live_network_status(client, realtime_store, &best_block_header)
+            .await
+    }
+
     async fn fetch_and_update_live_network_status(
         &self,
         client: &SubstrateClient,
+        realtime_store: &dyn RealtimeStore,
         best_block_header: &BlockHeader,
     ) -> anyhow::Result<LiveNetworkStatus> {
         let last_status = {
@@ -225,9 +349,9 @@ impl LiveNetworkStatusUpdater {
             median_stake,
             era_reward_points,
         };
-        // write to redis
-        LiveNetworkStatusUpdater::update_redis(&live_network_status)?;
-        debug!("Redis updated.");
+        // write to the real-time store
+        LiveNetworkStatusUpdater::update_realtime_store(realtime_store, &live_network_status)?;
+        debug!("Real-time store updated.");
         Ok(live_network_status)
     }
 }
@@ -238,15 +362,21 @@ impl Service for LiveNetworkStatusUpdater {
     async fn run(&'static self) -> anyhow::Result<()> {
         loop {
             let substrate_client = Arc::new(SubstrateClient::new(&CONFIG).await?);
-            substrate_client.subscribe_to_new_blocks(|best_block_header| {
+            let realtime_store = get_realtime_store(&CONFIG)?;
+            let on_block = |block_header: BlockHeader| {
                 let substrate_client = Arc::clone(&substrate_client);
+                let realtime_store = Arc::clone(&realtime_store);
                 tokio::spawn(async move {
-                    let update_result = self.fetch_and_update_live_network_status(
-                        &substrate_client,
-                        &best_block_header,
-                    ).await;
+                    let update_result = self
+                        .update_with_block(
+                            &substrate_client,
+                            realtime_store.as_ref(),
+                            &block_header,
+                        )
+                        .await;
                     match update_result {
                         Ok(network_status) => {
+                            self.check_finality(&network_status).await;
                             let mut last_network_status = self.last_network_status.lock().unwrap();
                             *last_network_status = network_status;
                         }
@@ -254,15 +384,40 @@ impl Service for LiveNetworkStatusUpdater {
                             error!("{:?}", error);
                             error!(
                                 "Live network status update failed for block #{}. Will try again with the next block.",
-                                best_block_header.get_number().unwrap_or(0),
+                                block_header.get_number().unwrap_or(0),
                             );
                         }
                     }
                 });
-            }).await?;
+            };
+            let block_subscription = async {
+                if CONFIG.live_network_status_updater.follow_best_blocks {
+                    substrate_client.subscribe_to_new_blocks(on_block).await
+                } else {
+                    substrate_client
+                        .subscribe_to_finalized_blocks(on_block)
+                        .await
+                }
+            };
+            tokio::select! {
+                subscription_result = block_subscription => subscription_result?,
+                runtime_upgrade_result = substrate_client.wait_for_runtime_upgrade() => {
+                    let runtime_version = runtime_upgrade_result?;
+                    info!(
+                        "Runtime upgraded from #{} to #{}. Will reconnect with the new metadata.",
+                        substrate_client.metadata.last_runtime_upgrade_info.spec_version,
+                        runtime_version.spec_version,
+                    );
+                    LiveNetworkStatusUpdater::publish_runtime_upgrade(
+                        realtime_store.as_ref(),
+                        &runtime_version,
+                    )?;
+                    continue;
+                }
+            }
             let delay_seconds = CONFIG.common.recovery_retry_seconds;
             error!(
-                "New block subscription exited. Will refresh connection and subscription after {} seconds.",
+                "Block subscription exited. Will refresh connection and subscription after {} seconds.",
                 delay_seconds
             );
             std::thread::sleep(std::time::Duration::from_secs(delay_seconds));
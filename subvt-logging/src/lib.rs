@@ -15,13 +15,19 @@ pub fn init(config: &subvt_config::Config) {
     builder.filter(None, other_modules_log_level);
     // include all executable SubVT modules here
     builder.filter(Some("subvt_app_service"), log_level);
+    builder.filter(Some("subvt_auditor"), log_level);
     builder.filter(Some("subvt_block_processor"), log_level);
+    builder.filter(Some("subvt_cli"), log_level);
+    builder.filter(Some("subvt_era_report_archiver"), log_level);
+    builder.filter(Some("subvt_era_summary_publisher"), log_level);
+    builder.filter(Some("subvt_geolocation_updater"), log_level);
     builder.filter(Some("subvt_live_network_status_server"), log_level);
     builder.filter(Some("subvt_live_network_status_updater"), log_level);
     builder.filter(Some("subvt_notification_generator"), log_level);
     builder.filter(Some("subvt_notification_sender"), log_level);
     builder.filter(Some("subvt_onekv_updater"), log_level);
     builder.filter(Some("subvt_persistence"), log_level);
+    builder.filter(Some("subvt_price_updater"), log_level);
     builder.filter(Some("subvt_report_service"), log_level);
     builder.filter(Some("subvt_substrate_client"), log_level);
     builder.filter(Some("subvt_telemetry_processor"), log_level);
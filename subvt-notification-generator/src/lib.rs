@@ -1,10 +1,18 @@
 //! Generates notifications according to the notification rules depending on three sources of data:
-//! 1. Validator list updates from Redis, updated by `subvt-validator-list-updater`, and published
-//! using the Redis notification (PUBLISH) support.
+//! 1. Validator list updates from the real-time store (Redis in production), updated by
+//! `subvt-validator-list-updater`, and published using the store's publish support.
 //! 2. Events and extrinsics in new blocks. Block are processed by `subvt-block-processor`, and the
 //! finishing of the processing of a block is signalled by the processor by means of PostgreSQL
 //! notifications.
 //! 3. Regular Telemetry checks (this is work in progress still).
+//!
+//! The periodic checks on the validator list updates, e.g. of the unclaimed payouts, get
+//! evaluated with the cadences configured per notification type, see the `schedule` module.
+//!
+//! Multiple instances can run concurrently, see the `partition` module for how the users are
+//! distributed among the instances. The periodic checks are not partitioned: each period of a
+//! check gets claimed and evaluated for all users by a single instance.
+use crate::partition::get_work_partition;
 use async_trait::async_trait;
 use lazy_static::lazy_static;
 use log::debug;
@@ -12,13 +20,16 @@ use serde::Serialize;
 use std::sync::Arc;
 use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
 use subvt_service_common::Service;
 use subvt_substrate_client::SubstrateClient;
-use subvt_types::app::{Notification, UserNotificationRule};
+use subvt_types::app::{Notification, NotificationDeepLink, UserNotificationRule};
 use subvt_types::crypto::AccountId;
 use tokio::runtime::Builder;
 
+mod partition;
 mod processor;
+mod schedule;
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -29,7 +40,9 @@ pub struct NotificationGenerator;
 
 impl NotificationGenerator {
     /// Persist notifications for a validator, which will later be be processed by
-    /// `subvt-notification-sender`.
+    /// `subvt-notification-sender`. Rules of the users that are not in the work partition
+    /// of this instance for the block are skipped. The block must have been reserved, see
+    /// the `partition` module.
     async fn generate_notifications<T: Clone + Serialize>(
         config: &Config,
         app_postgres: &PostgreSQLAppStorage,
@@ -39,6 +52,59 @@ impl NotificationGenerator {
         validator_account_id: &AccountId,
         notification_data: Option<&T>,
     ) -> anyhow::Result<()> {
+        let partition = get_work_partition(block_number);
+        let rules: Vec<&UserNotificationRule> = rules
+            .iter()
+            .filter(|rule| partition.includes_user(rule.user_id))
+            .collect();
+        NotificationGenerator::persist_notifications(
+            config,
+            app_postgres,
+            substrate_client,
+            &rules,
+            block_number,
+            validator_account_id,
+            notification_data,
+        )
+        .await
+    }
+
+    /// Persist notifications for a validator for the rules of all users. Used by the periodic
+    /// checks, which get evaluated by a single instance per period, see the `schedule` module.
+    async fn generate_notifications_for_all_users<T: Clone + Serialize>(
+        config: &Config,
+        app_postgres: &PostgreSQLAppStorage,
+        substrate_client: &Arc<SubstrateClient>,
+        rules: &[UserNotificationRule],
+        block_number: u64,
+        validator_account_id: &AccountId,
+        notification_data: Option<&T>,
+    ) -> anyhow::Result<()> {
+        let rules: Vec<&UserNotificationRule> = rules.iter().collect();
+        NotificationGenerator::persist_notifications(
+            config,
+            app_postgres,
+            substrate_client,
+            &rules,
+            block_number,
+            validator_account_id,
+            notification_data,
+        )
+        .await
+    }
+
+    async fn persist_notifications<T: Clone + Serialize>(
+        config: &Config,
+        app_postgres: &PostgreSQLAppStorage,
+        substrate_client: &Arc<SubstrateClient>,
+        rules: &[&UserNotificationRule],
+        block_number: u64,
+        validator_account_id: &AccountId,
+        notification_data: Option<&T>,
+    ) -> anyhow::Result<()> {
+        if rules.is_empty() {
+            return Ok(());
+        }
         let block_hash = substrate_client.get_block_hash(block_number).await?;
         // get account information for the validator stash address, which is used to display
         // identity information if exists
@@ -51,8 +117,16 @@ impl NotificationGenerator {
         } else {
             None
         };
+        let active_era_index = substrate_client.get_active_era(&block_hash).await?.index;
         // create separate notifications for each rule and notification channel
         for rule in rules {
+            let deep_link = NotificationDeepLink::new(
+                &config.substrate.chain,
+                validator_account_id,
+                &rule.notification_type.code,
+                Some(active_era_index),
+            );
+            let deep_link_json = serde_json::to_string(&deep_link)?;
             debug!(
                 "Generate {} notification for {}.",
                 rule.notification_type.code,
@@ -82,6 +156,7 @@ impl NotificationGenerator {
                     } else {
                         None
                     },
+                    deep_link_json: Some(deep_link_json.clone()),
                 };
                 let _ = app_postgres.save_notification(&notification).await?;
             }
@@ -93,6 +168,14 @@ impl NotificationGenerator {
 #[async_trait(?Send)]
 impl Service for NotificationGenerator {
     async fn run(&'static self) -> anyhow::Result<()> {
+        // join the work partition before processing any data, then keep it up to date
+        let partition_network_postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        NotificationGenerator::join_work_partition(&CONFIG, &partition_network_postgres).await?;
+        tokio::spawn(NotificationGenerator::maintain_work_partition(
+            &CONFIG,
+            partition_network_postgres,
+        ));
         let substrate_client = Arc::new(SubstrateClient::new(&CONFIG).await?);
         // for async in sync context
         let tokio_rt = Builder::new_current_thread().enable_all().build().unwrap();
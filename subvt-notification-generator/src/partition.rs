@@ -0,0 +1,297 @@
+//! Partitions the users among the concurrently running generator instances. Each instance
+//! records a heartbeat regularly, and the instances that have a heartbeat within the lease
+//! period form the work partition. The partition changes in epochs, which take effect at a block
+//! number that all the instances agree on: an instance reserves each block number before
+//! processing the block, and a new epoch takes effect after the highest reserved block number.
+//! So each block gets processed with the same partition by all the instances, and no user gets
+//! skipped or processed twice when the instances join or leave. The users of an instance that
+//! stops (or crashes) within a block are not processed for the blocks it has reserved but not
+//! processed.
+//!
+//! In an epoch, the instances are sorted by their ids, and an instance generates notifications
+//! only for the users whose ids modulo the instance count equal its position in the sorted list.
+//! The first instance is the leader, and it's the only one that persists the app events, which
+//! are shared by all users.
+//!
+//! The instance ids have to be unique. The id defaults to the host name, and every process gets
+//! a run id, so that an instance fails to start if there is another live process with the same
+//! instance id. A process that finds its instance id taken over by another process (i.e. after
+//! it couldn't send a heartbeat for longer than the lease period) exits.
+use crate::NotificationGenerator;
+use chrono::Utc;
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+use subvt_config::Config;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+
+lazy_static! {
+    /// Partition epochs that apply to the reserved blocks, instance ids by effective block number.
+    static ref EPOCHS: RwLock<BTreeMap<u64, Vec<String>>> = RwLock::new(BTreeMap::new());
+    static ref INSTANCE_ID: String = get_configured_instance_id(&crate::CONFIG);
+    /// Distinguishes this process from a process with the same instance id.
+    static ref RUN_ID: String = format!(
+        "{}-{}",
+        std::process::id(),
+        Utc::now().timestamp_nanos()
+    );
+}
+
+/// Configured instance id if exists, the host name otherwise.
+fn get_configured_instance_id(config: &Config) -> String {
+    if let Some(instance_id) = &config.notification_generator.instance_id {
+        return instance_id.clone();
+    }
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|host_name| host_name.trim().to_string())
+        .filter(|host_name| !host_name.is_empty())
+        .unwrap_or_else(|| "default".to_string())
+}
+
+pub(crate) fn get_instance_id() -> &'static str {
+    &INSTANCE_ID
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct WorkPartition {
+    /// `None` if the instance is not in the partition, i.e. it has no users.
+    pub maybe_instance_index: Option<u32>,
+    pub instance_count: u32,
+}
+
+impl WorkPartition {
+    /// Partition of the instance among the instances sorted by their ids.
+    fn new(instance_ids: &[String], instance_id: &str) -> Self {
+        Self {
+            maybe_instance_index: instance_ids
+                .iter()
+                .position(|id| id == instance_id)
+                .map(|instance_index| instance_index as u32),
+            instance_count: instance_ids.len() as u32,
+        }
+    }
+
+    /// Partition of the instance in the epoch of the block. The instance has no users before
+    /// the first epoch.
+    fn of_block(epochs: &BTreeMap<u64, Vec<String>>, block_number: u64, instance_id: &str) -> Self {
+        match epochs.range(..=block_number).next_back() {
+            Some((_, instance_ids)) => WorkPartition::new(instance_ids, instance_id),
+            None => WorkPartition::new(&[], instance_id),
+        }
+    }
+
+    pub fn includes_user(&self, user_id: u32) -> bool {
+        match self.maybe_instance_index {
+            Some(instance_index) => user_id % self.instance_count == instance_index,
+            None => false,
+        }
+    }
+
+    pub fn is_leader(&self) -> bool {
+        self.maybe_instance_index == Some(0)
+    }
+}
+
+/// Partition of this instance for a block that has been reserved, see
+/// `NotificationGenerator::reserve_work_partition`.
+pub(crate) fn get_work_partition(block_number: u64) -> WorkPartition {
+    WorkPartition::of_block(&EPOCHS.read().unwrap(), block_number, get_instance_id())
+}
+
+impl NotificationGenerator {
+    /// Reserves the block for this instance, which fixes the work partition of the block, and
+    /// gets the partition. Has to be called before processing the block.
+    pub(crate) async fn reserve_work_partition(
+        network_postgres: &PostgreSQLNetworkStorage,
+        block_number: u64,
+    ) -> anyhow::Result<WorkPartition> {
+        let epochs = network_postgres
+            .reserve_notification_generator_block(get_instance_id(), block_number)
+            .await?;
+        let partition = {
+            let mut cached_epochs = EPOCHS.write().unwrap();
+            cached_epochs.extend(epochs);
+            WorkPartition::of_block(&cached_epochs, block_number, get_instance_id())
+        };
+        debug!(
+            "Block #{} partition: {:?} of {} instance(s).",
+            block_number, partition.maybe_instance_index, partition.instance_count,
+        );
+        Ok(partition)
+    }
+
+    /// Records the heartbeat of this instance and starts a new partition epoch if the live
+    /// instances have changed. Returns `false` without starting an epoch if the instance id is
+    /// in use by another live process.
+    async fn update_work_partition(
+        config: &Config,
+        network_postgres: &PostgreSQLNetworkStorage,
+    ) -> anyhow::Result<bool> {
+        let instance_id = get_instance_id();
+        if !network_postgres
+            .save_notification_generator_heartbeat(
+                instance_id,
+                &RUN_ID,
+                config.notification_generator.instance_lease_seconds,
+            )
+            .await?
+        {
+            return Ok(false);
+        }
+        let instance_ids = network_postgres
+            .get_live_notification_generator_instance_ids(
+                config.notification_generator.instance_lease_seconds,
+            )
+            .await?;
+        // heartbeat has just been saved, should not happen unless the lease is too short
+        if !instance_ids.iter().any(|id| id == instance_id) {
+            return Err(anyhow::anyhow!("Instance {} is not live.", instance_id));
+        }
+        if let Some(effective_block_number) = network_postgres
+            .save_notification_generator_epoch(&instance_ids)
+            .await?
+        {
+            info!(
+                "New work partition of {} instance(s) {:?} from block #{}.",
+                instance_ids.len(),
+                instance_ids,
+                effective_block_number,
+            );
+        }
+        Ok(true)
+    }
+
+    /// Joins the work partition. A process with the same instance id that has stopped without
+    /// a chance to be dropped from the partitioning (e.g. the previous run of this instance)
+    /// is waited out for a lease period, and the join fails if the instance id is still in use.
+    pub(crate) async fn join_work_partition(
+        config: &Config,
+        network_postgres: &PostgreSQLNetworkStorage,
+    ) -> anyhow::Result<()> {
+        if NotificationGenerator::update_work_partition(config, network_postgres).await? {
+            return Ok(());
+        }
+        warn!(
+            "Instance id {} is in use by another process. Wait for its lease to expire.",
+            get_instance_id(),
+        );
+        tokio::time::sleep(std::time::Duration::from_secs(
+            config.notification_generator.instance_lease_seconds,
+        ))
+        .await;
+        if NotificationGenerator::update_work_partition(config, network_postgres).await? {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!(
+                "Instance id {} is in use by another live generator instance. Instance ids have to be unique.",
+                get_instance_id(),
+            ))
+        }
+    }
+
+    /// Keeps the heartbeat of this instance and the partition up to date.
+    pub(crate) async fn maintain_work_partition(
+        config: &'static Config,
+        network_postgres: PostgreSQLNetworkStorage,
+    ) {
+        loop {
+            tokio::time::sleep(std::time::Duration::from_secs(
+                config.notification_generator.instance_heartbeat_seconds,
+            ))
+            .await;
+            match NotificationGenerator::update_work_partition(config, &network_postgres).await {
+                Ok(true) => (),
+                Ok(false) => {
+                    error!(
+                        "Instance id {} has been taken over by another process. Exit.",
+                        get_instance_id(),
+                    );
+                    std::process::exit(1);
+                }
+                Err(error) => error!("Error while updating the work partition: {:?}", error),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkPartition;
+    use std::collections::BTreeMap;
+
+    fn get_instance_ids(instance_ids: &[&str]) -> Vec<String> {
+        instance_ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    /// Asserts that each user is included by exactly one of the instances.
+    fn assert_users_partitioned(partitions: &[WorkPartition]) {
+        for user_id in 0..100 {
+            assert_eq!(
+                partitions
+                    .iter()
+                    .filter(|partition| partition.includes_user(user_id))
+                    .count(),
+                1,
+                "User {} is not in exactly one partition.",
+                user_id
+            );
+        }
+    }
+
+    #[test]
+    fn test_includes_user() {
+        let instance_ids = get_instance_ids(&["a", "b", "c"]);
+        let partitions: Vec<WorkPartition> = ["a", "b", "c"]
+            .iter()
+            .map(|instance_id| WorkPartition::new(&instance_ids, instance_id))
+            .collect();
+        assert_users_partitioned(&partitions);
+        assert!(partitions[0].includes_user(3));
+        assert!(partitions[1].includes_user(4));
+        assert!(partitions[2].includes_user(5));
+        // not in the partition
+        let partition = WorkPartition::new(&instance_ids, "d");
+        assert!((0..100).all(|user_id| !partition.includes_user(user_id)));
+    }
+
+    #[test]
+    fn test_leader() {
+        let instance_ids = get_instance_ids(&["a", "b"]);
+        assert!(WorkPartition::new(&instance_ids, "a").is_leader());
+        assert!(!WorkPartition::new(&instance_ids, "b").is_leader());
+        assert!(!WorkPartition::new(&instance_ids, "c").is_leader());
+        assert!(!WorkPartition::new(&[], "a").is_leader());
+    }
+
+    #[test]
+    fn test_partition_of_block() {
+        let mut epochs = BTreeMap::new();
+        epochs.insert(10, get_instance_ids(&["a"]));
+        epochs.insert(20, get_instance_ids(&["a", "b"]));
+        epochs.insert(30, get_instance_ids(&["b"]));
+        // no epoch before block #10
+        for instance_id in ["a", "b"] {
+            let partition = WorkPartition::of_block(&epochs, 9, instance_id);
+            assert!(!partition.is_leader());
+            assert!((0..100).all(|user_id| !partition.includes_user(user_id)));
+        }
+        for block_number in 10..40 {
+            let partition_a = WorkPartition::of_block(&epochs, block_number, "a");
+            let partition_b = WorkPartition::of_block(&epochs, block_number, "b");
+            // the instances agree on the partition of each block
+            assert_users_partitioned(&[partition_a, partition_b]);
+            assert!(partition_a.is_leader() ^ partition_b.is_leader());
+        }
+        assert_eq!(WorkPartition::of_block(&epochs, 19, "a").instance_count, 1);
+        assert_eq!(WorkPartition::of_block(&epochs, 20, "a").instance_count, 2);
+        assert!(WorkPartition::of_block(&epochs, 29, "a").is_leader());
+        assert!(WorkPartition::of_block(&epochs, 30, "b").is_leader());
+        assert_eq!(
+            WorkPartition::of_block(&epochs, 30, "a").maybe_instance_index,
+            None
+        );
+    }
+}
@@ -1,6 +1,7 @@
 //! Contains the logic to process new blocks' events and extrinsics and persist notifications
 //! to be later sent by `subvt-notification-sender`.
 
+use crate::partition::get_instance_id;
 use crate::NotificationGenerator;
 use async_lock::Mutex;
 use log::{error, info};
@@ -152,6 +153,7 @@ impl NotificationGenerator {
         block_number: u64,
     ) -> anyhow::Result<()> {
         info!("Process block #{}.", block_number);
+        NotificationGenerator::reserve_work_partition(network_postgres, block_number).await?;
         let block = match network_postgres.get_block_by_number(block_number).await? {
             Some(block) => block,
             None => {
@@ -192,7 +194,7 @@ impl NotificationGenerator {
         .await?;
 
         network_postgres
-            .save_notification_generator_state(&block.hash, block_number)
+            .save_notification_generator_state(get_instance_id(), &block.hash, block_number)
             .await
     }
 
@@ -207,7 +209,7 @@ impl NotificationGenerator {
         );
         let maybe_last_processed_block_number_mutex = Arc::new(Mutex::new(
             network_postgres
-                .get_notification_generator_state()
+                .get_notification_generator_state(get_instance_id())
                 .await?
                 .map(|state| state.1),
         ));
@@ -1,22 +1,30 @@
-//! Checks validator changes for notifications. Validator list in Redis gets updated by
-//! `subvt-validator-list-updater`, and the update is notified using the Redis PUBLISH function.
-//! Keeps a copy of the validator list in heap memory (vector) to track changes.
+//! Checks validator changes for notifications. Validator list in the real-time store gets
+//! updated by `subvt-validator-list-updater`, and the update is published on the finalized block
+//! number channel of the store.
+//! Keeps a copy of the validator list in heap memory (vector) to track changes. App events are
+//! persisted only by the leader instance.
 
+use crate::partition::{get_instance_id, get_work_partition};
+use crate::schedule::SchedulePeriod;
 use crate::NotificationGenerator;
 use anyhow::Context;
 use chrono::Utc;
 use log::{debug, error, info, warn};
-use redis::Connection;
 use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
-use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore};
 use subvt_substrate_client::SubstrateClient;
-use subvt_types::app::app_event::{OneKVRankChange, OneKVValidityChange};
+use subvt_types::app::app_event::{
+    CommissionChangeAnnounced, ControllerLowBalance, NominatorRewardBelowExpectation,
+    NominatorStakeNotRewarded, OneKVRankChange, OneKVValidityChange, PrimarySlotsMissed,
+    ReferendumVoteMissing, ZeroRewardPoints,
+};
+use subvt_types::app::event::DemocracyStartedEvent;
 use subvt_types::substrate::Era;
 use subvt_types::{
     app::app_event,
@@ -26,9 +34,16 @@ use subvt_types::{
     subvt::ValidatorDetails,
 };
 
+/// Gets the value of a key that is expected to exist in the real-time store.
+fn get_realtime_value(realtime_store: &dyn RealtimeStore, key: &str) -> anyhow::Result<String> {
+    realtime_store
+        .get(key)?
+        .ok_or_else(|| anyhow::anyhow!("Key {} is missing in the real-time store.", key))
+}
+
 /// Does the initial population of the cached validator map.
 fn populate_validator_map(
-    connection: &mut Connection,
+    realtime_store: &dyn RealtimeStore,
     prefix: &str,
     active_validator_account_ids: &HashSet<String>,
     all_validator_account_ids: &HashSet<String>,
@@ -49,17 +64,17 @@ fn populate_validator_map(
             )
         })
         .collect();
-    let validator_json_strings: Vec<String> = redis::cmd("MGET")
-        .arg(&all_keys)
-        .query(connection)
-        .context("Can't read validator json string from Redis.")
-        .unwrap();
+    let validator_json_strings = realtime_store
+        .get_multiple(&all_keys)
+        .context("Can't read validator JSON strings from the real-time store.")?;
     debug!(
         "Got JSON string for {} validators.",
         validator_json_strings.len()
     );
-    for validator_json_string in validator_json_strings {
-        let validator: ValidatorDetails = serde_json::from_str(&validator_json_string).unwrap();
+    for (key, validator_json_string) in all_keys.iter().zip(validator_json_strings) {
+        let validator_json_string = validator_json_string
+            .ok_or_else(|| anyhow::anyhow!("Key {} is missing in the real-time store.", key))?;
+        let validator: ValidatorDetails = serde_json::from_str(&validator_json_string)?;
         validator_map.insert(validator.account.id.to_string(), validator);
     }
     info!(
@@ -76,12 +91,13 @@ impl NotificationGenerator {
         config: &Config,
         (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
         substrate_client: &Arc<SubstrateClient>,
-        redis_connection: &mut Connection,
-        redis_prefix: &str,
+        realtime_store: &dyn RealtimeStore,
+        validator_prefix: &str,
         finalized_block_number: u64,
         last: &ValidatorDetails,
     ) -> anyhow::Result<Option<ValidatorDetails>> {
         let account_id = &last.account.id;
+        let is_leader = get_work_partition(finalized_block_number).is_leader();
         // last hash
         let hash = {
             let mut hasher = DefaultHasher::new();
@@ -89,19 +105,17 @@ impl NotificationGenerator {
             hasher.finish()
         };
         // current hash
-        let db_hash: u64 = redis::cmd("GET")
-            .arg(format!("{}:hash", redis_prefix))
-            .query(redis_connection)
-            .context("Can't read validator hash from Redis.")?;
+        let db_hash: u64 =
+            get_realtime_value(realtime_store, &format!("{}:hash", validator_prefix))
+                .context("Can't read validator hash from the real-time store.")?
+                .parse()?;
         // return if there's no change in the validator's details
         if hash == db_hash {
             return Ok(None);
         }
         let current = {
-            let db_validator_json: String = redis::cmd("GET")
-                .arg(redis_prefix)
-                .query(redis_connection)
-                .context("Can't read validator JSON from Redis.")?;
+            let db_validator_json = get_realtime_value(realtime_store, validator_prefix)
+                .context("Can't read validator JSON from the real-time store.")?;
             serde_json::from_str::<ValidatorDetails>(&db_validator_json)?
         };
 
@@ -171,7 +185,9 @@ impl NotificationGenerator {
                 )
                 .await?;
             }
-            network_postgres.save_new_nomination_event(&event).await?;
+            if is_leader {
+                network_postgres.save_new_nomination_event(&event).await?;
+            }
         }
         // lost nominations
         for lost_nominator_id in lost_nominator_ids {
@@ -217,7 +233,9 @@ impl NotificationGenerator {
                 )
                 .await?;
             }
-            network_postgres.save_lost_nomination_event(&event).await?;
+            if is_leader {
+                network_postgres.save_lost_nomination_event(&event).await?;
+            }
         }
         // nomination amount changes
         for renominator_id in renominator_ids {
@@ -260,9 +278,60 @@ impl NotificationGenerator {
                     Some(&event),
                 )
                 .await?;
-                network_postgres
-                    .save_nomination_amount_change_event(&event)
+                if is_leader {
+                    network_postgres
+                        .save_nomination_amount_change_event(&event)
+                        .await?;
+                }
+            }
+            // unbonding
+            let unbonding_amount = current_nomination
+                .stake
+                .get_unbonding_amount_since(&prev_nomination.stake);
+            if unbonding_amount > 0 {
+                debug!(
+                    "Nominator unbonding for {} :: {} :: {}",
+                    account_id.to_ss58_check(),
+                    renominator_id.to_ss58_check(),
+                    unbonding_amount,
+                );
+                let rules = app_postgres
+                    .get_notification_rules_for_validator(
+                        &NotificationTypeCode::ChainValidatorNominatorUnbonding.to_string(),
+                        config.substrate.network_id,
+                        &current.account.id,
+                    )
+                    .await?;
+                let last_chunk = current_nomination.stake.unlocking.last();
+                let event = app_event::NominatorUnbonding {
+                    validator_account_id: current.account.id.clone(),
+                    nominator_stash_account_id: current_nomination.stash_account_id.clone(),
+                    unbonding_amount,
+                    active_amount: current_nomination.stake.active_amount,
+                    unlock_era_index: last_chunk.map(|chunk| chunk.era_index).unwrap_or(0),
+                    estimated_unlock_timestamp: last_chunk
+                        .and_then(|chunk| chunk.estimated_unlock_timestamp),
+                    discovered_block_number: finalized_block_number,
+                };
+                for rule in rules {
+                    if let Some(min_param) = rule.parameters.get(0) {
+                        if let Ok(min_amount) = min_param.value.parse::<Balance>() {
+                            if unbonding_amount < min_amount {
+                                continue;
+                            }
+                        }
+                    }
+                    NotificationGenerator::generate_notifications(
+                        config,
+                        app_postgres,
+                        substrate_client,
+                        &[rule],
+                        finalized_block_number,
+                        &current.account.id,
+                        Some(&event),
+                    )
                     .await?;
+                }
             }
         }
         // check (in)active next session
@@ -289,9 +358,11 @@ impl NotificationGenerator {
                     None::<&()>,
                 )
                 .await?;
-                network_postgres
-                    .save_active_next_session_event(&current.account.id, finalized_block_number)
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_active_next_session_event(&current.account.id, finalized_block_number)
+                        .await?;
+                }
             } else {
                 debug!(
                     "Inactive next session: {}",
@@ -314,9 +385,14 @@ impl NotificationGenerator {
                     None::<&()>,
                 )
                 .await?;
-                network_postgres
-                    .save_inactive_next_session_event(&current.account.id, finalized_block_number)
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_inactive_next_session_event(
+                            &current.account.id,
+                            finalized_block_number,
+                        )
+                        .await?;
+                }
             }
         }
         // check (in)active now
@@ -340,9 +416,11 @@ impl NotificationGenerator {
                     None::<&()>,
                 )
                 .await?;
-                network_postgres
-                    .save_active_event(&current.account.id, finalized_block_number)
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_active_event(&current.account.id, finalized_block_number)
+                        .await?;
+                }
             } else {
                 debug!("Now inactive: {}", current.account.id.to_ss58_check());
                 let rules = app_postgres
@@ -362,13 +440,181 @@ impl NotificationGenerator {
                     None::<&()>,
                 )
                 .await?;
-                network_postgres
-                    .save_inactive_event(&current.account.id, finalized_block_number)
+                if is_leader {
+                    network_postgres
+                        .save_inactive_event(&current.account.id, finalized_block_number)
+                        .await?;
+                }
+            }
+        }
+        // check missed primary slots, once per session
+        if let Some(missed_primary_slots) = &current.missed_primary_slots {
+            let is_new_session = last
+                .missed_primary_slots
+                .as_ref()
+                .map(|last_missed_primary_slots| {
+                    last_missed_primary_slots.session_index != missed_primary_slots.session_index
+                })
+                .unwrap_or(true);
+            if missed_primary_slots.is_likely_offline && is_new_session {
+                debug!(
+                    "{} has likely been offline in session #{}. Expected {} primary slots, authored {} blocks.",
+                    current.account.id.to_ss58_check(),
+                    missed_primary_slots.session_index,
+                    missed_primary_slots.expected_primary_slot_count_thousandths as f64 / 1000.0,
+                    missed_primary_slots.primary_block_count,
+                );
+                let rules = app_postgres
+                    .get_notification_rules_for_validator(
+                        &NotificationTypeCode::ChainValidatorMissedPrimarySlots.to_string(),
+                        config.substrate.network_id,
+                        &current.account.id,
+                    )
+                    .await?;
+                NotificationGenerator::generate_notifications(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    &rules,
+                    finalized_block_number,
+                    &current.account.id,
+                    Some(&PrimarySlotsMissed {
+                        validator_account_id: current.account.id.clone(),
+                        session_index: missed_primary_slots.session_index,
+                        elapsed_slot_count: missed_primary_slots.elapsed_slot_count,
+                        expected_primary_slot_count_thousandths: missed_primary_slots
+                            .expected_primary_slot_count_thousandths,
+                        primary_block_count: missed_primary_slots.primary_block_count,
+                        missed_primary_slot_count: missed_primary_slots.missed_primary_slot_count,
+                        bad_luck_probability_per_billion: missed_primary_slots
+                            .bad_luck_probability_per_billion,
+                        discovered_block_number: finalized_block_number,
+                    }),
+                )
+                .await?;
+            }
+        }
+        // check zero reward points, once per session
+        if let Some(session_reward_points) = &current.last_session_reward_points {
+            let is_new_session = last
+                .last_session_reward_points
+                .as_ref()
+                .map(|last_session_reward_points| {
+                    last_session_reward_points.session_index != session_reward_points.session_index
+                })
+                .unwrap_or(true);
+            if session_reward_points.zero_point_session_count > 0 && is_new_session {
+                let rules = app_postgres
+                    .get_notification_rules_for_validator(
+                        &NotificationTypeCode::ChainValidatorZeroRewardPoints.to_string(),
+                        config.substrate.network_id,
+                        &current.account.id,
+                    )
+                    .await?;
+                let event = ZeroRewardPoints {
+                    validator_account_id: current.account.id.clone(),
+                    session_index: session_reward_points.session_index,
+                    zero_point_session_count: session_reward_points.zero_point_session_count,
+                    discovered_block_number: finalized_block_number,
+                };
+                for rule in rules {
+                    // notify once, when the zero-point sessions reach the count of the rule
+                    let session_count = rule
+                        .parameters
+                        .get(0)
+                        .and_then(|param| param.value.parse::<u32>().ok())
+                        .unwrap_or(2);
+                    if session_reward_points.zero_point_session_count != session_count {
+                        continue;
+                    }
+                    debug!(
+                        "{} has not earned any reward points in {} sessions until session #{}.",
+                        current.account.id.to_ss58_check(),
+                        session_count,
+                        session_reward_points.session_index,
+                    );
+                    NotificationGenerator::generate_notifications(
+                        config,
+                        app_postgres,
+                        substrate_client,
+                        &[rule],
+                        finalized_block_number,
+                        &current.account.id,
+                        Some(&event),
+                    )
+                    .await?;
+                }
+            }
+        }
+        // check controller balance
+        if current.risk_flags.controller_low_balance && !last.risk_flags.controller_low_balance {
+            debug!(
+                "Controller {} of {} has low balance: {} free, {} spendable on fees.",
+                current.controller_account_id.to_ss58_check(),
+                current.account.id.to_ss58_check(),
+                current.controller_free_balance,
+                current.controller_fee_spendable_balance,
+            );
+            let rules = app_postgres
+                .get_notification_rules_for_validator(
+                    &NotificationTypeCode::ChainValidatorControllerLowBalance.to_string(),
+                    config.substrate.network_id,
+                    &current.account.id,
+                )
+                .await?;
+            NotificationGenerator::generate_notifications(
+                config,
+                app_postgres,
+                substrate_client,
+                &rules,
+                finalized_block_number,
+                &current.account.id,
+                Some(&ControllerLowBalance {
+                    validator_account_id: current.account.id.clone(),
+                    controller_account_id: current.controller_account_id.clone(),
+                    free_balance: current.controller_free_balance,
+                    fee_spendable_balance: current.controller_fee_spendable_balance,
+                    discovered_block_number: finalized_block_number,
+                }),
+            )
+            .await?;
+        }
+        // check commission change announcement
+        if let Some(announcement) = &current.announced_commission_change {
+            if last.announced_commission_change.as_ref() != Some(announcement) {
+                debug!(
+                    "{} announced commission change from {} to {} per billion.",
+                    current.account.id.to_ss58_check(),
+                    current.preferences.commission_per_billion,
+                    announcement.commission_per_billion,
+                );
+                let rules = app_postgres
+                    .get_notification_rules_for_validator(
+                        &NotificationTypeCode::ChainValidatorCommissionChangeAnnounced.to_string(),
+                        config.substrate.network_id,
+                        &current.account.id,
+                    )
                     .await?;
+                NotificationGenerator::generate_notifications(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    &rules,
+                    finalized_block_number,
+                    &current.account.id,
+                    Some(&CommissionChangeAnnounced {
+                        validator_account_id: current.account.id.clone(),
+                        current_commission_per_billion: current.preferences.commission_per_billion,
+                        announcement: announcement.clone(),
+                        discovered_block_number: finalized_block_number,
+                    }),
+                )
+                .await?;
             }
         }
         // check 1kv rank and validity
-        if current.onekv_candidate_record_id.is_some()
+        if config.is_onekv_enabled()
+            && current.onekv_candidate_record_id.is_some()
             && (current.onekv_candidate_record_id == last.onekv_candidate_record_id)
         {
             if current.onekv_rank != last.onekv_rank {
@@ -399,13 +645,15 @@ impl NotificationGenerator {
                     }),
                 )
                 .await?;
-                network_postgres
-                    .save_onekv_rank_change_event(
-                        &current.account.id,
-                        last.onekv_rank.unwrap(),
-                        current.onekv_rank.unwrap(),
-                    )
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_onekv_rank_change_event(
+                            &current.account.id,
+                            last.onekv_rank.unwrap(),
+                            current.onekv_rank.unwrap(),
+                        )
+                        .await?;
+                }
             }
             // check validity
             if current.onekv_is_valid != last.onekv_is_valid {
@@ -439,15 +687,254 @@ impl NotificationGenerator {
                     }),
                 )
                 .await?;
-                network_postgres
-                    .save_onekv_validity_change_event(
-                        &current.account.id,
-                        current.onekv_is_valid.unwrap(),
+                if is_leader {
+                    network_postgres
+                        .save_onekv_validity_change_event(
+                            &current.account.id,
+                            current.onekv_is_valid.unwrap(),
+                        )
+                        .await?;
+                }
+            }
+        }
+        Ok(Some(current))
+    }
+
+    /// Evaluates the periodic check of the notification type, see the `schedule` module.
+    /// Returns `false` if the check is not ready to be evaluated in the current period yet,
+    /// so that it gets evaluated again with the next update.
+    async fn evaluate_periodic_check(
+        config: &Config,
+        (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
+        substrate_client: &Arc<SubstrateClient>,
+        validator_map: &HashMap<String, ValidatorDetails>,
+        active_era: &Era,
+        finalized_block_number: u64,
+        notification_type_code: &str,
+    ) -> anyhow::Result<bool> {
+        match notification_type_code {
+            "chain_validator_unclaimed_payout" => {
+                NotificationGenerator::check_unclaimed_payouts(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    validator_map,
+                    active_era,
+                    finalized_block_number,
+                )
+                .await
+            }
+            "chain_nominator_reward_below_expectation" => {
+                NotificationGenerator::check_nominator_rewards(
+                    config,
+                    (app_postgres, network_postgres),
+                    substrate_client,
+                    active_era.index,
+                    finalized_block_number,
+                )
+                .await?;
+                Ok(true)
+            }
+            "chain_nominator_stake_not_rewarded" => {
+                NotificationGenerator::check_nominator_exposures(
+                    config,
+                    (app_postgres, network_postgres),
+                    substrate_client,
+                    active_era.index,
+                    finalized_block_number,
+                )
+                .await?;
+                Ok(true)
+            }
+            "chain_validator_referendum_vote_missing" => {
+                NotificationGenerator::check_referendum_votes(
+                    config,
+                    (app_postgres, network_postgres),
+                    substrate_client,
+                    validator_map,
+                    finalized_block_number,
+                )
+                .await?;
+                Ok(true)
+            }
+            _ => {
+                warn!(
+                    "There is no periodic check for the notification type {}.",
+                    notification_type_code
+                );
+                Ok(true)
+            }
+        }
+    }
+
+    /// Notifies the validators that have unclaimed payouts, once the active era has been
+    /// running for `unclaimed_payout_check_delay_hours` hours.
+    async fn check_unclaimed_payouts(
+        config: &Config,
+        app_postgres: &PostgreSQLAppStorage,
+        substrate_client: &Arc<SubstrateClient>,
+        validator_map: &HashMap<String, ValidatorDetails>,
+        active_era: &Era,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<bool> {
+        let era_elapsed = Utc::now() - active_era.get_start_date_time();
+        if era_elapsed.num_hours()
+            < config
+                .notification_generator
+                .unclaimed_payout_check_delay_hours as i64
+        {
+            return Ok(false);
+        }
+        debug!("Process era #{} for unclaimed payouts.", active_era.index);
+        for validator in validator_map.values() {
+            if !validator.unclaimed_era_indices.is_empty() {
+                let rules = app_postgres
+                    .get_notification_rules_for_validator(
+                        &NotificationTypeCode::ChainValidatorUnclaimedPayout.to_string(),
+                        config.substrate.network_id,
+                        &validator.account.id,
                     )
                     .await?;
+                // generate notifications
+                NotificationGenerator::generate_notifications_for_all_users(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    &rules,
+                    finalized_block_number,
+                    &validator.account.id,
+                    Some(&validator.unclaimed_era_indices),
+                )
+                .await?;
             }
         }
-        Ok(Some(current))
+        Ok(true)
+    }
+
+    /// Checks the ongoing referendums that end within the configured window, and persists
+    /// notifications for the validators that haven't voted from either the stash or the
+    /// controller account. Each referendum gets claimed and checked by a single instance.
+    async fn check_referendum_votes(
+        config: &Config,
+        (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
+        substrate_client: &Arc<SubstrateClient>,
+        validator_map: &HashMap<String, ValidatorDetails>,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        let window_blocks = config
+            .notification_generator
+            .referendum_vote_missing_window_hours as u64
+            * 60
+            * 60
+            * 1000
+            / substrate_client
+                .metadata
+                .constants
+                .expected_block_time_millis;
+        let referendums = network_postgres
+            .get_ongoing_referendums_ending_between(
+                finalized_block_number,
+                finalized_block_number + window_blocks,
+            )
+            .await?;
+        for referendum in referendums {
+            if !network_postgres
+                .claim_notification_generator_referendum(
+                    get_instance_id(),
+                    referendum.referendum_index,
+                    config.notification_generator.instance_lease_seconds,
+                )
+                .await?
+            {
+                continue;
+            }
+            debug!(
+                "Check validator votes for referendum #{}.",
+                referendum.referendum_index
+            );
+            match NotificationGenerator::check_referendum_validator_votes(
+                config,
+                (app_postgres, network_postgres),
+                substrate_client,
+                validator_map,
+                &referendum,
+                finalized_block_number,
+            )
+            .await
+            {
+                Ok(()) => {
+                    network_postgres
+                        .complete_notification_generator_referendum(
+                            get_instance_id(),
+                            referendum.referendum_index,
+                        )
+                        .await?
+                }
+                Err(error) => {
+                    network_postgres
+                        .release_notification_generator_referendum(
+                            get_instance_id(),
+                            referendum.referendum_index,
+                        )
+                        .await?;
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Persists notifications for the validators that haven't voted on the referendum.
+    async fn check_referendum_validator_votes(
+        config: &Config,
+        (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
+        substrate_client: &Arc<SubstrateClient>,
+        validator_map: &HashMap<String, ValidatorDetails>,
+        referendum: &DemocracyStartedEvent,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        for validator in validator_map.values() {
+            let rules = app_postgres
+                .get_notification_rules_for_validator(
+                    &NotificationTypeCode::ChainValidatorReferendumVoteMissing.to_string(),
+                    config.substrate.network_id,
+                    &validator.account.id,
+                )
+                .await?;
+            if rules.is_empty() {
+                continue;
+            }
+            if network_postgres
+                .democracy_vote_exists(
+                    referendum.referendum_index,
+                    &[&validator.account.id, &validator.controller_account_id],
+                )
+                .await?
+            {
+                continue;
+            }
+            debug!(
+                "{} has not voted on referendum #{}.",
+                validator.account.id.to_ss58_check(),
+                referendum.referendum_index,
+            );
+            NotificationGenerator::generate_notifications_for_all_users(
+                config,
+                app_postgres,
+                substrate_client,
+                &rules,
+                finalized_block_number,
+                &validator.account.id,
+                Some(&ReferendumVoteMissing {
+                    validator_account_id: validator.account.id.clone(),
+                    referendum_index: referendum.referendum_index,
+                    end_block_number: referendum.end_block_number,
+                    discovered_block_number: finalized_block_number,
+                }),
+            )
+            .await?;
+        }
+        Ok(())
     }
 
     /// Called after each validator list update PUBLISH event.
@@ -455,25 +942,26 @@ impl NotificationGenerator {
         config: &Config,
         (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
         substrate_client: &Arc<SubstrateClient>,
-        redis_connection: &mut Connection,
+        realtime_store: &dyn RealtimeStore,
         validator_map: &mut HashMap<String, ValidatorDetails>,
         finalized_block_number: u64,
-        last_active_era_index: &AtomicU32,
     ) -> anyhow::Result<()> {
         info!(
             "Process new update from validator list updater. Block #{}.",
             finalized_block_number
         );
+        let is_leader =
+            NotificationGenerator::reserve_work_partition(network_postgres, finalized_block_number)
+                .await?
+                .is_leader();
         let prefix = format!(
             "subvt:{}:validators:{}",
             config.substrate.chain, finalized_block_number
         );
-        let active_validator_account_ids: HashSet<String> = redis::cmd("SMEMBERS")
-            .arg(format!("{}:active:account_id_set", prefix))
-            .query(redis_connection)?;
-        let inactive_validator_account_ids: HashSet<String> = redis::cmd("SMEMBERS")
-            .arg(format!("{}:inactive:account_id_set", prefix))
-            .query(redis_connection)?;
+        let active_validator_account_ids =
+            realtime_store.get_set_members(&format!("{}:active:account_id_set", prefix))?;
+        let inactive_validator_account_ids =
+            realtime_store.get_set_members(&format!("{}:inactive:account_id_set", prefix))?;
         let all_validator_account_ids: HashSet<String> = active_validator_account_ids
             .union(&inactive_validator_account_ids)
             .cloned()
@@ -482,7 +970,7 @@ impl NotificationGenerator {
             // first run
             info!("Validator map is empty. Populate.");
             populate_validator_map(
-                redis_connection,
+                realtime_store,
                 &prefix,
                 &active_validator_account_ids,
                 &all_validator_account_ids,
@@ -497,9 +985,11 @@ impl NotificationGenerator {
             for added_id in &added_validator_ids {
                 let account_id = AccountId::from_str(added_id)?;
                 info!("Persist new validator: {}", account_id.to_ss58_check());
-                network_postgres
-                    .save_new_validator_event(&account_id, finalized_block_number)
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_new_validator_event(&account_id, finalized_block_number)
+                        .await?;
+                }
                 // add to validator map
                 let validator_prefix = format!(
                     "{}:{}:validator:{}",
@@ -512,10 +1002,9 @@ impl NotificationGenerator {
                     added_id
                 );
                 let validator = {
-                    let db_validator_json: String = redis::cmd("GET")
-                        .arg(validator_prefix)
-                        .query(redis_connection)
-                        .context("Can't read validator JSON from Redis.")?;
+                    let db_validator_json =
+                        get_realtime_value(realtime_store, &validator_prefix)
+                            .context("Can't read validator JSON from the real-time store.")?;
                     serde_json::from_str::<ValidatorDetails>(&db_validator_json)?
                 };
                 validator_map.insert(added_id.clone(), validator);
@@ -523,9 +1012,11 @@ impl NotificationGenerator {
             for removed_id in &removed_validator_ids {
                 let account_id = AccountId::from_str(removed_id)?;
                 info!("Remove validator: {}", account_id.to_ss58_check());
-                network_postgres
-                    .save_removed_validator_event(&account_id, finalized_block_number)
-                    .await?;
+                if is_leader {
+                    network_postgres
+                        .save_removed_validator_event(&account_id, finalized_block_number)
+                        .await?;
+                }
                 validator_map.remove(removed_id);
             }
             debug!("Checking for changes in existing validators.");
@@ -544,7 +1035,7 @@ impl NotificationGenerator {
                     config,
                     (app_postgres, network_postgres),
                     substrate_client,
-                    redis_connection,
+                    realtime_store,
                     &validator_prefix,
                     finalized_block_number,
                     validator_map.get(validator_id).unwrap(),
@@ -554,54 +1045,208 @@ impl NotificationGenerator {
                     validator_map.insert(validator_id.clone(), updated);
                 }
             }
-            // check era change & unclaimed payouts
-            let db_active_era_json: String = redis::cmd("GET")
-                .arg(format!("{}:active_era", prefix))
-                .query(redis_connection)
-                .context("Can't read active era JSON from Redis.")?;
+            // periodic checks
+            let db_active_era_json =
+                get_realtime_value(realtime_store, &format!("{}:active_era", prefix))
+                    .context("Can't read active era JSON from the real-time store.")?;
             let active_era: Era = serde_json::from_str(&db_active_era_json)?;
-            let era_start = active_era.get_start_date_time();
-            let era_elapsed = Utc::now() - era_start;
-            if era_elapsed.num_hours()
-                >= config
-                    .notification_generator
-                    .unclaimed_payout_check_delay_hours as i64
-                && last_active_era_index.load(Ordering::SeqCst) != active_era.index
-            {
-                if !network_postgres
-                    .notification_generator_has_processed_era(active_era.index)
+            let period = SchedulePeriod::new(
+                config,
+                substrate_client,
+                finalized_block_number,
+                active_era.index,
+            )
+            .await?;
+            for (notification_type_code, cadence) in
+                NotificationGenerator::claim_due_periodic_checks(config, network_postgres, &period)
                     .await?
-                {
-                    debug!("Process era #{} for unclaimed payouts.", active_era.index);
-                    for validator in validator_map.values() {
-                        if !validator.unclaimed_era_indices.is_empty() {
-                            let rules = app_postgres
-                                .get_notification_rules_for_validator(
-                                    &NotificationTypeCode::ChainValidatorUnclaimedPayout
-                                        .to_string(),
-                                    config.substrate.network_id,
-                                    &validator.account.id,
-                                )
-                                .await?;
-                            // generate notifications
-                            NotificationGenerator::generate_notifications(
-                                config,
-                                app_postgres,
-                                substrate_client,
-                                &rules,
-                                finalized_block_number,
-                                &validator.account.id,
-                                Some(&validator.unclaimed_era_indices),
-                            )
-                            .await?;
-                        }
-                    }
-                    network_postgres
-                        .save_notification_generator_processed_era(active_era.index)
-                        .await?;
+            {
+                let result = NotificationGenerator::evaluate_periodic_check(
+                    config,
+                    (app_postgres, network_postgres),
+                    substrate_client,
+                    validator_map,
+                    &active_era,
+                    finalized_block_number,
+                    &notification_type_code,
+                )
+                .await;
+                NotificationGenerator::finish_periodic_check(
+                    network_postgres,
+                    &notification_type_code,
+                    cadence,
+                    &period,
+                    matches!(result, Ok(true)),
+                )
+                .await?;
+                result?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compares the rewards that the watched nominators have received for the era
+    /// `nominator_reward_check_era_delay` eras before the active era with their expected
+    /// rewards, to catch missed payouts and unexpected commission changes.
+    async fn check_nominator_rewards(
+        config: &Config,
+        (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
+        substrate_client: &Arc<SubstrateClient>,
+        active_era_index: u32,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        let era_index = match active_era_index.checked_sub(
+            config
+                .notification_generator
+                .nominator_reward_check_era_delay
+                .max(1),
+        ) {
+            Some(era_index) => era_index,
+            None => return Ok(()),
+        };
+        debug!("Check watched nominator rewards for era #{}.", era_index);
+        for nominator_account_id in app_postgres
+            .get_watched_nominator_account_ids(config.substrate.network_id)
+            .await?
+        {
+            let era_reward = match network_postgres
+                .get_nominator_era_reward(&nominator_account_id, era_index)
+                .await?
+            {
+                Some(era_reward) => era_reward,
+                None => continue,
+            };
+            if era_reward.expected_reward == 0
+                || era_reward.received_reward >= era_reward.expected_reward
+            {
+                continue;
+            }
+            let delta_percent = (era_reward.expected_reward - era_reward.received_reward) as f64
+                / era_reward.expected_reward as f64
+                * 100.0;
+            let rules = app_postgres
+                .get_notification_rules_for_nominator(
+                    &NotificationTypeCode::ChainNominatorRewardBelowExpectation.to_string(),
+                    config.substrate.network_id,
+                    &nominator_account_id,
+                )
+                .await?;
+            let event = NominatorRewardBelowExpectation {
+                nominator_account_id: nominator_account_id.clone(),
+                era_index,
+                expected_reward: era_reward.expected_reward,
+                received_reward: era_reward.received_reward,
+                unpaid_validator_account_ids: era_reward.unpaid_validator_account_ids.clone(),
+                discovered_block_number: finalized_block_number,
+            };
+            for rule in rules {
+                let min_delta_percent = rule
+                    .parameters
+                    .get(0)
+                    .and_then(|param| param.value.parse::<u32>().ok())
+                    .unwrap_or(10);
+                if delta_percent < min_delta_percent as f64 {
+                    continue;
                 }
-                // and add the era index to processed era indices
-                last_active_era_index.store(active_era.index, Ordering::SeqCst);
+                debug!(
+                    "{} received {} for era #{}, expected {}.",
+                    nominator_account_id.to_ss58_check(),
+                    era_reward.received_reward,
+                    era_index,
+                    era_reward.expected_reward,
+                );
+                NotificationGenerator::generate_notifications_for_all_users(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    &[rule],
+                    finalized_block_number,
+                    &nominator_account_id,
+                    Some(&event),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks whether the exposures of the watched nominators in the active era are outside
+    /// the rewarded exposures of their oversubscribed validators.
+    async fn check_nominator_exposures(
+        config: &Config,
+        (app_postgres, network_postgres): (&PostgreSQLAppStorage, &PostgreSQLNetworkStorage),
+        substrate_client: &Arc<SubstrateClient>,
+        active_era_index: u32,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        debug!(
+            "Check watched nominator exposures for era #{}.",
+            active_era_index
+        );
+        let max_rewarded_nominator_count = match substrate_client
+            .get_constants()
+            .max_nominator_rewarded_per_validator
+        {
+            Some(max_rewarded_nominator_count) => max_rewarded_nominator_count,
+            None => {
+                debug!("All nominators get rewarded in the runtime with paged exposures.");
+                return Ok(());
+            }
+        };
+        for nominator_account_id in app_postgres
+            .get_watched_nominator_account_ids(config.substrate.network_id)
+            .await?
+        {
+            let exposures = network_postgres
+                .get_nominator_unrewarded_exposures(
+                    &nominator_account_id,
+                    active_era_index,
+                    max_rewarded_nominator_count,
+                )
+                .await?;
+            if exposures.is_empty() {
+                continue;
+            }
+            let rules = app_postgres
+                .get_notification_rules_for_nominator(
+                    &NotificationTypeCode::ChainNominatorStakeNotRewarded.to_string(),
+                    config.substrate.network_id,
+                    &nominator_account_id,
+                )
+                .await?;
+            if rules.is_empty() {
+                continue;
+            }
+            for exposure in exposures {
+                debug!(
+                    "{} stake {} on {} is not rewarded in era #{}, minimum rewarded stake is {}.",
+                    nominator_account_id.to_ss58_check(),
+                    exposure.stake,
+                    exposure.validator_account_id.to_ss58_check(),
+                    active_era_index,
+                    exposure.min_rewarded_stake,
+                );
+                let event = NominatorStakeNotRewarded {
+                    nominator_account_id: nominator_account_id.clone(),
+                    validator_account_id: exposure.validator_account_id,
+                    era_index: active_era_index,
+                    stake: exposure.stake,
+                    min_rewarded_stake: exposure.min_rewarded_stake,
+                    min_reentry_stake: exposure.min_rewarded_stake.saturating_add(1),
+                    max_rewarded_nominator_count,
+                    nominator_count: exposure.nominator_count,
+                    discovered_block_number: finalized_block_number,
+                };
+                NotificationGenerator::generate_notifications_for_all_users(
+                    config,
+                    app_postgres,
+                    substrate_client,
+                    &rules,
+                    finalized_block_number,
+                    &nominator_account_id,
+                    Some(&event),
+                )
+                .await?;
             }
         }
         Ok(())
@@ -620,18 +1265,10 @@ impl NotificationGenerator {
                 PostgreSQLNetworkStorage::new(config, config.get_network_postgres_url())
                     .await
                     .unwrap();
-            // intialize Redis connection
-            let redis_client = redis::Client::open(config.redis.url.as_str())
-                .context(format!(
-                    "Cannot connect to Redis at URL {}.",
-                    config.redis.url
-                ))
-                .unwrap();
-            let mut pub_sub_connection = redis_client.get_connection().unwrap();
-            let mut pub_sub = pub_sub_connection.as_pubsub();
-            let mut data_connection = redis_client.get_connection().unwrap();
-            let _ = pub_sub
-                .subscribe(format!(
+            // initialize the real-time store, keys are mapped to the configured key versions
+            let realtime_store = get_realtime_store(config).unwrap();
+            let subscription = realtime_store
+                .subscribe(&format!(
                     "subvt:{}:validators:publish:finalized_block_number",
                     config.substrate.chain
                 ))
@@ -640,18 +1277,12 @@ impl NotificationGenerator {
             let mut last_finalized_block_number = 0;
             // keep track of validators
             let mut validator_map: HashMap<String, ValidatorDetails> = HashMap::new();
-            let last_active_era_index = AtomicU32::new(0);
 
             let error: anyhow::Error = loop {
-                let message = pub_sub.get_message();
-                if let Err(error) = message {
-                    break error.into();
-                }
-                let payload = message.unwrap().get_payload();
-                if let Err(error) = payload {
-                    break error.into();
-                }
-                let finalized_block_number: u64 = payload.unwrap();
+                let finalized_block_number = match subscription.next_block_number() {
+                    Ok(finalized_block_number) => finalized_block_number,
+                    Err(error) => break error,
+                };
                 if last_finalized_block_number == finalized_block_number {
                     warn!(
                         "Skip duplicate finalized block #{}.",
@@ -663,10 +1294,9 @@ impl NotificationGenerator {
                     config,
                     (&app_postgres, &network_postgres),
                     &substrate_client,
-                    &mut data_connection,
+                    realtime_store.as_ref(),
                     &mut validator_map,
                     finalized_block_number,
-                    &last_active_era_index,
                 )
                 .await
                 {
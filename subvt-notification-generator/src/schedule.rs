@@ -0,0 +1,128 @@
+//! Unified scheduler of the periodic checks, i.e. the checks that evaluate the state of the
+//! network at intervals rather than following the changes in a validator. A periodic check is
+//! identified by its notification type code, and gets evaluated once in every period of the
+//! cadence configured for the type in `notification_generator.check_cadences`: every finalized
+//! block, session, era or clock hour. The periods are global: a generator instance claims the
+//! period of a check before evaluating it, and evaluates it for all users, so that the check
+//! gets evaluated once per period however many instances are running or restarted. The claim
+//! of an instance that stops before completing the evaluation expires with its lease, see the
+//! `partition` module. A new periodic check needs only its cadence configuration and its
+//! evaluation, see `evaluate_periodic_check` in the validator list processor.
+use crate::partition::get_instance_id;
+use crate::NotificationGenerator;
+use chrono::Utc;
+use std::sync::Arc;
+use subvt_config::{Config, NotificationCheckCadence};
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_substrate_client::SubstrateClient;
+
+/// Periods of the cadences at a finalized block.
+pub(crate) struct SchedulePeriod {
+    pub block_number: u64,
+    /// `None` if there is no check with the session cadence, so that it doesn't get fetched.
+    pub maybe_session_index: Option<u64>,
+    pub era_index: u32,
+    /// Clock hours since the Unix epoch.
+    pub hour_index: u64,
+}
+
+impl SchedulePeriod {
+    pub async fn new(
+        config: &Config,
+        substrate_client: &Arc<SubstrateClient>,
+        block_number: u64,
+        era_index: u32,
+    ) -> anyhow::Result<SchedulePeriod> {
+        let maybe_session_index = if config
+            .notification_generator
+            .check_cadences
+            .values()
+            .any(|cadence| *cadence == NotificationCheckCadence::Session)
+        {
+            let block_hash = substrate_client.get_block_hash(block_number).await?;
+            Some(
+                substrate_client
+                    .get_current_session_index(&block_hash)
+                    .await? as u64,
+            )
+        } else {
+            None
+        };
+        Ok(SchedulePeriod {
+            block_number,
+            maybe_session_index,
+            era_index,
+            hour_index: Utc::now().timestamp() as u64 / 3600,
+        })
+    }
+
+    fn get_index(&self, cadence: NotificationCheckCadence) -> anyhow::Result<u64> {
+        match cadence {
+            NotificationCheckCadence::Block => Ok(self.block_number),
+            NotificationCheckCadence::Session => self
+                .maybe_session_index
+                .ok_or_else(|| anyhow::anyhow!("Session index is not available.")),
+            NotificationCheckCadence::Era => Ok(self.era_index as u64),
+            NotificationCheckCadence::Hour => Ok(self.hour_index),
+        }
+    }
+}
+
+impl NotificationGenerator {
+    /// Claims the periodic checks that haven't been evaluated or claimed by another live
+    /// instance in their current periods, and returns their notification type codes and
+    /// cadences sorted by the type code. Each claimed check has to be finished with
+    /// `finish_periodic_check`.
+    pub(crate) async fn claim_due_periodic_checks(
+        config: &Config,
+        network_postgres: &PostgreSQLNetworkStorage,
+        period: &SchedulePeriod,
+    ) -> anyhow::Result<Vec<(String, NotificationCheckCadence)>> {
+        let mut due_checks = Vec::new();
+        for (notification_type_code, cadence) in &config.notification_generator.check_cadences {
+            if network_postgres
+                .claim_notification_generator_check_period(
+                    get_instance_id(),
+                    (
+                        notification_type_code,
+                        &cadence.to_string(),
+                        period.get_index(*cadence)?,
+                    ),
+                    config.notification_generator.instance_lease_seconds,
+                )
+                .await?
+            {
+                due_checks.push((notification_type_code.clone(), *cadence));
+            }
+        }
+        due_checks.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(due_checks)
+    }
+
+    /// Marks the claimed periodic check as evaluated in the current period of its cadence if
+    /// `is_evaluated`, releases the claim otherwise so that the check gets evaluated again with
+    /// a later update.
+    pub(crate) async fn finish_periodic_check(
+        network_postgres: &PostgreSQLNetworkStorage,
+        notification_type_code: &str,
+        cadence: NotificationCheckCadence,
+        period: &SchedulePeriod,
+        is_evaluated: bool,
+    ) -> anyhow::Result<()> {
+        let cadence_code = cadence.to_string();
+        let check_period = (
+            notification_type_code,
+            cadence_code.as_str(),
+            period.get_index(cadence)?,
+        );
+        if is_evaluated {
+            network_postgres
+                .complete_notification_generator_check_period(get_instance_id(), check_period)
+                .await
+        } else {
+            network_postgres
+                .release_notification_generator_check_period(get_instance_id(), check_period)
+                .await
+        }
+    }
+}
@@ -15,18 +15,22 @@ pub(crate) async fn send_apple_push_notification(
     content_provider: &Arc<ContentProvider>,
     notification: &Notification,
 ) -> anyhow::Result<()> {
-    let message =
-        content_provider.get_push_notification_content_for_notification(config, notification)?;
+    let message = content_provider
+        .get_push_notification_content_for_notification(config, notification)
+        .await?;
     let mut builder = a2::PlainNotificationBuilder::new(&message);
     builder.set_sound("default");
     // builder.set_badge(1u32);
-    let payload = builder.build(
+    let deep_link = notification.get_deep_link(&config.substrate.chain)?;
+    let mut payload = builder.build(
         &notification.notification_target,
         a2::NotificationOptions {
             apns_topic: Some(config.notification_sender.apns_topic.as_ref()),
             ..Default::default()
         },
     );
+    // delivered as a custom root key next to `aps`
+    payload.add_custom_data("deep_link", &deep_link)?;
     postgres
         .mark_notification_processing(notification.id)
         .await?;
@@ -1,7 +1,9 @@
 //! Email sending logic.
 
+use crate::content::ERA_POINTS_CHART_CONTENT_ID;
 use crate::ContentProvider;
-use lettre::message::{header, MultiPart, SinglePart};
+use lettre::message::header::{Header, HeaderName};
+use lettre::message::{header, Attachment, MultiPart, SinglePart};
 use lettre::transport::smtp::authentication::Credentials;
 use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
 use log::{debug, error};
@@ -10,6 +12,49 @@ use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
 use subvt_types::app::Notification;
 
+/// `List-Unsubscribe` header (RFC 2369) with the URL of the unsubscribe endpoint of
+/// `subvt-app-service`.
+#[derive(Clone)]
+struct ListUnsubscribe(String);
+
+impl Header for ListUnsubscribe {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe")
+    }
+
+    fn parse(s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self(
+            s.trim()
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string(),
+        ))
+    }
+
+    fn display(&self) -> String {
+        format!("<{}>", self.0)
+    }
+}
+
+/// `List-Unsubscribe-Post` header (RFC 8058), lets the mail clients unsubscribe with a single
+/// POST request to the `List-Unsubscribe` URL, without opening the confirmation page.
+#[derive(Clone)]
+struct ListUnsubscribePost;
+
+impl Header for ListUnsubscribePost {
+    fn name() -> HeaderName {
+        HeaderName::new_from_ascii_str("List-Unsubscribe-Post")
+    }
+
+    fn parse(_s: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(Self)
+    }
+
+    fn display(&self) -> String {
+        "List-Unsubscribe=One-Click".to_string()
+    }
+}
+
 pub(crate) type Mailer = AsyncSmtpTransport<Tokio1Executor>;
 
 pub(crate) fn new_mailer(config: &Config) -> anyhow::Result<Mailer> {
@@ -31,26 +76,55 @@ pub(crate) async fn send_email(
     content_provider: &Arc<ContentProvider>,
     notification: &Notification,
 ) -> anyhow::Result<()> {
-    let (subject, text_body, html_body) =
-        content_provider.get_email_content_for_notification(config, notification)?;
-    let message = lettre::Message::builder()
+    let maybe_unsubscribe_token = postgres
+        .get_user_notification_rule_channel_unsubscribe_token(
+            notification.user_notification_rule_id,
+            notification.user_notification_channel_id,
+        )
+        .await?;
+    let maybe_unsubscribe_url = maybe_unsubscribe_token.map(|unsubscribe_token| {
+        format!(
+            "{}/{}",
+            config
+                .notification_sender
+                .email_unsubscribe_url
+                .trim_end_matches('/'),
+            unsubscribe_token
+        )
+    });
+    let content = content_provider
+        .get_email_content_for_notification(config, notification, maybe_unsubscribe_url.as_deref())
+        .await?;
+    let text_part = SinglePart::builder()
+        .header(header::ContentType::TEXT_PLAIN)
+        .body(content.text_body);
+    let html_part = SinglePart::builder()
+        .header(header::ContentType::TEXT_HTML)
+        .body(content.html_body);
+    // the chart gets embedded as a related part of the HTML body
+    let body = if let Some(era_points_chart) = content.era_points_chart {
+        MultiPart::alternative().singlepart(text_part).multipart(
+            MultiPart::related().singlepart(html_part).singlepart(
+                Attachment::new_inline(ERA_POINTS_CHART_CONTENT_ID.to_string())
+                    .body(era_points_chart, header::ContentType::parse("image/png")?),
+            ),
+        )
+    } else {
+        MultiPart::alternative()
+            .singlepart(text_part)
+            .singlepart(html_part)
+    };
+    let mut message_builder = lettre::Message::builder()
         .from(config.notification_sender.email_from.parse()?)
         .reply_to(config.notification_sender.email_reply_to.parse()?)
-        .to("kutsalbilgin@gmail.com".parse()?)
-        .subject(subject)
-        .multipart(
-            MultiPart::alternative()
-                .singlepart(
-                    SinglePart::builder()
-                        .header(header::ContentType::TEXT_PLAIN)
-                        .body(text_body),
-                )
-                .singlepart(
-                    SinglePart::builder()
-                        .header(header::ContentType::TEXT_HTML)
-                        .body(html_body),
-                ),
-        )?;
+        .to(notification.notification_target.parse()?)
+        .subject(content.subject);
+    if let Some(unsubscribe_url) = maybe_unsubscribe_url {
+        message_builder = message_builder
+            .header(ListUnsubscribe(unsubscribe_url))
+            .header(ListUnsubscribePost);
+    }
+    let message = message_builder.multipart(body)?;
     postgres
         .mark_notification_processing(notification.id)
         .await?;
@@ -11,6 +11,8 @@ use subvt_types::app::Notification;
 #[derive(Serialize)]
 struct FCMMessage {
     message: String,
+    /// JSON of `NotificationDeepLink`, as the FCM data values are strings.
+    deep_link: String,
 }
 
 pub(crate) async fn send_fcm_message(
@@ -22,7 +24,9 @@ pub(crate) async fn send_fcm_message(
 ) -> anyhow::Result<()> {
     let message = FCMMessage {
         message: content_provider
-            .get_push_notification_content_for_notification(config, notification)?,
+            .get_push_notification_content_for_notification(config, notification)
+            .await?,
+        deep_link: serde_json::to_string(&notification.get_deep_link(&config.substrate.chain)?)?,
     };
     let mut builder = fcm::MessageBuilder::new(
         &config.notification_sender.fcm_api_key,
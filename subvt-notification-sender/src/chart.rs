@@ -0,0 +1,58 @@
+//! Server-side chart rendering for the email content.
+use anyhow::Context;
+use plotters::prelude::*;
+
+const CHART_WIDTH: u32 = 600;
+const CHART_HEIGHT: u32 = 240;
+
+/// Renders the bar chart of the given `(era index, reward points)` pairs into a PNG image.
+pub(crate) fn render_era_points_chart(era_reward_points: &[(u32, u32)]) -> anyhow::Result<Vec<u8>> {
+    let first_era_index = era_reward_points
+        .first()
+        .map(|(era_index, _)| *era_index)
+        .context("No era reward points to chart.")?;
+    let last_era_index = era_reward_points
+        .last()
+        .map(|(era_index, _)| *era_index)
+        .unwrap_or(first_era_index);
+    let max_reward_points = era_reward_points
+        .iter()
+        .map(|(_, reward_points)| *reward_points)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+    let mut buffer = vec![0u8; (CHART_WIDTH * CHART_HEIGHT * 3) as usize];
+    {
+        let root = BitMapBackend::with_buffer(&mut buffer, (CHART_WIDTH, CHART_HEIGHT))
+            .into_drawing_area();
+        root.fill(&WHITE)?;
+        let mut chart = ChartBuilder::on(&root)
+            .caption("Era Points", ("sans-serif", 18))
+            .margin(10)
+            .x_label_area_size(30)
+            .y_label_area_size(50)
+            .build_cartesian_2d(
+                (first_era_index..last_era_index + 1).into_segmented(),
+                0u32..(max_reward_points + max_reward_points / 10),
+            )?;
+        chart
+            .configure_mesh()
+            .disable_x_mesh()
+            .x_desc("Era")
+            .axis_desc_style(("sans-serif", 14))
+            .draw()?;
+        chart.draw_series(
+            Histogram::vertical(&chart)
+                .style(RGBColor(230, 0, 122).filled())
+                .margin(2)
+                .data(era_reward_points.iter().cloned()),
+        )?;
+        root.present()?;
+    }
+    let image = image::RgbImage::from_raw(CHART_WIDTH, CHART_HEIGHT, buffer)
+        .context("Cannot create chart image from the bitmap buffer.")?;
+    let mut png_bytes = Vec::new();
+    image::DynamicImage::ImageRgb8(image)
+        .write_to(&mut png_bytes, image::ImageOutputFormat::Png)?;
+    Ok(png_bytes)
+}
@@ -1,12 +1,69 @@
 //! Templated notification content provider.
 
+use crate::chart::render_era_points_chart;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
 use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::app::app_event::{
+    CommissionChangeAnnounced, ControllerLowBalance, LostNomination, NewNomination,
+    NominationAmountChange, NominatorRewardBelowExpectation, NominatorStakeNotRewarded,
+    NominatorUnbonding, OneKVRankChange, PrimarySlotsMissed, ReferendumVoteMissing,
+    ZeroRewardPoints,
+};
+use subvt_types::app::extrinsic::ValidateExtrinsic;
 use subvt_types::app::{Block, Notification, NotificationTypeCode, NotificationTypeCode::*};
+use subvt_types::crypto::AccountId;
+use subvt_types::local_time::LocalTimeHints;
+use subvt_types::substrate::{Balance, SystemProperties};
+use subvt_types::subvt::TokenPrice;
 use tera::{Context, Tera};
 
+/// Content id of the inline era points chart image, referenced by the HTML email layout.
+pub(crate) const ERA_POINTS_CHART_CONTENT_ID: &str = "era_points_chart";
+
+/// Validity of the 1KV candidate as persisted in the notification data. `OneKVValidity` can't
+/// be used, since it deserializes the field names of the 1KV API.
+#[derive(Deserialize)]
+struct OneKVValidityData {
+    details: String,
+    is_valid: bool,
+}
+
+#[derive(Deserialize)]
+struct OneKVValidityChangeData {
+    is_valid: bool,
+    validity_items: Vec<OneKVValidityData>,
+}
+
+pub(crate) struct EmailContent {
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+    /// PNG image to be embedded in the HTML body.
+    pub era_points_chart: Option<Vec<u8>>,
+}
+
 /// Provider struct. Hash separate renderers for separate text notification channels.
 /// Expects the `template` folder in this crate to be in the same folder as the executable.
+/// Email templates extend the common `layout.html` and `layout.txt` templates.
+/// Email templates also get the latest `token_price` in the `currency_code` preferred by the
+/// user (both absent if the user has no preference or there's no price yet), so that they can
+/// display the fiat equivalents of the token amounts.
+/// Email and push notification templates get the `era_local_times` of the era of the
+/// notification (see `LocalTimeHints` in `subvt-types`) if the notification channel has a
+/// timezone, and the `locale` of the channel if it has one.
+/// Every notification type that is generated by `subvt-notification-generator` has an email
+/// and a push notification template, named after the type code. The content of the other types
+/// is an error, which gets logged on the notification.
+/// Token amounts get formatted with the system properties of the chain.
 pub struct ContentProvider {
+    app_postgres: Arc<PostgreSQLAppStorage>,
+    network_postgres: Arc<PostgreSQLNetworkStorage>,
+    system_properties: SystemProperties,
     email_renderer: Tera,
     push_notification_renderer: Tera,
     _sms_renderer: Tera,
@@ -14,9 +71,16 @@ pub struct ContentProvider {
 }
 
 impl ContentProvider {
-    pub fn new() -> anyhow::Result<ContentProvider> {
+    pub fn new(
+        app_postgres: Arc<PostgreSQLAppStorage>,
+        network_postgres: Arc<PostgreSQLNetworkStorage>,
+        system_properties: SystemProperties,
+    ) -> anyhow::Result<ContentProvider> {
         Ok(ContentProvider {
-            email_renderer: { Tera::new("template/email/*.txt")? },
+            app_postgres,
+            network_postgres,
+            system_properties,
+            email_renderer: { Tera::new("template/email/*")? },
             _instant_message_renderer: { Tera::new("template/instant_message/*.txt")? },
             push_notification_renderer: { Tera::new("template/push_notification/*.txt")? },
             _sms_renderer: { Tera::new("template/sms/*.txt")? },
@@ -25,82 +89,438 @@ impl ContentProvider {
 }
 
 impl ContentProvider {
-    pub(crate) fn get_email_content_for_notification(
+    /// Builds the multipart email content. The era points chart of the validator gets embedded
+    /// if there is era data for the validator, and the unsubscribe link gets added to the footer
+    /// if an unsubscribe URL is given.
+    pub(crate) async fn get_email_content_for_notification(
         &self,
         config: &Config,
         notification: &Notification,
-    ) -> anyhow::Result<(String, String, String)> {
-        let (subject, text_body, html_body) =
-            match NotificationTypeCode::from(notification.notification_type_code.as_ref()) {
-                ChainValidatorBlockAuthorship => {
-                    let mut context = Context::new();
-                    context.insert("chain", &config.substrate.chain);
-                    context.insert(
-                        "validator_address",
-                        &notification.validator_account_id.to_ss58_check(),
-                    );
-                    context.insert(
-                        "validator_display",
-                        &if let Some(account) = &notification.get_account()? {
-                            account.to_string()
-                        } else {
-                            notification.validator_account_id.to_ss58_check()
-                        },
-                    );
-                    let block: Block =
-                        serde_json::from_str(notification.data_json.as_ref().unwrap())?;
-                    context.insert("block_number", &block.number);
-                    let subject = self.email_renderer.render(
-                        &format!("{}_subject.txt", notification.notification_type_code),
-                        &context,
-                    )?;
-                    let text_body = self.email_renderer.render(
-                        &format!("{}_body_text.txt", notification.notification_type_code),
-                        &context,
-                    )?;
-                    let html_body = self.email_renderer.render(
-                        &format!("{}_body_html.txt", notification.notification_type_code),
-                        &context,
-                    )?;
-                    (subject, text_body, html_body)
-                }
-                _ => todo!(
-                    "Email content not yet ready for {}.",
-                    notification.notification_type_code
-                ),
-            };
-        Ok((subject, text_body, html_body))
+        maybe_unsubscribe_url: Option<&str>,
+    ) -> anyhow::Result<EmailContent> {
+        let era_points_chart = self
+            .get_era_points_chart(config, &notification.validator_account_id)
+            .await;
+        let mut context = Context::new();
+        context.insert("chain", &config.substrate.chain);
+        context.insert(
+            "validator_address",
+            &notification.validator_account_id.to_ss58_check(),
+        );
+        context.insert(
+            "validator_display",
+            &if let Some(account) = &notification.get_account()? {
+                account.to_string()
+            } else {
+                notification.validator_account_id.to_ss58_check()
+            },
+        );
+        context.insert("unsubscribe_url", &maybe_unsubscribe_url);
+        if let Some(token_price) = self.get_token_price(notification.user_id).await {
+            context.insert("currency_code", &token_price.currency_code.to_uppercase());
+            context.insert("token_price", &token_price.price);
+        }
+        self.insert_local_times(config, notification, &mut context)
+            .await;
+        context.insert(
+            "era_points_chart_content_id",
+            &era_points_chart
+                .as_ref()
+                .map(|_| ERA_POINTS_CHART_CONTENT_ID),
+        );
+        insert_notification_data(&self.system_properties, notification, &mut context)?;
+        Ok(EmailContent {
+            subject: self.email_renderer.render(
+                &format!("{}_subject.txt", notification.notification_type_code),
+                &context,
+            )?,
+            text_body: self.email_renderer.render(
+                &format!("{}_body_text.txt", notification.notification_type_code),
+                &context,
+            )?,
+            html_body: self.email_renderer.render(
+                &format!("{}_body_html.html", notification.notification_type_code),
+                &context,
+            )?,
+            era_points_chart,
+        })
     }
 
-    pub(crate) fn get_push_notification_content_for_notification(
+    /// Latest token price in the currency preferred by the user. The price is optional content
+    /// too, so the email gets sent without the fiat equivalents in case of an error.
+    async fn get_token_price(&self, user_id: u32) -> Option<TokenPrice> {
+        let currency_code = match self.app_postgres.get_user_currency_code(user_id).await {
+            Ok(maybe_currency_code) => maybe_currency_code?,
+            Err(error) => {
+                warn!(
+                    "Cannot get the currency preference of the user: {:?}",
+                    error
+                );
+                return None;
+            }
+        };
+        match self
+            .network_postgres
+            .get_latest_token_price(&currency_code)
+            .await
+        {
+            Ok(maybe_token_price) => maybe_token_price,
+            Err(error) => {
+                warn!("Cannot get the {} token price: {:?}", currency_code, error);
+                None
+            }
+        }
+    }
+
+    /// Inserts the locale of the notification channel and the local times of the boundaries of
+    /// the era of the notification. Optional content too, nothing gets inserted in case of an
+    /// error, or if the channel has no timezone or the notification has no era.
+    async fn insert_local_times(
         &self,
-        _config: &Config,
+        config: &Config,
         notification: &Notification,
-    ) -> anyhow::Result<String> {
-        let message = match NotificationTypeCode::from(notification.notification_type_code.as_ref())
+        context: &mut Context,
+    ) {
+        let (maybe_timezone, maybe_locale) = match self
+            .app_postgres
+            .get_user_notification_channel_locale(notification.user_notification_channel_id)
+            .await
         {
-            ChainValidatorBlockAuthorship => {
-                let mut context = Context::new();
-                context.insert(
-                    "validator_display",
-                    &if let Some(account) = &notification.get_account()? {
-                        account.to_string()
-                    } else {
-                        notification.validator_account_id.to_ss58_check()
-                    },
+            Ok(maybe_channel_locale) => maybe_channel_locale.unwrap_or_default(),
+            Err(error) => {
+                warn!(
+                    "Cannot get the timezone and locale of the notification channel: {:?}",
+                    error
                 );
-                let block: Block = serde_json::from_str(notification.data_json.as_ref().unwrap())?;
-                context.insert("block_number", &block.number);
-                self.push_notification_renderer.render(
-                    &format!("{}_subject.txt", notification.notification_type_code),
-                    &context,
-                )?
+                return;
             }
-            _ => todo!(
-                "Push notification content not yet ready for {}.",
-                notification.notification_type_code
-            ),
         };
-        Ok(message)
+        if let Some(locale) = &maybe_locale {
+            context.insert("locale", locale);
+        }
+        let timezone = match maybe_timezone {
+            Some(timezone) => timezone,
+            None => return,
+        };
+        let maybe_era_index = match notification.get_deep_link(&config.substrate.chain) {
+            Ok(deep_link) => deep_link.era_index,
+            Err(error) => {
+                warn!("Cannot get the deep link of the notification: {:?}", error);
+                return;
+            }
+        };
+        let era_index = match maybe_era_index {
+            Some(era_index) => era_index,
+            None => return,
+        };
+        let maybe_era = match self.network_postgres.get_eras(era_index, era_index).await {
+            Ok(eras) => eras.into_iter().next(),
+            Err(error) => {
+                warn!("Cannot get era {}: {:?}", era_index, error);
+                return;
+            }
+        };
+        if let Some(era) = maybe_era {
+            match LocalTimeHints::for_era(&era, &timezone, maybe_locale.as_deref()) {
+                Ok(era_local_times) => context.insert("era_local_times", &era_local_times),
+                Err(error) => warn!(
+                    "Cannot get the local times of era {}: {:?}",
+                    era_index, error
+                ),
+            }
+        }
+    }
+
+    /// The chart is optional content, so the email gets sent without it in case of an error.
+    async fn get_era_points_chart(
+        &self,
+        config: &Config,
+        validator_account_id: &AccountId,
+    ) -> Option<Vec<u8>> {
+        let era_reward_points = match self
+            .network_postgres
+            .get_validator_era_reward_points(
+                &validator_account_id.to_string(),
+                config.notification_sender.email_chart_era_count,
+            )
+            .await
+        {
+            Ok(era_reward_points) => era_reward_points,
+            Err(error) => {
+                warn!("Cannot get era reward points for the chart: {:?}", error);
+                return None;
+            }
+        };
+        if era_reward_points.is_empty() {
+            return None;
+        }
+        match render_era_points_chart(&era_reward_points) {
+            Ok(png_bytes) => Some(png_bytes),
+            Err(error) => {
+                warn!("Cannot render era points chart: {:?}", error);
+                None
+            }
+        }
+    }
+
+    pub(crate) async fn get_push_notification_content_for_notification(
+        &self,
+        config: &Config,
+        notification: &Notification,
+    ) -> anyhow::Result<String> {
+        let mut context = Context::new();
+        context.insert(
+            "validator_display",
+            &if let Some(account) = &notification.get_account()? {
+                account.to_string()
+            } else {
+                notification.validator_account_id.to_ss58_check()
+            },
+        );
+        insert_notification_data(&self.system_properties, notification, &mut context)?;
+        self.insert_local_times(config, notification, &mut context)
+            .await;
+        Ok(self.push_notification_renderer.render(
+            &format!("{}.txt", notification.notification_type_code),
+            &context,
+        )?)
+    }
+}
+
+/// Formats the amount in token units with four decimals, e.g. `1234.5678 KSM`.
+fn format_amount(system_properties: &SystemProperties, amount: Balance) -> String {
+    format!(
+        "{:.4} {}",
+        amount as f64 / 10f64.powi(system_properties.token_decimals as i32),
+        system_properties.token_symbol
+    )
+}
+
+/// Formats the per-billion commission as percent, e.g. `5.00%`.
+fn format_commission(commission_per_billion: u64) -> String {
+    format!("{:.2}%", commission_per_billion as f64 / 10_000_000.0)
+}
+
+fn get_notification_data<T: DeserializeOwned>(notification: &Notification) -> anyhow::Result<T> {
+    match &notification.data_json {
+        Some(data_json) => Ok(serde_json::from_str(data_json)?),
+        None => Err(anyhow::anyhow!(
+            "Notification #{} has no data.",
+            notification.id
+        )),
+    }
+}
+
+/// Inserts the data of the notification that is specific to its type into the template
+/// context. The amounts get inserted formatted. Errors for the notification types that don't
+/// have content yet.
+fn insert_notification_data(
+    system_properties: &SystemProperties,
+    notification: &Notification,
+    context: &mut Context,
+) -> anyhow::Result<()> {
+    match NotificationTypeCode::from(notification.notification_type_code.as_ref()) {
+        ChainValidatorBlockAuthorship => {
+            let block: Block = get_notification_data(notification)?;
+            context.insert("block_number", &block.number);
+        }
+        ChainValidatorOfflineOffence
+        | ChainValidatorChilled
+        | ChainValidatorActive
+        | ChainValidatorActiveNextSession
+        | ChainValidatorInactive
+        | ChainValidatorInactiveNextSession => (),
+        ChainValidateExtrinsic => {
+            let extrinsic: ValidateExtrinsic = get_notification_data(notification)?;
+            context.insert(
+                "commission",
+                &format_commission(extrinsic.commission_per_billion),
+            );
+            context.insert("blocks_nominations", &extrinsic.blocks_nominations);
+        }
+        ChainValidatorNewNomination => {
+            let nomination: NewNomination = get_notification_data(notification)?;
+            context.insert(
+                "nominator_address",
+                &nomination.nominator_stash_account_id.to_ss58_check(),
+            );
+            context.insert(
+                "active_amount",
+                &format_amount(system_properties, nomination.active_amount),
+            );
+            context.insert("nominee_count", &nomination.nominee_count);
+        }
+        ChainValidatorLostNomination => {
+            let nomination: LostNomination = get_notification_data(notification)?;
+            context.insert(
+                "nominator_address",
+                &nomination.nominator_stash_account_id.to_ss58_check(),
+            );
+            context.insert(
+                "active_amount",
+                &format_amount(system_properties, nomination.active_amount),
+            );
+            context.insert("nominee_count", &nomination.nominee_count);
+        }
+        ChainValidatorNominationAmountChange => {
+            let change: NominationAmountChange = get_notification_data(notification)?;
+            context.insert(
+                "nominator_address",
+                &change.nominator_stash_account_id.to_ss58_check(),
+            );
+            context.insert(
+                "prev_active_amount",
+                &format_amount(system_properties, change.prev_active_amount),
+            );
+            context.insert(
+                "active_amount",
+                &format_amount(system_properties, change.active_amount),
+            );
+        }
+        ChainValidatorUnclaimedPayout => {
+            let unclaimed_era_indices: Vec<u32> = get_notification_data(notification)?;
+            context.insert("unclaimed_era_count", &unclaimed_era_indices.len());
+            context.insert(
+                "unclaimed_era_indices",
+                &unclaimed_era_indices
+                    .iter()
+                    .map(|era_index| era_index.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            );
+        }
+        ChainValidatorReferendumVoteMissing => {
+            let vote_missing: ReferendumVoteMissing = get_notification_data(notification)?;
+            context.insert("referendum_index", &vote_missing.referendum_index);
+            context.insert("end_block_number", &vote_missing.end_block_number);
+        }
+        ChainValidatorMissedPrimarySlots => {
+            let slots_missed: PrimarySlotsMissed = get_notification_data(notification)?;
+            context.insert("session_index", &slots_missed.session_index);
+            context.insert(
+                "expected_primary_slot_count",
+                &format!(
+                    "{:.1}",
+                    slots_missed.expected_primary_slot_count_thousandths as f64 / 1000.0
+                ),
+            );
+            context.insert("primary_block_count", &slots_missed.primary_block_count);
+        }
+        ChainValidatorZeroRewardPoints => {
+            let zero_reward_points: ZeroRewardPoints = get_notification_data(notification)?;
+            context.insert("session_index", &zero_reward_points.session_index);
+            context.insert(
+                "zero_point_session_count",
+                &zero_reward_points.zero_point_session_count,
+            );
+        }
+        ChainValidatorControllerLowBalance => {
+            let low_balance: ControllerLowBalance = get_notification_data(notification)?;
+            context.insert(
+                "controller_address",
+                &low_balance.controller_account_id.to_ss58_check(),
+            );
+            context.insert(
+                "free_balance",
+                &format_amount(system_properties, low_balance.free_balance),
+            );
+            context.insert(
+                "fee_spendable_balance",
+                &format_amount(system_properties, low_balance.fee_spendable_balance),
+            );
+        }
+        ChainValidatorCommissionChangeAnnounced => {
+            let announced: CommissionChangeAnnounced = get_notification_data(notification)?;
+            context.insert(
+                "current_commission",
+                &format_commission(announced.current_commission_per_billion as u64),
+            );
+            context.insert(
+                "commission",
+                &format_commission(announced.announcement.commission_per_billion as u64),
+            );
+            context.insert(
+                "effective_era_index",
+                &announced.announcement.effective_era_index,
+            );
+        }
+        ChainValidatorNominatorUnbonding => {
+            let unbonding: NominatorUnbonding = get_notification_data(notification)?;
+            context.insert(
+                "nominator_address",
+                &unbonding.nominator_stash_account_id.to_ss58_check(),
+            );
+            context.insert(
+                "unbonding_amount",
+                &format_amount(system_properties, unbonding.unbonding_amount),
+            );
+            context.insert(
+                "active_amount",
+                &format_amount(system_properties, unbonding.active_amount),
+            );
+            context.insert("unlock_era_index", &unbonding.unlock_era_index);
+        }
+        ChainNominatorRewardBelowExpectation => {
+            let below_expectation: NominatorRewardBelowExpectation =
+                get_notification_data(notification)?;
+            context.insert("era_index", &below_expectation.era_index);
+            context.insert(
+                "expected_reward",
+                &format_amount(system_properties, below_expectation.expected_reward),
+            );
+            context.insert(
+                "received_reward",
+                &format_amount(system_properties, below_expectation.received_reward),
+            );
+            context.insert(
+                "unpaid_validator_count",
+                &below_expectation.unpaid_validator_account_ids.len(),
+            );
+        }
+        ChainNominatorStakeNotRewarded => {
+            let not_rewarded: NominatorStakeNotRewarded = get_notification_data(notification)?;
+            context.insert(
+                "nominee_address",
+                &not_rewarded.validator_account_id.to_ss58_check(),
+            );
+            context.insert("era_index", &not_rewarded.era_index);
+            context.insert(
+                "stake",
+                &format_amount(system_properties, not_rewarded.stake),
+            );
+            context.insert(
+                "min_reentry_stake",
+                &format_amount(system_properties, not_rewarded.min_reentry_stake),
+            );
+            context.insert(
+                "max_rewarded_nominator_count",
+                &not_rewarded.max_rewarded_nominator_count,
+            );
+        }
+        OneKVValidatorRankChange => {
+            let rank_change: OneKVRankChange = get_notification_data(notification)?;
+            context.insert("prev_rank", &rank_change.prev_rank);
+            context.insert("current_rank", &rank_change.current_rank);
+        }
+        OneKVValidatorValidityChange => {
+            let validity_change: OneKVValidityChangeData = get_notification_data(notification)?;
+            context.insert("is_valid", &validity_change.is_valid);
+            context.insert(
+                "invalidity_reasons",
+                &validity_change
+                    .validity_items
+                    .into_iter()
+                    .filter(|validity_item| !validity_item.is_valid)
+                    .map(|validity_item| validity_item.details)
+                    .collect::<Vec<String>>(),
+            );
+        }
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Content is not available for {} notifications.",
+                notification.notification_type_code
+            ))
+        }
     }
+    Ok(())
 }
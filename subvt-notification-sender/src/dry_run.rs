@@ -0,0 +1,106 @@
+//! Per-channel dry-run mode for staging environments. The notifications of a dry-run channel
+//! get rendered and logged in the `app_notification_dry_run` table instead of being delivered
+//! to their targets, so that the whole generator-to-sender pipeline can be exercised without
+//! notifying the real users. If the channel has an override recipient, the notification gets
+//! delivered to it instead of the real target.
+use crate::content::ContentProvider;
+use log::debug;
+use std::sync::Arc;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_types::app::Notification;
+
+/// Gets whether the channel is in dry-run mode, and the override recipient of the channel.
+fn get_channel_dry_run_config<'a>(
+    config: &'a Config,
+    channel_code: &str,
+) -> (bool, Option<&'a str>) {
+    let sender_config = &config.notification_sender;
+    let (is_dry_run, maybe_recipient) = match channel_code {
+        "email" => (
+            sender_config.email_dry_run,
+            &sender_config.email_dry_run_recipient,
+        ),
+        "apns" => (
+            sender_config.apns_dry_run,
+            &sender_config.apns_dry_run_recipient,
+        ),
+        "fcm" => (
+            sender_config.fcm_dry_run,
+            &sender_config.fcm_dry_run_recipient,
+        ),
+        _ => (false, &None),
+    };
+    (
+        is_dry_run,
+        maybe_recipient
+            .as_deref()
+            .map(|recipient| recipient.trim())
+            .filter(|recipient| !recipient.is_empty()),
+    )
+}
+
+/// Renders the content that would be delivered to the channel, for the dry-run log.
+async fn get_dry_run_content(
+    config: &Config,
+    content_provider: &Arc<ContentProvider>,
+    notification: &Notification,
+) -> anyhow::Result<String> {
+    match notification.notification_channel_code.as_ref() {
+        "email" => {
+            let content = content_provider
+                .get_email_content_for_notification(config, notification, None)
+                .await?;
+            Ok(format!("{}\n\n{}", content.subject, content.text_body))
+        }
+        "apns" | "fcm" => Ok(format!(
+            "{}\n\n{}",
+            content_provider
+                .get_push_notification_content_for_notification(config, notification)
+                .await?,
+            serde_json::to_string(&notification.get_deep_link(&config.substrate.chain)?)?
+        )),
+        _ => Ok(String::new()),
+    }
+}
+
+/// Logs the notification if its channel is in dry-run mode. Returns `true` if the notification
+/// should still be sent, in which case its target is replaced with the channel's override
+/// recipient for a dry-run channel. Otherwise the notification gets marked as sent without
+/// being delivered.
+pub(crate) async fn process_dry_run(
+    config: &Config,
+    postgres: &Arc<PostgreSQLAppStorage>,
+    content_provider: &Arc<ContentProvider>,
+    notification: &mut Notification,
+) -> anyhow::Result<bool> {
+    let (is_dry_run, maybe_override_recipient) =
+        get_channel_dry_run_config(config, &notification.notification_channel_code);
+    if !is_dry_run {
+        return Ok(true);
+    }
+    let content = get_dry_run_content(config, content_provider, notification).await?;
+    postgres
+        .save_notification_dry_run(notification, maybe_override_recipient, &content)
+        .await?;
+    if let Some(override_recipient) = maybe_override_recipient {
+        debug!(
+            "Dry run. Send notification #{} to the override recipient.",
+            notification.id
+        );
+        notification.notification_target = override_recipient.to_string();
+        return Ok(true);
+    }
+    debug!(
+        "Dry run. Skip delivery of notification #{}.",
+        notification.id
+    );
+    postgres
+        .mark_notification_processing(notification.id)
+        .await?;
+    postgres.mark_notification_sent(notification.id).await?;
+    postgres
+        .set_notification_log(notification.id, "Dry run.")
+        .await?;
+    Ok(false)
+}
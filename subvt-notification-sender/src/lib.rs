@@ -3,7 +3,6 @@
 use crate::channel::email;
 use crate::channel::email::Mailer;
 use crate::content::ContentProvider;
-use anyhow::Context;
 use async_trait::async_trait;
 use chrono::{Datelike, Timelike, Utc};
 use lazy_static::lazy_static;
@@ -11,13 +10,18 @@ use log::{debug, error};
 use std::sync::Arc;
 use subvt_config::Config;
 use subvt_persistence::postgres::app::PostgreSQLAppStorage;
-use subvt_service_common::Service;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_persistence::realtime::get_realtime_store;
+use subvt_service_common::{analytics, Service};
+use subvt_substrate_client::SubstrateClient;
 use subvt_types::app::{Notification, NotificationPeriodType};
 use subvt_types::subvt::LiveNetworkStatus;
 use tokio::runtime::Builder;
 
 mod channel;
+mod chart;
 mod content;
+mod dry_run;
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -27,6 +31,9 @@ lazy_static! {
 pub struct NotificationSender;
 
 impl NotificationSender {
+    /// Sends the notification. Errors, such as a notification type without content, get logged
+    /// on the notification, which gets marked as failed so that it doesn't get picked up again
+    /// until the pending and failed notifications get reset.
     async fn send_notification(
         postgres: Arc<PostgreSQLAppStorage>,
         mailer: Arc<Mailer>,
@@ -34,6 +41,40 @@ impl NotificationSender {
         fcm_client: Arc<fcm::Client>,
         content_provider: Arc<ContentProvider>,
         notification: Notification,
+    ) -> anyhow::Result<()> {
+        let notification_id = notification.id;
+        if let Err(error) = NotificationSender::deliver_notification(
+            &postgres,
+            &mailer,
+            &apns_client,
+            &fcm_client,
+            &content_provider,
+            notification,
+        )
+        .await
+        {
+            error!(
+                "Error while sending notification #{}: {:?}",
+                notification_id, error
+            );
+            postgres
+                .mark_notification_processing(notification_id)
+                .await?;
+            postgres.mark_notification_failed(notification_id).await?;
+            postgres
+                .set_notification_log(notification_id, format!("{:?}", error).as_ref())
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn deliver_notification(
+        postgres: &Arc<PostgreSQLAppStorage>,
+        mailer: &Arc<Mailer>,
+        apns_client: &Arc<a2::Client>,
+        fcm_client: &Arc<fcm::Client>,
+        content_provider: &Arc<ContentProvider>,
+        mut notification: Notification,
     ) -> anyhow::Result<()> {
         debug!(
             "Send {} notification #{} for {}.",
@@ -41,13 +82,28 @@ impl NotificationSender {
             notification.id,
             notification.validator_account_id.to_ss58_check()
         );
+        // notifications created before a mute don't get sent during the mute, nor after it
+        if postgres.notification_is_muted(&notification).await? {
+            debug!("Notification #{} is muted. Skip.", notification.id);
+            postgres
+                .mark_notification_processing(notification.id)
+                .await?;
+            postgres
+                .set_notification_log(notification.id, "Muted.")
+                .await?;
+            return Ok(());
+        }
+        if !dry_run::process_dry_run(&CONFIG, postgres, content_provider, &mut notification).await?
+        {
+            return Ok(());
+        }
         match notification.notification_channel_code.as_ref() {
             "email" => {
                 channel::email::send_email(
                     &CONFIG,
-                    &postgres,
-                    &mailer,
-                    &content_provider,
+                    postgres,
+                    mailer,
+                    content_provider,
                     &notification,
                 )
                 .await?;
@@ -55,9 +111,9 @@ impl NotificationSender {
             "apns" => {
                 channel::apns::send_apple_push_notification(
                     &CONFIG,
-                    &postgres,
-                    &apns_client,
-                    &content_provider,
+                    postgres,
+                    apns_client,
+                    content_provider,
                     &notification,
                 )
                 .await?
@@ -65,18 +121,24 @@ impl NotificationSender {
             "fcm" => {
                 channel::fcm::send_fcm_message(
                     &CONFIG,
-                    &postgres,
-                    &fcm_client,
-                    &content_provider,
+                    postgres,
+                    fcm_client,
+                    content_provider,
                     &notification,
                 )
                 .await?
             }
-            _ => todo!(
-                "Notification channel not implemented yet: {}",
-                notification.notification_channel_code
-            ),
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Notification channel is not supported: {}",
+                    notification.notification_channel_code
+                ))
+            }
         }
+        analytics::record(
+            &format!("notification:{}", notification.notification_channel_code),
+            1,
+        );
         Ok(())
     }
 
@@ -157,9 +219,9 @@ impl NotificationSender {
         Ok(())
     }
 
-    /// Subscribes to the live network status notifications from Redis (which are generated by
-    /// `subvt-live-network-status-updater`) and processes epoch and era notification at epoch
-    /// and era changes.
+    /// Subscribes to the live network status notifications from the real-time store (which are
+    /// generated by `subvt-live-network-status-updater`) and processes epoch and era notification
+    /// at epoch and era changes.
     fn start_era_and_epoch_notification_processor(
         postgres: Arc<PostgreSQLAppStorage>,
         mailer: Arc<Mailer>,
@@ -167,57 +229,66 @@ impl NotificationSender {
         fcm_client: Arc<fcm::Client>,
         content_provider: Arc<ContentProvider>,
     ) -> anyhow::Result<()> {
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str()).context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
-        let mut data_connection = redis_client.get_connection()?;
-        let mut pub_sub_connection = redis_client.get_connection()?;
+        let realtime_store = get_realtime_store(&CONFIG)?;
         let tokio_rt = Builder::new_current_thread().enable_all().build()?;
         std::thread::spawn(move || {
             let mut active_era_index = 0;
             let mut current_epoch_index = 0;
-            let mut pub_sub = pub_sub_connection.as_pubsub();
-            pub_sub
-                .subscribe(format!(
+            loop {
+                let error: anyhow::Error = match realtime_store.subscribe(&format!(
                     "subvt:{}:live_network_status:publish:best_block_number",
                     CONFIG.substrate.chain
-                ))
-                .unwrap();
-            loop {
-                let _ = pub_sub.get_message();
-                let key = format!("subvt:{}:live_network_status", CONFIG.substrate.chain);
-                let status_json_string: String = redis::cmd("GET")
-                    .arg(key)
-                    .query(&mut data_connection)
-                    .unwrap();
-                let status: LiveNetworkStatus = serde_json::from_str(&status_json_string).unwrap();
-                // process epoch notifications if epoch has changed
-                if current_epoch_index != status.current_epoch.index {
-                    tokio_rt.block_on(NotificationSender::process_notifications(
-                        &postgres,
-                        &mailer,
-                        &apns_client,
-                        &fcm_client,
-                        &content_provider,
-                        NotificationPeriodType::Epoch,
-                        current_epoch_index as u32,
-                    ));
-                    current_epoch_index = status.current_epoch.index;
-                }
-                // process era notifications if epoch has changed
-                if active_era_index != status.active_era.index {
-                    tokio_rt.block_on(NotificationSender::process_notifications(
-                        &postgres,
-                        &mailer,
-                        &apns_client,
-                        &fcm_client,
-                        &content_provider,
-                        NotificationPeriodType::Era,
-                        active_era_index,
-                    ));
-                    active_era_index = status.active_era.index;
-                }
+                )) {
+                    Ok(subscription) => loop {
+                        if let Err(error) = subscription.next_block_number() {
+                            break error;
+                        }
+                        let key = format!("subvt:{}:live_network_status", CONFIG.substrate.chain);
+                        let status_json_string = match realtime_store.get(&key) {
+                            Ok(Some(status_json_string)) => status_json_string,
+                            Ok(None) => continue,
+                            Err(error) => break error,
+                        };
+                        let status: LiveNetworkStatus =
+                            match serde_json::from_str(&status_json_string) {
+                                Ok(status) => status,
+                                Err(error) => break error.into(),
+                            };
+                        // process epoch notifications if epoch has changed
+                        if current_epoch_index != status.current_epoch.index {
+                            tokio_rt.block_on(NotificationSender::process_notifications(
+                                &postgres,
+                                &mailer,
+                                &apns_client,
+                                &fcm_client,
+                                &content_provider,
+                                NotificationPeriodType::Epoch,
+                                current_epoch_index as u32,
+                            ));
+                            current_epoch_index = status.current_epoch.index;
+                        }
+                        // process era notifications if epoch has changed
+                        if active_era_index != status.active_era.index {
+                            tokio_rt.block_on(NotificationSender::process_notifications(
+                                &postgres,
+                                &mailer,
+                                &apns_client,
+                                &fcm_client,
+                                &content_provider,
+                                NotificationPeriodType::Era,
+                                active_era_index,
+                            ));
+                            active_era_index = status.active_era.index;
+                        }
+                    },
+                    Err(error) => error,
+                };
+                let delay_seconds = CONFIG.common.recovery_retry_seconds;
+                error!(
+                    "Error while processing the live network status: {:?}. Sleep for {} seconds, then continue.",
+                    error, delay_seconds,
+                );
+                std::thread::sleep(std::time::Duration::from_secs(delay_seconds));
             }
         });
         Ok(())
@@ -280,7 +351,15 @@ impl Service for NotificationSender {
         let postgres =
             Arc::new(PostgreSQLAppStorage::new(&CONFIG, CONFIG.get_app_postgres_url()).await?);
         let mailer = Arc::new(email::new_mailer(&CONFIG)?);
-        let content_provider = Arc::new(ContentProvider::new()?);
+        let network_postgres = Arc::new(
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?,
+        );
+        let system_properties = SubstrateClient::new(&CONFIG).await?.system_properties;
+        let content_provider = Arc::new(ContentProvider::new(
+            postgres.clone(),
+            network_postgres,
+            system_properties,
+        )?);
         let mut apns_key = std::fs::File::open(&CONFIG.notification_sender.apns_key_location)?;
         let apns_client = Arc::new(a2::Client::token(
             &mut apns_key,
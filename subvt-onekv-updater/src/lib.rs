@@ -1,12 +1,21 @@
 //! Updates the complete 1KV data for the network (only Polkadot and Kusama) on the database.
+//! Stays idle when 1KV is not enabled in the configuration.
+//!
+//! Run with `--backfill <source>` to ingest archived candidate list snapshots once and exit,
+//! so that the rank and score history charts cover the period before the deployment. A source
+//! is a URL or a file path of a JSON array of `OneKVCandidateSnapshot`s, and the option can be
+//! repeated for multiple sources.
 
+use anyhow::Context;
 use async_trait::async_trait;
+use clap::{App, Arg};
 use lazy_static::lazy_static;
 use log::{debug, error, info};
-use subvt_config::Config;
+use subvt_config::{Config, OneKVConfig};
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
 use subvt_service_common::Service;
-use subvt_types::onekv::{OneKVCandidate, OneKVCandidateDetails};
+use subvt_types::crypto::AccountId;
+use subvt_types::onekv::{OneKVCandidate, OneKVCandidateDetails, OneKVCandidateSnapshot};
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -18,25 +27,54 @@ pub struct OneKVUpdater {
 
 impl Default for OneKVUpdater {
     fn default() -> Self {
-        let http_client: reqwest::Client = reqwest::Client::builder()
-            .gzip(true)
-            .brotli(true)
-            .timeout(std::time::Duration::from_secs(
-                CONFIG.onekv.request_timeout_seconds,
-            ))
-            .build()
-            .unwrap();
+        let mut http_client_builder = reqwest::Client::builder().gzip(true).brotli(true);
+        if let Some(onekv_config) = CONFIG.get_onekv_config() {
+            http_client_builder = http_client_builder.timeout(std::time::Duration::from_secs(
+                onekv_config.request_timeout_seconds,
+            ));
+        }
+        let http_client: reqwest::Client = http_client_builder.build().unwrap();
         Self { http_client }
     }
 }
 
 impl OneKVUpdater {
-    async fn update(&self, postgres: &PostgreSQLNetworkStorage) -> anyhow::Result<()> {
+    /// Saves the commission declared to 1KV as an announcement when it changes. It gets
+    /// surfaced as an announced commission change while it differs from the on-chain commission.
+    async fn save_commission_change_announcement(
+        postgres: &PostgreSQLNetworkStorage,
+        candidate_details: &OneKVCandidateDetails,
+    ) -> anyhow::Result<()> {
+        let validator_account_id = AccountId::from_ss58_check(&candidate_details.stash_address)?;
+        // 1KV commission is in percent
+        let commission_per_billion = (candidate_details.commission as f64 * 10_000_000.0)
+            .round()
+            .clamp(0.0, 1_000_000_000.0) as u32;
+        if postgres
+            .save_onekv_commission_change_announcement(
+                &validator_account_id,
+                commission_per_billion,
+            )
+            .await?
+        {
+            debug!(
+                "Saved 1KV declared commission {} per billion for {}.",
+                commission_per_billion, candidate_details.stash_address
+            );
+        }
+        Ok(())
+    }
+
+    async fn update(
+        &self,
+        onekv_config: &OneKVConfig,
+        postgres: &PostgreSQLNetworkStorage,
+    ) -> anyhow::Result<()> {
         info!("Update 1KV.");
         info!("Fetch candidate list.");
         let response = self
             .http_client
-            .get(&CONFIG.onekv.candidate_list_endpoint)
+            .get(&onekv_config.candidate_list_endpoint)
             .send()
             .await?;
         let candidates: Vec<OneKVCandidate> = response.json().await?;
@@ -50,7 +88,7 @@ impl OneKVUpdater {
                 .http_client
                 .get(&format!(
                     "{}{}",
-                    CONFIG.onekv.candidate_details_endpoint, candidate.stash_address
+                    onekv_config.candidate_details_endpoint, candidate.stash_address
                 ))
                 .send()
                 .await;
@@ -81,7 +119,7 @@ impl OneKVUpdater {
             let save_result = postgres
                 .save_onekv_candidate(
                     &candidate_details,
-                    CONFIG.onekv.candidate_history_record_count as i64,
+                    onekv_config.candidate_history_record_count as i64,
                 )
                 .await;
             match save_result {
@@ -92,6 +130,17 @@ impl OneKVUpdater {
                         candidates.len(),
                         candidate.stash_address
                     );
+                    if let Err(error) = OneKVUpdater::save_commission_change_announcement(
+                        postgres,
+                        &candidate_details,
+                    )
+                    .await
+                    {
+                        error!(
+                            "Error while persisting declared commission of candidate {}:{:?}",
+                            candidate.stash_address, error
+                        );
+                    }
                 }
                 Err(error) => {
                     error!(
@@ -104,23 +153,104 @@ impl OneKVUpdater {
         info!("1KV update completed.");
         Ok(())
     }
+
+    async fn fetch_snapshots(&self, source: &str) -> anyhow::Result<Vec<OneKVCandidateSnapshot>> {
+        if source.starts_with("http://") || source.starts_with("https://") {
+            let response = self.http_client.get(source).send().await?;
+            Ok(response.error_for_status()?.json().await?)
+        } else {
+            let file = std::fs::File::open(source)
+                .context(format!("Cannot open 1KV snapshot file {}.", source))?;
+            Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+        }
+    }
+
+    /// Saves the rank and score history records of the candidates in the archived snapshots.
+    /// Idempotent, see `save_onekv_candidate_backfill_history_item`.
+    async fn backfill(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        sources: &[&str],
+    ) -> anyhow::Result<()> {
+        for source in sources {
+            info!("Fetch 1KV snapshots from {}.", source);
+            let snapshots = self.fetch_snapshots(source).await?;
+            info!("Fetched {} snapshots. Backfill history.", snapshots.len());
+            let mut saved_count = 0;
+            for snapshot in &snapshots {
+                for candidate in &snapshot.candidates {
+                    let validator_account_id =
+                        match AccountId::from_ss58_check(&candidate.stash_address) {
+                            Ok(account_id) => account_id,
+                            Err(error) => {
+                                error!(
+                                    "Invalid stash address {} in 1KV snapshot: {:?}",
+                                    candidate.stash_address, error
+                                );
+                                continue;
+                            }
+                        };
+                    if postgres
+                        .save_onekv_candidate_backfill_history_item(
+                            &validator_account_id,
+                            candidate,
+                            snapshot.timestamp,
+                        )
+                        .await?
+                    {
+                        saved_count += 1;
+                    }
+                }
+            }
+            info!(
+                "Saved {} history records from the snapshots of {}.",
+                saved_count, source
+            );
+        }
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
 impl Service for OneKVUpdater {
     async fn run(&'static self) -> anyhow::Result<()> {
+        let onekv_config = match CONFIG.get_onekv_config() {
+            Some(onekv_config) => onekv_config,
+            None => {
+                info!("1KV is not enabled. 1KV updater will stay idle.");
+                return std::future::pending().await;
+            }
+        };
+        let matches = App::new("SubVT 1KV Updater")
+            .version("0.1.0")
+            .author("Kutsal Kaan Bilgin <kutsal@helikon.io>")
+            .about("Updates the 1KV candidate data of the network.")
+            .arg(
+                Arg::new("backfill")
+                    .long("backfill")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .help("URL or file path of archived candidate list snapshots to backfill the history from, then exit."),
+            )
+            .get_matches();
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        if let Some(sources) = matches.values_of("backfill") {
+            let sources: Vec<&str> = sources.collect();
+            self.backfill(&postgres, &sources).await?;
+            info!("1KV backfill completed.");
+            std::process::exit(0);
+        }
         info!(
             "1KV updater has started with {} seconds refresh wait period.",
-            CONFIG.onekv.refresh_seconds
+            onekv_config.refresh_seconds
         );
-        let postgres =
-            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
         loop {
-            if let Err(error) = self.update(&postgres).await {
+            if let Err(error) = self.update(onekv_config, &postgres).await {
                 error!("1KV update has failed: {:?}", error);
-                error!("Will retry in {} seconds.", CONFIG.onekv.refresh_seconds);
+                error!("Will retry in {} seconds.", onekv_config.refresh_seconds);
             }
-            std::thread::sleep(std::time::Duration::from_secs(CONFIG.onekv.refresh_seconds));
+            std::thread::sleep(std::time::Duration::from_secs(onekv_config.refresh_seconds));
         }
     }
 }
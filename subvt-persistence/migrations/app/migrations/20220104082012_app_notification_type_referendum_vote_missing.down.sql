@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_validator_referendum_vote_missing';
\ No newline at end of file
@@ -0,0 +1,2 @@
+INSERT INTO app_notification_type(code) VALUES('chain_validator_referendum_vote_missing');
+-- chain_validator_referendum_vote_missing :: no param
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS app_user_validator_trg_notify_user_event ON app_user_validator;
+DROP TRIGGER IF EXISTS app_notification_trg_notify_user_event ON app_notification;
+DROP FUNCTION IF EXISTS app_notify_user_event;
\ No newline at end of file
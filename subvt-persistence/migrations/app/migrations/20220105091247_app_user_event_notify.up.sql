@@ -0,0 +1,34 @@
+CREATE OR REPLACE FUNCTION app_notify_user_event()
+RETURNS trigger
+AS $$
+DECLARE
+    event_type_var text;
+BEGIN
+    IF TG_TABLE_NAME = 'app_notification' THEN
+        event_type_var := 'notification_created';
+    ELSIF TG_OP = 'INSERT' THEN
+        event_type_var := 'user_validator_created';
+    ELSIF NEW.deleted_at IS NOT NULL AND OLD.deleted_at IS NULL THEN
+        event_type_var := 'user_validator_deleted';
+    ELSE
+        RETURN NEW;
+    END IF;
+    PERFORM pg_notify(
+        'app_user_event',
+        json_build_object(
+            'user_id', NEW.user_id,
+            'event_type', event_type_var,
+            'id', NEW.id
+        )::text
+    );
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER app_notification_trg_notify_user_event
+    AFTER INSERT ON app_notification
+    FOR EACH ROW EXECUTE PROCEDURE app_notify_user_event();
+
+CREATE TRIGGER app_user_validator_trg_notify_user_event
+    AFTER INSERT OR UPDATE OF deleted_at ON app_user_validator
+    FOR EACH ROW EXECUTE PROCEDURE app_notify_user_event();
\ No newline at end of file
@@ -0,0 +1,4 @@
+DROP TABLE app_notification_type_channel CASCADE;
+ALTER TABLE app_notification_param_type DROP COLUMN default_value;
+ALTER TABLE app_notification_type DROP COLUMN severity;
+DROP TYPE app_notification_severity;
\ No newline at end of file
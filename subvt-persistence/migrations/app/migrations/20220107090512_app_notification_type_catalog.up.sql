@@ -0,0 +1,62 @@
+CREATE TYPE app_notification_severity AS ENUM ('info', 'warning', 'critical');
+
+ALTER TABLE app_notification_type ADD COLUMN severity app_notification_severity NOT NULL DEFAULT 'info';
+
+UPDATE app_notification_type SET severity = 'critical' WHERE code IN (
+    'chain_validator_offline_offence',
+    'chain_validator_chilled',
+    'telemetry_validator_offline'
+);
+UPDATE app_notification_type SET severity = 'warning' WHERE code IN (
+    'chain_validator_lost_nomination',
+    'chain_validator_inactive',
+    'chain_validator_inactive_next_session',
+    'chain_validator_unclaimed_payout',
+    'chain_validator_referendum_vote_missing',
+    'telemetry_validator_binary_out_of_date',
+    'telemetry_validator_peer_count_low',
+    'telemetry_validator_too_many_txs_in_queue',
+    'telemetry_validator_lagging',
+    'telemetry_validator_finality_lagging',
+    'telemetry_validator_download_bw_low',
+    'telemetry_validator_upload_bw_low',
+    'onekv_validator_validity_change'
+);
+
+ALTER TABLE app_notification_param_type ADD COLUMN default_value VARCHAR(128);
+
+UPDATE app_notification_param_type SET default_value = '0' WHERE code = 'minimum_amount';
+UPDATE app_notification_param_type SET default_value = '180' WHERE code = 'duration_sec' AND notification_type_code IN ('telemetry_validator_offline', 'telemetry_validator_binary_out_of_date');
+UPDATE app_notification_param_type SET default_value = '60' WHERE code = 'duration_sec' AND default_value IS NULL;
+UPDATE app_notification_param_type SET default_value = '5' WHERE code = 'peer_count';
+UPDATE app_notification_param_type SET default_value = '20' WHERE code = 'tx_count';
+UPDATE app_notification_param_type SET default_value = '10' WHERE code = 'block_count';
+UPDATE app_notification_param_type SET default_value = '102400' WHERE code = 'kilo_bits_per_second';
+
+CREATE TABLE IF NOT EXISTS app_notification_type_channel
+(
+    id                      SERIAL PRIMARY KEY,
+    notification_type_code  VARCHAR(256) NOT NULL,
+    channel_code            VARCHAR(16) NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_notification_type_channel_u_notification_type_channel
+        UNIQUE (notification_type_code, channel_code),
+    CONSTRAINT app_notification_type_channel_fk_notification_type
+        FOREIGN KEY (notification_type_code)
+            REFERENCES app_notification_type (code)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_notification_type_channel_fk_channel
+        FOREIGN KEY (channel_code)
+            REFERENCES app_notification_channel (code)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+-- only the channels that subvt-notification-sender can deliver to, telemetry notifications
+-- don't have content yet
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT NT.code, NC.code
+FROM app_notification_type NT, app_notification_channel NC
+WHERE NC.code IN ('apns', 'email', 'fcm')
+AND NT.code NOT LIKE 'telemetry_%';
\ No newline at end of file
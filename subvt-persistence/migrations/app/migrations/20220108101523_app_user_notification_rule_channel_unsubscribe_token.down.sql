@@ -0,0 +1,2 @@
+DROP INDEX app_user_notification_rule_channel_idx_unsubscribe_token;
+ALTER TABLE app_user_notification_rule_channel DROP COLUMN unsubscribe_token;
\ No newline at end of file
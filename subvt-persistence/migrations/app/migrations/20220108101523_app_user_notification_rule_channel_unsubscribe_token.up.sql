@@ -0,0 +1,4 @@
+ALTER TABLE app_user_notification_rule_channel ADD COLUMN unsubscribe_token VARCHAR(64) NOT NULL DEFAULT md5(random()::text || clock_timestamp()::text);
+
+CREATE UNIQUE INDEX app_user_notification_rule_channel_idx_unsubscribe_token
+    ON app_user_notification_rule_channel (unsubscribe_token);
\ No newline at end of file
@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_validator_missed_primary_slots';
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE app_network_default_notification_rule_param CASCADE;
+DROP TABLE app_network_default_notification_rule CASCADE;
\ No newline at end of file
@@ -0,0 +1,43 @@
+CREATE TABLE IF NOT EXISTS app_network_default_notification_rule
+(
+    id                      SERIAL PRIMARY KEY,
+    network_id              integer NOT NULL,
+    notification_type_code  VARCHAR(256) NOT NULL,
+    name                    text,
+    period_type             app_notification_period_type NOT NULL default 'immediate',
+    period                  integer NOT NULL default 0,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_network_default_notification_rule_u_network_notification_type
+        UNIQUE (network_id, notification_type_code),
+    CONSTRAINT app_network_default_notification_rule_fk_network
+        FOREIGN KEY (network_id)
+            REFERENCES app_network (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_network_default_notification_rule_fk_notification_type
+        FOREIGN KEY (notification_type_code)
+            REFERENCES app_notification_type (code)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS app_network_default_notification_rule_param
+(
+    network_default_notification_rule_id    integer NOT NULL,
+    notification_param_type_id              integer NOT NULL,
+    "value"                                 VARCHAR(128),
+    created_at                              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_network_default_notification_rule_param_pk
+        PRIMARY KEY (network_default_notification_rule_id, notification_param_type_id),
+    CONSTRAINT app_network_default_notification_rule_param_fk_rule
+        FOREIGN KEY (network_default_notification_rule_id)
+            REFERENCES app_network_default_notification_rule (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_network_default_notification_rule_param_fk_param_type
+        FOREIGN KEY (notification_param_type_id)
+            REFERENCES app_notification_param_type (id)
+            ON DELETE RESTRICT
+            ON UPDATE CASCADE
+);
\ No newline at end of file
@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_validator_controller_low_balance';
\ No newline at end of file
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS app_user_notification_mute
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    user_validator_id       integer,
+    notification_type_code  VARCHAR(256),
+    expires_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_notification_mute_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_notification_mute_fk_user_validator
+        FOREIGN KEY (user_validator_id)
+            REFERENCES app_user_validator (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_notification_mute_fk_notification_type
+        FOREIGN KEY (notification_type_code)
+            REFERENCES app_notification_type (code)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_notification_mute_idx_user_id_expires_at
+    ON app_user_notification_mute (user_id, expires_at);
\ No newline at end of file
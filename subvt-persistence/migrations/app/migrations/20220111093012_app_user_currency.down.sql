@@ -0,0 +1 @@
+ALTER TABLE app_user DROP COLUMN currency_code;
\ No newline at end of file
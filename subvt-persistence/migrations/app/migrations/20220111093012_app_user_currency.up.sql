@@ -0,0 +1 @@
+ALTER TABLE app_user ADD COLUMN currency_code VARCHAR(8);
\ No newline at end of file
@@ -0,0 +1,2 @@
+DROP TABLE app_user_session CASCADE;
+DROP TABLE app_user_auth_challenge CASCADE;
\ No newline at end of file
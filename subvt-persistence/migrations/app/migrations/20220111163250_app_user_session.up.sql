@@ -0,0 +1,34 @@
+CREATE EXTENSION IF NOT EXISTS pgcrypto;
+
+CREATE TABLE IF NOT EXISTS app_user_auth_challenge
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    nonce                   VARCHAR(64) NOT NULL DEFAULT encode(gen_random_bytes(32), 'hex'),
+    expires_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_auth_challenge_u_nonce UNIQUE (nonce),
+    CONSTRAINT app_user_auth_challenge_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS app_user_session
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    token_hash              VARCHAR(64) NOT NULL,
+    expires_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_session_u_token_hash UNIQUE (token_hash),
+    CONSTRAINT app_user_session_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_session_idx_expires_at
+    ON app_user_session (expires_at);
\ No newline at end of file
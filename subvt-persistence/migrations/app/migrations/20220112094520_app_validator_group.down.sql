@@ -0,0 +1,4 @@
+ALTER TABLE app_user_notification_rule DROP COLUMN validator_group_id;
+DROP TABLE app_user_validator_group CASCADE;
+DROP TABLE app_validator_group_validator CASCADE;
+DROP TABLE app_validator_group CASCADE;
\ No newline at end of file
@@ -0,0 +1,75 @@
+CREATE TABLE IF NOT EXISTS app_validator_group
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    network_id              integer NOT NULL,
+    name                    text NOT NULL,
+    share_code              VARCHAR(32) NOT NULL DEFAULT encode(gen_random_bytes(8), 'hex'),
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    deleted_at              TIMESTAMP WITHOUT TIME ZONE,
+    CONSTRAINT app_validator_group_u_share_code
+        UNIQUE (share_code),
+    CONSTRAINT app_validator_group_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_validator_group_fk_network
+        FOREIGN KEY (network_id)
+            REFERENCES app_network (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_validator_group_idx_user_id
+    ON app_validator_group (user_id);
+
+CREATE TABLE IF NOT EXISTS app_validator_group_validator
+(
+    validator_group_id      integer NOT NULL,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_validator_group_validator_pk
+        PRIMARY KEY (validator_group_id, validator_account_id),
+    CONSTRAINT app_validator_group_validator_fk_group
+        FOREIGN KEY (validator_group_id)
+            REFERENCES app_validator_group (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_validator_group_validator_idx_validator_account_id
+    ON app_validator_group_validator (validator_account_id);
+
+CREATE TABLE IF NOT EXISTS app_user_validator_group
+(
+    user_id                 integer NOT NULL,
+    validator_group_id      integer NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_validator_group_pk
+        PRIMARY KEY (user_id, validator_group_id),
+    CONSTRAINT app_user_validator_group_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_validator_group_fk_group
+        FOREIGN KEY (validator_group_id)
+            REFERENCES app_validator_group (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_validator_group_idx_validator_group_id
+    ON app_user_validator_group (validator_group_id);
+
+ALTER TABLE app_user_notification_rule
+    ADD COLUMN validator_group_id integer
+        CONSTRAINT app_user_notification_rule_fk_validator_group
+            REFERENCES app_validator_group (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE;
+
+CREATE INDEX app_user_notification_rule_idx_validator_group_id
+    ON app_user_notification_rule (validator_group_id);
\ No newline at end of file
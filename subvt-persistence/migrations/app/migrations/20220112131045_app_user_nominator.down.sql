@@ -0,0 +1 @@
+DROP TABLE app_user_nominator CASCADE;
\ No newline at end of file
@@ -0,0 +1,27 @@
+CREATE TABLE IF NOT EXISTS app_user_nominator
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    network_id              integer NOT NULL,
+    nominator_account_id    VARCHAR(66) NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    deleted_at              TIMESTAMP WITHOUT TIME ZONE,
+    CONSTRAINT app_user_nominator_u_user_network_nominator
+        UNIQUE (user_id, network_id, nominator_account_id, deleted_at),
+    CONSTRAINT app_user_nominator_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_nominator_fk_network
+        FOREIGN KEY (network_id)
+            REFERENCES app_network (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_nominator_idx_user_id
+    ON app_user_nominator (user_id);
+
+CREATE INDEX app_user_nominator_idx_search
+    ON app_user_nominator (network_id, nominator_account_id, deleted_at);
\ No newline at end of file
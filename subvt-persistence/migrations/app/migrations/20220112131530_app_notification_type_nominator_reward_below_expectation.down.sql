@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_nominator_reward_below_expectation';
\ No newline at end of file
@@ -0,0 +1,26 @@
+INSERT INTO app_notification_type(code, severity) VALUES('chain_nominator_reward_below_expectation', 'warning');
+INSERT INTO app_notification_param_type(
+    notification_type_code,
+    code,
+    "order",
+    type,
+    "min",
+    "max",
+    default_value,
+    is_optional,
+    description
+) VALUES(
+    'chain_nominator_reward_below_expectation',
+    'min_delta_percent',
+    0,
+    'integer',
+    '1',
+    '100',
+    '10',
+    true,
+    'Notify only when the received era reward is below the expected reward by at least this percentage.'
+);
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT 'chain_nominator_reward_below_expectation', code
+FROM app_notification_channel
+WHERE code IN ('apns', 'email', 'fcm');
\ No newline at end of file
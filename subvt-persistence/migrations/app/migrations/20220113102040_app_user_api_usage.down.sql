@@ -0,0 +1,2 @@
+DROP TABLE app_user_api_usage CASCADE;
+DROP TABLE app_user_api_key CASCADE;
\ No newline at end of file
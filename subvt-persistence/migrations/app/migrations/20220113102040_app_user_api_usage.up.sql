@@ -0,0 +1,36 @@
+CREATE TABLE IF NOT EXISTS app_user_api_key
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    key_hash                VARCHAR(64) NOT NULL,
+    key_prefix              VARCHAR(8) NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    deleted_at              TIMESTAMP WITHOUT TIME ZONE,
+    CONSTRAINT app_user_api_key_u_key_hash UNIQUE (key_hash),
+    CONSTRAINT app_user_api_key_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_api_key_idx_user_id
+    ON app_user_api_key (user_id);
+
+CREATE TABLE IF NOT EXISTS app_user_api_usage
+(
+    api_key_id              integer NOT NULL,
+    usage_date              DATE NOT NULL,
+    request_count           bigint NOT NULL DEFAULT 0,
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_api_usage_pk
+        PRIMARY KEY (api_key_id, usage_date),
+    CONSTRAINT app_user_api_usage_fk_api_key
+        FOREIGN KEY (api_key_id)
+            REFERENCES app_user_api_key (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_api_usage_idx_usage_date
+    ON app_user_api_usage (usage_date);
\ No newline at end of file
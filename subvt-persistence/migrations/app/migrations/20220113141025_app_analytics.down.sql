@@ -0,0 +1 @@
+DROP TABLE app_analytics_counter CASCADE;
\ No newline at end of file
@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS app_analytics_counter
+(
+    service_name            VARCHAR(128) NOT NULL,
+    chain                   VARCHAR(64) NOT NULL,
+    metric                  VARCHAR(256) NOT NULL,
+    period_start            TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    count                   bigint NOT NULL DEFAULT 0,
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_analytics_counter_pk
+        PRIMARY KEY (service_name, chain, metric, period_start)
+);
+
+CREATE INDEX app_analytics_counter_idx_period_start
+    ON app_analytics_counter (period_start);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE app_notification_dry_run CASCADE;
\ No newline at end of file
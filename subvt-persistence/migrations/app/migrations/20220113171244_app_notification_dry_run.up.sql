@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS app_notification_dry_run
+(
+    id                          SERIAL PRIMARY KEY,
+    notification_id             integer NOT NULL,
+    notification_channel_code   VARCHAR(16) NOT NULL,
+    notification_target         text NOT NULL,
+    override_target             text,
+    content                     text NOT NULL,
+    created_at                  TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_notification_dry_run_fk_notification
+        FOREIGN KEY (notification_id)
+            REFERENCES app_notification (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_notification_dry_run_idx_notification_id
+    ON app_notification_dry_run (notification_id);
\ No newline at end of file
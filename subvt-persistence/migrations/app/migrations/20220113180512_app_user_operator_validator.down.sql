@@ -0,0 +1,4 @@
+DROP TABLE app_user_operator_validator CASCADE;
+DROP TABLE app_user_validator_claim_challenge CASCADE;
+ALTER TABLE app_notification_type DROP COLUMN is_operator_only;
+ALTER TABLE app_user DROP COLUMN is_verified_operator;
\ No newline at end of file
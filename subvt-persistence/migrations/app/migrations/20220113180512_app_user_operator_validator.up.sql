@@ -0,0 +1,55 @@
+ALTER TABLE app_user ADD COLUMN is_verified_operator boolean NOT NULL DEFAULT false;
+
+ALTER TABLE app_notification_type ADD COLUMN is_operator_only boolean NOT NULL DEFAULT false;
+
+UPDATE app_notification_type SET is_operator_only = true WHERE code IN (
+    'chain_validator_referendum_vote_missing',
+    'chain_validator_controller_low_balance'
+);
+
+CREATE TABLE IF NOT EXISTS app_user_validator_claim_challenge
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    network_id              integer NOT NULL,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    nonce                   VARCHAR(64) NOT NULL DEFAULT encode(gen_random_bytes(32), 'hex'),
+    expires_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_validator_claim_challenge_u_nonce UNIQUE (nonce),
+    CONSTRAINT app_user_validator_claim_challenge_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_validator_claim_challenge_fk_network
+        FOREIGN KEY (network_id)
+            REFERENCES app_network (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS app_user_operator_validator
+(
+    id                      SERIAL PRIMARY KEY,
+    user_id                 integer NOT NULL,
+    network_id              integer NOT NULL,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    is_session_key_claim    boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT app_user_operator_validator_u_user_network_validator
+        UNIQUE (user_id, network_id, validator_account_id),
+    CONSTRAINT app_user_operator_validator_fk_user
+        FOREIGN KEY (user_id)
+            REFERENCES app_user (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT app_user_operator_validator_fk_network
+        FOREIGN KEY (network_id)
+            REFERENCES app_network (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX app_user_operator_validator_idx_user_id
+    ON app_user_operator_validator (user_id);
\ No newline at end of file
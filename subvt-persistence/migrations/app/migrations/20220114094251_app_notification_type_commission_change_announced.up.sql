@@ -0,0 +1,6 @@
+INSERT INTO app_notification_type(code, severity) VALUES('chain_validator_commission_change_announced', 'warning');
+-- chain_validator_commission_change_announced :: no param
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT 'chain_validator_commission_change_announced', code
+FROM app_notification_channel
+WHERE code IN ('apns', 'email', 'fcm');
\ No newline at end of file
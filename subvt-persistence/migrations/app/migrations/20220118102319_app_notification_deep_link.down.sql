@@ -0,0 +1 @@
+ALTER TABLE app_notification DROP COLUMN deep_link_json;
\ No newline at end of file
@@ -0,0 +1 @@
+ALTER TABLE app_notification ADD COLUMN deep_link_json text;
\ No newline at end of file
@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_nominator_stake_not_rewarded';
\ No newline at end of file
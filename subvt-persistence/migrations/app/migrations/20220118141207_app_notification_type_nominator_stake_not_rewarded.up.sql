@@ -0,0 +1,5 @@
+INSERT INTO app_notification_type(code, severity) VALUES('chain_nominator_stake_not_rewarded', 'warning');
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT 'chain_nominator_stake_not_rewarded', code
+FROM app_notification_channel
+WHERE code IN ('apns', 'email', 'fcm');
\ No newline at end of file
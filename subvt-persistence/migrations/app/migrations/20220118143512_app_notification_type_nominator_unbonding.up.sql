@@ -0,0 +1,26 @@
+INSERT INTO app_notification_type(code, severity) VALUES('chain_validator_nominator_unbonding', 'warning');
+INSERT INTO app_notification_param_type(
+    notification_type_code,
+    code,
+    "order",
+    type,
+    "min",
+    "max",
+    default_value,
+    is_optional,
+    description
+) VALUES(
+    'chain_validator_nominator_unbonding',
+    'minimum_amount',
+    0,
+    'balance',
+    '0',
+    NULL,
+    NULL,
+    true,
+    'Notify only when a nominator of the validator starts unbonding at least this amount.'
+);
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT 'chain_validator_nominator_unbonding', code
+FROM app_notification_channel
+WHERE code IN ('apns', 'email', 'fcm');
\ No newline at end of file
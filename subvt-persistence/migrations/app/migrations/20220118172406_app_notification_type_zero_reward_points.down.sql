@@ -0,0 +1 @@
+DELETE FROM app_notification_type WHERE code = 'chain_validator_zero_reward_points';
\ No newline at end of file
@@ -0,0 +1,26 @@
+INSERT INTO app_notification_type(code, severity) VALUES('chain_validator_zero_reward_points', 'warning');
+INSERT INTO app_notification_param_type(
+    notification_type_code,
+    code,
+    "order",
+    type,
+    "min",
+    "max",
+    default_value,
+    is_optional,
+    description
+) VALUES(
+    'chain_validator_zero_reward_points',
+    'session_count',
+    0,
+    'integer',
+    '1',
+    '24',
+    '2',
+    true,
+    'Notify when the active validator has not earned any era reward points in this many consecutive sessions.'
+);
+INSERT INTO app_notification_type_channel(notification_type_code, channel_code)
+SELECT 'chain_validator_zero_reward_points', code
+FROM app_notification_channel
+WHERE code IN ('apns', 'email', 'fcm');
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE app_user_notification_channel DROP COLUMN locale;
+ALTER TABLE app_user_notification_channel DROP COLUMN timezone;
\ No newline at end of file
@@ -0,0 +1,2 @@
+ALTER TABLE app_user_notification_channel ADD COLUMN timezone VARCHAR(64);
+ALTER TABLE app_user_notification_channel ADD COLUMN locale VARCHAR(35);
\ No newline at end of file
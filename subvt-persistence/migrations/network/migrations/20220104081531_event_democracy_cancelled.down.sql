@@ -0,0 +1 @@
+DROP TABLE sub_event_democracy_cancelled CASCADE;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_extrinsic_democracy_vote CASCADE;
\ No newline at end of file
@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_democracy_vote
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer NOT NULL,
+    is_nested_call          boolean NOT NULL,
+    account_id              VARCHAR(66) NOT NULL,
+    referendum_index        bigint NOT NULL,
+    aye_balance             VARCHAR(128),
+    nay_balance             VARCHAR(128),
+    conviction              integer,
+    is_successful           boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_democracy_vote_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_extrinsic_democracy_vote_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_democracy_vote_idx_block_hash
+    ON sub_extrinsic_democracy_vote (block_hash);
+
+CREATE INDEX sub_extrinsic_democracy_vote_idx_referendum_account_successful
+    ON sub_extrinsic_democracy_vote (referendum_index, account_id, is_successful);
\ No newline at end of file
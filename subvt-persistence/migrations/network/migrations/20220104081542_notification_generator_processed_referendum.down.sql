@@ -0,0 +1 @@
+DROP TABLE sub_notification_generator_processed_referendum CASCADE;
\ No newline at end of file
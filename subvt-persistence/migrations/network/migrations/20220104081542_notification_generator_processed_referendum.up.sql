@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS sub_notification_generator_processed_referendum
+(
+    referendum_index    bigint PRIMARY KEY,
+    created_at          TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
\ No newline at end of file
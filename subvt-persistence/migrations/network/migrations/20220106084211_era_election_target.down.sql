@@ -0,0 +1 @@
+DROP TABLE sub_era_election_target CASCADE;
\ No newline at end of file
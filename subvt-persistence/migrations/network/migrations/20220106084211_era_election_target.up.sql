@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS sub_era_election_target
+(
+    id              SERIAL PRIMARY KEY,
+    era_index       bigint NOT NULL,
+    account_id      VARCHAR(66) NOT NULL,
+    created_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_era_election_target_u_era_index_account_id
+        UNIQUE (era_index, account_id),
+    CONSTRAINT sub_era_election_target_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_era_election_target_idx_era_index
+    ON sub_era_election_target (era_index);
\ No newline at end of file
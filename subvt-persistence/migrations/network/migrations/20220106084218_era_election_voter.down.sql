@@ -0,0 +1 @@
+DROP TABLE sub_era_election_voter CASCADE;
\ No newline at end of file
@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS sub_era_election_voter
+(
+    id                      SERIAL PRIMARY KEY,
+    era_index               bigint NOT NULL,
+    account_id              VARCHAR(66) NOT NULL,
+    stake                   VARCHAR(128) NOT NULL,
+    target_account_ids      VARCHAR(66)[] NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_era_election_voter_u_era_index_account_id
+        UNIQUE (era_index, account_id),
+    CONSTRAINT sub_era_election_voter_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_era_election_voter_idx_era_index
+    ON sub_era_election_voter (era_index);
+
+CREATE INDEX sub_era_election_voter_idx_account_id
+    ON sub_era_election_voter (account_id);
\ No newline at end of file
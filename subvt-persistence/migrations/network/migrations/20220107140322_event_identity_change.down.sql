@@ -0,0 +1,2 @@
+DROP TABLE sub_event_identity_change CASCADE;
+DROP TYPE sub_identity_change_type;
\ No newline at end of file
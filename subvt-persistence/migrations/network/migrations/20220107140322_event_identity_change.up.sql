@@ -0,0 +1,37 @@
+CREATE TYPE sub_identity_change_type AS ENUM ('identity_set', 'identity_cleared', 'identity_killed', 'judgement_requested', 'judgement_unrequested', 'judgement_given', 'sub_identity_added', 'sub_identity_removed', 'sub_identity_revoked');
+
+CREATE TABLE IF NOT EXISTS sub_event_identity_change
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer,
+    event_index             integer NOT NULL,
+    account_id              VARCHAR(66) NOT NULL,
+    change_type             sub_identity_change_type NOT NULL,
+    related_account_id      VARCHAR(66),
+    registrar_index         bigint,
+    identity_json           text,
+    sub_display             text,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_event_identity_change_u_event
+        UNIQUE (block_hash, event_index),
+    CONSTRAINT sub_event_identity_change_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_event_identity_change_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_event_identity_change_idx_block_hash
+    ON sub_event_identity_change (block_hash);
+
+CREATE INDEX sub_event_identity_change_idx_account_id
+    ON sub_event_identity_change (account_id);
+
+CREATE INDEX sub_event_identity_change_idx_related_account_id
+    ON sub_event_identity_change (related_account_id);
\ No newline at end of file
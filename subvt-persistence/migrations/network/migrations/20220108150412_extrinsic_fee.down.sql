@@ -0,0 +1 @@
+DROP TABLE sub_extrinsic_fee CASCADE;
\ No newline at end of file
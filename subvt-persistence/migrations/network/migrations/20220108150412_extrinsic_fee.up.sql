@@ -0,0 +1,32 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_fee
+(
+    id                          SERIAL PRIMARY KEY,
+    block_hash                  VARCHAR(66) NOT NULL,
+    extrinsic_index             integer NOT NULL,
+    signer_account_id           VARCHAR(66),
+    block_author_account_id     VARCHAR(66),
+    fee                         VARCHAR(128) NOT NULL,
+    tip                         VARCHAR(128) NOT NULL,
+    author_fee_share            VARCHAR(128) NOT NULL,
+    treasury_fee_share          VARCHAR(128) NOT NULL,
+    weight                      bigint NOT NULL,
+    pays_fee                    boolean NOT NULL,
+    is_successful               boolean NOT NULL,
+    created_at                  TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_fee_u_extrinsic
+        UNIQUE (block_hash, extrinsic_index),
+    CONSTRAINT sub_extrinsic_fee_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_fee_idx_block_hash
+    ON sub_extrinsic_fee (block_hash);
+
+CREATE INDEX sub_extrinsic_fee_idx_signer_account_id
+    ON sub_extrinsic_fee (signer_account_id);
+
+CREATE INDEX sub_extrinsic_fee_idx_block_author_account_id
+    ON sub_extrinsic_fee (block_author_account_id);
\ No newline at end of file
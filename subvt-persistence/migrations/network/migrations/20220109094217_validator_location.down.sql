@@ -0,0 +1,2 @@
+DROP TABLE sub_validator_location CASCADE;
+DROP TABLE sub_geolocation CASCADE;
\ No newline at end of file
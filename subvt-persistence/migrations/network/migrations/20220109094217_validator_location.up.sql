@@ -0,0 +1,30 @@
+CREATE TABLE IF NOT EXISTS sub_geolocation
+(
+    id                      SERIAL PRIMARY KEY,
+    query                   VARCHAR(512) NOT NULL,
+    country_code            VARCHAR(2),
+    region_code             VARCHAR(16),
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_geolocation_u_query
+        UNIQUE (query)
+);
+
+CREATE TABLE IF NOT EXISTS sub_validator_location
+(
+    id                      SERIAL PRIMARY KEY,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    country_code            VARCHAR(2) NOT NULL,
+    region_code             VARCHAR(16),
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_validator_location_u_validator_account_id
+        UNIQUE (validator_account_id),
+    CONSTRAINT sub_validator_location_fk_account
+        FOREIGN KEY (validator_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_validator_location_idx_country_code
+    ON sub_validator_location (country_code);
\ No newline at end of file
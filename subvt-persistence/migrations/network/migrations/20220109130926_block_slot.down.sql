@@ -0,0 +1,3 @@
+DROP INDEX sub_block_idx_epoch_index_author_account_id;
+ALTER TABLE sub_block DROP COLUMN is_primary_slot;
+ALTER TABLE sub_block DROP COLUMN slot_number;
\ No newline at end of file
@@ -0,0 +1,5 @@
+ALTER TABLE sub_block ADD COLUMN slot_number bigint;
+ALTER TABLE sub_block ADD COLUMN is_primary_slot boolean;
+
+CREATE INDEX sub_block_idx_epoch_index_author_account_id
+    ON sub_block (epoch_index, author_account_id);
\ No newline at end of file
@@ -0,0 +1,18 @@
+DELETE FROM sub_notification_generator_processed_referendum WHERE instance_id <> 'default';
+ALTER TABLE sub_notification_generator_processed_referendum DROP CONSTRAINT sub_notification_generator_processed_referendum_pkey;
+ALTER TABLE sub_notification_generator_processed_referendum DROP COLUMN instance_id;
+ALTER TABLE sub_notification_generator_processed_referendum ADD PRIMARY KEY (referendum_index);
+
+DELETE FROM sub_notification_generator_processed_era WHERE instance_id <> 'default';
+ALTER TABLE sub_notification_generator_processed_era DROP CONSTRAINT sub_notification_generator_processed_era_pkey;
+ALTER TABLE sub_notification_generator_processed_era DROP COLUMN instance_id;
+ALTER TABLE sub_notification_generator_processed_era ADD PRIMARY KEY (era_index);
+
+DELETE FROM sub_notification_generator_state WHERE instance_id <> 'default';
+ALTER TABLE sub_notification_generator_state DROP CONSTRAINT sub_notification_generator_state_pkey;
+ALTER TABLE sub_notification_generator_state DROP COLUMN instance_id;
+ALTER TABLE sub_notification_generator_state ADD COLUMN id integer NOT NULL DEFAULT 1;
+ALTER TABLE sub_notification_generator_state ALTER COLUMN id DROP DEFAULT;
+ALTER TABLE sub_notification_generator_state ADD PRIMARY KEY (id);
+
+DROP TABLE sub_notification_generator_instance CASCADE;
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS sub_notification_generator_instance
+(
+    instance_id     VARCHAR(128) PRIMARY KEY,
+    heartbeat_at    TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    created_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
+
+ALTER TABLE sub_notification_generator_state DROP CONSTRAINT sub_notification_generator_state_pkey;
+ALTER TABLE sub_notification_generator_state DROP COLUMN id;
+ALTER TABLE sub_notification_generator_state ADD COLUMN instance_id VARCHAR(128) NOT NULL DEFAULT 'default';
+ALTER TABLE sub_notification_generator_state ADD PRIMARY KEY (instance_id);
+
+ALTER TABLE sub_notification_generator_processed_era DROP CONSTRAINT sub_notification_generator_processed_era_pkey;
+ALTER TABLE sub_notification_generator_processed_era ADD COLUMN instance_id VARCHAR(128) NOT NULL DEFAULT 'default';
+ALTER TABLE sub_notification_generator_processed_era ADD PRIMARY KEY (instance_id, era_index);
+
+ALTER TABLE sub_notification_generator_processed_referendum DROP CONSTRAINT sub_notification_generator_processed_referendum_pkey;
+ALTER TABLE sub_notification_generator_processed_referendum ADD COLUMN instance_id VARCHAR(128) NOT NULL DEFAULT 'default';
+ALTER TABLE sub_notification_generator_processed_referendum ADD PRIMARY KEY (instance_id, referendum_index);
\ No newline at end of file
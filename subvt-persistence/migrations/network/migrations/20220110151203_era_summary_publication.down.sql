@@ -0,0 +1 @@
+DROP TABLE sub_era_summary_publication CASCADE;
\ No newline at end of file
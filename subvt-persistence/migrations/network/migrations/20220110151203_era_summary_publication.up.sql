@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS sub_era_summary_publication
+(
+    era_index       bigint NOT NULL,
+    target          VARCHAR(512) NOT NULL,
+    created_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (era_index, target),
+    CONSTRAINT sub_era_summary_publication_fk_era
+        FOREIGN KEY (era_index)
+            REFERENCES sub_era (index)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
\ No newline at end of file
@@ -0,0 +1,3 @@
+DROP TRIGGER IF EXISTS sub_domain_event_trg_notify ON sub_domain_event;
+DROP FUNCTION IF EXISTS sub_notify_domain_event;
+DROP TABLE sub_domain_event CASCADE;
\ No newline at end of file
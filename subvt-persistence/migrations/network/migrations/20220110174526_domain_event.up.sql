@@ -0,0 +1,45 @@
+CREATE TABLE IF NOT EXISTS sub_domain_event
+(
+    id                      BIGSERIAL PRIMARY KEY,
+    event_type              VARCHAR(64) NOT NULL,
+    block_hash              VARCHAR(66) NOT NULL,
+    validator_account_id    VARCHAR(66),
+    data_json               text NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_domain_event_u_event
+        UNIQUE (block_hash, event_type, validator_account_id, data_json),
+    CONSTRAINT sub_domain_event_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_domain_event_fk_validator_account
+        FOREIGN KEY (validator_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_domain_event_idx_event_type
+    ON sub_domain_event (event_type);
+CREATE INDEX sub_domain_event_idx_validator_account_id
+    ON sub_domain_event (validator_account_id);
+
+CREATE OR REPLACE FUNCTION sub_notify_domain_event()
+RETURNS trigger
+AS $$
+BEGIN
+    PERFORM pg_notify(
+        'domain_event',
+        json_build_object(
+            'id', NEW.id,
+            'event_type', NEW.event_type
+        )::text
+    );
+    RETURN NEW;
+END
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER sub_domain_event_trg_notify
+    AFTER INSERT ON sub_domain_event
+    FOR EACH ROW EXECUTE PROCEDURE sub_notify_domain_event();
\ No newline at end of file
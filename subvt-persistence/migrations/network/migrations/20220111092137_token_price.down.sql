@@ -0,0 +1 @@
+DROP TABLE sub_token_price CASCADE;
\ No newline at end of file
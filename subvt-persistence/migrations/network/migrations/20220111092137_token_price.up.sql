@@ -0,0 +1,10 @@
+CREATE TABLE IF NOT EXISTS sub_token_price
+(
+    id                      BIGSERIAL PRIMARY KEY,
+    currency_code           VARCHAR(8) NOT NULL,
+    price                   DOUBLE PRECISION NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
+
+CREATE INDEX sub_token_price_idx_currency_code_created_at
+    ON sub_token_price (currency_code, created_at);
\ No newline at end of file
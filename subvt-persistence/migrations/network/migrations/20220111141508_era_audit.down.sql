@@ -0,0 +1,2 @@
+DROP TABLE sub_era_audit_discrepancy CASCADE;
+DROP TABLE sub_era_audit CASCADE;
\ No newline at end of file
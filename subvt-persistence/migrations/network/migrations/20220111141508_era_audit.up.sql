@@ -0,0 +1,29 @@
+CREATE TABLE IF NOT EXISTS sub_era_audit
+(
+    era_index               bigint PRIMARY KEY,
+    discrepancy_count       integer NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_era_audit_fk_era
+        FOREIGN KEY (era_index)
+            REFERENCES sub_era (index)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS sub_era_audit_discrepancy
+(
+    id                      SERIAL PRIMARY KEY,
+    era_index               bigint NOT NULL,
+    check_code              VARCHAR(64) NOT NULL,
+    indexed_value           VARCHAR(128),
+    chain_value             VARCHAR(128),
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_era_audit_discrepancy_fk_era_audit
+        FOREIGN KEY (era_index)
+            REFERENCES sub_era_audit (era_index)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_era_audit_discrepancy_idx_era_index
+    ON sub_era_audit_discrepancy (era_index);
\ No newline at end of file
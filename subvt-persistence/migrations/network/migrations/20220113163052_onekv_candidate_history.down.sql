@@ -0,0 +1 @@
+DROP TABLE sub_onekv_candidate_history CASCADE;
\ No newline at end of file
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS sub_onekv_candidate_history
+(
+    id                      SERIAL PRIMARY KEY,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    rank                    bigint,
+    is_valid                boolean NOT NULL,
+    score_total             double precision,
+    score_aggregate         double precision,
+    recorded_at             bigint NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_onekv_candidate_history_fk_validator_account_id
+        FOREIGN KEY (validator_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_onekv_candidate_history_idx_validator_account_id_recorded_at
+    ON sub_onekv_candidate_history (validator_account_id, recorded_at);
\ No newline at end of file
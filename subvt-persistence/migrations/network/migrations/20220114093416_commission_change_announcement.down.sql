@@ -0,0 +1 @@
+DROP TABLE sub_commission_change_announcement CASCADE;
\ No newline at end of file
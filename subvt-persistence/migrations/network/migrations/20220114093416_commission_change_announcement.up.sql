@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS sub_commission_change_announcement
+(
+    id                      SERIAL PRIMARY KEY,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    source                  VARCHAR(16) NOT NULL,
+    commission_per_billion  bigint NOT NULL,
+    effective_era_index     bigint,
+    block_hash              VARCHAR(66),
+    extrinsic_index         integer,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_commission_change_announcement_fk_validator_account_id
+        FOREIGN KEY (validator_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_commission_change_announcement_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_commission_change_announcement_idx_validator_account_id_created_at
+    ON sub_commission_change_announcement (validator_account_id, created_at);
\ No newline at end of file
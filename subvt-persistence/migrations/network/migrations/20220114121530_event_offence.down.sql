@@ -0,0 +1 @@
+DROP TABLE sub_event_offence CASCADE;
\ No newline at end of file
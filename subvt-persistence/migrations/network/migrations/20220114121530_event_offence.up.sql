@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS sub_event_offence
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer,
+    event_index             integer NOT NULL,
+    offence_kind            VARCHAR(64) NOT NULL,
+    time_slot               VARCHAR(256) NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_event_offence_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_event_offence_idx_block_hash
+    ON sub_event_offence (block_hash);
\ No newline at end of file
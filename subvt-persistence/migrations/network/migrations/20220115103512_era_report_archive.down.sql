@@ -0,0 +1,2 @@
+DROP TABLE sub_era_report_archive_manifest CASCADE;
+DROP TABLE sub_era_report_archive CASCADE;
\ No newline at end of file
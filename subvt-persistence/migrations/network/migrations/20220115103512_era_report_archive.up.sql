@@ -0,0 +1,22 @@
+CREATE TABLE IF NOT EXISTS sub_era_report_archive
+(
+    era_index       bigint NOT NULL,
+    target          VARCHAR(512) NOT NULL,
+    file_name       VARCHAR(256) NOT NULL,
+    location        VARCHAR(1024) NOT NULL,
+    created_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (era_index, target, file_name),
+    CONSTRAINT sub_era_report_archive_fk_era
+        FOREIGN KEY (era_index)
+            REFERENCES sub_era (index)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS sub_era_report_archive_manifest
+(
+    target          VARCHAR(512) PRIMARY KEY,
+    location        VARCHAR(1024) NOT NULL,
+    created_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    updated_at      TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
\ No newline at end of file
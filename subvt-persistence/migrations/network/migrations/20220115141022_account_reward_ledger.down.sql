@@ -0,0 +1 @@
+DROP TABLE sub_account_reward_ledger CASCADE;
\ No newline at end of file
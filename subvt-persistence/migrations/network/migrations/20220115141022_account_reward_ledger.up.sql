@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS sub_account_reward_ledger
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    block_number            bigint NOT NULL,
+    timestamp               bigint,
+    event_index             integer NOT NULL,
+    account_id              VARCHAR(66) NOT NULL,
+    era_index               bigint,
+    validator_account_id    VARCHAR(66),
+    amount                  VARCHAR(128) NOT NULL,
+    reward_destination      VARCHAR(16),
+    destination_account_id  VARCHAR(66),
+    is_restaked             boolean NOT NULL DEFAULT false,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_account_reward_ledger_u_block_hash_event_index
+        UNIQUE (block_hash, event_index),
+    CONSTRAINT sub_account_reward_ledger_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_account_reward_ledger_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_account_reward_ledger_idx_account_id_block_number
+    ON sub_account_reward_ledger (account_id, block_number);
+
+CREATE INDEX sub_account_reward_ledger_idx_account_id_era_index
+    ON sub_account_reward_ledger (account_id, era_index);
+
+CREATE INDEX sub_account_reward_ledger_idx_account_id_timestamp
+    ON sub_account_reward_ledger (account_id, timestamp);
+
+-- backfill from the indexed reward events, the era and the validator are known only for
+-- the rewards of the extrinsics with a single payout, and the reward destinations are unknown
+INSERT INTO sub_account_reward_ledger (block_hash, block_number, timestamp, event_index, account_id, era_index, validator_account_id, amount)
+SELECT R.block_hash, B.number, B.timestamp, R.event_index, R.rewardee_account_id, P.era_index, P.validator_account_id, R.amount
+FROM sub_event_rewarded R
+INNER JOIN sub_block B
+    ON B.hash = R.block_hash
+LEFT JOIN LATERAL (
+    SELECT MIN(EPS.era_index) AS era_index, MIN(EPS.validator_account_id) AS validator_account_id
+    FROM sub_extrinsic_payout_stakers EPS
+    WHERE EPS.block_hash = R.block_hash
+    AND EPS.extrinsic_index = R.extrinsic_index
+    AND EPS.is_successful = true
+    HAVING COUNT(EPS.id) = 1
+) P ON true
+ON CONFLICT (block_hash, event_index) DO NOTHING;
\ No newline at end of file
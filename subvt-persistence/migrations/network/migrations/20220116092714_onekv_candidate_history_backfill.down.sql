@@ -0,0 +1 @@
+ALTER TABLE sub_onekv_candidate_history DROP COLUMN is_backfilled;
\ No newline at end of file
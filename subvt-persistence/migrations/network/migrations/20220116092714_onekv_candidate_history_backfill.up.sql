@@ -0,0 +1 @@
+ALTER TABLE sub_onekv_candidate_history ADD COLUMN is_backfilled boolean NOT NULL DEFAULT false;
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_extrinsic_staking_call CASCADE;
\ No newline at end of file
@@ -0,0 +1,28 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_staking_call
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer NOT NULL,
+    is_nested_call          boolean NOT NULL,
+    caller_account_id       VARCHAR(66) NOT NULL,
+    call_name               VARCHAR(64) NOT NULL,
+    amount                  VARCHAR(128),
+    is_successful           boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_staking_call_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_extrinsic_staking_call_fk_caller_account_id
+        FOREIGN KEY (caller_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_staking_call_idx_block_hash
+    ON sub_extrinsic_staking_call (block_hash);
+
+CREATE INDEX sub_extrinsic_staking_call_idx_caller_account_id_call_name
+    ON sub_extrinsic_staking_call (caller_account_id, call_name);
\ No newline at end of file
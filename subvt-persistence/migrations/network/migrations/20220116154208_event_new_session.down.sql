@@ -0,0 +1 @@
+DROP TABLE sub_event_new_session CASCADE;
\ No newline at end of file
@@ -0,0 +1,20 @@
+CREATE TABLE IF NOT EXISTS sub_event_new_session
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer,
+    event_index             integer NOT NULL,
+    session_index           bigint NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_event_new_session_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_event_new_session_idx_block_hash
+    ON sub_event_new_session (block_hash);
+
+CREATE INDEX sub_event_new_session_idx_session_index
+    ON sub_event_new_session (session_index);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_data_retention CASCADE;
\ No newline at end of file
@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS sub_data_retention
+(
+    category                VARCHAR(32) PRIMARY KEY,
+    retention_days          integer NOT NULL,
+    last_pruned_at          TIMESTAMP WITHOUT TIME ZONE,
+    last_pruned_row_count   bigint,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    updated_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
+
+-- the telemetry hypertables have a TimescaleDB retention policy of 1 day since their creation
+INSERT INTO sub_data_retention (category, retention_days) VALUES ('telemetry', 1);
\ No newline at end of file
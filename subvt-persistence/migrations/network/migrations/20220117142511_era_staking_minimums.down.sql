@@ -0,0 +1,3 @@
+ALTER TABLE sub_era DROP COLUMN min_active_nominator_stake;
+ALTER TABLE sub_era DROP COLUMN min_validator_bond;
+ALTER TABLE sub_era DROP COLUMN min_nominator_bond;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE sub_era ADD COLUMN min_nominator_bond VARCHAR(128);
+ALTER TABLE sub_era ADD COLUMN min_validator_bond VARCHAR(128);
+ALTER TABLE sub_era ADD COLUMN min_active_nominator_stake VARCHAR(128);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_extrinsic_crowdloan_contribute CASCADE;
\ No newline at end of file
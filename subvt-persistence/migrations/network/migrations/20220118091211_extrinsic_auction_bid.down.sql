@@ -0,0 +1 @@
+DROP TABLE sub_extrinsic_auction_bid CASCADE;
\ No newline at end of file
@@ -0,0 +1,31 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_auction_bid
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer NOT NULL,
+    is_nested_call          boolean NOT NULL,
+    account_id              VARCHAR(66) NOT NULL,
+    parachain_id            bigint NOT NULL,
+    auction_index           bigint NOT NULL,
+    first_slot              bigint NOT NULL,
+    last_slot               bigint NOT NULL,
+    amount                  VARCHAR(128) NOT NULL,
+    is_successful           boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_auction_bid_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_extrinsic_auction_bid_fk_account
+        FOREIGN KEY (account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_auction_bid_idx_block_hash
+    ON sub_extrinsic_auction_bid (block_hash);
+
+CREATE INDEX sub_extrinsic_auction_bid_idx_account_successful
+    ON sub_extrinsic_auction_bid (account_id, is_successful);
\ No newline at end of file
@@ -0,0 +1,35 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_identity_call
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer NOT NULL,
+    is_nested_call          boolean NOT NULL,
+    caller_account_id       VARCHAR(66) NOT NULL,
+    call_name               VARCHAR(64) NOT NULL,
+    target_account_id       VARCHAR(66),
+    registrar_index         bigint,
+    judgement               VARCHAR(32),
+    identity_json           text,
+    sub_count               integer,
+    is_successful           boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_identity_call_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_extrinsic_identity_call_fk_caller_account_id
+        FOREIGN KEY (caller_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_identity_call_idx_block_hash
+    ON sub_extrinsic_identity_call (block_hash);
+
+CREATE INDEX sub_extrinsic_identity_call_idx_caller_account_id
+    ON sub_extrinsic_identity_call (caller_account_id);
+
+CREATE INDEX sub_extrinsic_identity_call_idx_target_account_id
+    ON sub_extrinsic_identity_call (target_account_id);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_era_validator_commission CASCADE;
\ No newline at end of file
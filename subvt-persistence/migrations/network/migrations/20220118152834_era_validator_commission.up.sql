@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS sub_era_validator_commission
+(
+    era_index               bigint NOT NULL,
+    validator_account_id    VARCHAR(66) NOT NULL,
+    commission_per_billion  bigint NOT NULL,
+    blocks_nominations      bool NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (era_index, validator_account_id),
+    CONSTRAINT sub_era_validator_commission_fk_account
+        FOREIGN KEY (validator_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_era_validator_commission_idx_validator_account_id_era_index
+    ON sub_era_validator_commission (validator_account_id, era_index);
\ No newline at end of file
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS sub_notification_generator_processed_era
+(
+    instance_id VARCHAR(128) NOT NULL DEFAULT 'default',
+    era_index   bigint NOT NULL,
+    created_at  TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (instance_id, era_index)
+);
+
+INSERT INTO sub_notification_generator_processed_era(instance_id, era_index)
+SELECT instance_id, period_index
+FROM sub_notification_generator_check_period
+WHERE notification_type_code = 'chain_validator_unclaimed_payout'
+AND cadence = 'era';
+
+DROP TABLE sub_notification_generator_check_period CASCADE;
\ No newline at end of file
@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS sub_notification_generator_check_period
+(
+    instance_id             VARCHAR(128) NOT NULL,
+    notification_type_code  VARCHAR(128) NOT NULL,
+    cadence                 VARCHAR(16) NOT NULL,
+    period_index            bigint NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    PRIMARY KEY (instance_id, notification_type_code, cadence, period_index)
+);
+
+INSERT INTO sub_notification_generator_check_period(instance_id, notification_type_code, cadence, period_index)
+SELECT E.instance_id, T.notification_type_code, 'era', E.era_index
+FROM sub_notification_generator_processed_era E
+CROSS JOIN (
+    VALUES ('chain_validator_unclaimed_payout'), ('chain_nominator_reward_below_expectation'), ('chain_nominator_stake_not_rewarded')
+) AS T(notification_type_code);
+
+DROP TABLE sub_notification_generator_processed_era CASCADE;
\ No newline at end of file
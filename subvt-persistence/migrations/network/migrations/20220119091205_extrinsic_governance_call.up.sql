@@ -0,0 +1,37 @@
+CREATE TABLE IF NOT EXISTS sub_extrinsic_governance_call
+(
+    id                      SERIAL PRIMARY KEY,
+    block_hash              VARCHAR(66) NOT NULL,
+    extrinsic_index         integer NOT NULL,
+    is_nested_call          boolean NOT NULL,
+    caller_account_id       VARCHAR(66) NOT NULL,
+    pallet_name             VARCHAR(64) NOT NULL,
+    call_name               VARCHAR(64) NOT NULL,
+    referendum_index        bigint,
+    proposal_index          bigint,
+    track_id                integer,
+    target_account_id       VARCHAR(66),
+    aye_balance             VARCHAR(128),
+    nay_balance             VARCHAR(128),
+    abstain_balance         VARCHAR(128),
+    conviction              integer,
+    amount                  VARCHAR(128),
+    is_successful           boolean NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_extrinsic_governance_call_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE,
+    CONSTRAINT sub_extrinsic_governance_call_fk_caller_account_id
+        FOREIGN KEY (caller_account_id)
+            REFERENCES sub_account (id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_extrinsic_governance_call_idx_block_hash
+    ON sub_extrinsic_governance_call (block_hash);
+
+CREATE INDEX sub_extrinsic_governance_call_idx_caller_account_id
+    ON sub_extrinsic_governance_call (caller_account_id);
\ No newline at end of file
@@ -0,0 +1 @@
+DROP TABLE sub_event_referenda_submitted CASCADE;
\ No newline at end of file
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS sub_event_referenda_submitted
+(
+    id                  SERIAL PRIMARY KEY,
+    block_hash          VARCHAR(66) NOT NULL,
+    extrinsic_index     integer,
+    event_index         integer NOT NULL,
+    referendum_index    bigint NOT NULL,
+    track_id            integer NOT NULL,
+    created_at          TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now(),
+    CONSTRAINT sub_event_referenda_submitted_fk_block
+        FOREIGN KEY (block_hash)
+            REFERENCES sub_block (hash)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+);
+
+CREATE INDEX sub_event_referenda_submitted_idx_block_hash_extrinsic_index
+    ON sub_event_referenda_submitted (block_hash, extrinsic_index);
+
+CREATE INDEX sub_event_referenda_submitted_idx_referendum_index
+    ON sub_event_referenda_submitted (referendum_index);
\ No newline at end of file
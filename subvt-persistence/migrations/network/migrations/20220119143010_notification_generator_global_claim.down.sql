@@ -0,0 +1,13 @@
+DELETE FROM sub_notification_generator_processed_referendum WHERE completed_at IS NULL;
+ALTER TABLE sub_notification_generator_processed_referendum DROP CONSTRAINT sub_notification_generator_processed_referendum_pkey;
+ALTER TABLE sub_notification_generator_processed_referendum DROP COLUMN completed_at;
+ALTER TABLE sub_notification_generator_processed_referendum RENAME COLUMN claimed_at TO created_at;
+ALTER TABLE sub_notification_generator_processed_referendum ADD PRIMARY KEY (instance_id, referendum_index);
+
+DELETE FROM sub_notification_generator_check_period WHERE completed_at IS NULL;
+ALTER TABLE sub_notification_generator_check_period DROP CONSTRAINT sub_notification_generator_check_period_pkey;
+ALTER TABLE sub_notification_generator_check_period DROP COLUMN completed_at;
+ALTER TABLE sub_notification_generator_check_period RENAME COLUMN claimed_at TO created_at;
+ALTER TABLE sub_notification_generator_check_period ADD PRIMARY KEY (instance_id, notification_type_code, cadence, period_index);
+
+ALTER TABLE sub_notification_generator_instance DROP COLUMN run_id;
\ No newline at end of file
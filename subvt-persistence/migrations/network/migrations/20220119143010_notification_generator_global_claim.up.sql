@@ -0,0 +1,24 @@
+ALTER TABLE sub_notification_generator_instance ADD COLUMN run_id VARCHAR(64) NOT NULL DEFAULT '';
+
+-- the periods of the checks are claimed globally, by a single instance each
+ALTER TABLE sub_notification_generator_check_period RENAME COLUMN created_at TO claimed_at;
+ALTER TABLE sub_notification_generator_check_period ADD COLUMN completed_at TIMESTAMP WITHOUT TIME ZONE;
+UPDATE sub_notification_generator_check_period SET completed_at = claimed_at;
+DELETE FROM sub_notification_generator_check_period A
+USING sub_notification_generator_check_period B
+WHERE A.notification_type_code = B.notification_type_code
+AND A.cadence = B.cadence
+AND A.period_index = B.period_index
+AND A.instance_id > B.instance_id;
+ALTER TABLE sub_notification_generator_check_period DROP CONSTRAINT sub_notification_generator_check_period_pkey;
+ALTER TABLE sub_notification_generator_check_period ADD PRIMARY KEY (notification_type_code, cadence, period_index);
+
+ALTER TABLE sub_notification_generator_processed_referendum RENAME COLUMN created_at TO claimed_at;
+ALTER TABLE sub_notification_generator_processed_referendum ADD COLUMN completed_at TIMESTAMP WITHOUT TIME ZONE;
+UPDATE sub_notification_generator_processed_referendum SET completed_at = claimed_at;
+DELETE FROM sub_notification_generator_processed_referendum A
+USING sub_notification_generator_processed_referendum B
+WHERE A.referendum_index = B.referendum_index
+AND A.instance_id > B.instance_id;
+ALTER TABLE sub_notification_generator_processed_referendum DROP CONSTRAINT sub_notification_generator_processed_referendum_pkey;
+ALTER TABLE sub_notification_generator_processed_referendum ADD PRIMARY KEY (referendum_index);
\ No newline at end of file
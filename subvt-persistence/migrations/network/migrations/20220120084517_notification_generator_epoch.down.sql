@@ -0,0 +1,2 @@
+ALTER TABLE sub_notification_generator_instance DROP COLUMN reserved_block_number;
+DROP TABLE sub_notification_generator_epoch CASCADE;
\ No newline at end of file
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS sub_notification_generator_epoch
+(
+    effective_block_number  bigint PRIMARY KEY,
+    instance_ids            VARCHAR(128)[] NOT NULL,
+    created_at              TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT now()
+);
+
+-- highest block number for which the instance has read the work partition
+ALTER TABLE sub_notification_generator_instance ADD COLUMN reserved_block_number bigint NOT NULL DEFAULT 0;
\ No newline at end of file
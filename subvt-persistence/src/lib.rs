@@ -1,3 +1,5 @@
 //! PostgreSQL and Redis persistence and query logic.
+pub mod memory;
 pub mod postgres;
+pub mod realtime;
 pub mod redis;
@@ -0,0 +1,230 @@
+//! In-process memory implementation of the real-time store. For tests only: the data is not
+//! visible to the other processes, i.e. to the other services.
+use crate::realtime::{RealtimeStore, RealtimeSubscription, RealtimeWriteBatch};
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::Sender;
+use std::sync::{Mutex, RwLock};
+
+#[derive(Default)]
+pub struct MemoryRealtimeStore {
+    values: RwLock<HashMap<String, String>>,
+    sets: RwLock<HashMap<String, HashSet<String>>>,
+    subscribers: Mutex<HashMap<String, Vec<Sender<anyhow::Result<u64>>>>>,
+}
+
+impl RealtimeStore for MemoryRealtimeStore {
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        Ok(self.values.read().unwrap().get(key).cloned())
+    }
+
+    fn get_multiple(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        let values = self.values.read().unwrap();
+        Ok(keys.iter().map(|key| values.get(key).cloned()).collect())
+    }
+
+    fn get_set_members(&self, key: &str) -> anyhow::Result<HashSet<String>> {
+        Ok(self
+            .sets
+            .read()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn write(&self, batch: &RealtimeWriteBatch) -> anyhow::Result<()> {
+        {
+            let mut values = self.values.write().unwrap();
+            let mut sets = self.sets.write().unwrap();
+            for prefix in &batch.delete_prefixes {
+                values.retain(|key, _| !key.starts_with(prefix));
+                sets.retain(|key, _| !key.starts_with(prefix));
+            }
+            for key in &batch.delete_keys {
+                values.remove(key);
+                sets.remove(key);
+            }
+            for (key, members) in &batch.set_additions {
+                sets.entry(key.clone())
+                    .or_default()
+                    .extend(members.iter().cloned());
+            }
+            for (key, value) in &batch.values {
+                values.insert(key.clone(), value.clone());
+            }
+        }
+        let mut subscribers = self.subscribers.lock().unwrap();
+        for (channel, block_number) in &batch.publications {
+            if let Some(senders) = subscribers.get_mut(channel) {
+                // drop the senders of the closed subscriptions
+                senders.retain(|sender| sender.send(Ok(*block_number)).is_ok());
+            }
+        }
+        Ok(())
+    }
+
+    fn subscribe(&self, channel: &str) -> anyhow::Result<RealtimeSubscription> {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry(channel.to_string())
+            .or_default()
+            .push(sender);
+        Ok(RealtimeSubscription::new(receiver))
+    }
+
+    fn increment(&self, key: &str, amount: u64) -> anyhow::Result<u64> {
+        let mut values = self.values.write().unwrap();
+        let value = match values.get(key) {
+            Some(value) => value.parse::<u64>()? + amount,
+            None => amount,
+        };
+        values.insert(key.to_string(), value.to_string());
+        Ok(value)
+    }
+
+    fn take_counters(&self, prefix: &str) -> anyhow::Result<HashMap<String, u64>> {
+        let mut values = self.values.write().unwrap();
+        let keys: Vec<String> = values
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        let mut counters = HashMap::new();
+        for key in keys {
+            if let Some(value) = values.remove(&key) {
+                counters.insert(key, value.parse()?);
+            }
+        }
+        Ok(counters)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryRealtimeStore;
+    use crate::realtime::{RealtimeStore, RealtimeWriteBatch};
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_write_and_get_multiple() {
+        let store = MemoryRealtimeStore::default();
+        let mut batch = RealtimeWriteBatch::default();
+        batch
+            .set("subvt:kusama:a", 1)
+            .set("subvt:kusama:b", "two")
+            .add_to_set(
+                "subvt:kusama:set",
+                HashSet::from(["x".to_string(), "y".to_string()]),
+            );
+        store.write(&batch).unwrap();
+        assert_eq!(
+            store
+                .get_multiple(&[
+                    "subvt:kusama:b".to_string(),
+                    "subvt:kusama:missing".to_string(),
+                    "subvt:kusama:a".to_string(),
+                ])
+                .unwrap(),
+            vec![Some("two".to_string()), None, Some("1".to_string())],
+        );
+        assert_eq!(store.get_set_members("subvt:kusama:set").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_write_applies_deletions_first() {
+        let store = MemoryRealtimeStore::default();
+        let mut batch = RealtimeWriteBatch::default();
+        batch
+            .set("subvt:kusama:validator:1", 1)
+            .set("subvt:kusama:validator:2", 2)
+            .set("subvt:kusama:other", 3);
+        store.write(&batch).unwrap();
+        let mut batch = RealtimeWriteBatch::default();
+        batch
+            .set("subvt:kusama:validator:3", 3)
+            .delete_with_prefix("subvt:kusama:validator:")
+            .delete("subvt:kusama:other");
+        store.write(&batch).unwrap();
+        assert_eq!(
+            store
+                .get_multiple(&[
+                    "subvt:kusama:validator:1".to_string(),
+                    "subvt:kusama:validator:2".to_string(),
+                    "subvt:kusama:validator:3".to_string(),
+                    "subvt:kusama:other".to_string(),
+                ])
+                .unwrap(),
+            vec![None, None, Some("3".to_string()), None],
+        );
+    }
+
+    #[test]
+    fn test_publish_after_write() {
+        let store = std::sync::Arc::new(MemoryRealtimeStore::default());
+        let subscription = store.subscribe("subvt:kusama:publish:block").unwrap();
+        let writer_store = store.clone();
+        let writer = std::thread::spawn(move || {
+            let mut batch = RealtimeWriteBatch::default();
+            batch
+                .publish_block_number("subvt:kusama:publish:block", 10)
+                .set("subvt:kusama:finalized_block_number", 10);
+            writer_store.write(&batch).unwrap();
+        });
+        let block_number = subscription.next_block_number().unwrap();
+        assert_eq!(block_number, 10);
+        // the value must be readable as soon as the block number is received
+        assert_eq!(
+            store.get("subvt:kusama:finalized_block_number").unwrap(),
+            Some("10".to_string()),
+        );
+        writer.join().unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_coalesce() {
+        let store = MemoryRealtimeStore::default();
+        let subscription = store
+            .subscribe("subvt:kusama:publish:block")
+            .unwrap()
+            .coalesce(true);
+        let plain_subscription = store.subscribe("subvt:kusama:publish:block").unwrap();
+        for block_number in 1..=3 {
+            let mut batch = RealtimeWriteBatch::default();
+            batch.publish_block_number("subvt:kusama:publish:block", block_number);
+            store.write(&batch).unwrap();
+        }
+        assert_eq!(subscription.next_block_number().unwrap(), 3);
+        assert_eq!(subscription.take_skipped_block_count(), 2);
+        assert_eq!(subscription.take_skipped_block_count(), 0);
+        for block_number in 1..=3 {
+            assert_eq!(
+                plain_subscription.next_block_number().unwrap(),
+                block_number
+            );
+        }
+        assert_eq!(plain_subscription.take_skipped_block_count(), 0);
+    }
+
+    #[test]
+    fn test_increment_and_take_counters() {
+        let store = MemoryRealtimeStore::default();
+        assert_eq!(store.increment("subvt:kusama:counter:a", 2).unwrap(), 2);
+        assert_eq!(store.increment("subvt:kusama:counter:a", 3).unwrap(), 5);
+        assert_eq!(store.increment("subvt:kusama:counter:b", 1).unwrap(), 1);
+        assert_eq!(store.increment("subvt:kusama:other", 1).unwrap(), 1);
+        let counters = store.take_counters("subvt:kusama:counter:").unwrap();
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters.get("subvt:kusama:counter:a"), Some(&5));
+        assert_eq!(counters.get("subvt:kusama:counter:b"), Some(&1));
+        assert!(store
+            .take_counters("subvt:kusama:counter:")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store.get("subvt:kusama:other").unwrap(),
+            Some("1".to_string())
+        );
+    }
+}
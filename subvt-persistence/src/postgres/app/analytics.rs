@@ -0,0 +1,103 @@
+//! Storage related to the anonymized analytics counters of the services, kept as hourly totals.
+use crate::postgres::app::PostgreSQLAppStorage;
+use chrono::NaiveDateTime;
+use std::collections::HashMap;
+use subvt_types::app::{AnalyticsSummary, HourlyAnalyticsCount};
+
+impl PostgreSQLAppStorage {
+    /// Adds the counts to the totals of the current hour.
+    pub async fn add_analytics_counts(
+        &self,
+        service_name: &str,
+        chain: &str,
+        counts: &HashMap<String, u64>,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        for (metric, count) in counts {
+            sqlx::query(
+                r#"
+                INSERT INTO app_analytics_counter (service_name, chain, metric, period_start, count)
+                VALUES ($1, $2, $3, date_trunc('hour', now() AT TIME ZONE 'UTC'), $4)
+                ON CONFLICT (service_name, chain, metric, period_start) DO UPDATE
+                SET count = app_analytics_counter.count + EXCLUDED.count,
+                updated_at = now()
+                "#,
+            )
+            .bind(service_name)
+            .bind(chain)
+            .bind(metric)
+            .bind(*count as i64)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Gets the totals of each metric of each service on each chain in the given number of most
+    /// recent hours, optionally for a single service.
+    pub async fn get_analytics_summaries(
+        &self,
+        hour_count: u32,
+        maybe_service_name: Option<&str>,
+    ) -> anyhow::Result<Vec<AnalyticsSummary>> {
+        let db_summaries: Vec<(String, String, String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT service_name, chain, metric, SUM(count)::bigint, MAX(count)
+            FROM app_analytics_counter
+            WHERE period_start > date_trunc('hour', now() AT TIME ZONE 'UTC') - make_interval(hours => $1)
+            AND ($2::VARCHAR IS NULL OR service_name = $2)
+            GROUP BY service_name, chain, metric
+            ORDER BY service_name ASC, chain ASC, metric ASC
+            "#,
+        )
+        .bind(hour_count as i32)
+        .bind(maybe_service_name)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_summaries
+            .into_iter()
+            .map(|db_summary| AnalyticsSummary {
+                service_name: db_summary.0,
+                chain: db_summary.1,
+                metric: db_summary.2,
+                total_count: db_summary.3 as u64,
+                max_hourly_count: db_summary.4 as u64,
+            })
+            .collect())
+    }
+
+    /// Gets the hourly totals of the metric in the given number of most recent hours, summed
+    /// over the services and the chains, optionally for a single service. Hours without any
+    /// count are omitted.
+    pub async fn get_hourly_analytics_counts(
+        &self,
+        metric: &str,
+        hour_count: u32,
+        maybe_service_name: Option<&str>,
+    ) -> anyhow::Result<Vec<HourlyAnalyticsCount>> {
+        let db_counts: Vec<(NaiveDateTime, i64)> = sqlx::query_as(
+            r#"
+            SELECT period_start, SUM(count)::bigint
+            FROM app_analytics_counter
+            WHERE metric = $1
+            AND period_start > date_trunc('hour', now() AT TIME ZONE 'UTC') - make_interval(hours => $2)
+            AND ($3::VARCHAR IS NULL OR service_name = $3)
+            GROUP BY period_start
+            ORDER BY period_start ASC
+            "#,
+        )
+        .bind(metric)
+        .bind(hour_count as i32)
+        .bind(maybe_service_name)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_counts
+            .into_iter()
+            .map(|db_count| HourlyAnalyticsCount {
+                period_start: db_count.0,
+                count: db_count.1 as u64,
+            })
+            .collect())
+    }
+}
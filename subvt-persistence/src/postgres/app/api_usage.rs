@@ -0,0 +1,239 @@
+//! Storage related to the public API keys of the users and the API usage statistics. The
+//! request counts are kept in the real-time store by the report service, and get added here
+//! periodically. Dates are in UTC.
+use crate::postgres::app::PostgreSQLAppStorage;
+use chrono::{NaiveDate, NaiveDateTime};
+use subvt_types::app::{ApiUsage, DailyApiUsage, UserApiKey, UserApiUsageSummary};
+
+impl PostgreSQLAppStorage {
+    /// Creates a new API key for the user. Only the hash and the prefix of the key get
+    /// persisted, so the returned key cannot be retrieved again.
+    pub async fn save_user_api_key(&self, user_id: u32) -> anyhow::Result<UserApiKey> {
+        let (key,): (String,) = sqlx::query_as("SELECT encode(gen_random_bytes(32), 'hex')")
+            .fetch_one(&self.connection_pool)
+            .await?;
+        let key_prefix = key[..8].to_string();
+        let (id, created_at): (i32, NaiveDateTime) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_api_key (user_id, key_hash, key_prefix)
+            VALUES ($1, encode(digest($2, 'sha256'), 'hex'), $3)
+            RETURNING id, created_at
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(&key)
+        .bind(&key_prefix)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(UserApiKey {
+            id: id as u32,
+            user_id,
+            key: Some(key),
+            key_prefix,
+            created_at,
+        })
+    }
+
+    /// Gets the non-deleted API keys of the user, without the keys themselves.
+    pub async fn get_user_api_keys(&self, user_id: u32) -> anyhow::Result<Vec<UserApiKey>> {
+        let db_api_keys: Vec<(i32, String, NaiveDateTime)> = sqlx::query_as(
+            r#"
+            SELECT id, key_prefix, created_at
+            FROM app_user_api_key
+            WHERE user_id = $1 AND deleted_at IS NULL
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_api_keys
+            .into_iter()
+            .map(|db_api_key| UserApiKey {
+                id: db_api_key.0 as u32,
+                user_id,
+                key: None,
+                key_prefix: db_api_key.1,
+                created_at: db_api_key.2,
+            })
+            .collect())
+    }
+
+    /// Soft-deletes the API key, keeping its usage history. Returns `false` if the user doesn't
+    /// have a non-deleted key with the given id.
+    pub async fn delete_user_api_key(&self, user_id: u32, api_key_id: u32) -> anyhow::Result<bool> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            UPDATE app_user_api_key
+            SET deleted_at = now()
+            WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(api_key_id as i32)
+        .bind(user_id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.is_some())
+    }
+
+    /// Gets the id of the non-deleted API key.
+    pub async fn get_api_key_id(&self, key: &str) -> anyhow::Result<Option<u32>> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT id
+            FROM app_user_api_key
+            WHERE key_hash = encode(digest($1, 'sha256'), 'hex')
+            AND deleted_at IS NULL
+            "#,
+        )
+        .bind(key)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.map(|id| id.0 as u32))
+    }
+
+    /// Adds to the request count of the API key on the date, given as the number of days since
+    /// the Unix epoch.
+    pub async fn add_api_usage(
+        &self,
+        api_key_id: u32,
+        epoch_day: u32,
+        request_count: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO app_user_api_usage (api_key_id, usage_date, request_count)
+            VALUES ($1, DATE '1970-01-01' + $2, $3)
+            ON CONFLICT (api_key_id, usage_date) DO UPDATE
+            SET request_count = app_user_api_usage.request_count + EXCLUDED.request_count,
+            updated_at = now()
+            "#,
+        )
+        .bind(api_key_id as i32)
+        .bind(epoch_day as i32)
+        .bind(request_count as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the daily request counts of all the keys of the user, including the deleted ones,
+    /// for the given number of most recent days including the current day.
+    pub async fn get_user_api_usage(
+        &self,
+        user_id: u32,
+        day_count: u32,
+    ) -> anyhow::Result<Vec<ApiUsage>> {
+        let db_usages: Vec<(i32, NaiveDate, i64)> = sqlx::query_as(
+            r#"
+            SELECT U.api_key_id, U.usage_date, U.request_count
+            FROM app_user_api_usage U
+            INNER JOIN app_user_api_key K ON K.id = U.api_key_id
+            WHERE K.user_id = $1
+            AND U.usage_date > (now() AT TIME ZONE 'UTC')::date - $2
+            ORDER BY U.usage_date DESC, U.api_key_id ASC
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(day_count as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_usages
+            .into_iter()
+            .map(|db_usage| ApiUsage {
+                api_key_id: db_usage.0 as u32,
+                date: db_usage.1,
+                request_count: db_usage.2 as u64,
+            })
+            .collect())
+    }
+
+    /// Gets the total request count of all the keys of the user on the current day.
+    pub async fn get_user_today_api_request_count(&self, user_id: u32) -> anyhow::Result<u64> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(U.request_count), 0)::bigint
+            FROM app_user_api_usage U
+            INNER JOIN app_user_api_key K ON K.id = U.api_key_id
+            WHERE K.user_id = $1
+            AND U.usage_date = (now() AT TIME ZONE 'UTC')::date
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(count.0 as u64)
+    }
+
+    /// Gets the total API usage of each user with at least one request in the given number of
+    /// most recent days, busiest user first.
+    pub async fn get_user_api_usage_summaries(
+        &self,
+        day_count: u32,
+    ) -> anyhow::Result<Vec<UserApiUsageSummary>> {
+        let db_summaries: Vec<(i32, i64, i64, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT
+                user_id,
+                COUNT(DISTINCT api_key_id),
+                SUM(request_count)::bigint,
+                COUNT(DISTINCT usage_date),
+                MAX(daily_request_count)::bigint
+            FROM (
+                SELECT
+                    K.user_id,
+                    U.api_key_id,
+                    U.usage_date,
+                    U.request_count,
+                    SUM(U.request_count) OVER (PARTITION BY K.user_id, U.usage_date)
+                        AS daily_request_count
+                FROM app_user_api_usage U
+                INNER JOIN app_user_api_key K ON K.id = U.api_key_id
+                WHERE U.usage_date > (now() AT TIME ZONE 'UTC')::date - $1
+            ) AS usage
+            GROUP BY user_id
+            ORDER BY SUM(request_count) DESC, user_id ASC
+            "#,
+        )
+        .bind(day_count as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_summaries
+            .into_iter()
+            .map(|db_summary| UserApiUsageSummary {
+                user_id: db_summary.0 as u32,
+                api_key_count: db_summary.1 as u32,
+                request_count: db_summary.2 as u64,
+                active_day_count: db_summary.3 as u32,
+                max_daily_request_count: db_summary.4 as u64,
+            })
+            .collect())
+    }
+
+    /// Gets the total API usage of all the users for each of the given number of most recent
+    /// days that has at least one request, most recent day first.
+    pub async fn get_daily_api_usage(&self, day_count: u32) -> anyhow::Result<Vec<DailyApiUsage>> {
+        let db_daily_usages: Vec<(NaiveDate, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT U.usage_date, COUNT(DISTINCT K.user_id), SUM(U.request_count)::bigint
+            FROM app_user_api_usage U
+            INNER JOIN app_user_api_key K ON K.id = U.api_key_id
+            WHERE U.usage_date > (now() AT TIME ZONE 'UTC')::date - $1
+            GROUP BY U.usage_date
+            ORDER BY U.usage_date DESC
+            "#,
+        )
+        .bind(day_count as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_daily_usages
+            .into_iter()
+            .map(|db_daily_usage| DailyApiUsage {
+                date: db_daily_usage.0,
+                user_count: db_daily_usage.1 as u32,
+                request_count: db_daily_usage.2 as u64,
+            })
+            .collect())
+    }
+}
@@ -4,11 +4,18 @@ use log::debug;
 use sqlx::{Pool, Postgres};
 use subvt_config::Config;
 
+pub mod analytics;
+pub mod api_usage;
 pub mod network;
 pub mod notification;
 pub mod notification_channel;
+pub mod notification_mute;
 pub mod notification_type;
+pub mod retention;
+pub mod session;
 pub mod user;
+pub mod validator_claim;
+pub mod validator_group;
 
 pub struct PostgreSQLAppStorage {
     _uri: String,
@@ -31,4 +38,11 @@ impl PostgreSQLAppStorage {
             connection_pool,
         })
     }
+    /// Runs the pending application database migrations.
+    pub async fn run_migrations(&self) -> anyhow::Result<()> {
+        sqlx::migrate!("migrations/app/migrations")
+            .run(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
 }
@@ -1,7 +1,18 @@
 //! Application storage related to the networks supported by SubVT.
 use crate::postgres::app::PostgreSQLAppStorage;
 use subvt_types::app::db::PostgresNetwork;
-use subvt_types::app::Network;
+use subvt_types::app::{
+    Network, NetworkDefaultNotificationRule, NotificationPeriodType, UserNotificationRuleParameter,
+};
+
+type PostgresNetworkDefaultNotificationRule = (
+    i32,
+    i32,
+    String,
+    Option<String>,
+    NotificationPeriodType,
+    i32,
+);
 
 impl PostgreSQLAppStorage {
     pub async fn get_network_by_id(&self, id: u32) -> anyhow::Result<Network> {
@@ -31,6 +42,56 @@ impl PostgreSQLAppStorage {
         Ok(record_count.0 > 0)
     }
 
+    /// Gets the id of the network with the given genesis hash, if it's registered.
+    pub async fn get_network_id_by_hash(&self, hash: &str) -> anyhow::Result<Option<u32>> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT id FROM app_network
+            WHERE hash = $1
+            "#,
+        )
+        .bind(hash)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.map(|id| id.0 as u32))
+    }
+
+    /// Registers a new network. The id is assigned by the caller, because the network id is
+    /// also set in the configuration of the network's services.
+    pub async fn save_network(
+        &self,
+        id: u32,
+        hash: &str,
+        name: &str,
+        ss58_prefix: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO app_network (id, hash, name, ss58_prefix)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(id as i32)
+        .bind(hash)
+        .bind(name)
+        .bind(ss58_prefix as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the next available network id.
+    pub async fn get_next_network_id(&self) -> anyhow::Result<u32> {
+        let max_id: (Option<i32>,) = sqlx::query_as(
+            r#"
+            SELECT MAX(id) FROM app_network
+            "#,
+        )
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(max_id.0.unwrap_or(0) as u32 + 1)
+    }
+
     pub async fn get_networks(&self) -> anyhow::Result<Vec<Network>> {
         Ok(sqlx::query_as(
             r#"
@@ -46,4 +107,120 @@ impl PostgreSQLAppStorage {
             .map(PostgresNetwork::into)
             .collect())
     }
+
+    pub async fn get_network_default_notification_rules(
+        &self,
+        network_id: u32,
+    ) -> anyhow::Result<Vec<NetworkDefaultNotificationRule>> {
+        let db_rules: Vec<PostgresNetworkDefaultNotificationRule> = sqlx::query_as(
+            r#"
+            SELECT id, network_id, notification_type_code, name, period_type, period
+            FROM app_network_default_notification_rule
+            WHERE network_id = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(network_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut rules = Vec::new();
+        for db_rule in db_rules {
+            let db_parameters: Vec<(i32, i32, String, i16, String)> = sqlx::query_as(
+                r#"
+                SELECT P.network_default_notification_rule_id, P.notification_param_type_id, ANPT.code, ANPT."order", P."value"
+                FROM app_network_default_notification_rule_param P, app_notification_param_type ANPT
+                WHERE P.notification_param_type_id = ANPT.id
+                AND P.network_default_notification_rule_id = $1
+                ORDER BY ANPT."order" ASC
+                "#,
+            )
+                .bind(db_rule.0)
+                .fetch_all(&self.connection_pool)
+                .await?;
+            rules.push(NetworkDefaultNotificationRule {
+                id: db_rule.0 as u32,
+                network_id: db_rule.1 as u32,
+                notification_type_code: db_rule.2,
+                name: db_rule.3,
+                period_type: db_rule.4,
+                period: db_rule.5 as u16,
+                parameters: db_parameters
+                    .iter()
+                    .map(|db_parameter| {
+                        let mut parameter = UserNotificationRuleParameter::from(db_parameter);
+                        parameter.user_notification_rule_id = 0;
+                        parameter
+                    })
+                    .collect(),
+            });
+        }
+        Ok(rules)
+    }
+
+    /// Saves the default rule of the network for the notification type, replacing the existing
+    /// one and its parameters if exists.
+    pub async fn save_network_default_notification_rule(
+        &self,
+        rule: &NetworkDefaultNotificationRule,
+    ) -> anyhow::Result<u32> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let result: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_network_default_notification_rule (network_id, notification_type_code, name, period_type, period)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (network_id, notification_type_code) DO UPDATE
+            SET name = EXCLUDED.name, period_type = EXCLUDED.period_type, period = EXCLUDED.period, updated_at = now()
+            RETURNING id
+            "#,
+        )
+            .bind(rule.network_id as i32)
+            .bind(&rule.notification_type_code)
+            .bind(&rule.name)
+            .bind(&rule.period_type)
+            .bind(rule.period as i32)
+            .fetch_one(&mut transaction)
+            .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM app_network_default_notification_rule_param
+            WHERE network_default_notification_rule_id = $1
+            "#,
+        )
+        .bind(result.0)
+        .execute(&mut transaction)
+        .await?;
+        for parameter in &rule.parameters {
+            sqlx::query(
+                r#"
+                INSERT INTO app_network_default_notification_rule_param (network_default_notification_rule_id, notification_param_type_id, value)
+                VALUES ($1, $2, $3)
+                "#,
+            )
+                .bind(result.0)
+                .bind(parameter.parameter_type_id as i32)
+                .bind(&parameter.value)
+                .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(result.0 as u32)
+    }
+
+    pub async fn delete_network_default_notification_rule(
+        &self,
+        network_id: u32,
+        notification_type_code: &str,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_network_default_notification_rule
+            WHERE network_id = $1 AND notification_type_code = $2
+            "#,
+        )
+        .bind(network_id as i32)
+        .bind(notification_type_code)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
 }
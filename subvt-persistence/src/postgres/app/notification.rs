@@ -13,7 +13,7 @@ impl PostgreSQLAppStorage {
     ) -> anyhow::Result<Vec<NotificationParamType>> {
         let db_notification_param_types: Vec<PostgresNotificationParamType> = sqlx::query_as(
             r#"
-            SELECT id, notification_type_code, code, "order", type, "min", "max", is_optional
+            SELECT id, notification_type_code, code, "order", type, "min", "max", default_value, is_optional, description
             FROM app_notification_param_type
             WHERE notification_type_code = $1
             ORDER BY notification_type_code ASC, "order" ASC
@@ -32,12 +32,17 @@ impl PostgreSQLAppStorage {
                 type_: db_notification_param_type.4.clone(),
                 min: db_notification_param_type.5.clone(),
                 max: db_notification_param_type.6.clone(),
-                is_optional: db_notification_param_type.7,
+                default_value: db_notification_param_type.7.clone(),
+                is_optional: db_notification_param_type.8,
+                description: db_notification_param_type.9.clone(),
             })
             .collect();
         Ok(param_types)
     }
 
+    /// Gets the rules of the type that apply to the validator, either directly or through a
+    /// validator group that the rule's user owns or has subscribed to, except the rules of the
+    /// users who have muted the notifications of the type for the validator.
     pub async fn get_notification_rules_for_validator(
         &self,
         notification_type_code: &str,
@@ -78,6 +83,46 @@ impl PostgreSQLAppStorage {
                         )
                     )
                 )
+                OR
+                (
+                    UNR.validator_group_id IS NOT NULL
+                    AND EXISTS (
+                        SELECT G.id
+                        FROM app_validator_group G
+                        INNER JOIN app_validator_group_validator GV
+                            ON GV.validator_group_id = G.id
+                        WHERE G.id = UNR.validator_group_id
+                        AND G.network_id = $2
+                        AND G.deleted_at IS NULL
+                        AND GV.validator_account_id = $3
+                        AND (
+                            G.user_id = UNR.user_id
+                            OR EXISTS (
+                                SELECT UVG.user_id
+                                FROM app_user_validator_group UVG
+                                WHERE UVG.validator_group_id = G.id
+                                AND UVG.user_id = UNR.user_id
+                            )
+                        )
+                    )
+                )
+            )
+            AND NOT EXISTS (
+                SELECT M.id
+                FROM app_user_notification_mute M
+                WHERE M.user_id = UNR.user_id
+                AND M.expires_at > now()
+                AND (M.notification_type_code IS NULL OR M.notification_type_code = $1)
+                AND (
+                    M.user_validator_id IS NULL
+                    OR EXISTS (
+                        SELECT UV3.id
+                        FROM app_user_validator UV3
+                        WHERE UV3.id = M.user_validator_id
+                        AND UV3.network_id = $2
+                        AND UV3.validator_account_id = $3
+                    )
+                )
             );
             "#,
         )
@@ -98,11 +143,63 @@ impl PostgreSQLAppStorage {
         Ok(result)
     }
 
+    /// Gets the rules of the type of the users who watch the nominator on the network, except
+    /// the rules of the users who have muted the notifications of the type for all accounts.
+    /// Nominator rules are not attached to user validators, they apply to all the watched
+    /// nominators of the user.
+    pub async fn get_notification_rules_for_nominator(
+        &self,
+        notification_type_code: &str,
+        network_id: u32,
+        nominator_account_id: &AccountId,
+    ) -> anyhow::Result<Vec<UserNotificationRule>> {
+        let rule_ids: Vec<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT "id"
+            FROM app_user_notification_rule UNR
+            WHERE UNR.notification_type_code = $1
+            AND UNR.deleted_at IS NULL
+            AND (UNR.network_id IS NULL OR UNR.network_id = $2)
+            AND EXISTS (
+                SELECT UN.id
+                FROM app_user_nominator UN
+                WHERE UN.user_id = UNR.user_id
+                AND UN.network_id = $2
+                AND UN.nominator_account_id = $3
+                AND UN.deleted_at IS NULL
+            )
+            AND NOT EXISTS (
+                SELECT M.id
+                FROM app_user_notification_mute M
+                WHERE M.user_id = UNR.user_id
+                AND M.expires_at > now()
+                AND M.user_validator_id IS NULL
+                AND (M.notification_type_code IS NULL OR M.notification_type_code = $1)
+            );
+            "#,
+        )
+        .bind(notification_type_code)
+        .bind(network_id as i32)
+        .bind(nominator_account_id.to_string())
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut result = Vec::new();
+        for rule_id in rule_ids {
+            if let Some(rule) = self
+                .get_user_notification_rule_by_id(rule_id.0 as u32)
+                .await?
+            {
+                result.push(rule);
+            }
+        }
+        Ok(result)
+    }
+
     pub async fn save_notification(&self, notification: &Notification) -> anyhow::Result<u32> {
         let result: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO app_notification (user_id, user_notification_rule_id, network_id, period_type, period, validator_account_id, validator_account_json, notification_type_code, user_notification_channel_id, notification_channel_code, notification_target, data_json, log)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            INSERT INTO app_notification (user_id, user_notification_rule_id, network_id, period_type, period, validator_account_id, validator_account_json, notification_type_code, user_notification_channel_id, notification_channel_code, notification_target, data_json, deep_link_json, log)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             RETURNING id
             "#,
         )
@@ -118,6 +215,7 @@ impl PostgreSQLAppStorage {
             .bind(&notification.notification_channel_code)
             .bind(&notification.notification_target)
             .bind(&notification.data_json)
+            .bind(&notification.deep_link_json)
             .bind(&notification.log)
             .fetch_one(&self.connection_pool)
             .await?;
@@ -131,7 +229,7 @@ impl PostgreSQLAppStorage {
     ) -> anyhow::Result<Vec<Notification>> {
         let db_notifications: Vec<PostgresNotification> = sqlx::query_as(
             r#"
-            SELECT id, user_id, user_notification_rule_id, network_id, period_type, period, validator_account_id, validator_account_json, notification_type_code, user_notification_channel_id, notification_channel_code, notification_target, data_json, log
+            SELECT id, user_id, user_notification_rule_id, network_id, period_type, period, validator_account_id, validator_account_json, notification_type_code, user_notification_channel_id, notification_channel_code, notification_target, data_json, deep_link_json, log
             FROM app_notification
             WHERE processing_started_at IS NULL
             AND period_type = $1
@@ -246,4 +344,29 @@ impl PostgreSQLAppStorage {
         .await?;
         Ok(())
     }
+
+    /// Logs the would-be delivery of a notification of a dry-run channel, along with the
+    /// override recipient that it gets delivered to instead, if any.
+    pub async fn save_notification_dry_run(
+        &self,
+        notification: &Notification,
+        maybe_override_target: Option<&str>,
+        content: &str,
+    ) -> anyhow::Result<u32> {
+        let result: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_notification_dry_run (notification_id, notification_channel_code, notification_target, override_target, content)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+        .bind(notification.id as i32)
+        .bind(&notification.notification_channel_code)
+        .bind(&notification.notification_target)
+        .bind(maybe_override_target)
+        .bind(content)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0 as u32)
+    }
 }
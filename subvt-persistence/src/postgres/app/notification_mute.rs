@@ -0,0 +1,119 @@
+//! Storage related to the temporary notification mutes of the users.
+use crate::postgres::app::PostgreSQLAppStorage;
+use chrono::NaiveDateTime;
+use subvt_types::app::{Notification, UserNotificationMute};
+
+type PostgresUserNotificationMute = (i32, i32, Option<i32>, Option<String>, NaiveDateTime);
+
+impl From<PostgresUserNotificationMute> for UserNotificationMute {
+    fn from(db_mute: PostgresUserNotificationMute) -> Self {
+        UserNotificationMute {
+            id: db_mute.0 as u32,
+            user_id: db_mute.1 as u32,
+            user_validator_id: db_mute.2.map(|id| id as u32),
+            notification_type_code: db_mute.3,
+            expires_at: db_mute.4,
+        }
+    }
+}
+
+impl PostgreSQLAppStorage {
+    /// Gets the mutes of the user that haven't expired yet, in the order of expiry.
+    pub async fn get_user_notification_mutes(
+        &self,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<UserNotificationMute>> {
+        let db_mutes: Vec<PostgresUserNotificationMute> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, user_validator_id, notification_type_code, expires_at
+            FROM app_user_notification_mute
+            WHERE user_id = $1
+            AND expires_at > now()
+            ORDER BY expires_at ASC, id ASC
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_mutes
+            .into_iter()
+            .map(UserNotificationMute::from)
+            .collect())
+    }
+
+    /// Saves a mute that expires after the given duration from now.
+    pub async fn save_user_notification_mute(
+        &self,
+        user_id: u32,
+        maybe_user_validator_id: Option<u32>,
+        maybe_notification_type_code: Option<&str>,
+        duration_seconds: u64,
+    ) -> anyhow::Result<UserNotificationMute> {
+        let db_mute: PostgresUserNotificationMute = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_notification_mute (user_id, user_validator_id, notification_type_code, expires_at)
+            VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+            RETURNING id, user_id, user_validator_id, notification_type_code, expires_at
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(maybe_user_validator_id.map(|id| id as i32))
+        .bind(maybe_notification_type_code)
+        .bind(duration_seconds as f64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(db_mute.into())
+    }
+
+    /// Deletes the mute, which ends it before its expiry.
+    pub async fn delete_user_notification_mute(
+        &self,
+        user_id: u32,
+        id: u32,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_user_notification_mute
+            WHERE id = $1 AND user_id = $2
+            "#,
+        )
+        .bind(id as i32)
+        .bind(user_id as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Checks whether the notification is muted at the moment, so that a notification that has
+    /// been created before the mute doesn't get sent during the mute.
+    pub async fn notification_is_muted(&self, notification: &Notification) -> anyhow::Result<bool> {
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT M.id
+                FROM app_user_notification_mute M
+                WHERE M.user_id = $1
+                AND M.expires_at > now()
+                AND (M.notification_type_code IS NULL OR M.notification_type_code = $2)
+                AND (
+                    M.user_validator_id IS NULL
+                    OR EXISTS (
+                        SELECT UV.id
+                        FROM app_user_validator UV
+                        WHERE UV.id = M.user_validator_id
+                        AND UV.network_id = $3
+                        AND UV.validator_account_id = $4
+                    )
+                )
+            )
+            "#,
+        )
+        .bind(notification.user_id as i32)
+        .bind(&notification.notification_type_code)
+        .bind(notification.network_id as i32)
+        .bind(notification.validator_account_id.to_string())
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+}
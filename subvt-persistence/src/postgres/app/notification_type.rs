@@ -1,15 +1,77 @@
 //! Notification type (authorship, offences, new/lost/changed nominations, etc.) related storage.
 use crate::postgres::app::PostgreSQLAppStorage;
-use subvt_types::app::NotificationType;
+use subvt_types::app::{NotificationSeverity, NotificationType};
 
 impl PostgreSQLAppStorage {
+    /// Fetches the parameter types and the supported channels of the notification type,
+    /// and generates its parameter schema.
+    async fn populate_notification_type(
+        &self,
+        notification_type: &mut NotificationType,
+    ) -> anyhow::Result<()> {
+        notification_type.param_types = self
+            .get_notification_parameter_types(&notification_type.code)
+            .await?;
+        notification_type.supported_channel_codes = self
+            .get_notification_type_channel_codes(&notification_type.code)
+            .await?;
+        notification_type.param_schema = notification_type.get_param_schema();
+        Ok(())
+    }
+
+    /// Codes of the channels that `subvt-notification-sender` can deliver the notifications of
+    /// the type to.
+    pub async fn get_notification_type_channel_codes(
+        &self,
+        notification_type_code: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        let db_channel_codes: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT channel_code
+            FROM app_notification_type_channel
+            WHERE notification_type_code = $1
+            ORDER BY channel_code ASC
+            "#,
+        )
+        .bind(notification_type_code)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_channel_codes
+            .into_iter()
+            .map(|db_channel_code| db_channel_code.0)
+            .collect())
+    }
+
+    /// Whether the notifications of the type can be delivered to the channel of the user
+    /// notification channel.
+    pub async fn notification_type_supports_user_notification_channel(
+        &self,
+        notification_type_code: &str,
+        user_notification_channel_id: u32,
+    ) -> anyhow::Result<bool> {
+        let record_count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT NTC.id)
+            FROM app_notification_type_channel NTC
+            INNER JOIN app_user_notification_channel UNC
+                ON UNC.notification_channel_code = NTC.channel_code
+            WHERE NTC.notification_type_code = $1 AND UNC.id = $2
+            "#,
+        )
+        .bind(notification_type_code)
+        .bind(user_notification_channel_id as i32)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(record_count.0 > 0)
+    }
+
     pub async fn get_notification_type_by_code(
         &self,
         code: &str,
     ) -> anyhow::Result<NotificationType> {
         let mut notification_type = sqlx::query_as(
             r#"
-            SELECT code
+            SELECT code, severity, is_operator_only
             FROM app_notification_type
             WHERE code = $1
             "#,
@@ -17,21 +79,23 @@ impl PostgreSQLAppStorage {
         .bind(code)
         .fetch_one(&self.connection_pool)
         .await
-        .map(|db_notification_type: (String,)| NotificationType {
-            code: db_notification_type.0,
-            param_types: Vec::new(),
-        })?;
-        // get params
-        notification_type.param_types = self
-            .get_notification_parameter_types(&notification_type.code)
+        .map(
+            |db_notification_type: (String, NotificationSeverity, bool)| NotificationType {
+                code: db_notification_type.0,
+                severity: db_notification_type.1,
+                is_operator_only: db_notification_type.2,
+                ..Default::default()
+            },
+        )?;
+        self.populate_notification_type(&mut notification_type)
             .await?;
         Ok(notification_type)
     }
 
     pub async fn get_notification_types(&self) -> anyhow::Result<Vec<NotificationType>> {
-        let db_notification_types: Vec<(String,)> = sqlx::query_as(
+        let db_notification_types: Vec<(String, NotificationSeverity, bool)> = sqlx::query_as(
             r#"
-            SELECT code
+            SELECT code, severity, is_operator_only
             FROM app_notification_type
             ORDER BY code ASC
             "#,
@@ -39,18 +103,17 @@ impl PostgreSQLAppStorage {
         .fetch_all(&self.connection_pool)
         .await?;
         let mut notification_types: Vec<NotificationType> = db_notification_types
-            .iter()
-            .cloned()
+            .into_iter()
             .map(|db_notification_type| NotificationType {
                 code: db_notification_type.0,
-                param_types: Vec::new(),
+                severity: db_notification_type.1,
+                is_operator_only: db_notification_type.2,
+                ..Default::default()
             })
             .collect();
-        // get params for each notification type
+        // get params and channels for each notification type
         for notification_type in notification_types.iter_mut() {
-            notification_type.param_types = self
-                .get_notification_parameter_types(&notification_type.code)
-                .await?;
+            self.populate_notification_type(notification_type).await?;
         }
         Ok(notification_types)
     }
@@ -68,6 +131,23 @@ impl PostgreSQLAppStorage {
         Ok(record_count.0 > 0)
     }
 
+    /// Rules of operator-only notification types can only be created by the verified
+    /// validator operators. `false` if the type doesn't exist.
+    pub async fn notification_type_is_operator_only(&self, code: &str) -> anyhow::Result<bool> {
+        let maybe_is_operator_only: Option<(bool,)> = sqlx::query_as(
+            r#"
+            SELECT is_operator_only FROM app_notification_type
+            WHERE code = $1
+            "#,
+        )
+        .bind(code)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_is_operator_only
+            .map(|is_operator_only| is_operator_only.0)
+            .unwrap_or(false))
+    }
+
     pub async fn parameter_exists_for_notification_type(
         &self,
         notification_type_code: &str,
@@ -0,0 +1,52 @@
+//! Storage related to the pruning of the notifications by age and the disk space usage of the
+//! application database tables. See the `retention` module of the network storage for the
+//! retention policies.
+use crate::postgres::app::PostgreSQLAppStorage;
+use subvt_types::rdb::TableSpaceUsage;
+
+impl PostgreSQLAppStorage {
+    /// Deletes the notifications of the network created before the retention period, along with
+    /// their dry-run records. Returns the number of deleted notifications.
+    pub async fn prune_notifications(
+        &self,
+        network_id: u32,
+        retention_days: u32,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_notification
+            WHERE network_id = $1
+            AND created_at < (now() AT TIME ZONE 'UTC') - make_interval(days => $2)
+            "#,
+        )
+        .bind(network_id as i32)
+        .bind(retention_days as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Space usage of each table, largest first.
+    pub async fn get_table_space_usages(&self) -> anyhow::Result<Vec<TableSpaceUsage>> {
+        let db_usages: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT C.relname::text, pg_total_relation_size(C.oid) AS total_bytes, GREATEST(C.reltuples, 0)::bigint
+            FROM pg_class C
+            INNER JOIN pg_namespace N ON N.oid = C.relnamespace
+            WHERE N.nspname = 'public'
+            AND C.relkind IN ('r', 'p')
+            ORDER BY total_bytes DESC, C.relname ASC
+            "#,
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_usages
+            .into_iter()
+            .map(|db_usage| TableSpaceUsage {
+                table_name: db_usage.0,
+                total_bytes: db_usage.1 as u64,
+                row_count_estimate: db_usage.2 as u64,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,127 @@
+//! Storage related to the wallet sign-in challenges and the user sessions.
+use crate::postgres::app::PostgreSQLAppStorage;
+use chrono::NaiveDateTime;
+use subvt_types::app::{UserAuthChallenge, UserSession};
+
+impl PostgreSQLAppStorage {
+    /// Saves a new challenge with a random nonce that expires after the given duration.
+    /// Expired challenges get deleted along the way.
+    pub async fn save_user_auth_challenge(
+        &self,
+        user_id: u32,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<UserAuthChallenge> {
+        sqlx::query(
+            r#"
+            DELETE FROM app_user_auth_challenge
+            WHERE expires_at <= now()
+            "#,
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        let (nonce, expires_at): (String, NaiveDateTime) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_auth_challenge (user_id, expires_at)
+            VALUES ($1, now() + make_interval(secs => $2))
+            RETURNING nonce, expires_at
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(ttl_seconds as f64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(UserAuthChallenge {
+            message: UserAuthChallenge::get_message(&nonce),
+            nonce,
+            expires_at,
+        })
+    }
+
+    /// Deletes the challenge so that it cannot be used again. Returns `false` if there's no
+    /// unexpired challenge of the user with the nonce.
+    pub async fn consume_user_auth_challenge(
+        &self,
+        user_id: u32,
+        nonce: &str,
+    ) -> anyhow::Result<bool> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            DELETE FROM app_user_auth_challenge
+            WHERE user_id = $1 AND nonce = $2 AND expires_at > now()
+            RETURNING id
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(nonce)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.is_some())
+    }
+
+    /// Creates a session with a random token that expires after the given duration. Only the
+    /// hash of the token gets persisted. Expired sessions get deleted along the way.
+    pub async fn save_user_session(
+        &self,
+        user_id: u32,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<UserSession> {
+        sqlx::query(
+            r#"
+            DELETE FROM app_user_session
+            WHERE expires_at <= now()
+            "#,
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        let (token,): (String,) = sqlx::query_as("SELECT encode(gen_random_bytes(32), 'hex')")
+            .fetch_one(&self.connection_pool)
+            .await?;
+        let (expires_at,): (NaiveDateTime,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_session (user_id, token_hash, expires_at)
+            VALUES ($1, encode(digest($2, 'sha256'), 'hex'), now() + make_interval(secs => $3))
+            RETURNING expires_at
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(&token)
+        .bind(ttl_seconds as f64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(UserSession {
+            user_id,
+            token,
+            expires_at,
+        })
+    }
+
+    /// Gets the user id of the unexpired session with the given token.
+    pub async fn get_session_user_id(&self, token: &str) -> anyhow::Result<Option<u32>> {
+        let maybe_user_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            SELECT user_id
+            FROM app_user_session
+            WHERE token_hash = encode(digest($1, 'sha256'), 'hex')
+            AND expires_at > now()
+            "#,
+        )
+        .bind(token)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_user_id.map(|user_id| user_id.0 as u32))
+    }
+
+    /// Deletes the session with the given token, i.e. signs the user out.
+    pub async fn delete_user_session(&self, token: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_user_session
+            WHERE token_hash = encode(digest($1, 'sha256'), 'hex')
+            "#,
+        )
+        .bind(token)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
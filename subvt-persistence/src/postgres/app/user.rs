@@ -1,12 +1,13 @@
 //! Storage related to SubVT application users.
 use crate::postgres::app::PostgreSQLAppStorage;
+use sqlx::postgres::PgListener;
 use std::collections::HashSet;
 use std::str::FromStr;
 use subvt_types::app::db::{
     PostgresUserNotificationChannel, PostgresUserNotificationRule, PostgresUserValidator,
 };
 use subvt_types::app::{
-    NotificationPeriodType, User, UserNotificationChannel, UserNotificationRule,
+    NotificationPeriodType, User, UserNominator, UserNotificationChannel, UserNotificationRule,
     UserNotificationRuleParameter, UserValidator,
 };
 use subvt_types::crypto::AccountId;
@@ -52,13 +53,93 @@ impl PostgreSQLAppStorage {
         Ok(record_count.0 > 0)
     }
 
+    pub async fn get_user_by_id(&self, id: u32) -> anyhow::Result<Option<User>> {
+        let maybe_db_user: Option<(i32, String, bool)> = sqlx::query_as(
+            r#"
+            SELECT id, public_key_hex, is_verified_operator FROM app_user
+            WHERE id = $1
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_db_user.map(|db_user| User {
+            id: db_user.0 as u32,
+            public_key_hex: db_user.1,
+            is_verified_operator: db_user.2,
+        }))
+    }
+
+    pub async fn get_user_by_public_key_hex(
+        &self,
+        public_key_hex: &str,
+    ) -> anyhow::Result<Option<User>> {
+        let maybe_db_user: Option<(i32, String, bool)> = sqlx::query_as(
+            r#"
+            SELECT id, public_key_hex, is_verified_operator FROM app_user
+            WHERE public_key_hex = $1
+            "#,
+        )
+        .bind(public_key_hex)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_db_user.map(|db_user| User {
+            id: db_user.0 as u32,
+            public_key_hex: db_user.1,
+            is_verified_operator: db_user.2,
+        }))
+    }
+
+    /// Gets the fiat currency code that the user prefers the amounts to be displayed in,
+    /// `None` if the user has no preference or doesn't exist.
+    pub async fn get_user_currency_code(&self, user_id: u32) -> anyhow::Result<Option<String>> {
+        let maybe_currency_code: Option<(Option<String>,)> = sqlx::query_as(
+            r#"
+            SELECT currency_code FROM app_user
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_currency_code.and_then(|currency_code| currency_code.0))
+    }
+
+    /// Saves the fiat currency preference of the user, `None` clears the preference.
+    pub async fn save_user_currency_code(
+        &self,
+        user_id: u32,
+        currency_code: Option<&str>,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE app_user
+            SET currency_code = $1, updated_at = now()
+            WHERE id = $2
+            "#,
+        )
+        .bind(currency_code)
+        .bind(user_id as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Starts listening to the `app_user_event` channel, which receives a `UserEvent` JSON
+    /// payload whenever a notification gets persisted or a user validator gets added/deleted.
+    pub async fn get_user_event_listener(&self) -> anyhow::Result<PgListener> {
+        let mut listener = PgListener::connect_with(&self.connection_pool).await?;
+        listener.listen("app_user_event").await?;
+        Ok(listener)
+    }
+
     pub async fn get_user_notification_channels(
         &self,
         user_id: u32,
     ) -> anyhow::Result<Vec<UserNotificationChannel>> {
-        let db_user_notification_channels: Vec<(i32, i32, String, String)> = sqlx::query_as(
+        Ok(sqlx::query_as(
             r#"
-            SELECT id, user_id, notification_channel_code, target
+            SELECT id, user_id, notification_channel_code, target, timezone, locale
             FROM app_user_notification_channel
             WHERE user_id = $1 AND deleted_at IS NULL
             ORDER BY id ASC
@@ -66,16 +147,10 @@ impl PostgreSQLAppStorage {
         )
         .bind(user_id as i32)
         .fetch_all(&self.connection_pool)
-        .await?;
-        Ok(db_user_notification_channels
-            .iter()
-            .map(|db_user_notification_channel| UserNotificationChannel {
-                id: db_user_notification_channel.0 as u32,
-                user_id: db_user_notification_channel.1 as u32,
-                channel_code: db_user_notification_channel.2.clone(),
-                target: db_user_notification_channel.3.clone(),
-            })
-            .collect())
+        .await?
+        .into_iter()
+        .map(PostgresUserNotificationChannel::into)
+        .collect())
     }
 
     pub async fn user_notification_channel_exists(
@@ -120,19 +195,59 @@ impl PostgreSQLAppStorage {
     ) -> anyhow::Result<u32> {
         let result: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO app_user_notification_channel (user_id, notification_channel_code, target)
-            VALUES ($1, $2, $3)
+            INSERT INTO app_user_notification_channel (user_id, notification_channel_code, target, timezone, locale)
+            VALUES ($1, $2, $3, $4, $5)
             RETURNING id
             "#,
         )
         .bind(user_notification_channel.user_id as i32)
         .bind(&user_notification_channel.channel_code)
         .bind(&user_notification_channel.target)
+        .bind(&user_notification_channel.timezone)
+        .bind(&user_notification_channel.locale)
         .fetch_one(&self.connection_pool)
         .await?;
         Ok(result.0 as u32)
     }
 
+    /// Timezone and locale of the notification channel, `None` if the channel doesn't exist.
+    pub async fn get_user_notification_channel_locale(
+        &self,
+        id: u32,
+    ) -> anyhow::Result<Option<(Option<String>, Option<String>)>> {
+        Ok(sqlx::query_as(
+            r#"
+            SELECT timezone, locale FROM app_user_notification_channel
+            WHERE id = $1
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?)
+    }
+
+    /// Saves the timezone and the locale of the notification channel, `None` clears the value.
+    pub async fn save_user_notification_channel_locale(
+        &self,
+        id: u32,
+        timezone: Option<&str>,
+        locale: Option<&str>,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE app_user_notification_channel
+            SET timezone = $1, locale = $2
+            WHERE id = $3 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(timezone)
+        .bind(locale)
+        .bind(id as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn delete_user_notification_channel(&self, id: u32) -> anyhow::Result<bool> {
         let maybe_id: Option<(i32,)> = sqlx::query_as(
             r#"
@@ -242,6 +357,122 @@ impl PostgreSQLAppStorage {
         Ok(maybe_id.is_some() && maybe_id.unwrap().0 == id as i32)
     }
 
+    pub async fn user_nominator_exists_by_id(
+        &self,
+        user_id: u32,
+        user_nominator_id: u32,
+    ) -> anyhow::Result<bool> {
+        let record_count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT id) FROM app_user_nominator
+            WHERE id = $1 AND user_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_nominator_id as i32)
+        .bind(user_id as i32)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(record_count.0 > 0)
+    }
+
+    pub async fn user_nominator_exists(
+        &self,
+        user_nominator: &UserNominator,
+    ) -> anyhow::Result<bool> {
+        let record_count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(DISTINCT id) FROM app_user_nominator
+            WHERE user_id = $1
+            AND network_id = $2
+            AND nominator_account_id = $3
+            AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_nominator.user_id as i32)
+        .bind(user_nominator.network_id as i32)
+        .bind(user_nominator.nominator_account_id.to_string())
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(record_count.0 > 0)
+    }
+
+    pub async fn get_user_nominators(&self, user_id: u32) -> anyhow::Result<Vec<UserNominator>> {
+        let db_user_nominators: Vec<(i32, i32, i32, String)> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, network_id, nominator_account_id
+            FROM app_user_nominator
+            WHERE user_id = $1 AND deleted_at IS NULL
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut user_nominators = Vec::new();
+        for db_user_nominator in db_user_nominators {
+            user_nominators.push(UserNominator {
+                id: db_user_nominator.0 as u32,
+                user_id: db_user_nominator.1 as u32,
+                network_id: db_user_nominator.2 as u32,
+                nominator_account_id: AccountId::from_str(&db_user_nominator.3)?,
+            });
+        }
+        Ok(user_nominators)
+    }
+
+    /// Gets the distinct nominator accounts watched by the users on the network.
+    pub async fn get_watched_nominator_account_ids(
+        &self,
+        network_id: u32,
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT nominator_account_id
+            FROM app_user_nominator
+            WHERE network_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(network_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
+    pub async fn save_user_nominator(&self, user_nominator: &UserNominator) -> anyhow::Result<u32> {
+        let result: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_nominator (user_id, network_id, nominator_account_id)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(user_nominator.user_id as i32)
+        .bind(user_nominator.network_id as i32)
+        .bind(user_nominator.nominator_account_id.to_string())
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0 as u32)
+    }
+
+    pub async fn delete_user_nominator(&self, id: u32) -> anyhow::Result<bool> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            UPDATE app_user_nominator
+            SET deleted_at = now()
+            WHERE id = $1
+            RETURNING id
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.is_some() && maybe_id.unwrap().0 == id as i32)
+    }
+
     pub async fn get_user_notification_rule_validators(
         &self,
         rule_id: u32,
@@ -274,7 +505,7 @@ impl PostgreSQLAppStorage {
     ) -> anyhow::Result<Vec<UserNotificationChannel>> {
         Ok(sqlx::query_as(
             r#"
-            SELECT id, user_id, notification_channel_code, target
+            SELECT id, user_id, notification_channel_code, target, timezone, locale
             FROM app_user_notification_channel
             WHERE id IN (
                 SELECT user_notification_channel_id
@@ -294,6 +525,59 @@ impl PostgreSQLAppStorage {
         .collect())
     }
 
+    pub async fn get_user_notification_rule_channel_unsubscribe_token(
+        &self,
+        rule_id: u32,
+        channel_id: u32,
+    ) -> anyhow::Result<Option<String>> {
+        let maybe_token: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT unsubscribe_token
+            FROM app_user_notification_rule_channel
+            WHERE user_notification_rule_id = $1 AND user_notification_channel_id = $2
+            "#,
+        )
+        .bind(rule_id as i32)
+        .bind(channel_id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_token.map(|token| token.0))
+    }
+
+    pub async fn user_notification_rule_channel_exists_by_unsubscribe_token(
+        &self,
+        unsubscribe_token: &str,
+    ) -> anyhow::Result<bool> {
+        let record_count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(*) FROM app_user_notification_rule_channel
+            WHERE unsubscribe_token = $1
+            "#,
+        )
+        .bind(unsubscribe_token)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(record_count.0 > 0)
+    }
+
+    /// Removes the channel from the notification rule that the token belongs to. The rule
+    /// and the channel themselves are kept.
+    pub async fn delete_user_notification_rule_channel_by_unsubscribe_token(
+        &self,
+        unsubscribe_token: &str,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_user_notification_rule_channel
+            WHERE unsubscribe_token = $1
+            "#,
+        )
+        .bind(unsubscribe_token)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn get_user_notification_rule_parameters(
         &self,
         rule_id: u32,
@@ -322,7 +606,7 @@ impl PostgreSQLAppStorage {
     ) -> anyhow::Result<Option<UserNotificationRule>> {
         let maybe_db_notification_rule: Option<PostgresUserNotificationRule> = sqlx::query_as(
             r#"
-            SELECT id, user_id, notification_type_code, name, network_id, is_for_all_validators, period_type, period, notes
+            SELECT id, user_id, notification_type_code, name, network_id, is_for_all_validators, period_type, period, notes, validator_group_id
             FROM app_user_notification_rule
             WHERE id = $1
             "#
@@ -362,6 +646,9 @@ impl PostgreSQLAppStorage {
                 .get_user_notification_rule_parameters(db_notification_rule.0 as u32)
                 .await?,
             notes: db_notification_rule.8,
+            validator_group_id: db_notification_rule
+                .9
+                .map(|validator_group_id| validator_group_id as u32),
         }))
     }
 
@@ -430,7 +717,7 @@ impl PostgreSQLAppStorage {
         user_id: u32,
         notification_type_code: &str,
         (name, notes): (Option<&str>, Option<&str>),
-        (network_id, is_for_all_validators): (Option<u32>, bool),
+        (network_id, is_for_all_validators, validator_group_id): (Option<u32>, bool, Option<u32>),
         (period_type, period): (&NotificationPeriodType, u16),
         (user_validator_ids, user_notification_channel_ids, parameters): (
             &HashSet<u32>,
@@ -442,8 +729,8 @@ impl PostgreSQLAppStorage {
         // insert notification rule
         let result: (i32,) = sqlx::query_as(
             r#"
-            INSERT INTO app_user_notification_rule (user_id, notification_type_code, name, network_id, is_for_all_validators, period_type, period, notes)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            INSERT INTO app_user_notification_rule (user_id, notification_type_code, name, network_id, is_for_all_validators, period_type, period, notes, validator_group_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
             RETURNING id
             "#,
         )
@@ -455,6 +742,7 @@ impl PostgreSQLAppStorage {
             .bind(period_type)
             .bind(period as i32)
             .bind(notes)
+            .bind(validator_group_id.map(|validator_group_id| validator_group_id as i32))
             .fetch_one(&self.connection_pool)
             .await?;
         let user_notification_rule_id = result.0;
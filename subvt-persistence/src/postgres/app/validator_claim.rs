@@ -0,0 +1,207 @@
+//! Storage related to the validator claims, i.e. the validators that the users have proven
+//! to operate by signing a challenge with the stash key or a session key of the validator.
+use crate::postgres::app::PostgreSQLAppStorage;
+use chrono::NaiveDateTime;
+use std::str::FromStr;
+use subvt_types::app::{UserOperatorValidator, ValidatorClaimChallenge};
+use subvt_types::crypto::AccountId;
+
+impl PostgreSQLAppStorage {
+    /// Creates a claim challenge that expires after the given duration. Expired challenges
+    /// get deleted along the way.
+    pub async fn save_validator_claim_challenge(
+        &self,
+        user_id: u32,
+        network_id: u32,
+        validator_account_id: &AccountId,
+        ttl_seconds: u64,
+    ) -> anyhow::Result<ValidatorClaimChallenge> {
+        sqlx::query(
+            r#"
+            DELETE FROM app_user_validator_claim_challenge
+            WHERE expires_at <= now()
+            "#,
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        let (nonce, expires_at): (String, NaiveDateTime) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_validator_claim_challenge (user_id, network_id, validator_account_id, expires_at)
+            VALUES ($1, $2, $3, now() + make_interval(secs => $4))
+            RETURNING nonce, expires_at
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(network_id as i32)
+        .bind(validator_account_id.to_string())
+        .bind(ttl_seconds as f64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(ValidatorClaimChallenge {
+            network_id,
+            validator_account_id: validator_account_id.clone(),
+            message: ValidatorClaimChallenge::get_message(user_id, validator_account_id, &nonce),
+            nonce,
+            expires_at,
+        })
+    }
+
+    /// Deletes the challenge so that it cannot be used again. Returns `false` if there's no
+    /// unexpired challenge of the user for the validator with the nonce.
+    pub async fn consume_validator_claim_challenge(
+        &self,
+        user_id: u32,
+        network_id: u32,
+        validator_account_id: &AccountId,
+        nonce: &str,
+    ) -> anyhow::Result<bool> {
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            DELETE FROM app_user_validator_claim_challenge
+            WHERE user_id = $1 AND network_id = $2 AND validator_account_id = $3 AND nonce = $4 AND expires_at > now()
+            RETURNING id
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(network_id as i32)
+        .bind(validator_account_id.to_string())
+        .bind(nonce)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_id.is_some())
+    }
+
+    /// Saves the claimed validator and flags the user as a verified operator. Claiming a
+    /// validator again updates the claim type. Returns the id of the claim.
+    pub async fn save_user_operator_validator(
+        &self,
+        user_id: u32,
+        network_id: u32,
+        validator_account_id: &AccountId,
+        is_session_key_claim: bool,
+    ) -> anyhow::Result<u32> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let result: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_user_operator_validator (user_id, network_id, validator_account_id, is_session_key_claim)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, network_id, validator_account_id) DO UPDATE
+            SET is_session_key_claim = EXCLUDED.is_session_key_claim
+            RETURNING id
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(network_id as i32)
+        .bind(validator_account_id.to_string())
+        .bind(is_session_key_claim)
+        .fetch_one(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE app_user
+            SET is_verified_operator = true, updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id as i32)
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(result.0 as u32)
+    }
+
+    pub async fn get_user_operator_validator_by_id(
+        &self,
+        user_id: u32,
+        id: u32,
+    ) -> anyhow::Result<Option<UserOperatorValidator>> {
+        let maybe_db_operator_validator: Option<(i32, i32, i32, String, bool, NaiveDateTime)> =
+            sqlx::query_as(
+                r#"
+                SELECT id, user_id, network_id, validator_account_id, is_session_key_claim, created_at
+                FROM app_user_operator_validator
+                WHERE user_id = $1 AND id = $2
+                "#,
+            )
+            .bind(user_id as i32)
+            .bind(id as i32)
+            .fetch_optional(&self.connection_pool)
+            .await?;
+        match maybe_db_operator_validator {
+            Some(db_operator_validator) => Ok(Some(UserOperatorValidator {
+                id: db_operator_validator.0 as u32,
+                user_id: db_operator_validator.1 as u32,
+                network_id: db_operator_validator.2 as u32,
+                validator_account_id: AccountId::from_str(&db_operator_validator.3)?,
+                is_session_key_claim: db_operator_validator.4,
+                created_at: db_operator_validator.5,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn get_user_operator_validators(
+        &self,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<UserOperatorValidator>> {
+        let db_operator_validators: Vec<(i32, i32, i32, String, bool, NaiveDateTime)> =
+            sqlx::query_as(
+                r#"
+                SELECT id, user_id, network_id, validator_account_id, is_session_key_claim, created_at
+                FROM app_user_operator_validator
+                WHERE user_id = $1
+                ORDER BY id ASC
+                "#,
+            )
+            .bind(user_id as i32)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        let mut operator_validators = Vec::new();
+        for db_operator_validator in db_operator_validators {
+            operator_validators.push(UserOperatorValidator {
+                id: db_operator_validator.0 as u32,
+                user_id: db_operator_validator.1 as u32,
+                network_id: db_operator_validator.2 as u32,
+                validator_account_id: AccountId::from_str(&db_operator_validator.3)?,
+                is_session_key_claim: db_operator_validator.4,
+                created_at: db_operator_validator.5,
+            });
+        }
+        Ok(operator_validators)
+    }
+
+    /// Deletes the claim, and clears the verified operator flag of the user if it was the last
+    /// claim of the user. Returns `false` if the claim doesn't exist.
+    pub async fn delete_user_operator_validator(
+        &self,
+        user_id: u32,
+        id: u32,
+    ) -> anyhow::Result<bool> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_user_operator_validator
+            WHERE user_id = $1 AND id = $2
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(id as i32)
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE app_user
+            SET is_verified_operator = EXISTS(
+                SELECT id FROM app_user_operator_validator
+                WHERE user_id = $1
+            ), updated_at = now()
+            WHERE id = $1
+            "#,
+        )
+        .bind(user_id as i32)
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
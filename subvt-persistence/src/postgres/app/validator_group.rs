@@ -0,0 +1,337 @@
+//! Storage related to the validator groups and the users' subscriptions to them.
+use crate::postgres::app::PostgreSQLAppStorage;
+use std::str::FromStr;
+use subvt_types::app::ValidatorGroup;
+use subvt_types::crypto::AccountId;
+
+type PostgresValidatorGroup = (i32, i32, i32, String, String);
+
+impl PostgreSQLAppStorage {
+    async fn get_validator_group_validator_account_ids(
+        &self,
+        validator_group_id: u32,
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id
+            FROM app_validator_group_validator
+            WHERE validator_group_id = $1
+            ORDER BY created_at ASC, validator_account_id ASC
+            "#,
+        )
+        .bind(validator_group_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
+    async fn get_validator_group_subscriber_count(
+        &self,
+        validator_group_id: u32,
+    ) -> anyhow::Result<u32> {
+        let count: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COUNT(user_id)
+            FROM app_user_validator_group
+            WHERE validator_group_id = $1
+            "#,
+        )
+        .bind(validator_group_id as i32)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(count.0 as u32)
+    }
+
+    async fn get_validator_group(
+        &self,
+        db_validator_group: PostgresValidatorGroup,
+    ) -> anyhow::Result<ValidatorGroup> {
+        let id = db_validator_group.0 as u32;
+        Ok(ValidatorGroup {
+            id,
+            user_id: db_validator_group.1 as u32,
+            network_id: db_validator_group.2 as u32,
+            name: db_validator_group.3,
+            share_code: db_validator_group.4,
+            validator_account_ids: self.get_validator_group_validator_account_ids(id).await?,
+            subscriber_count: self.get_validator_group_subscriber_count(id).await?,
+        })
+    }
+
+    /// Gets the non-deleted validator group with the given id.
+    pub async fn get_validator_group_by_id(
+        &self,
+        id: u32,
+    ) -> anyhow::Result<Option<ValidatorGroup>> {
+        let maybe_db_validator_group: Option<PostgresValidatorGroup> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, network_id, name, share_code
+            FROM app_validator_group
+            WHERE id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        match maybe_db_validator_group {
+            Some(db_validator_group) => {
+                Ok(Some(self.get_validator_group(db_validator_group).await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the non-deleted validator group with the given share code.
+    pub async fn get_validator_group_by_share_code(
+        &self,
+        share_code: &str,
+    ) -> anyhow::Result<Option<ValidatorGroup>> {
+        let maybe_db_validator_group: Option<PostgresValidatorGroup> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, network_id, name, share_code
+            FROM app_validator_group
+            WHERE share_code = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(share_code)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        match maybe_db_validator_group {
+            Some(db_validator_group) => {
+                Ok(Some(self.get_validator_group(db_validator_group).await?))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the non-deleted validator groups that the user owns or has subscribed to.
+    pub async fn get_user_validator_groups(
+        &self,
+        user_id: u32,
+    ) -> anyhow::Result<Vec<ValidatorGroup>> {
+        let db_validator_groups: Vec<PostgresValidatorGroup> = sqlx::query_as(
+            r#"
+            SELECT id, user_id, network_id, name, share_code
+            FROM app_validator_group G
+            WHERE G.deleted_at IS NULL
+            AND (
+                G.user_id = $1
+                OR EXISTS (
+                    SELECT UVG.user_id
+                    FROM app_user_validator_group UVG
+                    WHERE UVG.validator_group_id = G.id
+                    AND UVG.user_id = $1
+                )
+            )
+            ORDER BY G.id ASC
+            "#,
+        )
+        .bind(user_id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut validator_groups = Vec::new();
+        for db_validator_group in db_validator_groups {
+            validator_groups.push(self.get_validator_group(db_validator_group).await?);
+        }
+        Ok(validator_groups)
+    }
+
+    /// Checks whether the user owns or has subscribed to the non-deleted validator group.
+    pub async fn user_has_access_to_validator_group(
+        &self,
+        user_id: u32,
+        validator_group_id: u32,
+    ) -> anyhow::Result<bool> {
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT G.id
+                FROM app_validator_group G
+                WHERE G.id = $2
+                AND G.deleted_at IS NULL
+                AND (
+                    G.user_id = $1
+                    OR EXISTS (
+                        SELECT UVG.user_id
+                        FROM app_user_validator_group UVG
+                        WHERE UVG.validator_group_id = G.id
+                        AND UVG.user_id = $1
+                    )
+                )
+            )
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(validator_group_id as i32)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+
+    /// Saves a new validator group with a random share code, returns its id.
+    pub async fn save_validator_group(
+        &self,
+        user_id: u32,
+        network_id: u32,
+        name: &str,
+        validator_account_ids: &[AccountId],
+    ) -> anyhow::Result<u32> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let result: (i32,) = sqlx::query_as(
+            r#"
+            INSERT INTO app_validator_group (user_id, network_id, name)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(network_id as i32)
+        .bind(name)
+        .fetch_one(&mut transaction)
+        .await?;
+        for validator_account_id in validator_account_ids {
+            sqlx::query(
+                r#"
+                INSERT INTO app_validator_group_validator (validator_group_id, validator_account_id)
+                VALUES ($1, $2)
+                ON CONFLICT (validator_group_id, validator_account_id) DO NOTHING
+                "#,
+            )
+            .bind(result.0)
+            .bind(validator_account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(result.0 as u32)
+    }
+
+    /// Updates the name and replaces the validators of the group.
+    pub async fn update_validator_group(
+        &self,
+        id: u32,
+        name: &str,
+        validator_account_ids: &[AccountId],
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE app_validator_group
+            SET name = $1, updated_at = now()
+            WHERE id = $2
+            "#,
+        )
+        .bind(name)
+        .bind(id as i32)
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM app_validator_group_validator
+            WHERE validator_group_id = $1
+            "#,
+        )
+        .bind(id as i32)
+        .execute(&mut transaction)
+        .await?;
+        for validator_account_id in validator_account_ids {
+            sqlx::query(
+                r#"
+                INSERT INTO app_validator_group_validator (validator_group_id, validator_account_id)
+                VALUES ($1, $2)
+                ON CONFLICT (validator_group_id, validator_account_id) DO NOTHING
+                "#,
+            )
+            .bind(id as i32)
+            .bind(validator_account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Soft-deletes the group along with the notification rules of all users attached to it.
+    pub async fn delete_validator_group(&self, id: u32) -> anyhow::Result<bool> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let maybe_id: Option<(i32,)> = sqlx::query_as(
+            r#"
+            UPDATE app_validator_group
+            SET deleted_at = now()
+            WHERE id = $1 AND deleted_at IS NULL
+            RETURNING id
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_optional(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE app_user_notification_rule
+            SET deleted_at = now()
+            WHERE validator_group_id = $1 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(id as i32)
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(maybe_id.is_some())
+    }
+
+    pub async fn save_user_validator_group_subscription(
+        &self,
+        user_id: u32,
+        validator_group_id: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO app_user_validator_group (user_id, validator_group_id)
+            VALUES ($1, $2)
+            ON CONFLICT (user_id, validator_group_id) DO NOTHING
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(validator_group_id as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes the subscription along with the user's notification rules attached to the group.
+    /// Returns `false` if the user hasn't subscribed to the group.
+    pub async fn delete_user_validator_group_subscription(
+        &self,
+        user_id: u32,
+        validator_group_id: u32,
+    ) -> anyhow::Result<bool> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let result = sqlx::query(
+            r#"
+            DELETE FROM app_user_validator_group
+            WHERE user_id = $1 AND validator_group_id = $2
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(validator_group_id as i32)
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE app_user_notification_rule
+            SET deleted_at = now()
+            WHERE user_id = $1 AND validator_group_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(user_id as i32)
+        .bind(validator_group_id as i32)
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,3 +1,4 @@
 //! PostgreSQL-related modules.
 pub mod app;
 pub mod network;
+pub(crate) mod page;
@@ -0,0 +1,142 @@
+//! Storage related to the era-boundary consistency checks of `subvt-auditor`.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::substrate::Balance;
+
+/// Era totals as indexed by the block processor.
+#[derive(Clone, Debug, Default)]
+pub struct IndexedEraTotals {
+    pub total_reward_points: Option<u32>,
+    /// Sum of the reward points of the era validators.
+    pub validator_reward_points_sum: u64,
+    pub total_validator_reward: Option<Balance>,
+    pub total_stake: Balance,
+    pub active_validator_count: u32,
+    /// `None` if no block of the era has been indexed.
+    pub first_block_number: Option<u64>,
+}
+
+/// Mismatch between an indexed value and the value read from the chain.
+/// Missing values are `None`.
+#[derive(Clone, Debug)]
+pub struct AuditDiscrepancy {
+    pub check_code: String,
+    pub indexed_value: Option<String>,
+    pub chain_value: Option<String>,
+}
+
+type PostgresIndexedEraTotals = (Option<i64>, i64, Option<String>, String, i64, Option<i64>);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn get_indexed_era_totals(
+        &self,
+        era_index: u32,
+    ) -> anyhow::Result<Option<IndexedEraTotals>> {
+        let maybe_db_totals: Option<PostgresIndexedEraTotals> = sqlx::query_as(
+            r#"
+            SELECT E.total_reward_points, (
+                SELECT COALESCE(SUM(reward_points), 0)::bigint
+                FROM sub_era_validator
+                WHERE era_index = E.index
+            ), E.total_validator_reward, E.total_stake, (
+                SELECT COUNT(id)
+                FROM sub_era_validator
+                WHERE era_index = E.index
+                AND is_active = true
+            ), (
+                SELECT MIN(number)
+                FROM sub_block
+                WHERE era_index = E.index
+            )
+            FROM sub_era E
+            WHERE E.index = $1
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        let db_totals = match maybe_db_totals {
+            Some(db_totals) => db_totals,
+            None => return Ok(None),
+        };
+        Ok(Some(IndexedEraTotals {
+            total_reward_points: db_totals.0.map(|points| points as u32),
+            validator_reward_points_sum: db_totals.1 as u64,
+            total_validator_reward: match &db_totals.2 {
+                Some(reward) => Some(reward.parse()?),
+                None => None,
+            },
+            total_stake: db_totals.3.parse()?,
+            active_validator_count: db_totals.4 as u32,
+            first_block_number: db_totals.5.map(|number| number as u64),
+        }))
+    }
+
+    /// Total amount of the slashes indexed in the given block.
+    pub async fn get_block_slashed_amount(&self, block_number: u64) -> anyhow::Result<Balance> {
+        let total: (String,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(S.amount::numeric), 0)::text
+            FROM sub_event_slashed S
+            INNER JOIN sub_block B
+                ON B.hash = S.block_hash
+            WHERE B.number = $1
+            "#,
+        )
+        .bind(block_number as i64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(total.0.parse()?)
+    }
+
+    pub async fn era_is_audited(&self, era_index: u32) -> anyhow::Result<bool> {
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT era_index
+                FROM sub_era_audit
+                WHERE era_index = $1
+            )
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+
+    /// Saves the audit of the era along with its discrepancies, if any.
+    pub async fn save_era_audit(
+        &self,
+        era_index: u32,
+        discrepancies: &[AuditDiscrepancy],
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO sub_era_audit (era_index, discrepancy_count)
+            VALUES ($1, $2)
+            ON CONFLICT (era_index) DO NOTHING
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(discrepancies.len() as i32)
+        .execute(&mut transaction)
+        .await?;
+        for discrepancy in discrepancies {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_era_audit_discrepancy (era_index, check_code, indexed_value, chain_value)
+                VALUES ($1, $2, $3, $4)
+                "#,
+            )
+            .bind(era_index as i64)
+            .bind(&discrepancy.check_code)
+            .bind(&discrepancy.indexed_value)
+            .bind(&discrepancy.chain_value)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
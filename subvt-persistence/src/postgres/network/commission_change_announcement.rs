@@ -0,0 +1,106 @@
+//! Storage related to the commission changes announced by the validators ahead of the
+//! on-chain change, either with a `system.remark` or declared to the 1KV programme.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::HashMap;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::subvt::{AnnouncedCommissionChange, CommissionChangeAnnouncementSource};
+
+type PostgresAnnouncedCommissionChange = (String, String, i64, Option<i64>, i64);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_remark_commission_change_announcement(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        validator_account_id: &AccountId,
+        (commission_per_billion, maybe_effective_era_index): (u32, Option<u32>),
+    ) -> anyhow::Result<()> {
+        self.save_account(validator_account_id).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO sub_commission_change_announcement (validator_account_id, source, commission_per_billion, effective_era_index, block_hash, extrinsic_index)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(CommissionChangeAnnouncementSource::Remark.to_string())
+        .bind(commission_per_billion as i64)
+        .bind(maybe_effective_era_index.map(|era_index| era_index as i64))
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Saves the commission declared to 1KV only if it's different than the last declared
+    /// commission of the validator, returns `true` if saved.
+    pub async fn save_onekv_commission_change_announcement(
+        &self,
+        validator_account_id: &AccountId,
+        commission_per_billion: u32,
+    ) -> anyhow::Result<bool> {
+        self.save_account(validator_account_id).await?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sub_commission_change_announcement (validator_account_id, source, commission_per_billion)
+            SELECT $1, $2, $3
+            WHERE NOT EXISTS (
+                SELECT id FROM (
+                    SELECT id, commission_per_billion
+                    FROM sub_commission_change_announcement
+                    WHERE validator_account_id = $1 AND source = $2
+                    ORDER BY id DESC
+                    LIMIT 1
+                ) AS L
+                WHERE L.commission_per_billion = $3
+            )
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(CommissionChangeAnnouncementSource::OneKV.to_string())
+        .bind(commission_per_billion as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gets the latest announcement of each validator made in the last `max_age_days` days,
+    /// excluding the ones with an effective era before the given era.
+    pub async fn get_announced_commission_changes(
+        &self,
+        era_index: u32,
+        max_age_days: u32,
+    ) -> anyhow::Result<HashMap<AccountId, AnnouncedCommissionChange>> {
+        let db_announcements: Vec<PostgresAnnouncedCommissionChange> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, source, commission_per_billion, effective_era_index, announced_at
+            FROM (
+                SELECT DISTINCT ON (validator_account_id) validator_account_id, source, commission_per_billion, effective_era_index, (EXTRACT(EPOCH FROM created_at) * 1000)::bigint AS announced_at
+                FROM sub_commission_change_announcement
+                WHERE created_at > now() - make_interval(days => $2)
+                ORDER BY validator_account_id, id DESC
+            ) AS A
+            WHERE effective_era_index IS NULL OR effective_era_index >= $1
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(max_age_days as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut announcements = HashMap::new();
+        for db_announcement in db_announcements {
+            announcements.insert(
+                AccountId::from_str(&db_announcement.0)?,
+                AnnouncedCommissionChange {
+                    source: CommissionChangeAnnouncementSource::from_str(&db_announcement.1)?,
+                    commission_per_billion: db_announcement.2 as u32,
+                    effective_era_index: db_announcement.3.map(|era_index| era_index as u32),
+                    announced_at: db_announcement.4 as u64,
+                },
+            );
+        }
+        Ok(announcements)
+    }
+}
@@ -0,0 +1,125 @@
+//! Storage related to the per-era commission of the validators, read from the
+//! `ErasValidatorPrefs` storage of the staking pallet by `subvt-block-processor`, including the
+//! past eras that are still in the staking history when the processing starts.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::{HashMap, HashSet};
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{ValidatorCommissionChange, ValidatorCommissionHistoryReport};
+use subvt_types::substrate::ValidatorPreferences;
+
+type PostgresValidatorCommission = (i64, Option<i64>, Option<i64>, i64, bool);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_era_validator_commissions(
+        &self,
+        era_index: u32,
+        validator_prefs_map: &HashMap<AccountId, ValidatorPreferences>,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        for (validator_account_id, validator_prefs) in validator_prefs_map {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_account (id)
+                VALUES ($1)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(validator_account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO sub_era_validator_commission (era_index, validator_account_id, commission_per_billion, blocks_nominations)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (era_index, validator_account_id) DO NOTHING
+                "#,
+            )
+            .bind(era_index as i64)
+            .bind(validator_account_id.to_string())
+            .bind(validator_prefs.commission_per_billion as i64)
+            .bind(validator_prefs.blocks_nominations)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Indices of the eras in the inclusive range that have persisted validator commissions.
+    pub async fn get_validator_commission_era_indices(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+    ) -> anyhow::Result<HashSet<u32>> {
+        let era_indices: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT era_index
+            FROM sub_era_validator_commission
+            WHERE era_index >= $1 AND era_index <= $2
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(era_indices
+            .into_iter()
+            .map(|era_index| era_index.0 as u32)
+            .collect())
+    }
+
+    /// Eras in which the commission or the nomination blocking of the validator differs from
+    /// its previous era with persisted preferences, earliest first.
+    pub async fn get_validator_commission_history_report(
+        &self,
+        validator_account_id: &AccountId,
+    ) -> anyhow::Result<ValidatorCommissionHistoryReport> {
+        let era_range: (Option<i64>, Option<i64>) = sqlx::query_as(
+            r#"
+            SELECT MIN(era_index), MAX(era_index)
+            FROM sub_era_validator_commission
+            WHERE validator_account_id = $1
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .fetch_one(&self.connection_pool)
+        .await?;
+        let db_changes: Vec<PostgresValidatorCommission> = sqlx::query_as(
+            r#"
+            SELECT era_index, previous_era_index, previous_commission_per_billion, commission_per_billion, blocks_nominations
+            FROM (
+                SELECT era_index, commission_per_billion, blocks_nominations,
+                LAG(era_index) OVER (ORDER BY era_index ASC) AS previous_era_index,
+                LAG(commission_per_billion) OVER (ORDER BY era_index ASC) AS previous_commission_per_billion,
+                LAG(blocks_nominations) OVER (ORDER BY era_index ASC) AS previous_blocks_nominations
+                FROM sub_era_validator_commission
+                WHERE validator_account_id = $1
+            ) AS C
+            WHERE previous_era_index IS NULL
+            OR previous_commission_per_billion <> commission_per_billion
+            OR previous_blocks_nominations <> blocks_nominations
+            ORDER BY era_index ASC
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(ValidatorCommissionHistoryReport {
+            validator_account_id: validator_account_id.clone(),
+            first_era_index: era_range.0.map(|era_index| era_index as u32),
+            last_era_index: era_range.1.map(|era_index| era_index as u32),
+            changes: db_changes
+                .into_iter()
+                .map(|db_change| ValidatorCommissionChange {
+                    era_index: db_change.0 as u32,
+                    previous_era_index: db_change.1.map(|era_index| era_index as u32),
+                    previous_commission_per_billion: db_change
+                        .2
+                        .map(|commission| commission as u32),
+                    commission_per_billion: db_change.3 as u32,
+                    blocks_nominations: db_change.4,
+                })
+                .collect(),
+        })
+    }
+}
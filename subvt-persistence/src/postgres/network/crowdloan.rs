@@ -0,0 +1,78 @@
+//! Storage related to the crowdloan contributions and the auction bids of the parachain slots.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::crypto::AccountId;
+use subvt_types::substrate::Balance;
+
+impl PostgreSQLNetworkStorage {
+    /// `amount` is `None` for `Crowdloan.contribute_all`, which contributes the whole free
+    /// balance of the account.
+    pub async fn save_crowdloan_contribute_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        (account_id, parachain_id): (&AccountId, u32),
+        maybe_amount: Option<Balance>,
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(account_id).await?;
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_crowdloan_contribute (block_hash, extrinsic_index, is_nested_call, account_id, parachain_id, amount, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(is_nested_call)
+        .bind(account_id.to_string())
+        .bind(parachain_id as i64)
+        .bind(maybe_amount.map(|amount| amount.to_string()))
+        .bind(is_successful)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        if let Some(result) = maybe_result {
+            Ok(Some(result.0))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub async fn save_auction_bid_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        (account_id, parachain_id, auction_index): (&AccountId, u32, u32),
+        (first_slot, last_slot): (u32, u32),
+        amount: Balance,
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(account_id).await?;
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_auction_bid (block_hash, extrinsic_index, is_nested_call, account_id, parachain_id, auction_index, first_slot, last_slot, amount, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(is_nested_call)
+        .bind(account_id.to_string())
+        .bind(parachain_id as i64)
+        .bind(auction_index as i64)
+        .bind(first_slot as i64)
+        .bind(last_slot as i64)
+        .bind(amount.to_string())
+        .bind(is_successful)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        if let Some(result) = maybe_result {
+            Ok(Some(result.0))
+        } else {
+            Ok(None)
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Storage related to the democracy pallet (referendums and votes).
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::app::event::DemocracyStartedEvent;
+use subvt_types::crypto::AccountId;
+use subvt_types::substrate::DemocracyVote;
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_democracy_started_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        (referendum_index, vote_threshold): (u32, &str),
+        end_block_number: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_event_democracy_started (block_hash, extrinsic_index, event_index, referendum_index, vote_threshold, end_block_number)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+            .bind(block_hash)
+            .bind(extrinsic_index)
+            .bind(event_index)
+            .bind(referendum_index as i64)
+            .bind(vote_threshold)
+            .bind(end_block_number as i64)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn save_democracy_cancelled_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        referendum_index: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_event_democracy_cancelled (block_hash, extrinsic_index, event_index, referendum_index)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+            .bind(block_hash)
+            .bind(extrinsic_index)
+            .bind(event_index)
+            .bind(referendum_index as i64)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn save_democracy_vote_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        (account_id, referendum_index): (&AccountId, u32),
+        vote: &DemocracyVote,
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(account_id).await?;
+        let maybe_result: Option<(i32, )> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_democracy_vote (block_hash, extrinsic_index, is_nested_call, account_id, referendum_index, aye_balance, nay_balance, conviction, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id
+            "#,
+        )
+            .bind(block_hash)
+            .bind(extrinsic_index)
+            .bind(is_nested_call)
+            .bind(account_id.to_string())
+            .bind(referendum_index as i64)
+            .bind(vote.aye_balance.map(|balance| balance.to_string()))
+            .bind(vote.nay_balance.map(|balance| balance.to_string()))
+            .bind(vote.conviction.map(|conviction| conviction as i32))
+            .bind(is_successful)
+            .fetch_optional(&self.connection_pool)
+            .await?;
+        if let Some(result) = maybe_result {
+            Ok(Some(result.0))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Referendums that have not been cancelled, and that end after `after_block_number`
+    /// and at or before `until_block_number`.
+    pub async fn get_ongoing_referendums_ending_between(
+        &self,
+        after_block_number: u64,
+        until_block_number: u64,
+    ) -> anyhow::Result<Vec<DemocracyStartedEvent>> {
+        let db_events: Vec<(i32, String, Option<i32>, i32, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT "id", block_hash, extrinsic_index, event_index, referendum_index, end_block_number
+            FROM sub_event_democracy_started S
+            WHERE S.end_block_number > $1
+            AND S.end_block_number <= $2
+            AND NOT EXISTS (
+                SELECT C.id
+                FROM sub_event_democracy_cancelled C
+                WHERE C.referendum_index = S.referendum_index
+            )
+            ORDER BY S.referendum_index ASC
+            "#,
+        )
+        .bind(after_block_number as i64)
+        .bind(until_block_number as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_events
+            .iter()
+            .map(|db_event| DemocracyStartedEvent {
+                id: db_event.0 as u32,
+                block_hash: db_event.1.clone(),
+                extrinsic_index: db_event.2.map(|index| index as u32),
+                event_index: db_event.3 as u32,
+                referendum_index: db_event.4 as u32,
+                end_block_number: db_event.5 as u64,
+            })
+            .collect())
+    }
+
+    /// Checks whether any of the given accounts has successfully voted on the referendum.
+    pub async fn democracy_vote_exists(
+        &self,
+        referendum_index: u32,
+        account_ids: &[&AccountId],
+    ) -> anyhow::Result<bool> {
+        let account_ids: Vec<String> = account_ids
+            .iter()
+            .map(|account_id| account_id.to_string())
+            .collect();
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT id
+                FROM sub_extrinsic_democracy_vote
+                WHERE referendum_index = $1
+                AND account_id = ANY($2)
+                AND is_successful = true
+            )
+            "#,
+        )
+        .bind(referendum_index as i64)
+        .bind(&account_ids)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+}
@@ -0,0 +1,128 @@
+//! Storage of the domain event changefeed. See `subvt_types::domain_event` for details.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::domain_event::{DomainEvent, DomainEventType};
+
+type PostgresDomainEvent = (i64, String, String, i64, i64, Option<String>, String);
+
+impl PostgreSQLNetworkStorage {
+    /// Returns the id of the saved event, `None` if the event has already been saved, which is
+    /// the case when a block gets processed again.
+    pub async fn save_domain_event(
+        &self,
+        event_type: DomainEventType,
+        block_hash: &str,
+        maybe_validator_account_id: Option<&AccountId>,
+        data: &serde_json::Value,
+    ) -> anyhow::Result<Option<u64>> {
+        if let Some(validator_account_id) = maybe_validator_account_id {
+            self.save_account(validator_account_id).await?;
+        }
+        let maybe_result: Option<(i64,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_domain_event (event_type, block_hash, validator_account_id, data_json)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (block_hash, event_type, validator_account_id, data_json) DO NOTHING
+            RETURNING id
+            "#,
+        )
+        .bind(event_type.to_string())
+        .bind(block_hash)
+        .bind(maybe_validator_account_id.map(|account_id| account_id.to_string()))
+        .bind(serde_json::to_string(data)?)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_result.map(|result| result.0 as u64))
+    }
+
+    /// Saves a `validator_joined_active_set` event for each validator that is active in the era
+    /// but was not in the previous era. Nothing is saved if the previous era has not been
+    /// indexed, as all the validators would then be reported as joined.
+    pub async fn save_validator_joined_active_set_events(
+        &self,
+        era_index: u32,
+        block_hash: &str,
+    ) -> anyhow::Result<u64> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sub_domain_event (event_type, block_hash, validator_account_id, data_json)
+            SELECT $1, $2, V.validator_account_id, json_build_object('era_index', V.era_index)::text
+            FROM sub_era_validator V
+            WHERE V.era_index = $3
+            AND V.is_active = true
+            AND EXISTS (
+                SELECT id FROM sub_era_validator
+                WHERE era_index = $3 - 1
+                AND is_active = true
+            )
+            AND NOT EXISTS (
+                SELECT id FROM sub_era_validator P
+                WHERE P.era_index = $3 - 1
+                AND P.validator_account_id = V.validator_account_id
+                AND P.is_active = true
+            )
+            ORDER BY V.validator_account_id ASC
+            ON CONFLICT (block_hash, event_type, validator_account_id, data_json) DO NOTHING
+            "#,
+        )
+        .bind(DomainEventType::ValidatorJoinedActiveSet.to_string())
+        .bind(block_hash)
+        .bind(era_index as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Gets the events with an id greater than the given id, in the order of persistence.
+    pub async fn get_domain_events_after_id(
+        &self,
+        id: u64,
+        limit: u32,
+    ) -> anyhow::Result<Vec<DomainEvent>> {
+        let db_events: Vec<PostgresDomainEvent> = sqlx::query_as(
+            r#"
+            SELECT E.id, E.event_type, E.block_hash, B.number, B.era_index, E.validator_account_id, E.data_json
+            FROM sub_domain_event E
+            INNER JOIN sub_block B
+                ON B.hash = E.block_hash
+            WHERE E.id > $1
+            ORDER BY E.id ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(id as i64)
+        .bind(limit as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut events = Vec::new();
+        for db_event in db_events {
+            events.push(DomainEvent {
+                id: db_event.0 as u64,
+                event_type: DomainEventType::from_str(&db_event.1)?,
+                block_hash: db_event.2,
+                block_number: db_event.3 as u64,
+                era_index: db_event.4 as u32,
+                validator_account_id: if let Some(account_id_hex_string) = &db_event.5 {
+                    Some(AccountId::from_str(account_id_hex_string)?)
+                } else {
+                    None
+                },
+                data: serde_json::from_str(&db_event.6)?,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Id of the most recent event, 0 if there are no events.
+    pub async fn get_last_domain_event_id(&self) -> anyhow::Result<u64> {
+        let result: (Option<i64>,) = sqlx::query_as(
+            r#"
+            SELECT MAX(id) FROM sub_domain_event
+            "#,
+        )
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0.unwrap_or(0) as u64)
+    }
+}
@@ -0,0 +1,141 @@
+//! Storage related to the staking election snapshots (voters and targets).
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::EraElectionReport;
+use subvt_types::substrate::{ElectionSnapshot, ElectionVoter};
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_era_election_snapshot(
+        &self,
+        era_index: u32,
+        snapshot: &ElectionSnapshot,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        for target_account_id in &snapshot.targets {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_account (id)
+                VALUES ($1)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(target_account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+            sqlx::query(
+                r#"
+                INSERT INTO sub_era_election_target (era_index, account_id)
+                VALUES ($1, $2)
+                ON CONFLICT (era_index, account_id) DO NOTHING
+                "#,
+            )
+            .bind(era_index as i64)
+            .bind(target_account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+        }
+        for voter in &snapshot.voters {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_account (id)
+                VALUES ($1)
+                ON CONFLICT (id) DO NOTHING
+                "#,
+            )
+            .bind(voter.account_id.to_string())
+            .execute(&mut transaction)
+            .await?;
+            let target_account_ids: Vec<String> = voter
+                .target_account_ids
+                .iter()
+                .map(|account_id| account_id.to_string())
+                .collect();
+            sqlx::query(
+                r#"
+                INSERT INTO sub_era_election_voter (era_index, account_id, stake, target_account_ids)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (era_index, account_id) DO NOTHING
+                "#,
+            )
+                .bind(era_index as i64)
+                .bind(voter.account_id.to_string())
+                .bind(voter.stake.to_string())
+                .bind(&target_account_ids)
+                .execute(&mut transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn era_election_snapshot_exists(&self, era_index: u32) -> anyhow::Result<bool> {
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT id
+                FROM sub_era_election_target
+                WHERE era_index = $1
+            )
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+
+    /// Returns `None` if the election snapshot has not been indexed for the era.
+    pub async fn get_era_election_report(
+        &self,
+        era_index: u32,
+    ) -> anyhow::Result<Option<EraElectionReport>> {
+        if !self.era_election_snapshot_exists(era_index).await? {
+            return Ok(None);
+        }
+        let db_targets: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT account_id
+            FROM sub_era_election_target
+            WHERE era_index = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let db_voters: Vec<(String, String, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT account_id, stake, target_account_ids
+            FROM sub_era_election_voter
+            WHERE era_index = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut targets = Vec::with_capacity(db_targets.len());
+        for db_target in &db_targets {
+            targets.push(AccountId::from_str(&db_target.0)?);
+        }
+        let mut voters = Vec::with_capacity(db_voters.len());
+        for db_voter in &db_voters {
+            let mut target_account_ids = Vec::with_capacity(db_voter.2.len());
+            for target_account_id in &db_voter.2 {
+                target_account_ids.push(AccountId::from_str(target_account_id)?);
+            }
+            voters.push(ElectionVoter {
+                account_id: AccountId::from_str(&db_voter.0)?,
+                stake: db_voter.1.parse()?,
+                target_account_ids,
+            });
+        }
+        Ok(Some(EraElectionReport {
+            era_index,
+            total_voter_stake: voters.iter().map(|voter| voter.stake as u128).sum(),
+            targets,
+            voters,
+        }))
+    }
+}
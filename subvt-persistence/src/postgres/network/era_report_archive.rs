@@ -0,0 +1,146 @@
+//! Storage related to the era reports archived by `subvt-era-report-archiver`.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{ArchivedEraReport, ArchivedEraReportFile};
+
+impl PostgreSQLNetworkStorage {
+    /// Gets the account ids of all the validators (active or inactive) of the era.
+    pub async fn get_era_validator_account_ids(
+        &self,
+        era_index: u32,
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id
+            FROM sub_era_validator
+            WHERE era_index = $1
+            ORDER BY validator_account_id ASC
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
+    /// Gets the indices of the ended eras that haven't been archived to the target yet,
+    /// ascending.
+    pub async fn get_unarchived_ended_era_indices(
+        &self,
+        target: &str,
+        limit: u32,
+    ) -> anyhow::Result<Vec<u32>> {
+        let db_era_indices: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT E.index
+            FROM sub_era E
+            WHERE E.total_validator_reward IS NOT NULL
+            AND NOT EXISTS (
+                SELECT A.era_index
+                FROM sub_era_report_archive A
+                WHERE A.era_index = E.index
+                AND A.target = $1
+            )
+            ORDER BY E.index ASC
+            LIMIT $2
+            "#,
+        )
+        .bind(target)
+        .bind(limit as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_era_indices
+            .iter()
+            .map(|db_era_index| db_era_index.0 as u32)
+            .collect())
+    }
+
+    /// Saves the archived files of the era together, so that a partially archived era gets
+    /// archived again in the next run.
+    pub async fn save_era_report_archive(
+        &self,
+        era_index: u32,
+        target: &str,
+        files: &[ArchivedEraReportFile],
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        for file in files {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_era_report_archive (era_index, target, file_name, location)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (era_index, target, file_name) DO UPDATE
+                SET location = EXCLUDED.location
+                "#,
+            )
+            .bind(era_index as i64)
+            .bind(target)
+            .bind(&file.file_name)
+            .bind(&file.location)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Gets all the archived eras of the target, ascending by era index.
+    pub async fn get_archived_era_reports(
+        &self,
+        target: &str,
+    ) -> anyhow::Result<Vec<ArchivedEraReport>> {
+        let db_files: Vec<(i64, String, String)> = sqlx::query_as(
+            r#"
+            SELECT era_index, file_name, location
+            FROM sub_era_report_archive
+            WHERE target = $1
+            ORDER BY era_index ASC, file_name ASC
+            "#,
+        )
+        .bind(target)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut archived_era_reports: Vec<ArchivedEraReport> = Vec::new();
+        for (era_index, file_name, location) in db_files {
+            let file = ArchivedEraReportFile {
+                file_name,
+                location,
+            };
+            match archived_era_reports.last_mut() {
+                Some(archived_era_report) if archived_era_report.era_index == era_index as u32 => {
+                    archived_era_report.files.push(file)
+                }
+                _ => archived_era_reports.push(ArchivedEraReport {
+                    era_index: era_index as u32,
+                    files: vec![file],
+                }),
+            }
+        }
+        Ok(archived_era_reports)
+    }
+
+    pub async fn save_era_report_archive_manifest_location(
+        &self,
+        target: &str,
+        location: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_era_report_archive_manifest (target, location)
+            VALUES ($1, $2)
+            ON CONFLICT (target) DO UPDATE
+            SET location = EXCLUDED.location, updated_at = now()
+            "#,
+        )
+        .bind(target)
+        .bind(location)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
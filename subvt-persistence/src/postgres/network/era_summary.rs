@@ -0,0 +1,142 @@
+//! Storage related to the era summaries published by `subvt-era-summary-publisher`.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::HashSet;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{EraPointsLeader, EraSummary};
+
+impl PostgreSQLNetworkStorage {
+    /// Gets the index of the most recent ended era. Total validator reward of an era is saved
+    /// at the beginning of the next era.
+    pub async fn get_last_ended_era_index(&self) -> anyhow::Result<Option<u32>> {
+        let maybe_era_index: (Option<i64>,) = sqlx::query_as(
+            r#"
+            SELECT MAX(index)
+            FROM sub_era
+            WHERE total_validator_reward IS NOT NULL
+            "#,
+        )
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(maybe_era_index.0.map(|era_index| era_index as u32))
+    }
+
+    /// Composes the summary of the era from the era report, the active validator sets of the era
+    /// and the previous era, and the reward points of the validators.
+    pub async fn get_era_summary(
+        &self,
+        era_index: u32,
+        points_leader_count: u32,
+    ) -> anyhow::Result<Option<EraSummary>> {
+        let mut era_reports = self
+            .get_era_report(era_index.saturating_sub(1), era_index)
+            .await?;
+        let report = match era_reports.pop() {
+            Some(report) if report.era.index == era_index => report,
+            _ => return Ok(None),
+        };
+        let previous_total_stake = era_reports
+            .pop()
+            .and_then(|previous_report| previous_report.total_stake);
+        let db_era_validators: Vec<(i64, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT era_index, validator_account_id, reward_points
+            FROM sub_era_validator
+            WHERE era_index BETWEEN $1 AND $2
+            AND is_active = true
+            "#,
+        )
+        .bind(era_index.saturating_sub(1) as i64)
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let previous_active_validator_account_ids: HashSet<&String> = db_era_validators
+            .iter()
+            .filter(|db_era_validator| db_era_validator.0 as u32 != era_index)
+            .map(|db_era_validator| &db_era_validator.1)
+            .collect();
+        let mut active_validators: Vec<(AccountId, u64, bool)> = Vec::new();
+        for db_era_validator in &db_era_validators {
+            if db_era_validator.0 as u32 != era_index {
+                continue;
+            }
+            active_validators.push((
+                AccountId::from_str(&db_era_validator.1)?,
+                db_era_validator.2.max(0) as u64,
+                !previous_active_validator_account_ids.contains(&db_era_validator.1),
+            ));
+        }
+        active_validators.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.to_string().cmp(&b.0.to_string())));
+        let offences = self
+            .get_offence_report(era_index, era_index)
+            .await?
+            .eras
+            .pop()
+            .unwrap_or_default();
+        Ok(Some(EraSummary {
+            report,
+            previous_total_stake,
+            active_validator_count: active_validators.len() as u32,
+            // all validators would be new if the previous era has not been indexed
+            new_active_validator_account_ids: if previous_active_validator_account_ids.is_empty() {
+                Vec::new()
+            } else {
+                active_validators
+                    .iter()
+                    .filter(|(_, _, is_new)| *is_new)
+                    .map(|(account_id, _, _)| account_id.clone())
+                    .collect()
+            },
+            points_leaders: active_validators
+                .iter()
+                .take(points_leader_count as usize)
+                .map(|(account_id, reward_points, _)| EraPointsLeader {
+                    account_id: account_id.clone(),
+                    reward_points: *reward_points,
+                })
+                .collect(),
+            offences,
+        }))
+    }
+
+    pub async fn era_summary_is_published(
+        &self,
+        era_index: u32,
+        target: &str,
+    ) -> anyhow::Result<bool> {
+        let result: (bool,) = sqlx::query_as(
+            r#"
+            SELECT EXISTS(
+                SELECT era_index
+                FROM sub_era_summary_publication
+                WHERE era_index = $1
+                AND target = $2
+            )
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(target)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        Ok(result.0)
+    }
+
+    pub async fn save_era_summary_publication(
+        &self,
+        era_index: u32,
+        target: &str,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_era_summary_publication(era_index, target)
+            VALUES ($1, $2)
+            ON CONFLICT(era_index, target) DO NOTHING
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(target)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+}
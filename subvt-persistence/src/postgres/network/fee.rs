@@ -0,0 +1,119 @@
+//! Storage related to the transaction fees and tips of the extrinsics.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::app::extrinsic::ExtrinsicFee;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::BlockEconomicsReport;
+
+type PostgresExtrinsicFee = (
+    i32,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+    String,
+    String,
+    i64,
+    bool,
+    bool,
+);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_extrinsic_fee(&self, extrinsic_fee: &ExtrinsicFee) -> anyhow::Result<()> {
+        if let Some(signer_account_id) = &extrinsic_fee.signer_account_id {
+            self.save_account(signer_account_id).await?;
+        }
+        sqlx::query(
+            r#"
+            INSERT INTO sub_extrinsic_fee (block_hash, extrinsic_index, signer_account_id, block_author_account_id, fee, tip, author_fee_share, treasury_fee_share, weight, pays_fee, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (block_hash, extrinsic_index) DO NOTHING
+            "#,
+        )
+            .bind(&extrinsic_fee.block_hash)
+            .bind(extrinsic_fee.extrinsic_index as i32)
+            .bind(extrinsic_fee.signer_account_id.as_ref().map(|account_id| account_id.to_string()))
+            .bind(extrinsic_fee.block_author_account_id.as_ref().map(|account_id| account_id.to_string()))
+            .bind(extrinsic_fee.fee.to_string())
+            .bind(extrinsic_fee.tip.to_string())
+            .bind(extrinsic_fee.author_fee_share.to_string())
+            .bind(extrinsic_fee.treasury_fee_share.to_string())
+            .bind(extrinsic_fee.weight as i64)
+            .bind(extrinsic_fee.pays_fee)
+            .bind(extrinsic_fee.is_successful)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_block_economics_report(
+        &self,
+        block_number: u64,
+    ) -> anyhow::Result<Option<BlockEconomicsReport>> {
+        let maybe_db_block: Option<(String, Option<i64>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT hash, timestamp, author_account_id
+            FROM sub_block
+            WHERE number = $1
+            "#,
+        )
+        .bind(block_number as i64)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        let db_block = if let Some(db_block) = maybe_db_block {
+            db_block
+        } else {
+            return Ok(None);
+        };
+        let db_extrinsic_fees: Vec<PostgresExtrinsicFee> = sqlx::query_as(
+            r#"
+            SELECT extrinsic_index, signer_account_id, block_author_account_id, fee, tip, author_fee_share, treasury_fee_share, weight, pays_fee, is_successful
+            FROM sub_extrinsic_fee
+            WHERE block_hash = $1
+            ORDER BY extrinsic_index ASC
+            "#,
+        )
+            .bind(&db_block.0)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        let mut report = BlockEconomicsReport {
+            block_hash: db_block.0.clone(),
+            block_number,
+            timestamp: db_block.1.map(|timestamp| timestamp as u64),
+            author_account_id: match &db_block.2 {
+                Some(author_account_id) => Some(AccountId::from_str(author_account_id)?),
+                None => None,
+            },
+            ..Default::default()
+        };
+        for db_extrinsic_fee in db_extrinsic_fees {
+            let extrinsic_fee = ExtrinsicFee {
+                block_hash: db_block.0.clone(),
+                extrinsic_index: db_extrinsic_fee.0 as u32,
+                signer_account_id: match &db_extrinsic_fee.1 {
+                    Some(account_id) => Some(AccountId::from_str(account_id)?),
+                    None => None,
+                },
+                block_author_account_id: match &db_extrinsic_fee.2 {
+                    Some(account_id) => Some(AccountId::from_str(account_id)?),
+                    None => None,
+                },
+                fee: db_extrinsic_fee.3.parse()?,
+                tip: db_extrinsic_fee.4.parse()?,
+                author_fee_share: db_extrinsic_fee.5.parse()?,
+                treasury_fee_share: db_extrinsic_fee.6.parse()?,
+                weight: db_extrinsic_fee.7 as u64,
+                pays_fee: db_extrinsic_fee.8,
+                is_successful: db_extrinsic_fee.9,
+            };
+            report.signed_extrinsic_count += 1;
+            report.total_fee += extrinsic_fee.fee;
+            report.total_tip += extrinsic_fee.tip;
+            report.total_author_fee_share += extrinsic_fee.author_fee_share;
+            report.total_treasury_fee_share += extrinsic_fee.treasury_fee_share;
+            report.total_weight += extrinsic_fee.weight;
+            report.extrinsic_fees.push(extrinsic_fee);
+        }
+        Ok(Some(report))
+    }
+}
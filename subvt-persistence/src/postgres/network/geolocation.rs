@@ -0,0 +1,283 @@
+//! Storage related to the node locations of the validators and their resolved
+//! country and region codes.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::HashMap;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{CountryValidatorCount, DecentralizationReport, RegionValidatorCount};
+use subvt_types::subvt::ValidatorLocation;
+
+/// Location to be resolved into country and region codes.
+#[derive(Clone, Debug)]
+pub enum GeolocationQuery {
+    /// Telemetry node coordinates, rounded to a single decimal.
+    Coordinates {
+        query: String,
+        latitude: f64,
+        longitude: f64,
+    },
+    /// 1KV candidate location, a free-form place name.
+    Place { query: String, place: String },
+}
+
+impl GeolocationQuery {
+    pub fn get_query(&self) -> &str {
+        match self {
+            GeolocationQuery::Coordinates { query, .. } => query,
+            GeolocationQuery::Place { query, .. } => query,
+        }
+    }
+}
+
+impl PostgreSQLNetworkStorage {
+    /// Gets the Telemetry node coordinates and the 1KV candidate locations that haven't been
+    /// resolved yet. Coordinates are rounded to a single decimal (~10 km), so that the nodes
+    /// in the same area share a single query.
+    pub async fn get_unresolved_geolocation_queries(
+        &self,
+    ) -> anyhow::Result<Vec<GeolocationQuery>> {
+        let db_queries: Vec<(String, Option<f64>, Option<f64>, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT Q.query, Q.latitude, Q.longitude, Q.place
+            FROM (
+                SELECT DISTINCT 'coordinates:' || ROUND(latitude::numeric, 1)::text || ',' || ROUND(longitude::numeric, 1)::text AS query, ROUND(latitude::numeric, 1)::double precision AS latitude, ROUND(longitude::numeric, 1)::double precision AS longitude, NULL::text AS place
+                FROM sub_telemetry_node
+                WHERE latitude IS NOT NULL AND longitude IS NOT NULL
+                UNION
+                SELECT DISTINCT 'place:' || LOWER(TRIM(location)), NULL::double precision, NULL::double precision, LOWER(TRIM(location))
+                FROM sub_onekv_candidate
+                WHERE location IS NOT NULL AND TRIM(location) <> '' AND LOWER(TRIM(location)) <> 'none'
+            ) Q
+            WHERE NOT EXISTS (
+                SELECT id FROM sub_geolocation G WHERE G.query = Q.query
+            )
+            "#,
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_queries
+            .into_iter()
+            .filter_map(
+                |(query, latitude, longitude, place)| match (latitude, longitude, place) {
+                    (Some(latitude), Some(longitude), _) => Some(GeolocationQuery::Coordinates {
+                        query,
+                        latitude,
+                        longitude,
+                    }),
+                    (_, _, Some(place)) => Some(GeolocationQuery::Place { query, place }),
+                    _ => None,
+                },
+            )
+            .collect())
+    }
+
+    /// Saves the resolution result of a query. Unresolvable queries are saved without a country
+    /// code, so that they don't get queried again.
+    pub async fn save_geolocation(
+        &self,
+        query: &str,
+        country_code: Option<&str>,
+        region_code: Option<&str>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_geolocation (query, country_code, region_code)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (query) DO UPDATE
+            SET country_code = EXCLUDED.country_code, region_code = EXCLUDED.region_code
+            "#,
+        )
+        .bind(query)
+        .bind(country_code)
+        .bind(region_code)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the resolved location of a validator. The location of the most recently updated
+    /// Telemetry node of the validator (reported by the stash or the controller account) takes
+    /// precedence over the latest 1KV candidate location.
+    pub async fn get_resolved_validator_location(
+        &self,
+        validator_account_id: &AccountId,
+        controller_account_id: &AccountId,
+    ) -> anyhow::Result<Option<ValidatorLocation>> {
+        let maybe_db_location: Option<(String, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT country_code, region_code
+            FROM (
+                SELECT 0 AS priority, N.updated_at AS updated_at, G.country_code, G.region_code
+                FROM sub_telemetry_node N
+                INNER JOIN sub_geolocation G
+                    ON G.query = 'coordinates:' || ROUND(N.latitude::numeric, 1)::text || ',' || ROUND(N.longitude::numeric, 1)::text
+                WHERE N.controller_account_id IN ($1, $2)
+                AND G.country_code IS NOT NULL
+                UNION ALL
+                SELECT 1, C.created_at, G.country_code, G.region_code
+                FROM sub_onekv_candidate C
+                INNER JOIN sub_geolocation G
+                    ON G.query = 'place:' || LOWER(TRIM(C.location))
+                WHERE C.validator_account_id = $1
+                AND G.country_code IS NOT NULL
+            ) L
+            ORDER BY priority ASC, updated_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(controller_account_id.to_string())
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(
+            maybe_db_location.map(|(country_code, region_code)| ValidatorLocation {
+                country_code,
+                region_code,
+            }),
+        )
+    }
+
+    /// Saves the location of the validator, or deletes the existing one when the location
+    /// cannot be resolved anymore.
+    pub async fn save_validator_location(
+        &self,
+        validator_account_id: &AccountId,
+        maybe_location: Option<&ValidatorLocation>,
+    ) -> anyhow::Result<()> {
+        if let Some(location) = maybe_location {
+            self.save_account(validator_account_id).await?;
+            sqlx::query(
+                r#"
+                INSERT INTO sub_validator_location (validator_account_id, country_code, region_code)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (validator_account_id) DO UPDATE
+                SET country_code = EXCLUDED.country_code, region_code = EXCLUDED.region_code, updated_at = now()
+                "#,
+            )
+            .bind(validator_account_id.to_string())
+            .bind(&location.country_code)
+            .bind(&location.region_code)
+            .execute(&self.connection_pool)
+            .await?;
+        } else {
+            sqlx::query(
+                r#"
+                DELETE FROM sub_validator_location
+                WHERE validator_account_id = $1
+                "#,
+            )
+            .bind(validator_account_id.to_string())
+            .execute(&self.connection_pool)
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get_validator_locations(
+        &self,
+    ) -> anyhow::Result<HashMap<AccountId, ValidatorLocation>> {
+        let db_locations: Vec<(String, String, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, country_code, region_code
+            FROM sub_validator_location
+            "#,
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut locations = HashMap::new();
+        for (account_id_hex_string, country_code, region_code) in db_locations {
+            locations.insert(
+                AccountId::from_str(&account_id_hex_string)?,
+                ValidatorLocation {
+                    country_code,
+                    region_code,
+                },
+            );
+        }
+        Ok(locations)
+    }
+
+    pub async fn get_era_decentralization_report(
+        &self,
+        era_index: u32,
+        min_region_validator_count: u32,
+    ) -> anyhow::Result<Option<DecentralizationReport>> {
+        let db_validator_locations: Vec<(String, Option<String>, Option<String>)> = sqlx::query_as(
+            r#"
+                SELECT V.validator_account_id, L.country_code, L.region_code
+                FROM sub_era_validator V
+                LEFT JOIN sub_validator_location L
+                    ON L.validator_account_id = V.validator_account_id
+                WHERE V.era_index = $1
+                AND V.is_active = true
+                "#,
+        )
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        if db_validator_locations.is_empty() {
+            return Ok(None);
+        }
+        let mut country_map: HashMap<String, (u32, HashMap<String, u32>)> = HashMap::new();
+        let mut located_validator_count = 0;
+        for (_, maybe_country_code, maybe_region_code) in &db_validator_locations {
+            if let Some(country_code) = maybe_country_code {
+                located_validator_count += 1;
+                let (country_validator_count, region_map) =
+                    country_map.entry(country_code.clone()).or_default();
+                *country_validator_count += 1;
+                if let Some(region_code) = maybe_region_code {
+                    *region_map.entry(region_code.clone()).or_default() += 1;
+                }
+            }
+        }
+        let mut countries: Vec<CountryValidatorCount> = country_map
+            .into_iter()
+            .map(
+                |(country_code, (validator_count, region_map))| CountryValidatorCount {
+                    country_code,
+                    validator_count,
+                    regions: {
+                        let mut regions: Vec<RegionValidatorCount> = region_map
+                            .into_iter()
+                            .filter(|(_, validator_count)| {
+                                *validator_count >= min_region_validator_count
+                            })
+                            .map(|(region_code, validator_count)| RegionValidatorCount {
+                                region_code,
+                                validator_count,
+                            })
+                            .collect();
+                        regions.sort_by(|a, b| {
+                            b.validator_count
+                                .cmp(&a.validator_count)
+                                .then(a.region_code.cmp(&b.region_code))
+                        });
+                        regions
+                    },
+                },
+            )
+            .collect();
+        countries.sort_by(|a, b| {
+            b.validator_count
+                .cmp(&a.validator_count)
+                .then(a.country_code.cmp(&b.country_code))
+        });
+        let mut country_nakamoto_coefficient = 0;
+        let mut cumulative_validator_count = 0;
+        for country in &countries {
+            if cumulative_validator_count * 3 > located_validator_count {
+                break;
+            }
+            cumulative_validator_count += country.validator_count;
+            country_nakamoto_coefficient += 1;
+        }
+        Ok(Some(DecentralizationReport {
+            era_index,
+            active_validator_count: db_validator_locations.len() as u32,
+            located_validator_count,
+            country_nakamoto_coefficient,
+            countries,
+        }))
+    }
+}
@@ -0,0 +1,196 @@
+//! Storage related to the governance calls of the democracy, conviction voting and referenda
+//! pallets, and the governance activity of the accounts. Democracy votes are stored separately,
+//! see the `democracy` module.
+use crate::postgres::network::staking_minimums::parse_maybe_balance;
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{GovernanceActivity, GovernanceActivityType, GovernancePallet};
+use subvt_types::substrate::extrinsic::GovernanceCall;
+use subvt_types::substrate::DemocracyVote;
+
+type PostgresGovernanceActivity = (
+    String,
+    String,
+    i64,
+    Option<i64>,
+    bool,
+    Option<i64>,
+    Option<i64>,
+    Option<i32>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<i32>,
+    Option<String>,
+);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_governance_call_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        caller_account_id: &AccountId,
+        call: &GovernanceCall,
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(caller_account_id).await?;
+        if let Some(target_account_id) = &call.target_account_id {
+            self.save_account(target_account_id).await?;
+        }
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_governance_call (block_hash, extrinsic_index, is_nested_call, caller_account_id, pallet_name, call_name, referendum_index, proposal_index, track_id, target_account_id, aye_balance, nay_balance, abstain_balance, conviction, amount, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            RETURNING id
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(is_nested_call)
+        .bind(caller_account_id.to_string())
+        .bind(call.pallet_name)
+        .bind(call.call_name)
+        .bind(call.referendum_index.map(|index| index as i64))
+        .bind(call.proposal_index.map(|index| index as i64))
+        .bind(call.track_id.map(|track_id| track_id as i32))
+        .bind(
+            call.target_account_id
+                .as_ref()
+                .map(|account_id| account_id.to_string()),
+        )
+        .bind(
+            call.vote
+                .as_ref()
+                .and_then(|vote| vote.aye_balance)
+                .map(|balance| balance.to_string()),
+        )
+        .bind(
+            call.vote
+                .as_ref()
+                .and_then(|vote| vote.nay_balance)
+                .map(|balance| balance.to_string()),
+        )
+        .bind(
+            call.vote
+                .as_ref()
+                .and_then(|vote| vote.abstain_balance)
+                .map(|balance| balance.to_string()),
+        )
+        .bind(
+            call.vote
+                .as_ref()
+                .map_or(call.conviction, |vote| vote.conviction)
+                .map(|conviction| conviction as i32),
+        )
+        .bind(call.amount.map(|amount| amount.to_string()))
+        .bind(is_successful)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_result.map(|result| result.0))
+    }
+
+    pub async fn save_referenda_submitted_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        referendum_index: u32,
+        track_id: u16,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_event_referenda_submitted (block_hash, extrinsic_index, event_index, referendum_index, track_id)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(event_index)
+        .bind(referendum_index as i64)
+        .bind(track_id as i32)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Last `count` successful governance calls of the account, latest first. The index and
+    /// the track of a submitted referendum are read from the first `Submitted` event of the
+    /// extrinsic.
+    pub async fn get_account_governance_activity(
+        &self,
+        account_id: &AccountId,
+        count: u32,
+    ) -> anyhow::Result<Vec<GovernanceActivity>> {
+        let db_activities: Vec<PostgresGovernanceActivity> = sqlx::query_as(
+            r#"
+            SELECT pallet_name, call_name, block_number, timestamp, is_nested_call, referendum_index, proposal_index, track_id, target_account_id, aye_balance, nay_balance, abstain_balance, conviction, amount
+            FROM (
+                SELECT 'democracy' AS pallet_name, 'vote' AS call_name, B.number AS block_number, B.timestamp, V.is_nested_call, V.referendum_index, NULL::bigint AS proposal_index, NULL::integer AS track_id, NULL::VARCHAR AS target_account_id, V.aye_balance, V.nay_balance, NULL::VARCHAR AS abstain_balance, V.conviction, NULL::VARCHAR AS amount, V.extrinsic_index
+                FROM sub_extrinsic_democracy_vote V
+                INNER JOIN sub_block B
+                    ON B.hash = V.block_hash
+                WHERE V.account_id = $1
+                AND V.is_successful = true
+                UNION ALL
+                SELECT G.pallet_name, G.call_name, B.number, B.timestamp, G.is_nested_call, COALESCE(G.referendum_index, S.referendum_index), G.proposal_index, COALESCE(G.track_id, S.track_id), G.target_account_id, G.aye_balance, G.nay_balance, G.abstain_balance, G.conviction, G.amount, G.extrinsic_index
+                FROM sub_extrinsic_governance_call G
+                INNER JOIN sub_block B
+                    ON B.hash = G.block_hash
+                LEFT JOIN LATERAL (
+                    SELECT RS.referendum_index, RS.track_id
+                    FROM sub_event_referenda_submitted RS
+                    WHERE RS.block_hash = G.block_hash
+                    AND RS.extrinsic_index = G.extrinsic_index
+                    AND G.call_name = 'submit'
+                    ORDER BY RS.event_index ASC
+                    LIMIT 1
+                ) S ON true
+                WHERE G.caller_account_id = $1
+                AND G.is_successful = true
+            ) A
+            ORDER BY A.block_number DESC, A.extrinsic_index DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut activities = Vec::new();
+        for db_activity in db_activities {
+            let activity_type = GovernanceActivityType::from_call_name(&db_activity.1)?;
+            let conviction = db_activity.12.map(|conviction| conviction as u8);
+            let vote = if activity_type == GovernanceActivityType::Vote {
+                Some(DemocracyVote {
+                    aye_balance: parse_maybe_balance(db_activity.9)?,
+                    nay_balance: parse_maybe_balance(db_activity.10)?,
+                    abstain_balance: parse_maybe_balance(db_activity.11)?,
+                    conviction,
+                })
+            } else {
+                None
+            };
+            activities.push(GovernanceActivity {
+                activity_type,
+                pallet: GovernancePallet::from_str(&db_activity.0)?,
+                block_number: db_activity.2 as u64,
+                timestamp: db_activity.3.map(|timestamp| timestamp as u64),
+                is_nested_call: db_activity.4,
+                referendum_index: db_activity.5.map(|index| index as u32),
+                proposal_index: db_activity.6.map(|index| index as u32),
+                track_id: db_activity.7.map(|track_id| track_id as u16),
+                target_account_id: match &db_activity.8 {
+                    Some(account_id) => Some(AccountId::from_str(account_id)?),
+                    None => None,
+                },
+                conviction: if vote.is_none() { conviction } else { None },
+                vote,
+                amount: parse_maybe_balance(db_activity.13)?,
+            });
+        }
+        Ok(activities)
+    }
+}
@@ -0,0 +1,178 @@
+//! Storage related to the identity pallet (identity, judgement and sub-identity changes, and
+//! the identity calls).
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use crate::postgres::page::{get_cursor_bounds, get_history_page};
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{AccountIdentityChange, HistoryCursor, HistoryPage};
+use subvt_types::substrate::extrinsic::IdentityExtrinsic;
+use subvt_types::substrate::{judgement_to_string, IdentityChange, IdentityChangeType};
+
+type PostgresAccountIdentityChange = (
+    String,
+    i64,
+    i32,
+    Option<i64>,
+    IdentityChangeType,
+    Option<String>,
+    Option<i64>,
+    Option<String>,
+    Option<String>,
+);
+
+impl PostgreSQLNetworkStorage {
+    pub async fn save_identity_change_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        account_id: &AccountId,
+        change: &IdentityChange,
+    ) -> anyhow::Result<()> {
+        self.save_account(account_id).await?;
+        let identity_json = match &change.identity {
+            Some(identity) => Some(serde_json::to_string(identity)?),
+            None => None,
+        };
+        sqlx::query(
+            r#"
+            INSERT INTO sub_event_identity_change (block_hash, extrinsic_index, event_index, account_id, change_type, related_account_id, registrar_index, identity_json, sub_display)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (block_hash, event_index) DO NOTHING
+            "#,
+        )
+            .bind(block_hash)
+            .bind(extrinsic_index)
+            .bind(event_index)
+            .bind(account_id.to_string())
+            .bind(&change.change_type)
+            .bind(change.related_account_id.as_ref().map(|account_id| account_id.to_string()))
+            .bind(change.registrar_index.map(|registrar_index| registrar_index as i64))
+            .bind(identity_json)
+            .bind(&change.sub_display)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Saves an `Identity` call. The target is only set for `provide_judgement` and
+    /// `kill_identity`, the identity only for `set_identity`, and the sub-account count only
+    /// for `set_subs`.
+    pub async fn save_identity_call_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        caller_account_id: &AccountId,
+        extrinsic: &IdentityExtrinsic,
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(caller_account_id).await?;
+        let (maybe_target_account_id, maybe_registrar_index, maybe_judgement) = match extrinsic {
+            IdentityExtrinsic::ProvideJudgement {
+                registrar_index,
+                target,
+                judgement,
+                ..
+            } => (
+                target.get_account_id(),
+                Some(*registrar_index as i64),
+                Some(judgement_to_string(judgement)),
+            ),
+            IdentityExtrinsic::KillIdentity { target, .. } => (target.get_account_id(), None, None),
+            _ => (None, None, None),
+        };
+        let maybe_identity_json = match extrinsic {
+            IdentityExtrinsic::SetIdentity { identity, .. } => {
+                Some(serde_json::to_string(identity)?)
+            }
+            _ => None,
+        };
+        let maybe_sub_count = match extrinsic {
+            IdentityExtrinsic::SetSubs { subs, .. } => Some(subs.len() as i32),
+            _ => None,
+        };
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_identity_call (block_hash, extrinsic_index, is_nested_call, caller_account_id, call_name, target_account_id, registrar_index, judgement, identity_json, sub_count, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            RETURNING id
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(is_nested_call)
+        .bind(caller_account_id.to_string())
+        .bind(extrinsic.get_call_name())
+        .bind(maybe_target_account_id.map(|account_id| account_id.to_string()))
+        .bind(maybe_registrar_index)
+        .bind(maybe_judgement)
+        .bind(maybe_identity_json)
+        .bind(maybe_sub_count)
+        .bind(is_successful)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_result.map(|result| result.0))
+    }
+
+    /// Gets a page of the identity changes of the account in chronological order, including
+    /// the sub-identity changes in which the account is the main account. The page starts
+    /// after the cursor, or at the first change if there's no cursor.
+    pub async fn get_account_identity_history(
+        &self,
+        account_id: &AccountId,
+        maybe_cursor: Option<&HistoryCursor>,
+        page_size: u32,
+    ) -> anyhow::Result<HistoryPage<AccountIdentityChange>> {
+        let (block_number_bound, index_bound) = get_cursor_bounds(maybe_cursor);
+        let db_changes: Vec<PostgresAccountIdentityChange> = sqlx::query_as(
+            r#"
+            SELECT IC.account_id, B.number, IC.event_index, B.timestamp, IC.change_type, IC.related_account_id, IC.registrar_index, IC.identity_json, IC.sub_display
+            FROM sub_event_identity_change IC
+            INNER JOIN sub_block B
+                ON B.hash = IC.block_hash
+            WHERE (IC.account_id = $1 OR IC.related_account_id = $1)
+            AND (B.number, IC.event_index) > ($2, $3)
+            ORDER BY B.number ASC, IC.event_index ASC
+            LIMIT $4
+            "#,
+        )
+            .bind(account_id.to_string())
+            .bind(block_number_bound)
+            .bind(index_bound)
+            .bind(page_size as i64 + 1)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        let mut rows = Vec::with_capacity(db_changes.len());
+        for db_change in db_changes {
+            let identity = match &db_change.7 {
+                Some(identity_json) => Some(serde_json::from_str(identity_json)?),
+                None => None,
+            };
+            let related_account_id = match &db_change.5 {
+                Some(related_account_id) => Some(AccountId::from_str(related_account_id)?),
+                None => None,
+            };
+            let cursor = HistoryCursor {
+                block_number: db_change.1 as u64,
+                index: db_change.2 as u32,
+            };
+            rows.push((
+                cursor,
+                AccountIdentityChange {
+                    account_id: AccountId::from_str(&db_change.0)?,
+                    block_number: db_change.1 as u64,
+                    timestamp: db_change.3.map(|timestamp| timestamp as u64),
+                    change: IdentityChange {
+                        change_type: db_change.4,
+                        related_account_id,
+                        registrar_index: db_change.6.map(|registrar_index| registrar_index as u32),
+                        identity,
+                        sub_display: db_change.8,
+                    },
+                },
+            ));
+        }
+        Ok(get_history_page(rows, page_size))
+    }
+}
@@ -18,13 +18,39 @@ use subvt_types::{
         argument::IdentificationTuple,
         EraStakers, ValidatorPreferences, ValidatorStake, {Balance, BlockHeader, Era},
     },
+    subvt::ValidatorPreferenceChange,
 };
 
 pub mod app_event;
+pub mod audit;
+pub mod commission_change_announcement;
+pub mod commission_history;
+pub mod crowdloan;
+pub mod democracy;
+pub mod domain_event;
+pub mod election;
+pub mod era_report_archive;
+pub mod era_summary;
+pub mod fee;
+pub mod geolocation;
+pub mod governance;
+pub mod identity;
 pub mod notify;
 pub mod onekv;
+pub mod portfolio;
+pub mod price;
 pub mod report;
+pub mod retention;
+pub mod reward_ledger;
+pub mod session;
+pub mod slot;
+pub mod staking_minimums;
 pub mod telemetry;
+pub mod validator_activity;
+pub mod validator_snapshot;
+
+type PostgresValidatorPreferenceChange =
+    (i64, Option<i64>, i64, Option<i64>, i64, Option<bool>, bool);
 
 type PostgresValidatorInfo = (
     Option<i64>,
@@ -64,6 +90,22 @@ impl PostgreSQLNetworkStorage {
             connection_pool,
         })
     }
+
+    /// Runs the pending network database migrations.
+    pub async fn run_migrations(&self) -> anyhow::Result<()> {
+        sqlx::migrate!("migrations/network/migrations")
+            .run(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Acquires a connection from the pool and returns the time it took in milliseconds, which
+    /// grows as the connections of the pool get busy with long running queries.
+    pub async fn get_connection_wait_millis(&self) -> anyhow::Result<u64> {
+        let start = std::time::Instant::now();
+        let _connection = self.connection_pool.acquire().await?;
+        Ok(start.elapsed().as_millis() as u64)
+    }
 }
 
 impl PostgreSQLNetworkStorage {
@@ -252,6 +294,34 @@ impl PostgreSQLNetworkStorage {
         Ok(record_count.0 > 0)
     }
 
+    /// Persisted eras in the inclusive range, earliest first.
+    pub async fn get_eras(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+    ) -> anyhow::Result<Vec<Era>> {
+        let db_eras: Vec<(i64, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT index, start_timestamp, end_timestamp
+            FROM sub_era
+            WHERE index BETWEEN $1 AND $2
+            ORDER BY index ASC
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_eras
+            .into_iter()
+            .map(|db_era| Era {
+                index: db_era.0 as u32,
+                start_timestamp: db_era.1 as u64,
+                end_timestamp: db_era.2 as u64,
+            })
+            .collect())
+    }
+
     pub async fn update_era_reward_points(
         &self,
         era_index: u32,
@@ -335,10 +405,11 @@ impl PostgreSQLNetworkStorage {
             maybe_author_account_id_hex = Some(author_account_id.to_string());
             self.save_account(&author_account_id).await?;
         }
+        let maybe_slot_claim = block_header.get_babe_slot_claim();
         let maybe_result: Option<(String, )> = sqlx::query_as(
             r#"
-            INSERT INTO sub_block (hash, number, timestamp, author_account_id, era_index, epoch_index, parent_hash, state_root, extrinsics_root, is_finalized, metadata_version, runtime_version)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            INSERT INTO sub_block (hash, number, timestamp, author_account_id, era_index, epoch_index, parent_hash, state_root, extrinsics_root, is_finalized, metadata_version, runtime_version, slot_number, is_primary_slot, slot_type)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
             ON CONFLICT (hash) DO NOTHING
             RETURNING hash
             "#)
@@ -354,6 +425,9 @@ impl PostgreSQLNetworkStorage {
             .bind(true)
             .bind(metadata_version)
             .bind(runtime_version)
+            .bind(maybe_slot_claim.as_ref().map(|slot_claim| slot_claim.slot_number as i64))
+            .bind(maybe_slot_claim.as_ref().map(|slot_claim| slot_claim.is_primary()))
+            .bind(maybe_slot_claim.as_ref().map(|slot_claim| slot_claim.slot_type.to_string()))
             .fetch_optional(&self.connection_pool)
             .await?;
         if let Some(result) = maybe_result {
@@ -533,6 +607,28 @@ impl PostgreSQLNetworkStorage {
         Ok(())
     }
 
+    pub async fn save_new_session_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        session_index: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO sub_event_new_session (block_hash, extrinsic_index, event_index, session_index)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(event_index)
+        .bind(session_index as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
     pub async fn save_era_paid_event(
         &self,
         block_hash: &str,
@@ -643,6 +739,40 @@ impl PostgreSQLNetworkStorage {
         }
     }
 
+    /// Saves an `Offences.Offence` event. The offence kind is a 16-byte identifier such as
+    /// `im-online:offlin`, persisted as text.
+    pub async fn save_offence_event(
+        &self,
+        block_hash: &str,
+        extrinsic_index: Option<i32>,
+        event_index: i32,
+        offence_kind: &[u8],
+        time_slot: &[u8],
+    ) -> anyhow::Result<Option<i32>> {
+        let offence_kind = String::from_utf8_lossy(offence_kind)
+            .trim_end_matches('\0')
+            .to_string();
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_event_offence (block_hash, extrinsic_index, event_index, offence_kind, time_slot)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id
+            "#,
+        )
+            .bind(block_hash)
+            .bind(extrinsic_index)
+            .bind(event_index)
+            .bind(offence_kind)
+            .bind(format!("0x{}", hex::encode_upper(time_slot)))
+            .fetch_optional(&self.connection_pool)
+            .await?;
+        if let Some(result) = maybe_result {
+            Ok(Some(result.0))
+        } else {
+            Ok(None)
+        }
+    }
+
     pub async fn save_new_account_event(
         &self,
         block_hash: &str,
@@ -855,6 +985,75 @@ impl PostgreSQLNetworkStorage {
         Ok(extrinsics)
     }
 
+    /// Last `count` successful `validate` extrinsics of the validator that have changed
+    /// its preferences, latest first.
+    pub async fn get_validator_preference_changes(
+        &self,
+        stash_account_id: &AccountId,
+        count: u32,
+    ) -> anyhow::Result<Vec<ValidatorPreferenceChange>> {
+        let db_changes: Vec<PostgresValidatorPreferenceChange> = sqlx::query_as(
+            r#"
+            SELECT block_number, block_timestamp, era_index, previous_commission_per_billion, commission_per_billion, previous_blocks_nominations, blocks_nominations
+            FROM (
+                SELECT B.number AS block_number, B.timestamp AS block_timestamp, B.era_index, E.commission_per_billion, E.blocks_nominations,
+                LAG(E.commission_per_billion) OVER (ORDER BY B.number ASC, E.extrinsic_index ASC) AS previous_commission_per_billion,
+                LAG(E.blocks_nominations) OVER (ORDER BY B.number ASC, E.extrinsic_index ASC) AS previous_blocks_nominations
+                FROM sub_extrinsic_validate E
+                INNER JOIN sub_block B
+                    ON B.hash = E.block_hash
+                WHERE E.stash_account_id = $1
+                AND E.is_successful = true
+            ) AS V
+            WHERE previous_commission_per_billion IS NULL
+            OR previous_commission_per_billion <> commission_per_billion
+            OR previous_blocks_nominations <> blocks_nominations
+            ORDER BY block_number DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(stash_account_id.to_string())
+        .bind(count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_changes
+            .iter()
+            .map(|db_change| ValidatorPreferenceChange {
+                block_number: db_change.0 as u64,
+                timestamp: db_change.1.map(|timestamp| timestamp as u64),
+                era_index: db_change.2 as u32,
+                previous_commission_per_billion: db_change.3.map(|commission| commission as u32),
+                commission_per_billion: db_change.4 as u32,
+                previous_blocks_nominations: db_change.5,
+                blocks_nominations: db_change.6,
+            })
+            .collect())
+    }
+
+    /// Stash account ids of the `validate` extrinsics with an id greater than the given id,
+    /// along with the greatest extrinsic id for each stash.
+    pub async fn get_validate_extrinsic_stash_account_ids_after_id(
+        &self,
+        id: u32,
+    ) -> anyhow::Result<Vec<(u32, AccountId)>> {
+        let db_records: Vec<(i32, String)> = sqlx::query_as(
+            r#"
+            SELECT MAX(id), stash_account_id
+            FROM sub_extrinsic_validate
+            WHERE id > $1
+            GROUP BY stash_account_id
+            "#,
+        )
+        .bind(id as i32)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut records = Vec::new();
+        for db_record in db_records {
+            records.push((db_record.0 as u32, AccountId::from_str(&db_record.1)?));
+        }
+        Ok(records)
+    }
+
     pub async fn save_validate_extrinsic(
         &self,
         block_hash: &str,
@@ -957,6 +1156,37 @@ impl PostgreSQLNetworkStorage {
         }
     }
 
+    /// Saves a staking call that doesn't have its own table, i.e. `bond_extra`, `chill`, `kick`,
+    /// `rebond`, `set_payee`, `unbond` and `withdraw_unbonded`. The amount is only set for the
+    /// calls with an amount argument.
+    pub async fn save_staking_call_extrinsic(
+        &self,
+        block_hash: &str,
+        extrinsic_index: i32,
+        is_nested_call: bool,
+        is_successful: bool,
+        (caller_account_id, call_name, maybe_amount): (&AccountId, &str, Option<Balance>),
+    ) -> anyhow::Result<Option<i32>> {
+        self.save_account(caller_account_id).await?;
+        let maybe_result: Option<(i32,)> = sqlx::query_as(
+            r#"
+            INSERT INTO sub_extrinsic_staking_call (block_hash, extrinsic_index, is_nested_call, caller_account_id, call_name, amount, is_successful)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#,
+        )
+        .bind(block_hash)
+        .bind(extrinsic_index)
+        .bind(is_nested_call)
+        .bind(caller_account_id.to_string())
+        .bind(call_name)
+        .bind(maybe_amount.map(|amount| amount.to_string()))
+        .bind(is_successful)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_result.map(|result| result.0))
+    }
+
     pub async fn save_bond_extrinsic(
         &self,
         block_hash: &str,
@@ -1041,6 +1271,62 @@ impl PostgreSQLNetworkStorage {
         })
     }
 
+    pub async fn get_validator_account_ids_slashed_since_era(
+        &self,
+        era_index: u32,
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT DISTINCT S.validator_account_id
+            FROM sub_event_slashed S
+            INNER JOIN sub_block B
+                ON B.hash = S.block_hash
+            WHERE B.era_index >= $1
+            "#,
+        )
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
+    /// Returns the ones among the given accounts that have been killed (reaped) after
+    /// they were last created.
+    pub async fn get_killed_account_ids(
+        &self,
+        account_ids: &[AccountId],
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT A.id
+            FROM sub_account A
+            INNER JOIN sub_block KB
+                ON KB.hash = A.killed_at_block_hash
+            LEFT JOIN sub_block DB
+                ON DB.hash = A.discovered_at_block_hash
+            WHERE A.id = ANY($1)
+            AND (DB.number IS NULL OR KB.number > DB.number)
+            "#,
+        )
+        .bind(
+            account_ids
+                .iter()
+                .map(|account_id| account_id.to_string())
+                .collect::<Vec<String>>(),
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
     pub async fn save_heartbeat_extrinsic(
         &self,
         block_hash: &str,
@@ -1,17 +1,26 @@
 //! PostgreSQL notifications support, and SubVT notification generator state storage.
 use crate::postgres::network::PostgreSQLNetworkStorage;
+use log::debug;
 use serde::Serialize;
 use sqlx::postgres::PgListener;
-use subvt_types::rdb::BlockProcessedNotification;
+use subvt_types::domain_event::{DomainEvent, DomainEventNotification};
+use subvt_types::rdb::{BlockProcessedNotification, ReportInvalidationNotification};
+
+/// Maximum number of domain events fetched from the changefeed at once.
+const DOMAIN_EVENT_FETCH_LIMIT: u32 = 1000;
 
 enum Channel {
     BlockProcessed,
+    DomainEvent,
+    ReportInvalidation,
 }
 
 impl Channel {
     pub fn get_name(&self) -> &str {
         match self {
             Self::BlockProcessed => "block_processed",
+            Self::DomainEvent => "domain_event",
+            Self::ReportInvalidation => "report_invalidation",
         }
     }
 }
@@ -64,13 +73,252 @@ impl PostgreSQLNetworkStorage {
         }
     }
 
-    pub async fn get_notification_generator_state(&self) -> anyhow::Result<Option<(String, u64)>> {
+    pub async fn notify_report_invalidation(
+        &self,
+        block_number: u64,
+        era_indices: Vec<u32>,
+    ) -> anyhow::Result<()> {
+        self.notify(
+            Channel::ReportInvalidation.get_name(),
+            &ReportInvalidationNotification {
+                block_number,
+                era_indices,
+            },
+        )
+        .await
+    }
+
+    /// Notifications sent while not listening are lost, so the listener should drop all its
+    /// cached reports when it (re)connects.
+    pub async fn subscribe_to_report_invalidations<F>(&self, callback: F) -> anyhow::Result<()>
+    where
+        F: Fn(ReportInvalidationNotification),
+    {
+        let mut listener = PgListener::connect(&self.uri).await?;
+        listener
+            .listen(Channel::ReportInvalidation.get_name())
+            .await?;
+        loop {
+            let pg_notification = listener.recv().await?;
+            let notification: ReportInvalidationNotification =
+                serde_json::from_str(pg_notification.payload())?;
+            callback(notification)
+        }
+    }
+
+    /// Calls the callback for each domain event after the given event id, first for the
+    /// already persisted ones, then for the new ones as they get persisted. Notifications only
+    /// wake the listener up (they're sent by the insert trigger of `sub_domain_event`), the
+    /// events are read from the table, so none are missed in between.
+    pub async fn subscribe_to_domain_events<F>(
+        &self,
+        after_event_id: u64,
+        callback: F,
+    ) -> anyhow::Result<()>
+    where
+        F: Fn(DomainEvent),
+    {
+        let mut listener = PgListener::connect(&self.uri).await?;
+        listener.listen(Channel::DomainEvent.get_name()).await?;
+        let mut last_event_id = after_event_id;
+        loop {
+            loop {
+                let events = self
+                    .get_domain_events_after_id(last_event_id, DOMAIN_EVENT_FETCH_LIMIT)
+                    .await?;
+                let event_count = events.len();
+                for event in events {
+                    last_event_id = event.id;
+                    callback(event);
+                }
+                if event_count < DOMAIN_EVENT_FETCH_LIMIT as usize {
+                    break;
+                }
+            }
+            let pg_notification = listener.recv().await?;
+            let notification: DomainEventNotification =
+                serde_json::from_str(pg_notification.payload())?;
+            debug!(
+                "Domain event notification #{} {}.",
+                notification.id, notification.event_type
+            );
+        }
+    }
+
+    /// Records the heartbeat of a notification generator instance, which keeps the instance
+    /// in the work partitioning for the lease period. The run id distinguishes the process of
+    /// the instance from the other processes that have the same instance id. Returns `false`
+    /// without recording the heartbeat if another process with the same instance id has sent a
+    /// heartbeat within the lease period.
+    pub async fn save_notification_generator_heartbeat(
+        &self,
+        instance_id: &str,
+        run_id: &str,
+        lease_seconds: u64,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sub_notification_generator_instance(instance_id, run_id)
+            VALUES ($1, $2)
+            ON CONFLICT(instance_id) DO UPDATE
+            SET heartbeat_at = now(), run_id = EXCLUDED.run_id
+            WHERE sub_notification_generator_instance.run_id = EXCLUDED.run_id
+            OR sub_notification_generator_instance.heartbeat_at <= now() - make_interval(secs => $3)
+            "#,
+        )
+        .bind(instance_id)
+        .bind(run_id)
+        .bind(lease_seconds as f64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gets the ids of the notification generator instances that have sent a heartbeat within
+    /// the lease period, in ascending order.
+    pub async fn get_live_notification_generator_instance_ids(
+        &self,
+        lease_seconds: u64,
+    ) -> anyhow::Result<Vec<String>> {
+        let db_instance_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT instance_id
+            FROM sub_notification_generator_instance
+            WHERE heartbeat_at > now() - make_interval(secs => $1)
+            ORDER BY instance_id ASC
+            "#,
+        )
+        .bind(lease_seconds as f64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_instance_ids
+            .into_iter()
+            .map(|instance_id| instance_id.0)
+            .collect())
+    }
+
+    /// Reserves the block number for the notification generator instance, i.e. guarantees that
+    /// the work partition of the block doesn't change after this call, and gets the partition
+    /// epochs that may apply to the block or to the following blocks, as
+    /// `(effective block number, instance ids)` in ascending order of the effective block number.
+    /// See `save_notification_generator_epoch`.
+    pub async fn reserve_notification_generator_block(
+        &self,
+        instance_id: &str,
+        block_number: u64,
+    ) -> anyhow::Result<Vec<(u64, Vec<String>)>> {
+        let mut transaction = self.connection_pool.begin().await?;
+        sqlx::query("LOCK TABLE sub_notification_generator_epoch IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut transaction)
+            .await?;
+        sqlx::query(
+            r#"
+            UPDATE sub_notification_generator_instance
+            SET reserved_block_number = GREATEST(reserved_block_number, $2)
+            WHERE instance_id = $1
+            "#,
+        )
+        .bind(instance_id)
+        .bind(block_number as i64)
+        .execute(&mut transaction)
+        .await?;
+        let db_epochs: Vec<(i64, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT effective_block_number, instance_ids
+            FROM sub_notification_generator_epoch
+            WHERE effective_block_number >= COALESCE(
+                (
+                    SELECT MAX(effective_block_number)
+                    FROM sub_notification_generator_epoch
+                    WHERE effective_block_number <= $1
+                ),
+                0
+            )
+            ORDER BY effective_block_number ASC
+            "#,
+        )
+        .bind(block_number as i64)
+        .fetch_all(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(db_epochs
+            .into_iter()
+            .map(|(effective_block_number, instance_ids)| {
+                (effective_block_number as u64, instance_ids)
+            })
+            .collect())
+    }
+
+    /// Starts a new work partition epoch of the notification generator instances if the
+    /// instances differ from the ones of the latest epoch. The epoch takes effect after the
+    /// highest block number reserved by any instance, so that no block that has already been
+    /// processed, or is being processed, changes its partition. Returns the effective block
+    /// number of the new epoch, `None` if the instances haven't changed.
+    pub async fn save_notification_generator_epoch(
+        &self,
+        instance_ids: &[String],
+    ) -> anyhow::Result<Option<u64>> {
+        let mut transaction = self.connection_pool.begin().await?;
+        sqlx::query("LOCK TABLE sub_notification_generator_epoch IN SHARE ROW EXCLUSIVE MODE")
+            .execute(&mut transaction)
+            .await?;
+        let maybe_latest_epoch: Option<(i64, Vec<String>)> = sqlx::query_as(
+            r#"
+            SELECT effective_block_number, instance_ids
+            FROM sub_notification_generator_epoch
+            ORDER BY effective_block_number DESC
+            LIMIT 1
+            "#,
+        )
+        .fetch_optional(&mut transaction)
+        .await?;
+        if let Some((_, latest_instance_ids)) = &maybe_latest_epoch {
+            if latest_instance_ids == instance_ids {
+                return Ok(None);
+            }
+        }
+        let max_reserved_block_number: (i64,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(MAX(reserved_block_number), 0)
+            FROM sub_notification_generator_instance
+            "#,
+        )
+        .fetch_one(&mut transaction)
+        .await?;
+        // the latest epoch gets replaced if no block of it has been reserved yet
+        let effective_block_number = (max_reserved_block_number.0 + 1).max(
+            maybe_latest_epoch
+                .map(|(effective_block_number, _)| effective_block_number)
+                .unwrap_or(0),
+        );
+        sqlx::query(
+            r#"
+            INSERT INTO sub_notification_generator_epoch(effective_block_number, instance_ids)
+            VALUES ($1, $2)
+            ON CONFLICT(effective_block_number) DO UPDATE
+            SET instance_ids = EXCLUDED.instance_ids, created_at = now()
+            "#,
+        )
+        .bind(effective_block_number)
+        .bind(instance_ids.to_vec())
+        .execute(&mut transaction)
+        .await?;
+        transaction.commit().await?;
+        Ok(Some(effective_block_number as u64))
+    }
+
+    pub async fn get_notification_generator_state(
+        &self,
+        instance_id: &str,
+    ) -> anyhow::Result<Option<(String, u64)>> {
         Ok(sqlx::query_as(
             r#"
                 SELECT last_processed_block_hash, last_processed_block_number
                 FROM sub_notification_generator_state
+                WHERE instance_id = $1
                 "#,
         )
+        .bind(instance_id)
         .fetch_optional(&self.connection_pool)
         .await?
         .map(|state: (String, i64)| (state.0, state.1 as u64)))
@@ -78,17 +326,19 @@ impl PostgreSQLNetworkStorage {
 
     pub async fn save_notification_generator_state(
         &self,
+        instance_id: &str,
         last_processed_block_hash: &str,
         last_processed_block_number: u64,
     ) -> anyhow::Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO sub_notification_generator_state(id, last_processed_block_hash, last_processed_block_number)
-            VALUES (1, $1, $2)
-            ON CONFLICT(id) DO UPDATE
+            INSERT INTO sub_notification_generator_state(instance_id, last_processed_block_hash, last_processed_block_number)
+            VALUES ($1, $2, $3)
+            ON CONFLICT(instance_id) DO UPDATE
             SET last_processed_block_hash = EXCLUDED.last_processed_block_hash, last_processed_block_number = EXCLUDED.last_processed_block_number, updated_at = now()
             "#,
         )
+            .bind(instance_id)
             .bind(last_processed_block_hash)
             .bind(last_processed_block_number as i64)
             .execute(&self.connection_pool)
@@ -96,39 +346,186 @@ impl PostgreSQLNetworkStorage {
         Ok(())
     }
 
-    pub async fn notification_generator_has_processed_era(
+    /// Claims the evaluation of the periodic check of the notification type in the period of
+    /// the cadence for the generator instance, see `schedule` in `subvt-notification-generator`.
+    /// The periods are global, so each period gets evaluated by a single instance. Returns
+    /// `false` if the period has already been evaluated, or if it's claimed by another instance
+    /// that is still live, i.e. has sent a heartbeat within the lease period.
+    pub async fn claim_notification_generator_check_period(
         &self,
-        era_index: u32,
+        instance_id: &str,
+        (notification_type_code, cadence, period_index): (&str, &str, u64),
+        lease_seconds: u64,
     ) -> anyhow::Result<bool> {
-        let result: (bool,) = sqlx::query_as(
+        let result = sqlx::query(
             r#"
-                SELECT EXISTS(
-                    SELECT era_index
-                    FROM sub_notification_generator_processed_era
-                    WHERE era_index = $1
+            INSERT INTO sub_notification_generator_check_period(notification_type_code, cadence, period_index, instance_id)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT(notification_type_code, cadence, period_index) DO UPDATE
+            SET instance_id = EXCLUDED.instance_id, claimed_at = now()
+            WHERE sub_notification_generator_check_period.completed_at IS NULL
+            AND (
+                sub_notification_generator_check_period.instance_id = EXCLUDED.instance_id
+                OR NOT EXISTS(
+                    SELECT instance_id
+                    FROM sub_notification_generator_instance
+                    WHERE instance_id = sub_notification_generator_check_period.instance_id
+                    AND heartbeat_at > now() - make_interval(secs => $5)
                 )
-                "#,
+            )
+            "#,
         )
-        .bind(era_index as i64)
-        .fetch_one(&self.connection_pool)
+        .bind(notification_type_code)
+        .bind(cadence)
+        .bind(period_index as i64)
+        .bind(instance_id)
+        .bind(lease_seconds as f64)
+        .execute(&self.connection_pool)
         .await?;
-        Ok(result.0)
+        Ok(result.rows_affected() > 0)
     }
 
-    pub async fn save_notification_generator_processed_era(
+    /// Marks the period of the check claimed by the instance as evaluated.
+    pub async fn complete_notification_generator_check_period(
         &self,
-        era_index: u32,
+        instance_id: &str,
+        (notification_type_code, cadence, period_index): (&str, &str, u64),
     ) -> anyhow::Result<()> {
         sqlx::query(
             r#"
-            INSERT INTO sub_notification_generator_processed_era(era_index)
-            VALUES ($1)
-            ON CONFLICT(era_index) DO NOTHING
+            UPDATE sub_notification_generator_check_period
+            SET completed_at = now()
+            WHERE notification_type_code = $1
+            AND cadence = $2
+            AND period_index = $3
+            AND instance_id = $4
+            "#,
+        )
+        .bind(notification_type_code)
+        .bind(cadence)
+        .bind(period_index as i64)
+        .bind(instance_id)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Releases the claim of the instance on the period of the check without marking it as
+    /// evaluated, so that the period can be claimed again, e.g. if the check is not ready to
+    /// be evaluated yet or its evaluation has failed.
+    pub async fn release_notification_generator_check_period(
+        &self,
+        instance_id: &str,
+        (notification_type_code, cadence, period_index): (&str, &str, u64),
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM sub_notification_generator_check_period
+            WHERE notification_type_code = $1
+            AND cadence = $2
+            AND period_index = $3
+            AND instance_id = $4
+            AND completed_at IS NULL
+            "#,
+        )
+        .bind(notification_type_code)
+        .bind(cadence)
+        .bind(period_index as i64)
+        .bind(instance_id)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Removes the evaluation records of the era-cadence checks for the era, so that the
+    /// notification generator evaluates the checks (e.g. for unclaimed payouts) again while it
+    /// is still the active era.
+    pub async fn delete_notification_generator_era_checks(
+        &self,
+        era_index: u32,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM sub_notification_generator_check_period
+            WHERE cadence = 'era'
+            AND period_index = $1
             "#,
         )
         .bind(era_index as i64)
         .execute(&self.connection_pool)
         .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Claims the check of the validator votes on the referendum for the generator instance.
+    /// Each referendum gets checked by a single instance. Returns `false` if the referendum has
+    /// already been checked, or if it's claimed by another instance that is still live.
+    pub async fn claim_notification_generator_referendum(
+        &self,
+        instance_id: &str,
+        referendum_index: u32,
+        lease_seconds: u64,
+    ) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sub_notification_generator_processed_referendum(referendum_index, instance_id)
+            VALUES ($1, $2)
+            ON CONFLICT(referendum_index) DO UPDATE
+            SET instance_id = EXCLUDED.instance_id, claimed_at = now()
+            WHERE sub_notification_generator_processed_referendum.completed_at IS NULL
+            AND (
+                sub_notification_generator_processed_referendum.instance_id = EXCLUDED.instance_id
+                OR NOT EXISTS(
+                    SELECT instance_id
+                    FROM sub_notification_generator_instance
+                    WHERE instance_id = sub_notification_generator_processed_referendum.instance_id
+                    AND heartbeat_at > now() - make_interval(secs => $3)
+                )
+            )
+            "#,
+        )
+        .bind(referendum_index as i64)
+        .bind(instance_id)
+        .bind(lease_seconds as f64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn complete_notification_generator_referendum(
+        &self,
+        instance_id: &str,
+        referendum_index: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE sub_notification_generator_processed_referendum
+            SET completed_at = now()
+            WHERE referendum_index = $1 AND instance_id = $2
+            "#,
+        )
+        .bind(referendum_index as i64)
+        .bind(instance_id)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn release_notification_generator_referendum(
+        &self,
+        instance_id: &str,
+        referendum_index: u32,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            DELETE FROM sub_notification_generator_processed_referendum
+            WHERE referendum_index = $1 AND instance_id = $2 AND completed_at IS NULL
+            "#,
+        )
+        .bind(referendum_index as i64)
+        .bind(instance_id)
+        .execute(&self.connection_pool)
+        .await?;
         Ok(())
     }
 }
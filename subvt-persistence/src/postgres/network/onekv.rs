@@ -1,7 +1,9 @@
 //! 1KV-related storage - for Polkadot and Kusama.
 use crate::postgres::network::PostgreSQLNetworkStorage;
 use subvt_types::crypto::AccountId;
-use subvt_types::onekv::{OneKVCandidateDetails, OneKVValidity};
+use subvt_types::onekv::{
+    OneKVArchivedCandidate, OneKVCandidateDetails, OneKVCandidateHistoryItem, OneKVValidity,
+};
 
 impl PostgreSQLNetworkStorage {
     pub async fn save_onekv_candidate(
@@ -109,6 +111,40 @@ impl PostgreSQLNetworkStorage {
                 .execute(&mut transaction)
                 .await?;
         }
+        // rank and score history for the charts
+        sqlx::query(
+            r#"
+            INSERT INTO sub_onekv_candidate_history (validator_account_id, rank, is_valid, score_total, score_aggregate, recorded_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(candidate_details.rank)
+        .bind(candidate_details.is_valid())
+        .bind(candidate_details.score.as_ref().map(|score| score.total))
+        .bind(candidate_details.score.as_ref().map(|score| score.aggregate))
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(&mut transaction)
+        .await?;
+        sqlx::query(
+            r#"
+            DELETE FROM sub_onekv_candidate_history
+            WHERE validator_account_id = $1
+            AND is_backfilled = false
+            AND id NOT IN
+            (
+                SELECT id FROM sub_onekv_candidate_history
+                WHERE validator_account_id = $1
+                AND is_backfilled = false
+                ORDER BY id DESC
+                LIMIT $2
+            )
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(history_record_count)
+        .execute(&mut transaction)
+        .await?;
         transaction.commit().await?;
 
         // only keep the relevant number of candidate records
@@ -168,4 +204,67 @@ impl PostgreSQLNetworkStorage {
             })
             .collect())
     }
+
+    /// Saves a rank and score history record of the candidate from an archived candidate list.
+    /// Backfilled records are kept regardless of the history record count. A record is only
+    /// saved if it's earlier than the candidate's first record from the live data, and if
+    /// there isn't already a record with the same timestamp, so the backfill can be re-run.
+    /// Returns whether the record has been saved.
+    pub async fn save_onekv_candidate_backfill_history_item(
+        &self,
+        validator_account_id: &AccountId,
+        candidate: &OneKVArchivedCandidate,
+        recorded_at: u64,
+    ) -> anyhow::Result<bool> {
+        self.save_account(validator_account_id).await?;
+        let result = sqlx::query(
+            r#"
+            INSERT INTO sub_onekv_candidate_history (validator_account_id, rank, is_valid, score_total, score_aggregate, recorded_at, is_backfilled)
+            SELECT $1, $2, $3, $4, $5, $6, true
+            WHERE NOT EXISTS (
+                SELECT id FROM sub_onekv_candidate_history
+                WHERE validator_account_id = $1
+                AND (recorded_at = $6 OR (is_backfilled = false AND recorded_at <= $6))
+            )
+            "#,
+        )
+        .bind(validator_account_id.to_string())
+        .bind(candidate.rank)
+        .bind(candidate.is_valid)
+        .bind(candidate.score.as_ref().map(|score| score.total))
+        .bind(candidate.score.as_ref().map(|score| score.aggregate))
+        .bind(recorded_at as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Gets the rank and score history of the 1KV candidate, earliest first.
+    pub async fn get_onekv_candidate_history(
+        &self,
+        validator_account_id: &AccountId,
+    ) -> anyhow::Result<Vec<OneKVCandidateHistoryItem>> {
+        let db_history_items: Vec<(i64, Option<i64>, bool, Option<f64>, Option<f64>)> =
+            sqlx::query_as(
+                r#"
+                SELECT recorded_at, rank, is_valid, score_total, score_aggregate
+                FROM sub_onekv_candidate_history
+                WHERE validator_account_id = $1
+                ORDER BY recorded_at ASC, id ASC
+                "#,
+            )
+            .bind(validator_account_id.to_string())
+            .fetch_all(&self.connection_pool)
+            .await?;
+        Ok(db_history_items
+            .iter()
+            .map(|db_history_item| OneKVCandidateHistoryItem {
+                timestamp: db_history_item.0 as u64,
+                rank: db_history_item.1,
+                is_valid: db_history_item.2,
+                score_total: db_history_item.3,
+                score_aggregate: db_history_item.4,
+            })
+            .collect())
+    }
 }
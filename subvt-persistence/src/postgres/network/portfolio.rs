@@ -0,0 +1,321 @@
+//! Storage related to the portfolio (watch-only nominator dashboard) report.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::HashMap;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{PendingReward, PortfolioEvent, PortfolioEventType};
+use subvt_types::substrate::Balance;
+
+type PostgresPendingReward = (i64, String, String, String, i64, Option<i64>, String, i64);
+
+/// Expected and received rewards of a nominator for an era.
+#[derive(Clone, Debug, Default)]
+pub struct NominatorEraReward {
+    pub expected_reward: Balance,
+    pub received_reward: Balance,
+    /// Validators of the nominator that haven't been paid out for the era.
+    pub unpaid_validator_account_ids: Vec<AccountId>,
+}
+
+/// Exposure of a nominator on an oversubscribed validator in an era, which is outside the
+/// largest exposures that get rewarded.
+#[derive(Clone, Debug)]
+pub struct UnrewardedExposure {
+    pub validator_account_id: AccountId,
+    pub stake: Balance,
+    /// Smallest stake among the rewarded exposures of the validator.
+    pub min_rewarded_stake: Balance,
+    pub nominator_count: u32,
+}
+
+/// Estimates the reward of a staker from the era data of its validator. The reward of a
+/// validator is shared by its stakers in proportion to their stakes, after the commission.
+/// `None` if the validator has no stake.
+fn estimate_staker_reward(db_reward: &PostgresPendingReward) -> anyhow::Result<Option<Balance>> {
+    const BILLION: u128 = 1_000_000_000;
+    let stake: u128 = db_reward.2.parse()?;
+    let validator_total_stake: u128 = db_reward.3.parse()?;
+    let reward_points = db_reward.4.max(0) as u128;
+    let commission_per_billion = (db_reward.5.unwrap_or(0).max(0) as u128).min(BILLION);
+    let era_total_validator_reward: u128 = db_reward.6.parse()?;
+    let era_total_reward_points = db_reward.7 as u128;
+    if validator_total_stake == 0 {
+        return Ok(None);
+    }
+    let validator_reward = era_total_validator_reward * reward_points / era_total_reward_points;
+    let staker_reward = validator_reward * (BILLION - commission_per_billion) / BILLION;
+    // float share, the product of the amounts may overflow
+    Ok(Some(
+        (staker_reward as f64 * (stake as f64 / validator_total_stake as f64)) as u128,
+    ))
+}
+
+impl PostgreSQLNetworkStorage {
+    /// Estimates the rewards of the nominator from the active eras of its validators since the
+    /// given era, for which the validator hasn't been paid out yet.
+    pub async fn get_pending_rewards(
+        &self,
+        nominator_account_id: &AccountId,
+        start_era_index: u32,
+    ) -> anyhow::Result<Vec<PendingReward>> {
+        let db_pending_rewards: Vec<PostgresPendingReward> = sqlx::query_as(
+            r#"
+            SELECT S.era_index, S.validator_account_id, S.stake, V.total_stake, V.reward_points, V.commission_per_billion, E.total_validator_reward, E.total_reward_points
+            FROM sub_era_staker S
+            INNER JOIN sub_era_validator V
+                ON V.era_index = S.era_index
+                AND V.validator_account_id = S.validator_account_id
+            INNER JOIN sub_era E
+                ON E.index = S.era_index
+            WHERE S.nominator_account_id = $1
+            AND S.era_index >= $2
+            AND V.is_active = true
+            AND V.total_stake IS NOT NULL
+            AND E.total_validator_reward IS NOT NULL
+            AND E.total_reward_points > 0
+            AND NOT EXISTS (
+                SELECT id
+                FROM sub_extrinsic_payout_stakers EPS
+                WHERE EPS.validator_account_id = S.validator_account_id
+                AND EPS.era_index = S.era_index
+                AND EPS.is_successful = true
+            )
+            ORDER BY S.era_index ASC
+            "#,
+        )
+            .bind(nominator_account_id.to_string())
+            .bind(start_era_index as i64)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        let mut pending_rewards = Vec::new();
+        for db_pending_reward in db_pending_rewards {
+            if let Some(amount) = estimate_staker_reward(&db_pending_reward)? {
+                pending_rewards.push(PendingReward {
+                    era_index: db_pending_reward.0 as u32,
+                    validator_account_id: AccountId::from_str(&db_pending_reward.1)?,
+                    amount,
+                });
+            }
+        }
+        Ok(pending_rewards)
+    }
+
+    /// Compares the reward the nominator is expected to receive for the era, estimated from
+    /// the era data of its validators, with the reward it has received from the successful
+    /// payouts of the era. `None` if the nominator had no active stake in the era, or the era
+    /// rewards haven't been indexed yet.
+    pub async fn get_nominator_era_reward(
+        &self,
+        nominator_account_id: &AccountId,
+        era_index: u32,
+    ) -> anyhow::Result<Option<NominatorEraReward>> {
+        let db_rewards: Vec<(i64, String, String, String, i64, Option<i64>, String, i64, bool)> =
+            sqlx::query_as(
+                r#"
+            SELECT S.era_index, S.validator_account_id, S.stake, V.total_stake, V.reward_points, V.commission_per_billion, E.total_validator_reward, E.total_reward_points, EXISTS (
+                SELECT id
+                FROM sub_extrinsic_payout_stakers EPS
+                WHERE EPS.validator_account_id = S.validator_account_id
+                AND EPS.era_index = S.era_index
+                AND EPS.is_successful = true
+            )
+            FROM sub_era_staker S
+            INNER JOIN sub_era_validator V
+                ON V.era_index = S.era_index
+                AND V.validator_account_id = S.validator_account_id
+            INNER JOIN sub_era E
+                ON E.index = S.era_index
+            WHERE S.nominator_account_id = $1
+            AND S.era_index = $2
+            AND V.is_active = true
+            AND V.total_stake IS NOT NULL
+            AND E.total_validator_reward IS NOT NULL
+            AND E.total_reward_points > 0
+            "#,
+            )
+            .bind(nominator_account_id.to_string())
+            .bind(era_index as i64)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        if db_rewards.is_empty() {
+            return Ok(None);
+        }
+        let mut era_reward = NominatorEraReward::default();
+        for db_reward in db_rewards {
+            let is_paid = db_reward.8;
+            let db_pending_reward: PostgresPendingReward = (
+                db_reward.0,
+                db_reward.1,
+                db_reward.2,
+                db_reward.3,
+                db_reward.4,
+                db_reward.5,
+                db_reward.6,
+                db_reward.7,
+            );
+            if let Some(amount) = estimate_staker_reward(&db_pending_reward)? {
+                era_reward.expected_reward += amount;
+            }
+            if !is_paid {
+                era_reward
+                    .unpaid_validator_account_ids
+                    .push(AccountId::from_str(&db_pending_reward.1)?);
+            }
+        }
+        // rewards of the payouts of the era, a batch of payouts is matched by extrinsic index
+        let received_reward: (String,) = sqlx::query_as(
+            r#"
+            SELECT COALESCE(SUM(R.amount::numeric), 0)::text
+            FROM sub_event_rewarded R
+            WHERE R.rewardee_account_id = $1
+            AND EXISTS (
+                SELECT id
+                FROM sub_extrinsic_payout_stakers EPS
+                WHERE EPS.block_hash = R.block_hash
+                AND EPS.extrinsic_index = R.extrinsic_index
+                AND EPS.era_index = $2
+                AND EPS.is_successful = true
+            )
+            "#,
+        )
+        .bind(nominator_account_id.to_string())
+        .bind(era_index as i64)
+        .fetch_one(&self.connection_pool)
+        .await?;
+        era_reward.received_reward = received_reward.0.parse()?;
+        Ok(Some(era_reward))
+    }
+
+    /// Gets the validators that have an active stake from the nominator in the given era.
+    pub async fn get_nominator_era_validator_account_ids(
+        &self,
+        nominator_account_id: &AccountId,
+        era_index: u32,
+    ) -> anyhow::Result<Vec<AccountId>> {
+        let db_account_ids: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id
+            FROM sub_era_staker
+            WHERE nominator_account_id = $1
+            AND era_index = $2
+            "#,
+        )
+        .bind(nominator_account_id.to_string())
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut account_ids = Vec::new();
+        for db_account_id in db_account_ids {
+            account_ids.push(AccountId::from_str(&db_account_id.0)?);
+        }
+        Ok(account_ids)
+    }
+
+    /// Gets the most recent reward and slash events of the given accounts, latest first.
+    pub async fn get_portfolio_events(
+        &self,
+        stash_account_ids: &[AccountId],
+        count: u32,
+    ) -> anyhow::Result<Vec<PortfolioEvent>> {
+        let account_id_hex_strings: Vec<String> = stash_account_ids
+            .iter()
+            .map(|account_id| account_id.to_string())
+            .collect();
+        let db_events: Vec<(String, bool, i64, Option<i64>, String)> = sqlx::query_as(
+            r#"
+            SELECT account_id, is_reward, block_number, timestamp, amount
+            FROM (
+                SELECT L.account_id AS account_id, true AS is_reward, L.block_number AS block_number, L.timestamp AS timestamp, L.amount AS amount, L.event_index AS event_index
+                FROM sub_account_reward_ledger L
+                WHERE L.account_id = ANY($1)
+                UNION ALL
+                SELECT S.validator_account_id, false, B.number, B.timestamp, S.amount, S.event_index
+                FROM sub_event_slashed S
+                INNER JOIN sub_block B
+                    ON B.hash = S.block_hash
+                WHERE S.validator_account_id = ANY($1)
+            ) E
+            ORDER BY block_number DESC, event_index DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(&account_id_hex_strings)
+        .bind(count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut events = Vec::new();
+        for db_event in db_events {
+            events.push(PortfolioEvent {
+                stash_account_id: AccountId::from_str(&db_event.0)?,
+                event_type: if db_event.1 {
+                    PortfolioEventType::Rewarded
+                } else {
+                    PortfolioEventType::Slashed
+                },
+                block_number: db_event.2 as u64,
+                timestamp: db_event.3.map(|timestamp| timestamp as u64),
+                amount: db_event.4.parse()?,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Gets the exposures of the nominator in the era that don't get rewarded, i.e. the ones on
+    /// the validators with more nominators than `max_rewarded_nominator_count` that are not
+    /// among the largest `max_rewarded_nominator_count` exposures of the validator.
+    pub async fn get_nominator_unrewarded_exposures(
+        &self,
+        nominator_account_id: &AccountId,
+        era_index: u32,
+        max_rewarded_nominator_count: u32,
+    ) -> anyhow::Result<Vec<UnrewardedExposure>> {
+        let db_era_stakers: Vec<(String, String, String)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, nominator_account_id, stake
+            FROM sub_era_staker
+            WHERE era_index = $1
+            AND validator_account_id IN (
+                SELECT validator_account_id
+                FROM sub_era_staker
+                WHERE era_index = $1
+                AND nominator_account_id = $2
+            )
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(nominator_account_id.to_string())
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let nominator_account_id = nominator_account_id.to_string();
+        // (nominator account id, stake) pairs of each validator
+        let mut validator_stakes: HashMap<String, Vec<(String, Balance)>> = HashMap::new();
+        for (validator_account_id, staker_account_id, stake) in db_era_stakers {
+            validator_stakes
+                .entry(validator_account_id)
+                .or_default()
+                .push((staker_account_id, stake.parse()?));
+        }
+        let max_rewarded_nominator_count = max_rewarded_nominator_count as usize;
+        let mut exposures = Vec::new();
+        for (validator_account_id, mut stakes) in validator_stakes {
+            if max_rewarded_nominator_count == 0 || stakes.len() <= max_rewarded_nominator_count {
+                continue;
+            }
+            stakes.sort_by(|a, b| b.1.cmp(&a.1));
+            let position = match stakes
+                .iter()
+                .position(|(staker_account_id, _)| *staker_account_id == nominator_account_id)
+            {
+                Some(position) if position >= max_rewarded_nominator_count => position,
+                _ => continue,
+            };
+            exposures.push(UnrewardedExposure {
+                validator_account_id: AccountId::from_str(&validator_account_id)?,
+                stake: stakes[position].1,
+                min_rewarded_stake: stakes[max_rewarded_nominator_count - 1].1,
+                nominator_count: stakes.len() as u32,
+            });
+        }
+        Ok(exposures)
+    }
+}
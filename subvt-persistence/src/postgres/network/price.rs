@@ -0,0 +1,88 @@
+//! Storage related to the fiat price history of the network token.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use chrono::NaiveDateTime;
+use subvt_types::subvt::TokenPrice;
+
+impl PostgreSQLNetworkStorage {
+    /// Saves the prices in a single transaction, all prices get the same timestamp.
+    pub async fn save_token_prices(&self, prices: &[(String, f64)]) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        for (currency_code, price) in prices {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_token_price (currency_code, price)
+                VALUES ($1, $2)
+                "#,
+            )
+            .bind(currency_code)
+            .bind(price)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_latest_token_price(
+        &self,
+        currency_code: &str,
+    ) -> anyhow::Result<Option<TokenPrice>> {
+        let maybe_db_price: Option<(String, f64, NaiveDateTime)> = sqlx::query_as(
+            r#"
+            SELECT currency_code, price, created_at
+            FROM sub_token_price
+            WHERE currency_code = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(currency_code)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(
+            maybe_db_price.map(|(currency_code, price, created_at)| TokenPrice {
+                currency_code,
+                price,
+                timestamp: created_at.timestamp_millis() as u64,
+            }),
+        )
+    }
+
+    /// Gets the price history in the given millisecond timestamp range, earliest first.
+    pub async fn get_token_price_history(
+        &self,
+        currency_code: &str,
+        start_timestamp: u64,
+        end_timestamp: u64,
+    ) -> anyhow::Result<Vec<TokenPrice>> {
+        let db_prices: Vec<(String, f64, NaiveDateTime)> = sqlx::query_as(
+            r#"
+            SELECT currency_code, price, created_at
+            FROM sub_token_price
+            WHERE currency_code = $1
+            AND created_at >= $2
+            AND created_at <= $3
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(currency_code)
+        .bind(NaiveDateTime::from_timestamp(
+            start_timestamp as i64 / 1000,
+            (start_timestamp % 1000) as u32 * 1_000_000,
+        ))
+        .bind(NaiveDateTime::from_timestamp(
+            end_timestamp as i64 / 1000,
+            (end_timestamp % 1000) as u32 * 1_000_000,
+        ))
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_prices
+            .into_iter()
+            .map(|(currency_code, price, created_at)| TokenPrice {
+                currency_code,
+                price,
+                timestamp: created_at.timestamp_millis() as u64,
+            })
+            .collect())
+    }
+}
@@ -1,7 +1,14 @@
 //! Era and validator report storage and types.
 use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::str::FromStr;
-use subvt_types::report::{EraReport, EraValidatorReport};
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{
+    CommissionBandComparison, CommissionComparisonReport, EraOffenceReport, EraReport,
+    EraValidatorNominatorChurn, EraValidatorPayoutLag, EraValidatorReport, OffenceReport,
+    RewardProjection, ValidatorNominatorChurnReport, ValidatorPayoutLagReport,
+    ValidatorRewardProjection,
+};
 use subvt_types::substrate::Era;
 
 type PostgresEraValidatorReport = (
@@ -18,6 +25,9 @@ type PostgresEraValidatorReport = (
     i32,
     i64,
     i32,
+    i32,
+    i32,
+    i32,
 );
 
 type PostgresEraReport = (
@@ -53,7 +63,7 @@ impl PostgreSQLNetworkStorage {
     ) -> anyhow::Result<Option<EraValidatorReport>> {
         let era_validator_report: PostgresEraValidatorReport = sqlx::query_as(
             r#"
-            SELECT era_start_timestamp, era_end_timestamp, is_active, commission_per_billion, self_stake, total_stake, block_count, reward_points, self_reward, staker_reward, offline_offence_count, slashed_amount, chilling_count
+            SELECT era_start_timestamp, era_end_timestamp, is_active, commission_per_billion, self_stake, total_stake, block_count, reward_points, self_reward, staker_reward, offline_offence_count, slashed_amount, chilling_count, primary_block_count, secondary_plain_block_count, secondary_vrf_block_count
             FROM sub_get_era_validator_report($1, $2)
             "#
         )
@@ -78,6 +88,9 @@ impl PostgreSQLNetworkStorage {
                 self_stake: parse_maybe_string(&era_validator_report.4)?,
                 total_stake: parse_maybe_string(&era_validator_report.5)?,
                 block_count: era_validator_report.6 as u32,
+                primary_block_count: era_validator_report.13 as u32,
+                secondary_plain_block_count: era_validator_report.14 as u32,
+                secondary_vrf_block_count: era_validator_report.15 as u32,
                 reward_points: era_validator_report.7.map(|value| value as u128),
                 self_reward: era_validator_report.8 as u128,
                 staker_reward: era_validator_report.9 as u128,
@@ -173,4 +186,577 @@ impl PostgreSQLNetworkStorage {
         };
         Ok(era_reports)
     }
+
+    /// Gets the offence and slash statistics of the indexed eras in the range.
+    pub async fn get_offence_report(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+    ) -> anyhow::Result<OffenceReport> {
+        let mut report = OffenceReport {
+            start_era_index,
+            end_era_index,
+            ..Default::default()
+        };
+        if start_era_index > end_era_index {
+            return Ok(report);
+        }
+        let db_era_indices: Vec<(i64,)> = sqlx::query_as(
+            r#"
+            SELECT index
+            FROM sub_era
+            WHERE index BETWEEN $1 AND $2
+            ORDER BY index ASC
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut era_reports: BTreeMap<u32, EraOffenceReport> = db_era_indices
+            .iter()
+            .map(|db_era_index| {
+                (
+                    db_era_index.0 as u32,
+                    EraOffenceReport {
+                        era_index: db_era_index.0 as u32,
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+        let db_offence_counts: Vec<(i64, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT B.era_index, O.offence_kind, COUNT(O.id)
+            FROM sub_event_offence O
+            INNER JOIN sub_block B
+                ON B.hash = O.block_hash
+            WHERE B.era_index BETWEEN $1 AND $2
+            GROUP BY B.era_index, O.offence_kind
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        for (era_index, offence_kind, count) in db_offence_counts {
+            if let Some(era_report) = era_reports.get_mut(&(era_index as u32)) {
+                era_report.offence_count += count as u32;
+                era_report
+                    .offence_counts_by_kind
+                    .insert(offence_kind, count as u32);
+            }
+        }
+        let db_offline_offence_counts: Vec<(i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT B.era_index, COUNT(EVO.id)
+            FROM sub_event_validator_offline EVO
+            INNER JOIN sub_block B
+                ON B.hash = EVO.block_hash
+            WHERE B.era_index BETWEEN $1 AND $2
+            GROUP BY B.era_index
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        for (era_index, count) in db_offline_offence_counts {
+            if let Some(era_report) = era_reports.get_mut(&(era_index as u32)) {
+                era_report.offline_offence_count = count as u32;
+            }
+        }
+        let db_slashes: Vec<(i64, i64, i64, String)> = sqlx::query_as(
+            r#"
+            SELECT B.era_index, COUNT(ES.id), COUNT(DISTINCT ES.validator_account_id), SUM(ES.amount::numeric)::VARCHAR
+            FROM sub_event_slashed ES
+            INNER JOIN sub_block B
+                ON B.hash = ES.block_hash
+            WHERE B.era_index BETWEEN $1 AND $2
+            GROUP BY B.era_index
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        for (era_index, slash_count, slashed_validator_count, slashed_amount) in db_slashes {
+            if let Some(era_report) = era_reports.get_mut(&(era_index as u32)) {
+                era_report.slash_count = slash_count as u32;
+                era_report.slashed_validator_count = slashed_validator_count as u32;
+                era_report.slashed_amount = slashed_amount.parse()?;
+            }
+        }
+        for era_report in era_reports.values() {
+            report.total_offence_count += era_report.offence_count;
+            for (offence_kind, count) in &era_report.offence_counts_by_kind {
+                *report
+                    .total_offence_counts_by_kind
+                    .entry(offence_kind.clone())
+                    .or_insert(0) += count;
+            }
+            report.total_offline_offence_count += era_report.offline_offence_count;
+            report.total_slash_count += era_report.slash_count;
+            report.total_slashed_amount += era_report.slashed_amount;
+        }
+        report.eras = era_reports.into_values().collect();
+        Ok(report)
+    }
+
+    /// Compares the nominator set of each era with the one of the previous era in which the
+    /// validator had an exposure. The first era in the range gets compared with the last
+    /// exposure before the range, if any.
+    pub async fn get_validator_nominator_churn_report(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+        validator_account_id_hex_string: &str,
+    ) -> anyhow::Result<ValidatorNominatorChurnReport> {
+        let mut report = ValidatorNominatorChurnReport {
+            start_era_index,
+            end_era_index,
+            ..Default::default()
+        };
+        if start_era_index > end_era_index {
+            return Ok(report);
+        }
+        let db_era_stakers: Vec<(i64, String)> = sqlx::query_as(
+            r#"
+            SELECT era_index, nominator_account_id
+            FROM sub_era_staker
+            WHERE validator_account_id = $1
+            AND (
+                era_index BETWEEN $2 AND $3
+                OR era_index = (
+                    SELECT MAX(era_index)
+                    FROM sub_era_staker
+                    WHERE validator_account_id = $1
+                    AND era_index < $2
+                )
+            )
+            "#,
+        )
+        .bind(validator_account_id_hex_string)
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut era_nominators: BTreeMap<u32, HashSet<String>> = BTreeMap::new();
+        for (era_index, nominator_account_id) in db_era_stakers {
+            era_nominators
+                .entry(era_index as u32)
+                .or_default()
+                .insert(nominator_account_id);
+        }
+        let empty_set = HashSet::new();
+        let mut previous_nominators: &HashSet<String> = &empty_set;
+        // consecutive eras count of the currently running nominations
+        let mut tenures: HashMap<&String, u32> = HashMap::new();
+        let mut completed_tenures: Vec<u32> = Vec::new();
+        for (era_index, nominators) in &era_nominators {
+            if *era_index < start_era_index {
+                previous_nominators = nominators;
+                continue;
+            }
+            let joined_nominator_count = nominators.difference(previous_nominators).count();
+            let left_nominator_count = previous_nominators.difference(nominators).count();
+            for nominator in previous_nominators.difference(nominators) {
+                if let Some(tenure) = tenures.remove(nominator) {
+                    completed_tenures.push(tenure);
+                }
+            }
+            for nominator in nominators {
+                *tenures.entry(nominator).or_default() += 1;
+            }
+            let tenure_sum: u32 = nominators.iter().map(|nominator| tenures[nominator]).sum();
+            report.eras.push(EraValidatorNominatorChurn {
+                era_index: *era_index,
+                nominator_count: nominators.len() as u32,
+                joined_nominator_count: joined_nominator_count as u32,
+                left_nominator_count: left_nominator_count as u32,
+                average_nominator_tenure_eras: if nominators.is_empty() {
+                    0.0
+                } else {
+                    tenure_sum as f64 / nominators.len() as f64
+                },
+            });
+            report.total_joined_nominator_count += joined_nominator_count as u32;
+            report.total_left_nominator_count += left_nominator_count as u32;
+            previous_nominators = nominators;
+        }
+        completed_tenures.extend(tenures.values());
+        if !completed_tenures.is_empty() {
+            report.average_nominator_tenure_eras =
+                completed_tenures.iter().sum::<u32>() as f64 / completed_tenures.len() as f64;
+        }
+        Ok(report)
+    }
+
+    /// Gets the payout lag of each era in the range in which the validator was active, i.e. the
+    /// number of eras that elapsed after the end of the era before the first successful
+    /// `payout_stakers` call for the era. The percentiles are of the nearest-rank kind.
+    pub async fn get_validator_payout_lag_report(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+        validator_account_id_hex_string: &str,
+    ) -> anyhow::Result<ValidatorPayoutLagReport> {
+        let mut report = ValidatorPayoutLagReport {
+            start_era_index,
+            end_era_index,
+            ..Default::default()
+        };
+        let db_era_payouts: Vec<(i64, Option<i64>)> = sqlx::query_as(
+            r#"
+            SELECT EV.era_index, MIN(B.era_index)
+            FROM sub_era_validator EV
+            LEFT JOIN sub_extrinsic_payout_stakers EPS
+                ON EPS.validator_account_id = EV.validator_account_id
+                AND EPS.era_index = EV.era_index
+                AND EPS.is_successful = true
+            LEFT JOIN sub_block B
+                ON B.hash = EPS.block_hash
+            WHERE EV.validator_account_id = $1
+            AND EV.is_active = true
+            AND EV.era_index BETWEEN $2 AND $3
+            GROUP BY EV.era_index
+            ORDER BY EV.era_index ASC
+            "#,
+        )
+        .bind(validator_account_id_hex_string)
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut payout_lags: Vec<u32> = Vec::new();
+        for (era_index, maybe_payout_era_index) in db_era_payouts {
+            let maybe_payout_lag_eras = maybe_payout_era_index
+                .map(|payout_era_index| (payout_era_index - era_index - 1).max(0) as u32);
+            if let Some(payout_lag_eras) = maybe_payout_lag_eras {
+                payout_lags.push(payout_lag_eras);
+            } else {
+                report.unpaid_era_count += 1;
+            }
+            report.eras.push(EraValidatorPayoutLag {
+                era_index: era_index as u32,
+                payout_era_index: maybe_payout_era_index.map(|index| index as u32),
+                payout_lag_eras: maybe_payout_lag_eras,
+            });
+        }
+        report.paid_era_count = payout_lags.len() as u32;
+        if !payout_lags.is_empty() {
+            payout_lags.sort_unstable();
+            let percentile = |percent: usize| {
+                let rank = (percent * payout_lags.len() + 99) / 100;
+                payout_lags[rank.max(1) - 1]
+            };
+            report.average_payout_lag_eras =
+                Some(payout_lags.iter().sum::<u32>() as f64 / payout_lags.len() as f64);
+            report.median_payout_lag_eras = Some(percentile(50));
+            report.p90_payout_lag_eras = Some(percentile(90));
+            report.max_payout_lag_eras = payout_lags.last().cloned();
+        }
+        Ok(report)
+    }
+
+    /// Gets the average payout lag (see `get_validator_payout_lag_report`) of each validator in
+    /// thousandths of an era, over its eras paid out within the `era_count` eras before the
+    /// given era.
+    pub async fn get_average_payout_lags(
+        &self,
+        era_index: u32,
+        era_count: u32,
+    ) -> anyhow::Result<HashMap<AccountId, u32>> {
+        let db_average_payout_lags: Vec<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, ROUND(AVG(GREATEST(payout_era_index - era_index - 1, 0)) * 1000)::bigint
+            FROM (
+                SELECT EPS.validator_account_id, EPS.era_index, MIN(B.era_index) AS payout_era_index
+                FROM sub_extrinsic_payout_stakers EPS
+                INNER JOIN sub_block B
+                    ON B.hash = EPS.block_hash
+                WHERE EPS.is_successful = true
+                AND EPS.era_index >= $1
+                AND EPS.era_index < $2
+                GROUP BY EPS.validator_account_id, EPS.era_index
+            ) AS EP
+            GROUP BY validator_account_id
+            "#,
+        )
+        .bind(era_index.saturating_sub(era_count) as i64)
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut average_payout_lags = HashMap::new();
+        for (account_id_hex_string, average_payout_lag_thousandths) in db_average_payout_lags {
+            average_payout_lags.insert(
+                AccountId::from_str(&account_id_hex_string)?,
+                average_payout_lag_thousandths as u32,
+            );
+        }
+        Ok(average_payout_lags)
+    }
+
+    /// Gets the reward points of the validator in the most recent `era_count` eras in which
+    /// it was a validator (active or inactive), in ascending era order.
+    pub async fn get_validator_era_reward_points(
+        &self,
+        validator_account_id_hex_string: &str,
+        era_count: u32,
+    ) -> anyhow::Result<Vec<(u32, u32)>> {
+        let db_era_reward_points: Vec<(i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT era_index, reward_points
+            FROM sub_era_validator
+            WHERE validator_account_id = $1
+            ORDER BY era_index DESC
+            LIMIT $2
+            "#,
+        )
+        .bind(validator_account_id_hex_string)
+        .bind(era_count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_era_reward_points
+            .iter()
+            .rev()
+            .map(|(era_index, reward_points)| (*era_index as u32, *reward_points as u32))
+            .collect())
+    }
+
+    /// Projects the rewards of the stake, evenly distributed among the given validators, using
+    /// the most recent `era_count` eras with complete reward data. The validator reward of an
+    /// era is its share of the era payout by reward points. The nominator share is the
+    /// allocated stake's share of the validator reward after commission, with the allocated
+    /// stake added to the validator's backing stake.
+    pub async fn get_reward_projection(
+        &self,
+        stake: u128,
+        validator_account_ids: &[AccountId],
+        era_count: u32,
+    ) -> anyhow::Result<RewardProjection> {
+        const YEAR_MILLIS: u128 = 365 * 24 * 60 * 60 * 1000;
+        const BILLION: u128 = 1_000_000_000;
+        let mut projection = RewardProjection {
+            stake,
+            ..Default::default()
+        };
+        let db_eras: Vec<(i64, i64, i64, String, i64)> = sqlx::query_as(
+            r#"
+            SELECT index, start_timestamp, end_timestamp, total_validator_reward, total_reward_points
+            FROM sub_era
+            WHERE total_validator_reward IS NOT NULL
+            AND total_reward_points > 0
+            ORDER BY index DESC
+            LIMIT $1
+            "#,
+        )
+            .bind(era_count as i64)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        if db_eras.is_empty() || validator_account_ids.is_empty() {
+            return Ok(projection);
+        }
+        // era index -> (total validator reward, total reward points)
+        let mut era_rewards: HashMap<u32, (u128, u128)> = HashMap::new();
+        let mut total_era_duration_millis = 0;
+        for db_era in &db_eras {
+            era_rewards.insert(db_era.0 as u32, (db_era.3.parse()?, db_era.4 as u128));
+            total_era_duration_millis += (db_era.2 - db_era.1).max(0) as u64;
+        }
+        projection.era_count = db_eras.len() as u32;
+        projection.start_era_index = db_eras.last().unwrap().0 as u32;
+        projection.end_era_index = db_eras.first().unwrap().0 as u32;
+        projection.average_era_duration_millis =
+            total_era_duration_millis / projection.era_count as u64;
+        let allocated_stake = stake / validator_account_ids.len() as u128;
+        for validator_account_id in validator_account_ids {
+            let db_era_validators: Vec<(i64, Option<i64>, Option<String>, i64)> = sqlx::query_as(
+                r#"
+                SELECT era_index, commission_per_billion, total_stake, reward_points
+                FROM sub_era_validator
+                WHERE validator_account_id = $1
+                AND is_active = true
+                AND era_index BETWEEN $2 AND $3
+                "#,
+            )
+            .bind(validator_account_id.to_string())
+            .bind(projection.start_era_index as i64)
+            .bind(projection.end_era_index as i64)
+            .fetch_all(&self.connection_pool)
+            .await?;
+            let mut validator_projection = ValidatorRewardProjection {
+                account_id: validator_account_id.clone(),
+                allocated_stake,
+                ..Default::default()
+            };
+            let mut total_reward_points: u128 = 0;
+            let mut total_commission_per_billion: u128 = 0;
+            let mut total_stake: u128 = 0;
+            let mut total_reward: u128 = 0;
+            for db_era_validator in &db_era_validators {
+                let (era_total_validator_reward, era_total_reward_points) =
+                    match era_rewards.get(&(db_era_validator.0 as u32)) {
+                        Some(era_reward) => *era_reward,
+                        None => continue,
+                    };
+                let commission_per_billion =
+                    (db_era_validator.1.unwrap_or(0).max(0) as u128).min(BILLION);
+                let validator_total_stake: u128 =
+                    parse_maybe_string(&db_era_validator.2)?.unwrap_or(0);
+                let reward_points = db_era_validator.3.max(0) as u128;
+                validator_projection.active_era_count += 1;
+                total_reward_points += reward_points;
+                total_commission_per_billion += commission_per_billion;
+                total_stake += validator_total_stake;
+                let validator_reward =
+                    era_total_validator_reward * reward_points / era_total_reward_points;
+                let staker_reward = validator_reward * (BILLION - commission_per_billion) / BILLION;
+                let backing_stake = validator_total_stake + allocated_stake;
+                if backing_stake > 0 {
+                    // float share, the product of the amounts may overflow
+                    total_reward += (staker_reward as f64
+                        * (allocated_stake as f64 / backing_stake as f64))
+                        as u128;
+                }
+            }
+            if validator_projection.active_era_count > 0 {
+                let active_era_count = validator_projection.active_era_count as u128;
+                validator_projection.average_reward_points =
+                    (total_reward_points / active_era_count) as u32;
+                validator_projection.average_commission_per_billion =
+                    (total_commission_per_billion / active_era_count) as u32;
+                validator_projection.average_total_stake = total_stake / active_era_count;
+            }
+            // inactive eras count as zero reward
+            validator_projection.expected_era_reward = total_reward / projection.era_count as u128;
+            projection.expected_era_reward += validator_projection.expected_era_reward;
+            projection.validators.push(validator_projection);
+        }
+        if projection.average_era_duration_millis > 0 {
+            projection.expected_annual_reward = projection.expected_era_reward * YEAR_MILLIS
+                / projection.average_era_duration_millis as u128;
+        }
+        if stake > 0 {
+            projection.expected_annual_return_rate =
+                projection.expected_annual_reward as f64 / stake as f64;
+        }
+        Ok(projection)
+    }
+
+    /// Compares the active validators of the most recent `era_count` eras with complete reward
+    /// data in the commission bands defined by the given ascending lower bounds. The return of
+    /// a validator era is the validator reward after commission over the total backing stake.
+    pub async fn get_commission_comparison_report(
+        &self,
+        band_lower_bounds_per_billion: &[u32],
+        era_count: u32,
+    ) -> anyhow::Result<CommissionComparisonReport> {
+        const YEAR_MILLIS: f64 = (365 * 24 * 60 * 60 * 1000) as f64;
+        const BILLION: u128 = 1_000_000_000;
+        let mut report = CommissionComparisonReport::default();
+        // (validator account ids, validator era count, total reward points, total return rate)
+        let mut band_totals: Vec<(HashSet<String>, u32, u64, f64)> =
+            vec![Default::default(); band_lower_bounds_per_billion.len()];
+        for (index, lower_bound) in band_lower_bounds_per_billion.iter().enumerate() {
+            report.bands.push(CommissionBandComparison {
+                min_commission_per_billion: *lower_bound,
+                max_commission_per_billion: band_lower_bounds_per_billion
+                    .get(index + 1)
+                    .cloned()
+                    .unwrap_or(BILLION as u32),
+                ..Default::default()
+            });
+        }
+        let db_eras: Vec<(i64, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT index, start_timestamp, end_timestamp
+            FROM sub_era
+            WHERE total_validator_reward IS NOT NULL
+            AND total_reward_points > 0
+            ORDER BY index DESC
+            LIMIT $1
+            "#,
+        )
+        .bind(era_count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        if db_eras.is_empty() || report.bands.is_empty() {
+            return Ok(report);
+        }
+        report.era_count = db_eras.len() as u32;
+        report.start_era_index = db_eras.last().unwrap().0 as u32;
+        report.end_era_index = db_eras.first().unwrap().0 as u32;
+        report.average_era_duration_millis = db_eras
+            .iter()
+            .map(|db_era| (db_era.2 - db_era.1).max(0) as u64)
+            .sum::<u64>()
+            / report.era_count as u64;
+        let db_era_validators: Vec<(String, Option<i64>, String, i64, String, i64)> =
+            sqlx::query_as(
+                r#"
+                SELECT V.validator_account_id, V.commission_per_billion, V.total_stake, V.reward_points, E.total_validator_reward, E.total_reward_points
+                FROM sub_era_validator V
+                INNER JOIN sub_era E
+                    ON E.index = V.era_index
+                WHERE V.era_index BETWEEN $1 AND $2
+                AND V.is_active = true
+                AND V.total_stake IS NOT NULL
+                AND E.total_validator_reward IS NOT NULL
+                AND E.total_reward_points > 0
+                "#,
+            )
+            .bind(report.start_era_index as i64)
+            .bind(report.end_era_index as i64)
+            .fetch_all(&self.connection_pool)
+            .await?;
+        for db_era_validator in db_era_validators {
+            let commission_per_billion =
+                (db_era_validator.1.unwrap_or(0).max(0) as u128).min(BILLION);
+            let band_index = match band_lower_bounds_per_billion
+                .iter()
+                .rposition(|lower_bound| *lower_bound as u128 <= commission_per_billion)
+            {
+                Some(band_index) => band_index,
+                None => continue,
+            };
+            let validator_total_stake: u128 = db_era_validator.2.parse()?;
+            let reward_points = db_era_validator.3.max(0) as u128;
+            let era_total_validator_reward: u128 = db_era_validator.4.parse()?;
+            let era_total_reward_points = db_era_validator.5 as u128;
+            let (
+                validator_account_ids,
+                validator_era_count,
+                total_reward_points,
+                total_return_rate,
+            ) = &mut band_totals[band_index];
+            validator_account_ids.insert(db_era_validator.0);
+            *validator_era_count += 1;
+            *total_reward_points += reward_points as u64;
+            if validator_total_stake > 0 {
+                let validator_reward =
+                    era_total_validator_reward * reward_points / era_total_reward_points;
+                let staker_reward = validator_reward * (BILLION - commission_per_billion) / BILLION;
+                *total_return_rate += staker_reward as f64 / validator_total_stake as f64;
+            }
+        }
+        for (
+            band,
+            (validator_account_ids, validator_era_count, total_reward_points, total_return_rate),
+        ) in report.bands.iter_mut().zip(band_totals)
+        {
+            band.validator_count = validator_account_ids.len() as u32;
+            band.validator_era_count = validator_era_count;
+            if validator_era_count > 0 {
+                band.average_reward_points =
+                    (total_reward_points / validator_era_count as u64) as u32;
+                band.average_era_return_rate = total_return_rate / validator_era_count as f64;
+            }
+            if report.average_era_duration_millis > 0 {
+                band.average_annual_return_rate = band.average_era_return_rate * YEAR_MILLIS
+                    / report.average_era_duration_millis as f64;
+            }
+        }
+        Ok(report)
+    }
 }
@@ -0,0 +1,337 @@
+//! Storage related to the data retention policies, the pruning of the indexed data by age and
+//! the disk space usage of the tables. Used by the admin endpoints of `subvt-report-service`
+//! and the `retention` command of `subvt-cli`.
+use crate::postgres::app::PostgreSQLAppStorage;
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use chrono::NaiveDateTime;
+use log::debug;
+use std::collections::BTreeSet;
+use std::str::FromStr;
+use subvt_types::rdb::{
+    DataPruneResult, DataRetentionCategory, DataRetentionPolicy, TableSpaceUsage,
+};
+
+/// Telemetry time series, TimescaleDB hypertables.
+const TELEMETRY_HYPERTABLES: [&str; 2] = [
+    "sub_telemetry_node_stats",
+    "sub_telemetry_node_network_stats",
+];
+
+/// Raw extrinsic tables that neither the reports nor the SQL functions read. The others, such
+/// as `sub_extrinsic_validate`, `sub_extrinsic_payout_stakers` and `sub_extrinsic_heartbeat`,
+/// are kept indefinitely. The nominated validators of a nomination get pruned through their
+/// cascading foreign key.
+const PRUNABLE_EXTRINSIC_TABLES: [&str; 4] = [
+    "sub_extrinsic_nominate",
+    "sub_extrinsic_set_controller",
+    "sub_extrinsic_bond",
+    "sub_extrinsic_staking_call",
+];
+
+/// Raw event tables that neither the reports nor the SQL functions read. The others, such as
+/// `sub_event_era_paid`, `sub_event_rewarded`, `sub_event_offence`, `sub_event_slashed` and
+/// `sub_event_democracy_started`, are kept indefinitely.
+const PRUNABLE_EVENT_TABLES: [&str; 7] = [
+    "sub_event_batch_item_completed",
+    "sub_event_batch_interrupted",
+    "sub_event_batch_completed",
+    "sub_event_heartbeat_received",
+    "sub_event_new_account",
+    "sub_event_killed_account",
+    "sub_event_nominator_kicked",
+];
+
+/// Maximum number of rows deleted by a single pruning statement.
+const PRUNE_BATCH_SIZE: u64 = 10_000;
+
+type PostgresDataRetentionPolicy = (String, i32, Option<NaiveDateTime>, Option<i64>);
+
+const MILLIS_PER_DAY: u64 = 24 * 60 * 60 * 1000;
+
+impl PostgreSQLNetworkStorage {
+    pub async fn get_data_retention_policies(&self) -> anyhow::Result<Vec<DataRetentionPolicy>> {
+        let db_policies: Vec<PostgresDataRetentionPolicy> = sqlx::query_as(
+            r#"
+            SELECT category, retention_days, last_pruned_at, last_pruned_row_count
+            FROM sub_data_retention
+            ORDER BY category ASC
+            "#,
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut policies = Vec::new();
+        for db_policy in db_policies {
+            policies.push(DataRetentionPolicy {
+                category: DataRetentionCategory::from_str(&db_policy.0)?,
+                retention_days: db_policy.1 as u32,
+                last_pruned_at: db_policy.2,
+                last_pruned_row_count: db_policy.3.map(|count| count as u64),
+            });
+        }
+        Ok(policies)
+    }
+
+    /// Creates or updates the policy of the category. The TimescaleDB retention policy of the
+    /// telemetry hypertables gets replaced too, so that the chunks keep getting dropped in the
+    /// background between the pruning runs.
+    pub async fn save_data_retention_policy(
+        &self,
+        category: DataRetentionCategory,
+        retention_days: u32,
+    ) -> anyhow::Result<()> {
+        let mut transaction = self.connection_pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO sub_data_retention (category, retention_days)
+            VALUES ($1, $2)
+            ON CONFLICT (category) DO UPDATE
+            SET retention_days = EXCLUDED.retention_days, updated_at = now()
+            "#,
+        )
+        .bind(category.to_string())
+        .bind(retention_days as i32)
+        .execute(&mut transaction)
+        .await?;
+        if category == DataRetentionCategory::Telemetry {
+            for hypertable in TELEMETRY_HYPERTABLES {
+                sqlx::query("SELECT remove_retention_policy($1::regclass, if_exists => true)")
+                    .bind(hypertable)
+                    .execute(&mut transaction)
+                    .await?;
+                sqlx::query("SELECT add_retention_policy($1::regclass, make_interval(days => $2))")
+                    .bind(hypertable)
+                    .bind(retention_days as i32)
+                    .execute(&mut transaction)
+                    .await?;
+            }
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Deletes the policy of the category, so that its data is kept indefinitely.
+    /// Returns `false` if the category has no policy.
+    pub async fn delete_data_retention_policy(
+        &self,
+        category: DataRetentionCategory,
+    ) -> anyhow::Result<bool> {
+        let mut transaction = self.connection_pool.begin().await?;
+        let maybe_deleted: Option<(String,)> = sqlx::query_as(
+            r#"
+            DELETE FROM sub_data_retention
+            WHERE category = $1
+            RETURNING category
+            "#,
+        )
+        .bind(category.to_string())
+        .fetch_optional(&mut transaction)
+        .await?;
+        if category == DataRetentionCategory::Telemetry {
+            for hypertable in TELEMETRY_HYPERTABLES {
+                sqlx::query("SELECT remove_retention_policy($1::regclass, if_exists => true)")
+                    .bind(hypertable)
+                    .execute(&mut transaction)
+                    .await?;
+            }
+        }
+        transaction.commit().await?;
+        Ok(maybe_deleted.is_some())
+    }
+
+    pub async fn save_data_prune_result(
+        &self,
+        category: DataRetentionCategory,
+        deleted_count: u64,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE sub_data_retention
+            SET last_pruned_at = now(), last_pruned_row_count = $1, updated_at = now()
+            WHERE category = $2
+            "#,
+        )
+        .bind(deleted_count as i64)
+        .bind(category.to_string())
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Deletes the rows of the tables that are older than the retention period, in batches of
+    /// `PRUNE_BATCH_SIZE` rows, so that no single statement locks a large part of a table. The
+    /// blocks themselves are kept. The cached reports of the eras of the deleted rows get
+    /// invalidated. Returns the number of deleted rows.
+    async fn prune_block_data(
+        &self,
+        table_names: &[&str],
+        retention_days: u32,
+    ) -> anyhow::Result<u64> {
+        let cutoff_timestamp = (chrono::Utc::now().timestamp_millis() as u64)
+            .saturating_sub(retention_days as u64 * MILLIS_PER_DAY);
+        let mut deleted_count = 0;
+        let mut pruned_era_indices = BTreeSet::new();
+        let mut max_pruned_block_number = 0;
+        for table_name in table_names {
+            loop {
+                // (era index, deleted row count, max block number) for each era of the batch
+                let era_deletions: Vec<(i64, i64, i64)> = sqlx::query_as(&format!(
+                    r#"
+                    WITH deleted AS (
+                        DELETE FROM {0}
+                        WHERE ctid IN (
+                            SELECT T.ctid
+                            FROM {0} T
+                            INNER JOIN sub_block B ON B.hash = T.block_hash
+                            WHERE B.timestamp < $1
+                            LIMIT $2
+                        )
+                        RETURNING block_hash
+                    )
+                    SELECT B.era_index, COUNT(*), MAX(B.number)
+                    FROM deleted D
+                    INNER JOIN sub_block B ON B.hash = D.block_hash
+                    GROUP BY B.era_index
+                    "#,
+                    table_name
+                ))
+                .bind(cutoff_timestamp as i64)
+                .bind(PRUNE_BATCH_SIZE as i64)
+                .fetch_all(&self.connection_pool)
+                .await?;
+                let mut batch_deleted_count = 0;
+                for (era_index, era_deleted_count, max_block_number) in era_deletions {
+                    pruned_era_indices.insert(era_index as u32);
+                    batch_deleted_count += era_deleted_count as u64;
+                    max_pruned_block_number = max_pruned_block_number.max(max_block_number as u64);
+                }
+                deleted_count += batch_deleted_count;
+                if batch_deleted_count < PRUNE_BATCH_SIZE {
+                    break;
+                }
+            }
+            debug!("Pruned {}.", table_name);
+        }
+        if !pruned_era_indices.is_empty() {
+            self.notify_report_invalidation(
+                max_pruned_block_number,
+                pruned_era_indices.into_iter().collect(),
+            )
+            .await?;
+        }
+        Ok(deleted_count)
+    }
+
+    /// Drops the telemetry hypertable chunks older than the retention period.
+    /// Returns the number of dropped chunks.
+    async fn prune_telemetry(&self, retention_days: u32) -> anyhow::Result<u64> {
+        let mut dropped_chunk_count = 0;
+        for hypertable in TELEMETRY_HYPERTABLES {
+            let count: (i64,) = sqlx::query_as(
+                r#"
+                SELECT COUNT(*)
+                FROM drop_chunks($1::regclass, older_than => make_interval(days => $2))
+                "#,
+            )
+            .bind(hypertable)
+            .bind(retention_days as i32)
+            .fetch_one(&self.connection_pool)
+            .await?;
+            dropped_chunk_count += count.0 as u64;
+        }
+        Ok(dropped_chunk_count)
+    }
+
+    /// Prunes the data of a category stored in the network database, i.e. all categories but
+    /// notifications, which are stored in the application database.
+    pub async fn prune_data(
+        &self,
+        category: DataRetentionCategory,
+        retention_days: u32,
+    ) -> anyhow::Result<u64> {
+        match category {
+            DataRetentionCategory::Extrinsics => {
+                self.prune_block_data(&PRUNABLE_EXTRINSIC_TABLES, retention_days)
+                    .await
+            }
+            DataRetentionCategory::Events => {
+                self.prune_block_data(&PRUNABLE_EVENT_TABLES, retention_days)
+                    .await
+            }
+            DataRetentionCategory::Telemetry => self.prune_telemetry(retention_days).await,
+            DataRetentionCategory::Notifications => Err(anyhow::anyhow!(
+                "Notifications are not stored in the network database."
+            )),
+        }
+    }
+
+    /// Prunes each category that has a retention policy, or only the given category, and records
+    /// the results. The notifications of the network get pruned in the application database.
+    pub async fn prune_data_by_policies(
+        &self,
+        app_postgres: &PostgreSQLAppStorage,
+        network_id: u32,
+        maybe_category: Option<DataRetentionCategory>,
+    ) -> anyhow::Result<Vec<DataPruneResult>> {
+        let mut results = Vec::new();
+        for policy in self.get_data_retention_policies().await? {
+            if maybe_category.is_some() && maybe_category != Some(policy.category) {
+                continue;
+            }
+            debug!(
+                "Prune {} older than {} days.",
+                policy.category, policy.retention_days
+            );
+            let deleted_count = match policy.category {
+                DataRetentionCategory::Notifications => {
+                    app_postgres
+                        .prune_notifications(network_id, policy.retention_days)
+                        .await?
+                }
+                category => self.prune_data(category, policy.retention_days).await?,
+            };
+            self.save_data_prune_result(policy.category, deleted_count)
+                .await?;
+            results.push(DataPruneResult {
+                category: policy.category,
+                retention_days: policy.retention_days,
+                deleted_count,
+            });
+        }
+        Ok(results)
+    }
+
+    /// Space usage of each table, largest first. The chunks of the hypertables are included in
+    /// their size.
+    pub async fn get_table_space_usages(&self) -> anyhow::Result<Vec<TableSpaceUsage>> {
+        let db_usages: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT C.relname::text, COALESCE(
+                CASE WHEN EXISTS (
+                    SELECT 1
+                    FROM timescaledb_information.hypertables H
+                    WHERE H.hypertable_schema = N.nspname
+                    AND H.hypertable_name = C.relname
+                ) THEN hypertable_size(C.oid)
+                ELSE pg_total_relation_size(C.oid)
+                END,
+                0
+            )::bigint AS total_bytes, GREATEST(C.reltuples, 0)::bigint
+            FROM pg_class C
+            INNER JOIN pg_namespace N ON N.oid = C.relnamespace
+            WHERE N.nspname = 'public'
+            AND C.relkind IN ('r', 'p')
+            ORDER BY total_bytes DESC, C.relname ASC
+            "#,
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_usages
+            .into_iter()
+            .map(|db_usage| TableSpaceUsage {
+                table_name: db_usage.0,
+                total_bytes: db_usage.1 as u64,
+                row_count_estimate: db_usage.2 as u64,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,167 @@
+//! Storage related to the reward ledger, the staking rewards of the accounts indexed together
+//! with their payouts and reward destinations. Reward reports and exports should read the
+//! ledger rather than the raw `Rewarded` events.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::RewardLedgerEntry;
+use subvt_types::substrate::RewardDestination;
+
+type PostgresRewardLedgerEntry = (
+    String,
+    i64,
+    Option<i64>,
+    i32,
+    Option<i64>,
+    Option<String>,
+    String,
+    Option<String>,
+    Option<String>,
+    bool,
+    Option<f64>,
+);
+
+/// The account of an `Account` destination is persisted as the destination account.
+fn get_reward_destination_kind(reward_destination: &RewardDestination) -> &'static str {
+    match reward_destination {
+        RewardDestination::Staked => "Staked",
+        RewardDestination::Stash => "Stash",
+        RewardDestination::Controller => "Controller",
+        RewardDestination::Account(_) => "Account",
+        RewardDestination::None => "None",
+    }
+}
+
+fn parse_reward_destination(
+    kind: &str,
+    maybe_destination_account_id: Option<&AccountId>,
+) -> anyhow::Result<RewardDestination> {
+    let reward_destination = match kind {
+        "Staked" => RewardDestination::Staked,
+        "Stash" => RewardDestination::Stash,
+        "Controller" => RewardDestination::Controller,
+        "Account" => match maybe_destination_account_id {
+            Some(account_id) => RewardDestination::Account(account_id.clone()),
+            None => anyhow::bail!("Account reward destination without account."),
+        },
+        "None" => RewardDestination::None,
+        _ => anyhow::bail!("Unknown reward destination: {}", kind),
+    };
+    Ok(reward_destination)
+}
+
+impl PostgreSQLNetworkStorage {
+    /// Saves the ledger entries of the rewards in the block. Idempotent, entries that have
+    /// already been saved for the block are skipped.
+    pub async fn save_reward_ledger_entries(
+        &self,
+        block_hash: &str,
+        entries: &[RewardLedgerEntry],
+    ) -> anyhow::Result<()> {
+        for entry in entries {
+            self.save_account(&entry.account_id).await?;
+        }
+        let mut transaction = self.connection_pool.begin().await?;
+        for entry in entries {
+            sqlx::query(
+                r#"
+                INSERT INTO sub_account_reward_ledger (block_hash, block_number, timestamp, event_index, account_id, era_index, validator_account_id, amount, reward_destination, destination_account_id, is_restaked)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                ON CONFLICT (block_hash, event_index) DO NOTHING
+                "#,
+            )
+            .bind(block_hash)
+            .bind(entry.block_number as i64)
+            .bind(entry.timestamp.map(|timestamp| timestamp as i64))
+            .bind(entry.event_index as i32)
+            .bind(entry.account_id.to_string())
+            .bind(entry.era_index.map(|era_index| era_index as i64))
+            .bind(
+                entry
+                    .validator_account_id
+                    .as_ref()
+                    .map(|account_id| account_id.to_string()),
+            )
+            .bind(entry.amount.to_string())
+            .bind(entry.reward_destination.as_ref().map(get_reward_destination_kind))
+            .bind(
+                entry
+                    .destination_account_id
+                    .as_ref()
+                    .map(|account_id| account_id.to_string()),
+            )
+            .bind(entry.is_restaked)
+            .execute(&mut transaction)
+            .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    /// Gets the rewards of the account in the given millisecond timestamp range, earliest
+    /// first. Each entry carries the latest token price in the given currency before the
+    /// reward, if a currency is given.
+    pub async fn get_reward_ledger(
+        &self,
+        account_id: &AccountId,
+        start_timestamp: u64,
+        end_timestamp: u64,
+        maybe_currency_code: Option<&str>,
+    ) -> anyhow::Result<Vec<RewardLedgerEntry>> {
+        let db_entries: Vec<PostgresRewardLedgerEntry> = sqlx::query_as(
+            r#"
+            SELECT L.account_id, L.block_number, L.timestamp, L.event_index, L.era_index, L.validator_account_id, L.amount, L.reward_destination, L.destination_account_id, L.is_restaked, P.price
+            FROM sub_account_reward_ledger L
+            LEFT JOIN LATERAL (
+                SELECT TP.price
+                FROM sub_token_price TP
+                WHERE TP.currency_code = $4
+                AND TP.created_at <= to_timestamp(L.timestamp / 1000.0) AT TIME ZONE 'UTC'
+                ORDER BY TP.created_at DESC
+                LIMIT 1
+            ) P ON true
+            WHERE L.account_id = $1
+            AND L.timestamp >= $2
+            AND L.timestamp <= $3
+            ORDER BY L.block_number ASC, L.event_index ASC
+            "#,
+        )
+        .bind(account_id.to_string())
+        .bind(start_timestamp as i64)
+        .bind(end_timestamp as i64)
+        .bind(maybe_currency_code)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut entries = Vec::new();
+        for db_entry in db_entries {
+            let destination_account_id = match &db_entry.8 {
+                Some(account_id) => Some(AccountId::from_str(account_id)?),
+                None => None,
+            };
+            let reward_destination = match &db_entry.7 {
+                Some(kind) => Some(parse_reward_destination(
+                    kind,
+                    destination_account_id.as_ref(),
+                )?),
+                None => None,
+            };
+            entries.push(RewardLedgerEntry {
+                account_id: AccountId::from_str(&db_entry.0)?,
+                block_number: db_entry.1 as u64,
+                timestamp: db_entry.2.map(|timestamp| timestamp as u64),
+                event_index: db_entry.3 as u32,
+                era_index: db_entry.4.map(|era_index| era_index as u32),
+                validator_account_id: match &db_entry.5 {
+                    Some(account_id) => Some(AccountId::from_str(account_id)?),
+                    None => None,
+                },
+                amount: db_entry.6.parse()?,
+                reward_destination,
+                destination_account_id,
+                is_restaked: db_entry.9,
+                token_price: db_entry.10,
+            });
+        }
+        Ok(entries)
+    }
+}
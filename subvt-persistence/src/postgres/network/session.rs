@@ -0,0 +1,181 @@
+//! Storage related to the sessions and the changes in the active validator set between them.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::report::{SessionSetChange, SessionSetChangeReason, SessionSetChangeReport};
+
+/// Indexed state of a validator in the era after the set change.
+struct NextEraValidator {
+    is_active: bool,
+    is_elected: bool,
+}
+
+impl PostgreSQLNetworkStorage {
+    /// Hash and era index of the first block of the session, i.e. the block of its `NewSession`
+    /// event. Returns `None` if the block is not indexed.
+    pub async fn get_session_first_block(
+        &self,
+        session_index: u32,
+    ) -> anyhow::Result<Option<(String, u32)>> {
+        let maybe_block: Option<(String, i64)> = sqlx::query_as(
+            r#"
+            SELECT B.hash, B.era_index
+            FROM sub_event_new_session S
+            INNER JOIN sub_block B
+                ON B.hash = S.block_hash
+            WHERE S.session_index = $1
+            ORDER BY B.number ASC
+            LIMIT 1
+            "#,
+        )
+        .bind(session_index as i64)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        Ok(maybe_block.map(|(block_hash, era_index)| (block_hash, era_index as u32)))
+    }
+
+    /// Era of the first block of the session.
+    async fn get_session_era_index(&self, session_index: u32) -> anyhow::Result<Option<u32>> {
+        Ok(self
+            .get_session_first_block(session_index)
+            .await?
+            .map(|(_, era_index)| era_index))
+    }
+
+    /// Stash account ids of the validators that have an event of the given table during the era.
+    async fn get_era_event_validator_account_ids(
+        &self,
+        table_name: &str,
+        account_id_column_name: &str,
+        era_index: u32,
+    ) -> anyhow::Result<HashSet<String>> {
+        let account_ids: Vec<(String,)> = sqlx::query_as(&format!(
+            r#"
+            SELECT DISTINCT E.{}
+            FROM {} E
+            INNER JOIN sub_block B
+                ON B.hash = E.block_hash
+            WHERE B.era_index = $1
+            "#,
+            account_id_column_name, table_name
+        ))
+        .bind(era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(account_ids
+            .into_iter()
+            .map(|account_id| account_id.0)
+            .collect())
+    }
+
+    /// Validators that joined or left the active set at the start of the session, compared to
+    /// the previous session. Joining validators have been elected. The reason of a leaving
+    /// validator is, in order of precedence, a slash or a chill during the previous era, missing
+    /// session keys if it has been elected but isn't active, or not being elected if it's still
+    /// a validator candidate. Returns `None` if the session or the previous session is not
+    /// indexed.
+    pub async fn get_session_set_change_report(
+        &self,
+        session_index: u32,
+    ) -> anyhow::Result<Option<SessionSetChangeReport>> {
+        if session_index == 0 {
+            return Ok(None);
+        }
+        let era_index = match self.get_session_era_index(session_index).await? {
+            Some(era_index) => era_index,
+            None => return Ok(None),
+        };
+        let previous_era_index = match self.get_session_era_index(session_index - 1).await? {
+            Some(era_index) => era_index,
+            None => return Ok(None),
+        };
+        let mut report = SessionSetChangeReport {
+            session_index,
+            era_index,
+            previous_era_index,
+            ..Default::default()
+        };
+        if era_index == previous_era_index {
+            return Ok(Some(report));
+        }
+        let db_validators: Vec<(String, i64, bool, bool)> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, era_index, is_active, total_stake IS NOT NULL
+            FROM sub_era_validator
+            WHERE era_index = $1 OR era_index = $2
+            "#,
+        )
+        .bind(era_index as i64)
+        .bind(previous_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut previous_active_account_ids: HashSet<String> = HashSet::new();
+        let mut next_era_validators: HashMap<String, NextEraValidator> = HashMap::new();
+        for (account_id, validator_era_index, is_active, is_elected) in db_validators {
+            if validator_era_index as u32 == era_index {
+                next_era_validators.insert(
+                    account_id,
+                    NextEraValidator {
+                        is_active,
+                        is_elected,
+                    },
+                );
+            } else if is_active {
+                previous_active_account_ids.insert(account_id);
+            }
+        }
+        for (account_id, validator) in &next_era_validators {
+            if validator.is_active && !previous_active_account_ids.contains(account_id) {
+                report.joined.push(SessionSetChange {
+                    validator_account_id: AccountId::from_str(account_id)?,
+                    reason: SessionSetChangeReason::Elected,
+                });
+            }
+        }
+        let slashed_account_ids = self
+            .get_era_event_validator_account_ids(
+                "sub_event_slashed",
+                "validator_account_id",
+                previous_era_index,
+            )
+            .await?;
+        let chilled_account_ids = self
+            .get_era_event_validator_account_ids(
+                "sub_event_chilled",
+                "stash_account_id",
+                previous_era_index,
+            )
+            .await?;
+        for account_id in &previous_active_account_ids {
+            let maybe_validator = next_era_validators.get(account_id);
+            if maybe_validator.map(|validator| validator.is_active) == Some(true) {
+                continue;
+            }
+            let reason = if slashed_account_ids.contains(account_id) {
+                SessionSetChangeReason::Slashed
+            } else if chilled_account_ids.contains(account_id) {
+                SessionSetChangeReason::Chilled
+            } else {
+                match maybe_validator {
+                    Some(validator) if validator.is_elected => {
+                        SessionSetChangeReason::SessionKeysMissing
+                    }
+                    Some(_) => SessionSetChangeReason::NotElected,
+                    None => SessionSetChangeReason::Unknown,
+                }
+            };
+            report.left.push(SessionSetChange {
+                validator_account_id: AccountId::from_str(account_id)?,
+                reason,
+            });
+        }
+        report
+            .joined
+            .sort_by_key(|change| change.validator_account_id.to_string());
+        report
+            .left
+            .sort_by_key(|change| change.validator_account_id.to_string());
+        Ok(Some(report))
+    }
+}
@@ -0,0 +1,59 @@
+//! Storage related to the BABE slot claims of the blocks.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::rdb::SessionSlotClaims;
+
+impl PostgreSQLNetworkStorage {
+    /// Returns `None` if no block with a slot claim has been indexed for the session yet.
+    pub async fn get_session_slot_claims(
+        &self,
+        session_index: u64,
+    ) -> anyhow::Result<Option<SessionSlotClaims>> {
+        let maybe_slot_range: Option<(Option<i64>, Option<i64>, Option<i64>)> = sqlx::query_as(
+            r#"
+            SELECT MAX(era_index), MIN(slot_number), MAX(slot_number)
+            FROM sub_block
+            WHERE epoch_index = $1
+            AND slot_number IS NOT NULL
+            "#,
+        )
+        .bind(session_index as i64)
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        let (era_index, first_slot_number, last_slot_number) = match maybe_slot_range {
+            Some((Some(era_index), Some(first_slot_number), Some(last_slot_number))) => (
+                era_index as u32,
+                first_slot_number as u64,
+                last_slot_number as u64,
+            ),
+            _ => return Ok(None),
+        };
+        let db_block_counts: Vec<(String, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT author_account_id, COUNT(*) FILTER (WHERE is_primary_slot), COUNT(*) FILTER (WHERE NOT is_primary_slot)
+            FROM sub_block
+            WHERE epoch_index = $1
+            AND author_account_id IS NOT NULL
+            AND slot_number IS NOT NULL
+            GROUP BY author_account_id
+            "#,
+        )
+        .bind(session_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut slot_claims = SessionSlotClaims {
+            era_index,
+            first_slot_number,
+            last_slot_number,
+            ..Default::default()
+        };
+        for (account_id_hex_string, primary_block_count, secondary_block_count) in db_block_counts {
+            slot_claims.validator_block_counts.insert(
+                AccountId::from_str(&account_id_hex_string)?,
+                (primary_block_count as u32, secondary_block_count as u32),
+            );
+        }
+        Ok(Some(slot_claims))
+    }
+}
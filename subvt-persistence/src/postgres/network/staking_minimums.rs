@@ -0,0 +1,71 @@
+//! Storage related to the minimum bonds and the minimum active stakes of the eras, recorded by
+//! `subvt-block-processor` at the start of each era.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use subvt_types::report::EraStakingMinimums;
+use subvt_types::substrate::Balance;
+
+type PostgresEraStakingMinimums = (i64, Option<String>, Option<String>, Option<String>, String);
+
+pub(crate) fn parse_maybe_balance(
+    maybe_balance: Option<String>,
+) -> anyhow::Result<Option<Balance>> {
+    match maybe_balance {
+        Some(balance) => Ok(Some(balance.parse()?)),
+        None => Ok(None),
+    }
+}
+
+impl PostgreSQLNetworkStorage {
+    pub async fn update_era_staking_minimums(
+        &self,
+        era_index: u32,
+        (min_nominator_bond, min_validator_bond): (Option<Balance>, Option<Balance>),
+        min_active_nominator_stake: Option<Balance>,
+    ) -> anyhow::Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE sub_era
+            SET min_nominator_bond = $1, min_validator_bond = $2, min_active_nominator_stake = $3, updated_at = now()
+            WHERE index = $4
+            "#,
+        )
+        .bind(min_nominator_bond.map(|balance| balance.to_string()))
+        .bind(min_validator_bond.map(|balance| balance.to_string()))
+        .bind(min_active_nominator_stake.map(|balance| balance.to_string()))
+        .bind(era_index as i64)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gets the minimums of the indexed eras in the range, in ascending era order.
+    pub async fn get_era_staking_minimums(
+        &self,
+        start_era_index: u32,
+        end_era_index: u32,
+    ) -> anyhow::Result<Vec<EraStakingMinimums>> {
+        let db_minimums: Vec<PostgresEraStakingMinimums> = sqlx::query_as(
+            r#"
+            SELECT index, min_nominator_bond, min_validator_bond, min_active_nominator_stake, minimum_stake
+            FROM sub_era
+            WHERE index BETWEEN $1 AND $2
+            ORDER BY index ASC
+            "#,
+        )
+        .bind(start_era_index as i64)
+        .bind(end_era_index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut minimums = Vec::new();
+        for db_era_minimums in db_minimums {
+            minimums.push(EraStakingMinimums {
+                era_index: db_era_minimums.0 as u32,
+                min_nominator_bond: parse_maybe_balance(db_era_minimums.1)?,
+                min_validator_bond: parse_maybe_balance(db_era_minimums.2)?,
+                min_active_nominator_stake: parse_maybe_balance(db_era_minimums.3)?,
+                min_active_validator_stake: db_era_minimums.4.parse()?,
+            });
+        }
+        Ok(minimums)
+    }
+}
@@ -142,6 +142,33 @@ impl PostgreSQLNetworkStorage {
         }
     }
 
+    /// Names of the Telemetry nodes that have reported any of the given accounts, e.g. the
+    /// stash and the controller of a validator.
+    pub async fn get_node_names_by_account_ids(
+        &self,
+        account_ids: &[AccountId],
+    ) -> anyhow::Result<Vec<String>> {
+        let account_id_strings: Vec<String> = account_ids
+            .iter()
+            .map(|account_id| account_id.to_string())
+            .collect();
+        let db_node_names: Vec<(String,)> = sqlx::query_as(
+            r#"
+            SELECT name
+            FROM sub_telemetry_node
+            WHERE controller_account_id = ANY($1)
+            ORDER BY name ASC
+            "#,
+        )
+        .bind(&account_id_strings)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        Ok(db_node_names
+            .into_iter()
+            .map(|db_node_name| db_node_name.0)
+            .collect())
+    }
+
     pub async fn save_node_network_stats(
         &self,
         node_id: u64,
@@ -0,0 +1,122 @@
+//! Storage related to the recent on-chain activity feed of the validators, assembled from the
+//! indexed payouts, new nominations, preference changes, offline offences and slashes.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::collections::HashMap;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::subvt::{ValidatorActivity, ValidatorActivityType};
+
+type PostgresValidatorActivity = (
+    String,
+    String,
+    i64,
+    Option<i64>,
+    Option<i64>,
+    Option<String>,
+    Option<String>,
+    Option<i64>,
+);
+
+impl PostgreSQLNetworkStorage {
+    /// Last `count` activities of each of the given validators, latest first.
+    pub async fn get_validator_activities(
+        &self,
+        validator_account_ids: &[AccountId],
+        count: u32,
+    ) -> anyhow::Result<HashMap<AccountId, Vec<ValidatorActivity>>> {
+        let db_activities: Vec<PostgresValidatorActivity> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, activity_type, block_number, timestamp, era_index, account_id, amount, commission_per_billion
+            FROM (
+                SELECT A.*, ROW_NUMBER() OVER (PARTITION BY A.validator_account_id ORDER BY A.block_number DESC) AS row_number
+                FROM (
+                    SELECT P.validator_account_id, 'payout' AS activity_type, B.number AS block_number, B.timestamp, P.era_index, P.caller_account_id AS account_id, NULL::VARCHAR AS amount, NULL::bigint AS commission_per_billion
+                    FROM sub_extrinsic_payout_stakers P
+                    INNER JOIN sub_block B
+                        ON B.hash = P.block_hash
+                    WHERE P.validator_account_id = ANY($1)
+                    AND P.is_successful = true
+                    UNION ALL
+                    SELECT N.validator_account_id, 'nomination_received', N.discovered_block_number, B.timestamp, B.era_index, N.nominator_stash_account_id, N.active_amount, NULL
+                    FROM sub_app_event_new_nomination N
+                    LEFT JOIN LATERAL (
+                        SELECT SB.timestamp, SB.era_index
+                        FROM sub_block SB
+                        WHERE SB.number = N.discovered_block_number
+                        LIMIT 1
+                    ) B ON true
+                    WHERE N.validator_account_id = ANY($1)
+                    UNION ALL
+                    SELECT V.stash_account_id, 'preference_change', B.number, B.timestamp, B.era_index, NULL, NULL, V.commission_per_billion
+                    FROM sub_extrinsic_validate V
+                    INNER JOIN sub_block B
+                        ON B.hash = V.block_hash
+                    WHERE V.stash_account_id = ANY($1)
+                    AND V.is_successful = true
+                    UNION ALL
+                    SELECT O.validator_account_id, 'offline_offence', B.number, B.timestamp, B.era_index, NULL, NULL, NULL
+                    FROM sub_event_validator_offline O
+                    INNER JOIN sub_block B
+                        ON B.hash = O.block_hash
+                    WHERE O.validator_account_id = ANY($1)
+                    UNION ALL
+                    SELECT S.validator_account_id, 'slash', B.number, B.timestamp, B.era_index, NULL, S.amount, NULL
+                    FROM sub_event_slashed S
+                    INNER JOIN sub_block B
+                        ON B.hash = S.block_hash
+                    WHERE S.validator_account_id = ANY($1)
+                ) A
+            ) R
+            WHERE R.row_number <= $2
+            ORDER BY R.validator_account_id ASC, R.block_number DESC
+            "#,
+        )
+        .bind(
+            validator_account_ids
+                .iter()
+                .map(|account_id| account_id.to_string())
+                .collect::<Vec<String>>(),
+        )
+        .bind(count as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut activity_map: HashMap<AccountId, Vec<ValidatorActivity>> = HashMap::new();
+        for db_activity in db_activities {
+            let activity = ValidatorActivity {
+                activity_type: ValidatorActivityType::from_str(&db_activity.1)?,
+                block_number: db_activity.2 as u64,
+                timestamp: db_activity.3.map(|timestamp| timestamp as u64),
+                era_index: db_activity.4.map(|era_index| era_index as u32),
+                account_id: match &db_activity.5 {
+                    Some(account_id) => Some(AccountId::from_str(account_id)?),
+                    None => None,
+                },
+                amount: match &db_activity.6 {
+                    Some(amount) => Some(amount.parse()?),
+                    None => None,
+                },
+                commission_per_billion: db_activity
+                    .7
+                    .map(|commission_per_billion| commission_per_billion as u32),
+            };
+            activity_map
+                .entry(AccountId::from_str(&db_activity.0)?)
+                .or_default()
+                .push(activity);
+        }
+        Ok(activity_map)
+    }
+
+    /// Last `count` activities of the validator, latest first.
+    pub async fn get_validator_activity(
+        &self,
+        validator_account_id: &AccountId,
+        count: u32,
+    ) -> anyhow::Result<Vec<ValidatorActivity>> {
+        Ok(self
+            .get_validator_activities(&[validator_account_id.clone()], count)
+            .await?
+            .remove(validator_account_id)
+            .unwrap_or_default())
+    }
+}
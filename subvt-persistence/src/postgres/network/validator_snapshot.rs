@@ -0,0 +1,99 @@
+//! Storage related to the provisional validator list, a best-effort reconstruction of the
+//! validator list from the latest indexed era. Published by `subvt-validator-list-updater` on
+//! a cold start, until the validator list of the first finalized block is ready.
+use crate::postgres::network::PostgreSQLNetworkStorage;
+use std::str::FromStr;
+use subvt_types::crypto::AccountId;
+use subvt_types::substrate::{Account, Era, Stake, ValidatorPreferences, ValidatorStake};
+use subvt_types::subvt::ValidatorDetails;
+
+type PostgresEraValidator = (
+    String,
+    Option<String>,
+    bool,
+    Option<i64>,
+    Option<bool>,
+    Option<String>,
+    Option<String>,
+);
+
+impl PostgreSQLNetworkStorage {
+    /// Latest indexed era and its validators. Only the fields that are indexed per era are set,
+    /// i.e. the controller, the preferences, the self stake and the total active stake (without
+    /// the nominators) of each validator. `None` if no era has been indexed yet.
+    pub async fn get_provisional_validator_list(
+        &self,
+    ) -> anyhow::Result<Option<(Era, Vec<ValidatorDetails>)>> {
+        let maybe_db_era: Option<(i64, i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT index, start_timestamp, end_timestamp
+            FROM sub_era
+            WHERE index = (SELECT MAX(era_index) FROM sub_era_validator)
+            "#,
+        )
+        .fetch_optional(&self.connection_pool)
+        .await?;
+        let era = match maybe_db_era {
+            Some(db_era) => Era {
+                index: db_era.0 as u32,
+                start_timestamp: db_era.1 as u64,
+                end_timestamp: db_era.2 as u64,
+            },
+            None => return Ok(None),
+        };
+        let db_validators: Vec<PostgresEraValidator> = sqlx::query_as(
+            r#"
+            SELECT validator_account_id, controller_account_id, is_active, commission_per_billion, blocks_nominations, self_stake, total_stake
+            FROM sub_era_validator
+            WHERE era_index = $1
+            "#,
+        )
+        .bind(era.index as i64)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let mut validators = Vec::new();
+        for db_validator in db_validators {
+            let account_id = AccountId::from_str(&db_validator.0)?;
+            let controller_account_id = match &db_validator.1 {
+                Some(controller_account_id) => AccountId::from_str(controller_account_id)?,
+                None => AccountId::default(),
+            };
+            let self_stake = match &db_validator.5 {
+                Some(self_stake) => self_stake.parse()?,
+                None => 0,
+            };
+            let account = Account {
+                id: account_id.clone(),
+                ..Default::default()
+            };
+            let validator_stake = match (db_validator.2, &db_validator.6) {
+                (true, Some(total_stake)) => Some(ValidatorStake {
+                    account: account.clone(),
+                    self_stake,
+                    total_stake: total_stake.parse()?,
+                    nominators: Vec::new(),
+                }),
+                _ => None,
+            };
+            validators.push(ValidatorDetails {
+                account,
+                controller_account_id,
+                preferences: ValidatorPreferences {
+                    commission_per_billion: db_validator.3.unwrap_or(0) as u32,
+                    blocks_nominations: db_validator.4.unwrap_or(false),
+                },
+                self_stake: Stake {
+                    stash_account_id: account_id,
+                    total_amount: self_stake,
+                    active_amount: self_stake,
+                    unlocking: Vec::new(),
+                },
+                is_active: db_validator.2,
+                active_next_session: db_validator.2,
+                validator_stake,
+                ..Default::default()
+            });
+        }
+        Ok(Some((era, validators)))
+    }
+}
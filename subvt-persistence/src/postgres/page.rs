@@ -0,0 +1,35 @@
+//! Keyset pagination helpers for the block/event history queries. History rows are ordered by
+//! their `(block_number, index)` key and a page starts right after the key of the cursor, so
+//! that the cost of fetching a page doesn't grow with its depth like it does with `OFFSET`.
+//!
+//! Queries should filter with `(block_number, index) > ($n, $n + 1)` using the bounds from
+//! [`get_cursor_bounds`], order by the same key and fetch `page_size + 1` rows, which are then
+//! split into a page by [`get_history_page`].
+use subvt_types::report::{HistoryCursor, HistoryPage};
+
+/// Exclusive lower bounds for the `(block_number, index)` key of the rows of the page.
+pub(crate) fn get_cursor_bounds(maybe_cursor: Option<&HistoryCursor>) -> (i64, i64) {
+    match maybe_cursor {
+        Some(cursor) => (cursor.block_number as i64, cursor.index as i64),
+        None => (-1, -1),
+    }
+}
+
+/// Builds the page from the `page_size + 1` rows fetched from the cursor. The extra row
+/// only indicates that there is a next page.
+pub(crate) fn get_history_page<T>(
+    mut rows: Vec<(HistoryCursor, T)>,
+    page_size: u32,
+) -> HistoryPage<T> {
+    let has_next_page = rows.len() > page_size as usize;
+    rows.truncate(page_size as usize);
+    let next_cursor = if has_next_page {
+        rows.last().map(|(cursor, _)| cursor.encode())
+    } else {
+        None
+    };
+    HistoryPage {
+        items: rows.into_iter().map(|(_, item)| item).collect(),
+        next_cursor,
+    }
+}
@@ -0,0 +1,304 @@
+//! Storage abstraction for the real-time buffer that sits between the updaters
+//! (`subvt-live-network-status-updater`, `subvt-validator-list-updater`) and the WebSocket
+//! servers. Redis is the backend of all deployments. The memory store lives in the process
+//! that creates it, so it can't connect the services, which all run as separate processes. It's
+//! only for the tests, which run the writers and the readers in the same process.
+use crate::memory::MemoryRealtimeStore;
+use crate::redis::RedisRealtimeStore;
+use lazy_static::lazy_static;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::Arc;
+use subvt_config::{Config, RealtimeStoreType};
+
+lazy_static! {
+    static ref MEMORY_STORE: Arc<MemoryRealtimeStore> = Arc::new(MemoryRealtimeStore::default());
+}
+
+/// A set of writes to be applied together. Deletions are applied first, then the set
+/// additions and the values, and finally the publications.
+#[derive(Default)]
+pub struct RealtimeWriteBatch {
+    pub(crate) delete_prefixes: Vec<String>,
+    pub(crate) delete_keys: Vec<String>,
+    pub(crate) set_additions: Vec<(String, HashSet<String>)>,
+    pub(crate) values: Vec<(String, String)>,
+    pub(crate) publications: Vec<(String, u64)>,
+}
+
+impl RealtimeWriteBatch {
+    /// Deletes all the keys (values and sets) that start with the given prefix.
+    pub fn delete_with_prefix(&mut self, prefix: &str) -> &mut Self {
+        self.delete_prefixes.push(prefix.to_string());
+        self
+    }
+
+    /// Deletes a single key (value or set), without the prefix scan of `delete_with_prefix`.
+    pub fn delete(&mut self, key: &str) -> &mut Self {
+        self.delete_keys.push(key.to_string());
+        self
+    }
+
+    pub fn add_to_set(&mut self, key: &str, members: HashSet<String>) -> &mut Self {
+        self.set_additions.push((key.to_string(), members));
+        self
+    }
+
+    pub fn set<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
+        self.values.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn publish_block_number(&mut self, channel: &str, block_number: u64) -> &mut Self {
+        self.publications.push((channel.to_string(), block_number));
+        self
+    }
+}
+
+/// Error of a write that has not been published, because some of its keys are missing after the
+/// write, e.g. when a pipeline got interrupted. See `RealtimeStore::write`.
+#[derive(Debug)]
+pub struct IncompleteWriteError {
+    pub expected_key_count: usize,
+    pub found_key_count: usize,
+}
+
+impl std::fmt::Display for IncompleteWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Incomplete real-time store write. Expected {} keys, found {}.",
+            self.expected_key_count, self.found_key_count
+        )
+    }
+}
+
+impl std::error::Error for IncompleteWriteError {}
+
+/// Key of the counter of the block notifications skipped due to coalescing by a server, see
+/// `RealtimeSubscription::coalesce`. The servers keep these counters regardless of the
+/// analytics configuration, so that the skipped blocks can always be monitored in the store.
+pub fn get_skipped_block_counter_key(chain: &str, server_name: &str) -> String {
+    format!("subvt:{}:skipped_blocks:{}", chain, server_name)
+}
+
+/// Block number notifications published on a channel.
+pub struct RealtimeSubscription {
+    receiver: Receiver<anyhow::Result<u64>>,
+    coalesce: bool,
+    skipped_block_count: AtomicU64,
+}
+
+impl RealtimeSubscription {
+    pub(crate) fn new(receiver: Receiver<anyhow::Result<u64>>) -> Self {
+        Self {
+            receiver,
+            coalesce: false,
+            skipped_block_count: AtomicU64::new(0),
+        }
+    }
+
+    /// Sets whether the notifications that have queued up while the consumer was busy should
+    /// be skipped in favour of the newest one.
+    pub fn coalesce(mut self, coalesce: bool) -> Self {
+        self.coalesce = coalesce;
+        self
+    }
+
+    /// Number of notifications skipped due to coalescing since the last call, for the
+    /// `realtime:skipped_blocks` analytics metric and the skipped block counters of the servers,
+    /// see `get_skipped_block_counter_key`.
+    pub fn take_skipped_block_count(&self) -> u64 {
+        self.skipped_block_count.swap(0, Ordering::Relaxed)
+    }
+
+    /// Blocks until the next block number gets published. Returns the newest of the queued
+    /// block numbers if coalescing is enabled.
+    pub fn next_block_number(&self) -> anyhow::Result<u64> {
+        let mut block_number = self.receiver.recv()??;
+        if !self.coalesce {
+            return Ok(block_number);
+        }
+        let mut skipped_block_count = 0;
+        loop {
+            match self.receiver.try_recv() {
+                Ok(result) => {
+                    block_number = result?;
+                    skipped_block_count += 1;
+                }
+                Err(TryRecvError::Empty) | Err(TryRecvError::Disconnected) => break,
+            }
+        }
+        if skipped_block_count > 0 {
+            self.skipped_block_count
+                .fetch_add(skipped_block_count, Ordering::Relaxed);
+            warn!(
+                "Skipped {} queued block notification(s), continue with #{}.",
+                skipped_block_count, block_number,
+            );
+        }
+        Ok(block_number)
+    }
+}
+
+pub trait RealtimeStore: Send + Sync {
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>>;
+
+    /// Gets the values of the keys in a single round-trip, in the order of the keys.
+    fn get_multiple(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>>;
+
+    fn get_set_members(&self, key: &str) -> anyhow::Result<HashSet<String>>;
+
+    /// Applies the batch. The publications are made only after all the written keys are
+    /// verified to exist, so that the subscribers never read a partially written batch.
+    /// Fails with `IncompleteWriteError` when the verification fails.
+    fn write(&self, batch: &RealtimeWriteBatch) -> anyhow::Result<()>;
+
+    fn subscribe(&self, channel: &str) -> anyhow::Result<RealtimeSubscription>;
+
+    /// Increments the counter at the key by the given amount, creating it when missing.
+    /// Returns the new value.
+    fn increment(&self, key: &str, amount: u64) -> anyhow::Result<u64>;
+
+    /// Gets and deletes all the counters that start with the given prefix. Each counter gets
+    /// read and deleted atomically, so that no increment gets lost or taken twice when there
+    /// are concurrent consumers.
+    fn take_counters(&self, prefix: &str) -> anyhow::Result<HashMap<String, u64>>;
+}
+
+/// Maps the keys and channels given in the version 1 format (`subvt:<chain>:...`) to the
+/// configured key format versions: all the write versions for writes, and the read version
+/// for reads and subscriptions.
+struct VersionedRealtimeStore {
+    inner: Arc<dyn RealtimeStore>,
+    base_prefix: String,
+    write_prefixes: Vec<String>,
+    read_prefix: String,
+}
+
+impl VersionedRealtimeStore {
+    fn get_key_prefix(chain: &str, version: u32) -> String {
+        if version <= 1 {
+            format!("subvt:{}:", chain)
+        } else {
+            format!("subvt:v{}:{}:", version, chain)
+        }
+    }
+
+    fn new(inner: Arc<dyn RealtimeStore>, config: &Config) -> Self {
+        let chain = &config.substrate.chain;
+        Self {
+            inner,
+            base_prefix: VersionedRealtimeStore::get_key_prefix(chain, 1),
+            write_prefixes: config
+                .realtime
+                .write_key_versions
+                .iter()
+                .map(|version| VersionedRealtimeStore::get_key_prefix(chain, *version))
+                .collect(),
+            read_prefix: VersionedRealtimeStore::get_key_prefix(
+                chain,
+                config.realtime.read_key_version,
+            ),
+        }
+    }
+
+    fn map_key(&self, key: &str, prefix: &str) -> String {
+        match key.strip_prefix(&self.base_prefix) {
+            Some(rest) => format!("{}{}", prefix, rest),
+            None => key.to_string(),
+        }
+    }
+}
+
+impl RealtimeStore for VersionedRealtimeStore {
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        self.inner.get(&self.map_key(key, &self.read_prefix))
+    }
+
+    fn get_multiple(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        let keys: Vec<String> = keys
+            .iter()
+            .map(|key| self.map_key(key, &self.read_prefix))
+            .collect();
+        self.inner.get_multiple(&keys)
+    }
+
+    fn get_set_members(&self, key: &str) -> anyhow::Result<HashSet<String>> {
+        self.inner
+            .get_set_members(&self.map_key(key, &self.read_prefix))
+    }
+
+    /// Writes all versions in a single batch.
+    fn write(&self, batch: &RealtimeWriteBatch) -> anyhow::Result<()> {
+        let mut versioned_batch = RealtimeWriteBatch::default();
+        for prefix in &self.write_prefixes {
+            for delete_prefix in &batch.delete_prefixes {
+                versioned_batch
+                    .delete_prefixes
+                    .push(self.map_key(delete_prefix, prefix));
+            }
+            for key in &batch.delete_keys {
+                versioned_batch.delete_keys.push(self.map_key(key, prefix));
+            }
+            for (key, members) in &batch.set_additions {
+                versioned_batch
+                    .set_additions
+                    .push((self.map_key(key, prefix), members.clone()));
+            }
+            for (key, value) in &batch.values {
+                versioned_batch
+                    .values
+                    .push((self.map_key(key, prefix), value.clone()));
+            }
+            for (channel, block_number) in &batch.publications {
+                versioned_batch
+                    .publications
+                    .push((self.map_key(channel, prefix), *block_number));
+            }
+        }
+        self.inner.write(&versioned_batch)
+    }
+
+    fn subscribe(&self, channel: &str) -> anyhow::Result<RealtimeSubscription> {
+        self.inner
+            .subscribe(&self.map_key(channel, &self.read_prefix))
+    }
+
+    /// Counters are not versioned, they are kept in the read version only.
+    fn increment(&self, key: &str, amount: u64) -> anyhow::Result<u64> {
+        self.inner
+            .increment(&self.map_key(key, &self.read_prefix), amount)
+    }
+
+    fn take_counters(&self, prefix: &str) -> anyhow::Result<HashMap<String, u64>> {
+        self.inner
+            .take_counters(&self.map_key(prefix, &self.read_prefix))
+    }
+}
+
+/// Gets the store of the configured type, mapped to the configured key format versions.
+/// The memory store is shared only within the process, see the module documentation.
+pub fn get_realtime_store(config: &Config) -> anyhow::Result<Arc<dyn RealtimeStore>> {
+    let store: Arc<dyn RealtimeStore> = match config.realtime.store_type {
+        RealtimeStoreType::Redis => Arc::new(RedisRealtimeStore::new(&config.redis.url)?),
+        RealtimeStoreType::Memory => {
+            warn!("Using the in-process memory real-time store, which is not shared with the other services. It's for tests only.");
+            MEMORY_STORE.clone()
+        }
+    };
+    // no mapping needed for the plain version 1 configuration
+    if config.realtime.read_key_version <= 1
+        && config
+            .realtime
+            .write_key_versions
+            .iter()
+            .all(|version| *version <= 1)
+    {
+        Ok(store)
+    } else {
+        Ok(Arc::new(VersionedRealtimeStore::new(store, config)))
+    }
+}
@@ -1 +1,213 @@
-// TODO :: refactor the Redis logic from other crates to here.
+//! Redis implementation of the real-time store.
+use crate::realtime::{
+    IncompleteWriteError, RealtimeStore, RealtimeSubscription, RealtimeWriteBatch,
+};
+use anyhow::Context;
+use redis::{Connection, Pipeline, RedisResult};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// Maximum number of keys in a single `MGET` command. Larger reads get split into multiple
+/// commands in a single pipeline. Same for the `EXISTS` commands of the write verification.
+const MGET_CHUNK_SIZE: usize = 1000;
+
+pub struct RedisRealtimeStore {
+    client: redis::Client,
+    data_connection: Mutex<Connection>,
+}
+
+impl RedisRealtimeStore {
+    pub fn new(url: &str) -> anyhow::Result<Self> {
+        let client =
+            redis::Client::open(url).context(format!("Cannot connect to Redis at URL {}.", url))?;
+        let data_connection = client
+            .get_connection()
+            .context(format!("Cannot connect to Redis at URL {}.", url))?;
+        Ok(Self {
+            client,
+            data_connection: Mutex::new(data_connection),
+        })
+    }
+
+    /// Runs the query on the data connection, which gets re-established if it has been
+    /// dropped, so that the next query can succeed.
+    fn query<T, F>(&self, query: F) -> anyhow::Result<T>
+    where
+        F: FnOnce(&mut Connection) -> RedisResult<T>,
+    {
+        let mut connection = self.data_connection.lock().unwrap();
+        match query(&mut *connection) {
+            Ok(result) => Ok(result),
+            Err(error) => {
+                if error.is_connection_dropped() || error.is_io_error() {
+                    if let Ok(new_connection) = self.client.get_connection() {
+                        *connection = new_connection;
+                    }
+                }
+                Err(error.into())
+            }
+        }
+    }
+}
+
+impl RealtimeStore for RedisRealtimeStore {
+    fn get(&self, key: &str) -> anyhow::Result<Option<String>> {
+        self.query(|connection| redis::cmd("GET").arg(key).query(connection))
+    }
+
+    fn get_multiple(&self, keys: &[String]) -> anyhow::Result<Vec<Option<String>>> {
+        // MGET fails without any keys
+        if keys.is_empty() {
+            return Ok(Vec::new());
+        }
+        let chunk_values: Vec<Vec<Option<String>>> = self.query(|connection| {
+            let mut redis_cmd_pipeline = Pipeline::new();
+            for chunk in keys.chunks(MGET_CHUNK_SIZE) {
+                redis_cmd_pipeline.cmd("MGET").arg(chunk);
+            }
+            redis_cmd_pipeline.query(connection)
+        })?;
+        Ok(chunk_values.into_iter().flatten().collect())
+    }
+
+    fn get_set_members(&self, key: &str) -> anyhow::Result<HashSet<String>> {
+        self.query(|connection| redis::cmd("SMEMBERS").arg(key).query(connection))
+    }
+
+    /// Writes in two phases: the keys first, then the publications after the existence of all
+    /// the written keys gets verified.
+    fn write(&self, batch: &RealtimeWriteBatch) -> anyhow::Result<()> {
+        self.query(|connection| {
+            let mut redis_cmd_pipeline = Pipeline::new();
+            for prefix in &batch.delete_prefixes {
+                let keys: Vec<String> = redis::cmd("KEYS")
+                    .arg(format!("{}*", prefix))
+                    .query(connection)?;
+                for key in keys {
+                    redis_cmd_pipeline.cmd("DEL").arg(key);
+                }
+            }
+            for key in &batch.delete_keys {
+                redis_cmd_pipeline.cmd("DEL").arg(key);
+            }
+            for (key, members) in &batch.set_additions {
+                // SADD fails without any members
+                if !members.is_empty() {
+                    redis_cmd_pipeline.cmd("SADD").arg(key).arg(members);
+                }
+            }
+            if !batch.values.is_empty() {
+                redis_cmd_pipeline.cmd("MSET");
+                for (key, value) in &batch.values {
+                    redis_cmd_pipeline.arg(key).arg(value);
+                }
+            }
+            redis_cmd_pipeline.query::<()>(connection)
+        })
+        .context("Error while writing to Redis.")?;
+        if batch.publications.is_empty() {
+            return Ok(());
+        }
+        // verify, sets without members don't get created
+        let written_keys: Vec<&String> = batch
+            .set_additions
+            .iter()
+            .filter(|(_, members)| !members.is_empty())
+            .map(|(key, _)| key)
+            .chain(batch.values.iter().map(|(key, _)| key))
+            .collect::<HashSet<&String>>()
+            .into_iter()
+            .collect();
+        if !written_keys.is_empty() {
+            let chunk_counts: Vec<usize> = self
+                .query(|connection| {
+                    let mut redis_cmd_pipeline = Pipeline::new();
+                    for chunk in written_keys.chunks(MGET_CHUNK_SIZE) {
+                        redis_cmd_pipeline.cmd("EXISTS").arg(chunk);
+                    }
+                    redis_cmd_pipeline.query(connection)
+                })
+                .context("Error while verifying the Redis write.")?;
+            let found_key_count: usize = chunk_counts.iter().sum();
+            if found_key_count != written_keys.len() {
+                return Err(IncompleteWriteError {
+                    expected_key_count: written_keys.len(),
+                    found_key_count,
+                }
+                .into());
+            }
+        }
+        self.query(|connection| {
+            let mut redis_cmd_pipeline = Pipeline::new();
+            for (channel, block_number) in &batch.publications {
+                redis_cmd_pipeline
+                    .cmd("PUBLISH")
+                    .arg(channel)
+                    .arg(block_number);
+            }
+            redis_cmd_pipeline.query(connection)
+        })
+        .context("Error while publishing to Redis.")
+    }
+
+    /// Messages are received on a dedicated connection in a separate thread, which
+    /// exits on error or when the subscription gets dropped.
+    fn subscribe(&self, channel: &str) -> anyhow::Result<RealtimeSubscription> {
+        let mut pub_sub_connection = self.client.get_connection()?;
+        let channel = channel.to_string();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        std::thread::spawn(move || {
+            let mut pub_sub = pub_sub_connection.as_pubsub();
+            if let Err(error) = pub_sub.subscribe(&channel) {
+                let _ = sender.send(Err(error.into()));
+                return;
+            }
+            loop {
+                match pub_sub
+                    .get_message()
+                    .and_then(|message| message.get_payload::<u64>())
+                {
+                    Ok(block_number) => {
+                        if sender.send(Ok(block_number)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(error) => {
+                        let _ = sender.send(Err(error.into()));
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(RealtimeSubscription::new(receiver))
+    }
+
+    fn increment(&self, key: &str, amount: u64) -> anyhow::Result<u64> {
+        self.query(|connection| redis::cmd("INCRBY").arg(key).arg(amount).query(connection))
+    }
+
+    /// Each counter is read and deleted in a transaction.
+    fn take_counters(&self, prefix: &str) -> anyhow::Result<HashMap<String, u64>> {
+        self.query(|connection| {
+            let keys: Vec<String> = redis::cmd("KEYS")
+                .arg(format!("{}*", prefix))
+                .query(connection)?;
+            let mut counters = HashMap::new();
+            for key in keys {
+                let (maybe_value,): (Option<u64>,) = redis::pipe()
+                    .atomic()
+                    .cmd("GET")
+                    .arg(&key)
+                    .cmd("DEL")
+                    .arg(&key)
+                    .ignore()
+                    .query(connection)?;
+                if let Some(value) = maybe_value {
+                    counters.insert(key, value);
+                }
+            }
+            Ok(counters)
+        })
+        .context("Error while taking the counters from Redis.")
+    }
+}
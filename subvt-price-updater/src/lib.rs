@@ -0,0 +1,119 @@
+//! Fetches the fiat prices of the network token from a CoinGecko-compatible API in the
+//! configured currencies, then saves them to the price history in the network database and
+//! writes the latest prices to the real-time store, so that the report service and the
+//! notification sender can display the fiat equivalents of the amounts.
+
+use anyhow::Context;
+use async_trait::async_trait;
+use lazy_static::lazy_static;
+use log::{debug, error, info, warn};
+use std::collections::HashMap;
+use subvt_config::Config;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore, RealtimeWriteBatch};
+use subvt_service_common::Service;
+
+lazy_static! {
+    static ref CONFIG: Config = Config::default();
+}
+
+pub struct PriceUpdater {
+    http_client: reqwest::Client,
+}
+
+impl Default for PriceUpdater {
+    fn default() -> Self {
+        let http_client: reqwest::Client = reqwest::Client::builder()
+            .gzip(true)
+            .brotli(true)
+            .timeout(std::time::Duration::from_secs(
+                CONFIG.price.request_timeout_seconds,
+            ))
+            .build()
+            .unwrap();
+        Self { http_client }
+    }
+}
+
+impl PriceUpdater {
+    /// Returns the prices of the token in the configured currencies. Currencies that are not
+    /// supported by the API are missing from the result.
+    async fn fetch_prices(&self) -> anyhow::Result<Vec<(String, f64)>> {
+        let config = &CONFIG.price;
+        // response is in the form of `{"kusama":{"usd":123.45,"eur":112.34}}`
+        let mut response: HashMap<String, HashMap<String, f64>> = self
+            .http_client
+            .get(&format!(
+                "{}/simple/price",
+                config.api_base_url.trim_end_matches('/')
+            ))
+            .query(&[
+                ("ids", config.token_id.as_str()),
+                ("vs_currencies", config.currencies.join(",").as_str()),
+            ])
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        let token_prices = response
+            .remove(&config.token_id)
+            .context(format!("No price for token {}.", config.token_id))?;
+        let mut prices = Vec::new();
+        for currency_code in &config.currencies {
+            let currency_code = currency_code.to_lowercase();
+            match token_prices.get(&currency_code) {
+                Some(price) => prices.push((currency_code, *price)),
+                None => warn!("No {} price for token {}.", currency_code, config.token_id),
+            }
+        }
+        Ok(prices)
+    }
+
+    async fn update(
+        &self,
+        postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
+    ) -> anyhow::Result<()> {
+        let prices = self.fetch_prices().await?;
+        if prices.is_empty() {
+            return Ok(());
+        }
+        debug!("Fetched prices :: {:?}", prices);
+        postgres.save_token_prices(&prices).await?;
+        let mut batch = RealtimeWriteBatch::default();
+        for (currency_code, _) in &prices {
+            // read back for the database timestamp
+            if let Some(token_price) = postgres.get_latest_token_price(currency_code).await? {
+                batch.set(
+                    &format!("subvt:{}:price:{}", CONFIG.substrate.chain, currency_code),
+                    serde_json::to_string(&token_price)?,
+                );
+            }
+        }
+        realtime_store.write(&batch)?;
+        info!("Updated {} token price(s).", prices.len());
+        Ok(())
+    }
+}
+
+#[async_trait(?Send)]
+impl Service for PriceUpdater {
+    async fn run(&'static self) -> anyhow::Result<()> {
+        info!(
+            "Price updater has started for token {} in {} currencies with {} seconds refresh wait period.",
+            CONFIG.price.token_id,
+            CONFIG.price.currencies.len(),
+            CONFIG.price.refresh_seconds
+        );
+        let postgres =
+            PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?;
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        loop {
+            if let Err(error) = self.update(&postgres, realtime_store.as_ref()).await {
+                error!("Price update has failed: {:?}", error);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(CONFIG.price.refresh_seconds)).await;
+        }
+    }
+}
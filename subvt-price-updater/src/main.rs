@@ -0,0 +1,14 @@
+//! See `./lib.rs` for details.
+
+use lazy_static::lazy_static;
+use subvt_price_updater::PriceUpdater;
+use subvt_service_common::Service;
+
+lazy_static! {
+    static ref SERVICE: PriceUpdater = PriceUpdater::default();
+}
+
+#[tokio::main]
+async fn main() {
+    SERVICE.start().await;
+}
@@ -0,0 +1,90 @@
+//! Public API usage tracking, the basis of the tiered public API access. Requests with an API
+//! key (see `UserApiKey` in `subvt-types`) in the `X-API-Key` header get counted per key and per
+//! (UTC) day in the real-time store, and the counts get added to the application database
+//! periodically. Requests without a key, or with an unknown key, are served but not counted.
+use crate::CONFIG;
+use log::{debug, error, warn};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+use subvt_persistence::realtime::RealtimeStore;
+
+pub(crate) const API_KEY_HEADER: &str = "X-API-Key";
+/// Counter keys are in the `subvt:api_usage:<api_key_id>:<epoch_day>` format. Not prefixed by
+/// the chain, so the report services of all networks sharing the store count together.
+const API_USAGE_KEY_PREFIX: &str = "subvt:api_usage:";
+
+/// Number of days since the Unix epoch, i.e. the current UTC date.
+fn get_epoch_day() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        / (24 * 60 * 60)
+}
+
+/// Counts a request with the given API key, if the key exists.
+pub(crate) async fn count_api_request(
+    app_postgres: &PostgreSQLAppStorage,
+    realtime_store: &dyn RealtimeStore,
+    api_key: &str,
+) -> anyhow::Result<()> {
+    if let Some(api_key_id) = app_postgres.get_api_key_id(api_key).await? {
+        realtime_store.increment(
+            &format!("{}{}:{}", API_USAGE_KEY_PREFIX, api_key_id, get_epoch_day()),
+            1,
+        )?;
+    }
+    Ok(())
+}
+
+/// Moves the request counts from the real-time store to the application database. A count that
+/// cannot be persisted is put back to be persisted with the next flush.
+async fn flush_api_usage(
+    app_postgres: &PostgreSQLAppStorage,
+    realtime_store: &dyn RealtimeStore,
+) -> anyhow::Result<()> {
+    let counters = realtime_store.take_counters(API_USAGE_KEY_PREFIX)?;
+    for (key, request_count) in counters {
+        let mut key_parts = key.trim_start_matches(API_USAGE_KEY_PREFIX).split(':');
+        let (api_key_id, epoch_day) = match (
+            key_parts.next().and_then(|part| part.parse::<u32>().ok()),
+            key_parts.next().and_then(|part| part.parse::<u32>().ok()),
+        ) {
+            (Some(api_key_id), Some(epoch_day)) => (api_key_id, epoch_day),
+            _ => {
+                warn!("Invalid API usage counter key {}, discard.", key);
+                continue;
+            }
+        };
+        if let Err(error) = app_postgres
+            .add_api_usage(api_key_id, epoch_day, request_count)
+            .await
+        {
+            error!(
+                "Error while persisting the API usage for key #{}: {:?}",
+                api_key_id, error
+            );
+            realtime_store.increment(&key, request_count)?;
+        }
+    }
+    Ok(())
+}
+
+/// Flushes the API usage periodically. Errors are logged, and the flush is retried in the next
+/// period.
+pub(crate) async fn run_api_usage_flusher(
+    app_postgres: Arc<PostgreSQLAppStorage>,
+    realtime_store: Arc<dyn RealtimeStore>,
+) -> anyhow::Result<()> {
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(
+            CONFIG.report.api_usage_flush_period_seconds,
+        ))
+        .await;
+        debug!("Flush API usage.");
+        if let Err(error) = flush_api_usage(&app_postgres, realtime_store.as_ref()).await {
+            error!("Error while flushing the API usage: {:?}", error);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Streaming exports of the era-range reports. The reports are fetched in chunks of eras, with
+//! the next era index as the cursor, and each chunk is written to the response body as soon as
+//! it's ready, so that exports spanning hundreds of eras are never materialized in memory.
+use crate::CONFIG;
+use actix_web::web::Bytes;
+use futures::{Stream, StreamExt};
+use log::error;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use subvt_types::report::{EraReport, EraValidatorReport};
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum ExportFormat {
+    /// One JSON object per line.
+    Jsonl,
+    Csv,
+}
+
+impl Default for ExportFormat {
+    fn default() -> Self {
+        ExportFormat::Jsonl
+    }
+}
+
+impl ExportFormat {
+    pub(crate) fn get_content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "application/x-ndjson",
+            ExportFormat::Csv => "text/csv",
+        }
+    }
+}
+
+/// A report that can be exported as a CSV row.
+pub(crate) trait CsvRow {
+    fn get_csv_header() -> &'static str;
+    fn get_csv_row(&self) -> String;
+}
+
+/// Missing values are exported as empty CSV fields.
+fn csv_field<T: ToString>(maybe_value: &Option<T>) -> String {
+    maybe_value
+        .as_ref()
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+impl CsvRow for EraReport {
+    fn get_csv_header() -> &'static str {
+        "era_index,start_timestamp,end_timestamp,minimum_stake,maximum_stake,average_stake,median_stake,total_validator_reward,total_reward_points,total_reward,total_stake,active_nominator_count,offline_offence_count,slashed_amount,chilling_count"
+    }
+
+    fn get_csv_row(&self) -> String {
+        [
+            self.era.index.to_string(),
+            self.era.start_timestamp.to_string(),
+            self.era.end_timestamp.to_string(),
+            csv_field(&self.minimum_stake),
+            csv_field(&self.maximum_stake),
+            csv_field(&self.average_stake),
+            csv_field(&self.median_stake),
+            csv_field(&self.total_validator_reward),
+            csv_field(&self.total_reward_points),
+            self.total_reward.to_string(),
+            csv_field(&self.total_stake),
+            csv_field(&self.active_nominator_count),
+            self.offline_offence_count.to_string(),
+            self.slashed_amount.to_string(),
+            self.chilling_count.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+impl CsvRow for EraValidatorReport {
+    fn get_csv_header() -> &'static str {
+        "era_index,start_timestamp,end_timestamp,is_active,commission_per_billion,self_stake,total_stake,block_count,primary_block_count,secondary_plain_block_count,secondary_vrf_block_count,reward_points,self_reward,staker_reward,offline_offence_count,slashed_amount,chilling_count"
+    }
+
+    fn get_csv_row(&self) -> String {
+        [
+            self.era.index.to_string(),
+            self.era.start_timestamp.to_string(),
+            self.era.end_timestamp.to_string(),
+            csv_field(&self.is_active),
+            csv_field(&self.commission_per_billion),
+            csv_field(&self.self_stake),
+            csv_field(&self.total_stake),
+            self.block_count.to_string(),
+            self.primary_block_count.to_string(),
+            self.secondary_plain_block_count.to_string(),
+            self.secondary_vrf_block_count.to_string(),
+            csv_field(&self.reward_points),
+            self.self_reward.to_string(),
+            self.staker_reward.to_string(),
+            self.offline_offence_count.to_string(),
+            self.slashed_amount.to_string(),
+            self.chilling_count.to_string(),
+        ]
+        .join(",")
+    }
+}
+
+fn format_chunk<T: CsvRow + Serialize>(
+    reports: &[T],
+    format: ExportFormat,
+) -> anyhow::Result<String> {
+    let mut chunk = String::new();
+    for report in reports {
+        match format {
+            ExportFormat::Jsonl => chunk.push_str(&serde_json::to_string(report)?),
+            ExportFormat::Csv => chunk.push_str(&report.get_csv_row()),
+        }
+        chunk.push('\n');
+    }
+    Ok(chunk)
+}
+
+/// Streams the reports of the eras between the given indices (inclusive), fetched by
+/// `fetch_chunk` for each chunk of `export_chunk_era_count` eras. A fetch error ends the
+/// stream, which aborts the response since the status has already been sent by then.
+pub(crate) fn stream_era_range_export<T, F, Fut>(
+    start_era_index: u32,
+    end_era_index: u32,
+    format: ExportFormat,
+    fetch_chunk: F,
+) -> impl Stream<Item = anyhow::Result<Bytes>>
+where
+    T: CsvRow + Serialize,
+    F: Fn(u32, u32) -> Fut + 'static,
+    Fut: Future<Output = anyhow::Result<Vec<T>>> + 'static,
+{
+    let chunk_era_count = CONFIG.report.export_chunk_era_count.max(1);
+    let maybe_header = match format {
+        ExportFormat::Jsonl => None,
+        ExportFormat::Csv => Some(Ok(Bytes::from(format!("{}\n", T::get_csv_header())))),
+    };
+    let chunks = futures::stream::unfold(Some(start_era_index), move |maybe_cursor| {
+        let maybe_fetch = maybe_cursor.map(|cursor| {
+            let chunk_end_era_index = cursor
+                .saturating_add(chunk_era_count - 1)
+                .min(end_era_index);
+            (
+                chunk_end_era_index,
+                fetch_chunk(cursor, chunk_end_era_index),
+            )
+        });
+        async move {
+            let (chunk_end_era_index, fetch) = maybe_fetch?;
+            let next_cursor = if chunk_end_era_index < end_era_index {
+                Some(chunk_end_era_index + 1)
+            } else {
+                None
+            };
+            match fetch
+                .await
+                .and_then(|reports| format_chunk(&reports, format))
+            {
+                Ok(chunk) => Some((Ok(Bytes::from(chunk)), next_cursor)),
+                Err(error) => {
+                    error!(
+                        "Error while exporting the eras until #{}: {:?}",
+                        chunk_end_era_index, error
+                    );
+                    Some((Err(error), None))
+                }
+            }
+        }
+    });
+    futures::stream::iter(maybe_header).chain(chunks)
+}
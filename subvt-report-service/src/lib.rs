@@ -1,17 +1,39 @@
 //!  Public reporting REST services.
+use crate::export::{stream_era_range_export, ExportFormat};
+use actix_web::dev::Service as _;
 use actix_web::web::Data;
-use actix_web::{get, web, App, HttpResponse, HttpServer};
+use actix_web::{get, post, web, App, HttpResponse, HttpServer};
+use anyhow::Context;
 use async_trait::async_trait;
 use lazy_static::lazy_static;
-use log::debug;
+use log::{debug, error};
 use serde::Deserialize;
+use std::collections::{BTreeMap, HashSet};
 use std::str::FromStr;
 use std::sync::Arc;
 use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
-use subvt_service_common::{err::InternalServerError, Service};
+use subvt_persistence::realtime::{get_realtime_store, RealtimeStore};
+use subvt_service_common::response::ResponseMeta;
+use subvt_service_common::{analytics, err::InternalServerError, response, Service};
+use subvt_substrate_client::SubstrateClient;
 use subvt_types::crypto::AccountId;
-use subvt_types::err::ServiceError;
+use subvt_types::err::ServiceErrorCode;
+use subvt_types::local_time::{self, LocalTimeHints};
+use subvt_types::report::{
+    HistoryCursor, NominationPoolReport, NominatorStakeBucket, Portfolio, PortfolioAccount,
+    PortfolioRequest, RewardProjectionRequest, UnbondingScheduleItem,
+    ValidatorNominatorDistribution, ValidatorOnboardingChecklist, ValidatorOnboardingItem,
+    ValidatorOnboardingItemCode, ValidatorOnboardingItemStatus,
+};
+use subvt_types::subvt::{TokenPrice, ValidatorDetails};
+
+mod api_usage;
+mod export;
+mod load_shedding;
+mod report_cache;
+mod retention;
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -22,6 +44,9 @@ type ResultResponse = Result<HttpResponse, InternalServerError>;
 #[derive(Clone)]
 struct ServiceState {
     postgres: Arc<PostgreSQLNetworkStorage>,
+    app_postgres: Arc<PostgreSQLAppStorage>,
+    substrate_client: Arc<SubstrateClient>,
+    realtime_store: Arc<dyn RealtimeStore>,
 }
 
 #[derive(Deserialize)]
@@ -35,11 +60,77 @@ struct EraReportQueryParameters {
     /// Report will be generated for a single era when this parameter is omitted.
     #[serde(rename(deserialize = "end_era_index"))]
     maybe_end_era_index: Option<u32>,
+    /// IANA timezone name, e.g. `Europe/Istanbul`. The era and validator reports get accompanied
+    /// with the local times of the era boundaries in the `era_local_times` metadata if given.
+    #[serde(rename(deserialize = "timezone"))]
+    maybe_timezone: Option<String>,
+    /// BCP 47 language tag, echoed back in the local times.
+    #[serde(rename(deserialize = "locale"))]
+    maybe_locale: Option<String>,
+}
+
+/// Error response if the requested timezone or locale is invalid.
+fn validate_local_time_query(query: &EraReportQueryParameters) -> Option<HttpResponse> {
+    if let Some(timezone) = &query.maybe_timezone {
+        if !local_time::is_valid_timezone(timezone) {
+            return Some(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Invalid timezone {}.", timezone),
+            ));
+        }
+    }
+    if let Some(locale) = &query.maybe_locale {
+        if !local_time::is_valid_locale(locale) {
+            return Some(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Invalid locale {}.", locale),
+            ));
+        }
+    }
+    None
+}
+
+/// Local times of the boundaries of the persisted eras in the range, if a timezone is requested.
+async fn get_era_local_times_meta(
+    data: &ServiceState,
+    query: &EraReportQueryParameters,
+    end_era_index: u32,
+) -> anyhow::Result<Option<ResponseMeta>> {
+    let timezone = match &query.maybe_timezone {
+        Some(timezone) => timezone,
+        None => return Ok(None),
+    };
+    let mut era_local_times = BTreeMap::new();
+    for era in data
+        .postgres
+        .get_eras(query.start_era_index, end_era_index)
+        .await?
+    {
+        era_local_times.insert(
+            era.index,
+            LocalTimeHints::for_era(&era, timezone, query.maybe_locale.as_deref())?,
+        );
+    }
+    Ok(Some(ResponseMeta {
+        era_local_times: Some(era_local_times),
+        ..Default::default()
+    }))
+}
+
+/// Cached report JSON response, with the local times of the era boundaries if requested.
+fn report_json_response(report_json: &str, maybe_meta: Option<ResponseMeta>) -> ResultResponse {
+    match maybe_meta {
+        Some(meta) => Ok(response::ok_json_with_meta(report_json, &meta)?),
+        None => Ok(response::ok_json(report_json)),
+    }
 }
 
 /// Gets the report for a certain validator in a range of eras, or a single era.
 /// See `EraValidatorReport` struct in the `subvt-types` for details.
-#[get("/report/validator/{account_id_hex_string}")]
+#[get(
+    "/report/validator/{account_id_hex_string}",
+    wrap = "load_shedding::Expensive"
+)]
 async fn era_validator_report_service(
     path: web::Path<ValidatorReportPathParameters>,
     query: web::Query<EraReportQueryParameters>,
@@ -47,64 +138,1336 @@ async fn era_validator_report_service(
 ) -> ResultResponse {
     if let Some(end_era_index) = query.maybe_end_era_index {
         if end_era_index < query.start_era_index {
-            return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+            return Ok(response::error(
+                ServiceErrorCode::InvalidEraRange,
                 "End era index cannot be less than start era index.".to_string(),
-            )));
+            ));
         }
         let era_count = end_era_index - query.start_era_index;
         if era_count > CONFIG.report.max_era_index_range {
-            return Ok(HttpResponse::BadRequest().json(ServiceError::from(format!(
-                "Report cannot span {} eras. Maximum allowed is {}.",
-                era_count, CONFIG.report.max_era_index_range
-            ))));
+            return Ok(response::error(
+                ServiceErrorCode::InvalidEraRange,
+                format!(
+                    "Report cannot span {} eras. Maximum allowed is {}.",
+                    era_count, CONFIG.report.max_era_index_range
+                ),
+            ));
         }
     }
     if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
-        Ok(HttpResponse::Ok().json(
-            data.postgres
-                .get_era_validator_report(
-                    query.start_era_index,
-                    query.maybe_end_era_index.unwrap_or(query.start_era_index),
-                    &account_id.to_string(),
-                )
-                .await?,
-        ))
+        let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+        if let Some(error_response) = validate_local_time_query(&query) {
+            return Ok(error_response);
+        }
+        let maybe_meta = get_era_local_times_meta(&data, &query, end_era_index).await?;
+        let report_json = report_cache::get_report_json(
+            &data.postgres,
+            data.realtime_store.as_ref(),
+            &format!("validator:{}", account_id),
+            (query.start_era_index, end_era_index),
+            data.postgres.get_era_validator_report(
+                query.start_era_index,
+                end_era_index,
+                &account_id.to_string(),
+            ),
+        )
+        .await?;
+        report_json_response(&report_json, maybe_meta)
     } else {
-        Ok(HttpResponse::BadRequest().json(ServiceError::from("Invalid account id.".to_string())))
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
     }
 }
 
 /// Gets the report for a range of eras, or a single era.
 /// See `EraReport` struct in the `subvt-types` definition for details.
-#[get("/report/era")]
+#[get("/report/era", wrap = "load_shedding::Expensive")]
 async fn era_report_service(
     query: web::Query<EraReportQueryParameters>,
     data: web::Data<ServiceState>,
 ) -> ResultResponse {
     if let Some(end_era_index) = query.maybe_end_era_index {
         if end_era_index < query.start_era_index {
-            return Ok(HttpResponse::BadRequest().json(ServiceError::from(
+            return Ok(response::error(
+                ServiceErrorCode::InvalidEraRange,
                 "End era index cannot be less than start era index.".to_string(),
-            )));
+            ));
         }
         let era_count = end_era_index - query.start_era_index;
         if era_count > CONFIG.report.max_era_index_range {
-            return Ok(HttpResponse::BadRequest().json(ServiceError::from(format!(
+            return Ok(response::error(
+                ServiceErrorCode::InvalidEraRange,
+                format!(
+                    "Report cannot span {} eras. Maximum allowed is {}.",
+                    era_count, CONFIG.report.max_era_index_range
+                ),
+            ));
+        }
+    }
+    let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+    if let Some(error_response) = validate_local_time_query(&query) {
+        return Ok(error_response);
+    }
+    let maybe_meta = get_era_local_times_meta(&data, &query, end_era_index).await?;
+    let report_json = report_cache::get_report_json(
+        &data.postgres,
+        data.realtime_store.as_ref(),
+        "era",
+        (query.start_era_index, end_era_index),
+        data.postgres
+            .get_era_report(query.start_era_index, end_era_index),
+    )
+    .await?;
+    report_json_response(&report_json, maybe_meta)
+}
+
+/// Gets the network-wide offence and slash statistics for a range of eras, or a single era.
+/// See `OffenceReport` struct in the `subvt-types` for details.
+#[get("/report/offences", wrap = "load_shedding::Expensive")]
+async fn offence_report_service(
+    query: web::Query<EraReportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+    if end_era_index < query.start_era_index {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            "End era index cannot be less than start era index.".to_string(),
+        ));
+    }
+    let era_count = end_era_index - query.start_era_index;
+    if era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
+                "Report cannot span {} eras. Maximum allowed is {}.",
+                era_count, CONFIG.report.max_era_index_range
+            ),
+        ));
+    }
+    Ok(response::ok(
+        data.postgres
+            .get_offence_report(query.start_era_index, end_era_index)
+            .await?,
+    ))
+}
+
+/// Gets the minimum bonds and the minimum active stakes for a range of eras, or a single era.
+/// See `EraStakingMinimums` struct in the `subvt-types` for details.
+#[get("/report/staking-minimums", wrap = "load_shedding::Expensive")]
+async fn staking_minimums_report_service(
+    query: web::Query<EraReportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+    if end_era_index < query.start_era_index {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            "End era index cannot be less than start era index.".to_string(),
+        ));
+    }
+    let era_count = end_era_index - query.start_era_index;
+    if era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
+                "Report cannot span {} eras. Maximum allowed is {}.",
+                era_count, CONFIG.report.max_era_index_range
+            ),
+        ));
+    }
+    Ok(response::ok(
+        data.postgres
+            .get_era_staking_minimums(query.start_era_index, end_era_index)
+            .await?,
+    ))
+}
+
+/// Gets the nominator churn and loyalty report of a validator in a range of eras, computed from
+/// the era exposures. See `ValidatorNominatorChurnReport` struct in the `subvt-types` for details.
+#[get(
+    "/report/validator/{account_id_hex_string}/nominator-churn",
+    wrap = "load_shedding::Expensive"
+)]
+async fn validator_nominator_churn_report_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<EraReportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+    if end_era_index < query.start_era_index {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            "End era index cannot be less than start era index.".to_string(),
+        ));
+    }
+    let era_count = end_era_index - query.start_era_index;
+    if era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
+                "Report cannot span {} eras. Maximum allowed is {}.",
+                era_count, CONFIG.report.max_era_index_range
+            ),
+        ));
+    }
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_validator_nominator_churn_report(
+                    query.start_era_index,
+                    end_era_index,
+                    &account_id.to_string(),
+                )
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+/// Gets the payout lag report of a validator in a range of eras, i.e. how many eras elapsed
+/// before the rewards of each era got paid out. See `ValidatorPayoutLagReport` struct in the
+/// `subvt-types` for details.
+#[get(
+    "/report/validator/{account_id_hex_string}/payout-lag",
+    wrap = "load_shedding::Expensive"
+)]
+async fn validator_payout_lag_report_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<EraReportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let end_era_index = query.maybe_end_era_index.unwrap_or(query.start_era_index);
+    if end_era_index < query.start_era_index {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            "End era index cannot be less than start era index.".to_string(),
+        ));
+    }
+    let era_count = end_era_index - query.start_era_index;
+    if era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
                 "Report cannot span {} eras. Maximum allowed is {}.",
                 era_count, CONFIG.report.max_era_index_range
-            ))));
+            ),
+        ));
+    }
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_validator_payout_lag_report(
+                    query.start_era_index,
+                    end_era_index,
+                    &account_id.to_string(),
+                )
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct EraRangeExportQueryParameters {
+    start_era_index: u32,
+    end_era_index: u32,
+    /// `jsonl` or `csv`, JSON lines when omitted.
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+impl EraRangeExportQueryParameters {
+    /// Validates the era range against the export limit, returns the error message on failure.
+    fn validate(&self) -> Result<(), String> {
+        if self.end_era_index < self.start_era_index {
+            return Err("End era index cannot be less than start era index.".to_string());
+        }
+        let era_count = self.end_era_index - self.start_era_index;
+        if era_count > CONFIG.report.max_export_era_index_range {
+            return Err(format!(
+                "Export cannot span {} eras. Maximum allowed is {}.",
+                era_count, CONFIG.report.max_export_era_index_range
+            ));
         }
+        Ok(())
     }
-    Ok(HttpResponse::Ok().json(
+}
+
+/// Streams the era reports of a range of eras as JSON lines or CSV, for exports that span more
+/// eras than allowed by `/report/era`. See `EraReport` struct in the `subvt-types` definition
+/// for details.
+#[get("/report/era/export", wrap = "load_shedding::Expensive")]
+async fn era_report_export_service(
+    query: web::Query<EraRangeExportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Err(message) = query.validate() {
+        return Ok(response::error(ServiceErrorCode::InvalidRequest, message));
+    }
+    let postgres = data.postgres.clone();
+    Ok(HttpResponse::Ok()
+        .content_type(query.format.get_content_type())
+        .streaming(stream_era_range_export(
+            query.start_era_index,
+            query.end_era_index,
+            query.format,
+            move |start_era_index, end_era_index| {
+                let postgres = postgres.clone();
+                async move {
+                    postgres
+                        .get_era_report(start_era_index, end_era_index)
+                        .await
+                }
+            },
+        )))
+}
+
+/// Streams the reports of a validator in a range of eras as JSON lines or CSV, for exports that
+/// span more eras than allowed by `/report/validator/{account_id_hex_string}`.
+/// See `EraValidatorReport` struct in the `subvt-types` for details.
+#[get(
+    "/report/validator/{account_id_hex_string}/export",
+    wrap = "load_shedding::Expensive"
+)]
+async fn era_validator_report_export_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<EraRangeExportQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Err(message) = query.validate() {
+        return Ok(response::error(ServiceErrorCode::InvalidRequest, message));
+    }
+    let account_id = match AccountId::from_str(&path.account_id_hex_string) {
+        Ok(account_id) => account_id.to_string(),
+        Err(_) => {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidAccountId,
+                "Invalid account id.".to_string(),
+            ))
+        }
+    };
+    let postgres = data.postgres.clone();
+    Ok(HttpResponse::Ok()
+        .content_type(query.format.get_content_type())
+        .streaming(stream_era_range_export(
+            query.start_era_index,
+            query.end_era_index,
+            query.format,
+            move |start_era_index, end_era_index| {
+                let postgres = postgres.clone();
+                let account_id = account_id.clone();
+                async move {
+                    postgres
+                        .get_era_validator_report(start_era_index, end_era_index, &account_id)
+                        .await
+                }
+            },
+        )))
+}
+
+/// Finalized block number of the latest validator list snapshot in the real-time store.
+fn get_snapshot_finalized_block_number(realtime_store: &dyn RealtimeStore) -> anyhow::Result<u64> {
+    Ok(realtime_store
+        .get(&format!(
+            "subvt:{}:validators:finalized_block_number",
+            CONFIG.substrate.chain
+        ))?
+        .context("No validator list in the real-time store.")?
+        .parse()?)
+}
+
+/// Reads the validator from the latest validator list snapshot in the real-time store.
+fn get_snapshot_validator(
+    realtime_store: &dyn RealtimeStore,
+    account_id: &AccountId,
+) -> anyhow::Result<Option<(u64, ValidatorDetails)>> {
+    let finalized_block_number = get_snapshot_finalized_block_number(realtime_store)?;
+    for list in ["active", "inactive"] {
+        if let Some(validator_json_string) = realtime_store.get(&format!(
+            "subvt:{}:validators:{}:{}:validator:{}",
+            CONFIG.substrate.chain, finalized_block_number, list, account_id
+        ))? {
+            return Ok(Some((
+                finalized_block_number,
+                serde_json::from_str(&validator_json_string)?,
+            )));
+        }
+    }
+    Ok(None)
+}
+
+/// Gets the distribution of the active stakes of the current nominators of a validator, along
+/// with the minimum stake in the rewarded set, so that small nominators can see whether their
+/// nomination would earn rewards. Read from the latest validator list snapshot.
+/// See `ValidatorNominatorDistribution` struct in the `subvt-types` definition for details.
+#[get("/report/validator/{account_id_hex_string}/nominator-distribution")]
+async fn validator_nominator_distribution_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let account_id = match AccountId::from_str(&path.account_id_hex_string) {
+        Ok(account_id) => account_id,
+        Err(_) => {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidAccountId,
+                "Invalid account id.".to_string(),
+            ))
+        }
+    };
+    let (finalized_block_number, validator) =
+        match get_snapshot_validator(data.realtime_store.as_ref(), &account_id)? {
+            Some(snapshot_validator) => snapshot_validator,
+            None => {
+                return Ok(response::error(
+                    ServiceErrorCode::NotFound,
+                    "Validator not found.".to_string(),
+                ))
+            }
+        };
+    let max_rewarded_nominator_count = data
+        .substrate_client
+        .get_constants()
+        .max_nominator_rewarded_per_validator;
+    let mut stakes: Vec<u128> = validator
+        .nominations
+        .iter()
+        .map(|nomination| nomination.stake.active_amount)
+        .collect();
+    stakes.sort_unstable_by(|a, b| b.cmp(a));
+    let rewarded_nominator_count = match max_rewarded_nominator_count {
+        Some(max_rewarded_nominator_count) => {
+            stakes.len().min(max_rewarded_nominator_count as usize)
+        }
+        None => stakes.len(),
+    };
+    let mut distribution = ValidatorNominatorDistribution {
+        account_id,
+        finalized_block_number,
+        nominator_count: stakes.len() as u32,
+        total_nominator_stake: stakes.iter().sum(),
+        max_rewarded_nominator_count,
+        is_oversubscribed: rewarded_nominator_count < stakes.len(),
+        min_rewarded_stake: if rewarded_nominator_count > 0 {
+            Some(stakes[rewarded_nominator_count - 1])
+        } else {
+            None
+        },
+        buckets: Vec::new(),
+    };
+    let token_unit = 10u128.pow(data.substrate_client.system_properties.token_decimals);
+    let mut bucket = NominatorStakeBucket {
+        min_stake: 0,
+        max_stake: token_unit,
+        ..Default::default()
+    };
+    // stakes are sorted in descending order, iterate in ascending order
+    for (index, stake) in stakes.iter().enumerate().rev() {
+        while *stake >= bucket.max_stake && bucket.max_stake < u128::MAX {
+            let max_stake = bucket.max_stake.saturating_mul(10);
+            distribution.buckets.push(bucket);
+            bucket = NominatorStakeBucket {
+                min_stake: distribution.buckets.last().unwrap().max_stake,
+                max_stake,
+                ..Default::default()
+            };
+        }
+        bucket.nominator_count += 1;
+        bucket.total_stake += stake;
+        if index < rewarded_nominator_count {
+            bucket.rewarded_nominator_count += 1;
+        }
+    }
+    distribution.buckets.push(bucket);
+    Ok(response::ok(distribution))
+}
+
+/// Gets the rank and score history of a 1KV candidate validator, earliest first, for the
+/// 1KV charts. See `OneKVCandidateHistoryItem` struct in the `subvt-types` definition for
+/// details.
+#[get("/report/validator/{account_id_hex_string}/onekv-history")]
+async fn validator_onekv_history_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if !CONFIG.is_onekv_enabled() {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "1KV is not enabled.".to_string(),
+        ));
+    }
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_onekv_candidate_history(&account_id)
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+/// Gets the eras in which the commission of a validator has changed, earliest first. See
+/// `ValidatorCommissionHistoryReport` struct in the `subvt-types` definition for details.
+#[get(
+    "/report/validator/{account_id_hex_string}/commission",
+    wrap = "load_shedding::Expensive"
+)]
+async fn validator_commission_history_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_validator_commission_history_report(&account_id)
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct ValidatorActivityQueryParameters {
+    #[serde(rename(deserialize = "count"))]
+    maybe_count: Option<u32>,
+}
+
+/// Gets the most recent on-chain activity of a validator (payouts, nominations received,
+/// preference changes, offline offences and slashes), latest first. See `ValidatorActivity`
+/// struct in the `subvt-types` definition for details.
+#[get("/validator/{account_id_hex_string}/activity")]
+async fn validator_activity_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<ValidatorActivityQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let count = query.maybe_count.unwrap_or(CONFIG.report.history_page_size);
+    if count == 0 || count > CONFIG.report.max_history_page_size {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Count must be between 1 and {}.",
+                CONFIG.report.max_history_page_size
+            ),
+        ));
+    }
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_validator_activity(&account_id, count)
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+fn onboarding_item(
+    code: ValidatorOnboardingItemCode,
+    status: ValidatorOnboardingItemStatus,
+    detail: Option<String>,
+) -> ValidatorOnboardingItem {
+    ValidatorOnboardingItem {
+        code,
+        status,
+        detail,
+    }
+}
+
+fn passed_or_failed(is_passed: bool) -> ValidatorOnboardingItemStatus {
+    if is_passed {
+        ValidatorOnboardingItemStatus::Passed
+    } else {
+        ValidatorOnboardingItemStatus::Failed
+    }
+}
+
+/// Evaluates the onboarding checklist of a prospective validator for the guided onboarding flow
+/// of the app: validation intent, session keys, confirmed identity, commission within the
+/// recommended band, self-stake above the minimum validator bond, Telemetry visibility and
+/// 1KV validity. The items that depend on the validator list are `unknown` until the account
+/// declares its validation intent. See `ValidatorOnboardingChecklist` struct in the
+/// `subvt-types` definition for details.
+#[get("/validator/{account_id_hex_string}/onboarding-checklist")]
+async fn validator_onboarding_checklist_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let account_id = match AccountId::from_str(&path.account_id_hex_string) {
+        Ok(account_id) => account_id,
+        Err(_) => {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidAccountId,
+                "Invalid account id.".to_string(),
+            ))
+        }
+    };
+    let (finalized_block_number, maybe_validator) =
+        match get_snapshot_validator(data.realtime_store.as_ref(), &account_id)? {
+            Some((finalized_block_number, validator)) => (finalized_block_number, Some(validator)),
+            None => (
+                get_snapshot_finalized_block_number(data.realtime_store.as_ref())?,
+                None,
+            ),
+        };
+    let mut items = Vec::new();
+    let mut telemetry_account_ids = vec![account_id.clone()];
+    if let Some(validator) = &maybe_validator {
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::ValidationIntent,
+            ValidatorOnboardingItemStatus::Passed,
+            None,
+        ));
+        // session keys
+        let has_session_keys = !validator
+            .next_session_keys
+            .trim_start_matches("0x")
+            .is_empty();
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::SessionKeys,
+            passed_or_failed(has_session_keys),
+            if has_session_keys {
+                None
+            } else {
+                Some("Session keys are not set for the next session.".to_string())
+            },
+        ));
+        // identity
+        let has_identity = validator.account.identity.is_some()
+            || matches!(&*validator.account.parent, Some(parent) if parent.identity.is_some());
+        let is_identity_confirmed = validator.account.get_confirmed();
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::Identity,
+            passed_or_failed(is_identity_confirmed),
+            if is_identity_confirmed {
+                None
+            } else if has_identity {
+                Some("Identity is not confirmed by a registrar.".to_string())
+            } else {
+                Some("Identity is not set.".to_string())
+            },
+        ));
+        // commission
+        let commission_per_billion = validator.preferences.commission_per_billion;
+        let min_commission_per_billion =
+            CONFIG.report.onboarding_min_recommended_commission_percent * 10_000_000;
+        let max_commission_per_billion =
+            CONFIG.report.onboarding_max_recommended_commission_percent * 10_000_000;
+        let is_commission_in_band = commission_per_billion >= min_commission_per_billion
+            && commission_per_billion <= max_commission_per_billion;
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::Commission,
+            passed_or_failed(is_commission_in_band),
+            if is_commission_in_band {
+                None
+            } else {
+                Some(format!(
+                    "Commission is {:.2}%, recommended band is {}% to {}%.",
+                    commission_per_billion as f64 / 10_000_000.0,
+                    CONFIG.report.onboarding_min_recommended_commission_percent,
+                    CONFIG.report.onboarding_max_recommended_commission_percent,
+                ))
+            },
+        ));
+        // self stake
+        let block_hash = data
+            .substrate_client
+            .get_block_hash(finalized_block_number)
+            .await?;
+        let min_self_stake = data
+            .substrate_client
+            .get_minimum_bonds(&block_hash)
+            .await?
+            .1
+            .unwrap_or(data.substrate_client.get_constants().existential_deposit);
+        let self_stake = validator.self_stake.active_amount;
+        let is_self_stake_enough = self_stake > 0 && self_stake >= min_self_stake;
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::SelfStake,
+            passed_or_failed(is_self_stake_enough),
+            if is_self_stake_enough {
+                None
+            } else {
+                Some(format!(
+                    "Active self-stake {} is less than the minimum {}.",
+                    self_stake, min_self_stake,
+                ))
+            },
+        ));
+        telemetry_account_ids.push(validator.controller_account_id.clone());
+    } else {
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::ValidationIntent,
+            ValidatorOnboardingItemStatus::Failed,
+            Some("Account has no validation intent.".to_string()),
+        ));
+        for code in [
+            ValidatorOnboardingItemCode::SessionKeys,
+            ValidatorOnboardingItemCode::Identity,
+            ValidatorOnboardingItemCode::Commission,
+            ValidatorOnboardingItemCode::SelfStake,
+        ] {
+            items.push(onboarding_item(
+                code,
+                ValidatorOnboardingItemStatus::Unknown,
+                Some("Available after the validation intent is declared.".to_string()),
+            ));
+        }
+    }
+    // telemetry
+    if CONFIG.get_telemetry_config().is_some() {
+        let node_names = data
+            .postgres
+            .get_node_names_by_account_ids(&telemetry_account_ids)
+            .await?;
+        items.push(onboarding_item(
+            ValidatorOnboardingItemCode::Telemetry,
+            passed_or_failed(!node_names.is_empty()),
+            if node_names.is_empty() {
+                Some("No node is visible on Telemetry.".to_string())
+            } else {
+                Some(node_names.join(", "))
+            },
+        ));
+    }
+    // 1KV
+    if CONFIG.is_onekv_enabled() {
+        let item = match &maybe_validator {
+            Some(validator) => match (
+                validator.onekv_candidate_record_id,
+                validator.onekv_is_valid,
+            ) {
+                (Some(_), Some(true)) => onboarding_item(
+                    ValidatorOnboardingItemCode::OneKV,
+                    ValidatorOnboardingItemStatus::Passed,
+                    None,
+                ),
+                (Some(candidate_record_id), _) => {
+                    let invalidity_reasons: Vec<String> = data
+                        .postgres
+                        .get_onekv_candidate_validity_items(candidate_record_id)
+                        .await?
+                        .into_iter()
+                        .filter(|validity| !validity.is_valid)
+                        .map(|validity| validity.details)
+                        .collect();
+                    onboarding_item(
+                        ValidatorOnboardingItemCode::OneKV,
+                        ValidatorOnboardingItemStatus::Failed,
+                        Some(if invalidity_reasons.is_empty() {
+                            "Not a valid 1KV candidate.".to_string()
+                        } else {
+                            invalidity_reasons.join(" ")
+                        }),
+                    )
+                }
+                (None, _) => onboarding_item(
+                    ValidatorOnboardingItemCode::OneKV,
+                    ValidatorOnboardingItemStatus::Failed,
+                    Some("Not a 1KV candidate.".to_string()),
+                ),
+            },
+            None => onboarding_item(
+                ValidatorOnboardingItemCode::OneKV,
+                ValidatorOnboardingItemStatus::Unknown,
+                Some("Available after the validation intent is declared.".to_string()),
+            ),
+        };
+        items.push(item);
+    }
+    Ok(response::ok(ValidatorOnboardingChecklist {
+        account_id,
+        finalized_block_number,
+        is_complete: items
+            .iter()
+            .all(|item| item.status == ValidatorOnboardingItemStatus::Passed),
+        items,
+    }))
+}
+
+/// Gets the most recent governance activity of an account (Democracy votes, delegations,
+/// proposals and seconds, OpenGov votes, delegations, referendum submissions and decision
+/// deposits), latest first. See `GovernanceActivity` struct in the `subvt-types` definition.
+#[get("/validator/{account_id_hex_string}/governance-activity")]
+async fn validator_governance_activity_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<ValidatorActivityQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let count = query.maybe_count.unwrap_or(CONFIG.report.history_page_size);
+    if count == 0 || count > CONFIG.report.max_history_page_size {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Count must be between 1 and {}.",
+                CONFIG.report.max_history_page_size
+            ),
+        ));
+    }
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::ok(
+            data.postgres
+                .get_account_governance_activity(&account_id, count)
+                .await?,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct EraIndexPathParameter {
+    era_index: u32,
+}
+
+/// Gets the election snapshot (voters with their stakes and nominations, and targets) that
+/// was used to select the active validator set of the given era.
+/// See `EraElectionReport` struct in the `subvt-types` definition for details.
+#[get("/report/era/{era_index}/election")]
+async fn era_election_report_service(
+    path: web::Path<EraIndexPathParameter>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    match data
+        .postgres
+        .get_era_election_report(path.era_index)
+        .await?
+    {
+        Some(report) => Ok(response::ok(report)),
+        None => Ok(response::error(
+            ServiceErrorCode::NotFound,
+            format!("Election snapshot not found for era {}.", path.era_index),
+        )),
+    }
+}
+
+/// Gets the geographical distribution of the active validators of an era.
+/// See `DecentralizationReport` struct in the `subvt-types` definition for details.
+#[get("/report/era/{era_index}/decentralization")]
+async fn era_decentralization_report_service(
+    path: web::Path<EraIndexPathParameter>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    match data
+        .postgres
+        .get_era_decentralization_report(
+            path.era_index,
+            CONFIG.geolocation.min_report_region_validator_count,
+        )
+        .await?
+    {
+        Some(report) => Ok(response::ok(report)),
+        None => Ok(response::error(
+            ServiceErrorCode::NotFound,
+            format!("No active validators found for era {}.", path.era_index),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct SessionIndexPathParameter {
+    session_index: u32,
+}
+
+/// Gets the validators that joined or left the active validator set at the start of the
+/// given session, with the reasons where derivable from the indexed data.
+/// See `SessionSetChangeReport` struct in the `subvt-types` definition for details.
+#[get("/report/session/{session_index}/set-changes")]
+async fn session_set_change_report_service(
+    path: web::Path<SessionIndexPathParameter>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    match data
+        .postgres
+        .get_session_set_change_report(path.session_index)
+        .await?
+    {
+        Some(report) => Ok(response::ok(report)),
+        None => Ok(response::error(
+            ServiceErrorCode::NotFound,
+            format!(
+                "Session {} or its previous session is not indexed.",
+                path.session_index
+            ),
+        )),
+    }
+}
+
+#[derive(Deserialize)]
+struct AccountPathParameter {
+    account_id_hex_string: String,
+}
+
+#[derive(Deserialize)]
+struct HistoryQueryParameters {
+    /// `meta.next_cursor` of the previous page, first page is returned when omitted.
+    #[serde(rename(deserialize = "cursor"))]
+    maybe_cursor: Option<String>,
+    #[serde(rename(deserialize = "page_size"))]
+    maybe_page_size: Option<u32>,
+}
+
+impl HistoryQueryParameters {
+    /// Decodes the cursor and validates the page size, returns the error message on failure.
+    fn get_cursor_and_page_size(&self) -> Result<(Option<HistoryCursor>, u32), String> {
+        let maybe_cursor = match &self.maybe_cursor {
+            Some(cursor) => {
+                Some(HistoryCursor::decode(cursor).map_err(|_| "Invalid cursor.".to_string())?)
+            }
+            None => None,
+        };
+        let page_size = self
+            .maybe_page_size
+            .unwrap_or(CONFIG.report.history_page_size);
+        if page_size == 0 || page_size > CONFIG.report.max_history_page_size {
+            return Err(format!(
+                "Page size must be between 1 and {}.",
+                CONFIG.report.max_history_page_size
+            ));
+        }
+        Ok((maybe_cursor, page_size))
+    }
+}
+
+/// Gets a page of the identity, judgement and sub-identity changes of an account in
+/// chronological order. See `AccountIdentityChange` struct in the `subvt-types` definition
+/// for details.
+#[get("/account/{account_id_hex_string}/identity-history")]
+async fn account_identity_history_service(
+    path: web::Path<AccountPathParameter>,
+    query: web::Query<HistoryQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let (maybe_cursor, page_size) = match query.get_cursor_and_page_size() {
+        Ok(cursor_and_page_size) => cursor_and_page_size,
+        Err(message) => return Ok(response::error(ServiceErrorCode::InvalidRequest, message)),
+    };
+    if let Ok(account_id) = AccountId::from_str(&path.account_id_hex_string) {
+        Ok(response::page(
+            data.postgres
+                .get_account_identity_history(&account_id, maybe_cursor.as_ref(), page_size)
+                .await?,
+            page_size,
+        ))
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::InvalidAccountId,
+            "Invalid account id.".to_string(),
+        ))
+    }
+}
+
+#[derive(Deserialize)]
+struct BlockPathParameter {
+    block_number: u64,
+}
+
+/// Gets the fee, tip and weight totals of a block, along with the fee details of each signed
+/// extrinsic in the block. See `BlockEconomicsReport` struct in the `subvt-types` definition
+/// for details.
+#[get("/report/block/{block_number}/economics")]
+async fn block_economics_report_service(
+    path: web::Path<BlockPathParameter>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    match data
+        .postgres
+        .get_block_economics_report(path.block_number)
+        .await?
+    {
+        Some(report) => Ok(response::ok(report)),
+        None => Ok(response::error(
+            ServiceErrorCode::NotFound,
+            format!("Block {} not found.", path.block_number),
+        )),
+    }
+}
+
+/// Projects the expected per-era and annual rewards of a hypothetical nomination of the given
+/// stake to the given candidate validators, based on recent era data.
+/// See `RewardProjection` struct in the `subvt-types` definition for details.
+#[post("/report/projection", wrap = "load_shedding::Expensive")]
+async fn reward_projection_service(
+    request: web::Json<RewardProjectionRequest>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if request.stake == 0 {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "Stake must be positive.".to_string(),
+        ));
+    }
+    let validator_account_ids = {
+        let mut unique_account_ids = HashSet::new();
+        let mut validator_account_ids = Vec::new();
+        for account_id in &request.validator_account_ids {
+            if unique_account_ids.insert(account_id) {
+                validator_account_ids.push(account_id.clone());
+            }
+        }
+        validator_account_ids
+    };
+    if validator_account_ids.is_empty() {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "At least one validator is required.".to_string(),
+        ));
+    }
+    if validator_account_ids.len() > CONFIG.report.max_projection_validator_count as usize {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Projection cannot include {} validators. Maximum allowed is {}.",
+                validator_account_ids.len(),
+                CONFIG.report.max_projection_validator_count
+            ),
+        ));
+    }
+    let era_count = request
+        .era_count
+        .unwrap_or(CONFIG.report.projection_era_count);
+    if era_count == 0 || era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
+                "Era count must be between 1 and {}.",
+                CONFIG.report.max_era_index_range
+            ),
+        ));
+    }
+    Ok(response::ok(
         data.postgres
-            .get_era_report(
-                query.start_era_index,
-                query.maybe_end_era_index.unwrap_or(query.start_era_index),
+            .get_reward_projection(request.stake, &validator_account_ids, era_count)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct CommissionComparisonQueryParameters {
+    /// Defaults to the configured value when omitted.
+    #[serde(rename(deserialize = "era_count"))]
+    maybe_era_count: Option<u32>,
+}
+
+/// Compares the active validators in commission bands over the most recent eras: validator
+/// count, average era points and average nominator return in each band, to help nominators see
+/// whether higher commission has historically correlated with better returns.
+/// See `CommissionComparisonReport` struct in the `subvt-types` definition for details.
+#[get("/report/commission-comparison", wrap = "load_shedding::Expensive")]
+async fn commission_comparison_report_service(
+    query: web::Query<CommissionComparisonQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let era_count = query
+        .maybe_era_count
+        .unwrap_or(CONFIG.report.commission_comparison_era_count);
+    if era_count == 0 || era_count > CONFIG.report.max_era_index_range {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidEraRange,
+            format!(
+                "Era count must be between 1 and {}.",
+                CONFIG.report.max_era_index_range
+            ),
+        ));
+    }
+    let band_lower_bounds_per_billion: Vec<u32> = CONFIG
+        .report
+        .commission_comparison_band_lower_bounds_percent
+        .iter()
+        .map(|percent| percent * 10_000_000)
+        .collect();
+    Ok(response::ok(
+        data.postgres
+            .get_commission_comparison_report(&band_lower_bounds_per_billion, era_count)
+            .await?,
+    ))
+}
+
+/// Gets the bonded and unbonding amounts, nominations, pending rewards and recent events of the
+/// given stash accounts, along with their totals. Bonding and nomination data is read from the
+/// finalized chain state, pending rewards are estimated from the indexed era data.
+/// See `Portfolio` struct in the `subvt-types` definition for details.
+#[post("/report/portfolio", wrap = "load_shedding::Expensive")]
+async fn portfolio_service(
+    request: web::Json<PortfolioRequest>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let stash_account_ids = {
+        let mut unique_account_ids = HashSet::new();
+        let mut stash_account_ids = Vec::new();
+        for account_id in &request.stash_account_ids {
+            if unique_account_ids.insert(account_id) {
+                stash_account_ids.push(account_id.clone());
+            }
+        }
+        stash_account_ids
+    };
+    if stash_account_ids.is_empty() {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "At least one stash account is required.".to_string(),
+        ));
+    }
+    if stash_account_ids.len() > CONFIG.report.max_portfolio_account_count as usize {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!(
+                "Portfolio cannot include {} accounts. Maximum allowed is {}.",
+                stash_account_ids.len(),
+                CONFIG.report.max_portfolio_account_count
+            ),
+        ));
+    }
+    let block_hash = data.substrate_client.get_finalized_block_hash().await?;
+    let active_era = data.substrate_client.get_active_era(&block_hash).await?;
+    let pending_reward_start_era_index = active_era
+        .index
+        .saturating_sub(CONFIG.report.portfolio_pending_reward_era_count);
+    let mut portfolio = Portfolio {
+        active_era_index: active_era.index,
+        ..Default::default()
+    };
+    let era_duration_millis = data.substrate_client.metadata.constants.era_duration_millis;
+    let mut unbonding_map: BTreeMap<u32, u128> = BTreeMap::new();
+    for stash_account_id in &stash_account_ids {
+        let mut account = PortfolioAccount {
+            stash_account_id: stash_account_id.clone(),
+            ..Default::default()
+        };
+        account.controller_account_id = data
+            .substrate_client
+            .get_controller_account_id(stash_account_id, &block_hash)
+            .await?;
+        if let Some(controller_account_id) = &account.controller_account_id {
+            if let Some(mut stake) = data
+                .substrate_client
+                .get_stake(controller_account_id, &block_hash)
+                .await?
+            {
+                stake.estimate_unlock_timestamps(&active_era, era_duration_millis);
+                account.bonded_amount = stake.total_amount;
+                account.active_amount = stake.active_amount;
+                account.unlocking = stake.unlocking;
+            }
+        }
+        if let Some(nomination) = data
+            .substrate_client
+            .get_nomination(stash_account_id, &block_hash)
+            .await?
+        {
+            account.nominee_account_ids = nomination.target_account_ids;
+        }
+        account.active_validator_account_ids = data
+            .postgres
+            .get_nominator_era_validator_account_ids(stash_account_id, active_era.index)
+            .await?;
+        account.pending_rewards = data
+            .postgres
+            .get_pending_rewards(stash_account_id, pending_reward_start_era_index)
+            .await?;
+        account.pending_reward = account
+            .pending_rewards
+            .iter()
+            .map(|pending_reward| pending_reward.amount)
+            .sum();
+        for chunk in &account.unlocking {
+            *unbonding_map.entry(chunk.era_index).or_default() += chunk.amount;
+            portfolio.total_unlocking_amount += chunk.amount;
+        }
+        portfolio.total_bonded_amount += account.bonded_amount;
+        portfolio.total_active_amount += account.active_amount;
+        portfolio.total_pending_reward += account.pending_reward;
+        portfolio.accounts.push(account);
+    }
+    portfolio.unbonding_schedule = unbonding_map
+        .into_iter()
+        .map(|(era_index, amount)| UnbondingScheduleItem {
+            era_index,
+            amount,
+            estimated_unlock_timestamp: active_era
+                .estimate_start_timestamp(era_index, era_duration_millis),
+        })
+        .collect();
+    portfolio.recent_events = data
+        .postgres
+        .get_portfolio_events(
+            &stash_account_ids,
+            CONFIG.report.portfolio_recent_event_count,
+        )
+        .await?;
+    if let Some(currency_code) = &request.currency_code {
+        portfolio.token_price =
+            get_latest_token_price(data.realtime_store.as_ref(), currency_code)?;
+        if let Some(token_price) = &portfolio.token_price {
+            let token_decimals = data.substrate_client.system_properties.token_decimals;
+            portfolio.total_bonded_amount_fiat =
+                Some(token_price.get_fiat_value(portfolio.total_bonded_amount, token_decimals));
+            portfolio.total_pending_reward_fiat =
+                Some(token_price.get_fiat_value(portfolio.total_pending_reward, token_decimals));
+        }
+    }
+    Ok(response::ok(portfolio))
+}
+
+/// Reads the latest token price written by `subvt-price-updater` from the real-time store.
+fn get_latest_token_price(
+    realtime_store: &dyn RealtimeStore,
+    currency_code: &str,
+) -> anyhow::Result<Option<TokenPrice>> {
+    match realtime_store.get(&format!(
+        "subvt:{}:price:{}",
+        CONFIG.substrate.chain,
+        currency_code.to_lowercase()
+    ))? {
+        Some(token_price_json) => Ok(Some(serde_json::from_str(&token_price_json)?)),
+        None => Ok(None),
+    }
+}
+
+#[derive(Deserialize)]
+struct TokenPriceHistoryQueryParameters {
+    currency_code: String,
+    /// Milliseconds since the Unix epoch.
+    start_timestamp: u64,
+    /// Milliseconds since the Unix epoch.
+    end_timestamp: u64,
+}
+
+/// Gets the fiat price history of the network token in the given currency and time range,
+/// earliest first. See `TokenPrice` struct in the `subvt-types` definition for details.
+#[get("/report/price")]
+async fn token_price_history_service(
+    query: web::Query<TokenPriceHistoryQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let currency_code = query.currency_code.to_lowercase();
+    if !CONFIG.price.currencies.contains(&currency_code) {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            format!("Currency {} is not supported.", query.currency_code),
+        ));
+    }
+    if query.end_timestamp < query.start_timestamp {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "End timestamp cannot be earlier than the start timestamp.".to_string(),
+        ));
+    }
+    Ok(response::ok(
+        data.postgres
+            .get_token_price_history(&currency_code, query.start_timestamp, query.end_timestamp)
+            .await?,
+    ))
+}
+
+#[derive(Deserialize)]
+struct RewardLedgerQueryParameters {
+    /// Milliseconds since the Unix epoch.
+    start_timestamp: u64,
+    /// Milliseconds since the Unix epoch.
+    end_timestamp: u64,
+    /// Each reward gets the token price in this currency at the time of the reward.
+    #[serde(rename(deserialize = "currency_code"))]
+    maybe_currency_code: Option<String>,
+}
+
+/// Gets the staking rewards of an account in the given time range from the reward ledger,
+/// earliest first. See `RewardLedgerEntry` struct in the `subvt-types` definition for details.
+#[get(
+    "/report/account/{account_id_hex_string}/reward",
+    wrap = "load_shedding::Expensive"
+)]
+async fn reward_ledger_service(
+    path: web::Path<ValidatorReportPathParameters>,
+    query: web::Query<RewardLedgerQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    let account_id = match AccountId::from_str(&path.account_id_hex_string) {
+        Ok(account_id) => account_id,
+        Err(_) => {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidAccountId,
+                "Invalid account id.".to_string(),
+            ))
+        }
+    };
+    let maybe_currency_code = query
+        .maybe_currency_code
+        .as_ref()
+        .map(|currency_code| currency_code.to_lowercase());
+    if let Some(currency_code) = &maybe_currency_code {
+        if !CONFIG.price.currencies.contains(currency_code) {
+            return Ok(response::error(
+                ServiceErrorCode::InvalidRequest,
+                format!("Currency {} is not supported.", currency_code),
+            ));
+        }
+    }
+    if query.end_timestamp < query.start_timestamp {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "End timestamp cannot be earlier than the start timestamp.".to_string(),
+        ));
+    }
+    Ok(response::ok(
+        data.postgres
+            .get_reward_ledger(
+                &account_id,
+                query.start_timestamp,
+                query.end_timestamp,
+                maybe_currency_code.as_deref(),
             )
             .await?,
     ))
 }
 
+/// Gets the nomination pool statistics and the pools, with their bonded amounts and
+/// nominations, from the finalized chain state. See `NominationPoolReport` struct in the
+/// `subvt-types` definition for details.
+#[get("/report/nomination-pool")]
+async fn nomination_pool_report_service(data: web::Data<ServiceState>) -> ResultResponse {
+    if !data.substrate_client.has_nomination_pools() {
+        return Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Network doesn't have nomination pools.".to_string(),
+        ));
+    }
+    let block_hash = data.substrate_client.get_finalized_block_hash().await?;
+    let active_era = data.substrate_client.get_active_era(&block_hash).await?;
+    let pools = data
+        .substrate_client
+        .get_nomination_pools(&block_hash)
+        .await?;
+    Ok(response::ok(NominationPoolReport::new(
+        active_era.index,
+        pools,
+    )))
+}
+
 async fn on_server_ready() {
     debug!("HTTP service started.");
 }
@@ -118,14 +1481,87 @@ impl Service for ReportService {
         let postgres = Arc::new(
             PostgreSQLNetworkStorage::new(&CONFIG, CONFIG.get_network_postgres_url()).await?,
         );
+        let substrate_client = Arc::new(SubstrateClient::new(&CONFIG).await?);
+        let app_postgres =
+            Arc::new(PostgreSQLAppStorage::new(&CONFIG, CONFIG.get_app_postgres_url()).await?);
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        let api_usage_flusher =
+            api_usage::run_api_usage_flusher(app_postgres.clone(), realtime_store.clone());
+        let pool_pressure = Arc::new(load_shedding::PoolPressure::default());
+        let pool_pressure_monitor =
+            load_shedding::run_pool_pressure_monitor(postgres.clone(), pool_pressure.clone());
+        let report_cache_invalidation_listener =
+            report_cache::run_invalidation_listener(postgres.clone(), realtime_store.clone());
         debug!("Starting HTTP service.");
         let server = HttpServer::new(move || {
             App::new()
                 .app_data(Data::new(ServiceState {
                     postgres: postgres.clone(),
+                    app_postgres: app_postgres.clone(),
+                    substrate_client: substrate_client.clone(),
+                    realtime_store: realtime_store.clone(),
                 }))
+                .app_data(Data::from(pool_pressure.clone()))
+                .wrap_fn(|request, service| {
+                    let maybe_api_key = request
+                        .headers()
+                        .get(api_usage::API_KEY_HEADER)
+                        .and_then(|value| value.to_str().ok())
+                        .map(|value| value.trim().to_string());
+                    let maybe_state = request.app_data::<Data<ServiceState>>().cloned();
+                    let response = service.call(request);
+                    async move {
+                        if let (Some(api_key), Some(state)) = (maybe_api_key, maybe_state) {
+                            if let Err(error) = api_usage::count_api_request(
+                                &state.app_postgres,
+                                state.realtime_store.as_ref(),
+                                &api_key,
+                            )
+                            .await
+                            {
+                                error!("Error while counting API request: {:?}", error);
+                            }
+                        }
+                        let response = response.await?;
+                        if let Some(pattern) = response.request().match_pattern() {
+                            analytics::record(&format!("endpoint:{}", pattern), 1);
+                        }
+                        Ok(response)
+                    }
+                })
+                .app_data(web::JsonConfig::default().error_handler(response::handle_json_error))
+                .app_data(web::QueryConfig::default().error_handler(response::handle_query_error))
+                .app_data(web::PathConfig::default().error_handler(response::handle_path_error))
                 .service(era_validator_report_service)
+                .service(era_validator_report_export_service)
+                .service(validator_nominator_churn_report_service)
+                .service(validator_payout_lag_report_service)
+                .service(validator_onekv_history_service)
+                .service(validator_commission_history_service)
+                .service(validator_activity_service)
+                .service(validator_onboarding_checklist_service)
+                .service(validator_governance_activity_service)
+                .service(validator_nominator_distribution_service)
+                .service(era_report_export_service)
                 .service(era_report_service)
+                .service(offence_report_service)
+                .service(staking_minimums_report_service)
+                .service(era_election_report_service)
+                .service(era_decentralization_report_service)
+                .service(session_set_change_report_service)
+                .service(account_identity_history_service)
+                .service(reward_projection_service)
+                .service(block_economics_report_service)
+                .service(portfolio_service)
+                .service(commission_comparison_report_service)
+                .service(token_price_history_service)
+                .service(reward_ledger_service)
+                .service(nomination_pool_report_service)
+                .service(retention::get_retention_policies_service)
+                .service(retention::save_retention_policy_service)
+                .service(retention::delete_retention_policy_service)
+                .service(retention::prune_service)
+                .service(retention::space_usage_service)
         })
         .workers(10)
         .disable_signals()
@@ -134,7 +1570,17 @@ impl Service for ReportService {
             CONFIG.http.host, CONFIG.http.report_service_port,
         ))?
         .run();
-        let (server_result, _) = tokio::join!(server, on_server_ready());
-        Ok(server_result?)
+        let (result, _) = tokio::join!(
+            async {
+                tokio::select! {
+                    server_result = server => server_result.map_err(anyhow::Error::from),
+                    flusher_result = api_usage_flusher => flusher_result,
+                    monitor_result = pool_pressure_monitor => monitor_result,
+                    listener_result = report_cache_invalidation_listener => listener_result,
+                }
+            },
+            on_server_ready()
+        );
+        result
     }
 }
@@ -0,0 +1,156 @@
+//! Load shedding under network database pressure. The time it takes to acquire a connection
+//! from the pool is measured periodically, and while it's over the configured threshold the
+//! expensive endpoints get rejected early with `503 Service Unavailable` and a `Retry-After`
+//! header, so that a few heavy queries cannot stall every worker. The cheap endpoints (single
+//! era, block or account lookups, snapshots from the real-time store) are always served.
+//!
+//! The endpoints that run long or era-range queries are marked with the `Expensive` resource
+//! middleware where their routes are declared, i.e. with `wrap = "load_shedding::Expensive"` in
+//! their route attributes.
+use crate::CONFIG;
+use actix_web::body::BoxBody;
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderValue, RETRY_AFTER};
+use actix_web::web::Data;
+use futures::future::{ready, Either, Ready};
+use log::{debug, warn};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_service_common::{analytics, response};
+use subvt_types::err::ServiceErrorCode;
+
+fn get_current_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+/// Connection pool wait time, shared by the monitor and the workers.
+#[derive(Default)]
+pub(crate) struct PoolPressure {
+    /// Wait time of the last completed measurement.
+    wait_millis: AtomicU64,
+    /// Start time of the ongoing measurement, zero if none. A measurement that hasn't completed
+    /// yet counts as a wait time until now, so that pressure is detected without waiting for
+    /// a saturated pool.
+    probe_start_millis: AtomicU64,
+}
+
+impl PoolPressure {
+    fn get_wait_millis(&self) -> u64 {
+        let wait_millis = self.wait_millis.load(Ordering::SeqCst);
+        match self.probe_start_millis.load(Ordering::SeqCst) {
+            0 => wait_millis,
+            probe_start_millis => {
+                wait_millis.max(get_current_millis().saturating_sub(probe_start_millis))
+            }
+        }
+    }
+
+    /// Whether the expensive endpoints should be rejected at the moment.
+    pub(crate) fn is_under_pressure(&self) -> bool {
+        CONFIG.report.load_shedding_enabled
+            && self.get_wait_millis() >= CONFIG.report.load_shedding_wait_threshold_millis
+    }
+}
+
+/// Resource middleware that marks an endpoint as expensive, i.e. rejects its requests while the
+/// pool is under pressure. Reads the `PoolPressure` from the application data.
+pub(crate) struct Expensive;
+
+impl<S> Transform<S, ServiceRequest> for Expensive
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Transform = ExpensiveMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(ExpensiveMiddleware { service }))
+    }
+}
+
+pub(crate) struct ExpensiveMiddleware<S> {
+    service: S,
+}
+
+impl<S> Service<ServiceRequest> for ExpensiveMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = actix_web::Error;
+    type Future = Either<Ready<Result<Self::Response, Self::Error>>, S::Future>;
+
+    forward_ready!(service);
+
+    fn call(&self, request: ServiceRequest) -> Self::Future {
+        let is_shed = request
+            .app_data::<Data<PoolPressure>>()
+            .map(|pressure| pressure.is_under_pressure())
+            .unwrap_or(false);
+        if !is_shed {
+            return Either::Right(self.service.call(request));
+        }
+        analytics::record("load_shedding:rejected", 1);
+        let mut response = response::error(
+            ServiceErrorCode::Unavailable,
+            "Service is under heavy load, please retry later.".to_string(),
+        );
+        response.headers_mut().insert(
+            RETRY_AFTER,
+            HeaderValue::from(CONFIG.report.load_shedding_retry_after_seconds),
+        );
+        Either::Left(ready(Ok(request.into_response(response))))
+    }
+}
+
+/// Measures the connection pool wait time periodically. A failed measurement, e.g. an acquire
+/// timeout, counts as the connection timeout.
+pub(crate) async fn run_pool_pressure_monitor(
+    postgres: Arc<PostgreSQLNetworkStorage>,
+    pressure: Arc<PoolPressure>,
+) -> anyhow::Result<()> {
+    if !CONFIG.report.load_shedding_enabled {
+        debug!("Load shedding is disabled.");
+        return futures::future::pending().await;
+    }
+    let mut was_under_pressure = false;
+    loop {
+        pressure
+            .probe_start_millis
+            .store(get_current_millis(), Ordering::SeqCst);
+        let wait_millis = match postgres.get_connection_wait_millis().await {
+            Ok(wait_millis) => wait_millis,
+            Err(error) => {
+                warn!("Cannot acquire network database connection: {:?}", error);
+                CONFIG.network_postgres.connection_timeout_seconds * 1000
+            }
+        };
+        pressure.wait_millis.store(wait_millis, Ordering::SeqCst);
+        pressure.probe_start_millis.store(0, Ordering::SeqCst);
+        let is_under_pressure = pressure.is_under_pressure();
+        if is_under_pressure && !was_under_pressure {
+            warn!(
+                "Network database pool is under pressure, connection wait time is {}ms. Shed expensive requests.",
+                wait_millis
+            );
+        } else if !is_under_pressure && was_under_pressure {
+            debug!(
+                "Network database pool has recovered, connection wait time is {}ms.",
+                wait_millis
+            );
+        }
+        was_under_pressure = is_under_pressure;
+        tokio::time::sleep(Duration::from_millis(
+            CONFIG.report.load_shedding_probe_period_millis,
+        ))
+        .await;
+    }
+}
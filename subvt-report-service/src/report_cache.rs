@@ -0,0 +1,143 @@
+//! Cache of the era and validator reports of the ended eras in the real-time store. These reports
+//! only change with the late payouts of their eras, so they get served from the cache until the
+//! block processor sends a report invalidation for one of their eras (see
+//! `ReportInvalidationNotification` in `subvt-types`). Each cached report is a member of the key
+//! set of each era it covers, and an invalidation deletes the reports in the key sets of the
+//! invalidated eras.
+use crate::CONFIG;
+use log::{debug, error, warn};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
+use subvt_persistence::realtime::{RealtimeStore, RealtimeWriteBatch};
+use subvt_service_common::analytics;
+
+fn get_key_prefix() -> String {
+    format!("subvt:{}:report_cache:", CONFIG.substrate.chain)
+}
+
+/// Incremented with each invalidation. A report doesn't get cached if there has been an
+/// invalidation while it was being generated, since it might have been generated from the data
+/// before the change.
+fn get_generation_key() -> String {
+    format!("{}generation", get_key_prefix())
+}
+
+fn get_era_key_set_key(era_index: u32) -> String {
+    format!("{}era:{}", get_key_prefix(), era_index)
+}
+
+fn get_report_key(report_name: &str, start_era_index: u32, end_era_index: u32) -> String {
+    format!(
+        "{}report:{}:{}:{}",
+        get_key_prefix(),
+        report_name,
+        start_era_index,
+        end_era_index
+    )
+}
+
+/// Gets the JSON of the report of the era range from the cache if all the eras have ended,
+/// otherwise generates the report and caches it when all the eras have ended. Cache errors
+/// don't fail the request, the report gets generated instead.
+pub(crate) async fn get_report_json<T, F>(
+    postgres: &PostgreSQLNetworkStorage,
+    realtime_store: &dyn RealtimeStore,
+    report_name: &str,
+    (start_era_index, end_era_index): (u32, u32),
+    report_future: F,
+) -> anyhow::Result<String>
+where
+    T: Serialize,
+    F: Future<Output = anyhow::Result<T>>,
+{
+    let is_cacheable = CONFIG.report.cache_enabled
+        && match postgres.get_last_ended_era_index().await? {
+            Some(last_ended_era_index) => end_era_index <= last_ended_era_index,
+            None => false,
+        };
+    if !is_cacheable {
+        return Ok(serde_json::to_string(&report_future.await?)?);
+    }
+    let report_key = get_report_key(report_name, start_era_index, end_era_index);
+    match realtime_store.get(&report_key) {
+        Ok(Some(report_json)) => {
+            analytics::record("report_cache:hit", 1);
+            return Ok(report_json);
+        }
+        Ok(None) => analytics::record("report_cache:miss", 1),
+        Err(error) => warn!("Cannot read cached report {}: {:?}", report_key, error),
+    }
+    let generation = realtime_store.increment(&get_generation_key(), 0);
+    let report_json = serde_json::to_string(&report_future.await?)?;
+    match (
+        generation,
+        realtime_store.increment(&get_generation_key(), 0),
+    ) {
+        (Ok(generation), Ok(current_generation)) if generation == current_generation => {
+            let mut batch = RealtimeWriteBatch::default();
+            batch.set(&report_key, &report_json);
+            for era_index in start_era_index..=end_era_index {
+                batch.add_to_set(
+                    &get_era_key_set_key(era_index),
+                    HashSet::from([report_key.clone()]),
+                );
+            }
+            if let Err(error) = realtime_store.write(&batch) {
+                warn!("Cannot cache report {}: {:?}", report_key, error);
+            }
+        }
+        _ => debug!("Report {} invalidated while generating.", report_key),
+    }
+    Ok(report_json)
+}
+
+/// Deletes the cached reports that cover any of the eras.
+fn invalidate_eras(realtime_store: &dyn RealtimeStore, era_indices: &[u32]) -> anyhow::Result<()> {
+    realtime_store.increment(&get_generation_key(), 1)?;
+    let mut batch = RealtimeWriteBatch::default();
+    let mut report_count = 0;
+    for era_index in era_indices {
+        let era_key_set_key = get_era_key_set_key(*era_index);
+        for report_key in realtime_store.get_set_members(&era_key_set_key)? {
+            batch.delete(&report_key);
+            report_count += 1;
+        }
+        batch.delete(&era_key_set_key);
+    }
+    realtime_store.write(&batch)?;
+    debug!(
+        "Invalidated {} cached report(s) of era(s) {:?}.",
+        report_count, era_indices
+    );
+    Ok(())
+}
+
+/// Listens to the report invalidations of the block processor. Invalidations sent while
+/// not listening are lost, so the whole cache gets dropped first.
+pub(crate) async fn run_invalidation_listener(
+    postgres: Arc<PostgreSQLNetworkStorage>,
+    realtime_store: Arc<dyn RealtimeStore>,
+) -> anyhow::Result<()> {
+    if !CONFIG.report.cache_enabled {
+        debug!("Report cache is disabled.");
+        return futures::future::pending().await;
+    }
+    let mut batch = RealtimeWriteBatch::default();
+    batch.delete_with_prefix(&get_key_prefix());
+    realtime_store.write(&batch)?;
+    debug!("Report cache cleared.");
+    postgres
+        .subscribe_to_report_invalidations(|notification| {
+            if let Err(error) = invalidate_eras(realtime_store.as_ref(), &notification.era_indices)
+            {
+                error!(
+                    "Error while invalidating the cached reports of block #{}: {:?}",
+                    notification.block_number, error
+                );
+            }
+        })
+        .await
+}
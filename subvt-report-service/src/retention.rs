@@ -0,0 +1,169 @@
+//! Admin endpoints of the data retention policies, the pruning runs and the disk space usage.
+//! Require the `X-Admin-Key` header to match `http.report_service_admin_key`, and are disabled
+//! when the key is not configured. See `DataRetentionCategory` in `subvt-types` for the
+//! categories of the prunable data.
+use crate::{ResultResponse, ServiceState, CONFIG};
+use actix_web::{delete, get, post, put, web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use subvt_service_common::response;
+use subvt_types::err::ServiceErrorCode;
+use subvt_types::rdb::{DataRetentionCategory, TableSpaceUsage};
+
+fn check_admin_key(request: &HttpRequest) -> Option<HttpResponse> {
+    let admin_key = &CONFIG.http.report_service_admin_key;
+    let is_authorized = !admin_key.is_empty()
+        && request
+            .headers()
+            .get("X-Admin-Key")
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value == admin_key)
+            .unwrap_or(false);
+    if is_authorized {
+        None
+    } else {
+        Some(response::error(
+            ServiceErrorCode::Forbidden,
+            "Not authorized.".to_string(),
+        ))
+    }
+}
+
+fn parse_category(category: &str) -> Result<DataRetentionCategory, HttpResponse> {
+    DataRetentionCategory::from_str(category)
+        .map_err(|error| response::error(ServiceErrorCode::NotFound, format!("{}", error)))
+}
+
+#[derive(Deserialize)]
+pub(crate) struct CategoryPathParameter {
+    category: String,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct RetentionPolicyInput {
+    retention_days: u32,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct PruneQueryParameters {
+    /// All categories with a policy get pruned when this parameter is omitted.
+    #[serde(rename(deserialize = "category"))]
+    maybe_category: Option<String>,
+}
+
+#[derive(Serialize)]
+struct SpaceUsageReport {
+    network_tables: Vec<TableSpaceUsage>,
+    app_tables: Vec<TableSpaceUsage>,
+}
+
+/// Gets the retention policies. Data of the categories without a policy is kept indefinitely.
+#[get("/admin/retention")]
+pub(crate) async fn get_retention_policies_service(
+    request: HttpRequest,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    Ok(response::ok(
+        data.postgres.get_data_retention_policies().await?,
+    ))
+}
+
+/// Creates or updates the retention policy of a category. Doesn't prune the data, see the prune
+/// endpoint.
+#[put("/admin/retention/{category}")]
+pub(crate) async fn save_retention_policy_service(
+    request: HttpRequest,
+    path: web::Path<CategoryPathParameter>,
+    input: web::Json<RetentionPolicyInput>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let category = match parse_category(&path.category) {
+        Ok(category) => category,
+        Err(error_response) => return Ok(error_response),
+    };
+    if input.retention_days == 0 {
+        return Ok(response::error(
+            ServiceErrorCode::InvalidRequest,
+            "Retention period should be at least 1 day.".to_string(),
+        ));
+    }
+    data.postgres
+        .save_data_retention_policy(category, input.retention_days)
+        .await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Deletes the retention policy of a category, so that its data is kept indefinitely.
+#[delete("/admin/retention/{category}")]
+pub(crate) async fn delete_retention_policy_service(
+    request: HttpRequest,
+    path: web::Path<CategoryPathParameter>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let category = match parse_category(&path.category) {
+        Ok(category) => category,
+        Err(error_response) => return Ok(error_response),
+    };
+    if data.postgres.delete_data_retention_policy(category).await? {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(response::error(
+            ServiceErrorCode::NotFound,
+            "Retention policy not found.".to_string(),
+        ))
+    }
+}
+
+/// Runs the pruning of the categories with a retention policy, or of a single category, and
+/// responds when done. See `DataPruneResult` in `subvt-types` for the response items.
+#[post("/admin/retention/prune")]
+pub(crate) async fn prune_service(
+    request: HttpRequest,
+    query: web::Query<PruneQueryParameters>,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    let maybe_category = match &query.maybe_category {
+        Some(category) => match parse_category(category) {
+            Ok(category) => Some(category),
+            Err(error_response) => return Ok(error_response),
+        },
+        None => None,
+    };
+    Ok(response::ok(
+        data.postgres
+            .prune_data_by_policies(
+                &data.app_postgres,
+                CONFIG.substrate.network_id,
+                maybe_category,
+            )
+            .await?,
+    ))
+}
+
+/// Gets the disk space usage of each table of the network and application databases,
+/// largest first.
+#[get("/admin/space-usage")]
+pub(crate) async fn space_usage_service(
+    request: HttpRequest,
+    data: web::Data<ServiceState>,
+) -> ResultResponse {
+    if let Some(error_response) = check_admin_key(&request) {
+        return Ok(error_response);
+    }
+    Ok(response::ok(SpaceUsageReport {
+        network_tables: data.postgres.get_table_space_usages().await?,
+        app_tables: data.app_postgres.get_table_space_usages().await?,
+    }))
+}
@@ -0,0 +1,92 @@
+//! Opt-in anonymized analytics for capacity planning. Services record coarse usage counters,
+//! such as the number of new subscriptions, endpoint hits and sent notifications, which are
+//! accumulated in memory and get added to the hourly totals of the service in the application
+//! database periodically. Only the metric names and the counts are recorded, nothing about
+//! the users or the requests. Recording is a no-op unless `analytics.enabled` is set.
+use lazy_static::lazy_static;
+use log::{debug, error};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+
+static IS_ENABLED: AtomicBool = AtomicBool::new(false);
+
+lazy_static! {
+    static ref COUNTERS: Mutex<HashMap<String, u64>> = Mutex::new(HashMap::new());
+}
+
+/// Adds to the counter of the metric, if the analytics are enabled. Metric names should be
+/// of low cardinality, e.g. `endpoint:/report/era`, never containing user data such as
+/// account ids.
+pub fn record(metric: &str, count: u64) {
+    if !IS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+    *COUNTERS
+        .lock()
+        .unwrap()
+        .entry(metric.to_string())
+        .or_default() += count;
+}
+
+/// Service name is the name of the executable, e.g. `subvt-report-service`.
+fn get_service_name() -> String {
+    std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+async fn run_flusher(config: &Config, service_name: &str) -> anyhow::Result<()> {
+    let postgres = PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?;
+    loop {
+        tokio::time::sleep(std::time::Duration::from_secs(
+            config.analytics.flush_period_seconds,
+        ))
+        .await;
+        let counters = std::mem::take(&mut *COUNTERS.lock().unwrap());
+        if counters.is_empty() {
+            continue;
+        }
+        debug!("Flush {} analytics counter(s).", counters.len());
+        if let Err(error) = postgres
+            .add_analytics_counts(service_name, &config.substrate.chain, &counters)
+            .await
+        {
+            // put the counts back to be persisted with the next flush
+            let mut current_counters = COUNTERS.lock().unwrap();
+            for (metric, count) in counters {
+                *current_counters.entry(metric).or_default() += count;
+            }
+            return Err(error);
+        }
+    }
+}
+
+/// Enables the recording and starts the periodic flush, if the analytics are enabled in the
+/// configuration. Flush errors get logged, and the flush is retried after the recovery delay.
+pub(crate) fn start(config: &Config) {
+    if !config.analytics.enabled {
+        return;
+    }
+    IS_ENABLED.store(true, Ordering::Relaxed);
+    let config = config.clone();
+    tokio::spawn(async move {
+        let service_name = get_service_name();
+        debug!("Analytics enabled for {}.", service_name);
+        loop {
+            if let Err(error) = run_flusher(&config, &service_name).await {
+                error!("Analytics flush error: {:?}", error);
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(
+                config.common.recovery_retry_seconds,
+            ))
+            .await;
+        }
+    });
+}
@@ -1,7 +1,10 @@
 //! Service error types.
+use crate::response;
+use actix_web::http::StatusCode;
+use actix_web::HttpResponse;
 use log::error;
 use std::fmt::{Display, Formatter};
-use subvt_types::err::ServiceError;
+use subvt_types::err::ServiceErrorCode;
 
 #[derive(Debug)]
 pub struct InternalServerError {
@@ -10,13 +13,24 @@ pub struct InternalServerError {
 
 impl Display for InternalServerError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        error!("{:?}", self.err);
-        let err = ServiceError::from("Internal server error.".to_string());
-        write!(f, "{}", serde_json::to_string(&err).unwrap())
+        write!(f, "{}", self.err)
     }
 }
 
-impl actix_web::error::ResponseError for InternalServerError {}
+impl actix_web::error::ResponseError for InternalServerError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    /// Logs the cause and responds with a generic error, the cause is not exposed.
+    fn error_response(&self) -> HttpResponse {
+        error!("{:?}", self.err);
+        response::error(
+            ServiceErrorCode::Internal,
+            "Internal server error.".to_string(),
+        )
+    }
+}
 
 impl From<anyhow::Error> for InternalServerError {
     fn from(err: anyhow::Error) -> InternalServerError {
@@ -3,9 +3,12 @@
 use async_trait::async_trait;
 use std::str::FromStr;
 use subvt_config::Config;
-use subvt_types::substrate::Chain;
+use subvt_types::substrate::{set_default_ss58_prefix, Chain};
 
+pub mod analytics;
 pub mod err;
+pub mod response;
+pub mod subscription;
 
 #[async_trait(?Send)]
 pub trait Service {
@@ -15,9 +18,15 @@ pub trait Service {
         let config = Config::default();
         subvt_logging::init(&config);
         log::debug!("Starting service...");
-        Chain::from_str(&config.substrate.chain)
-            .unwrap()
-            .sp_core_set_default_ss58_version();
+        analytics::start(&config);
+        // the Substrate client sets the prefix in the system properties of the node on connection
+        if let Some(ss58_prefix) = config.substrate.ss58_prefix.or_else(|| {
+            Chain::from_str(&config.substrate.chain)
+                .unwrap()
+                .get_ss58_prefix()
+        }) {
+            set_default_ss58_prefix(ss58_prefix);
+        }
         let delay_seconds = config.common.recovery_retry_seconds;
         loop {
             let result = self.run().await;
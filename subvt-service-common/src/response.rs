@@ -0,0 +1,139 @@
+//! Response envelope of the REST services. Every JSON response, successful or not, has the
+//! same shape: the payload in `data`, the optional pagination metadata in `meta`, and the
+//! errors with machine-readable codes in `errors` (see `ServiceErrorCode` in `subvt-types`).
+//! Metadata also carries the optional local-time hints of the era boundaries.
+//! Responses without a body (`204 No Content`) and the file exports are not enveloped.
+use actix_web::error::{InternalError, JsonPayloadError, PathError, QueryPayloadError};
+use actix_web::http::StatusCode;
+use actix_web::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use subvt_types::err::{ServiceError, ServiceErrorCode};
+use subvt_types::local_time::LocalTimeHints;
+use subvt_types::report::HistoryPage;
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResponseMeta {
+    /// Maximum number of items in a page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_size: Option<u32>,
+    /// Cursor of the next page, `None` on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Local times of the start and end of the eras in the response, by era index. Only set if
+    /// the request has a `timezone` parameter.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub era_local_times: Option<BTreeMap<u32, LocalTimeHints>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ResponseEnvelope<T> {
+    /// `None` if the request has failed.
+    pub data: Option<T>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meta: Option<ResponseMeta>,
+    /// Empty if the request has succeeded.
+    #[serde(default)]
+    pub errors: Vec<ServiceError>,
+}
+
+impl<T> ResponseEnvelope<T> {
+    pub fn data(data: T) -> Self {
+        ResponseEnvelope {
+            data: Some(data),
+            meta: None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn error(error: ServiceError) -> Self {
+        ResponseEnvelope {
+            data: None,
+            meta: None,
+            errors: vec![error],
+        }
+    }
+}
+
+/// HTTP status of the responses of the error code.
+pub fn get_status_code(code: ServiceErrorCode) -> StatusCode {
+    match code {
+        ServiceErrorCode::InvalidRequest
+        | ServiceErrorCode::InvalidAccountId
+        | ServiceErrorCode::InvalidEraRange => StatusCode::BAD_REQUEST,
+        ServiceErrorCode::NotFound => StatusCode::NOT_FOUND,
+        ServiceErrorCode::Unauthorized => StatusCode::UNAUTHORIZED,
+        ServiceErrorCode::Forbidden => StatusCode::FORBIDDEN,
+        ServiceErrorCode::Conflict => StatusCode::CONFLICT,
+        ServiceErrorCode::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        ServiceErrorCode::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// `200 OK` response with the data.
+pub fn ok<T: Serialize>(data: T) -> HttpResponse {
+    HttpResponse::Ok().json(ResponseEnvelope::data(data))
+}
+
+/// `201 Created` response with the created resource.
+pub fn created<T: Serialize>(data: T) -> HttpResponse {
+    HttpResponse::Created().json(ResponseEnvelope::data(data))
+}
+
+/// `200 OK` response with the already serialized JSON of the data, e.g. a cached report.
+pub fn ok_json(data_json: &str) -> HttpResponse {
+    HttpResponse::Ok()
+        .content_type("application/json")
+        .body(format!(r#"{{"data":{},"errors":[]}}"#, data_json))
+}
+
+/// `200 OK` response with the already serialized JSON of the data and the metadata.
+pub fn ok_json_with_meta(data_json: &str, meta: &ResponseMeta) -> anyhow::Result<HttpResponse> {
+    Ok(HttpResponse::Ok()
+        .content_type("application/json")
+        .body(format!(
+            r#"{{"data":{},"meta":{},"errors":[]}}"#,
+            data_json,
+            serde_json::to_string(meta)?
+        )))
+}
+
+/// `200 OK` response with the items of the page as the data, and the page size and the next
+/// cursor as the metadata.
+pub fn page<T: Serialize>(page: HistoryPage<T>, page_size: u32) -> HttpResponse {
+    HttpResponse::Ok().json(ResponseEnvelope {
+        data: Some(page.items),
+        meta: Some(ResponseMeta {
+            page_size: Some(page_size),
+            next_cursor: page.next_cursor,
+            ..Default::default()
+        }),
+        errors: Vec::new(),
+    })
+}
+
+/// Error response with the status of the error code.
+pub fn error(code: ServiceErrorCode, description: String) -> HttpResponse {
+    HttpResponse::build(get_status_code(code)).json(ResponseEnvelope::<()>::error(
+        ServiceError::new(code, description),
+    ))
+}
+
+fn invalid_request_error(description: String) -> actix_web::Error {
+    InternalError::from_response("", error(ServiceErrorCode::InvalidRequest, description)).into()
+}
+
+/// Error handler of the JSON body extractor, see `JsonConfig::error_handler`.
+pub fn handle_json_error(error: JsonPayloadError, _: &HttpRequest) -> actix_web::Error {
+    invalid_request_error(error.to_string())
+}
+
+/// Error handler of the query extractor, see `QueryConfig::error_handler`.
+pub fn handle_query_error(error: QueryPayloadError, _: &HttpRequest) -> actix_web::Error {
+    invalid_request_error(error.to_string())
+}
+
+/// Error handler of the path extractor, see `PathConfig::error_handler`.
+pub fn handle_path_error(error: PathError, _: &HttpRequest) -> actix_web::Error {
+    invalid_request_error(error.to_string())
+}
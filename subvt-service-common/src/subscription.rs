@@ -0,0 +1,132 @@
+//! Authorization of the WebSocket subscriptions, shared by the WebSocket servers. When enabled
+//! by the configuration, a subscription can carry a session token issued by the wallet sign-in
+//! flow of `subvt-app-service`. Subscriptions with a valid token get the `full` plan and count
+//! towards the concurrent subscription limit of the user, subscriptions without a token get the
+//! configured anonymous plan, or get rejected if authentication is required.
+use log::error;
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use subvt_config::Config;
+use subvt_persistence::postgres::app::PostgreSQLAppStorage;
+
+/// Determines the data served to a subscription.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SubscriptionPlan {
+    /// Only the summary fields, e.g. `ValidatorSummary` instead of `ValidatorDetails`.
+    Summary,
+    Full,
+}
+
+impl FromStr for SubscriptionPlan {
+    type Err = anyhow::Error;
+
+    fn from_str(plan: &str) -> Result<Self, Self::Err> {
+        match plan {
+            "summary" => Ok(SubscriptionPlan::Summary),
+            "full" => Ok(SubscriptionPlan::Full),
+            _ => Err(anyhow::anyhow!("Unknown subscription plan: {}", plan)),
+        }
+    }
+}
+
+/// Authorized subscription. Holds a slot in the concurrent subscription limit of the user
+/// until dropped, so it should live as long as the subscription.
+pub struct SubscriptionPermit {
+    pub maybe_user_id: Option<u32>,
+    pub plan: SubscriptionPlan,
+    subscription_counts: Arc<Mutex<HashMap<u32, u32>>>,
+}
+
+impl Drop for SubscriptionPermit {
+    fn drop(&mut self) {
+        if let Some(user_id) = self.maybe_user_id {
+            let mut subscription_counts = self.subscription_counts.lock().unwrap();
+            if let Some(count) = subscription_counts.get_mut(&user_id) {
+                *count = count.saturating_sub(1);
+                if *count == 0 {
+                    subscription_counts.remove(&user_id);
+                }
+            }
+        }
+    }
+}
+
+pub struct SubscriptionAuthorizer {
+    /// `None` when subscription authentication is disabled.
+    maybe_postgres: Option<PostgreSQLAppStorage>,
+    auth_required: bool,
+    anonymous_plan: SubscriptionPlan,
+    max_user_subscription_count: u32,
+    subscription_counts: Arc<Mutex<HashMap<u32, u32>>>,
+}
+
+impl SubscriptionAuthorizer {
+    pub async fn new(config: &Config) -> anyhow::Result<Self> {
+        let maybe_postgres = if config.rpc.subscription_auth_enabled {
+            Some(PostgreSQLAppStorage::new(config, config.get_app_postgres_url()).await?)
+        } else {
+            None
+        };
+        Ok(Self {
+            maybe_postgres,
+            auth_required: config.rpc.subscription_auth_required,
+            anonymous_plan: SubscriptionPlan::from_str(&config.rpc.anonymous_subscription_plan)?,
+            max_user_subscription_count: config.rpc.max_user_subscription_count,
+            subscription_counts: Default::default(),
+        })
+    }
+
+    fn get_permit(&self, maybe_user_id: Option<u32>, plan: SubscriptionPlan) -> SubscriptionPermit {
+        SubscriptionPermit {
+            maybe_user_id,
+            plan,
+            subscription_counts: self.subscription_counts.clone(),
+        }
+    }
+
+    /// Authorizes a subscription with the optional session token. Returns the message
+    /// to be sent to the client on rejection.
+    pub async fn authorize(&self, maybe_token: Option<&str>) -> Result<SubscriptionPermit, String> {
+        let postgres = match &self.maybe_postgres {
+            Some(postgres) => postgres,
+            None => return Ok(self.get_permit(None, SubscriptionPlan::Full)),
+        };
+        let token = match maybe_token {
+            Some(token) => token,
+            None if self.auth_required => return Err("Session token required.".to_string()),
+            None => return Ok(self.get_permit(None, self.anonymous_plan)),
+        };
+        let user_id = match postgres.get_session_user_id(token).await {
+            Ok(Some(user_id)) => user_id,
+            Ok(None) => return Err("Invalid or expired session token.".to_string()),
+            Err(error) => {
+                error!("Error while authorizing subscription: {:?}", error);
+                return Err("Error while authorizing subscription.".to_string());
+            }
+        };
+        {
+            let mut subscription_counts = self.subscription_counts.lock().unwrap();
+            let count = subscription_counts.entry(user_id).or_insert(0);
+            if *count >= self.max_user_subscription_count {
+                return Err(format!(
+                    "Cannot have more than {} concurrent subscriptions.",
+                    self.max_user_subscription_count
+                ));
+            }
+            *count += 1;
+        }
+        Ok(self.get_permit(Some(user_id), SubscriptionPlan::Full))
+    }
+
+    /// Blocking version of `authorize` for the synchronous subscription callbacks of the
+    /// WebSocket servers. Has to be called on a multi-threaded Tokio runtime.
+    pub fn authorize_blocking(
+        &self,
+        maybe_token: Option<&str>,
+    ) -> Result<SubscriptionPermit, String> {
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(self.authorize(maybe_token))
+        })
+    }
+}
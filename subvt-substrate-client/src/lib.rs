@@ -1,60 +1,74 @@
 //! SubVT Substrate client implementation.
+use crate::rpc::FailoverClient;
 use crate::storage_utility::{
     get_rpc_paged_keys_params, get_rpc_paged_map_keys_params, get_rpc_storage_map_params,
     get_rpc_storage_plain_params, get_storage_map_key,
 };
+use anyhow::Context;
+use futures::{Stream, StreamExt, TryStreamExt};
 use jsonrpsee::{
-    core::client::{Client, ClientT, Subscription, SubscriptionClientT},
+    core::client::{ClientT, Subscription},
     rpc_params,
     ws_client::WsClientBuilder,
 };
-use log::{debug, error, trace};
-use parity_scale_codec::Decode;
+use log::{debug, error, trace, warn};
+use parity_scale_codec::{Decode, Encode};
 use sp_core::storage::{StorageChangeSet, StorageKey};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::convert::TryInto;
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
+use std::sync::Arc;
 use subvt_config::Config;
 use subvt_types::crypto::AccountId;
+use subvt_types::substrate::metadata::ChainConstants;
+use subvt_types::substrate::nomination_pools::{
+    get_pool_bonded_account_id, NominationPool, NominationPoolMember, PoolId,
+    PoolNominationsSummary,
+};
+use subvt_types::substrate::set_default_ss58_prefix;
 use subvt_types::substrate::{
     event::SubstrateEvent, extrinsic::SubstrateExtrinsic, legacy::LegacyValidatorPrefs,
-    metadata::Metadata, Account, Balance, Block, BlockHeader, BlockWrapper, Chain, Epoch, Era,
-    EraRewardPoints, EraStakers, IdentityRegistration, LastRuntimeUpgradeInfo, Nomination,
-    RewardDestination, Stake, SuperAccountId, SystemProperties, ValidatorPreferences,
-    ValidatorStake,
+    metadata::Metadata, Account, AccountBalance, Balance, Block, BlockHeader, BlockWrapper, Chain,
+    ChainDescriptor, ChainInfo, DemocracyReferendumInfo, ElectionSnapshot, Epoch, Era,
+    EraRewardPoints, EraStakers, EraUnappliedSlashes, IdentityRegistration, LastRuntimeUpgradeInfo,
+    Nomination, RewardDestination, RuntimeVersion, SessionKeysValidity, Stake, SuperAccountId,
+    SystemProperties, ValidatorPreferences, ValidatorStake,
 };
 /// Substrate client structure and its functions.
 /// This is the main gateway for SubVT to a Substrate node RPC interface.
 use subvt_types::subvt::ValidatorDetails;
 use subvt_utility::decode_hex_string;
 
+mod rpc;
 mod storage_utility;
 
 const KEY_QUERY_PAGE_SIZE: usize = 1000;
+/// Maximum number of concurrent `state_queryStorageAt` requests of a multi-key query per
+/// connection of the pool.
+const STORAGE_QUERY_CONCURRENCY: usize = 8;
+/// Maximum number of blocks in flight per connection of the pool while fetching the headers of
+/// a block range, see `get_block_header_stream`.
+const BLOCK_HEADER_QUERY_CONCURRENCY: usize = 4;
+/// Default value of the `Staking.HistoryDepth` storage, used when the storage is not set.
+const DEFAULT_HISTORY_DEPTH: u32 = 84;
 
 /// The client.
 pub struct SubstrateClient {
     pub chain: Chain,
     pub metadata: Metadata,
     pub system_properties: SystemProperties,
-    ws_client: Client,
+    ws_client: Arc<FailoverClient>,
 }
 
 impl SubstrateClient {
-    /// Connect to the node and construct a new Substrate client.
+    /// Connect to the node and construct a new Substrate client. Connects to the first healthy
+    /// node of the configured RPC endpoints, and fails over to the next one when the connection
+    /// is lost.
     pub async fn new(config: &Config) -> anyhow::Result<Self> {
         debug!("Constructing Substrate client.");
-        let ws_client = WsClientBuilder::default()
-            .connection_timeout(std::time::Duration::from_secs(
-                config.substrate.connection_timeout_seconds,
-            ))
-            .request_timeout(std::time::Duration::from_secs(
-                config.substrate.request_timeout_seconds,
-            ))
-            .build(&config.substrate.rpc_url)
-            .await?;
+        let ws_client = Arc::new(FailoverClient::connect(config).await?);
         debug!("Substrate connection successful.");
         // get current block hash
         let block_hash: String = ws_client.request("chain_getBlockHash", None).await?;
@@ -82,6 +96,7 @@ impl SubstrateClient {
         let system_properties: SystemProperties =
             ws_client.request("system_properties", None).await?;
         debug!("Got system properties. {:?}", system_properties);
+        set_default_ss58_prefix(system_properties.ss_58_format);
         Ok(Self {
             chain,
             metadata,
@@ -90,7 +105,81 @@ impl SubstrateClient {
         })
     }
 
-    pub async fn set_metadata_at_block(&mut self, block_hash: &str) -> anyhow::Result<()> {
+    /// Fetches the chain information required to bootstrap a deployment for the network of the
+    /// node at the given URL. Unlike `new`, doesn't require the chain to be a known `Chain`.
+    pub async fn fetch_chain_info(config: &Config, rpc_url: &str) -> anyhow::Result<ChainInfo> {
+        let ws_client = WsClientBuilder::default()
+            .connection_timeout(std::time::Duration::from_secs(
+                config.substrate.connection_timeout_seconds,
+            ))
+            .request_timeout(std::time::Duration::from_secs(
+                config.substrate.request_timeout_seconds,
+            ))
+            .build(rpc_url)
+            .await?;
+        let genesis_hash: String = ws_client
+            .request("chain_getBlockHash", rpc_params!(0))
+            .await?;
+        let block_hash: String = ws_client.request("chain_getBlockHash", None).await?;
+        let name: String = ws_client.request("system_chain", None).await?;
+        let metadata = {
+            let metadata_response: String = ws_client
+                .request("state_getMetadata", rpc_params!(&block_hash))
+                .await?;
+            Metadata::from(metadata_response.as_str())?
+        };
+        let last_runtime_upgrade_hex_string: String = ws_client
+            .request(
+                "state_getStorage",
+                get_rpc_storage_plain_params("System", "LastRuntimeUpgrade", Some(&block_hash)),
+            )
+            .await?;
+        let last_runtime_upgrade_info =
+            LastRuntimeUpgradeInfo::from_substrate_hex_string(last_runtime_upgrade_hex_string)?;
+        let system_properties: SystemProperties =
+            ws_client.request("system_properties", None).await?;
+        Ok(ChainInfo {
+            name,
+            genesis_hash: format!("0x{}", genesis_hash.trim_start_matches("0x").to_uppercase()),
+            system_properties,
+            constants: metadata.constants,
+            spec_name: last_runtime_upgrade_info.spec_name,
+            spec_version: last_runtime_upgrade_info.spec_version,
+        })
+    }
+
+    /// Fetches the finalized block number of the node at the given URL over a short-lived
+    /// connection. Used to cross-check the configured node against an independent one.
+    pub async fn fetch_finalized_block_number(
+        config: &Config,
+        rpc_url: &str,
+    ) -> anyhow::Result<u64> {
+        let ws_client = WsClientBuilder::default()
+            .connection_timeout(std::time::Duration::from_secs(
+                config.substrate.connection_timeout_seconds,
+            ))
+            .request_timeout(std::time::Duration::from_secs(
+                config.substrate.request_timeout_seconds,
+            ))
+            .build(rpc_url)
+            .await?;
+        let block_hash: String = ws_client.request("chain_getFinalizedHead", None).await?;
+        let header: BlockHeader = ws_client
+            .request("chain_getHeader", rpc_params!(&block_hash))
+            .await?;
+        header.get_number()
+    }
+
+    /// Parameters of the chain from the system properties of the node and the runtime constants.
+    pub fn get_chain_descriptor(&self) -> ChainDescriptor {
+        ChainDescriptor::new(
+            self.chain,
+            &self.system_properties,
+            &self.metadata.constants,
+        )
+    }
+
+    async fn get_metadata_at_block(&self, block_hash: &str) -> anyhow::Result<Metadata> {
         let mut metadata = {
             let metadata_response: String = self
                 .ws_client
@@ -102,10 +191,62 @@ impl SubstrateClient {
         // metadata.log_all_events();
         metadata.check_primitive_argument_support(&self.chain)?;
         metadata.last_runtime_upgrade_info = self.get_last_runtime_upgrade_info(block_hash).await?;
-        self.metadata = metadata;
+        Ok(metadata)
+    }
+
+    pub async fn set_metadata_at_block(&mut self, block_hash: &str) -> anyhow::Result<()> {
+        self.metadata = self.get_metadata_at_block(block_hash).await?;
         Ok(())
     }
 
+    /// Gets a client with the runtime metadata at the given block, sharing the connection of
+    /// this client. The block-hash-parameterized getters of this client (`get_all_validators`,
+    /// `get_era_stakers`, `get_identities` and so on) decode the storage with the current
+    /// metadata, so historical queries, e.g. to backfill the eras before a runtime upgrade,
+    /// should go through the client at the queried block. The state of the old blocks is
+    /// available only on an archive node, the other nodes prune it.
+    pub async fn at_block(&self, block_hash: &str) -> anyhow::Result<SubstrateClient> {
+        let metadata = self
+            .get_metadata_at_block(block_hash)
+            .await
+            .with_context(|| {
+                format!(
+                    "Cannot get metadata at block {}. Historical state requires an archive node.",
+                    block_hash
+                )
+            })?;
+        Ok(SubstrateClient {
+            chain: self.chain,
+            metadata,
+            system_properties: self.system_properties.clone(),
+            ws_client: self.ws_client.clone(),
+        })
+    }
+
+    /// Re-fetches the metadata if the runtime spec version at the given block differs from
+    /// the one of the current metadata, so that the events and extrinsics of the blocks after a
+    /// runtime upgrade get decoded with the new metadata. Returns `true` if it got re-fetched.
+    pub async fn update_metadata_on_runtime_upgrade(
+        &mut self,
+        block_hash: &str,
+    ) -> anyhow::Result<bool> {
+        let runtime_upgrade_info = self.get_last_runtime_upgrade_info(block_hash).await?;
+        if runtime_upgrade_info.spec_version == self.metadata.last_runtime_upgrade_info.spec_version
+        {
+            return Ok(false);
+        }
+        debug!(
+            "Different runtime version #{} than client's #{}. Will reset metadata.",
+            runtime_upgrade_info.spec_version, self.metadata.last_runtime_upgrade_info.spec_version
+        );
+        self.set_metadata_at_block(block_hash).await?;
+        debug!(
+            "Runtime {} metadata fetched.",
+            self.metadata.last_runtime_upgrade_info.spec_version
+        );
+        Ok(true)
+    }
+
     pub async fn get_current_block_hash(&self) -> anyhow::Result<String> {
         let hash = self.ws_client.request("chain_getBlockHash", None).await?;
         Ok(hash)
@@ -147,6 +288,25 @@ impl SubstrateClient {
         Ok(header)
     }
 
+    /// Hashes and headers of the blocks in the inclusive range, in the order of the block numbers.
+    /// Up to `BLOCK_HEADER_QUERY_CONCURRENCY` blocks get fetched ahead on each connection of
+    /// the pool, so that a lagging consumer can catch up with the finalized blocks it has missed
+    /// without waiting for the round trips of each block. The consumer is expected to stop at
+    /// the first error.
+    pub fn get_block_header_stream(
+        &self,
+        start_block_number: u64,
+        end_block_number: u64,
+    ) -> impl Stream<Item = anyhow::Result<(String, BlockHeader)>> + '_ {
+        futures::stream::iter(start_block_number..=end_block_number)
+            .map(move |block_number| async move {
+                let block_hash = self.get_block_hash(block_number).await?;
+                let block_header = self.get_block_header(&block_hash).await?;
+                Ok((block_hash, block_header))
+            })
+            .buffered(BLOCK_HEADER_QUERY_CONCURRENCY * self.ws_client.get_pool_size())
+    }
+
     /// Get the hash of the current finalized block.
     pub async fn get_finalized_block_hash(&self) -> anyhow::Result<String> {
         let hash: String = self
@@ -281,7 +441,8 @@ impl SubstrateClient {
         Ok(None)
     }
 
-    /// Get the ledger for a controller account at the given block.
+    /// Get the ledger, including the unbonding chunks, for a controller account at the given
+    /// block.
     pub async fn get_stake(
         &self,
         controller_account_id: &AccountId,
@@ -317,6 +478,25 @@ impl SubstrateClient {
         }
     }
 
+    /// Get the balances of the given accounts at the given block. Reaped accounts are not
+    /// included in the result.
+    pub async fn get_account_balances(
+        &self,
+        account_ids: &[AccountId],
+        block_hash: &str,
+    ) -> anyhow::Result<HashMap<AccountId, AccountBalance>> {
+        let keys: Vec<String> = account_ids
+            .iter()
+            .map(|account_id| get_storage_map_key(&self.metadata, "System", "Account", account_id))
+            .collect();
+        let mut balance_map: HashMap<AccountId, AccountBalance> = HashMap::new();
+        for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+            let account_id = self.account_id_from_storage_key(&storage_key);
+            balance_map.insert(account_id, AccountBalance::from_bytes(&data)?);
+        }
+        Ok(balance_map)
+    }
+
     /// Get the nomination details for a nominator stash account id at the given block.
     pub async fn get_nomination(
         &self,
@@ -376,20 +556,36 @@ impl SubstrateClient {
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
-        for chunk in keys.chunks(KEY_QUERY_PAGE_SIZE) {
-            let chunk_values: Vec<StorageChangeSet<String>> = self
-                .ws_client
-                .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                .await?;
-            for (storage_key, data) in &chunk_values[0].changes {
-                if let Some(data) = data {
-                    let bytes: [u8; 32] = (&data.0 as &[u8]).try_into()?;
-                    map.insert(
-                        self.account_id_from_storage_key(storage_key),
-                        AccountId::from(bytes),
-                    );
-                }
-            }
+        for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+            let bytes: [u8; 32] = data.as_slice().try_into()?;
+            map.insert(
+                self.account_id_from_storage_key(&storage_key),
+                AccountId::from(bytes),
+            );
+        }
+        Ok(map)
+    }
+
+    /// Gets the reward destinations (`Staking.Payee`) of the given stash account ids at the
+    /// given block. Returned map will not contain an entry for an account that isn't bonded.
+    pub async fn get_reward_destinations(
+        &self,
+        account_ids: &[AccountId],
+        block_hash: &str,
+    ) -> anyhow::Result<HashMap<AccountId, RewardDestination>> {
+        let mut map = HashMap::new();
+        let keys: Vec<String> = account_ids
+            .iter()
+            .map(|account_id| get_storage_map_key(&self.metadata, "Staking", "Payee", account_id))
+            .collect();
+        if keys.is_empty() {
+            return Ok(HashMap::new());
+        }
+        for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+            map.insert(
+                self.account_id_from_storage_key(&storage_key),
+                RewardDestination::from_bytes(&data)?,
+            );
         }
         Ok(map)
     }
@@ -426,29 +622,20 @@ impl SubstrateClient {
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
-        let values: Vec<StorageChangeSet<String>> = self
-            .ws_client
-            .request("state_queryStorageAt", rpc_params!(keys, &block_hash))
-            .await?;
-        trace!(
-            "Got {} optional super accounts records.",
-            values[0].changes.len()
-        );
+        let values = self.get_storage_values(&keys, block_hash).await?;
+        trace!("Got {} super accounts records.", values.len());
         let mut parent_account_map: HashMap<AccountId, (AccountId, Option<String>)> =
             HashMap::new();
-        for (storage_key, storage_data) in values[0].changes.iter() {
-            if let Some(data) = storage_data {
-                let account_id = self.account_id_from_storage_key(storage_key);
-                let mut bytes: &[u8] = &data.0;
-                let super_identity: SuperAccountId = Decode::decode(&mut bytes).unwrap();
-                parent_account_map.insert(
-                    account_id,
-                    (
-                        super_identity.0,
-                        subvt_types::substrate::data_to_string(super_identity.1),
-                    ),
-                );
-            }
+        for (storage_key, data) in values {
+            let account_id = self.account_id_from_storage_key(&storage_key);
+            let super_identity: SuperAccountId = Decode::decode(&mut data.as_slice()).unwrap();
+            parent_account_map.insert(
+                account_id,
+                (
+                    super_identity.0,
+                    subvt_types::substrate::data_to_string(super_identity.1),
+                ),
+            );
         }
         trace!(
             "Got {} super accounts. Get identities for super accounts.",
@@ -473,18 +660,12 @@ impl SubstrateClient {
         if keys.is_empty() {
             return Ok(HashMap::new());
         }
-        let values: Vec<StorageChangeSet<String>> = self
-            .ws_client
-            .request("state_queryStorageAt", rpc_params!(keys, &block_hash))
-            .await?;
-        trace!("Got {} optional identities.", values[0].changes.len());
+        let values = self.get_storage_values(&keys, block_hash).await?;
+        trace!("Got {} identities.", values.len());
         let mut identity_map: HashMap<AccountId, IdentityRegistration> = HashMap::new();
-        for (storage_key, storage_data) in values[0].changes.iter() {
-            let account_id = self.account_id_from_storage_key(storage_key);
-            if let Some(data) = storage_data {
-                let bytes: &[u8] = &data.0;
-                identity_map.insert(account_id, IdentityRegistration::from_bytes(bytes).unwrap());
-            }
+        for (storage_key, data) in values {
+            let account_id = self.account_id_from_storage_key(&storage_key);
+            identity_map.insert(account_id, IdentityRegistration::from_bytes(&data).unwrap());
         }
         Ok(identity_map)
     }
@@ -495,8 +676,10 @@ impl SubstrateClient {
         account_ids: &[AccountId],
         block_hash: &str,
     ) -> anyhow::Result<Vec<Account>> {
-        let identity_map = { self.get_identities(account_ids, block_hash).await? };
-        let parent_account_id_map = { self.get_parent_account_ids(account_ids, block_hash).await? };
+        let (identity_map, parent_account_id_map) = futures::try_join!(
+            self.get_identities(account_ids, block_hash),
+            self.get_parent_account_ids(account_ids, block_hash),
+        )?;
         let parent_account_identity_map = {
             let super_account_ids: Vec<AccountId> = parent_account_id_map
                 .values()
@@ -571,18 +754,141 @@ impl SubstrateClient {
         Ok(all_keys)
     }
 
+    /// Whether the runtime has the nomination pools pallet.
+    pub fn has_nomination_pools(&self) -> bool {
+        self.metadata.module("NominationPools").is_ok()
+    }
+
+    /// Bonded (stash and controller) account ids of all the nomination pools at the given block,
+    /// by pool id. Empty if the runtime doesn't have the nomination pools pallet.
+    pub async fn get_nomination_pool_bonded_account_ids(
+        &self,
+        block_hash: &str,
+    ) -> anyhow::Result<HashMap<PoolId, AccountId>> {
+        if !self.has_nomination_pools() {
+            return Ok(HashMap::new());
+        }
+        let pallet_id: [u8; 8] = self
+            .metadata
+            .module("NominationPools")?
+            .constant("PalletId")?
+            .value()?;
+        let mut bonded_account_id_map = HashMap::new();
+        for key in self
+            .get_all_keys_for_storage("NominationPools", "BondedPools", block_hash)
+            .await?
+        {
+            // Twox64Concat-hashed, the pool id is in the last 4 bytes
+            let pool_id: PoolId = decode_hex_string(&key[(key.len() - 8)..])?;
+            bonded_account_id_map.insert(pool_id, get_pool_bonded_account_id(&pallet_id, pool_id));
+        }
+        Ok(bonded_account_id_map)
+    }
+
+    /// Gets all the nomination pools at the given block, with their active bonded amounts and
+    /// nominations. Empty if the runtime doesn't have the nomination pools pallet.
+    pub async fn get_nomination_pools(
+        &self,
+        block_hash: &str,
+    ) -> anyhow::Result<Vec<NominationPool>> {
+        let bonded_account_id_map = self
+            .get_nomination_pool_bonded_account_ids(block_hash)
+            .await?;
+        if bonded_account_id_map.is_empty() {
+            return Ok(Vec::new());
+        }
+        let pool_keys: Vec<String> = bonded_account_id_map
+            .keys()
+            .map(|pool_id| {
+                get_storage_map_key(&self.metadata, "NominationPools", "BondedPools", pool_id)
+            })
+            .collect();
+        let mut pool_map: HashMap<AccountId, NominationPool> = HashMap::new();
+        for (storage_key, data) in self.get_storage_values(&pool_keys, block_hash).await? {
+            let pool_id_bytes = &storage_key.0[storage_key.0.len() - 4..];
+            let pool_id = PoolId::decode(&mut &pool_id_bytes[..])?;
+            let bonded_account_id = bonded_account_id_map[&pool_id].clone();
+            pool_map.insert(
+                bonded_account_id.clone(),
+                NominationPool::from_bytes(&data, pool_id, bonded_account_id)?,
+            );
+        }
+        // the bonded account is both the stash and the controller
+        let bonded_account_ids: Vec<AccountId> = pool_map.keys().cloned().collect();
+        let ledger_keys: Vec<String> = bonded_account_ids
+            .iter()
+            .map(|account_id| get_storage_map_key(&self.metadata, "Staking", "Ledger", account_id))
+            .collect();
+        for (_, data) in self.get_storage_values(&ledger_keys, block_hash).await? {
+            let stake = Stake::from_bytes(&data)?;
+            if let Some(pool) = pool_map.get_mut(&stake.stash_account_id) {
+                pool.bonded_amount = stake.active_amount;
+            }
+        }
+        let nomination_keys: Vec<String> = bonded_account_ids
+            .iter()
+            .map(|account_id| {
+                get_storage_map_key(&self.metadata, "Staking", "Nominators", account_id)
+            })
+            .collect();
+        for (storage_key, data) in self
+            .get_storage_values(&nomination_keys, block_hash)
+            .await?
+        {
+            let account_id = self.account_id_from_storage_key(&storage_key);
+            let nomination = Nomination::from_bytes(&data, account_id)?;
+            if let Some(pool) = pool_map.get_mut(&nomination.stash_account_id) {
+                pool.nominated_validator_account_ids = nomination.target_account_ids;
+            }
+        }
+        let mut pools: Vec<NominationPool> = pool_map.into_values().collect();
+        pools.sort_by_key(|pool| pool.id);
+        Ok(pools)
+    }
+
+    /// Gets the nomination pool membership of an account at the given block. `None` if the
+    /// account isn't a member of a pool, or if the runtime doesn't have the nomination pools
+    /// pallet.
+    pub async fn get_nomination_pool_member(
+        &self,
+        account_id: &AccountId,
+        block_hash: &str,
+    ) -> anyhow::Result<Option<NominationPoolMember>> {
+        if !self.has_nomination_pools() {
+            return Ok(None);
+        }
+        let storage_key =
+            get_storage_map_key(&self.metadata, "NominationPools", "PoolMembers", account_id);
+        match self
+            .get_storage_values(&[storage_key], block_hash)
+            .await?
+            .pop()
+        {
+            Some((_, data)) => Ok(Some(NominationPoolMember::from_bytes(
+                &data,
+                account_id.clone(),
+            )?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Staking runtime constants, decoded and cached with the runtime metadata, so they're
+    /// up-to-date after a runtime upgrade once the metadata of the new runtime is set.
+    pub fn get_constants(&self) -> &ChainConstants {
+        &self.metadata.chain_constants
+    }
+
     /// Get the complete details of all validators, active and inactive, at the given block.
+    /// Active validators that have chilled in the current era are included too.
     pub async fn get_all_validators(
         &self,
         block_hash: &str,
         era: &Era,
     ) -> anyhow::Result<Vec<ValidatorDetails>> {
         debug!("Getting all validators...");
-        let max_nominator_rewarded_per_validator: u32 = self
-            .metadata
-            .module("Staking")?
-            .constant("MaxNominatorRewardedPerValidator")?
-            .value()?;
+        let constants = self.get_constants();
+        let max_nominator_rewarded_per_validator = constants.max_nominator_rewarded_per_validator;
+        let existential_deposit = constants.existential_deposit;
         let all_keys: Vec<String> = self
             .get_all_keys_for_storage("Staking", "Validators", block_hash)
             .await?;
@@ -617,10 +923,18 @@ impl SubstrateClient {
                 }
                 account_ids
             };
-            let account_ids: Vec<AccountId> = all_keys
+            let mut account_ids: Vec<AccountId> = all_keys
                 .iter()
                 .map(|key| self.account_id_from_storage_key_string(key))
                 .collect();
+            // active validators that have chilled stay active until the end of the era,
+            // although they don't have a validation intent anymore
+            let intent_account_ids: HashSet<AccountId> = account_ids.iter().cloned().collect();
+            for account_id in &active_validator_account_ids {
+                if !intent_account_ids.contains(account_id) {
+                    account_ids.push(account_id.clone());
+                }
+            }
             let accounts = self.get_accounts(&account_ids, block_hash).await?;
             for account in accounts {
                 let is_active = active_validator_account_ids.contains(&account.id);
@@ -629,6 +943,7 @@ impl SubstrateClient {
                     ValidatorDetails {
                         account: account.clone(),
                         is_active,
+                        is_chilling: is_active && !intent_account_ids.contains(&account.id),
                         is_parachain_validator: if is_active {
                             Some(parachain_validator_account_ids.contains(&account.id))
                         } else {
@@ -653,20 +968,11 @@ impl SubstrateClient {
                     )
                 })
                 .collect();
-            for chunk in keys.chunks(KEY_QUERY_PAGE_SIZE) {
-                let chunk_values: Vec<StorageChangeSet<String>> = self
-                    .ws_client
-                    .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                    .await?;
-
-                for (storage_key, data) in chunk_values[0].changes.iter() {
-                    if let Some(data) = data {
-                        let account_id = self.account_id_from_storage_key(storage_key);
-                        let session_keys = format!("0x{}", hex::encode_upper(&data.0));
-                        let validator = validator_map.get_mut(&account_id).unwrap();
-                        validator.next_session_keys = session_keys;
-                    }
-                }
+            for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+                let account_id = self.account_id_from_storage_key(&storage_key);
+                let session_keys = format!("0x{}", hex::encode_upper(&data));
+                let validator = validator_map.get_mut(&account_id).unwrap();
+                validator.next_session_keys = session_keys;
             }
         }
         // get next session active validator keys
@@ -687,6 +993,10 @@ impl SubstrateClient {
                     validator.active_next_session = validator.next_session_keys == session_keys;
                 }
             }
+            for validator in validator_map.values_mut() {
+                validator.is_parked =
+                    validator.is_active && !validator.is_chilling && !validator.active_next_session;
+            }
         }
         // get reward destinations
         {
@@ -697,72 +1007,28 @@ impl SubstrateClient {
                     get_storage_map_key(&self.metadata, "Staking", "Payee", &validator.account.id)
                 })
                 .collect();
-
-            for chunk in keys.chunks(KEY_QUERY_PAGE_SIZE) {
-                let chunk_values: Vec<StorageChangeSet<String>> = self
-                    .ws_client
-                    .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                    .await?;
-
-                for (storage_key, data) in chunk_values[0].changes.iter() {
-                    if let Some(data) = data {
-                        let account_id = self.account_id_from_storage_key(storage_key);
-                        let bytes: &[u8] = &data.0;
-                        let reward_destination = RewardDestination::from_bytes(bytes).unwrap();
-                        let validator = validator_map.get_mut(&account_id).unwrap();
-                        validator.reward_destination = reward_destination;
-                    }
-                }
+            for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+                let account_id = self.account_id_from_storage_key(&storage_key);
+                let reward_destination = RewardDestination::from_bytes(&data).unwrap();
+                let validator = validator_map.get_mut(&account_id).unwrap();
+                validator.reward_destination = reward_destination;
             }
         }
         // get nominations
         {
             debug!("Get all nominations.");
-            let mut all_keys: Vec<String> = Vec::new();
-            loop {
-                let last = all_keys.last();
-                let mut keys: Vec<String> = self
-                    .ws_client
-                    .request(
-                        "state_getKeysPaged",
-                        get_rpc_paged_keys_params(
-                            "Staking",
-                            "Nominators",
-                            KEY_QUERY_PAGE_SIZE,
-                            if let Some(last) = last {
-                                Some(last.as_str())
-                            } else {
-                                None
-                            },
-                            Some(block_hash),
-                        ),
-                    )
-                    .await?;
-                let keys_length = keys.len();
-                all_keys.append(&mut keys);
-                if keys_length < KEY_QUERY_PAGE_SIZE {
-                    break;
-                }
-            }
-
+            let all_keys = self
+                .get_all_keys_for_storage("Staking", "Nominators", block_hash)
+                .await?;
             debug!(
                 "Got {} nomination storage keys. Accessing storage.",
                 all_keys.len()
             );
             let mut nomination_map: HashMap<AccountId, Nomination> = HashMap::new();
-            for chunk in all_keys.chunks(KEY_QUERY_PAGE_SIZE) {
-                let chunk_values: Vec<StorageChangeSet<String>> = self
-                    .ws_client
-                    .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                    .await?;
-                for (storage_key, data) in chunk_values[0].changes.iter() {
-                    if let Some(data) = data {
-                        let account_id = self.account_id_from_storage_key(storage_key);
-                        let bytes: &[u8] = &data.0;
-                        let nomination = Nomination::from_bytes(bytes, account_id).unwrap();
-                        nomination_map.insert(nomination.stash_account_id.clone(), nomination);
-                    }
-                }
+            for (storage_key, data) in self.get_storage_values(&all_keys, block_hash).await? {
+                let account_id = self.account_id_from_storage_key(&storage_key);
+                let nomination = Nomination::from_bytes(&data, account_id).unwrap();
+                nomination_map.insert(nomination.stash_account_id.clone(), nomination);
             }
             debug!("Got {} nominations.", nomination_map.len());
 
@@ -782,21 +1048,32 @@ impl SubstrateClient {
                 ));
             }
             let mut controller_account_id_map: HashMap<AccountId, AccountId> = HashMap::new();
-            for chunk in controller_storage_keys.chunks(KEY_QUERY_PAGE_SIZE) {
-                let chunk_values: Vec<StorageChangeSet<String>> = self
-                    .ws_client
-                    .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                    .await?;
-                for (storage_key, data) in chunk_values[0].changes.iter() {
-                    if let Some(data) = data {
-                        let account_id = self.account_id_from_storage_key(storage_key);
-                        let mut bytes: &[u8] = &data.0;
-                        let controller_account_id: AccountId = Decode::decode(&mut bytes).unwrap();
-                        if let Some(validator) = validator_map.get_mut(&account_id) {
-                            validator.controller_account_id = controller_account_id.clone();
-                        }
-                        controller_account_id_map.insert(account_id, controller_account_id);
-                    }
+            for (storage_key, data) in self
+                .get_storage_values(&controller_storage_keys, block_hash)
+                .await?
+            {
+                let account_id = self.account_id_from_storage_key(&storage_key);
+                let controller_account_id: AccountId =
+                    Decode::decode(&mut data.as_slice()).unwrap();
+                if let Some(validator) = validator_map.get_mut(&account_id) {
+                    validator.controller_account_id = controller_account_id.clone();
+                }
+                controller_account_id_map.insert(account_id, controller_account_id);
+            }
+            debug!("Get validator controller account balances.");
+            let validator_controller_account_ids: Vec<AccountId> = validator_map
+                .values()
+                .map(|validator| validator.controller_account_id.clone())
+                .collect();
+            let controller_balance_map = self
+                .get_account_balances(&validator_controller_account_ids, block_hash)
+                .await?;
+            for validator in validator_map.values_mut() {
+                if let Some(balance) = controller_balance_map.get(&validator.controller_account_id)
+                {
+                    validator.controller_free_balance = balance.free;
+                    validator.controller_fee_spendable_balance =
+                        balance.get_fee_spendable(existential_deposit);
                 }
             }
             debug!("Get nomination amounts and self stakes.");
@@ -808,32 +1085,49 @@ impl SubstrateClient {
                     get_storage_map_key(&self.metadata, "Staking", "Ledger", &controller_account_id)
                 })
                 .collect();
-            for chunk in ledger_storage_keys.chunks(KEY_QUERY_PAGE_SIZE) {
-                let chunk_values: Vec<StorageChangeSet<String>> = self
-                    .ws_client
-                    .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                    .await?;
-                for (_, data) in chunk_values[0].changes.iter() {
-                    if let Some(data) = data {
-                        let bytes: &[u8] = &data.0;
-                        let stake: Stake = Stake::from_bytes(bytes).unwrap();
-                        let account_id = &stake.stash_account_id;
-                        if let Some(nomination) = nomination_map.get_mut(account_id) {
-                            nomination.stake = stake;
-                        } else {
-                            let validator = validator_map.get_mut(account_id).unwrap();
-                            validator.self_stake = stake;
-                        }
-                    }
+            for (_, data) in self
+                .get_storage_values(&ledger_storage_keys, block_hash)
+                .await?
+            {
+                let mut stake: Stake = Stake::from_bytes(&data).unwrap();
+                stake.estimate_unlock_timestamps(era, self.metadata.constants.era_duration_millis);
+                let account_id = stake.stash_account_id.clone();
+                if let Some(nomination) = nomination_map.get_mut(&account_id) {
+                    nomination.stake = stake;
+                } else {
+                    let validator = validator_map.get_mut(&account_id).unwrap();
+                    validator.self_stake = stake;
                 }
             }
             for nomination in nomination_map.values() {
                 for account_id in nomination.target_account_ids.iter() {
                     if let Some(validator) = validator_map.get_mut(account_id) {
                         validator.nominations.push(nomination.clone());
-                        validator.oversubscribed = validator.nominations.len()
-                            > max_nominator_rewarded_per_validator as usize;
+                        // all nominators get rewarded in the runtimes with paged exposures
+                        if let Some(max_nominator_rewarded_per_validator) =
+                            max_nominator_rewarded_per_validator
+                        {
+                            validator.oversubscribed = validator.nominations.len()
+                                > max_nominator_rewarded_per_validator as usize;
+                        }
+                    }
+                }
+            }
+            if self.has_nomination_pools() {
+                let pool_account_ids: HashSet<AccountId> = self
+                    .get_nomination_pool_bonded_account_ids(block_hash)
+                    .await?
+                    .into_values()
+                    .collect();
+                for validator in validator_map.values_mut() {
+                    let mut pool_nominations = PoolNominationsSummary::default();
+                    for nomination in validator.nominations.iter().filter(|nomination| {
+                        pool_account_ids.contains(&nomination.stash_account_id)
+                    }) {
+                        pool_nominations.nomination_count += 1;
+                        pool_nominations.total_amount += nomination.stake.active_amount;
                     }
+                    validator.pool_nominations = Some(pool_nominations);
                 }
             }
             for validator in validator_map.values_mut() {
@@ -848,17 +1142,26 @@ impl SubstrateClient {
         // get validator prefs
         {
             debug!("Get validator preferences.");
-            let values: Vec<StorageChangeSet<String>> = self
-                .ws_client
-                .request("state_queryStorageAt", rpc_params!(all_keys, &block_hash))
-                .await?;
-            for (storage_key, data) in values[0].changes.iter() {
-                if let Some(data) = data {
-                    let bytes: &[u8] = &data.0;
-                    let preferences = ValidatorPreferences::from_bytes(bytes).unwrap();
-                    let validator_account_id = self.account_id_from_storage_key(storage_key);
-                    let validator = validator_map.get_mut(&validator_account_id).unwrap();
-                    validator.preferences = preferences;
+            for (storage_key, data) in self.get_storage_values(&all_keys, block_hash).await? {
+                let preferences = ValidatorPreferences::from_bytes(&data).unwrap();
+                let validator_account_id = self.account_id_from_storage_key(&storage_key);
+                let validator = validator_map.get_mut(&validator_account_id).unwrap();
+                validator.preferences = preferences;
+            }
+            // chilled validators don't have preferences anymore, use the ones of the era
+            if validator_map
+                .values()
+                .any(|validator| validator.is_chilling)
+            {
+                let era_validator_prefs =
+                    self.get_era_validator_prefs(era.index, block_hash).await?;
+                for validator in validator_map.values_mut() {
+                    if !validator.is_chilling {
+                        continue;
+                    }
+                    if let Some(preferences) = era_validator_prefs.get(&validator.account.id) {
+                        validator.preferences = preferences.clone();
+                    }
                 }
             }
         }
@@ -900,6 +1203,29 @@ impl SubstrateClient {
             .collect())
     }
 
+    /// Gets the minimum bonds to nominate and to validate (`Staking.MinNominatorBond` and
+    /// `Staking.MinValidatorBond`) at the given block. `None` if not set.
+    pub async fn get_minimum_bonds(
+        &self,
+        block_hash: &str,
+    ) -> anyhow::Result<(Option<Balance>, Option<Balance>)> {
+        let mut minimum_bonds = Vec::new();
+        for storage_name in ["MinNominatorBond", "MinValidatorBond"] {
+            let maybe_hex_string: Option<String> = self
+                .ws_client
+                .request(
+                    "state_getStorage",
+                    get_rpc_storage_plain_params("Staking", storage_name, Some(block_hash)),
+                )
+                .await?;
+            minimum_bonds.push(match maybe_hex_string {
+                Some(hex_string) => Some(decode_hex_string::<Balance>(&hex_string)?),
+                None => None,
+            });
+        }
+        Ok((minimum_bonds[0], minimum_bonds[1]))
+    }
+
     /// Get the number of all validation intents at the given block.
     pub async fn get_total_validator_count(&self, block_hash: &str) -> anyhow::Result<u32> {
         let hex_string: String = self
@@ -946,13 +1272,49 @@ impl SubstrateClient {
         decode_hex_string(hex_string.as_str())
     }
 
-    /// Get all the active stakes for the given era.
-    pub async fn get_era_stakers(
+    /// Get the deferred slashes to be applied at the start of the given era. The slashes get
+    /// removed from the storage once applied, so the block should be before the era start.
+    pub async fn get_era_unapplied_slashes(
         &self,
-        era: &Era,
-        clipped: bool,
+        era_index: u32,
         block_hash: &str,
-    ) -> anyhow::Result<EraStakers> {
+    ) -> anyhow::Result<EraUnappliedSlashes> {
+        let params = get_rpc_storage_map_params(
+            &self.metadata,
+            "Staking",
+            "UnappliedSlashes",
+            &era_index,
+            Some(block_hash),
+        );
+        let maybe_hex_string: Option<String> =
+            self.ws_client.request("state_getStorage", params).await?;
+        if let Some(hex_string) = maybe_hex_string {
+            let bytes: &[u8] = &hex::decode(hex_string.trim_start_matches("0x"))?;
+            EraUnappliedSlashes::from_bytes(bytes)
+        } else {
+            Ok(EraUnappliedSlashes::default())
+        }
+    }
+
+    /// Get all the keys of the era-prefixed `Staking` map storage for the given era.
+    async fn get_era_storage_keys(
+        &self,
+        storage_name: &str,
+        era_index: u32,
+        block_hash: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        self.get_prefixed_storage_keys("Staking", storage_name, &era_index, block_hash)
+            .await
+    }
+
+    /// Get all the keys of the double map storage with the given first key.
+    async fn get_prefixed_storage_keys<T: Encode>(
+        &self,
+        module_name: &str,
+        storage_name: &str,
+        first_key: &T,
+        block_hash: &str,
+    ) -> anyhow::Result<Vec<String>> {
         let mut all_keys: Vec<String> = Vec::new();
         loop {
             let last = all_keys.last();
@@ -962,13 +1324,9 @@ impl SubstrateClient {
                     "state_getKeysPaged",
                     get_rpc_paged_map_keys_params(
                         &self.metadata,
-                        "Staking",
-                        if clipped {
-                            "ErasStakersClipped"
-                        } else {
-                            "ErasStakers"
-                        },
-                        &era.index,
+                        module_name,
+                        storage_name,
+                        first_key,
                         KEY_QUERY_PAGE_SIZE,
                         if let Some(last) = last {
                             Some(last.as_str())
@@ -985,21 +1343,107 @@ impl SubstrateClient {
                 break;
             }
         }
+        Ok(all_keys)
+    }
 
-        let mut stakers: Vec<ValidatorStake> = Vec::new();
-        for chunk in all_keys.chunks(KEY_QUERY_PAGE_SIZE) {
-            let chunk_values: Vec<StorageChangeSet<String>> = self
-                .ws_client
-                .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
+    /// Get the existing values of the given storage keys. The keys get queried in chunks of
+    /// `KEY_QUERY_PAGE_SIZE`, with up to `STORAGE_QUERY_CONCURRENCY` chunk queries in flight
+    /// on each connection of the pool.
+    async fn get_storage_values(
+        &self,
+        keys: &[String],
+        block_hash: &str,
+    ) -> anyhow::Result<Vec<(StorageKey, Vec<u8>)>> {
+        let chunk_values_list: Vec<Vec<StorageChangeSet<String>>> =
+            futures::stream::iter(keys.chunks(KEY_QUERY_PAGE_SIZE))
+                .map(|chunk| {
+                    self.ws_client.request::<Vec<StorageChangeSet<String>>>(
+                        "state_queryStorageAt",
+                        rpc_params!(chunk, block_hash),
+                    )
+                })
+                .buffered(STORAGE_QUERY_CONCURRENCY * self.ws_client.get_pool_size())
+                .try_collect()
                 .await?;
+        Ok(chunk_values_list
+            .into_iter()
+            .flatten()
+            .flat_map(|change_set| change_set.changes)
+            .filter_map(|(storage_key, maybe_data)| maybe_data.map(|data| (storage_key, data.0)))
+            .collect())
+    }
 
-            for (storage_key, data) in chunk_values[0].changes.iter() {
-                if let Some(data) = data {
-                    let validator_account_id = self.account_id_from_storage_key(storage_key);
-                    let nomination =
-                        ValidatorStake::from_bytes(&data.0, validator_account_id).unwrap();
-                    stakers.push(nomination);
-                }
+    /// Get all the active stakes for the given era from the paged exposure storage of the newer
+    /// runtimes. Empty for the eras before the paged exposure migration.
+    async fn get_paged_era_stakers(
+        &self,
+        era_index: u32,
+        block_hash: &str,
+    ) -> anyhow::Result<Vec<ValidatorStake>> {
+        let overview_keys = self
+            .get_era_storage_keys("ErasStakersOverview", era_index, block_hash)
+            .await?;
+        let page_keys = self
+            .get_era_storage_keys("ErasStakersPaged", era_index, block_hash)
+            .await?;
+        // validator account id -> page bytes
+        let mut validator_pages: HashMap<AccountId, Vec<Vec<u8>>> = HashMap::new();
+        for (storage_key, data) in self.get_storage_values(&page_keys, block_hash).await? {
+            // key ends with the account id, then the Twox64Concat hash of the u32 page index
+            let validator_account_id: AccountId = storage_key.0
+                [storage_key.0.len() - 44..storage_key.0.len() - 12]
+                .try_into()
+                .unwrap();
+            validator_pages
+                .entry(validator_account_id)
+                .or_default()
+                .push(data);
+        }
+        let mut stakers: Vec<ValidatorStake> = Vec::new();
+        for (storage_key, data) in self.get_storage_values(&overview_keys, block_hash).await? {
+            let validator_account_id = self.account_id_from_storage_key(&storage_key);
+            let page_bytes_list: Vec<&[u8]> = validator_pages
+                .get(&validator_account_id)
+                .map(|pages| pages.iter().map(|page| page.as_slice()).collect())
+                .unwrap_or_default();
+            stakers.push(ValidatorStake::from_paged_bytes(
+                &data,
+                &page_bytes_list,
+                validator_account_id,
+            )?);
+        }
+        Ok(stakers)
+    }
+
+    /// Get all the active stakes for the given era. Reads the paged exposure storage if the
+    /// runtime has it, and falls back to the legacy storage for the eras before the paged
+    /// exposure migration. Paged exposures are never clipped, so `clipped` only applies
+    /// to the legacy storage.
+    pub async fn get_era_stakers(
+        &self,
+        era: &Era,
+        clipped: bool,
+        block_hash: &str,
+    ) -> anyhow::Result<EraStakers> {
+        let mut stakers: Vec<ValidatorStake> = Vec::new();
+        if self.metadata.is_era_stakers_paged(&self.chain) {
+            stakers = self.get_paged_era_stakers(era.index, block_hash).await?;
+        }
+        if stakers.is_empty() {
+            let keys = self
+                .get_era_storage_keys(
+                    if clipped {
+                        "ErasStakersClipped"
+                    } else {
+                        "ErasStakers"
+                    },
+                    era.index,
+                    block_hash,
+                )
+                .await?;
+            for (storage_key, data) in self.get_storage_values(&keys, block_hash).await? {
+                let validator_account_id = self.account_id_from_storage_key(&storage_key);
+                stakers.push(ValidatorStake::from_bytes(&data, validator_account_id)?);
             }
         }
         stakers.sort_by_key(|validator_stake| validator_stake.total_stake);
@@ -1010,7 +1454,8 @@ impl SubstrateClient {
     }
 
     /// Get total and individual era reward points earned by validators at the given era.
-    /// Will give the points earned so far for an active era.
+    /// Will give the points earned so far for an active era, and no points if the era has not
+    /// earned any points yet at the given block.
     pub async fn get_era_reward_points(
         &self,
         era_index: u32,
@@ -1023,8 +1468,45 @@ impl SubstrateClient {
             &era_index,
             Some(block_hash),
         );
-        let hex_string: String = self.ws_client.request("state_getStorage", params).await?;
-        Ok(decode_hex_string(hex_string.as_str())?)
+        let maybe_hex_string: Option<String> =
+            self.ws_client.request("state_getStorage", params).await?;
+        match maybe_hex_string {
+            Some(hex_string) => Ok(decode_hex_string(hex_string.as_str())?),
+            None => Ok(EraRewardPoints::default()),
+        }
+    }
+
+    /// Get the era reward points earned by each validator at the given era between the two
+    /// given blocks, i.e. the difference of the individual points at the end block and the
+    /// start block. Validators that have no points at the end block are not included.
+    pub async fn get_era_reward_points_delta(
+        &self,
+        era_index: u32,
+        start_block_hash: &str,
+        end_block_hash: &str,
+    ) -> anyhow::Result<HashMap<AccountId, u32>> {
+        let start_reward_points = self
+            .get_era_reward_points(era_index, start_block_hash)
+            .await?;
+        let end_reward_points = self
+            .get_era_reward_points(era_index, end_block_hash)
+            .await?;
+        Ok(end_reward_points
+            .individual
+            .iter()
+            .map(|(account_id_32, reward_points)| {
+                let start_points = start_reward_points
+                    .individual
+                    .get(account_id_32)
+                    .cloned()
+                    .unwrap_or(0);
+                let account_id_bytes: &[u8; 32] = account_id_32.as_ref();
+                (
+                    AccountId::new(*account_id_bytes),
+                    reward_points.saturating_sub(start_points),
+                )
+            })
+            .collect())
     }
 
     /// Get the session index at the given block.
@@ -1039,6 +1521,47 @@ impl SubstrateClient {
         decode_hex_string(hex_string.as_str())
     }
 
+    /// Get the `ImOnline` liveness of the session validators in the current session at the
+    /// given block, i.e. whether each validator has sent a heartbeat (`ReceivedHeartbeats`) or
+    /// authored a block (`AuthoredBlocks`) in the session. Heartbeats are keyed by the index of
+    /// the validator in the session validators.
+    pub async fn get_session_heartbeats(
+        &self,
+        block_hash: &str,
+    ) -> anyhow::Result<HashMap<AccountId, bool>> {
+        let session_index = self.get_current_session_index(block_hash).await?;
+        let session_validator_account_ids =
+            self.get_active_validator_account_ids(block_hash).await?;
+        let mut heartbeats: HashMap<AccountId, bool> = session_validator_account_ids
+            .iter()
+            .map(|account_id| (account_id.clone(), false))
+            .collect();
+        // the values are the network states of the validators, only the keys are needed
+        for storage_key in self
+            .get_prefixed_storage_keys("ImOnline", "ReceivedHeartbeats", &session_index, block_hash)
+            .await?
+        {
+            // authority index is the last key, `Twox64Concat` hashed
+            let authority_index: u32 = decode_hex_string(&storage_key[(storage_key.len() - 8)..])?;
+            if let Some(account_id) = session_validator_account_ids.get(authority_index as usize) {
+                heartbeats.insert(account_id.clone(), true);
+            }
+        }
+        let authored_block_keys = self
+            .get_prefixed_storage_keys("ImOnline", "AuthoredBlocks", &session_index, block_hash)
+            .await?;
+        for (storage_key, data) in self
+            .get_storage_values(&authored_block_keys, block_hash)
+            .await?
+        {
+            let block_count: u32 = Decode::decode(&mut data.as_slice())?;
+            if block_count > 0 {
+                heartbeats.insert(self.account_id_from_storage_key(&storage_key), true);
+            }
+        }
+        Ok(heartbeats)
+    }
+
     /// Get the complete events in the given block.
     pub async fn get_block_events(&self, block_hash: &str) -> anyhow::Result<Vec<SubstrateEvent>> {
         let block = self.get_block(block_hash).await?;
@@ -1101,6 +1624,55 @@ impl SubstrateClient {
         Ok(decode_hex_string(&account_id_hex_string)?)
     }
 
+    /// Checks the given 0x-prefixed hex-encoded session keys of a validator against its next
+    /// session keys on chain at the given block, and against the keystore of the connected node
+    /// with `author_hasSessionKeys`. The keystore check is only meaningful when connected to the
+    /// validator's own node, and requires the node to allow the unsafe RPC methods.
+    pub async fn validate_session_keys(
+        &self,
+        account_id: &AccountId,
+        session_keys_hex_string: &str,
+        block_hash: &str,
+    ) -> anyhow::Result<SessionKeysValidity> {
+        let session_keys = hex::decode(session_keys_hex_string.trim_start_matches("0x"))?;
+        let params = get_rpc_storage_map_params(
+            &self.metadata,
+            "Session",
+            "NextKeys",
+            account_id,
+            Some(block_hash),
+        );
+        let maybe_next_keys_hex_string: Option<String> =
+            self.ws_client.request("state_getStorage", params).await?;
+        let is_registered = match maybe_next_keys_hex_string {
+            Some(next_keys_hex_string) => {
+                hex::decode(next_keys_hex_string.trim_start_matches("0x"))? == session_keys
+            }
+            None => false,
+        };
+        let is_present_on_node = match self
+            .ws_client
+            .request::<bool>(
+                "author_hasSessionKeys",
+                rpc_params!(format!("0x{}", hex::encode(&session_keys))),
+            )
+            .await
+        {
+            Ok(has_session_keys) => Some(has_session_keys),
+            Err(error) => {
+                debug!(
+                    "Cannot check session keys in the node keystore: {:?}",
+                    error
+                );
+                None
+            }
+        };
+        Ok(SessionKeysValidity {
+            is_registered,
+            is_present_on_node,
+        })
+    }
+
     /// Get the indices of the paravalidators at the given block.
     pub async fn get_parachain_active_validator_indices(
         &self,
@@ -1113,72 +1685,122 @@ impl SubstrateClient {
         Ok(decode_hex_string(&indices_vector_hex_string)?)
     }
 
+    /// Get the democracy referendum information at the given block.
+    /// Returns `None` if the referendum doesn't exist.
+    pub async fn get_democracy_referendum_info(
+        &self,
+        referendum_index: u32,
+        block_hash: &str,
+    ) -> anyhow::Result<Option<DemocracyReferendumInfo>> {
+        let params = get_rpc_storage_map_params(
+            &self.metadata,
+            "Democracy",
+            "ReferendumInfoOf",
+            &referendum_index,
+            Some(block_hash),
+        );
+        let maybe_hex_string: Option<String> =
+            self.ws_client.request("state_getStorage", params).await?;
+        if let Some(hex_string) = maybe_hex_string {
+            let bytes: &[u8] = &hex::decode(hex_string.trim_start_matches("0x"))?;
+            Ok(Some(DemocracyReferendumInfo::from_bytes(bytes)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Get the election snapshot (voters and targets) at the given block.
+    /// Returns `None` if there is no ongoing election at the block.
+    pub async fn get_election_snapshot(
+        &self,
+        block_hash: &str,
+    ) -> anyhow::Result<Option<ElectionSnapshot>> {
+        let maybe_hex_string: Option<String> = self
+            .ws_client
+            .request(
+                "state_getStorage",
+                get_rpc_storage_plain_params(
+                    "ElectionProviderMultiPhase",
+                    "Snapshot",
+                    Some(block_hash),
+                ),
+            )
+            .await?;
+        if let Some(hex_string) = maybe_hex_string {
+            let bytes: &[u8] = &hex::decode(hex_string.trim_start_matches("0x"))?;
+            Ok(Some(ElectionSnapshot::from_bytes(bytes)?))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Validator preferences map at a given block.
     pub async fn get_era_validator_prefs(
         &self,
         era_index: u32,
         block_hash: &str,
     ) -> anyhow::Result<HashMap<AccountId, ValidatorPreferences>> {
-        let mut all_keys: Vec<String> = Vec::new();
-        loop {
-            let last = all_keys.last();
-            let mut keys: Vec<String> = self
-                .ws_client
-                .request(
-                    "state_getKeysPaged",
-                    get_rpc_paged_map_keys_params(
-                        &self.metadata,
-                        "Staking",
-                        "ErasValidatorPrefs",
-                        &era_index,
-                        KEY_QUERY_PAGE_SIZE,
-                        if let Some(last) = last {
-                            Some(last.as_str())
-                        } else {
-                            None
-                        },
-                        Some(block_hash),
-                    ),
-                )
-                .await?;
-            let keys_length = keys.len();
-            all_keys.append(&mut keys);
-            if keys_length < KEY_QUERY_PAGE_SIZE {
-                break;
-            }
-        }
+        let all_keys = self
+            .get_era_storage_keys("ErasValidatorPrefs", era_index, block_hash)
+            .await?;
         let mut validator_prefs_map: HashMap<AccountId, ValidatorPreferences> = HashMap::new();
-        for chunk in all_keys.chunks(KEY_QUERY_PAGE_SIZE) {
-            let chunk_values: Vec<StorageChangeSet<String>> = self
-                .ws_client
-                .request("state_queryStorageAt", rpc_params!(chunk, &block_hash))
-                .await?;
-
-            for (storage_key, data) in chunk_values[0].changes.iter() {
-                if let Some(data) = data {
-                    let validator_account_id = self.account_id_from_storage_key(storage_key);
-                    let bytes: &[u8] = &data.0;
-                    let mut bytes_clone: &[u8] = &data.0.clone();
-                    let validator_prefs = match ValidatorPreferences::from_bytes(bytes) {
-                        Ok(validator_preferences) => validator_preferences,
-                        Err(_) => {
-                            let legacy_validator_prefs: LegacyValidatorPrefs =
-                                Decode::decode(&mut bytes_clone)?;
-                            ValidatorPreferences {
-                                commission_per_billion: legacy_validator_prefs
-                                    .commission
-                                    .deconstruct(),
-                                blocks_nominations: false,
-                            }
-                        }
-                    };
-                    validator_prefs_map.insert(validator_account_id, validator_prefs);
+        for (storage_key, data) in self.get_storage_values(&all_keys, block_hash).await? {
+            let validator_account_id = self.account_id_from_storage_key(&storage_key);
+            let validator_prefs = match ValidatorPreferences::from_bytes(&data) {
+                Ok(validator_preferences) => validator_preferences,
+                Err(_) => {
+                    let legacy_validator_prefs: LegacyValidatorPrefs =
+                        Decode::decode(&mut data.as_slice())?;
+                    ValidatorPreferences {
+                        commission_per_billion: legacy_validator_prefs.commission.deconstruct(),
+                        blocks_nominations: false,
+                    }
                 }
-            }
+            };
+            validator_prefs_map.insert(validator_account_id, validator_prefs);
         }
         Ok(validator_prefs_map)
     }
 
+    /// Get the number of past eras for which the staking pallet keeps the era data, i.e.
+    /// the exposures, the preferences and the rewards, at the given block.
+    pub async fn get_history_depth(&self, block_hash: &str) -> anyhow::Result<u32> {
+        let maybe_hex_string: Option<String> = self
+            .ws_client
+            .request(
+                "state_getStorage",
+                get_rpc_storage_plain_params("Staking", "HistoryDepth", Some(block_hash)),
+            )
+            .await?;
+        match maybe_hex_string {
+            Some(hex_string) => decode_hex_string(&hex_string),
+            None => Ok(DEFAULT_HISTORY_DEPTH),
+        }
+    }
+
+    /// Validator preferences of the past eras that are still in the history at the given block,
+    /// by era index. Eras outside the history depth of the active era get skipped, since their
+    /// `ErasValidatorPrefs` storage has already been cleared.
+    pub async fn get_past_era_validator_prefs(
+        &self,
+        era_indices: &[u32],
+        block_hash: &str,
+    ) -> anyhow::Result<BTreeMap<u32, HashMap<AccountId, ValidatorPreferences>>> {
+        let active_era_index = self.get_active_era(block_hash).await?.index;
+        let history_depth = self.get_history_depth(block_hash).await?;
+        let mut era_validator_prefs = BTreeMap::new();
+        for era_index in era_indices {
+            if *era_index > active_era_index || active_era_index - era_index > history_depth {
+                continue;
+            }
+            era_validator_prefs.insert(
+                *era_index,
+                self.get_era_validator_prefs(*era_index, block_hash).await?,
+            );
+        }
+        Ok(era_validator_prefs)
+    }
+
     async fn subscribe_to_blocks<F>(
         &self,
         subscribe_method_name: &str,
@@ -1188,27 +1810,56 @@ impl SubstrateClient {
     where
         F: Fn(BlockHeader),
     {
-        let mut subscription: Subscription<BlockHeader> = self
-            .ws_client
-            .subscribe(subscribe_method_name, None, unsubscribe_method_name)
-            .await?;
         loop {
-            let maybe_block_header_result = subscription.next().await;
-            match maybe_block_header_result {
-                Some(block_header_result) => match block_header_result {
+            let mut subscription: Subscription<BlockHeader> = self
+                .ws_client
+                .subscribe(subscribe_method_name, None, unsubscribe_method_name)
+                .await?;
+            while let Some(block_header_result) = subscription.next().await {
+                match block_header_result {
                     Ok(block_header) => callback(block_header),
                     Err(error) => {
                         error!("Error while getting block header: {:?}", error);
-                        error!("Will exit new block subscription.");
                     }
-                },
-                None => {
-                    error!("Empty block header. Will exit new block subscription.");
-                    break;
                 }
             }
+            // the subscription ends when the connection is lost, resubscribe after failover
+            warn!("Block subscription ended. Resubscribe.");
+            self.ws_client.fail_over().await?;
+        }
+    }
+
+    /// Waits until the node reports a runtime spec version other than the one of the client's
+    /// metadata, and returns the new runtime version. The metadata of the client doesn't get
+    /// updated, the services that hold the client in an `Arc` reconnect with a new client.
+    pub async fn wait_for_runtime_upgrade(&self) -> anyhow::Result<RuntimeVersion> {
+        loop {
+            let mut subscription: Subscription<RuntimeVersion> = self
+                .ws_client
+                .subscribe(
+                    "state_subscribeRuntimeVersion",
+                    None,
+                    "state_unsubscribeRuntimeVersion",
+                )
+                .await?;
+            // the first notification is the current runtime version
+            while let Some(runtime_version_result) = subscription.next().await {
+                match runtime_version_result {
+                    Ok(runtime_version) => {
+                        if runtime_version.spec_version
+                            != self.metadata.last_runtime_upgrade_info.spec_version
+                        {
+                            return Ok(runtime_version);
+                        }
+                    }
+                    Err(error) => {
+                        error!("Error while getting runtime version: {:?}", error);
+                    }
+                }
+            }
+            warn!("Runtime version subscription ended. Resubscribe.");
+            self.ws_client.fail_over().await?;
         }
-        Ok(())
     }
 
     /// Subscribes to new blocks.
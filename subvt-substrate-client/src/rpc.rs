@@ -0,0 +1,284 @@
+//! WebSocket RPC connection with endpoint failover. The endpoints (`substrate.rpc_url`, then
+//! `substrate.fallback_rpc_urls`) are tried in order, and an endpoint gets used only if its node
+//! is healthy, i.e. not syncing and connected to peers. When the connection is lost during a
+//! request or a subscription, the client connects to the next healthy endpoint and retries, so
+//! that the services keep running when a node drops.
+//!
+//! A request that fails with a retryable error, i.e. a lost connection, a timeout or a full
+//! request queue, gets retried up to `substrate.request_retry_count` times with exponential
+//! backoff, starting at `substrate.request_retry_initial_delay_millis` and capped at
+//! `substrate.request_retry_max_delay_millis`. Errors returned by the node itself, such as an
+//! invalid call or an undecodable response, are fatal and returned right away, since a retry
+//! would fail the same way.
+//!
+//! The client keeps a pool of `substrate.connection_pool_size` connections and dispatches the
+//! requests and subscriptions to them in turns, so that the concurrent requests don't queue up
+//! on a single connection. Each connection of the pool fails over on its own.
+use async_lock::RwLock;
+use jsonrpsee::{
+    core::client::{Client, ClientT, Subscription, SubscriptionClientT},
+    ws_client::WsClientBuilder,
+};
+use jsonrpsee_core::Error;
+use jsonrpsee_types::params::ParamsSer;
+use log::{debug, warn};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use subvt_config::Config;
+
+/// Response of the `system_health` RPC method.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct NodeHealth {
+    peers: u64,
+    is_syncing: bool,
+    should_have_peers: bool,
+}
+
+impl NodeHealth {
+    fn is_healthy(&self) -> bool {
+        !self.is_syncing && (self.peers > 0 || !self.should_have_peers)
+    }
+}
+
+struct Connection {
+    endpoint_index: usize,
+    client: Arc<Client>,
+}
+
+struct Endpoints {
+    rpc_urls: Vec<String>,
+    connection_timeout: Duration,
+    request_timeout: Duration,
+}
+
+impl Endpoints {
+    async fn connect(&self, rpc_url: &str) -> anyhow::Result<Client> {
+        let client = WsClientBuilder::default()
+            .connection_timeout(self.connection_timeout)
+            .request_timeout(self.request_timeout)
+            .build(rpc_url)
+            .await?;
+        let health: NodeHealth = client.request("system_health", None).await?;
+        if !health.is_healthy() {
+            anyhow::bail!(
+                "Node is not healthy. Syncing: {}, peers: {}.",
+                health.is_syncing,
+                health.peers
+            );
+        }
+        Ok(client)
+    }
+
+    /// Connects to the first healthy endpoint, starting with the endpoint at the given index.
+    async fn connect_to_healthy_endpoint(&self, start_index: usize) -> anyhow::Result<Connection> {
+        for offset in 0..self.rpc_urls.len() {
+            let endpoint_index = (start_index + offset) % self.rpc_urls.len();
+            let rpc_url = &self.rpc_urls[endpoint_index];
+            match self.connect(rpc_url).await {
+                Ok(client) => {
+                    debug!("Connected to RPC endpoint {}.", rpc_url);
+                    return Ok(Connection {
+                        endpoint_index,
+                        client: Arc::new(client),
+                    });
+                }
+                Err(error) => warn!("Cannot use RPC endpoint {}: {:?}", rpc_url, error),
+            }
+        }
+        Err(anyhow::anyhow!(
+            "Cannot connect to any healthy RPC endpoint."
+        ))
+    }
+}
+
+/// Retry policy of the requests that fail with a retryable error.
+struct RetryPolicy {
+    max_retry_count: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Delay before the retry with the given 1-based index. Doubles with each retry, up to the
+    /// maximum delay.
+    fn get_delay(&self, retry_index: u32) -> Duration {
+        self.initial_delay
+            .saturating_mul(2u32.saturating_pow(retry_index.saturating_sub(1)))
+            .min(self.max_delay)
+    }
+}
+
+pub(crate) struct FailoverClient {
+    endpoints: Endpoints,
+    retry_policy: RetryPolicy,
+    connections: Vec<RwLock<Connection>>,
+    /// Incremented with each dispatch, for the round-robin selection of the connection.
+    dispatch_counter: AtomicUsize,
+}
+
+/// Connection errors, as opposed to call errors returned by a node that is still reachable.
+fn is_connection_error(error: &Error) -> bool {
+    matches!(error, Error::Transport(_) | Error::RestartNeeded(_))
+}
+
+/// Errors after which the same request may succeed: connection errors, timeouts and the request
+/// queue of the connection being full. All other errors, e.g. call errors returned by the node
+/// or response parse errors, are fatal.
+fn is_retryable_error(error: &Error) -> bool {
+    is_connection_error(error) || matches!(error, Error::RequestTimeout | Error::MaxSlotsExceeded)
+}
+
+impl FailoverClient {
+    pub(crate) async fn connect(config: &Config) -> anyhow::Result<Self> {
+        let mut rpc_urls = vec![config.substrate.rpc_url.clone()];
+        rpc_urls.extend(config.substrate.fallback_rpc_urls.iter().cloned());
+        let endpoints = Endpoints {
+            rpc_urls,
+            connection_timeout: Duration::from_secs(config.substrate.connection_timeout_seconds),
+            request_timeout: Duration::from_secs(config.substrate.request_timeout_seconds),
+        };
+        let retry_policy = RetryPolicy {
+            max_retry_count: config.substrate.request_retry_count,
+            initial_delay: Duration::from_millis(
+                config.substrate.request_retry_initial_delay_millis,
+            ),
+            max_delay: Duration::from_millis(config.substrate.request_retry_max_delay_millis),
+        };
+        let mut connections = Vec::new();
+        for _ in 0..config.substrate.connection_pool_size.max(1) {
+            connections.push(RwLock::new(endpoints.connect_to_healthy_endpoint(0).await?));
+        }
+        debug!("Connection pool of {} is ready.", connections.len());
+        Ok(FailoverClient {
+            endpoints,
+            retry_policy,
+            connections,
+            dispatch_counter: AtomicUsize::new(0),
+        })
+    }
+
+    pub(crate) fn get_pool_size(&self) -> usize {
+        self.connections.len()
+    }
+
+    /// Index of the pool connection for the next request or subscription.
+    fn next_connection_index(&self) -> usize {
+        self.dispatch_counter.fetch_add(1, Ordering::Relaxed) % self.connections.len()
+    }
+
+    async fn get_client(&self, connection_index: usize) -> Arc<Client> {
+        self.connections[connection_index]
+            .read()
+            .await
+            .client
+            .clone()
+    }
+
+    /// Switches the lost connections of the pool to the next healthy endpoint, e.g. after a
+    /// subscription has ended.
+    pub(crate) async fn fail_over(&self) -> anyhow::Result<()> {
+        for connection_index in 0..self.connections.len() {
+            self.fail_over_connection(connection_index).await?;
+        }
+        Ok(())
+    }
+
+    /// Switches the pool connection to the next healthy endpoint if the connection is lost.
+    /// No-op if the connection is alive, e.g. when another request has already failed over.
+    async fn fail_over_connection(&self, connection_index: usize) -> anyhow::Result<()> {
+        let mut connection = self.connections[connection_index].write().await;
+        if connection.client.is_connected() {
+            return Ok(());
+        }
+        warn!(
+            "Lost connection to RPC endpoint {}. Fail over.",
+            self.endpoints.rpc_urls[connection.endpoint_index]
+        );
+        *connection = self
+            .endpoints
+            .connect_to_healthy_endpoint(connection.endpoint_index + 1)
+            .await?;
+        Ok(())
+    }
+
+    /// Sends the request over the next connection of the pool, and retries it on a retryable
+    /// error according to the retry policy. A connection error fails the connection over to the
+    /// next healthy endpoint before the retry, in which case the retry is not delayed.
+    pub(crate) async fn request<'a, R>(
+        &self,
+        method: &'a str,
+        params: Option<ParamsSer<'a>>,
+    ) -> anyhow::Result<R>
+    where
+        R: DeserializeOwned,
+    {
+        let connection_index = self.next_connection_index();
+        let mut retry_index = 0;
+        loop {
+            let error = match self
+                .get_client(connection_index)
+                .await
+                .request(method, params.clone())
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+            if !is_retryable_error(&error) || retry_index >= self.retry_policy.max_retry_count {
+                return Err(error.into());
+            }
+            retry_index += 1;
+            if is_connection_error(&error) {
+                debug!("Connection error in {} request: {:?}", method, error);
+                self.fail_over_connection(connection_index).await?;
+            } else {
+                let delay = self.retry_policy.get_delay(retry_index);
+                warn!(
+                    "{} request has failed: {:?}. Retry {} of {} in {} ms.",
+                    method,
+                    error,
+                    retry_index,
+                    self.retry_policy.max_retry_count,
+                    delay.as_millis(),
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+
+    pub(crate) async fn subscribe<'a, N>(
+        &self,
+        subscribe_method: &'a str,
+        params: Option<ParamsSer<'a>>,
+        unsubscribe_method: &'a str,
+    ) -> anyhow::Result<Subscription<N>>
+    where
+        N: DeserializeOwned,
+    {
+        let connection_index = self.next_connection_index();
+        match self
+            .get_client(connection_index)
+            .await
+            .subscribe(subscribe_method, params.clone(), unsubscribe_method)
+            .await
+        {
+            Err(error) if is_connection_error(&error) => {
+                debug!(
+                    "Connection error in {} subscription: {:?}",
+                    subscribe_method, error
+                );
+                self.fail_over_connection(connection_index).await?;
+                Ok(self
+                    .get_client(connection_index)
+                    .await
+                    .subscribe(subscribe_method, params, unsubscribe_method)
+                    .await?)
+            }
+            result => Ok(result?),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Connects to the WebSocket feed stream of the given Telemetry and stores the feed data in
 //! the time series database (TimeScaleDB on PostgreSQL). Can be configured to connect to the
-//! W3F or Polkadot Telemetry servers.
+//! W3F or Polkadot Telemetry servers. Stays idle when Telemetry is not enabled in the
+//! configuration.
 use anyhow::Context;
 use async_lock::Mutex;
 use async_trait::async_trait;
@@ -10,7 +11,7 @@ use lazy_static::lazy_static;
 use log::{debug, error, info, trace, warn};
 use std::collections::HashMap;
 use std::sync::mpsc::{self, Receiver, Sender};
-use subvt_config::Config;
+use subvt_config::{Config, TelemetryConfig};
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
 use subvt_service_common::Service;
 use subvt_types::telemetry::{FeedMessage, NodeDetails, NodeLocation};
@@ -165,9 +166,12 @@ impl TelemetryProcessor {
         Ok(())
     }
 
-    async fn receive_messages(tx: Sender<Vec<FeedMessage>>) -> anyhow::Result<()> {
+    async fn receive_messages(
+        telemetry_config: &TelemetryConfig,
+        tx: Sender<Vec<FeedMessage>>,
+    ) -> anyhow::Result<()> {
         // connect to Telemetry feed
-        let (mut ws_stream, _) = connect_async(&CONFIG.telemetry.websocket_url)
+        let (mut ws_stream, _) = connect_async(&telemetry_config.websocket_url)
             .await
             .context("Failed to connect")?;
         debug!("Telemetry server websocket handshake has been successfully completed.");
@@ -225,12 +229,20 @@ impl TelemetryProcessor {
 #[async_trait(?Send)]
 impl Service for TelemetryProcessor {
     async fn run(&'static self) -> anyhow::Result<()> {
+        let telemetry_config = match CONFIG.get_telemetry_config() {
+            Some(telemetry_config) => telemetry_config,
+            None => {
+                info!("Telemetry is not enabled. Telemetry processor will stay idle.");
+                return std::future::pending().await;
+            }
+        };
         info!("Running the Telemetry processor.");
         let (tx, rx) = mpsc::channel();
         let receiver_join_handle = tokio::spawn(async move {
             loop {
                 let tx = tx.clone();
-                if let Err(error) = TelemetryProcessor::receive_messages(tx).await {
+                if let Err(error) = TelemetryProcessor::receive_messages(telemetry_config, tx).await
+                {
                     error!("Error while receiving feed messages: {:?}", error);
                 }
             }
@@ -2,6 +2,7 @@
 use crate::crypto::AccountId;
 use crate::onekv::OneKVValidity;
 use crate::substrate::Balance;
+use crate::subvt::AnnouncedCommissionChange;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -50,3 +51,95 @@ pub struct OneKVValidityChange {
     pub is_valid: bool,
     pub validity_items: Vec<OneKVValidity>,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReferendumVoteMissing {
+    pub validator_account_id: AccountId,
+    pub referendum_index: u32,
+    pub end_block_number: u64,
+    pub discovered_block_number: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PrimarySlotsMissed {
+    pub validator_account_id: AccountId,
+    pub session_index: u64,
+    pub elapsed_slot_count: u64,
+    pub expected_primary_slot_count_thousandths: u64,
+    pub primary_block_count: u32,
+    pub missed_primary_slot_count: u32,
+    pub bad_luck_probability_per_billion: u32,
+    pub discovered_block_number: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ZeroRewardPoints {
+    pub validator_account_id: AccountId,
+    /// Last session of the zero-point sessions.
+    pub session_index: u64,
+    pub zero_point_session_count: u32,
+    pub discovered_block_number: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ControllerLowBalance {
+    pub validator_account_id: AccountId,
+    pub controller_account_id: AccountId,
+    pub free_balance: Balance,
+    pub fee_spendable_balance: Balance,
+    pub discovered_block_number: u64,
+}
+
+/// Commission change announced by a validator ahead of the on-chain change.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CommissionChangeAnnounced {
+    pub validator_account_id: AccountId,
+    pub current_commission_per_billion: u32,
+    pub announcement: AnnouncedCommissionChange,
+    pub discovered_block_number: u64,
+}
+
+/// Amount that a nominator of the validator has started unbonding.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NominatorUnbonding {
+    pub validator_account_id: AccountId,
+    pub nominator_stash_account_id: AccountId,
+    pub unbonding_amount: Balance,
+    /// Active amount of the nominator after the unbonding.
+    pub active_amount: Balance,
+    /// Last of the eras that the unbonding chunks unlock at.
+    pub unlock_era_index: u32,
+    pub estimated_unlock_timestamp: Option<u64>,
+    pub discovered_block_number: u64,
+}
+
+/// Era reward received by a nominator that is below the reward expected from the era points
+/// of its validators, their commissions and the nominator's stake share.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NominatorRewardBelowExpectation {
+    pub nominator_account_id: AccountId,
+    pub era_index: u32,
+    pub expected_reward: Balance,
+    pub received_reward: Balance,
+    /// Validators of the nominator that haven't been paid out for the era.
+    pub unpaid_validator_account_ids: Vec<AccountId>,
+    pub discovered_block_number: u64,
+}
+
+/// Exposure of a watched nominator on an oversubscribed validator in an era that is not among
+/// the largest exposures of the validator, which are the only ones that get rewarded.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NominatorStakeNotRewarded {
+    pub nominator_account_id: AccountId,
+    pub validator_account_id: AccountId,
+    pub era_index: u32,
+    pub stake: Balance,
+    /// Smallest stake among the rewarded exposures of the validator.
+    pub min_rewarded_stake: Balance,
+    /// Stake needed to get into the rewarded exposures, one more than the smallest rewarded
+    /// stake.
+    pub min_reentry_stake: Balance,
+    pub max_rewarded_nominator_count: u32,
+    pub nominator_count: u32,
+    pub discovered_block_number: u64,
+}
@@ -48,7 +48,8 @@ impl From<PostgresUserValidator> for UserValidator {
     }
 }
 
-pub type PostgresUserNotificationChannel = (i32, i32, String, String);
+pub type PostgresUserNotificationChannel =
+    (i32, i32, String, String, Option<String>, Option<String>);
 
 impl From<PostgresUserNotificationChannel> for UserNotificationChannel {
     fn from(db_user_notification_channel: PostgresUserNotificationChannel) -> Self {
@@ -57,6 +58,8 @@ impl From<PostgresUserNotificationChannel> for UserNotificationChannel {
             user_id: db_user_notification_channel.1 as u32,
             channel_code: db_user_notification_channel.2.clone(),
             target: db_user_notification_channel.3,
+            timezone: db_user_notification_channel.4,
+            locale: db_user_notification_channel.5,
         }
     }
 }
@@ -71,6 +74,7 @@ pub type PostgresUserNotificationRule = (
     NotificationPeriodType,
     i32,
     Option<String>,
+    Option<i32>,
 );
 
 pub type PostgresNotificationParamType = (
@@ -81,7 +85,9 @@ pub type PostgresNotificationParamType = (
     NotificationParamDataType,
     Option<String>,
     Option<String>,
+    Option<String>,
     bool,
+    Option<String>,
 );
 
 pub type PostgresBlock = (
@@ -150,6 +156,7 @@ pub type PostgresNotification = (
     String,
     Option<String>,
     Option<String>,
+    Option<String>,
 );
 
 impl Notification {
@@ -168,7 +175,8 @@ impl Notification {
             notification_channel_code: db_notification.10.clone(),
             notification_target: db_notification.11.clone(),
             data_json: db_notification.12.clone(),
-            log: db_notification.13.clone(),
+            deep_link_json: db_notification.13.clone(),
+            log: db_notification.14.clone(),
             created_at: None,
             sent_at: None,
             delivered_at: None,
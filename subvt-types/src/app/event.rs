@@ -18,3 +18,13 @@ pub struct ChilledEvent {
     pub event_index: u32,
     pub stash_account_id: AccountId,
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DemocracyStartedEvent {
+    pub id: u32,
+    pub block_hash: String,
+    pub extrinsic_index: Option<u32>,
+    pub event_index: u32,
+    pub referendum_index: u32,
+    pub end_block_number: u64,
+}
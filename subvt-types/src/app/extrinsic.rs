@@ -1,5 +1,6 @@
 //! These types are used when reading Substrate extrinsics from PostgreSQL into the SubVT domain.
 use crate::crypto::AccountId;
+use crate::substrate::Balance;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -14,3 +15,21 @@ pub struct ValidateExtrinsic {
     pub blocks_nominations: bool,
     pub is_successful: bool,
 }
+
+/// Fee paid for a signed extrinsic. The author and treasury shares are the fee deposits
+/// made to the block author and the treasury in the extrinsic.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ExtrinsicFee {
+    pub block_hash: String,
+    pub extrinsic_index: u32,
+    pub signer_account_id: Option<AccountId>,
+    pub block_author_account_id: Option<AccountId>,
+    /// Total fee, including the tip.
+    pub fee: Balance,
+    pub tip: Balance,
+    pub author_fee_share: Balance,
+    pub treasury_fee_share: Balance,
+    pub weight: u64,
+    pub pays_fee: bool,
+    pub is_successful: bool,
+}
@@ -1,7 +1,7 @@
 //! Types used in the application logic of SubVT.
 use crate::crypto::AccountId;
 use crate::substrate::Account;
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter};
 
@@ -47,6 +47,97 @@ pub struct User {
     #[serde(default = "default_id")]
     pub id: u32,
     pub public_key_hex: String,
+    /// Whether the user has proven control of at least one validator stash. Unlocks the
+    /// operator-only notification types.
+    #[serde(default)]
+    pub is_verified_operator: bool,
+}
+
+fn decode_hex_bytes<const N: usize>(hex_string: &str) -> Option<[u8; N]> {
+    hex::decode(hex_string.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+}
+
+/// Verifies an sr25519 signature of the message against the public key.
+pub fn verify_sr25519_signature(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature_hex: &str,
+) -> bool {
+    let signature_bytes: [u8; 64] = match decode_hex_bytes(signature_hex) {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    <sp_core::sr25519::Pair as sp_core::Pair>::verify(
+        &sp_core::sr25519::Signature::from_raw(signature_bytes),
+        message,
+        &sp_core::sr25519::Public::from_raw(*public_key),
+    )
+}
+
+/// Verifies an ed25519 signature of the message against the public key.
+pub fn verify_ed25519_signature(
+    public_key: &[u8; 32],
+    message: &[u8],
+    signature_hex: &str,
+) -> bool {
+    let signature_bytes: [u8; 64] = match decode_hex_bytes(signature_hex) {
+        Some(bytes) => bytes,
+        None => return false,
+    };
+    <sp_core::ed25519::Pair as sp_core::Pair>::verify(
+        &sp_core::ed25519::Signature::from_raw(signature_bytes),
+        message,
+        &sp_core::ed25519::Public::from_raw(*public_key),
+    )
+}
+
+/// Verifies the sr25519 signature of the message, either signed as is, or wrapped in `<Bytes>`
+/// tags as the browser wallet extensions do when signing raw data.
+pub fn verify_wallet_signature(public_key: &[u8; 32], message: &str, signature_hex: &str) -> bool {
+    verify_sr25519_signature(public_key, message.as_bytes(), signature_hex)
+        || verify_sr25519_signature(
+            public_key,
+            format!("<Bytes>{}</Bytes>", message).as_bytes(),
+            signature_hex,
+        )
+}
+
+impl User {
+    /// Verifies an sr25519 signature of the message against the user's public key.
+    pub fn verify_signature(&self, message: &[u8], signature_hex: &str) -> bool {
+        match decode_hex_bytes(&self.public_key_hex) {
+            Some(public_key) => verify_sr25519_signature(&public_key, message, signature_hex),
+            None => false,
+        }
+    }
+
+    /// Verifies the signature of the message, either signed as is, or wrapped in `<Bytes>`
+    /// tags as the browser wallet extensions do when signing raw data.
+    pub fn verify_wallet_signature(&self, message: &str, signature_hex: &str) -> bool {
+        match decode_hex_bytes(&self.public_key_hex) {
+            Some(public_key) => verify_wallet_signature(&public_key, message, signature_hex),
+            None => false,
+        }
+    }
+}
+
+/// Change to a resource owned by a user, published by the application database
+/// on the `app_user_event` channel.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserEvent {
+    pub user_id: u32,
+    pub event_type: UserEventType,
+    pub id: u32,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UserEventType {
+    NotificationCreated,
+    UserValidatorCreated,
+    UserValidatorDeleted,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -67,6 +158,14 @@ pub enum NotificationTypeCode {
     ChainValidateExtrinsic,
     ChainValidatorUnclaimedPayout,
     ChainValidatorBlockAuthorship,
+    ChainValidatorReferendumVoteMissing,
+    ChainValidatorMissedPrimarySlots,
+    ChainValidatorZeroRewardPoints,
+    ChainValidatorControllerLowBalance,
+    ChainValidatorCommissionChangeAnnounced,
+    ChainValidatorNominatorUnbonding,
+    ChainNominatorRewardBelowExpectation,
+    ChainNominatorStakeNotRewarded,
     TelemetryValidatorOffline,
     TelemetryValidatorBinaryOutOfDate,
     TelemetryValidatorPeerCountLow,
@@ -104,6 +203,30 @@ impl Display for NotificationTypeCode {
             NotificationTypeCode::ChainValidatorBlockAuthorship => {
                 "chain_validator_block_authorship"
             }
+            NotificationTypeCode::ChainValidatorReferendumVoteMissing => {
+                "chain_validator_referendum_vote_missing"
+            }
+            NotificationTypeCode::ChainValidatorMissedPrimarySlots => {
+                "chain_validator_missed_primary_slots"
+            }
+            NotificationTypeCode::ChainValidatorZeroRewardPoints => {
+                "chain_validator_zero_reward_points"
+            }
+            NotificationTypeCode::ChainValidatorControllerLowBalance => {
+                "chain_validator_controller_low_balance"
+            }
+            NotificationTypeCode::ChainValidatorCommissionChangeAnnounced => {
+                "chain_validator_commission_change_announced"
+            }
+            NotificationTypeCode::ChainValidatorNominatorUnbonding => {
+                "chain_validator_nominator_unbonding"
+            }
+            NotificationTypeCode::ChainNominatorRewardBelowExpectation => {
+                "chain_nominator_reward_below_expectation"
+            }
+            NotificationTypeCode::ChainNominatorStakeNotRewarded => {
+                "chain_nominator_stake_not_rewarded"
+            }
             NotificationTypeCode::TelemetryValidatorOffline => "telemetry_validator_offline",
             NotificationTypeCode::TelemetryValidatorBinaryOutOfDate => {
                 "telemetry_validator_binary_out_of_date"
@@ -156,6 +279,30 @@ impl From<&str> for NotificationTypeCode {
             "chain_validator_block_authorship" => {
                 NotificationTypeCode::ChainValidatorBlockAuthorship
             }
+            "chain_validator_referendum_vote_missing" => {
+                NotificationTypeCode::ChainValidatorReferendumVoteMissing
+            }
+            "chain_validator_missed_primary_slots" => {
+                NotificationTypeCode::ChainValidatorMissedPrimarySlots
+            }
+            "chain_validator_zero_reward_points" => {
+                NotificationTypeCode::ChainValidatorZeroRewardPoints
+            }
+            "chain_validator_controller_low_balance" => {
+                NotificationTypeCode::ChainValidatorControllerLowBalance
+            }
+            "chain_validator_commission_change_announced" => {
+                NotificationTypeCode::ChainValidatorCommissionChangeAnnounced
+            }
+            "chain_validator_nominator_unbonding" => {
+                NotificationTypeCode::ChainValidatorNominatorUnbonding
+            }
+            "chain_nominator_reward_below_expectation" => {
+                NotificationTypeCode::ChainNominatorRewardBelowExpectation
+            }
+            "chain_nominator_stake_not_rewarded" => {
+                NotificationTypeCode::ChainNominatorStakeNotRewarded
+            }
             "telemetry_validator_offline" => NotificationTypeCode::TelemetryValidatorOffline,
             "telemetry_validator_binary_out_of_date" => {
                 NotificationTypeCode::TelemetryValidatorBinaryOutOfDate
@@ -183,10 +330,80 @@ impl From<&str> for NotificationTypeCode {
     }
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "app_notification_severity", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Default for NotificationSeverity {
+    fn default() -> Self {
+        NotificationSeverity::Info
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct NotificationType {
     pub code: String,
+    pub severity: NotificationSeverity,
+    /// Rules of operator-only types can only be created by the verified validator operators.
+    pub is_operator_only: bool,
+    pub supported_channel_codes: Vec<String>,
     pub param_types: Vec<NotificationParamType>,
+    /// JSON schema of the rule parameters object, for dynamic rule configuration UI.
+    pub param_schema: serde_json::Value,
+}
+
+impl NotificationType {
+    /// Generates the JSON schema of the parameters object of a notification rule for this type,
+    /// in which the keys are the parameter codes. Balance parameters are decimal strings.
+    pub fn get_param_schema(&self) -> serde_json::Value {
+        let mut properties = serde_json::Map::new();
+        let mut required = Vec::new();
+        for param_type in &self.param_types {
+            let mut property = serde_json::Map::new();
+            let parse_value = |value: &str| param_type.type_.parse_json_value(value);
+            property.insert(
+                "type".to_string(),
+                param_type.type_.get_json_schema_type().into(),
+            );
+            match param_type.type_ {
+                NotificationParamDataType::Integer | NotificationParamDataType::Float => {
+                    if let Some(min) = param_type.min.as_deref().and_then(parse_value) {
+                        property.insert("minimum".to_string(), min);
+                    }
+                    if let Some(max) = param_type.max.as_deref().and_then(parse_value) {
+                        property.insert("maximum".to_string(), max);
+                    }
+                }
+                NotificationParamDataType::Balance => {
+                    property.insert("pattern".to_string(), "^[0-9]+$".into());
+                }
+                _ => (),
+            }
+            if let Some(default) = param_type.default_value.as_deref().and_then(parse_value) {
+                property.insert("default".to_string(), default);
+            }
+            if let Some(description) = &param_type.description {
+                property.insert("description".to_string(), description.as_str().into());
+            }
+            properties.insert(param_type.code.clone(), property.into());
+            if !param_type.is_optional {
+                required.push(serde_json::Value::from(param_type.code.as_str()));
+            }
+        }
+        serde_json::json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": self.code,
+            "type": "object",
+            "properties": properties,
+            "required": required,
+            "additionalProperties": false,
+        })
+    }
 }
 
 #[derive(Clone, Debug, sqlx::Type, Serialize, Deserialize)]
@@ -203,6 +420,30 @@ pub enum NotificationParamDataType {
     Boolean,
 }
 
+impl NotificationParamDataType {
+    pub fn get_json_schema_type(&self) -> &'static str {
+        match self {
+            NotificationParamDataType::String => "string",
+            NotificationParamDataType::Integer => "integer",
+            NotificationParamDataType::Balance => "string",
+            NotificationParamDataType::Float => "number",
+            NotificationParamDataType::Boolean => "boolean",
+        }
+    }
+
+    /// Parses a parameter value stored as string into a JSON value of the matching type.
+    pub fn parse_json_value(&self, value: &str) -> Option<serde_json::Value> {
+        match self {
+            NotificationParamDataType::String | NotificationParamDataType::Balance => {
+                Some(value.into())
+            }
+            NotificationParamDataType::Integer => value.parse::<i64>().ok().map(Into::into),
+            NotificationParamDataType::Float => value.parse::<f64>().ok().map(Into::into),
+            NotificationParamDataType::Boolean => value.parse::<bool>().ok().map(Into::into),
+        }
+    }
+}
+
 impl Display for NotificationParamDataType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -229,7 +470,9 @@ pub struct NotificationParamType {
     pub type_: NotificationParamDataType,
     pub min: Option<String>,
     pub max: Option<String>,
+    pub default_value: Option<String>,
     pub is_optional: bool,
+    pub description: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -240,6 +483,14 @@ pub struct UserNotificationChannel {
     pub user_id: u32,
     pub channel_code: String,
     pub target: String,
+    /// IANA timezone of the device or the recipient of the channel, e.g. `Europe/Istanbul`.
+    /// The era and epoch boundaries in the notifications are accompanied with their local
+    /// times in this timezone, see `subvt_types::local_time`.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// BCP 47 language tag of the device, e.g. `en-US`.
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Serialize)]
@@ -252,6 +503,19 @@ pub struct UserValidator {
     pub validator_account_id: AccountId,
 }
 
+/// Nominator stash account watched by the user, e.g. for the
+/// `chain_nominator_reward_below_expectation` and `chain_nominator_stake_not_rewarded`
+/// notifications.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct UserNominator {
+    #[serde(default = "default_id")]
+    pub id: u32,
+    #[serde(default = "default_id")]
+    pub user_id: u32,
+    pub network_id: u32,
+    pub nominator_account_id: AccountId,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct UserNotificationRuleParameter {
     #[serde(default = "default_id")]
@@ -408,6 +672,255 @@ pub struct UserNotificationRule {
     pub notification_channels: Vec<UserNotificationChannel>,
     pub parameters: Vec<UserNotificationRuleParameter>,
     pub notes: Option<String>,
+    /// The rule applies to the validators of this group when set.
+    pub validator_group_id: Option<u32>,
+}
+
+/// Notification rule that gets created for a validator when a user adds the validator on the
+/// network, unless the user opts out. Configured by the admins per network.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct NetworkDefaultNotificationRule {
+    #[serde(default = "default_id")]
+    pub id: u32,
+    #[serde(default = "default_id")]
+    pub network_id: u32,
+    pub notification_type_code: String,
+    pub name: Option<String>,
+    pub period_type: NotificationPeriodType,
+    pub period: u16,
+    /// `user_notification_rule_id` is not used.
+    pub parameters: Vec<UserNotificationRuleParameter>,
+}
+
+/// Sign-in challenge issued to a user. The user signs the `message` with the wallet key
+/// to get a session before the challenge expires.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserAuthChallenge {
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl UserAuthChallenge {
+    pub fn get_message(nonce: &str) -> String {
+        format!("subvt_sign_in:{}", nonce)
+    }
+}
+
+/// Challenge issued to a user to prove control of a validator stash. The `message` has to be
+/// signed with the stash key, or with one of the session keys of the validator declared on
+/// chain, before the challenge expires.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidatorClaimChallenge {
+    pub network_id: u32,
+    pub validator_account_id: AccountId,
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: NaiveDateTime,
+}
+
+impl ValidatorClaimChallenge {
+    pub fn get_message(user_id: u32, validator_account_id: &AccountId, nonce: &str) -> String {
+        format!(
+            "subvt_validator_claim:{}:{}:{}",
+            user_id,
+            validator_account_id.to_ss58_check(),
+            nonce
+        )
+    }
+}
+
+/// Validator whose stash the user has proven control of.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserOperatorValidator {
+    pub id: u32,
+    pub user_id: u32,
+    pub network_id: u32,
+    pub validator_account_id: AccountId,
+    /// Whether the challenge was signed with a session key rather than the stash key.
+    pub is_session_key_claim: bool,
+    pub created_at: NaiveDateTime,
+}
+
+/// Short-lived session of a signed-in user. The token is only known to the user,
+/// its hash is persisted.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserSession {
+    pub user_id: u32,
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Temporary mute of the user's notifications, e.g. during validator maintenance. Applies to
+/// all the validators of the user when there's no user validator, and to all notification
+/// types when there's no notification type.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserNotificationMute {
+    pub id: u32,
+    pub user_id: u32,
+    pub user_validator_id: Option<u32>,
+    pub notification_type_code: Option<String>,
+    pub expires_at: NaiveDateTime,
+}
+
+/// Named validator set created by a user, e.g. the curated validator list of a staking
+/// community. Shared with the other users by its share code. The owner and the subscribed users
+/// can attach notification rules to the group, which then apply to all the group validators.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ValidatorGroup {
+    pub id: u32,
+    /// Owner of the group, the only user who can update or delete it.
+    pub user_id: u32,
+    pub network_id: u32,
+    pub name: String,
+    pub share_code: String,
+    pub validator_account_ids: Vec<AccountId>,
+    pub subscriber_count: u32,
+}
+
+/// Key of a user for the public API access, sent in the `X-API-Key` header of the requests to
+/// the report service. The key itself is only returned on creation, its hash is persisted, and
+/// the prefix of the key is kept so that the user can tell the keys apart.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserApiKey {
+    pub id: u32,
+    pub user_id: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    pub key_prefix: String,
+    pub created_at: NaiveDateTime,
+}
+
+/// Number of public API requests made with an API key on a (UTC) date.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ApiUsage {
+    pub api_key_id: u32,
+    pub date: NaiveDate,
+    pub request_count: u64,
+}
+
+/// API usage of a user: the daily request counts of each key of the user in a date range, and
+/// the request count of the current day against the daily request quota, which applies to all
+/// the keys of the user together. Request counts get persisted periodically, so the recent
+/// requests may not be reflected yet.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserApiUsageReport {
+    pub user_id: u32,
+    pub daily_request_quota: u64,
+    pub today_request_count: u64,
+    pub today_remaining_request_count: u64,
+    pub usage: Vec<ApiUsage>,
+}
+
+/// Total API usage of a user in a date range, for the admin usage report.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct UserApiUsageSummary {
+    pub user_id: u32,
+    pub api_key_count: u32,
+    pub request_count: u64,
+    /// Number of days in the range with at least one request.
+    pub active_day_count: u32,
+    /// Request count of the busiest day in the range, to be compared with the daily quota.
+    pub max_daily_request_count: u64,
+}
+
+/// Total API usage of all users on a (UTC) date, for the admin usage report.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DailyApiUsage {
+    pub date: NaiveDate,
+    pub user_count: u32,
+    pub request_count: u64,
+}
+
+/// Total of an analytics metric of a service on a chain in a time range, for the admin
+/// analytics report. See `analytics` in `subvt-service-common` for the metrics.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AnalyticsSummary {
+    pub service_name: String,
+    pub chain: String,
+    pub metric: String,
+    pub total_count: u64,
+    /// Highest count in a single hour of the range.
+    pub max_hourly_count: u64,
+}
+
+/// Total of an analytics metric in an hour, summed over the services and the chains.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct HourlyAnalyticsCount {
+    pub period_start: NaiveDateTime,
+    pub count: u64,
+}
+
+/// Screen of the mobile applications that a notification opens when tapped.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationDeepLinkScreen {
+    ValidatorDetails,
+    /// Report of the validator for the era of the deep link.
+    ValidatorEraReport,
+    ValidatorTelemetry,
+    #[serde(rename = "validator_onekv")]
+    ValidatorOneKV,
+}
+
+impl From<&NotificationTypeCode> for NotificationDeepLinkScreen {
+    fn from(type_code: &NotificationTypeCode) -> Self {
+        match type_code {
+            NotificationTypeCode::ChainValidatorOfflineOffence
+            | NotificationTypeCode::ChainValidatorUnclaimedPayout
+            | NotificationTypeCode::ChainValidatorMissedPrimarySlots
+            | NotificationTypeCode::ChainValidatorZeroRewardPoints
+            | NotificationTypeCode::ChainNominatorRewardBelowExpectation
+            | NotificationTypeCode::ChainNominatorStakeNotRewarded => {
+                NotificationDeepLinkScreen::ValidatorEraReport
+            }
+            NotificationTypeCode::TelemetryValidatorOffline
+            | NotificationTypeCode::TelemetryValidatorBinaryOutOfDate
+            | NotificationTypeCode::TelemetryValidatorPeerCountLow
+            | NotificationTypeCode::TelemetryValidatorTooManyTxsInQueue
+            | NotificationTypeCode::TelemetryValidatorLagging
+            | NotificationTypeCode::TelemetryValidatorFinalityLagging
+            | NotificationTypeCode::TelemetryValidatorDownloadBwLow
+            | NotificationTypeCode::TelemetryValidatorUploadBwLow => {
+                NotificationDeepLinkScreen::ValidatorTelemetry
+            }
+            NotificationTypeCode::OneKVValidatorRankChange
+            | NotificationTypeCode::OneKVValidatorValidityChange => {
+                NotificationDeepLinkScreen::ValidatorOneKV
+            }
+            _ => NotificationDeepLinkScreen::ValidatorDetails,
+        }
+    }
+}
+
+/// Deep link payload of the push notifications, as agreed with the mobile applications, so that
+/// the applications can open the related screen without parsing the notification text.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NotificationDeepLink {
+    /// Chain name, e.g. `kusama`.
+    pub network: String,
+    pub validator_account_id: AccountId,
+    pub screen: NotificationDeepLinkScreen,
+    /// Active era at the block the notification got generated at.
+    pub era_index: Option<u32>,
+}
+
+impl NotificationDeepLink {
+    pub fn new(
+        network: &str,
+        validator_account_id: &AccountId,
+        notification_type_code: &str,
+        era_index: Option<u32>,
+    ) -> NotificationDeepLink {
+        NotificationDeepLink {
+            network: network.to_string(),
+            validator_account_id: validator_account_id.clone(),
+            screen: NotificationDeepLinkScreen::from(&NotificationTypeCode::from(
+                notification_type_code,
+            )),
+            era_index,
+        }
+    }
 }
 
 pub struct Notification {
@@ -424,6 +937,7 @@ pub struct Notification {
     pub notification_channel_code: String,
     pub notification_target: String,
     pub data_json: Option<String>,
+    pub deep_link_json: Option<String>,
     pub log: Option<String>,
     pub created_at: Option<NaiveDateTime>,
     pub sent_at: Option<NaiveDateTime>,
@@ -439,4 +953,19 @@ impl Notification {
             Ok(None)
         }
     }
+
+    /// Deep link saved at generation, or a link to the screen of the notification type without
+    /// an era for the notifications generated before the deep links.
+    pub fn get_deep_link(&self, network: &str) -> anyhow::Result<NotificationDeepLink> {
+        if let Some(deep_link_json) = &self.deep_link_json {
+            Ok(serde_json::from_str(deep_link_json)?)
+        } else {
+            Ok(NotificationDeepLink::new(
+                network,
+                &self.validator_account_id,
+                &self.notification_type_code,
+                None,
+            ))
+        }
+    }
 }
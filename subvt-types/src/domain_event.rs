@@ -0,0 +1,73 @@
+//! Normalized domain events emitted by the block processor into the append-only changefeed of
+//! the network database, so that the consumers (notification generator, webhooks, analytics)
+//! don't have to derive the events from the raw chain data individually.
+use crate::crypto::AccountId;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainEventType {
+    /// Validator is active in the era, but was not in the previous era.
+    /// Data: `{"era_index": u32}`.
+    ValidatorJoinedActiveSet,
+    /// Successful `validate` extrinsic with a different commission than the previous one.
+    /// Data: `{"previous_commission_per_billion": u32, "commission_per_billion": u32}`.
+    CommissionChanged,
+    /// Data: `{"amount": "<balance>"}`.
+    Slashed,
+    /// Successful `payout_stakers` extrinsic.
+    /// Data: `{"era_index": u32, "caller_account_id": "<account id>"}`.
+    PayoutMade,
+}
+
+impl Display for DomainEventType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                DomainEventType::ValidatorJoinedActiveSet => "validator_joined_active_set",
+                DomainEventType::CommissionChanged => "commission_changed",
+                DomainEventType::Slashed => "slashed",
+                DomainEventType::PayoutMade => "payout_made",
+            }
+        )
+    }
+}
+
+impl FromStr for DomainEventType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "validator_joined_active_set" => Ok(DomainEventType::ValidatorJoinedActiveSet),
+            "commission_changed" => Ok(DomainEventType::CommissionChanged),
+            "slashed" => Ok(DomainEventType::Slashed),
+            "payout_made" => Ok(DomainEventType::PayoutMade),
+            _ => Err(anyhow::anyhow!("Unknown domain event type: {}", s)),
+        }
+    }
+}
+
+/// An event in the changefeed. Ids are strictly increasing in the order of persistence.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DomainEvent {
+    pub id: u64,
+    pub event_type: DomainEventType,
+    pub block_hash: String,
+    pub block_number: u64,
+    pub era_index: u32,
+    pub validator_account_id: Option<AccountId>,
+    /// Type-specific data, see `DomainEventType`.
+    pub data: serde_json::Value,
+}
+
+/// Payload of the PostgreSQL notification sent on the `domain_event` channel for each
+/// persisted event.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DomainEventNotification {
+    pub id: u64,
+    pub event_type: DomainEventType,
+}
@@ -1,13 +1,36 @@
 //! Error types.
 use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+/// Machine-readable code of a service error, so that the clients don't have to depend on the
+/// error descriptions. Each code maps to a single HTTP status, see `subvt-service-common`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceErrorCode {
+    /// Malformed or invalid path, query or body parameter.
+    InvalidRequest,
+    InvalidAccountId,
+    /// End era is before the start era, or the range is longer than allowed.
+    InvalidEraRange,
+    NotFound,
+    /// Missing or invalid session or signature.
+    Unauthorized,
+    /// Authenticated, but not allowed to access the resource.
+    Forbidden,
+    /// Resource exists or the request is in conflict with the current state.
+    Conflict,
+    /// Service is overloaded, the request can be retried later.
+    Unavailable,
+    Internal,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct ServiceError {
+    pub code: ServiceErrorCode,
     pub description: String,
 }
 
 impl ServiceError {
-    pub fn from(description: String) -> ServiceError {
-        ServiceError { description }
+    pub fn new(code: ServiceErrorCode, description: String) -> ServiceError {
+        ServiceError { code, description }
     }
 }
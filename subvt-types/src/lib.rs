@@ -1,7 +1,9 @@
 //! SubVT types. Refer to module documentations for details.
 pub mod app;
 pub mod crypto;
+pub mod domain_event;
 pub mod err;
+pub mod local_time;
 pub mod onekv;
 pub mod rdb;
 pub mod report;
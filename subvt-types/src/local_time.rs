@@ -0,0 +1,88 @@
+//! Pre-formatted local times of the era boundaries (or any time range, such as an epoch) in the
+//! timezone of a client device, so that the clients display the boundaries and count down to
+//! them without doing the timezone and DST arithmetic themselves.
+use crate::substrate::Era;
+use chrono::{DateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+/// Maximum length of a locale tag, as in the `app_user_notification_channel.locale` column.
+pub const MAX_LOCALE_LENGTH: usize = 35;
+
+/// Display format of the local times, e.g. `2022-01-18 21:30 +03`. Locale-independent, the
+/// clients may re-format the RFC 3339 times according to the locale.
+const DISPLAY_FORMAT: &str = "%Y-%m-%d %H:%M %Z";
+
+/// Whether the timezone is a valid IANA timezone name, e.g. `Europe/Istanbul`.
+pub fn is_valid_timezone(timezone: &str) -> bool {
+    timezone.parse::<Tz>().is_ok()
+}
+
+/// Whether the locale is a syntactically valid BCP 47 language tag, e.g. `en-US`. Only the
+/// syntax is checked: a 2-3 letter language followed by 1-8 character alphanumeric subtags.
+pub fn is_valid_locale(locale: &str) -> bool {
+    if locale.len() > MAX_LOCALE_LENGTH {
+        return false;
+    }
+    let mut subtags = locale.split('-');
+    let language_is_valid = subtags.next().map_or(false, |language| {
+        (2..=3).contains(&language.len()) && language.chars().all(|c| c.is_ascii_alphabetic())
+    });
+    language_is_valid
+        && subtags.all(|subtag| {
+            (1..=8).contains(&subtag.len()) && subtag.chars().all(|c| c.is_ascii_alphanumeric())
+        })
+}
+
+/// Start and end times of an era or an epoch in a timezone.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LocalTimeHints {
+    pub timezone: String,
+    /// Echoed back for the client-side localization, doesn't affect the formatting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// RFC 3339 time with the UTC offset of the timezone at the start,
+    /// e.g. `2022-01-18T21:30:00+03:00`.
+    pub start: String,
+    /// RFC 3339 time with the UTC offset of the timezone at the end, which may differ from the
+    /// offset at the start if there's a DST transition in between.
+    pub end: String,
+    pub start_display: String,
+    pub end_display: String,
+}
+
+impl LocalTimeHints {
+    pub fn new(
+        start_timestamp_millis: u64,
+        end_timestamp_millis: u64,
+        timezone: &str,
+        locale: Option<&str>,
+    ) -> anyhow::Result<LocalTimeHints> {
+        let tz: Tz = timezone
+            .parse()
+            .map_err(|error| anyhow::anyhow!("Invalid timezone {}: {}", timezone, error))?;
+        let start = to_local_time(start_timestamp_millis, &tz);
+        let end = to_local_time(end_timestamp_millis, &tz);
+        Ok(LocalTimeHints {
+            timezone: timezone.to_string(),
+            locale: locale.map(|locale| locale.to_string()),
+            start: start.to_rfc3339(),
+            end: end.to_rfc3339(),
+            start_display: start.format(DISPLAY_FORMAT).to_string(),
+            end_display: end.format(DISPLAY_FORMAT).to_string(),
+        })
+    }
+
+    pub fn for_era(
+        era: &Era,
+        timezone: &str,
+        locale: Option<&str>,
+    ) -> anyhow::Result<LocalTimeHints> {
+        LocalTimeHints::new(era.start_timestamp, era.end_timestamp, timezone, locale)
+    }
+}
+
+fn to_local_time(timestamp_millis: u64, tz: &Tz) -> DateTime<Tz> {
+    Utc.timestamp_millis(timestamp_millis as i64)
+        .with_timezone(tz)
+}
@@ -121,3 +121,40 @@ pub struct OneKVValidity {
     #[serde(rename(deserialize = "updated"))]
     pub updated_at: u64,
 }
+
+/// Rank, validity and score of a 1KV candidate at a refresh of the 1KV data, for the rank and
+/// score charts.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OneKVCandidateHistoryItem {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rank: Option<i64>,
+    pub is_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_total: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_aggregate: Option<f64>,
+}
+
+/// A candidate in an archived 1KV candidate list snapshot, see `OneKVCandidateSnapshot`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OneKVArchivedCandidate {
+    #[serde(rename(deserialize = "stash"))]
+    pub stash_address: String,
+    pub rank: Option<i64>,
+    #[serde(rename(deserialize = "valid"))]
+    pub is_valid: bool,
+    pub score: Option<OneKVScore>,
+}
+
+/// Archived 1KV candidate list, from the programme's archived data endpoints or an exported
+/// dump. Used to backfill the rank and score history of the candidates for the period before
+/// the deployment.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct OneKVCandidateSnapshot {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub candidates: Vec<OneKVArchivedCandidate>,
+}
@@ -1,5 +1,10 @@
 //! Types used in relational database storage.
+use crate::crypto::AccountId;
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 pub struct ValidatorInfo {
     pub discovered_at: Option<u64>,
@@ -23,3 +28,107 @@ pub struct BlockProcessedNotification {
     pub block_number: u64,
     pub block_hash: String,
 }
+
+/// Sent by the block processor when the indexed data of completed eras has changed, e.g. with
+/// a late payout or at the end of an era, so that the cached reports of these eras get dropped.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReportInvalidationNotification {
+    pub block_number: u64,
+    pub era_indices: Vec<u32>,
+}
+
+/// Slot range and the authored primary and secondary block counts of each validator
+/// in a session, from the BABE slot claims of the indexed blocks.
+#[derive(Clone, Debug, Default)]
+pub struct SessionSlotClaims {
+    pub era_index: u32,
+    pub first_slot_number: u64,
+    pub last_slot_number: u64,
+    /// Validator account id to `(primary block count, secondary block count)`.
+    pub validator_block_counts: HashMap<AccountId, (u32, u32)>,
+}
+
+/// Category of the indexed data that can be pruned by age, see `DataRetentionPolicy`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataRetentionCategory {
+    /// Rows of the raw `sub_extrinsic_*` tables that no report depends on, aged by the
+    /// timestamp of their block.
+    Extrinsics,
+    /// Rows of the raw `sub_event_*` tables that no report depends on, aged by the timestamp
+    /// of their block.
+    Events,
+    /// Node and network statistics time series of the telemetry processor.
+    Telemetry,
+    /// Application notifications of the network, aged by their creation time.
+    Notifications,
+}
+
+impl DataRetentionCategory {
+    pub fn all() -> [DataRetentionCategory; 4] {
+        [
+            DataRetentionCategory::Extrinsics,
+            DataRetentionCategory::Events,
+            DataRetentionCategory::Telemetry,
+            DataRetentionCategory::Notifications,
+        ]
+    }
+}
+
+impl Display for DataRetentionCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            DataRetentionCategory::Extrinsics => "extrinsics",
+            DataRetentionCategory::Events => "events",
+            DataRetentionCategory::Telemetry => "telemetry",
+            DataRetentionCategory::Notifications => "notifications",
+        };
+        write!(f, "{}", display)
+    }
+}
+
+impl FromStr for DataRetentionCategory {
+    type Err = anyhow::Error;
+
+    fn from_str(category: &str) -> Result<Self, Self::Err> {
+        match category {
+            "extrinsics" => Ok(DataRetentionCategory::Extrinsics),
+            "events" => Ok(DataRetentionCategory::Events),
+            "telemetry" => Ok(DataRetentionCategory::Telemetry),
+            "notifications" => Ok(DataRetentionCategory::Notifications),
+            _ => Err(anyhow::anyhow!(
+                "Unknown data retention category: {}",
+                category
+            )),
+        }
+    }
+}
+
+/// Data of a category older than the retention period gets deleted by the pruning runs.
+/// Data of the categories without a policy is kept indefinitely.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DataRetentionPolicy {
+    pub category: DataRetentionCategory,
+    pub retention_days: u32,
+    pub last_pruned_at: Option<NaiveDateTime>,
+    /// Number of rows deleted by the last pruning run. Dropped chunk count for telemetry.
+    pub last_pruned_row_count: Option<u64>,
+}
+
+/// Result of pruning a category.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct DataPruneResult {
+    pub category: DataRetentionCategory,
+    pub retention_days: u32,
+    /// Number of deleted rows, or the number of dropped chunks for telemetry.
+    pub deleted_count: u64,
+}
+
+/// Disk space used by a table, including its indices and TOAST data.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TableSpaceUsage {
+    pub table_name: String,
+    pub total_bytes: u64,
+    /// Planner estimate, may be off until the table gets analyzed.
+    pub row_count_estimate: u64,
+}
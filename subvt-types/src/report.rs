@@ -1,7 +1,15 @@
 //! Report presentation types. Utilized by the `subvt-report-service` crate to server era and
 //! validator reports.
-use crate::substrate::Era;
+use crate::app::extrinsic::ExtrinsicFee;
+use crate::crypto::AccountId;
+use crate::substrate::nomination_pools::{NominationPool, NominationPoolState};
+use crate::substrate::{
+    Balance, DemocracyVote, ElectionVoter, Era, IdentityChange, RewardDestination, UnlockChunk,
+};
+use crate::subvt::TokenPrice;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct EraValidatorReport {
@@ -11,6 +19,16 @@ pub struct EraValidatorReport {
     pub self_stake: Option<u128>,
     pub total_stake: Option<u128>,
     pub block_count: u32,
+    /// Authored blocks by BABE slot type, see `BabeSlotType`. Secondary-heavy authorship is
+    /// luck of the slot assignment rather than performance. The secondary slot types of the
+    /// blocks indexed before the slot types were recorded are unknown, so the split can add up
+    /// to less than the block count.
+    #[serde(default)]
+    pub primary_block_count: u32,
+    #[serde(default)]
+    pub secondary_plain_block_count: u32,
+    #[serde(default)]
+    pub secondary_vrf_block_count: u32,
     pub reward_points: Option<u128>,
     pub self_reward: u128,
     pub staker_reward: u128,
@@ -35,3 +53,701 @@ pub struct EraReport {
     pub slashed_amount: u128,
     pub chilling_count: u64,
 }
+
+/// Entry amounts of an era: the minimum bonds to nominate and to validate at the start of
+/// the era, and the minimum active stakes of a nominator and of a validator in the era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraStakingMinimums {
+    pub era_index: u32,
+    /// `None` for the eras indexed before the minimums got recorded, and when not set.
+    pub min_nominator_bond: Option<u128>,
+    pub min_validator_bond: Option<u128>,
+    /// Sum of the stakes of a nominator in the exposures of the active validators.
+    pub min_active_nominator_stake: Option<u128>,
+    /// Total stake backing the validator with the least stake in the active set.
+    pub min_active_validator_stake: u128,
+}
+
+/// Validator with the reward points it has earned in an era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraPointsLeader {
+    pub account_id: AccountId,
+    pub reward_points: u64,
+}
+
+/// Offence and slash statistics of an era. Offences are the `Offences.Offence` events, which
+/// are counted by their kind, e.g. `im-online:offlin` or `babe:equivocatio`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraOffenceReport {
+    pub era_index: u32,
+    pub offence_count: u32,
+    pub offence_counts_by_kind: BTreeMap<String, u32>,
+    /// Number of validators reported offline by `ImOnline.SomeOffline`.
+    pub offline_offence_count: u32,
+    pub slash_count: u32,
+    pub slashed_validator_count: u32,
+    pub slashed_amount: u128,
+}
+
+/// Network-wide offence and slash statistics in a range of eras.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct OffenceReport {
+    pub start_era_index: u32,
+    pub end_era_index: u32,
+    pub eras: Vec<EraOffenceReport>,
+    pub total_offence_count: u32,
+    pub total_offence_counts_by_kind: BTreeMap<String, u32>,
+    pub total_offline_offence_count: u32,
+    pub total_slash_count: u32,
+    pub total_slashed_amount: u128,
+}
+
+/// Network summary of an ended era, published by `subvt-era-summary-publisher`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraSummary {
+    pub report: EraReport,
+    /// Total stake of the previous era, for the stake change.
+    pub previous_total_stake: Option<u128>,
+    pub active_validator_count: u32,
+    /// Validators that were active in the era, but not in the previous era.
+    pub new_active_validator_account_ids: Vec<AccountId>,
+    /// Validators with the most reward points in the era, descending.
+    pub points_leaders: Vec<EraPointsLeader>,
+    #[serde(default)]
+    pub offences: EraOffenceReport,
+}
+
+/// Report of a validator in an archived era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ArchivedEraValidatorReport {
+    pub account_id: AccountId,
+    #[serde(flatten)]
+    pub report: EraValidatorReport,
+}
+
+/// Reports of an ended era, archived by `subvt-era-report-archiver`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraReportArchive {
+    pub report: EraReport,
+    pub offences: EraOffenceReport,
+    pub validators: Vec<ArchivedEraValidatorReport>,
+}
+
+/// An archived file of an era, with its location in the archive target, i.e. the object URL
+/// for S3-compatible storage and the `ipfs://` URI for IPFS.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ArchivedEraReportFile {
+    pub file_name: String,
+    pub location: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ArchivedEraReport {
+    pub era_index: u32,
+    pub files: Vec<ArchivedEraReportFile>,
+}
+
+/// Index of all the archived eras in an archive target, uploaded after every archived era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraReportArchiveManifest {
+    pub chain: String,
+    pub updated_at: u64,
+    pub eras: Vec<ArchivedEraReport>,
+}
+
+/// Election snapshot that was used to select the active validator set of an era:
+/// all voters with their vote weights and nominations, and all validator candidates.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EraElectionReport {
+    pub era_index: u32,
+    pub total_voter_stake: u128,
+    pub targets: Vec<AccountId>,
+    pub voters: Vec<ElectionVoter>,
+}
+
+/// Nominator churn of a validator in a single era, compared to the previous era in which
+/// the validator had an exposure.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct EraValidatorNominatorChurn {
+    pub era_index: u32,
+    pub nominator_count: u32,
+    pub joined_nominator_count: u32,
+    pub left_nominator_count: u32,
+    /// Average number of consecutive eras (within the report range) the nominators
+    /// in the era's exposure have been backing the validator.
+    pub average_nominator_tenure_eras: f64,
+}
+
+/// Nominator churn and loyalty report of a validator in a range of eras, computed from the
+/// per-era exposures. Eras in which the validator had no exposure are omitted.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ValidatorNominatorChurnReport {
+    pub start_era_index: u32,
+    pub end_era_index: u32,
+    pub eras: Vec<EraValidatorNominatorChurn>,
+    pub total_joined_nominator_count: u32,
+    pub total_left_nominator_count: u32,
+    /// Average length in eras of all the nominations in the report range. Nominations that
+    /// started before or continue after the range are counted only within the range.
+    pub average_nominator_tenure_eras: f64,
+}
+
+/// Payout of an era in which the validator was active.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraValidatorPayoutLag {
+    pub era_index: u32,
+    /// Era of the first successful `payout_stakers` call for the era, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_era_index: Option<u32>,
+    /// Number of eras that elapsed after the end of the era before the payout, zero when paid
+    /// out in the next era.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payout_lag_eras: Option<u32>,
+}
+
+/// Payout promptness report of a validator in a range of eras. Eras in which the validator
+/// was not active are omitted, and the statistics are computed over the paid eras.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ValidatorPayoutLagReport {
+    pub start_era_index: u32,
+    pub end_era_index: u32,
+    pub eras: Vec<EraValidatorPayoutLag>,
+    pub paid_era_count: u32,
+    pub unpaid_era_count: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub average_payout_lag_eras: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub median_payout_lag_eras: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub p90_payout_lag_eras: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_payout_lag_eras: Option<u32>,
+}
+
+/// Identity change of an account, with the block it happened in.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct AccountIdentityChange {
+    pub account_id: AccountId,
+    pub block_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    #[serde(flatten)]
+    pub change: IdentityChange,
+}
+
+/// Keyset pagination cursor over the block/event history, the `(block_number, index)` key of
+/// the last item of a page. Encoded as an opaque hex string in the API.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct HistoryCursor {
+    pub block_number: u64,
+    /// Index of the event (or extrinsic) in the block.
+    pub index: u32,
+}
+
+impl HistoryCursor {
+    pub fn encode(&self) -> String {
+        let mut bytes = self.block_number.to_be_bytes().to_vec();
+        bytes.extend_from_slice(&self.index.to_be_bytes());
+        hex::encode(bytes)
+    }
+
+    pub fn decode(cursor: &str) -> anyhow::Result<Self> {
+        let bytes = hex::decode(cursor)?;
+        if bytes.len() != 12 {
+            return Err(anyhow::anyhow!("Invalid cursor length: {}.", bytes.len()));
+        }
+        Ok(Self {
+            block_number: u64::from_be_bytes(bytes[..8].try_into()?),
+            index: u32::from_be_bytes(bytes[8..].try_into()?),
+        })
+    }
+}
+
+/// A page of history items. `next_cursor` is `None` on the last page.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct HistoryPage<T> {
+    pub items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// Hypothetical nomination to be projected: the stake and the candidate validators.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RewardProjectionRequest {
+    pub stake: u128,
+    pub validator_account_ids: Vec<AccountId>,
+    /// Number of most recent eras with complete reward data to base the projection on.
+    /// Defaults to the configured value when omitted.
+    #[serde(default)]
+    pub era_count: Option<u32>,
+}
+
+/// Projected reward from a single candidate validator for the part of the stake allocated to it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct ValidatorRewardProjection {
+    pub account_id: AccountId,
+    pub allocated_stake: u128,
+    /// Number of eras in the projection range in which the validator was active.
+    pub active_era_count: u32,
+    pub average_reward_points: u32,
+    pub average_commission_per_billion: u32,
+    /// Average total backing stake of the validator in the eras it was active, without
+    /// the allocated stake.
+    pub average_total_stake: u128,
+    pub expected_era_reward: u128,
+}
+
+/// Expected rewards of a hypothetical nomination, based on the reward points distribution,
+/// commissions and backing stakes of the candidate validators in recent eras. The stake is
+/// assumed to be distributed evenly among the candidates, and the eras in which a candidate
+/// was not active contribute no reward.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct RewardProjection {
+    pub stake: u128,
+    pub start_era_index: u32,
+    pub end_era_index: u32,
+    pub era_count: u32,
+    pub average_era_duration_millis: u64,
+    pub expected_era_reward: u128,
+    pub expected_annual_reward: u128,
+    /// Expected annual reward over the stake, e.g. `0.15` for 15%.
+    pub expected_annual_return_rate: f64,
+    pub validators: Vec<ValidatorRewardProjection>,
+}
+
+/// Validator performance and nominator returns in a commission band, `[min, max)` except for
+/// the last band, which includes the maximum. Each validator era is assigned to a band by the
+/// commission of the validator in that era, so a validator that has changed its commission may
+/// be counted in more than one band.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CommissionBandComparison {
+    pub min_commission_per_billion: u32,
+    pub max_commission_per_billion: u32,
+    pub validator_count: u32,
+    pub validator_era_count: u32,
+    pub average_reward_points: u32,
+    /// Average nominator reward over the backing stake in an era, after commission.
+    pub average_era_return_rate: f64,
+    /// Average era return rate, annualized by the average era duration.
+    pub average_annual_return_rate: f64,
+}
+
+/// Comparison of the active validators in commission bands over a range of eras.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CommissionComparisonReport {
+    pub start_era_index: u32,
+    pub end_era_index: u32,
+    pub era_count: u32,
+    pub average_era_duration_millis: u64,
+    pub bands: Vec<CommissionBandComparison>,
+}
+
+/// Nominators of a validator whose active stakes are in the `[min_stake, max_stake)` range.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NominatorStakeBucket {
+    pub min_stake: Balance,
+    pub max_stake: Balance,
+    pub nominator_count: u32,
+    /// Number of the nominators in the bucket that are in the rewarded set.
+    pub rewarded_nominator_count: u32,
+    pub total_stake: Balance,
+}
+
+/// Distribution of the active stakes of the current nominators of a validator in buckets of
+/// increasing orders of magnitude, the first one being `[0, 1)` in token units. When the
+/// validator is oversubscribed, only the largest `max_rewarded_nominator_count` nominations
+/// get rewarded, and a nomination needs to exceed `min_rewarded_stake` to get into the
+/// rewarded set.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorNominatorDistribution {
+    pub account_id: AccountId,
+    pub finalized_block_number: u64,
+    pub nominator_count: u32,
+    pub total_nominator_stake: Balance,
+    /// `None` if all nominators get rewarded regardless of their count.
+    pub max_rewarded_nominator_count: Option<u32>,
+    pub is_oversubscribed: bool,
+    /// Minimum active stake in the rewarded set, `None` if there are no nominators.
+    pub min_rewarded_stake: Option<Balance>,
+    pub buckets: Vec<NominatorStakeBucket>,
+}
+
+/// Fee economics of a block: the totals of the signed extrinsics, and the fee of each.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct BlockEconomicsReport {
+    pub block_hash: String,
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub author_account_id: Option<AccountId>,
+    pub signed_extrinsic_count: u32,
+    pub total_fee: u128,
+    pub total_tip: u128,
+    pub total_author_fee_share: u128,
+    pub total_treasury_fee_share: u128,
+    pub total_weight: u64,
+    pub extrinsic_fees: Vec<ExtrinsicFee>,
+}
+
+/// Number of validators located in a region of a country.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RegionValidatorCount {
+    pub region_code: String,
+    pub validator_count: u32,
+}
+
+/// Number of validators located in a country. Regions with fewer validators than the
+/// configured minimum are counted only at the country level.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CountryValidatorCount {
+    pub country_code: String,
+    pub validator_count: u32,
+    pub regions: Vec<RegionValidatorCount>,
+}
+
+/// Geographical distribution of the active validators of an era, based on the latest
+/// resolved validator locations. Countries are sorted by validator count, descending.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DecentralizationReport {
+    pub era_index: u32,
+    pub active_validator_count: u32,
+    pub located_validator_count: u32,
+    /// Minimum number of countries that host more than one third of the located validators.
+    pub country_nakamoto_coefficient: u32,
+    pub countries: Vec<CountryValidatorCount>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PortfolioRequest {
+    pub stash_account_ids: Vec<AccountId>,
+    /// Fiat currency code for the fiat equivalents of the totals, e.g. `usd`.
+    #[serde(default)]
+    pub currency_code: Option<String>,
+}
+
+/// Estimated reward of a nominator from an active era of a validator, for which
+/// the validator hasn't been paid out yet.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PendingReward {
+    pub era_index: u32,
+    pub validator_account_id: AccountId,
+    pub amount: Balance,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PortfolioEventType {
+    Rewarded,
+    Slashed,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PortfolioEvent {
+    pub stash_account_id: AccountId,
+    pub event_type: PortfolioEventType,
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub amount: Balance,
+}
+
+/// Staking reward of an account, an entry in the reward ledger that combines the `Rewarded`
+/// event with its payout (era and validator) and the reward destination of the account at
+/// the block of the payout. Payout and destination are unknown for the rewards indexed before
+/// the ledger.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct RewardLedgerEntry {
+    pub account_id: AccountId,
+    pub block_number: u64,
+    pub timestamp: Option<u64>,
+    pub event_index: u32,
+    pub era_index: Option<u32>,
+    pub validator_account_id: Option<AccountId>,
+    pub amount: Balance,
+    pub reward_destination: Option<RewardDestination>,
+    /// Account that has received the reward, the stash account for the restaked rewards.
+    pub destination_account_id: Option<AccountId>,
+    pub is_restaked: bool,
+    /// Token price in the requested fiat currency at the time of the reward, i.e. the latest
+    /// price before the block timestamp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_price: Option<f64>,
+}
+
+/// Total amount that becomes withdrawable at the start of an era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct UnbondingScheduleItem {
+    pub era_index: u32,
+    pub amount: Balance,
+    /// Estimated start of the era, see `UnlockChunk`.
+    pub estimated_unlock_timestamp: u64,
+}
+
+/// Staking status of a single stash account in a portfolio.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PortfolioAccount {
+    pub stash_account_id: AccountId,
+    /// `None` if the account is not bonded.
+    pub controller_account_id: Option<AccountId>,
+    pub bonded_amount: Balance,
+    pub active_amount: Balance,
+    pub unlocking: Vec<UnlockChunk>,
+    /// Validators nominated by the account.
+    pub nominee_account_ids: Vec<AccountId>,
+    /// Nominees that have an active stake from the account in the active era.
+    pub active_validator_account_ids: Vec<AccountId>,
+    pub pending_reward: Balance,
+    pub pending_rewards: Vec<PendingReward>,
+}
+
+/// Aggregated staking status of a set of stash accounts, for watch-only nominator dashboards.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Portfolio {
+    pub active_era_index: u32,
+    pub total_bonded_amount: Balance,
+    pub total_active_amount: Balance,
+    pub total_unlocking_amount: Balance,
+    pub total_pending_reward: Balance,
+    pub accounts: Vec<PortfolioAccount>,
+    /// Unbonding amounts of all accounts, summed per era, earliest first.
+    pub unbonding_schedule: Vec<UnbondingScheduleItem>,
+    /// Most recent reward and slash events of all accounts, latest first.
+    pub recent_events: Vec<PortfolioEvent>,
+    /// Latest token price in the requested currency, `None` if no currency is requested
+    /// or there is no price for the currency.
+    pub token_price: Option<TokenPrice>,
+    pub total_bonded_amount_fiat: Option<f64>,
+    pub total_pending_reward_fiat: Option<f64>,
+}
+
+/// Nomination pool statistics at the finalized block, along with the pools, ordered by id.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NominationPoolReport {
+    pub active_era_index: u32,
+    pub pool_count: u32,
+    pub open_pool_count: u32,
+    pub member_count: u32,
+    pub total_bonded_amount: Balance,
+    /// Number of the distinct validators nominated by at least one pool.
+    pub nominated_validator_count: u32,
+    pub pools: Vec<NominationPool>,
+}
+
+impl NominationPoolReport {
+    pub fn new(active_era_index: u32, pools: Vec<NominationPool>) -> Self {
+        let nominated_validator_count = pools
+            .iter()
+            .flat_map(|pool| pool.nominated_validator_account_ids.iter())
+            .collect::<std::collections::HashSet<&AccountId>>()
+            .len() as u32;
+        NominationPoolReport {
+            active_era_index,
+            pool_count: pools.len() as u32,
+            open_pool_count: pools
+                .iter()
+                .filter(|pool| pool.state == NominationPoolState::Open)
+                .count() as u32,
+            member_count: pools.iter().map(|pool| pool.member_count).sum(),
+            total_bonded_amount: pools.iter().map(|pool| pool.bonded_amount).sum(),
+            nominated_validator_count,
+            pools,
+        }
+    }
+}
+
+/// Reason of a change in the active validator set, where derivable from the indexed data.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSetChangeReason {
+    /// Joined the set by getting elected.
+    Elected,
+    /// Still a validator candidate, but not elected.
+    NotElected,
+    /// Chilled during the previous era.
+    Chilled,
+    /// Slashed during the previous era.
+    Slashed,
+    /// Elected, but left out of the session validators for not having session keys.
+    SessionKeysMissing,
+    Unknown,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionSetChange {
+    pub validator_account_id: AccountId,
+    pub reason: SessionSetChangeReason,
+}
+
+/// Validators that joined or left the active validator set at the start of a session. The set
+/// only changes at the first session of an era, so both lists are empty for the other sessions.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionSetChangeReport {
+    pub session_index: u32,
+    pub era_index: u32,
+    pub previous_era_index: u32,
+    pub joined: Vec<SessionSetChange>,
+    pub left: Vec<SessionSetChange>,
+}
+
+/// Commission of a validator as of an era in which it differs from the previous era with known
+/// preferences. The first known era of the validator is always included, without the previous
+/// values. See `ValidatorCommissionHistoryReport`.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorCommissionChange {
+    pub era_index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_era_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_commission_per_billion: Option<u32>,
+    pub commission_per_billion: u32,
+    pub blocks_nominations: bool,
+}
+
+/// Per-era commission history of a validator, read from the `ErasValidatorPrefs` storage of the
+/// staking pallet. Eras before the history depth of the first indexed era are not available.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorCommissionHistoryReport {
+    pub validator_account_id: AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_era_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_era_index: Option<u32>,
+    pub changes: Vec<ValidatorCommissionChange>,
+}
+
+/// Item of the validator onboarding checklist.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorOnboardingItemCode {
+    /// Has called `validate`, i.e. is in the validator list.
+    ValidationIntent,
+    /// Has set the session keys for the next session.
+    SessionKeys,
+    /// Has an identity, or a parent identity, confirmed by a registrar.
+    Identity,
+    /// Commission is within the recommended band.
+    Commission,
+    /// Active self-stake is at least the minimum validator bond.
+    SelfStake,
+    /// Has a node visible on Telemetry with the stash or the controller address.
+    Telemetry,
+    /// Is a valid 1KV candidate. Only on the chains with 1KV.
+    #[serde(rename = "onekv")]
+    OneKV,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorOnboardingItemStatus {
+    Passed,
+    Failed,
+    /// Cannot be evaluated, e.g. the account has no validation intent yet.
+    Unknown,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorOnboardingItem {
+    pub code: ValidatorOnboardingItemCode,
+    pub status: ValidatorOnboardingItemStatus,
+    /// Human-readable explanation of a failed or unknown item, or the evaluated value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+/// Onboarding checklist of a prospective validator, evaluated from the latest validator list
+/// snapshot and the indexed Telemetry and 1KV data. The checklist is complete when all the
+/// items have passed.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ValidatorOnboardingChecklist {
+    pub account_id: AccountId,
+    pub finalized_block_number: u64,
+    pub is_complete: bool,
+    pub items: Vec<ValidatorOnboardingItem>,
+}
+
+/// Governance pallet of a governance activity.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GovernancePallet {
+    Democracy,
+    ConvictionVoting,
+    Referenda,
+}
+
+impl FromStr for GovernancePallet {
+    type Err = anyhow::Error;
+
+    fn from_str(pallet_name: &str) -> Result<Self, Self::Err> {
+        match pallet_name {
+            "democracy" => Ok(GovernancePallet::Democracy),
+            "conviction_voting" => Ok(GovernancePallet::ConvictionVoting),
+            "referenda" => Ok(GovernancePallet::Referenda),
+            _ => Err(anyhow::anyhow!(
+                "Unknown governance pallet: {}",
+                pallet_name
+            )),
+        }
+    }
+}
+
+/// Type of a governance activity, see `GovernanceActivity`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GovernanceActivityType {
+    Vote,
+    Delegation,
+    Undelegation,
+    /// Democracy proposal or referenda submission.
+    Proposal,
+    /// Seconding of a democracy proposal.
+    Second,
+    /// Decision deposit of a referendum.
+    DecisionDeposit,
+}
+
+impl GovernanceActivityType {
+    /// Activity type of a governance call by the call name, as stored in the governance calls
+    /// table.
+    pub fn from_call_name(call_name: &str) -> anyhow::Result<Self> {
+        match call_name {
+            "vote" => Ok(GovernanceActivityType::Vote),
+            "delegate" => Ok(GovernanceActivityType::Delegation),
+            "undelegate" => Ok(GovernanceActivityType::Undelegation),
+            "propose" | "submit" => Ok(GovernanceActivityType::Proposal),
+            "second" => Ok(GovernanceActivityType::Second),
+            "place_decision_deposit" => Ok(GovernanceActivityType::DecisionDeposit),
+            _ => Err(anyhow::anyhow!("Unknown governance call: {}", call_name)),
+        }
+    }
+}
+
+/// Successful governance call of an account: a vote, a delegation, a proposal or a deposit
+/// in the democracy, conviction voting or referenda pallet. Calls made through a proxy or a
+/// multisig are attributed to the proxied or the multisig account.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct GovernanceActivity {
+    pub activity_type: GovernanceActivityType,
+    pub pallet: GovernancePallet,
+    pub block_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    pub is_nested_call: bool,
+    /// Voted referendum (poll), submitted referendum or the referendum of a decision deposit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referendum_index: Option<u32>,
+    /// Seconded democracy proposal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proposal_index: Option<u32>,
+    /// Conviction voting or referenda track.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub track_id: Option<u16>,
+    /// Delegation target.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_account_id: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vote: Option<DemocracyVote>,
+    /// Conviction of a delegation, the conviction of a vote is in the vote.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conviction: Option<u8>,
+    /// Delegated balance or proposal deposit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Balance>,
+}
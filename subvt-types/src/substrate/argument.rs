@@ -6,6 +6,7 @@ use crate::{
         extrinsic::SubstrateExtrinsic,
         legacy::{DefunctVoter, ElectionSize, LegacyValidatorPrefs, ReadySolution, ValidatorIndex},
         metadata::{ArgumentMeta, Metadata},
+        value::Value,
         CallHash, Chain, MultiAddress, OpaqueTimeSlot, ProxyType, RewardDestination, SlotRange,
         ValidatorPreferences,
     },
@@ -175,6 +176,8 @@ pub enum ArgumentPrimitive {
     ValidationCode(ValidationCode),
     ValidatorIndex(ValidatorIndex),
     ValidatorPreferences(ValidatorPreferences),
+    /// Argument without a typed representation, decoded from the runtime type registry.
+    Value(Value),
     VersionedMultiAssets(Box<xcm::VersionedMultiAssets>),
     VersionedMultiLocation(xcm::VersionedMultiLocation),
     VersionedXcm(Box<xcm::VersionedXcm<()>>),
@@ -218,6 +221,43 @@ pub fn extract_optional_argument_primitive(
     }
 }
 
+/// Unsigned integer argument, either typed or decoded from the runtime type registry. Used for
+/// the indices and balances of the calls and events whose argument types change between
+/// runtime versions.
+pub fn extract_unsigned_argument(argument: &Argument) -> Result<u128, DecodeError> {
+    let maybe_unsigned = match extract_argument_primitive(argument)? {
+        ArgumentPrimitive::Balance(balance) => Some(balance),
+        ArgumentPrimitive::CompactBalance(balance) => Some(balance.0),
+        ArgumentPrimitive::CompactDemocracyProposalIndex(index) => Some(index.0 as u128),
+        ArgumentPrimitive::CompactReferendumIndex(index) => Some(index.0 as u128),
+        ArgumentPrimitive::CompactU32(value) => Some(value.0 as u128),
+        ArgumentPrimitive::DemocracyProposalIndex(index) => Some(index as u128),
+        ArgumentPrimitive::ReferendumIndex(index) => Some(index as u128),
+        ArgumentPrimitive::U8(value) => Some(value as u128),
+        ArgumentPrimitive::U16(value) => Some(value as u128),
+        ArgumentPrimitive::U32(value) => Some(value as u128),
+        ArgumentPrimitive::U64(value) => Some(value as u128),
+        ArgumentPrimitive::Value(value) => value.as_unsigned(),
+        _ => None,
+    };
+    maybe_unsigned
+        .ok_or_else(|| DecodeError::Error(format!("Cannot get unsigned argument {:?}.", argument)))
+}
+
+/// Account id argument, either typed (`AccountId` or `MultiAddress`) or decoded from the runtime
+/// type registry.
+pub fn extract_account_id_argument(argument: &Argument) -> Result<AccountId, DecodeError> {
+    let maybe_account_id = match extract_argument_primitive(argument)? {
+        ArgumentPrimitive::AccountId(account_id) => Some(account_id),
+        ArgumentPrimitive::MultiAddress(address) => address.get_account_id(),
+        ArgumentPrimitive::Value(value) => value.as_account_id(),
+        _ => None,
+    };
+    maybe_account_id.ok_or_else(|| {
+        DecodeError::Error(format!("Cannot get account id argument {:?}.", argument))
+    })
+}
+
 macro_rules! get_argument_primitive {
     ($argument_expr: expr, $argument_primitive_type: ident) => {{
         let argument_primitive =
@@ -447,6 +487,32 @@ generate_argument_primitive_decoder_impl! {[
     ("Response", decode_xcm_response, XcmResponse),
 ]}
 
+/// Argument types that cannot be decoded by `Argument::decode`.
+const UNSUPPORTED_PRIMITIVE_NAMES: [&str; 7] = [
+    "sp_std::marker::PhantomData<(AccountId, Event)>",
+    "Box<RawSolution<CompactOf<T>>>",
+    "Box<RawSolution<SolutionOf<T>>>",
+    "RawSolution<CompactOf<T>>",
+    "CompactAssignments",
+    "Box<T::PalletsOrigin>",
+    "ChangesTrieConfiguration",
+];
+/// Argument types that are decoded as calls.
+const CALL_PRIMITIVE_NAMES: [&str; 8] = [
+    "Box<<T as Config>::Call>",
+    "Box<<T as Trait>::Call>",
+    "<T as Trait>::Call",
+    "<T as Config>::Call",
+    "Box<<T as Config<I>>::Proposal>",
+    "Box<<T as Trait<I>>::Proposal>",
+    "OpaqueCall",
+    "OpaqueCall<T>",
+];
+const LOOKUP_SOURCE_PRIMITIVE_NAME: &str = "<T::Lookup as StaticLookup>::Source";
+const VALIDATOR_PREFS_PRIMITIVE_NAME: &str = "ValidatorPrefs";
+const MULTI_LOCATION_PRIMITIVE_NAMES: [&str; 2] = ["MultiLocation", "Box<MultiLocation>"];
+const XCM_PRIMITIVE_NAMES: [&str; 2] = ["Xcm<()>", "Box<Xcm<T::Call>>"];
+
 #[derive(thiserror::Error, Clone, Debug)]
 pub enum ArgumentDecodeError {
     #[error("Decode error: {0}")]
@@ -458,6 +524,49 @@ pub enum ArgumentDecodeError {
 }
 
 impl Argument {
+    /// Whether all the primitive types of the argument are known to `Argument::decode`.
+    /// Arguments that aren't get decoded from the runtime type registry when available, see
+    /// `Argument::decode_from_registry`.
+    pub fn is_decodable(argument_meta: &ArgumentMeta) -> bool {
+        argument_meta.get_primitive_name_set().iter().all(|name| {
+            let name = name.as_str();
+            if UNSUPPORTED_PRIMITIVE_NAMES.contains(&name) {
+                return false;
+            }
+            if CALL_PRIMITIVE_NAMES.contains(&name)
+                || name == LOOKUP_SOURCE_PRIMITIVE_NAME
+                || name == VALIDATOR_PREFS_PRIMITIVE_NAME
+                || MULTI_LOCATION_PRIMITIVE_NAMES.contains(&name)
+                || XCM_PRIMITIVE_NAMES.contains(&name)
+            {
+                return true;
+            }
+            let mut empty_bytes: &[u8] = &[];
+            !matches!(
+                ArgumentPrimitive::decode(name, &mut empty_bytes),
+                Err(ArgumentDecodeError::UnknownPrimitiveType(_))
+            )
+        })
+    }
+
+    /// Decodes an argument of the given type from the runtime type registry, which is only
+    /// available with metadata v14 and later.
+    pub fn decode_from_registry(
+        metadata: &Metadata,
+        type_id: u32,
+        bytes: &mut &[u8],
+    ) -> anyhow::Result<Self, ArgumentDecodeError> {
+        match &metadata.type_registry {
+            Some(type_registry) => Ok(Argument::Primitive(Box::new(ArgumentPrimitive::Value(
+                Value::decode(type_registry, type_id, bytes)?,
+            )))),
+            None => Err(ArgumentDecodeError::UnsupportedPrimitiveType(format!(
+                "Registry type #{}",
+                type_id
+            ))),
+        }
+    }
+
     fn decode_validator_prefs(
         chain: &Chain,
         metadata: &Metadata,
@@ -607,26 +716,11 @@ impl Argument {
                 Ok(Argument::Tuple(result))
             }
             ArgumentMeta::Primitive(name) => {
-                if name == "sp_std::marker::PhantomData<(AccountId, Event)>"
-                    || name == "Box<RawSolution<CompactOf<T>>>"
-                    || name == "Box<RawSolution<SolutionOf<T>>>"
-                    || name == "RawSolution<CompactOf<T>>"
-                    || name == "CompactAssignments"
-                    || name == "Box<T::PalletsOrigin>"
-                    || name == "ChangesTrieConfiguration"
-                {
+                if UNSUPPORTED_PRIMITIVE_NAMES.contains(&name.as_str()) {
                     Err(ArgumentDecodeError::UnsupportedPrimitiveType(
                         name.to_string(),
                     ))
-                } else if name == "Box<<T as Config>::Call>"
-                    || name == "Box<<T as Trait>::Call>"
-                    || name == "<T as Trait>::Call"
-                    || name == "<T as Config>::Call"
-                    || name == "Box<<T as Config<I>>::Proposal>"
-                    || name == "Box<<T as Trait<I>>::Proposal>"
-                    || name == "OpaqueCall"
-                    || name == "OpaqueCall<T>"
-                {
+                } else if CALL_PRIMITIVE_NAMES.contains(&name.as_str()) {
                     if name == "OpaqueCall" || name == "OpaqueCall<T>" {
                         let vector_length_result: Result<Compact<u64>, _> =
                             Decode::decode(&mut *bytes);
@@ -654,7 +748,7 @@ impl Argument {
                             name, decode_error
                         ))),
                     }
-                } else if name == "<T::Lookup as StaticLookup>::Source" {
+                } else if name == LOOKUP_SOURCE_PRIMITIVE_NAME {
                     if metadata.is_signer_address_multi(chain) {
                         match MultiAddress::decode(&mut *bytes) {
                             Ok(multi_address) => {
@@ -678,11 +772,11 @@ impl Argument {
                             )),
                         }
                     }
-                } else if name == "ValidatorPrefs" {
+                } else if name == VALIDATOR_PREFS_PRIMITIVE_NAME {
                     Argument::decode_validator_prefs(chain, metadata, &mut *bytes)
-                } else if name == "MultiLocation" || name == "Box<MultiLocation>" {
+                } else if MULTI_LOCATION_PRIMITIVE_NAMES.contains(&name.as_str()) {
                     Argument::decode_multi_location(metadata, &mut *bytes)
-                } else if name == "Xcm<()>" || name == "Box<Xcm<T::Call>>" {
+                } else if XCM_PRIMITIVE_NAMES.contains(&name.as_str()) {
                     Argument::decode_xcm(metadata, &mut *bytes)
                 } else {
                     match ArgumentPrimitive::decode(name, &mut *bytes) {
@@ -4,8 +4,8 @@ use crate::{
     crypto::AccountId,
     substrate::{
         argument::{
-            get_argument_primitive, get_argument_vector, Argument, ArgumentPrimitive,
-            IdentificationTuple,
+            extract_unsigned_argument, get_argument_primitive, get_argument_vector, Argument,
+            ArgumentPrimitive, IdentificationTuple,
         },
         error::DecodeError,
         metadata::Metadata,
@@ -13,13 +13,15 @@ use crate::{
     },
 };
 use frame_support::dispatch::{DispatchError, DispatchInfo};
-use log::{debug, error};
+use log::{debug, error, warn};
+use pallet_democracy::{ReferendumIndex, VoteThreshold};
 use pallet_identity::RegistrarIndex;
 use pallet_staking::EraIndex;
 use parity_scale_codec::{Compact, Decode};
 use polkadot_primitives::v1::{CandidateReceipt, CoreIndex, GroupIndex, HeadData, Id};
 use sp_staking::offence::Kind;
 use sp_staking::SessionIndex;
+use std::collections::HashMap;
 
 #[derive(Debug)]
 pub enum BalancesEvent {
@@ -72,6 +74,78 @@ impl BalancesEvent {
     }
 }
 
+#[derive(Debug)]
+pub enum DemocracyEvent {
+    Cancelled {
+        extrinsic_index: Option<u32>,
+        referendum_index: ReferendumIndex,
+    },
+    NotPassed {
+        extrinsic_index: Option<u32>,
+        referendum_index: ReferendumIndex,
+    },
+    Passed {
+        extrinsic_index: Option<u32>,
+        referendum_index: ReferendumIndex,
+    },
+    Started {
+        extrinsic_index: Option<u32>,
+        referendum_index: ReferendumIndex,
+        vote_threshold: VoteThreshold,
+    },
+}
+
+impl DemocracyEvent {
+    pub fn from(
+        name: &str,
+        extrinsic_index: Option<u32>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateEvent>, DecodeError> {
+        let maybe_event = match name {
+            "Cancelled" => Some(SubstrateEvent::Democracy(DemocracyEvent::Cancelled {
+                extrinsic_index,
+                referendum_index: get_argument_primitive!(&arguments[0], ReferendumIndex),
+            })),
+            "NotPassed" => Some(SubstrateEvent::Democracy(DemocracyEvent::NotPassed {
+                extrinsic_index,
+                referendum_index: get_argument_primitive!(&arguments[0], ReferendumIndex),
+            })),
+            "Passed" => Some(SubstrateEvent::Democracy(DemocracyEvent::Passed {
+                extrinsic_index,
+                referendum_index: get_argument_primitive!(&arguments[0], ReferendumIndex),
+            })),
+            "Started" => Some(SubstrateEvent::Democracy(DemocracyEvent::Started {
+                extrinsic_index,
+                referendum_index: get_argument_primitive!(&arguments[0], ReferendumIndex),
+                vote_threshold: get_argument_primitive!(&arguments[1], DemocracyVoteThreshold),
+            })),
+            _ => None,
+        };
+        Ok(maybe_event)
+    }
+}
+
+#[derive(Debug)]
+pub enum ElectionProviderMultiPhaseEvent {
+    ElectionFinalized { extrinsic_index: Option<u32> },
+}
+
+impl ElectionProviderMultiPhaseEvent {
+    pub fn from(
+        name: &str,
+        extrinsic_index: Option<u32>,
+        _arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateEvent>, DecodeError> {
+        let maybe_event = match name {
+            "ElectionFinalized" => Some(SubstrateEvent::ElectionProviderMultiPhase(
+                ElectionProviderMultiPhaseEvent::ElectionFinalized { extrinsic_index },
+            )),
+            _ => None,
+        };
+        Ok(maybe_event)
+    }
+}
+
 #[derive(Debug)]
 pub enum IdentityEvent {
     IdentityCleared {
@@ -384,6 +458,33 @@ impl ParachainsEvent {
     }
 }
 
+#[derive(Debug)]
+pub enum ReferendaEvent {
+    Submitted {
+        extrinsic_index: Option<u32>,
+        referendum_index: u32,
+        track_id: u16,
+    },
+}
+
+impl ReferendaEvent {
+    pub fn from(
+        name: &str,
+        extrinsic_index: Option<u32>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateEvent>, DecodeError> {
+        let maybe_event = match name {
+            "Submitted" => Some(SubstrateEvent::Referenda(ReferendaEvent::Submitted {
+                extrinsic_index,
+                referendum_index: extract_unsigned_argument(&arguments[0])? as u32,
+                track_id: extract_unsigned_argument(&arguments[1])? as u16,
+            })),
+            _ => None,
+        };
+        Ok(maybe_event)
+    }
+}
+
 #[derive(Debug)]
 pub enum SessionEvent {
     NewSession {
@@ -597,6 +698,63 @@ impl SystemEvent {
     }
 }
 
+#[derive(Debug)]
+pub enum TransactionPaymentEvent {
+    /// Emitted by the newer runtimes only. `actual_fee` includes the tip.
+    TransactionFeePaid {
+        extrinsic_index: Option<u32>,
+        account_id: AccountId,
+        actual_fee: Balance,
+        tip: Balance,
+    },
+}
+
+impl TransactionPaymentEvent {
+    pub fn from(
+        name: &str,
+        extrinsic_index: Option<u32>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateEvent>, DecodeError> {
+        let maybe_event = match name {
+            "TransactionFeePaid" => Some(SubstrateEvent::TransactionPayment(
+                TransactionPaymentEvent::TransactionFeePaid {
+                    extrinsic_index,
+                    account_id: get_argument_primitive!(&arguments[0], AccountId),
+                    actual_fee: get_argument_primitive!(&arguments[1], Balance),
+                    tip: get_argument_primitive!(&arguments[2], Balance),
+                },
+            )),
+            _ => None,
+        };
+        Ok(maybe_event)
+    }
+}
+
+#[derive(Debug)]
+pub enum TreasuryEvent {
+    Deposit {
+        extrinsic_index: Option<u32>,
+        amount: Balance,
+    },
+}
+
+impl TreasuryEvent {
+    pub fn from(
+        name: &str,
+        extrinsic_index: Option<u32>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateEvent>, DecodeError> {
+        let maybe_event = match name {
+            "Deposit" => Some(SubstrateEvent::Treasury(TreasuryEvent::Deposit {
+                extrinsic_index,
+                amount: get_argument_primitive!(&arguments[0], Balance),
+            })),
+            _ => None,
+        };
+        Ok(maybe_event)
+    }
+}
+
 #[derive(Debug)]
 pub enum UtilityEvent {
     ItemCompleted {
@@ -636,18 +794,198 @@ impl UtilityEvent {
     }
 }
 
+/// Typed decoder of the events of a pallet. Gets the event name, the extrinsic index and the
+/// decoded arguments of the event, and returns `None` for the events without a typed
+/// representation.
+type PalletEventDecoder =
+    fn(&str, Option<u32>, Vec<Argument>) -> Result<Option<SubstrateEvent>, DecodeError>;
+
+/// Pallets with typed events, along with the typed events of each pallet and the minimum number
+/// of arguments that the typed decoder reads for the event. Runtimes may append arguments to an
+/// event, so the argument count in the metadata is allowed to be larger. Legacy event names are
+/// listed along with the current ones.
+const TYPED_PALLET_EVENTS: &[(&str, PalletEventDecoder, &[(&str, usize)])] = &[
+    (
+        "Balances",
+        BalancesEvent::from,
+        &[("BalanceSet", 3), ("Deposit", 2), ("Transfer", 3)],
+    ),
+    (
+        "Democracy",
+        DemocracyEvent::from,
+        &[
+            ("Cancelled", 1),
+            ("NotPassed", 1),
+            ("Passed", 1),
+            ("Started", 2),
+        ],
+    ),
+    (
+        "ElectionProviderMultiPhase",
+        ElectionProviderMultiPhaseEvent::from,
+        &[("ElectionFinalized", 0)],
+    ),
+    (
+        "Identity",
+        IdentityEvent::from,
+        &[
+            ("IdentityCleared", 2),
+            ("IdentityKilled", 2),
+            ("IdentitySet", 1),
+            ("JudgementGiven", 2),
+            ("JudgementRequested", 2),
+            ("JudgementUnrequested", 2),
+            ("SubIdentityAdded", 3),
+            ("SubIdentityRemoved", 3),
+            ("SubIdentityRevoked", 3),
+        ],
+    ),
+    (
+        "ImOnline",
+        ImOnlineEvent::from,
+        &[("AllGood", 0), ("HeartbeatReceived", 1), ("SomeOffline", 1)],
+    ),
+    ("Offences", OffencesEvent::from, &[("Offence", 2)]),
+    (
+        "ParaInclusion",
+        ParachainInclusionEvent::from,
+        &[
+            ("CandidateBacked", 4),
+            ("CandidateIncluded", 4),
+            ("CandidateTimedOut", 3),
+        ],
+    ),
+    (
+        "Paras",
+        ParachainsEvent::from,
+        &[
+            ("CurrentHeadUpdated", 1),
+            ("CodeUpgradeScheduled", 1),
+            ("NewHeadNoted", 1),
+            ("CurrentCodeUpdated", 1),
+        ],
+    ),
+    ("Referenda", ReferendaEvent::from, &[("Submitted", 2)]),
+    ("Session", SessionEvent::from, &[("NewSession", 1)]),
+    (
+        "Staking",
+        StakingEvent::from,
+        &[
+            ("Bonded", 2),
+            ("Chilled", 1),
+            ("EraPaid", 3),
+            ("EraPayout", 3),
+            ("Kicked", 2),
+            ("OldSlashingReportDiscarded", 1),
+            ("PayoutStarted", 2),
+            ("Rewarded", 2),
+            ("Reward", 2),
+            ("Slashed", 2),
+            ("Slash", 2),
+            ("StakersElected", 0),
+            ("StakingElection", 0),
+            ("StakingElectionFailed", 0),
+            ("Unbonded", 2),
+            ("Withdrawn", 2),
+        ],
+    ),
+    (
+        "System",
+        SystemEvent::from,
+        &[
+            ("CodeUpdated", 0),
+            ("ExtrinsicSuccess", 1),
+            ("ExtrinsicFailed", 2),
+            ("KilledAccount", 1),
+            ("NewAccount", 1),
+        ],
+    ),
+    (
+        "TransactionPayment",
+        TransactionPaymentEvent::from,
+        &[("TransactionFeePaid", 3)],
+    ),
+    ("Treasury", TreasuryEvent::from, &[("Deposit", 1)]),
+    (
+        "Utility",
+        UtilityEvent::from,
+        &[
+            ("ItemCompleted", 0),
+            ("BatchInterrupted", 2),
+            ("BatchCompleted", 0),
+        ],
+    ),
+];
+
+/// Registry of the typed event decoders, keyed by the pallet and event indices of the runtime
+/// metadata it's built from. An event gets registered only if both its pallet and variant name
+/// have a typed representation and its metadata has enough arguments for the typed decoder, so
+/// renamed or reshaped events in a runtime upgrade get decoded as `SubstrateEvent::Other`
+/// instead of failing the decode.
+#[derive(Default)]
+pub struct EventRegistry {
+    decoders: HashMap<(u8, u8), PalletEventDecoder>,
+}
+
+impl EventRegistry {
+    pub fn from(metadata: &Metadata) -> Self {
+        let mut decoders = HashMap::new();
+        for (module_index, module) in &metadata.modules {
+            let (decoder, typed_events) = match TYPED_PALLET_EVENTS
+                .iter()
+                .find(|(pallet_name, _, _)| *pallet_name == module.name)
+            {
+                Some((_, decoder, typed_events)) => (*decoder, *typed_events),
+                None => continue,
+            };
+            for (event_index, event) in &module.events {
+                let min_argument_count = match typed_events
+                    .iter()
+                    .find(|(event_name, _)| *event_name == event.name)
+                {
+                    Some((_, min_argument_count)) => *min_argument_count,
+                    None => continue,
+                };
+                if event.arguments.len() < min_argument_count {
+                    warn!(
+                        "Event {}.{} has {} arguments, typed decoder expects at least {}. Will decode as raw event.",
+                        module.name,
+                        event.name,
+                        event.arguments.len(),
+                        min_argument_count,
+                    );
+                    continue;
+                }
+                decoders.insert((*module_index, *event_index), decoder);
+            }
+        }
+        EventRegistry { decoders }
+    }
+
+    fn get_decoder(&self, module_index: u8, event_index: u8) -> Option<PalletEventDecoder> {
+        self.decoders.get(&(module_index, event_index)).copied()
+    }
+}
+
 #[derive(Debug)]
 pub enum SubstrateEvent {
     Balances(BalancesEvent),
+    Democracy(DemocracyEvent),
+    ElectionProviderMultiPhase(ElectionProviderMultiPhaseEvent),
     Identity(IdentityEvent),
     ImOnline(ImOnlineEvent),
     Offences(OffencesEvent),
     ParachainInclusion(Box<ParachainInclusionEvent>),
     Parachains(ParachainsEvent),
+    Referenda(ReferendaEvent),
     Session(SessionEvent),
     Staking(StakingEvent),
     System(SystemEvent),
+    TransactionPayment(TransactionPaymentEvent),
+    Treasury(TreasuryEvent),
     Utility(UtilityEvent),
+    /// Any event without a typed representation, or a typed event whose arguments didn't match
+    /// the typed decoder, with its raw decoded arguments.
     Other {
         module_name: String,
         event_name: String,
@@ -687,7 +1025,15 @@ impl SubstrateEvent {
         };
         // decode arguments
         let mut arguments: Vec<Argument> = Vec::new();
-        for argument_meta in &event.arguments {
+        for (argument_index, argument_meta) in event.arguments.iter().enumerate() {
+            if let Some(Some(type_id)) = event.registry_type_ids.get(argument_index) {
+                arguments.push(Argument::decode_from_registry(
+                    metadata,
+                    *type_id,
+                    &mut *bytes,
+                )?);
+                continue;
+            }
             arguments.push(Argument::decode(
                 chain,
                 metadata,
@@ -698,22 +1044,23 @@ impl SubstrateEvent {
         }
         // decode topics - unused
         let _topics = Vec::<sp_core::H256>::decode(bytes)?;
-        // decode events
-        // debug!("Will decode {}.{}.", module.name, event.name);
-        let maybe_event = match module.name.as_str() {
-            "Balances" => BalancesEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "Identity" => IdentityEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "ImOnline" => ImOnlineEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "Offences" => OffencesEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "ParaInclusion" => {
-                ParachainInclusionEvent::from(&event.name, extrinsic_index, arguments.clone())?
-            }
-            "Paras" => ParachainsEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "Session" => SessionEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "Staking" => StakingEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "System" => SystemEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            "Utility" => UtilityEvent::from(&event.name, extrinsic_index, arguments.clone())?,
-            _ => None,
+        // decode typed event - the arguments are already consumed from the bytes at this
+        // point, so a typed decode error only degrades the event to raw
+        let maybe_event = match metadata
+            .event_registry
+            .get_decoder(module_index, event_index)
+        {
+            Some(decoder) => match decoder(&event.name, extrinsic_index, arguments.clone()) {
+                Ok(maybe_event) => maybe_event,
+                Err(error) => {
+                    warn!(
+                        "Cannot decode typed event {}.{}, will decode as raw event: {:?}",
+                        module.name, event.name, error
+                    );
+                    None
+                }
+            },
+            None => None,
         };
         let substrate_event = if let Some(substrate_event) = maybe_event {
             debug!("Decoded event {}.{}.", module.name, event.name);
@@ -744,12 +1091,18 @@ impl SubstrateEvent {
         for event_index in 0..event_count {
             match SubstrateEvent::decode_event(chain, metadata, &mut *bytes) {
                 Ok(event) => events.push(event),
-                Err(error) => error!(
-                    "Error decoding event #{} for block #{}: {:?}",
-                    event_index,
-                    block.header.get_number().unwrap(),
-                    error
-                ),
+                Err(error) => {
+                    // the position of the next event in the bytes is unknown after a failed
+                    // decode, so the remaining events cannot be decoded
+                    error!(
+                        "Error decoding event #{} for block #{}, skipping the remaining {} events: {:?}",
+                        event_index,
+                        block.header.get_number().unwrap(),
+                        event_count - event_index - 1,
+                        error
+                    );
+                    break;
+                }
             }
         }
         Ok(events)
@@ -1,23 +1,299 @@
 //! Substrate extrinsic types, and decode logic.
 //! Note: These are only the extrinsics that are utilized in SubVT.
-use crate::substrate::{Balance, Chain, RewardDestination};
+use crate::substrate::{
+    data_to_string, get_conviction_index, Balance, Chain, DemocracyVote, IdentityRegistration,
+    RewardDestination,
+};
 use crate::{
     crypto::AccountId,
     substrate::{
         argument::{
+            extract_account_id_argument, extract_argument_primitive, extract_unsigned_argument,
             get_argument_primitive, get_argument_vector, get_optional_argument_primitive, Argument,
             ArgumentPrimitive,
         },
         error::DecodeError,
         metadata::{ArgumentMeta, Metadata},
+        value::Value,
         Block, MultiAddress, ProxyType, ValidatorPreferences,
     },
 };
 use log::{debug, error};
+use pallet_democracy::ReferendumIndex;
+use pallet_identity::{Judgement, RegistrarIndex};
 use pallet_multisig::Timepoint;
 use pallet_staking::EraIndex;
 use parity_scale_codec::{Compact, Decode, Input};
 use polkadot_core_primitives::BlockNumber;
+use polkadot_primitives::v1::Id;
+use polkadot_runtime_common::auctions::AuctionIndex;
+use sp_runtime::MultiSignature;
+
+/// Conviction argument of the democracy and conviction voting calls, either typed or decoded
+/// from the runtime type registry.
+fn extract_conviction_argument(argument: &Argument) -> Result<u8, DecodeError> {
+    let maybe_conviction = match extract_argument_primitive(argument)? {
+        ArgumentPrimitive::DemocracyConviction(conviction) => Some(conviction.into()),
+        ArgumentPrimitive::Value(Value::Variant { name, .. }) => get_conviction_index(&name),
+        _ => None,
+    };
+    maybe_conviction.ok_or_else(|| {
+        DecodeError::Error(format!("Cannot get conviction argument {:?}.", argument))
+    })
+}
+
+/// Vote argument of the democracy and conviction voting calls, either typed or decoded from the
+/// runtime type registry.
+fn extract_account_vote_argument(argument: &Argument) -> Result<DemocracyVote, DecodeError> {
+    let maybe_vote = match extract_argument_primitive(argument)? {
+        ArgumentPrimitive::DemocracyAccountVote(account_vote) => Some(account_vote.into()),
+        ArgumentPrimitive::Value(value) => DemocracyVote::from_value(&value),
+        _ => None,
+    };
+    maybe_vote
+        .ok_or_else(|| DecodeError::Error(format!("Cannot get vote argument {:?}.", argument)))
+}
+
+#[derive(Clone, Debug)]
+pub enum AuctionsExtrinsic {
+    Bid {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+        auction_index: AuctionIndex,
+        first_slot: BlockNumber,
+        last_slot: BlockNumber,
+        amount: Balance,
+    },
+}
+
+impl AuctionsExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "bid" => {
+                if arguments.len() < 5 {
+                    return Err(
+                        DecodeError::Error(
+                            format!(
+                                "Cannot decode Auctions.bid extrinsic. Not enough parameters. Expected 5, found {}.",
+                                arguments.len()
+                            )
+                        )
+                    );
+                }
+                Some(SubstrateExtrinsic::Auctions(AuctionsExtrinsic::Bid {
+                    maybe_signature,
+                    parachain_id: get_argument_primitive!(&arguments[0], CompactParachainId).0,
+                    auction_index: get_argument_primitive!(&arguments[1], CompactAuctionIndex).0,
+                    first_slot: get_argument_primitive!(&arguments[2], ParachainCompactLeasePeriod)
+                        .0,
+                    last_slot: get_argument_primitive!(&arguments[3], ParachainCompactLeasePeriod)
+                        .0,
+                    amount: get_argument_primitive!(&arguments[4], CompactBalance).0,
+                }))
+            }
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum CrowdloanExtrinsic {
+    Contribute {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+        amount: Balance,
+        /// Signature of the crowdloan's verifier, for the crowdloans that require one.
+        maybe_verifier_signature: Option<MultiSignature>,
+    },
+    /// Contributes the whole free balance of the signer, which is known only at dispatch.
+    ContributeAll {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+        maybe_verifier_signature: Option<MultiSignature>,
+    },
+}
+
+impl CrowdloanExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "contribute" => {
+                if arguments.len() < 3 {
+                    return Err(
+                        DecodeError::Error(
+                            format!(
+                                "Cannot decode Crowdloan.contribute extrinsic. Not enough parameters. Expected 3, found {}.",
+                                arguments.len()
+                            )
+                        )
+                    );
+                }
+                Some(SubstrateExtrinsic::Crowdloan(
+                    CrowdloanExtrinsic::Contribute {
+                        maybe_signature,
+                        parachain_id: get_argument_primitive!(&arguments[0], CompactParachainId).0,
+                        amount: get_argument_primitive!(&arguments[1], CompactBalance).0,
+                        maybe_verifier_signature: get_optional_argument_primitive!(
+                            &arguments[2],
+                            MultiSignature
+                        ),
+                    },
+                ))
+            }
+            "contribute_all" => {
+                if arguments.len() < 2 {
+                    return Err(
+                        DecodeError::Error(
+                            format!(
+                                "Cannot decode Crowdloan.contribute_all extrinsic. Not enough parameters. Expected 2, found {}.",
+                                arguments.len()
+                            )
+                        )
+                    );
+                }
+                Some(SubstrateExtrinsic::Crowdloan(
+                    CrowdloanExtrinsic::ContributeAll {
+                        maybe_signature,
+                        parachain_id: get_argument_primitive!(&arguments[0], CompactParachainId).0,
+                        maybe_verifier_signature: get_optional_argument_primitive!(
+                            &arguments[1],
+                            MultiSignature
+                        ),
+                    },
+                ))
+            }
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum ConvictionVotingExtrinsic {
+    Vote {
+        maybe_signature: Option<Signature>,
+        poll_index: u32,
+        vote: DemocracyVote,
+    },
+    Delegate {
+        maybe_signature: Option<Signature>,
+        track_id: u16,
+        target_account_id: AccountId,
+        conviction: u8,
+        balance: Balance,
+    },
+    Undelegate {
+        maybe_signature: Option<Signature>,
+        track_id: u16,
+    },
+}
+
+impl ConvictionVotingExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "vote" => Some(SubstrateExtrinsic::ConvictionVoting(
+                ConvictionVotingExtrinsic::Vote {
+                    maybe_signature,
+                    poll_index: extract_unsigned_argument(&arguments[0])? as u32,
+                    vote: extract_account_vote_argument(&arguments[1])?,
+                },
+            )),
+            "delegate" => Some(SubstrateExtrinsic::ConvictionVoting(
+                ConvictionVotingExtrinsic::Delegate {
+                    maybe_signature,
+                    track_id: extract_unsigned_argument(&arguments[0])? as u16,
+                    target_account_id: extract_account_id_argument(&arguments[1])?,
+                    conviction: extract_conviction_argument(&arguments[2])?,
+                    balance: extract_unsigned_argument(&arguments[3])?,
+                },
+            )),
+            "undelegate" => Some(SubstrateExtrinsic::ConvictionVoting(
+                ConvictionVotingExtrinsic::Undelegate {
+                    maybe_signature,
+                    track_id: extract_unsigned_argument(&arguments[0])? as u16,
+                },
+            )),
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum DemocracyExtrinsic {
+    Vote {
+        maybe_signature: Option<Signature>,
+        referendum_index: ReferendumIndex,
+        vote: DemocracyVote,
+    },
+    Delegate {
+        maybe_signature: Option<Signature>,
+        target_account_id: AccountId,
+        conviction: u8,
+        balance: Balance,
+    },
+    Undelegate {
+        maybe_signature: Option<Signature>,
+    },
+    Propose {
+        maybe_signature: Option<Signature>,
+        deposit: Balance,
+    },
+    Second {
+        maybe_signature: Option<Signature>,
+        proposal_index: u32,
+    },
+}
+
+impl DemocracyExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "vote" => Some(SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Vote {
+                maybe_signature,
+                referendum_index: get_argument_primitive!(&arguments[0], CompactReferendumIndex).0,
+                vote: get_argument_primitive!(&arguments[1], DemocracyAccountVote).into(),
+            })),
+            "delegate" => Some(SubstrateExtrinsic::Democracy(
+                DemocracyExtrinsic::Delegate {
+                    maybe_signature,
+                    target_account_id: extract_account_id_argument(&arguments[0])?,
+                    conviction: extract_conviction_argument(&arguments[1])?,
+                    balance: extract_unsigned_argument(&arguments[2])?,
+                },
+            )),
+            "undelegate" => Some(SubstrateExtrinsic::Democracy(
+                DemocracyExtrinsic::Undelegate { maybe_signature },
+            )),
+            // the proposal is a hash or a bounded call depending on the runtime version
+            "propose" => Some(SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Propose {
+                maybe_signature,
+                deposit: extract_unsigned_argument(&arguments[1])?,
+            })),
+            "second" => Some(SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Second {
+                maybe_signature,
+                proposal_index: extract_unsigned_argument(&arguments[0])? as u32,
+            })),
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
 
 #[derive(Clone, Debug)]
 pub enum MultisigExtrinsic {
@@ -160,6 +436,142 @@ impl ProxyExtrinsic {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum IdentityExtrinsic {
+    /// The judgements of the previous identity get removed by the call, so the identity
+    /// is not confirmed.
+    SetIdentity {
+        maybe_signature: Option<Signature>,
+        identity: IdentityRegistration,
+    },
+    /// Replaces all the sub-accounts of the signer. Sub-account display is `None` unless raw.
+    SetSubs {
+        maybe_signature: Option<Signature>,
+        subs: Vec<(AccountId, Option<String>)>,
+    },
+    ClearIdentity {
+        maybe_signature: Option<Signature>,
+    },
+    ProvideJudgement {
+        maybe_signature: Option<Signature>,
+        registrar_index: RegistrarIndex,
+        target: MultiAddress,
+        judgement: Judgement<Balance>,
+    },
+    KillIdentity {
+        maybe_signature: Option<Signature>,
+        target: MultiAddress,
+    },
+}
+
+impl IdentityExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "set_identity" => {
+                if arguments.is_empty() {
+                    return Err(DecodeError::Error(
+                        "Cannot decode Identity.set_identity extrinsic. Not enough parameters. Expected 1, found 0.".to_string(),
+                    ));
+                }
+                let info = get_argument_primitive!(&arguments[0], IdentityInfo);
+                Some(SubstrateExtrinsic::Identity(
+                    IdentityExtrinsic::SetIdentity {
+                        maybe_signature,
+                        identity: IdentityRegistration::from_info(*info, false),
+                    },
+                ))
+            }
+            "set_subs" => {
+                let sub_arguments = match arguments.first() {
+                    Some(Argument::Vec(sub_arguments)) => sub_arguments,
+                    _ => {
+                        return Err(DecodeError::Error(
+                            "Cannot decode Identity.set_subs extrinsic. Cannot get the sub-accounts vector.".to_string(),
+                        ))
+                    }
+                };
+                let mut subs = Vec::new();
+                for sub_argument in sub_arguments {
+                    match sub_argument {
+                        Argument::Tuple(elements) if elements.len() == 2 => subs.push((
+                            get_argument_primitive!(&elements[0], AccountId),
+                            data_to_string(get_argument_primitive!(&elements[1], IdentityData)),
+                        )),
+                        _ => {
+                            return Err(DecodeError::Error(format!(
+                                "Cannot decode Identity.set_subs extrinsic. Unexpected sub-account argument {:?}.",
+                                sub_argument
+                            )))
+                        }
+                    }
+                }
+                Some(SubstrateExtrinsic::Identity(IdentityExtrinsic::SetSubs {
+                    maybe_signature,
+                    subs,
+                }))
+            }
+            "clear_identity" => Some(SubstrateExtrinsic::Identity(
+                IdentityExtrinsic::ClearIdentity { maybe_signature },
+            )),
+            "provide_judgement" => {
+                // later runtime versions have a fourth argument, the hash of the judged identity
+                if arguments.len() < 3 {
+                    return Err(
+                        DecodeError::Error(
+                            format!(
+                                "Cannot decode Identity.provide_judgement extrinsic. Not enough parameters. Expected 3, found {}.",
+                                arguments.len()
+                            )
+                        )
+                    );
+                }
+                Some(SubstrateExtrinsic::Identity(
+                    IdentityExtrinsic::ProvideJudgement {
+                        maybe_signature,
+                        registrar_index: get_argument_primitive!(
+                            &arguments[0],
+                            CompactRegistrarIndex
+                        )
+                        .0,
+                        target: get_argument_primitive!(&arguments[1], MultiAddress),
+                        judgement: get_argument_primitive!(&arguments[2], IdentityJudgement),
+                    },
+                ))
+            }
+            "kill_identity" => {
+                if arguments.is_empty() {
+                    return Err(DecodeError::Error(
+                        "Cannot decode Identity.kill_identity extrinsic. Not enough parameters. Expected 1, found 0.".to_string(),
+                    ));
+                }
+                Some(SubstrateExtrinsic::Identity(
+                    IdentityExtrinsic::KillIdentity {
+                        maybe_signature,
+                        target: get_argument_primitive!(&arguments[0], MultiAddress),
+                    },
+                ))
+            }
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+
+    /// Name of the call, as stored in the identity extrinsics table.
+    pub fn get_call_name(&self) -> &'static str {
+        match self {
+            IdentityExtrinsic::SetIdentity { .. } => "set_identity",
+            IdentityExtrinsic::SetSubs { .. } => "set_subs",
+            IdentityExtrinsic::ClearIdentity { .. } => "clear_identity",
+            IdentityExtrinsic::ProvideJudgement { .. } => "provide_judgement",
+            IdentityExtrinsic::KillIdentity { .. } => "kill_identity",
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum ImOnlineExtrinsic {
     Hearbeat {
@@ -192,6 +604,33 @@ impl ImOnlineExtrinsic {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum SystemExtrinsic {
+    Remark {
+        maybe_signature: Option<Signature>,
+        remark: Vec<u8>,
+    },
+}
+
+impl SystemExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "remark" | "remark_with_event" => {
+                Some(SubstrateExtrinsic::System(SystemExtrinsic::Remark {
+                    maybe_signature,
+                    remark: get_argument_vector!(&arguments[0], U8),
+                }))
+            }
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum TimestampExtrinsic {
     Set {
@@ -217,6 +656,101 @@ impl TimestampExtrinsic {
     }
 }
 
+#[derive(Clone, Debug)]
+pub enum ReferendaExtrinsic {
+    /// The index of the submitted referendum is only available in the `Submitted` event.
+    Submit { maybe_signature: Option<Signature> },
+    PlaceDecisionDeposit {
+        maybe_signature: Option<Signature>,
+        referendum_index: u32,
+    },
+}
+
+impl ReferendaExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "submit" => Some(SubstrateExtrinsic::Referenda(ReferendaExtrinsic::Submit {
+                maybe_signature,
+            })),
+            "place_decision_deposit" => Some(SubstrateExtrinsic::Referenda(
+                ReferendaExtrinsic::PlaceDecisionDeposit {
+                    maybe_signature,
+                    referendum_index: extract_unsigned_argument(&arguments[0])? as u32,
+                },
+            )),
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum SlotsExtrinsic {
+    /// Root call, reserves the amount of the leaser for the lease periods.
+    ForceLease {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+        leaser_account_id: AccountId,
+        amount: Balance,
+        period_begin: BlockNumber,
+        period_count: BlockNumber,
+    },
+    /// Root call, clears the leases of the parachain and unreserves the deposits.
+    ClearAllLeases {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+    },
+    TriggerOnboard {
+        maybe_signature: Option<Signature>,
+        parachain_id: Id,
+    },
+}
+
+impl SlotsExtrinsic {
+    pub fn from(
+        name: &str,
+        maybe_signature: Option<Signature>,
+        arguments: Vec<Argument>,
+    ) -> Result<Option<SubstrateExtrinsic>, DecodeError> {
+        let maybe_extrinsic = match name {
+            "force_lease" => {
+                if arguments.len() < 5 {
+                    return Err(
+                        DecodeError::Error(
+                            format!(
+                                "Cannot decode Slots.force_lease extrinsic. Not enough parameters. Expected 5, found {}.",
+                                arguments.len()
+                            )
+                        )
+                    );
+                }
+                Some(SubstrateExtrinsic::Slots(SlotsExtrinsic::ForceLease {
+                    maybe_signature,
+                    parachain_id: get_argument_primitive!(&arguments[0], ParachainId),
+                    leaser_account_id: get_argument_primitive!(&arguments[1], AccountId),
+                    amount: get_argument_primitive!(&arguments[2], Balance),
+                    period_begin: get_argument_primitive!(&arguments[3], ParachainLeasePeriod),
+                    period_count: get_argument_primitive!(&arguments[4], ParachainLeasePeriod),
+                }))
+            }
+            "clear_all_leases" => Some(SubstrateExtrinsic::Slots(SlotsExtrinsic::ClearAllLeases {
+                maybe_signature,
+                parachain_id: get_argument_primitive!(&arguments[0], ParachainId),
+            })),
+            "trigger_onboard" => Some(SubstrateExtrinsic::Slots(SlotsExtrinsic::TriggerOnboard {
+                maybe_signature,
+                parachain_id: get_argument_primitive!(&arguments[0], ParachainId),
+            })),
+            _ => None,
+        };
+        Ok(maybe_extrinsic)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum StakingExtrinsic {
     Bond {
@@ -225,6 +759,17 @@ pub enum StakingExtrinsic {
         amount: Balance,
         reward_destination: RewardDestination,
     },
+    BondExtra {
+        maybe_signature: Option<Signature>,
+        max_additional: Balance,
+    },
+    Chill {
+        maybe_signature: Option<Signature>,
+    },
+    Kick {
+        maybe_signature: Option<Signature>,
+        nominators: Vec<MultiAddress>,
+    },
     Nominate {
         maybe_signature: Option<Signature>,
         targets: Vec<MultiAddress>,
@@ -234,14 +779,30 @@ pub enum StakingExtrinsic {
         validator_account_id: AccountId,
         era_index: EraIndex,
     },
+    Rebond {
+        maybe_signature: Option<Signature>,
+        amount: Balance,
+    },
     SetController {
         maybe_signature: Option<Signature>,
         controller: MultiAddress,
     },
+    SetPayee {
+        maybe_signature: Option<Signature>,
+        reward_destination: RewardDestination,
+    },
+    Unbond {
+        maybe_signature: Option<Signature>,
+        amount: Balance,
+    },
     Validate {
         maybe_signature: Option<Signature>,
         preferences: ValidatorPreferences,
     },
+    WithdrawUnbonded {
+        maybe_signature: Option<Signature>,
+        slashing_span_count: u32,
+    },
 }
 
 impl StakingExtrinsic {
@@ -257,6 +818,17 @@ impl StakingExtrinsic {
                 amount: get_argument_primitive!(&arguments[1], CompactBalance).0,
                 reward_destination: get_argument_primitive!(&arguments[2], RewardDestination),
             })),
+            "bond_extra" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::BondExtra {
+                maybe_signature: signature,
+                max_additional: get_argument_primitive!(&arguments[0], CompactBalance).0,
+            })),
+            "chill" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Chill {
+                maybe_signature: signature,
+            })),
+            "kick" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Kick {
+                maybe_signature: signature,
+                nominators: get_argument_vector!(&arguments[0], MultiAddress),
+            })),
             "nominate" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Nominate {
                 maybe_signature: signature,
                 targets: get_argument_vector!(&arguments[0], MultiAddress),
@@ -268,16 +840,34 @@ impl StakingExtrinsic {
                     era_index: get_argument_primitive!(&arguments[1], EraIndex),
                 },
             )),
+            "rebond" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Rebond {
+                maybe_signature: signature,
+                amount: get_argument_primitive!(&arguments[0], CompactBalance).0,
+            })),
             "set_controller" => Some(SubstrateExtrinsic::Staking(
                 StakingExtrinsic::SetController {
                     maybe_signature: signature,
                     controller: get_argument_primitive!(&arguments[0], MultiAddress),
                 },
             )),
+            "set_payee" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::SetPayee {
+                maybe_signature: signature,
+                reward_destination: get_argument_primitive!(&arguments[0], RewardDestination),
+            })),
+            "unbond" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Unbond {
+                maybe_signature: signature,
+                amount: get_argument_primitive!(&arguments[0], CompactBalance).0,
+            })),
             "validate" => Some(SubstrateExtrinsic::Staking(StakingExtrinsic::Validate {
                 maybe_signature: signature,
                 preferences: get_argument_primitive!(&arguments[0], ValidatorPreferences),
             })),
+            "withdraw_unbonded" => Some(SubstrateExtrinsic::Staking(
+                StakingExtrinsic::WithdrawUnbonded {
+                    maybe_signature: signature,
+                    slashing_span_count: get_argument_primitive!(&arguments[0], U32),
+                },
+            )),
             _ => None,
         };
         Ok(maybe_extrinsic)
@@ -327,18 +917,49 @@ impl UtilityExtrinsic {
     }
 }
 
+/// Flat representation of a governance call of the democracy, conviction voting and referenda
+/// pallets, as stored in the governance calls table. Only the fields of the call are set.
+/// Democracy votes are not included, see `DemocracyExtrinsic::Vote`.
+#[derive(Clone, Debug, Default)]
+pub struct GovernanceCall {
+    pub pallet_name: &'static str,
+    pub call_name: &'static str,
+    /// Referendum (poll) index of a conviction vote or a decision deposit.
+    pub referendum_index: Option<u32>,
+    /// Index of a seconded democracy proposal.
+    pub proposal_index: Option<u32>,
+    /// Conviction voting track (class) of a delegation or an undelegation.
+    pub track_id: Option<u16>,
+    pub target_account_id: Option<AccountId>,
+    pub vote: Option<DemocracyVote>,
+    /// Conviction of a delegation.
+    pub conviction: Option<u8>,
+    /// Delegated balance or proposal deposit.
+    pub amount: Option<Balance>,
+}
+
 #[derive(Clone, Debug)]
 pub enum SubstrateExtrinsic {
+    Auctions(AuctionsExtrinsic),
+    ConvictionVoting(ConvictionVotingExtrinsic),
+    Crowdloan(CrowdloanExtrinsic),
+    Democracy(DemocracyExtrinsic),
+    Identity(IdentityExtrinsic),
     ImOnline(ImOnlineExtrinsic),
     Multisig(MultisigExtrinsic),
     Proxy(ProxyExtrinsic),
+    Referenda(ReferendaExtrinsic),
+    Slots(SlotsExtrinsic),
     Staking(StakingExtrinsic),
+    System(SystemExtrinsic),
     Timestamp(TimestampExtrinsic),
     Utility(UtilityExtrinsic),
+    /// Any extrinsic without a typed representation, with its raw decoded arguments.
     Other {
         module_name: String,
         call_name: String,
         signature: Option<Signature>,
+        arguments: Vec<Argument>,
     },
 }
 
@@ -358,6 +979,239 @@ impl Signature {
 }
 
 impl SubstrateExtrinsic {
+    /// Signature of the extrinsic, `None` for the unsigned extrinsics (inherents).
+    pub fn get_signature(&self) -> Option<&Signature> {
+        let maybe_signature = match self {
+            SubstrateExtrinsic::Auctions(AuctionsExtrinsic::Bid {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Crowdloan(CrowdloanExtrinsic::Contribute {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::Crowdloan(CrowdloanExtrinsic::ContributeAll {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Vote {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Delegate {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Undelegate {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Vote {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Delegate {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Undelegate { maybe_signature })
+            | SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Propose {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Second {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Identity(IdentityExtrinsic::SetIdentity {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::Identity(IdentityExtrinsic::SetSubs {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Identity(IdentityExtrinsic::ClearIdentity { maybe_signature })
+            | SubstrateExtrinsic::Identity(IdentityExtrinsic::ProvideJudgement {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::Identity(IdentityExtrinsic::KillIdentity {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::ImOnline(ImOnlineExtrinsic::Hearbeat {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Multisig(MultisigExtrinsic::AsMulti {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Multisig(MultisigExtrinsic::AsMultiThreshold1 {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Proxy(ProxyExtrinsic::Proxy {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Proxy(ProxyExtrinsic::ProxyAnnounced {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Referenda(ReferendaExtrinsic::Submit { maybe_signature })
+            | SubstrateExtrinsic::Referenda(ReferendaExtrinsic::PlaceDecisionDeposit {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Slots(SlotsExtrinsic::ForceLease {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Slots(SlotsExtrinsic::ClearAllLeases {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Slots(SlotsExtrinsic::TriggerOnboard {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Staking(StakingExtrinsic::Bond {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::BondExtra {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Chill { maybe_signature })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Kick {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Nominate {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::PayoutStakers {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Rebond {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::SetController {
+                maybe_signature,
+                ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::SetPayee {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Unbond {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::Validate {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Staking(StakingExtrinsic::WithdrawUnbonded {
+                maybe_signature,
+                ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::System(SystemExtrinsic::Remark {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Timestamp(TimestampExtrinsic::Set {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Utility(UtilityExtrinsic::Batch {
+                maybe_signature, ..
+            })
+            | SubstrateExtrinsic::Utility(UtilityExtrinsic::BatchAll {
+                maybe_signature, ..
+            }) => maybe_signature,
+            SubstrateExtrinsic::Other { signature, .. } => signature,
+        };
+        maybe_signature.as_ref()
+    }
+
+    /// Governance call representation of the democracy (except the votes), conviction voting
+    /// and referenda extrinsics, `None` for the other extrinsics.
+    pub fn get_governance_call(&self) -> Option<GovernanceCall> {
+        let call = match self {
+            SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Vote {
+                poll_index,
+                vote,
+                ..
+            }) => GovernanceCall {
+                pallet_name: "conviction_voting",
+                call_name: "vote",
+                referendum_index: Some(*poll_index),
+                vote: Some(vote.clone()),
+                ..Default::default()
+            },
+            SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Delegate {
+                track_id,
+                target_account_id,
+                conviction,
+                balance,
+                ..
+            }) => GovernanceCall {
+                pallet_name: "conviction_voting",
+                call_name: "delegate",
+                track_id: Some(*track_id),
+                target_account_id: Some(target_account_id.clone()),
+                conviction: Some(*conviction),
+                amount: Some(*balance),
+                ..Default::default()
+            },
+            SubstrateExtrinsic::ConvictionVoting(ConvictionVotingExtrinsic::Undelegate {
+                track_id,
+                ..
+            }) => GovernanceCall {
+                pallet_name: "conviction_voting",
+                call_name: "undelegate",
+                track_id: Some(*track_id),
+                ..Default::default()
+            },
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Delegate {
+                target_account_id,
+                conviction,
+                balance,
+                ..
+            }) => GovernanceCall {
+                pallet_name: "democracy",
+                call_name: "delegate",
+                target_account_id: Some(target_account_id.clone()),
+                conviction: Some(*conviction),
+                amount: Some(*balance),
+                ..Default::default()
+            },
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Undelegate { .. }) => {
+                GovernanceCall {
+                    pallet_name: "democracy",
+                    call_name: "undelegate",
+                    ..Default::default()
+                }
+            }
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Propose { deposit, .. }) => {
+                GovernanceCall {
+                    pallet_name: "democracy",
+                    call_name: "propose",
+                    amount: Some(*deposit),
+                    ..Default::default()
+                }
+            }
+            SubstrateExtrinsic::Democracy(DemocracyExtrinsic::Second {
+                proposal_index, ..
+            }) => GovernanceCall {
+                pallet_name: "democracy",
+                call_name: "second",
+                proposal_index: Some(*proposal_index),
+                ..Default::default()
+            },
+            SubstrateExtrinsic::Referenda(ReferendaExtrinsic::Submit { .. }) => GovernanceCall {
+                pallet_name: "referenda",
+                call_name: "submit",
+                ..Default::default()
+            },
+            SubstrateExtrinsic::Referenda(ReferendaExtrinsic::PlaceDecisionDeposit {
+                referendum_index,
+                ..
+            }) => GovernanceCall {
+                pallet_name: "referenda",
+                call_name: "place_decision_deposit",
+                referendum_index: Some(*referendum_index),
+                ..Default::default()
+            },
+            _ => return None,
+        };
+        Some(call)
+    }
+
     pub fn decode_extrinsic(
         chain: &Chain,
         metadata: &Metadata,
@@ -412,7 +1266,15 @@ impl SubstrateExtrinsic {
             )));
         };
         let mut arguments: Vec<Argument> = Vec::new();
-        for argument_meta in &call.arguments {
+        for (argument_index, argument_meta) in call.arguments.iter().enumerate() {
+            if let Some(Some(type_id)) = call.registry_type_ids.get(argument_index) {
+                arguments.push(Argument::decode_from_registry(
+                    metadata,
+                    *type_id,
+                    &mut *bytes,
+                )?);
+                continue;
+            }
             if let ArgumentMeta::Vec(inner_argument_meta) = argument_meta {
                 // vector of calls should be handled differently,
                 // the encoding doesn't include the length of the calls as it would in the case
@@ -447,22 +1309,65 @@ impl SubstrateExtrinsic {
             arguments.push(argument);
         }
         let maybe_extrinsic = match (module.name.as_str(), call.name.as_str()) {
+            ("Auctions", "bid") => {
+                AuctionsExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
+            ("Crowdloan", "contribute") | ("Crowdloan", "contribute_all") => {
+                CrowdloanExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
+            ("ConvictionVoting", "vote")
+            | ("ConvictionVoting", "delegate")
+            | ("ConvictionVoting", "undelegate") => {
+                ConvictionVotingExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
+            ("Democracy", "vote")
+            | ("Democracy", "delegate")
+            | ("Democracy", "undelegate")
+            | ("Democracy", "propose")
+            | ("Democracy", "second") => {
+                DemocracyExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
+            ("Identity", "set_identity")
+            | ("Identity", "set_subs")
+            | ("Identity", "clear_identity")
+            | ("Identity", "provide_judgement")
+            | ("Identity", "kill_identity") => {
+                IdentityExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
             ("ImOnline", "heartbeat") => {
                 ImOnlineExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
             }
             ("Multisig", "as_multi") | ("Multisig", "as_multi_threshold_1") => {
                 MultisigExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
             }
+            ("Slots", "force_lease")
+            | ("Slots", "clear_all_leases")
+            | ("Slots", "trigger_onboard") => {
+                SlotsExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
             ("Staking", "bond")
+            | ("Staking", "bond_extra")
+            | ("Staking", "chill")
+            | ("Staking", "kick")
             | ("Staking", "nominate")
             | ("Staking", "payout_stakers")
+            | ("Staking", "rebond")
+            | ("Staking", "set_controller")
+            | ("Staking", "set_payee")
+            | ("Staking", "unbond")
             | ("Staking", "validate")
-            | ("Staking", "set_controller") => {
+            | ("Staking", "withdraw_unbonded") => {
                 StakingExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
             }
             ("Proxy", "proxy") | ("Proxy", "proxy_announced") => {
                 ProxyExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
             }
+            ("Referenda", "submit") | ("Referenda", "place_decision_deposit") => {
+                ReferendaExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
+            ("System", "remark") | ("System", "remark_with_event") => {
+                SystemExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
+            }
             ("Timestamp", "set") => {
                 TimestampExtrinsic::from(&call.name, signature.clone(), arguments.clone())?
             }
@@ -483,6 +1388,7 @@ impl SubstrateExtrinsic {
                 signature,
                 module_name: module.name.clone(),
                 call_name: call.name.clone(),
+                arguments,
             }
         };
         Ok(extrinsic)
@@ -1,11 +1,13 @@
 /// Substrate metadata. Most of this code has been adopted from [SubXT](https://github.com/paritytech/substrate-subxt).
 /// Modified, diminished and augmented as needed.
-use crate::substrate::{argument::Argument, Chain, LastRuntimeUpgradeInfo};
+use crate::substrate::{
+    argument::Argument, event::EventRegistry, Balance, Chain, LastRuntimeUpgradeInfo,
+};
 use core::convert::TryInto;
 use frame_metadata::{decode_different::DecodeDifferent, RuntimeMetadata, RuntimeMetadataPrefixed};
 use log::debug;
 use parity_scale_codec::{Decode, Encode, Error as CodecError};
-use scale_info::form::PortableForm;
+use scale_info::{form::PortableForm, PortableRegistry};
 use std::fmt::{Display, Formatter};
 use std::{
     collections::{HashMap, HashSet},
@@ -43,6 +45,18 @@ pub struct MetadataConstants {
     pub era_duration_millis: u64,
 }
 
+/// Runtime constants of the staking logic, decoded once per runtime metadata.
+#[derive(Clone, Debug, Default)]
+pub struct ChainConstants {
+    /// Number of the largest nominator exposures of a validator that get rewarded in an era.
+    /// `None` for the runtimes with paged exposures, in which all nominators get rewarded.
+    pub max_nominator_rewarded_per_validator: Option<u32>,
+    /// Number of eras that the unbonded funds stay locked for.
+    pub bonding_duration: u32,
+    pub sessions_per_era: u32,
+    pub existential_deposit: Balance,
+}
+
 pub enum MetadataVersion {
     V12,
     V13,
@@ -55,7 +69,13 @@ pub struct Metadata {
     pub modules: HashMap<u8, ModuleMetadata>,
     pub extrinsic_metadata: ExtrinsicMetadata,
     pub constants: MetadataConstants,
+    pub chain_constants: ChainConstants,
     pub last_runtime_upgrade_info: LastRuntimeUpgradeInfo,
+    /// Typed event decoders of the runtime, see `EventRegistry`.
+    pub event_registry: EventRegistry,
+    /// Type registry of the runtime for the arguments without a typed decoder, available
+    /// since metadata v14.
+    pub type_registry: Option<PortableRegistry>,
 }
 
 impl Metadata {
@@ -74,6 +94,16 @@ impl Metadata {
         let sessions_per_era: u32 = staking_module.constant("SessionsPerEra")?.value()?;
         let era_duration_blocks = epoch_duration_blocks * sessions_per_era as u64;
         let era_duration_millis = era_duration_blocks * expected_block_time_millis;
+        let max_nominator_rewarded_per_validator: Option<u32> = staking_module
+            .constant("MaxNominatorRewardedPerValidator")
+            .ok()
+            .map(|constant| constant.value())
+            .transpose()?;
+        let bonding_duration: u32 = staking_module.constant("BondingDuration")?.value()?;
+        let existential_deposit: Balance = metadata
+            .module("Balances")?
+            .constant("ExistentialDeposit")?
+            .value()?;
         metadata.constants = MetadataConstants {
             expected_block_time_millis,
             epoch_duration_blocks,
@@ -82,6 +112,13 @@ impl Metadata {
             era_duration_blocks,
             era_duration_millis,
         };
+        metadata.chain_constants = ChainConstants {
+            max_nominator_rewarded_per_validator,
+            bonding_duration,
+            sessions_per_era,
+            existential_deposit,
+        };
+        metadata.event_registry = EventRegistry::from(&metadata);
         Ok(metadata)
     }
 
@@ -110,6 +147,21 @@ impl Metadata {
         }
     }
 
+    /// Runtimes after the staking exposure migration keep the era stakers in the paged
+    /// `ErasStakersOverview` and `ErasStakersPaged` storages instead of `ErasStakers`
+    /// and `ErasStakersClipped`.
+    pub fn is_era_stakers_paged(&self, chain: &Chain) -> bool {
+        match chain {
+            Chain::Kusama | Chain::Polkadot => {
+                self.last_runtime_upgrade_info.spec_version >= 1_002_000
+            }
+            _ => self
+                .module("Staking")
+                .map(|module| module.storage.contains_key("ErasStakersPaged"))
+                .unwrap_or(false),
+        }
+    }
+
     pub fn get_xcm_version(&self) -> u8 {
         if self.last_runtime_upgrade_info.spec_version < 9100 {
             0
@@ -121,6 +173,10 @@ impl Metadata {
     }
 }
 
+fn is_registry_argument(registry_type_ids: &[Option<u32>], index: usize) -> bool {
+    matches!(registry_type_ids.get(index), Some(Some(_)))
+}
+
 impl Metadata {
     pub fn log_all_calls(&self) {
         let mut call_primitive_arg_name_set: HashSet<String> = HashSet::default();
@@ -179,16 +235,21 @@ impl Metadata {
         chain: &Chain,
     ) -> Result<(), crate::substrate::argument::ArgumentDecodeError> {
         debug!("Checking SubVT runtime for event primitive argument support...");
+        // arguments decoded from the type registry don't need a typed decoder
         let mut primitive_arg_name_set: HashSet<String> = HashSet::default();
         for module in self.modules.values() {
             for event in module.events.values() {
-                for arg in &event.arguments {
-                    primitive_arg_name_set.extend(arg.get_primitive_name_set());
+                for (index, arg) in event.arguments.iter().enumerate() {
+                    if !is_registry_argument(&event.registry_type_ids, index) {
+                        primitive_arg_name_set.extend(arg.get_primitive_name_set());
+                    }
                 }
             }
             for call in module.calls.values() {
-                for arg in &call.arguments {
-                    primitive_arg_name_set.extend(arg.get_primitive_name_set());
+                for (index, arg) in call.arguments.iter().enumerate() {
+                    if !is_registry_argument(&call.registry_type_ids, index) {
+                        primitive_arg_name_set.extend(arg.get_primitive_name_set());
+                    }
                 }
             }
         }
@@ -345,6 +406,9 @@ pub struct ModuleCallMetadata {
     pub index: usize,
     pub name: String,
     pub arguments: Vec<ArgumentMeta>,
+    /// Parallel to `arguments`, has the registry type id of each argument that gets decoded
+    /// from the runtime type registry. Empty before metadata v14.
+    pub registry_type_ids: Vec<Option<u32>>,
     pub documentation: Vec<String>,
 }
 
@@ -353,6 +417,8 @@ pub struct ModuleEventMetadata {
     pub index: usize,
     pub name: String,
     pub arguments: Vec<ArgumentMeta>,
+    /// See `ModuleCallMetadata::registry_type_ids`.
+    pub registry_type_ids: Vec<Option<u32>>,
     pub documentation: Vec<String>,
 }
 
@@ -496,7 +562,10 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                     modules: v12::convert_modules(meta)?,
                     extrinsic_metadata,
                     constants: Default::default(),
+                    chain_constants: Default::default(),
                     last_runtime_upgrade_info: Default::default(),
+                    event_registry: Default::default(),
+                    type_registry: None,
                 })
             }
             RuntimeMetadata::V13(meta) => {
@@ -509,7 +578,10 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                     modules: v13::convert_modules(meta)?,
                     extrinsic_metadata,
                     constants: Default::default(),
+                    chain_constants: Default::default(),
                     last_runtime_upgrade_info: Default::default(),
+                    event_registry: Default::default(),
+                    type_registry: None,
                 })
             }
             RuntimeMetadata::V14(meta) => {
@@ -517,12 +589,16 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
                     return Err(ConversionError::InvalidPrefix.into());
                 }
                 let extrinsic_metadata = v14::convert_extrinsic_metadata(&meta)?;
+                let type_registry = meta.types.clone();
                 Ok(Metadata {
                     version: MetadataVersion::V14,
                     modules: v14::convert_modules(meta)?,
                     extrinsic_metadata,
                     constants: Default::default(),
+                    chain_constants: Default::default(),
                     last_runtime_upgrade_info: Default::default(),
+                    event_registry: Default::default(),
+                    type_registry: Some(type_registry),
                 })
             }
             _ => Err(ConversionError::InvalidVersion.into()),
@@ -560,6 +636,7 @@ mod v12 {
             index,
             name,
             arguments,
+            registry_type_ids: Vec::new(),
             documentation,
         })
     }
@@ -728,6 +805,7 @@ mod v13 {
             index,
             name,
             arguments,
+            registry_type_ids: Vec::new(),
             documentation,
         })
     }
@@ -875,12 +953,12 @@ mod v13 {
 
 mod v14 {
     use super::{
-        ArgumentMeta, ModuleCallMetadata, ModuleConstantMetadata, ModuleEventMetadata,
+        Argument, ArgumentMeta, ModuleCallMetadata, ModuleConstantMetadata, ModuleEventMetadata,
         ModuleMetadata, StorageEntryType, StorageMetadata,
     };
     use frame_metadata::v14::RuntimeMetadataV14;
     use scale_info::form::PortableForm;
-    use scale_info::{Type, TypeDef};
+    use scale_info::{Field, Type, TypeDef};
     use std::collections::HashMap;
     use std::str::FromStr;
 
@@ -901,6 +979,43 @@ mod v14 {
         ty
     }
 
+    /// Converts the fields of a call or event variant to argument metas by their type names.
+    /// An argument that the typed argument decoder doesn't support, or that has no type name,
+    /// gets the id of its type in the registry to be decoded from the registry instead.
+    fn convert_arguments(
+        meta: &RuntimeMetadataV14,
+        fields: &[Field<PortableForm>],
+    ) -> (Vec<ArgumentMeta>, Vec<Option<u32>>) {
+        let mut arguments = Vec::new();
+        let mut registry_type_ids = Vec::new();
+        for field in fields {
+            let type_name = field.type_name().cloned().unwrap_or_default();
+            let is_compact = matches!(
+                meta.types.resolve(field.ty().id()).map(|ty| ty.type_def()),
+                Some(TypeDef::Compact(_))
+            );
+            let maybe_argument_meta = if type_name.is_empty() {
+                None
+            } else if is_compact {
+                ArgumentMeta::from_str(&format!("Compact<{}>", type_name)).ok()
+            } else {
+                ArgumentMeta::from_str(&type_name).ok()
+            };
+            match maybe_argument_meta {
+                Some(argument_meta) if Argument::is_decodable(&argument_meta) => {
+                    arguments.push(argument_meta);
+                    registry_type_ids.push(None);
+                }
+                maybe_argument_meta => {
+                    arguments
+                        .push(maybe_argument_meta.unwrap_or(ArgumentMeta::Primitive(type_name)));
+                    registry_type_ids.push(Some(field.ty().id()));
+                }
+            }
+        }
+        (arguments, registry_type_ids)
+    }
+
     fn convert_calls(
         meta: &RuntimeMetadataV14,
         calls_ty: &Type<PortableForm>,
@@ -909,27 +1024,13 @@ mod v14 {
         match calls_ty.type_def() {
             TypeDef::Variant(variant) => {
                 for call_variant in variant.variants() {
-                    let mut arguments = Vec::new();
-                    for field in call_variant.fields() {
-                        let type_name = field
-                            .type_name()
-                            .expect("Cannot get type name for call argument.");
-                        let ty = meta.types.resolve(field.ty().id()).unwrap();
-                        let argument_meta = match ty.type_def() {
-                            TypeDef::Compact(_) => {
-                                ArgumentMeta::from_str(&format!("Compact<{}>", type_name))
-                            }
-                            _ => ArgumentMeta::from_str(type_name),
-                        }
-                        .unwrap_or_else(|_| {
-                            panic!("Cannot get call argument meta for {}.", type_name)
-                        });
-                        arguments.push(argument_meta);
-                    }
+                    let (arguments, registry_type_ids) =
+                        convert_arguments(meta, call_variant.fields());
                     let call_meta = ModuleCallMetadata {
                         index: call_variant.index() as usize,
                         name: call_variant.name().clone(),
                         arguments,
+                        registry_type_ids,
                         documentation: Vec::from(call_variant.docs()),
                     };
                     call_map.insert(call_variant.index(), call_meta);
@@ -948,27 +1049,13 @@ mod v14 {
         match events_ty.type_def() {
             TypeDef::Variant(variant) => {
                 for event_variant in variant.variants() {
-                    let mut arguments = Vec::new();
-                    for field in event_variant.fields() {
-                        let type_name = field
-                            .type_name()
-                            .expect("Cannot get type name for event argument.");
-                        let ty = meta.types.resolve(field.ty().id()).unwrap();
-                        let argument_meta = match ty.type_def() {
-                            TypeDef::Compact(_) => {
-                                ArgumentMeta::from_str(&format!("Compact<{}>", type_name))
-                            }
-                            _ => ArgumentMeta::from_str(type_name),
-                        }
-                        .unwrap_or_else(|_| {
-                            panic!("Cannot get event argument meta for {}.", type_name)
-                        });
-                        arguments.push(argument_meta);
-                    }
+                    let (arguments, registry_type_ids) =
+                        convert_arguments(meta, event_variant.fields());
                     let event_meta = ModuleEventMetadata {
                         index: event_variant.index() as usize,
                         name: event_variant.name().clone(),
                         arguments,
+                        registry_type_ids,
                         documentation: Vec::from(event_variant.docs()),
                     };
                     event_map.insert(event_variant.index(), event_meta);
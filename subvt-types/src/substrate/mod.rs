@@ -2,17 +2,21 @@
 //! Mostly translations of the native Substrate runtime types.
 
 use crate::crypto::AccountId;
+use crate::substrate::value::Value;
 use chrono::{DateTime, TimeZone, Utc};
-use frame_support::traits::ConstU32;
-use log::error;
-use pallet_identity::{Data, Judgement, Registration};
-use pallet_staking::{Exposure, Nominations, StakingLedger, ValidatorPrefs};
+use frame_support::traits::{ConstU32, Get};
+use pallet_democracy::{AccountVote, ReferendumInfo};
+use pallet_election_provider_multi_phase::RoundSnapshot;
+use pallet_identity::{Data, IdentityInfo, Judgement, Registration};
+use pallet_staking::{
+    Exposure, IndividualExposure, Nominations, StakingLedger, UnappliedSlash, ValidatorPrefs,
+};
 use parity_scale_codec::{Decode, Encode};
 use serde::{Deserialize, Serialize};
 use sp_consensus_babe::digests::PreDigest;
 use sp_core::crypto::{AccountId32, Ss58AddressFormat};
 use sp_runtime::DigestItem;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::convert::From;
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
@@ -29,6 +33,8 @@ pub mod event;
 pub mod extrinsic;
 pub mod legacy;
 pub mod metadata;
+pub mod nomination_pools;
+pub mod value;
 
 #[derive(Default)]
 pub struct LastRuntimeUpgradeInfo {
@@ -51,51 +57,155 @@ impl LastRuntimeUpgradeInfo {
     }
 }
 
-/// Chain type.
+/// Runtime version, as returned by the `state_subscribeRuntimeVersion` subscription.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuntimeVersion {
+    pub spec_name: String,
+    pub impl_name: String,
+    pub spec_version: u32,
+    pub impl_version: u32,
+    pub transaction_version: u32,
+}
+
+/// Chain type. Only the chains with chain-specific decoding rules (see `Metadata`) are known,
+/// the others are `Other` and get the generic rules. Chain parameters such as the SS58 prefix
+/// and the token decimals come from the node, see `ChainDescriptor`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum Chain {
     Kusama,
     Polkadot,
-    Darwinia,
+    Other,
 }
 
 impl FromStr for Chain {
     type Err = std::string::ParseError;
 
-    /// Get chain from string.
+    /// Get chain from string. Never fails, unknown chains are `Other`.
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "kusama" | "ksm" => Ok(Self::Kusama),
             "polkadot" | "dot" => Ok(Self::Polkadot),
-            "darwinia" => Ok(Self::Darwinia),
-            _ => panic!("Unkown chain: {}", s),
+            _ => Ok(Self::Other),
         }
     }
 }
 
 impl Chain {
-    /// SS58 encoding format for the chain.
-    fn get_ss58_address_format(&self) -> Ss58AddressFormat {
+    /// SS58 prefix of the known chains, used until the system properties of the node are
+    /// available.
+    pub fn get_ss58_prefix(&self) -> Option<u16> {
         match self {
-            Self::Kusama => Ss58AddressFormat::from(2u16),
-            Self::Polkadot => Ss58AddressFormat::from(0u16),
-            Self::Darwinia => Ss58AddressFormat::from(18u16),
+            Self::Kusama => Some(2),
+            Self::Polkadot => Some(0),
+            Self::Other => None,
         }
     }
+}
+
+/// Sets the SS58 prefix of the addresses that get printed with `to_ss58_check`.
+pub fn set_default_ss58_prefix(ss58_prefix: u16) {
+    sp_core::crypto::set_default_ss58_version(Ss58AddressFormat::from(ss58_prefix))
+}
+
+/// Generic Substrate SS58 prefix, for the chains that don't have a registered prefix.
+const GENERIC_SS58_PREFIX: u16 = 42;
+
+fn default_ss58_prefix() -> u16 {
+    GENERIC_SS58_PREFIX
+}
 
-    pub fn sp_core_set_default_ss58_version(&self) {
-        sp_core::crypto::set_default_ss58_version(self.get_ss58_address_format())
+fn default_token_symbol() -> String {
+    "UNIT".to_string()
+}
+
+/// Deserializes a system property that is either a single value or, on the multi-token chains,
+/// a list of values in which the first one is the native token.
+fn deserialize_first_property<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SingleOrMultiple<T> {
+        Single(T),
+        Multiple(Vec<T>),
     }
+    Ok(match SingleOrMultiple::<T>::deserialize(deserializer)? {
+        SingleOrMultiple::Single(value) => value,
+        SingleOrMultiple::Multiple(values) => values.into_iter().next().unwrap_or_default(),
+    })
 }
 
-/// System properties as fetched from the node RPC interface.
-#[derive(Serialize, Deserialize, Debug)]
+/// System properties as fetched from the node RPC interface. The properties are optional in
+/// the chain spec, the missing ones get the generic Substrate defaults.
+#[derive(Clone, Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct SystemProperties {
-    pub ss_58_format: u8,
+    #[serde(default = "default_ss58_prefix")]
+    pub ss_58_format: u16,
+    #[serde(default, deserialize_with = "deserialize_first_property")]
     pub token_decimals: u32,
+    #[serde(
+        default = "default_token_symbol",
+        deserialize_with = "deserialize_first_property"
+    )]
     pub token_symbol: String,
 }
 
+/// Parameters of the chain, read from the node instead of being hardcoded per chain, so that
+/// SubVT can run against any Substrate chain with the staking pallets.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ChainDescriptor {
+    pub chain: Chain,
+    pub ss58_prefix: u16,
+    pub token_decimals: u32,
+    pub token_symbol: String,
+    pub era_duration_millis: u64,
+}
+
+impl ChainDescriptor {
+    pub fn new(
+        chain: Chain,
+        system_properties: &SystemProperties,
+        constants: &metadata::MetadataConstants,
+    ) -> ChainDescriptor {
+        ChainDescriptor {
+            chain,
+            ss58_prefix: system_properties.ss_58_format,
+            token_decimals: system_properties.token_decimals,
+            token_symbol: system_properties.token_symbol.clone(),
+            era_duration_millis: constants.era_duration_millis,
+        }
+    }
+}
+
+/// Validity of the session keys of a validator, see `validate_session_keys` in
+/// `subvt-substrate-client`.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SessionKeysValidity {
+    /// Keys are the next session keys of the validator on chain (`Session.NextKeys`).
+    pub is_registered: bool,
+    /// Node has the private keys of all the session keys in its keystore. `None` if the node
+    /// doesn't allow the unsafe `author_hasSessionKeys` RPC method.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_present_on_node: Option<bool>,
+}
+
+/// Chain information fetched from a node RPC interface, used to bootstrap a SubVT deployment
+/// for a new network.
+#[derive(Debug)]
+pub struct ChainInfo {
+    pub name: String,
+    /// 0x-prefixed upper-case hex-encoded genesis block hash.
+    pub genesis_hash: String,
+    pub system_properties: SystemProperties,
+    pub constants: metadata::MetadataConstants,
+    pub spec_name: String,
+    pub spec_version: u32,
+}
+
 #[derive(Debug, Decode, Clone, Eq, PartialEq)]
 pub enum MultiAddress {
     Id(AccountId),
@@ -179,6 +289,40 @@ pub struct Block {
     pub extrinsics: Vec<String>,
 }
 
+/// Type of the BABE slot claim of a block. Primary slots are claimed through the VRF lottery,
+/// secondary slots are assigned deterministically to fill the slots without a primary claim,
+/// with (`SecondaryVRF`) or without (`SecondaryPlain`) a VRF output for the randomness.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BabeSlotType {
+    Primary,
+    SecondaryPlain,
+    SecondaryVRF,
+}
+
+impl Display for BabeSlotType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            Self::Primary => "primary",
+            Self::SecondaryPlain => "secondary_plain",
+            Self::SecondaryVRF => "secondary_vrf",
+        };
+        write!(f, "{}", display)
+    }
+}
+
+/// BABE slot claim of a block, read from the pre-runtime digest of the block header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BabeSlotClaim {
+    pub slot_number: u64,
+    pub slot_type: BabeSlotType,
+}
+
+impl BabeSlotClaim {
+    pub fn is_primary(&self) -> bool {
+        self.slot_type == BabeSlotType::Primary
+    }
+}
+
 /// A block's header as fetched from the node RPC interface.
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -197,75 +341,136 @@ impl BlockHeader {
         Ok(number)
     }
 
-    fn authority_index_from_log_bytes(
-        consensus_engine: &str,
-        bytes: &mut Vec<u8>,
-    ) -> Option<usize> {
-        match consensus_engine {
-            "BABE" => {
-                let mut data_vec_bytes: &[u8] = &bytes[..];
-                let digest: PreDigest = Decode::decode(&mut data_vec_bytes).unwrap();
-                let authority_index = match digest {
-                    PreDigest::Primary(digest) => digest.authority_index,
-                    PreDigest::SecondaryPlain(digest) => digest.authority_index,
-                    PreDigest::SecondaryVRF(digest) => digest.authority_index,
-                };
-                Some(authority_index as usize)
-            }
-            "aura" => {
-                error!("Consensus engine [{}] not supported.", consensus_engine);
-                None
-            }
-            "FRNK" => {
-                // GRANDPA
-                error!("Consensus engine [{}] not supported.", consensus_engine);
-                None
-            }
-            "pow_" => {
-                error!("Consensus engine [{}] not supported.", consensus_engine);
-                None
-            }
-            _ => {
-                error!("Unknown consensus engine [{}].", consensus_engine);
-                None
+    pub fn get_babe_slot_claim(&self) -> Option<BabeSlotClaim> {
+        for log_string in &self.digest.logs {
+            let log_hex_string = log_string.trim_start_matches("0x");
+            let log_bytes = hex::decode(&log_hex_string).ok()?;
+            let digest_item: DigestItem = Decode::decode(&mut &log_bytes[..]).ok()?;
+            if let DigestItem::PreRuntime(consensus_engine_id, bytes) = digest_item {
+                if &consensus_engine_id != b"BABE" {
+                    continue;
+                }
+                let digest: PreDigest = Decode::decode(&mut &bytes[..]).ok()?;
+                return Some(BabeSlotClaim {
+                    slot_number: u64::from(digest.slot()),
+                    slot_type: match digest {
+                        PreDigest::Primary(_) => BabeSlotType::Primary,
+                        PreDigest::SecondaryPlain(_) => BabeSlotType::SecondaryPlain,
+                        PreDigest::SecondaryVRF(_) => BabeSlotType::SecondaryVRF,
+                    },
+                });
             }
         }
+        None
     }
 
-    pub fn get_validator_index(&self) -> Option<usize> {
-        let mut validator_index: Option<usize> = None;
+    /// Reads the author information from the digest logs. Logs of the unsupported consensus
+    /// engines and the logs that cannot be decoded get recorded and skipped.
+    pub fn get_author_digest(&self) -> BlockAuthorDigest {
+        let mut author_digest = BlockAuthorDigest::default();
         for log_string in &self.digest.logs {
-            let log_hex_string = log_string.trim_start_matches("0x");
-            let mut log_bytes: &[u8] = &hex::decode(&log_hex_string).unwrap();
-            let digest_item: DigestItem = Decode::decode(&mut log_bytes).unwrap();
-            match digest_item {
-                DigestItem::PreRuntime(consensus_engine_id, mut bytes) => {
-                    let consensus_engine = std::str::from_utf8(&consensus_engine_id).unwrap();
-                    validator_index =
-                        BlockHeader::authority_index_from_log_bytes(consensus_engine, &mut bytes);
+            let log_bytes = match hex::decode(log_string.trim_start_matches("0x")) {
+                Ok(log_bytes) => log_bytes,
+                Err(error) => {
+                    author_digest
+                        .decode_errors
+                        .push(format!("Invalid log hex string: {:?}", error));
+                    continue;
+                }
+            };
+            let digest_item: DigestItem = match Decode::decode(&mut &log_bytes[..]) {
+                Ok(digest_item) => digest_item,
+                Err(error) => {
+                    author_digest
+                        .decode_errors
+                        .push(format!("Cannot decode digest item: {:?}", error));
+                    continue;
+                }
+            };
+            let (consensus_engine_id, maybe_pre_runtime_bytes) = match digest_item {
+                DigestItem::PreRuntime(consensus_engine_id, bytes) => {
+                    (consensus_engine_id, Some(bytes))
+                }
+                DigestItem::Consensus(consensus_engine_id, _)
+                | DigestItem::Seal(consensus_engine_id, _) => (consensus_engine_id, None),
+                // other log types don't carry author information
+                _ => continue,
+            };
+            match &consensus_engine_id {
+                // author is in the pre-runtime log, consensus and seal logs are skipped
+                b"BABE" => {
+                    if let Some(bytes) = maybe_pre_runtime_bytes {
+                        match PreDigest::decode(&mut &bytes[..]) {
+                            Ok(digest) => {
+                                author_digest.babe_authority_index =
+                                    Some(digest.authority_index() as usize)
+                            }
+                            Err(error) => author_digest
+                                .decode_errors
+                                .push(format!("Cannot decode BABE pre-digest: {:?}", error)),
+                        }
+                    }
                 }
-                DigestItem::Consensus(consensus_engine_id, mut bytes) => {
-                    if validator_index.is_none() {
-                        let consensus_engine = std::str::from_utf8(&consensus_engine_id).unwrap();
-                        validator_index = BlockHeader::authority_index_from_log_bytes(
-                            consensus_engine,
-                            &mut bytes,
-                        );
+                b"aura" => {
+                    if let Some(bytes) = maybe_pre_runtime_bytes {
+                        match u64::decode(&mut &bytes[..]) {
+                            Ok(slot) => author_digest.aura_slot = Some(slot),
+                            Err(error) => author_digest
+                                .decode_errors
+                                .push(format!("Cannot decode Aura slot: {:?}", error)),
+                        }
                     }
                 }
-                DigestItem::Seal(consensus_engine_id, mut bytes) => {
-                    if validator_index.is_none() {
-                        let consensus_engine = std::str::from_utf8(&consensus_engine_id).unwrap();
-                        validator_index = BlockHeader::authority_index_from_log_bytes(
-                            consensus_engine,
-                            &mut bytes,
-                        );
+                // GRANDPA & BEEFY, no author information
+                b"FRNK" | b"BEEF" => (),
+                _ => {
+                    let consensus_engine =
+                        String::from_utf8_lossy(&consensus_engine_id).to_string();
+                    if !author_digest
+                        .unsupported_consensus_engines
+                        .contains(&consensus_engine)
+                    {
+                        author_digest
+                            .unsupported_consensus_engines
+                            .push(consensus_engine);
                     }
                 }
-                _ => error!("Unknown log type."),
             }
         }
-        validator_index
+        author_digest
+    }
+
+    /// Index of the block author in the authority set of the given size. See `BlockAuthorDigest`.
+    pub fn get_validator_index(&self, authority_count: usize) -> Option<usize> {
+        self.get_author_digest()
+            .get_validator_index(authority_count)
+    }
+}
+
+/// Author information in the digest logs of a block header.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BlockAuthorDigest {
+    /// Authority index in the BABE pre-runtime digest.
+    pub babe_authority_index: Option<usize>,
+    /// Slot in the Aura pre-runtime digest. Aura authorities author the slots in turns, so the
+    /// author is the authority at index `slot % authority count`.
+    pub aura_slot: Option<u64>,
+    /// Ids of the consensus engines in the logs, which are not supported for author
+    /// identification.
+    pub unsupported_consensus_engines: Vec<String>,
+    /// Errors for the logs that cannot be decoded.
+    pub decode_errors: Vec<String>,
+}
+
+impl BlockAuthorDigest {
+    pub fn get_validator_index(&self, authority_count: usize) -> Option<usize> {
+        if let Some(authority_index) = self.babe_authority_index {
+            return Some(authority_index);
+        }
+        match self.aura_slot {
+            Some(slot) if authority_count > 0 => Some((slot % authority_count as u64) as usize),
+            _ => None,
+        }
     }
 }
 
@@ -305,6 +510,17 @@ impl Era {
 }
 
 impl Era {
+    /// Estimates the start timestamp of another era, extrapolated from this era with the
+    /// expected era duration.
+    pub fn estimate_start_timestamp(&self, era_index: u32, era_duration_millis: u64) -> u64 {
+        if era_index >= self.index {
+            self.start_timestamp + (era_index - self.index) as u64 * era_duration_millis
+        } else {
+            self.start_timestamp
+                .saturating_sub((self.index - era_index) as u64 * era_duration_millis)
+        }
+    }
+
     pub fn get_start_date_time(&self) -> DateTime<Utc> {
         Utc::timestamp(&Utc, self.start_timestamp as i64 / 1000, 0)
     }
@@ -373,6 +589,66 @@ impl ValidatorStake {
         };
         Ok(validator_stake)
     }
+
+    /// Builds the stake from the paged exposure storage of newer runtimes, the overview
+    /// (`ErasStakersOverview`) and all the pages (`ErasStakersPaged`) of the validator.
+    pub fn from_paged_bytes(
+        mut overview_bytes: &[u8],
+        page_bytes_list: &[&[u8]],
+        validator_account_id: AccountId,
+    ) -> anyhow::Result<Self> {
+        let overview: PagedExposureMetadata = Decode::decode(&mut overview_bytes)?;
+        let mut nominators: Vec<NominatorStake> = Vec::new();
+        for mut page_bytes in page_bytes_list.iter().cloned() {
+            let page: ExposurePage = Decode::decode(&mut page_bytes)?;
+            for other in page.others {
+                nominators.push(NominatorStake {
+                    account: Account {
+                        id: other.who,
+                        ..Default::default()
+                    },
+                    stake: other.value,
+                });
+            }
+        }
+        if nominators.len() != overview.nominator_count as usize {
+            return Err(anyhow::anyhow!(
+                "Expected {} nominators in {} exposure page(s) of validator {}, got {}.",
+                overview.nominator_count,
+                overview.page_count,
+                validator_account_id,
+                nominators.len(),
+            ));
+        }
+        Ok(Self {
+            account: Account {
+                id: validator_account_id,
+                ..Default::default()
+            },
+            self_stake: overview.own,
+            total_stake: overview.total,
+            nominators,
+        })
+    }
+}
+
+/// Overview of the paged exposure of a validator in an era.
+#[derive(Clone, Debug, Decode)]
+pub struct PagedExposureMetadata {
+    #[codec(compact)]
+    pub total: Balance,
+    #[codec(compact)]
+    pub own: Balance,
+    pub nominator_count: u32,
+    pub page_count: u32,
+}
+
+/// A single page of the nominator stakes of a validator in an era.
+#[derive(Clone, Debug, Decode)]
+pub struct ExposurePage {
+    #[codec(compact)]
+    pub page_total: Balance,
+    pub others: Vec<IndividualExposure<AccountId, Balance>>,
 }
 
 /// A collection of all active stakers in an era. See `ValidatorStake` too for details.
@@ -425,16 +701,93 @@ impl EraStakers {
         let mid = self.stakers.len() / 2;
         self.stakers[mid].total_stake
     }
+
+    /// Gets the minimum active stake of a nominator, i.e. the sum of its stakes in the exposures
+    /// of the active validators. Should be called on the unclipped stakers, since the clipped
+    /// exposures leave out the smallest nominations. `None` if there is no active nominator.
+    pub fn min_nominator_stake(&self) -> Option<Balance> {
+        let mut nominator_stake_map: HashMap<&AccountId, Balance> = HashMap::new();
+        for validator_stake in &self.stakers {
+            for nominator_stake in &validator_stake.nominators {
+                *nominator_stake_map
+                    .entry(&nominator_stake.account.id)
+                    .or_default() += nominator_stake.stake;
+            }
+        }
+        nominator_stake_map.into_values().min()
+    }
+}
+
+/// A voter in the election snapshot, with its vote weight (stake) and nominated targets.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ElectionVoter {
+    pub account_id: AccountId,
+    pub stake: u64,
+    pub target_account_ids: Vec<AccountId>,
+}
+
+/// Snapshot of the voters and targets taken by the election provider pallet
+/// at the beginning of an election.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ElectionSnapshot {
+    pub voters: Vec<ElectionVoter>,
+    pub targets: Vec<AccountId>,
+}
+
+impl ElectionSnapshot {
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let snapshot: RoundSnapshot<AccountId> = Decode::decode(&mut bytes)?;
+        Ok(ElectionSnapshot {
+            voters: snapshot
+                .voters
+                .into_iter()
+                .map(|(account_id, stake, target_account_ids)| ElectionVoter {
+                    account_id,
+                    stake,
+                    target_account_ids,
+                })
+                .collect(),
+            targets: snapshot.targets,
+        })
+    }
 }
 
 /// Total reward points earned over an era. It will contain the points earned so far
 /// for an active era.
-#[derive(Encode, Decode, Serialize)]
+#[derive(Default, Encode, Decode, Serialize)]
 pub struct EraRewardPoints {
     pub total: u32,
     pub individual: BTreeMap<AccountId32, u32>,
 }
 
+/// Summary of the deferred slashes that get applied at the start of an era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct EraUnappliedSlashes {
+    pub slash_count: u32,
+    /// Total of the validators' own and their nominators' slash amounts.
+    pub total_amount: Balance,
+}
+
+impl EraUnappliedSlashes {
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let slashes: Vec<UnappliedSlash<AccountId, Balance>> = Decode::decode(&mut bytes)?;
+        Ok(Self {
+            slash_count: slashes.len() as u32,
+            total_amount: slashes
+                .iter()
+                .map(|slash| {
+                    slash.own
+                        + slash
+                            .others
+                            .iter()
+                            .map(|(_, amount)| *amount)
+                            .sum::<Balance>()
+                })
+                .sum(),
+        })
+    }
+}
+
 /// Validator commission and block preferences.
 #[derive(Clone, Debug, Encode, Decode, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ValidatorPreferences {
@@ -467,6 +820,50 @@ pub struct IdentityRegistration {
     pub confirmed: bool,
 }
 
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize, sqlx::Type)]
+#[sqlx(type_name = "sub_identity_change_type", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityChangeType {
+    IdentitySet,
+    IdentityCleared,
+    IdentityKilled,
+    JudgementRequested,
+    JudgementUnrequested,
+    JudgementGiven,
+    SubIdentityAdded,
+    SubIdentityRemoved,
+    SubIdentityRevoked,
+}
+
+/// A change in the identity of an account, as indicated by an identity pallet event.
+/// For the sub-identity changes, the account is the sub account and the related account
+/// is the main account. The identity is the one right after the change, and only present
+/// for the identity set and judgement given changes.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct IdentityChange {
+    pub change_type: IdentityChangeType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub related_account_id: Option<AccountId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registrar_index: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<IdentityRegistration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_display: Option<String>,
+}
+
+impl IdentityChange {
+    pub fn new(change_type: IdentityChangeType) -> Self {
+        Self {
+            change_type,
+            related_account_id: None,
+            registrar_index: None,
+            identity: None,
+            sub_display: None,
+        }
+    }
+}
+
 pub fn data_to_string(data: Data) -> Option<String> {
     match data {
         Data::Raw(raw) => {
@@ -481,15 +878,37 @@ pub fn data_to_string(data: Data) -> Option<String> {
     }
 }
 
+/// Name of the judgement, as stored in the identity extrinsics table.
+pub fn judgement_to_string(judgement: &Judgement<Balance>) -> &'static str {
+    match judgement {
+        Judgement::Unknown => "unknown",
+        Judgement::FeePaid(_) => "fee_paid",
+        Judgement::Reasonable => "reasonable",
+        Judgement::KnownGood => "known_good",
+        Judgement::OutOfDate => "out_of_date",
+        Judgement::LowQuality => "low_quality",
+        Judgement::Erroneous => "erroneous",
+    }
+}
+
 impl IdentityRegistration {
+    pub fn from_info<FieldLimit: Get<u32>>(
+        info: IdentityInfo<FieldLimit>,
+        confirmed: bool,
+    ) -> Self {
+        IdentityRegistration {
+            display: data_to_string(info.display),
+            email: data_to_string(info.email),
+            riot: data_to_string(info.riot),
+            twitter: data_to_string(info.twitter),
+            web: data_to_string(info.web),
+            confirmed,
+        }
+    }
+
     pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
         let registration: Registration<Balance, ConstU32<{ u32::MAX }>, ConstU32<{ u32::MAX }>> =
             Decode::decode(&mut bytes)?;
-        let display = data_to_string(registration.info.display);
-        let email = data_to_string(registration.info.email);
-        let riot = data_to_string(registration.info.riot);
-        let twitter = data_to_string(registration.info.twitter);
-        let web = data_to_string(registration.info.web);
         let mut confirmed = true;
         for judgement in registration.judgements {
             confirmed &= match judgement.1 {
@@ -501,14 +920,10 @@ impl IdentityRegistration {
                 Judgement::Erroneous => false,
             };
         }
-        Ok(IdentityRegistration {
-            display,
-            email,
-            riot,
-            twitter,
-            web,
+        Ok(IdentityRegistration::from_info(
+            registration.info,
             confirmed,
-        })
+        ))
     }
 }
 
@@ -566,11 +981,14 @@ impl From<&Vec<Nomination>> for InactiveNominationsSummary {
     }
 }
 
+/// Staking ledger of a controller account, including the unbonding chunks.
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct Stake {
     pub stash_account_id: AccountId,
     pub total_amount: Balance,
     pub active_amount: Balance,
+    /// Chunks of the bonded amount that are being unbonded, earliest first.
+    pub unlocking: Vec<UnlockChunk>,
     // pub claimed_era_indices: Vec<u32>,
 }
 
@@ -581,10 +999,85 @@ impl Stake {
             stash_account_id: ledger.stash,
             total_amount: ledger.total,
             active_amount: ledger.active,
+            unlocking: ledger
+                .unlocking
+                .iter()
+                .map(|chunk| UnlockChunk {
+                    amount: chunk.value,
+                    era_index: chunk.era,
+                    ..Default::default()
+                })
+                .collect(),
             // claimed_era_indices: ledger.claimed_rewards,
         };
         Ok(stake)
     }
+
+    /// Amount that has started unbonding since the previous state of the ledger, i.e. the
+    /// new chunks and the increases of the existing chunks. Chunks that unlock in the same
+    /// era are merged by the chain.
+    pub fn get_unbonding_amount_since(&self, prev: &Stake) -> Balance {
+        self.unlocking
+            .iter()
+            .map(|chunk| {
+                let prev_amount = prev
+                    .unlocking
+                    .iter()
+                    .find(|prev_chunk| prev_chunk.era_index == chunk.era_index)
+                    .map(|prev_chunk| prev_chunk.amount)
+                    .unwrap_or(0);
+                chunk.amount.saturating_sub(prev_amount)
+            })
+            .sum()
+    }
+
+    /// Estimates the unlock timestamps of all the unbonding chunks. See `UnlockChunk`.
+    pub fn estimate_unlock_timestamps(&mut self, active_era: &Era, era_duration_millis: u64) {
+        for chunk in self.unlocking.iter_mut() {
+            chunk.estimate_unlock_timestamp(active_era, era_duration_millis);
+        }
+    }
+}
+
+/// Balance data of an account, as in the `data` field of `System.Account`.
+#[derive(Clone, Debug, Decode, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct AccountBalance {
+    pub free: Balance,
+    pub reserved: Balance,
+    pub misc_frozen: Balance,
+    pub fee_frozen: Balance,
+}
+
+impl AccountBalance {
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let account_info: frame_system::AccountInfo<u32, AccountBalance> =
+            Decode::decode(&mut bytes)?;
+        Ok(account_info.data)
+    }
+
+    /// Part of the free balance that can be spent on transaction fees without reaping the
+    /// account, i.e. the free balance over the fee locks and the existential deposit.
+    pub fn get_fee_spendable(&self, existential_deposit: Balance) -> Balance {
+        self.free
+            .saturating_sub(self.fee_frozen.max(existential_deposit))
+    }
+}
+
+/// Part of the bonded amount that is being unbonded, withdrawable at the start of the era.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct UnlockChunk {
+    pub amount: Balance,
+    pub era_index: u32,
+    /// Estimated start of the unlock era, extrapolated from the active era with the expected
+    /// era duration. `None` until estimated.
+    pub estimated_unlock_timestamp: Option<u64>,
+}
+
+impl UnlockChunk {
+    pub fn estimate_unlock_timestamp(&mut self, active_era: &Era, era_duration_millis: u64) {
+        self.estimated_unlock_timestamp =
+            Some(active_era.estimate_start_timestamp(self.era_index, era_duration_millis));
+    }
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -643,6 +1136,123 @@ impl RewardDestination {
     }
 }
 
+/// Democracy referendum information as represented in the SubVT domain.
+/// `end_block_number` is the block at which the votes are tallied for an ongoing referendum.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DemocracyReferendumInfo {
+    pub end_block_number: u32,
+    pub is_ongoing: bool,
+}
+
+impl DemocracyReferendumInfo {
+    pub fn from_bytes(mut bytes: &[u8]) -> anyhow::Result<Self> {
+        let info: ReferendumInfo<
+            polkadot_core_primitives::BlockNumber,
+            polkadot_core_primitives::Hash,
+            Balance,
+        > = Decode::decode(&mut bytes)?;
+        let referendum_info = match info {
+            ReferendumInfo::Ongoing(status) => Self {
+                end_block_number: status.end,
+                is_ongoing: true,
+            },
+            ReferendumInfo::Finished { approved: _, end } => Self {
+                end_block_number: end,
+                is_ongoing: false,
+            },
+        };
+        Ok(referendum_info)
+    }
+}
+
+/// A democracy or conviction voting vote. Standard votes have either the aye or the nay balance
+/// set along with the conviction, split votes have both balances and no conviction. Split
+/// abstain votes of the conviction voting pallet also have the abstain balance.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct DemocracyVote {
+    pub aye_balance: Option<Balance>,
+    pub nay_balance: Option<Balance>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub abstain_balance: Option<Balance>,
+    pub conviction: Option<u8>,
+}
+
+impl DemocracyVote {
+    /// Decodes an `AccountVote` that has been decoded from the runtime type registry, i.e. the
+    /// votes of the conviction voting pallet. The vote byte has the aye flag in the most
+    /// significant bit, and the conviction in the rest.
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let name = match value {
+            Value::Variant { name, .. } => name.as_str(),
+            _ => return None,
+        };
+        let get_balance = |field_name: &str| {
+            value
+                .get_field(field_name)
+                .and_then(|field| field.as_unsigned())
+        };
+        match name {
+            "Standard" => {
+                let vote = value.get_field("vote")?.as_unsigned()? as u8;
+                let balance = get_balance("balance")?;
+                let is_aye = (vote & 0b10000000) != 0;
+                Some(Self {
+                    aye_balance: if is_aye { Some(balance) } else { None },
+                    nay_balance: if is_aye { None } else { Some(balance) },
+                    abstain_balance: None,
+                    conviction: Some(vote & 0b01111111),
+                })
+            }
+            "Split" => Some(Self {
+                aye_balance: Some(get_balance("aye")?),
+                nay_balance: Some(get_balance("nay")?),
+                abstain_balance: None,
+                conviction: None,
+            }),
+            "SplitAbstain" => Some(Self {
+                aye_balance: Some(get_balance("aye")?),
+                nay_balance: Some(get_balance("nay")?),
+                abstain_balance: Some(get_balance("abstain")?),
+                conviction: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Index of a conviction by its variant name, from `None` (0.1x) to `Locked6x`.
+pub fn get_conviction_index(name: &str) -> Option<u8> {
+    match name {
+        "None" => Some(0),
+        "Locked1x" => Some(1),
+        "Locked2x" => Some(2),
+        "Locked3x" => Some(3),
+        "Locked4x" => Some(4),
+        "Locked5x" => Some(5),
+        "Locked6x" => Some(6),
+        _ => None,
+    }
+}
+
+impl From<AccountVote<Balance>> for DemocracyVote {
+    fn from(account_vote: AccountVote<Balance>) -> Self {
+        match account_vote {
+            AccountVote::Standard { vote, balance } => Self {
+                aye_balance: if vote.aye { Some(balance) } else { None },
+                nay_balance: if vote.aye { None } else { Some(balance) },
+                abstain_balance: None,
+                conviction: Some(vote.conviction.into()),
+            },
+            AccountVote::Split { aye, nay } => Self {
+                aye_balance: Some(aye),
+                nay_balance: Some(nay),
+                abstain_balance: None,
+                conviction: None,
+            },
+        }
+    }
+}
+
 #[derive(Clone, Debug, Decode)]
 pub enum SlotRange {
     ZeroZero,
@@ -675,3 +1285,110 @@ pub struct Slash {
     pub validator_account_id: AccountId,
     pub amount: u128,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{BlockAuthorDigest, BlockHeader, EventDigest};
+
+    fn get_block_header(logs: &[&str]) -> BlockHeader {
+        BlockHeader {
+            digest: EventDigest {
+                logs: logs.iter().map(|log| log.to_string()).collect(),
+            },
+            extrinsics_root: String::new(),
+            number: "0x0".to_string(),
+            parent_hash: String::new(),
+            state_root: String::new(),
+        }
+    }
+
+    /// BABE seal log with an empty signature, carries no author information.
+    fn get_babe_seal_log() -> String {
+        format!("0x05424142450101{}", "00".repeat(64))
+    }
+
+    #[test]
+    fn test_babe_primary_author() {
+        // pre-runtime log: primary pre-digest of authority #12 at slot 262493679, with zero
+        // VRF output and proof
+        let pre_runtime_log = format!(
+            "0x0642414245b501010c000000ef55a50f00000000{}",
+            "00".repeat(96)
+        );
+        let header = get_block_header(&[&pre_runtime_log, &get_babe_seal_log()]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(author_digest.babe_authority_index, Some(12));
+        assert!(author_digest.unsupported_consensus_engines.is_empty());
+        assert!(author_digest.decode_errors.is_empty());
+        assert_eq!(header.get_validator_index(1000), Some(12));
+    }
+
+    #[test]
+    fn test_babe_secondary_author() {
+        // pre-runtime log: secondary plain pre-digest of authority #1 at slot 262493679
+        let header = get_block_header(&[
+            "0x0642414245340201000000ef55a50f00000000",
+            &get_babe_seal_log(),
+        ]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(author_digest.babe_authority_index, Some(1));
+        assert!(author_digest.decode_errors.is_empty());
+        assert_eq!(header.get_validator_index(1000), Some(1));
+    }
+
+    #[test]
+    fn test_aura_author() {
+        // pre-runtime log: Aura slot 100003
+        let header = get_block_header(&["0x066175726120a386010000000000"]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(author_digest.aura_slot, Some(100003));
+        assert_eq!(author_digest.babe_authority_index, None);
+        assert_eq!(header.get_validator_index(10), Some(3));
+        assert_eq!(header.get_validator_index(7), Some(100003 % 7));
+    }
+
+    #[test]
+    fn test_aura_author_without_authorities() {
+        let header = get_block_header(&["0x066175726120a386010000000000"]);
+        assert_eq!(header.get_validator_index(0), None);
+        let author_digest = BlockAuthorDigest {
+            aura_slot: Some(100003),
+            ..Default::default()
+        };
+        assert_eq!(author_digest.get_validator_index(0), None);
+    }
+
+    #[test]
+    fn test_unknown_consensus_engine() {
+        // pre-runtime log of the `POW_` engine, twice, with an empty payload
+        let header = get_block_header(&["0x06504f575f00", "0x06504f575f00"]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(
+            author_digest.unsupported_consensus_engines,
+            vec!["POW_".to_string()]
+        );
+        assert!(author_digest.decode_errors.is_empty());
+        assert_eq!(header.get_validator_index(10), None);
+    }
+
+    #[test]
+    fn test_undecodable_logs() {
+        let header = get_block_header(&[
+            // invalid hex
+            "0x06zz",
+            // truncated pre-runtime log
+            "0x06424142",
+            // BABE pre-runtime log with an unknown pre-digest variant
+            "0x0642414245340901000000ef55a50f00000000",
+        ]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(author_digest.decode_errors.len(), 3);
+        assert_eq!(author_digest.babe_authority_index, None);
+        assert_eq!(header.get_validator_index(10), None);
+        // the decodable logs are still read
+        let header = get_block_header(&["0x06zz", "0x0642414245340201000000ef55a50f00000000"]);
+        let author_digest = header.get_author_digest();
+        assert_eq!(author_digest.decode_errors.len(), 1);
+        assert_eq!(header.get_validator_index(10), Some(1));
+    }
+}
@@ -0,0 +1,149 @@
+//! Types of the nomination pools pallet (`NominationPools`). Pool members bond their funds to
+//! the pool's bonded account, which is the stash and the controller of the pool's stake and
+//! nominates on behalf of the members. Decoded from the storage layout of the pallet with
+//! the reward counters and without the commission (storage versions 2 to 4).
+use crate::crypto::AccountId;
+use crate::substrate::Balance;
+use parity_scale_codec::{Decode, Encode};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub type PoolId = u32;
+
+/// `modl` prefix of the pallet sub-accounts.
+const PALLET_ACCOUNT_PREFIX: &[u8; 4] = b"modl";
+
+/// Pallet sub-account types, as in the pallet's `AccountType`.
+#[derive(Encode)]
+enum PoolAccountType {
+    Bonded,
+}
+
+#[derive(Clone, Copy, Debug, Decode, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NominationPoolState {
+    /// Open to new members.
+    Open,
+    /// Not open to new members.
+    Blocked,
+    /// Members are being removed before the pool gets dissolved.
+    Destroying,
+}
+
+#[derive(Clone, Debug, Decode, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NominationPoolRoles {
+    pub depositor_account_id: AccountId,
+    pub root_account_id: Option<AccountId>,
+    pub nominator_account_id: Option<AccountId>,
+    pub state_toggler_account_id: Option<AccountId>,
+}
+
+/// `BondedPools` storage value.
+#[derive(Decode)]
+struct BondedPoolInner {
+    points: Balance,
+    state: NominationPoolState,
+    member_counter: u32,
+    roles: NominationPoolRoles,
+}
+
+/// `PoolMembers` storage value.
+#[derive(Decode)]
+struct PoolMember {
+    pool_id: PoolId,
+    points: Balance,
+    _last_recorded_reward_counter: u128,
+    unbonding_eras: BTreeMap<u32, Balance>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NominationPool {
+    pub id: PoolId,
+    pub state: NominationPoolState,
+    pub member_count: u32,
+    /// Total points of the members, the bonded amount of a member is proportional to its points.
+    pub points: Balance,
+    pub roles: NominationPoolRoles,
+    /// Stash and controller account of the pool's stake.
+    pub bonded_account_id: AccountId,
+    /// Active bonded amount of the pool.
+    pub bonded_amount: Balance,
+    /// Validators nominated by the pool, empty if the pool isn't nominating.
+    pub nominated_validator_account_ids: Vec<AccountId>,
+}
+
+impl NominationPool {
+    /// Decodes a `BondedPools` storage value. The bonded amount and the nominations are
+    /// in the staking storage of the bonded account, so they're left empty.
+    pub fn from_bytes(
+        mut bytes: &[u8],
+        id: PoolId,
+        bonded_account_id: AccountId,
+    ) -> anyhow::Result<Self> {
+        let inner: BondedPoolInner = Decode::decode(&mut bytes)?;
+        Ok(NominationPool {
+            id,
+            state: inner.state,
+            member_count: inner.member_counter,
+            points: inner.points,
+            roles: inner.roles,
+            bonded_account_id,
+            bonded_amount: 0,
+            nominated_validator_account_ids: Vec::new(),
+        })
+    }
+
+    /// Bonded amount of the given points of the pool.
+    pub fn points_to_balance(&self, points: Balance) -> Balance {
+        if self.points == 0 {
+            return 0;
+        }
+        self.bonded_amount.saturating_mul(points) / self.points
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct NominationPoolMember {
+    pub account_id: AccountId,
+    pub pool_id: PoolId,
+    /// Points of the member in the pool, see `NominationPool::points_to_balance`.
+    pub points: Balance,
+    /// Unbonding points by the era the unbonding gets withdrawable.
+    pub unbonding_eras: BTreeMap<u32, Balance>,
+}
+
+impl NominationPoolMember {
+    /// Decodes a `PoolMembers` storage value.
+    pub fn from_bytes(mut bytes: &[u8], account_id: AccountId) -> anyhow::Result<Self> {
+        let member: PoolMember = Decode::decode(&mut bytes)?;
+        Ok(NominationPoolMember {
+            account_id,
+            pool_id: member.pool_id,
+            points: member.points,
+            unbonding_eras: member.unbonding_eras,
+        })
+    }
+}
+
+/// Number and total active bonded amount of the nominations of a validator made by nomination
+/// pools.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PoolNominationsSummary {
+    pub nomination_count: u16,
+    pub total_amount: Balance,
+}
+
+/// Bonded (stash) account of a pool, derived from the pallet id (`NominationPools.PalletId`
+/// constant) like the pallet's sub-accounts.
+pub fn get_pool_bonded_account_id(pallet_id: &[u8; 8], pool_id: PoolId) -> AccountId {
+    let encoded = (
+        PALLET_ACCOUNT_PREFIX,
+        pallet_id,
+        PoolAccountType::Bonded,
+        pool_id,
+    )
+        .encode();
+    let mut bytes = [0u8; 32];
+    bytes[..encoded.len()].copy_from_slice(&encoded);
+    AccountId::from(bytes)
+}
@@ -0,0 +1,246 @@
+//! Self-describing runtime values, decoded from the type registry of the runtime metadata
+//! (v14 and later). Used for the call and event arguments that have no typed representation in
+//! SubVT, so that the extrinsics and events of any pallet can be decoded without code changes
+//! after a runtime upgrade.
+use crate::crypto::AccountId;
+use crate::substrate::argument::ArgumentDecodeError;
+use parity_scale_codec::{Compact, Decode};
+use scale_info::{form::PortableForm, PortableRegistry, TypeDef, TypeDefPrimitive};
+
+/// A named (struct) or unnamed (tuple) field of a composite or variant value.
+pub type ValueField = (Option<String>, Value);
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    Char(char),
+    String(String),
+    /// All unsigned integers up to 128 bits, compact or not.
+    Unsigned(u128),
+    /// All signed integers up to 128 bits.
+    Signed(i128),
+    /// Byte sequences and arrays, 256-bit integers and bit sequences, as encoded.
+    Bytes(Vec<u8>),
+    /// Sequences, arrays and tuples.
+    Sequence(Vec<Value>),
+    Composite(Vec<ValueField>),
+    Variant {
+        name: String,
+        fields: Vec<ValueField>,
+    },
+}
+
+fn decode_error(message: String) -> ArgumentDecodeError {
+    ArgumentDecodeError::DecodeError(message)
+}
+
+fn decode_primitive<T: Decode>(name: &str, bytes: &mut &[u8]) -> Result<T, ArgumentDecodeError> {
+    T::decode(&mut *bytes).map_err(|_| decode_error(format!("Cannot decode {}.", name)))
+}
+
+fn is_u8(registry: &PortableRegistry, type_id: u32) -> bool {
+    matches!(
+        registry.resolve(type_id).map(|ty| ty.type_def()),
+        Some(TypeDef::Primitive(TypeDefPrimitive::U8))
+    )
+}
+
+impl Value {
+    /// Decodes a value of the given registry type from the bytes.
+    pub fn decode(
+        registry: &PortableRegistry,
+        type_id: u32,
+        bytes: &mut &[u8],
+    ) -> Result<Self, ArgumentDecodeError> {
+        let ty = registry
+            .resolve(type_id)
+            .ok_or_else(|| decode_error(format!("Cannot find type #{} in registry.", type_id)))?;
+        let value = match ty.type_def() {
+            TypeDef::Primitive(primitive) => Value::decode_primitive_value(primitive, bytes)?,
+            TypeDef::Composite(composite) => {
+                Value::Composite(Value::decode_fields(registry, composite.fields(), bytes)?)
+            }
+            TypeDef::Variant(variant) => {
+                let index: u8 = decode_primitive("variant index", bytes)?;
+                let variant = variant
+                    .variants()
+                    .iter()
+                    .find(|variant| variant.index() == index)
+                    .ok_or_else(|| {
+                        decode_error(format!(
+                            "Cannot find variant #{} of {}.",
+                            index,
+                            ty.path().segments().join("::")
+                        ))
+                    })?;
+                Value::Variant {
+                    name: variant.name().clone(),
+                    fields: Value::decode_fields(registry, variant.fields(), bytes)?,
+                }
+            }
+            TypeDef::Sequence(sequence) => {
+                let length: Compact<u32> = decode_primitive("sequence length", bytes)?;
+                Value::decode_items(
+                    registry,
+                    sequence.type_param().id(),
+                    length.0 as usize,
+                    bytes,
+                )?
+            }
+            TypeDef::Array(array) => Value::decode_items(
+                registry,
+                array.type_param().id(),
+                array.len() as usize,
+                bytes,
+            )?,
+            TypeDef::Tuple(tuple) => {
+                let mut items = Vec::new();
+                for field_ty in tuple.fields() {
+                    items.push(Value::decode(registry, field_ty.id(), bytes)?);
+                }
+                Value::Sequence(items)
+            }
+            // the compact encoding doesn't depend on the width of the integer, nor on the
+            // single-field composite that may wrap it
+            TypeDef::Compact(_) => {
+                let compact: Compact<u128> = decode_primitive("compact", bytes)?;
+                Value::Unsigned(compact.0)
+            }
+            TypeDef::BitSequence(bit_sequence) => {
+                let bit_count: Compact<u32> = decode_primitive("bit sequence length", bytes)?;
+                let store_byte_count = match registry
+                    .resolve(bit_sequence.bit_store_type().id())
+                    .map(|ty| ty.type_def())
+                {
+                    Some(TypeDef::Primitive(TypeDefPrimitive::U8)) => 1,
+                    Some(TypeDef::Primitive(TypeDefPrimitive::U16)) => 2,
+                    Some(TypeDef::Primitive(TypeDefPrimitive::U32)) => 4,
+                    Some(TypeDef::Primitive(TypeDefPrimitive::U64)) => 8,
+                    _ => return Err(decode_error("Unknown bit sequence store type.".to_string())),
+                };
+                let store_bit_count = store_byte_count * 8;
+                let store_count = (bit_count.0 as usize + store_bit_count - 1) / store_bit_count;
+                Value::Bytes(Value::take_bytes(store_count * store_byte_count, bytes)?)
+            }
+        };
+        Ok(value)
+    }
+
+    fn decode_primitive_value(
+        primitive: &TypeDefPrimitive,
+        bytes: &mut &[u8],
+    ) -> Result<Self, ArgumentDecodeError> {
+        let value = match primitive {
+            TypeDefPrimitive::Bool => Value::Bool(decode_primitive("bool", bytes)?),
+            TypeDefPrimitive::Char => {
+                let code_point: u32 = decode_primitive("char", bytes)?;
+                Value::Char(
+                    char::from_u32(code_point)
+                        .ok_or_else(|| decode_error("Invalid char.".to_string()))?,
+                )
+            }
+            TypeDefPrimitive::Str => Value::String(decode_primitive("str", bytes)?),
+            TypeDefPrimitive::U8 => Value::Unsigned(decode_primitive::<u8>("u8", bytes)? as u128),
+            TypeDefPrimitive::U16 => {
+                Value::Unsigned(decode_primitive::<u16>("u16", bytes)? as u128)
+            }
+            TypeDefPrimitive::U32 => {
+                Value::Unsigned(decode_primitive::<u32>("u32", bytes)? as u128)
+            }
+            TypeDefPrimitive::U64 => {
+                Value::Unsigned(decode_primitive::<u64>("u64", bytes)? as u128)
+            }
+            TypeDefPrimitive::U128 => Value::Unsigned(decode_primitive("u128", bytes)?),
+            TypeDefPrimitive::I8 => Value::Signed(decode_primitive::<i8>("i8", bytes)? as i128),
+            TypeDefPrimitive::I16 => Value::Signed(decode_primitive::<i16>("i16", bytes)? as i128),
+            TypeDefPrimitive::I32 => Value::Signed(decode_primitive::<i32>("i32", bytes)? as i128),
+            TypeDefPrimitive::I64 => Value::Signed(decode_primitive::<i64>("i64", bytes)? as i128),
+            TypeDefPrimitive::I128 => Value::Signed(decode_primitive("i128", bytes)?),
+            TypeDefPrimitive::U256 | TypeDefPrimitive::I256 => {
+                Value::Bytes(Value::take_bytes(32, bytes)?)
+            }
+        };
+        Ok(value)
+    }
+
+    fn decode_fields(
+        registry: &PortableRegistry,
+        fields: &[scale_info::Field<PortableForm>],
+        bytes: &mut &[u8],
+    ) -> Result<Vec<ValueField>, ArgumentDecodeError> {
+        let mut values = Vec::new();
+        for field in fields {
+            values.push((
+                field.name().cloned(),
+                Value::decode(registry, field.ty().id(), bytes)?,
+            ));
+        }
+        Ok(values)
+    }
+
+    /// Byte sequences and arrays are kept as bytes rather than as a sequence of integers.
+    fn decode_items(
+        registry: &PortableRegistry,
+        item_type_id: u32,
+        count: usize,
+        bytes: &mut &[u8],
+    ) -> Result<Self, ArgumentDecodeError> {
+        if is_u8(registry, item_type_id) {
+            return Ok(Value::Bytes(Value::take_bytes(count, bytes)?));
+        }
+        let mut items = Vec::new();
+        for _ in 0..count {
+            items.push(Value::decode(registry, item_type_id, bytes)?);
+        }
+        Ok(Value::Sequence(items))
+    }
+
+    fn take_bytes(count: usize, bytes: &mut &[u8]) -> Result<Vec<u8>, ArgumentDecodeError> {
+        if bytes.len() < count {
+            return Err(decode_error(format!(
+                "Expected {} bytes, found {}.",
+                count,
+                bytes.len()
+            )));
+        }
+        let (taken, rest) = bytes.split_at(count);
+        *bytes = rest;
+        Ok(taken.to_vec())
+    }
+
+    /// Field of a composite or variant value by name.
+    pub fn get_field(&self, name: &str) -> Option<&Value> {
+        let fields = match self {
+            Value::Composite(fields) => fields,
+            Value::Variant { fields, .. } => fields,
+            _ => return None,
+        };
+        fields
+            .iter()
+            .find(|(field_name, _)| field_name.as_deref() == Some(name))
+            .map(|(_, value)| value)
+    }
+
+    /// Unsigned integer, also when wrapped in a single-field composite, e.g. `Vote(u8)` of the
+    /// democracy and conviction voting pallets.
+    pub fn as_unsigned(&self) -> Option<u128> {
+        match self {
+            Value::Unsigned(value) => Some(*value),
+            Value::Composite(fields) if fields.len() == 1 => fields[0].1.as_unsigned(),
+            _ => None,
+        }
+    }
+
+    /// 32-byte account id, also when wrapped in a single-field composite or in the `Id`
+    /// variant of a lookup source (`MultiAddress`).
+    pub fn as_account_id(&self) -> Option<AccountId> {
+        match self {
+            Value::Bytes(bytes) if bytes.len() == 32 => AccountId::try_from(&bytes[..]).ok(),
+            Value::Composite(fields) if fields.len() == 1 => fields[0].1.as_account_id(),
+            Value::Variant { name, fields } if name == "Id" && fields.len() == 1 => {
+                fields[0].1.as_account_id()
+            }
+            _ => None,
+        }
+    }
+}
@@ -3,12 +3,15 @@
 //! by other services that require it.
 
 use crate::crypto::AccountId;
+use crate::substrate::nomination_pools::PoolNominationsSummary;
 use crate::substrate::{
-    Account, Balance, Epoch, Era, InactiveNominationsSummary, Nomination, RewardDestination, Stake,
-    StakeSummary, ValidatorPreferences, ValidatorStake,
+    Account, Balance, ChainDescriptor, Epoch, Era, InactiveNominationsSummary, Nomination,
+    RewardDestination, Stake, StakeSummary, ValidatorPreferences, ValidatorStake,
 };
 use serde::{Deserialize, Serialize};
 use std::convert::From;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 use subvt_proc_macro::Diff;
 
 /// Represents the network's status that changes with every block.
@@ -43,19 +46,205 @@ pub struct LiveNetworkStatusUpdate {
     pub diff: Option<LiveNetworkStatusDiff>,
 }
 
+/// A change in the validator's commission and/or nomination blocking preference,
+/// from an indexed `staking.validate` extrinsic.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ValidatorPreferenceChange {
+    pub block_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    pub era_index: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_commission_per_billion: Option<u32>,
+    pub commission_per_billion: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_blocks_nominations: Option<bool>,
+    pub blocks_nominations: bool,
+}
+
+/// Type of a validator activity, see `ValidatorActivity`.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidatorActivityType {
+    /// Successful `payout_stakers` extrinsic for the validator.
+    Payout,
+    NominationReceived,
+    /// Successful `validate` extrinsic of the validator.
+    PreferenceChange,
+    OfflineOffence,
+    Slash,
+}
+
+impl Display for ValidatorActivityType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            ValidatorActivityType::Payout => "payout",
+            ValidatorActivityType::NominationReceived => "nomination_received",
+            ValidatorActivityType::PreferenceChange => "preference_change",
+            ValidatorActivityType::OfflineOffence => "offline_offence",
+            ValidatorActivityType::Slash => "slash",
+        };
+        write!(f, "{}", display)
+    }
+}
+
+impl FromStr for ValidatorActivityType {
+    type Err = anyhow::Error;
+
+    fn from_str(activity_type: &str) -> Result<Self, Self::Err> {
+        match activity_type {
+            "payout" => Ok(ValidatorActivityType::Payout),
+            "nomination_received" => Ok(ValidatorActivityType::NominationReceived),
+            "preference_change" => Ok(ValidatorActivityType::PreferenceChange),
+            "offline_offence" => Ok(ValidatorActivityType::OfflineOffence),
+            "slash" => Ok(ValidatorActivityType::Slash),
+            _ => Err(anyhow::anyhow!(
+                "Unknown validator activity type: {}",
+                activity_type
+            )),
+        }
+    }
+}
+
+/// An entry of the recent on-chain activity feed of a validator, assembled from the indexed
+/// extrinsics, events and application events.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ValidatorActivity {
+    pub activity_type: ValidatorActivityType,
+    pub block_number: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Paid era for a payout, era of the block otherwise.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub era_index: Option<u32>,
+    /// Payout caller or nominator stash account.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<AccountId>,
+    /// Active nomination amount or slashed amount.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<Balance>,
+    /// New commission of a preference change.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commission_per_billion: Option<u32>,
+}
+
+/// Source of a commission change announcement.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub enum CommissionChangeAnnouncementSource {
+    /// `system.remark` extrinsic of the validator, see `AnnouncedCommissionChange`.
+    #[serde(rename = "remark")]
+    Remark,
+    /// Commission declared to the 1KV programme.
+    #[serde(rename = "onekv")]
+    OneKV,
+}
+
+impl Display for CommissionChangeAnnouncementSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let display = match self {
+            CommissionChangeAnnouncementSource::Remark => "remark",
+            CommissionChangeAnnouncementSource::OneKV => "onekv",
+        };
+        write!(f, "{}", display)
+    }
+}
+
+impl FromStr for CommissionChangeAnnouncementSource {
+    type Err = anyhow::Error;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "remark" => Ok(CommissionChangeAnnouncementSource::Remark),
+            "onekv" => Ok(CommissionChangeAnnouncementSource::OneKV),
+            _ => Err(anyhow::anyhow!(
+                "Unknown commission change announcement source: {}",
+                source
+            )),
+        }
+    }
+}
+
+/// Commission change that the validator has announced ahead of the on-chain change, so that
+/// the nominators get an advance warning. A validator announces a change on-chain with a
+/// `system.remark` of the form `subvt:commission:<percent>[:<effective era index>]` signed by
+/// the stash or the controller account, e.g. `subvt:commission:5.5:3100`. An announcement
+/// is dropped once the on-chain commission changes after it.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct AnnouncedCommissionChange {
+    pub source: CommissionChangeAnnouncementSource,
+    pub commission_per_billion: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub effective_era_index: Option<u32>,
+    /// Milliseconds since the Unix epoch.
+    pub announced_at: u64,
+}
+
+impl AnnouncedCommissionChange {
+    const REMARK_PREFIX: &'static str = "subvt:commission:";
+
+    /// Parses the percent with up to 7 decimal places into per-billion.
+    fn parse_percent_per_billion(percent: &str) -> Option<u32> {
+        let (integer, fraction) = percent.split_once('.').unwrap_or((percent, ""));
+        if integer.is_empty()
+            || fraction.len() > 7
+            || !integer.chars().all(|c| c.is_ascii_digit())
+            || !fraction.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let integer: u64 = integer.parse().ok()?;
+        let fraction: u64 = format!("{:0<7}", fraction).parse().ok()?;
+        let per_billion = integer.checked_mul(10_000_000)?.checked_add(fraction)?;
+        if per_billion > 1_000_000_000 {
+            None
+        } else {
+            Some(per_billion as u32)
+        }
+    }
+
+    /// Parses a commission change announcement remark, returns the announced commission
+    /// per billion and the optional effective era index. `None` if the remark is not an
+    /// announcement or is malformed.
+    pub fn parse_remark(remark: &[u8]) -> Option<(u32, Option<u32>)> {
+        let remark = std::str::from_utf8(remark).ok()?.trim();
+        let announcement = remark.strip_prefix(Self::REMARK_PREFIX)?;
+        let mut parts = announcement.split(':');
+        let commission_per_billion = Self::parse_percent_per_billion(parts.next()?)?;
+        let effective_era_index = match parts.next() {
+            Some(era_index) => Some(era_index.parse::<u32>().ok()?),
+            None => None,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some((commission_per_billion, effective_era_index))
+    }
+}
+
 /// Represents an inactive validator, waiting to be in the active set.
 #[derive(Clone, Debug, Default, Deserialize, Diff, Eq, Hash, PartialEq, Serialize)]
 pub struct ValidatorDetails {
     #[diff_key]
     pub account: Account,
     pub controller_account_id: AccountId,
+    pub controller_free_balance: Balance,
+    /// Part of the controller free balance that can be spent on transaction fees.
+    pub controller_fee_spendable_balance: Balance,
     pub preferences: ValidatorPreferences,
     pub self_stake: Stake,
     pub reward_destination: RewardDestination,
     pub next_session_keys: String,
     pub is_active: bool,
     pub active_next_session: bool,
+    /// Active validator that has chilled, stays active until the end of the era.
+    pub is_chilling: bool,
+    /// Active validator with a validation intent that is not in the next session's
+    /// validator set, i.e. drops out next session.
+    pub is_parked: bool,
     pub nominations: Vec<Nomination>,
+    /// Nominations made by nomination pools, `None` if the network doesn't have pools.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_nominations: Option<PoolNominationsSummary>,
     pub oversubscribed: bool,
     pub active_era_count: u64,
     pub inactive_era_count: u64,
@@ -63,6 +252,11 @@ pub struct ValidatorDetails {
     pub offline_offence_count: u64,
     pub total_reward_points: u64,
     pub unclaimed_era_indices: Vec<u32>,
+    /// Most recent preference changes, latest first.
+    pub preference_changes: Vec<ValidatorPreferenceChange>,
+    /// Most recent on-chain activity, latest first.
+    pub recent_activity: Vec<ValidatorActivity>,
+    pub risk_flags: ValidatorRiskFlags,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_parachain_validator: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -81,6 +275,82 @@ pub struct ValidatorDetails {
     pub onekv_rank: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub onekv_is_valid: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<ValidatorLocation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub missed_primary_slots: Option<MissedPrimarySlots>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_session_reward_points: Option<SessionRewardPoints>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announced_commission_change: Option<AnnouncedCommissionChange>,
+    /// Average number of eras that elapsed after the end of an era before its payout, in
+    /// thousandths of an era, over the eras paid out within the most recent
+    /// `validator_list_updater.payout_lag_era_count` eras.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_payout_lag_eras_thousandths: Option<u32>,
+}
+
+/// Expected and actual BABE slot claims of an active validator in the last completed
+/// session (epoch). Primary slots are won through a private VRF lottery, so the expected number of
+/// primary slots is statistical: `slots * (1 - (1 - c) ^ (1 / validator count))`.
+/// A primary block shortfall is only attributed to downtime if the validator hasn't
+/// authored any secondary blocks either, and the shortfall is unlikely to be bad luck.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct MissedPrimarySlots {
+    pub session_index: u64,
+    pub elapsed_slot_count: u64,
+    /// Expected number of primary slots in thousandths, e.g. `1500` for 1.5 slots.
+    pub expected_primary_slot_count_thousandths: u64,
+    pub primary_block_count: u32,
+    pub secondary_block_count: u32,
+    /// Rounded-down expected primary slot count minus the authored primary block count.
+    pub missed_primary_slot_count: u32,
+    /// Probability of authoring at most this many primary blocks by chance.
+    pub bad_luck_probability_per_billion: u32,
+    pub is_likely_offline: bool,
+}
+
+/// Era reward points earned by an active validator in the last completed session, i.e. the
+/// increase in its `ErasRewardPoints` between the first blocks of the session and the next one.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct SessionRewardPoints {
+    pub session_index: u64,
+    pub reward_points: u32,
+    /// Number of the consecutive sessions up to and including this session in which the
+    /// validator has been active and hasn't earned any points, `0` if it has earned points
+    /// in this session. Counted from the start of `subvt-validator-list-updater`.
+    pub zero_point_session_count: u32,
+}
+
+/// Normalized node location of a validator, resolved by `subvt-geolocation-updater` from the
+/// Telemetry node coordinates, or from the 1KV candidate location when the node is not
+/// on Telemetry.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ValidatorLocation {
+    /// ISO 3166-1 alpha-2 country code, e.g. `DE`.
+    pub country_code: String,
+    /// ISO 3166-2 subdivision code, e.g. `DE-HE`. Not available when the location precision
+    /// is configured to be country-level.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region_code: Option<String>,
+}
+
+/// Warning flags computed by `subvt-validator-list-updater`, so that all the clients
+/// apply the same warning logic.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ValidatorRiskFlags {
+    pub commission_100_percent: bool,
+    pub blocks_nominations: bool,
+    /// Neither the validator nor its parent account has an on-chain identity.
+    pub no_identity: bool,
+    /// Slashed within the configured number of most recent eras.
+    pub slashed_recently: bool,
+    pub oversubscribed: bool,
+    /// Controller account has been reaped.
+    pub inactive_controller: bool,
+    /// Controller account may not be able to pay the fees of the staking extrinsics, e.g. to
+    /// validate again after getting chilled.
+    pub controller_low_balance: bool,
 }
 
 #[derive(Clone, Debug, Default, Deserialize, Diff, Eq, Hash, PartialEq, Serialize)]
@@ -99,10 +369,15 @@ pub struct ValidatorSummary {
     pub self_stake: StakeSummary,
     pub is_active: bool,
     pub active_next_session: bool,
+    pub is_chilling: bool,
+    pub is_parked: bool,
     pub inactive_nominations: InactiveNominationsSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pool_nominations: Option<PoolNominationsSummary>,
     pub oversubscribed: bool,
     pub slash_count: u64,
     pub is_enrolled_in_1kv: bool,
+    pub risk_flags: ValidatorRiskFlags,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_parachain_validator: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -115,6 +390,10 @@ pub struct ValidatorSummary {
     pub heartbeat_received: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub validator_stake: Option<ValidatorStakeSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announced_commission_change: Option<AnnouncedCommissionChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub avg_payout_lag_eras_thousandths: Option<u32>,
 }
 
 impl ValidatorDetails {
@@ -170,10 +449,14 @@ impl From<&ValidatorDetails> for ValidatorSummary {
             is_active: validator.is_active,
             is_parachain_validator: validator.is_parachain_validator,
             active_next_session: validator.active_next_session,
+            is_chilling: validator.is_chilling,
+            is_parked: validator.is_parked,
             inactive_nominations: InactiveNominationsSummary::from(&inactive_nominations),
+            pool_nominations: validator.pool_nominations.clone(),
             oversubscribed: validator.oversubscribed,
             slash_count: validator.slash_count,
             is_enrolled_in_1kv: validator.onekv_candidate_record_id.is_some(),
+            risk_flags: validator.risk_flags.clone(),
             blocks_authored: validator.blocks_authored,
             reward_points: validator.reward_points,
             heartbeat_received: validator.heartbeat_received,
@@ -182,6 +465,29 @@ impl From<&ValidatorDetails> for ValidatorSummary {
                 .validator_stake
                 .as_ref()
                 .map(ValidatorStakeSummary::from),
+            announced_commission_change: validator.announced_commission_change.clone(),
+            avg_payout_lag_eras_thousandths: validator.avg_payout_lag_eras_thousandths,
+        }
+    }
+}
+
+/// Token and address formatting properties and the era duration of the network, so that the
+/// clients don't need to hardcode them.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct NetworkMeta {
+    pub ss58_prefix: u16,
+    pub token_decimals: u32,
+    pub token_symbol: String,
+    pub era_duration_millis: u64,
+}
+
+impl From<&ChainDescriptor> for NetworkMeta {
+    fn from(chain_descriptor: &ChainDescriptor) -> Self {
+        NetworkMeta {
+            ss58_prefix: chain_descriptor.ss58_prefix,
+            token_decimals: chain_descriptor.token_decimals,
+            token_symbol: chain_descriptor.token_symbol.clone(),
+            era_duration_millis: chain_descriptor.era_duration_millis,
         }
     }
 }
@@ -190,11 +496,84 @@ impl From<&ValidatorDetails> for ValidatorSummary {
 pub struct ValidatorListUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub finalized_block_number: Option<u64>,
+    /// Finalized block number of the preceding update, so that the subscribers can detect
+    /// missed updates. Not set in the complete list.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub previous_finalized_block_number: Option<u64>,
+    /// The list is a best-effort reconstruction from the latest indexed era, published by
+    /// `subvt-validator-list-updater` on start until the list of the first finalized block
+    /// is ready.
+    pub is_provisional: bool,
+    /// Only in the first message of a subscription.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_meta: Option<NetworkMeta>,
     pub insert: Vec<ValidatorSummary>,
     pub update: Vec<ValidatorSummaryDiff>,
     pub remove_ids: Vec<AccountId>,
 }
 
+/// Aggregate numbers of a validator list, published by `subvt-validator-list-server` after
+/// each update for the clients that don't need the per-validator data. The stake of an active
+/// validator is its total active stake, the stake of an inactive validator is its self stake
+/// plus the active amounts of its nominations.
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct ValidatorListStats {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finalized_block_number: Option<u64>,
+    pub validator_count: u32,
+    pub active_next_session_count: u32,
+    pub oversubscribed_count: u32,
+    pub blocks_nominations_count: u32,
+    pub total_stake: Balance,
+    pub min_stake: Balance,
+    pub max_stake: Balance,
+    pub average_commission_per_billion: u32,
+}
+
+impl ValidatorListStats {
+    pub fn from_validators<'a>(
+        finalized_block_number: Option<u64>,
+        validators: impl Iterator<Item = &'a ValidatorSummary>,
+    ) -> ValidatorListStats {
+        let mut stats = ValidatorListStats {
+            finalized_block_number,
+            ..Default::default()
+        };
+        let mut total_commission_per_billion: u64 = 0;
+        for validator in validators {
+            let stake = if let Some(validator_stake) = &validator.validator_stake {
+                validator_stake.total_stake
+            } else {
+                // all nominations of a validator without active stakers are inactive
+                validator.self_stake.active_amount + validator.inactive_nominations.total_amount
+            };
+            stats.min_stake = if stats.validator_count == 0 {
+                stake
+            } else {
+                stats.min_stake.min(stake)
+            };
+            stats.max_stake = stats.max_stake.max(stake);
+            stats.total_stake += stake;
+            stats.validator_count += 1;
+            if validator.active_next_session {
+                stats.active_next_session_count += 1;
+            }
+            if validator.oversubscribed {
+                stats.oversubscribed_count += 1;
+            }
+            if validator.preferences.blocks_nominations {
+                stats.blocks_nominations_count += 1;
+            }
+            total_commission_per_billion += validator.preferences.commission_per_billion as u64;
+        }
+        if stats.validator_count > 0 {
+            stats.average_commission_per_billion =
+                (total_commission_per_billion / stats.validator_count as u64) as u32;
+        }
+        stats
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub struct ValidatorStakeSummary {
     pub self_stake: Balance,
@@ -211,3 +590,21 @@ impl From<&ValidatorStake> for ValidatorStakeSummary {
         }
     }
 }
+
+/// Fiat price of the network token, fetched by `subvt-price-updater`.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct TokenPrice {
+    /// Lower-case currency code, e.g. `usd`.
+    pub currency_code: String,
+    /// Price of a single token unit.
+    pub price: f64,
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl TokenPrice {
+    /// Fiat value of the given amount, which is in the smallest token denomination (e.g. planck).
+    pub fn get_fiat_value(&self, amount: Balance, token_decimals: u32) -> f64 {
+        amount as f64 / 10f64.powi(token_decimals as i32) * self.price
+    }
+}
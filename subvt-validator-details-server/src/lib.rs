@@ -4,20 +4,42 @@
 //! `subscribe_validator_details` accepts a single parameter: 0x-prefixed hex-encoded account id
 //! of the validator. Gives the complete details at first connection, then publishes only the
 //! changed fields after each update from `subvt-validator-list-updater`.
+//!
+//! When subscription authentication is enabled, `subscribe_validator_details` accepts a session
+//! token of the user as the second (optional) parameter, see `subscription` in
+//! `subvt-service-common`. Subscriptions with the `summary` plan get the validator summary and
+//! the summary changes instead of the complete details.
+//!
+//! Low-power clients can pass the minimum number of blocks between two pushes as the third
+//! (optional) parameter, `min_push_interval_blocks`. The updates of the blocks in between get
+//! skipped, and the next push carries the changes since the last push, so no change gets lost.
+//!
+//! `validate_session_keys` accepts the 0x-prefixed hex-encoded account id and session keys of
+//! a validator, and returns whether the keys are the validator's next session keys on chain
+//! and whether they are present in the keystore of the node the server is connected to. See
+//! `validate_session_keys` in `subvt-substrate-client`.
 use anyhow::Context;
 use async_trait::async_trait;
 use bus::Bus;
 use jsonrpsee::ws_server::{RpcModule, WsServerBuilder, WsServerHandle};
 use lazy_static::lazy_static;
 use log::{debug, error, warn};
-use redis::RedisResult;
 use serde::Serialize;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::sync::{Arc, Mutex, RwLock};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
 use subvt_config::Config;
-use subvt_service_common::Service;
-use subvt_types::subvt::{ValidatorDetails, ValidatorDetailsDiff};
+use subvt_persistence::realtime::{
+    get_realtime_store, get_skipped_block_counter_key, RealtimeStore,
+};
+use subvt_service_common::subscription::{SubscriptionAuthorizer, SubscriptionPlan};
+use subvt_service_common::{analytics, Service};
+use subvt_substrate_client::SubstrateClient;
+use subvt_types::crypto::AccountId;
+use subvt_types::subvt::{
+    ValidatorDetails, ValidatorDetailsDiff, ValidatorSummary, ValidatorSummaryDiff,
+};
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
@@ -36,6 +58,56 @@ struct ValidatorDetailsUpdate {
     validator_details: Option<ValidatorDetails>,
     #[serde(skip_serializing_if = "Option::is_none")]
     validator_details_update: Option<ValidatorDetailsDiff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validator_summary: Option<ValidatorSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    validator_summary_update: Option<ValidatorSummaryDiff>,
+}
+
+impl ValidatorDetailsUpdate {
+    /// Complete details or the summary of the validator, depending on the plan.
+    fn get_initial(plan: SubscriptionPlan, validator_details: &ValidatorDetails) -> Self {
+        match plan {
+            SubscriptionPlan::Full => ValidatorDetailsUpdate {
+                validator_details: Some(validator_details.clone()),
+                ..Default::default()
+            },
+            SubscriptionPlan::Summary => ValidatorDetailsUpdate {
+                validator_summary: Some(ValidatorSummary::from(validator_details)),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Changes between the last sent and the new details of the validator, depending on
+    /// the plan. The summary plan gets no diff when only the non-summary fields have changed.
+    fn get_change(
+        plan: SubscriptionPlan,
+        finalized_block_number: u64,
+        validator_details: &ValidatorDetails,
+        db_validator_details: &ValidatorDetails,
+    ) -> Self {
+        match plan {
+            SubscriptionPlan::Full => ValidatorDetailsUpdate {
+                finalized_block_number: Some(finalized_block_number),
+                validator_details_update: Some(validator_details.get_diff(db_validator_details)),
+                ..Default::default()
+            },
+            SubscriptionPlan::Summary => {
+                let validator_summary = ValidatorSummary::from(validator_details);
+                let db_validator_summary = ValidatorSummary::from(db_validator_details);
+                ValidatorDetailsUpdate {
+                    finalized_block_number: Some(finalized_block_number),
+                    validator_summary_update: if validator_summary != db_validator_summary {
+                        Some(validator_summary.get_diff(&db_validator_summary))
+                    } else {
+                        None
+                    },
+                    ..Default::default()
+                }
+            }
+        }
+    }
 }
 
 #[derive(Default)]
@@ -44,26 +116,22 @@ pub struct ValidatorDetailsServer;
 impl ValidatorDetailsServer {
     fn fetch_validator_details(
         account_id: &str,
-        redis_client: &redis::Client,
+        realtime_store: &dyn RealtimeStore,
     ) -> anyhow::Result<ValidatorDetails> {
-        let mut connection = redis_client.get_connection()?;
         let active_validator_key = format!(
             "subvt:{}:validators:active:validator:{}",
             CONFIG.substrate.chain, account_id,
         );
-        let active_validator_json_string_result: RedisResult<String> = redis::cmd("GET")
-            .arg(active_validator_key)
-            .query(&mut connection);
-        let validator_json_string = match active_validator_json_string_result {
-            Ok(validator_json_string) => validator_json_string,
-            Err(_) => {
+        let validator_json_string = match realtime_store.get(&active_validator_key)? {
+            Some(validator_json_string) => validator_json_string,
+            None => {
                 let inactive_validator_key = format!(
                     "subvt:{}:validators:inactive:validator:{}",
                     CONFIG.substrate.chain, account_id,
                 );
-                redis::cmd("GET")
-                    .arg(inactive_validator_key)
-                    .query(&mut connection)?
+                realtime_store
+                    .get(&inactive_validator_key)?
+                    .context("Validator not found.")?
             }
         };
         Ok(serde_json::from_str(&validator_json_string)?)
@@ -72,27 +140,73 @@ impl ValidatorDetailsServer {
     pub async fn run_rpc_server(
         host: &str,
         port: u16,
-        redis_client: &redis::Client,
+        realtime_store: &Arc<dyn RealtimeStore>,
         bus: Arc<Mutex<Bus<BusEvent>>>,
+        authorizer: Arc<SubscriptionAuthorizer>,
+        substrate_client: SubstrateClient,
     ) -> anyhow::Result<WsServerHandle> {
         let rpc_ws_server = WsServerBuilder::default()
             .max_request_body_size(u32::MAX)
             .build(format!("{}:{}", host, port))
             .await?;
-        let mut rpc_module = RpcModule::new(());
-        let redis_client = redis_client.clone();
-        let data_connection = Arc::new(RwLock::new(redis_client.get_connection()?));
+        let mut rpc_module = RpcModule::new(substrate_client);
+        rpc_module.register_async_method(
+            "validate_session_keys",
+            |params, substrate_client| async move {
+                let (account_id_hex_string, session_keys_hex_string): (String, String) =
+                    params.parse()?;
+                let account_id = AccountId::from_str(&account_id_hex_string).map_err(|_| {
+                    jsonrpsee_core::error::Error::Custom("Invalid account id.".to_string())
+                })?;
+                analytics::record("method:validate_session_keys", 1);
+                let validate_result = async {
+                    let block_hash = substrate_client.get_finalized_block_hash().await?;
+                    substrate_client
+                        .validate_session_keys(&account_id, &session_keys_hex_string, &block_hash)
+                        .await
+                };
+                validate_result.await.map_err(|error| {
+                    error!("Error while validating session keys: {:?}", error);
+                    jsonrpsee_core::error::Error::Custom(
+                        "Error while validating session keys. Please make sure you are sending valid session keys.".to_string(),
+                    )
+                })
+            },
+        )?;
+        let realtime_store = realtime_store.clone();
         rpc_module.register_subscription(
             "subscribe_validator_details",
             "subscribe_validator_details",
             "unsubscribe_validator_details",
             move |params, mut sink, _| {
-                let account_id: String = params.one()?;
+                let mut params = params.sequence();
+                let account_id: String = params.next()?;
+                let maybe_token: Option<String> = params.optional_next()?;
+                let min_push_interval_blocks: u64 = params.optional_next()?.unwrap_or(1);
+                if min_push_interval_blocks == 0
+                    || min_push_interval_blocks > CONFIG.rpc.max_push_interval_blocks
+                {
+                    let message = format!(
+                        "Minimum push interval should be between 1 and {} blocks.",
+                        CONFIG.rpc.max_push_interval_blocks
+                    );
+                    let _ = sink.send(&message);
+                    return Err(jsonrpsee_core::error::Error::Custom(message));
+                }
+                let permit = match authorizer.authorize_blocking(maybe_token.as_deref()) {
+                    Ok(permit) => permit,
+                    Err(message) => {
+                        let _ = sink.send(&message);
+                        return Err(jsonrpsee_core::error::Error::Custom(message));
+                    }
+                };
+                let plan = permit.plan;
                 debug!("New subscription {}.", account_id);
+                analytics::record("subscription:validator_details", 1);
                 let mut validator_details = {
                     let validator_details = match ValidatorDetailsServer::fetch_validator_details(
                         &account_id,
-                        &redis_client,
+                        realtime_store.as_ref(),
                     ) {
                         Ok(validator_details) => validator_details,
                         Err(error) => {
@@ -102,24 +216,35 @@ impl ValidatorDetailsServer {
                             return Err(jsonrpsee_core::error::Error::Custom(error_message));
                         }
                     };
-                    let _ = sink.send(&ValidatorDetailsUpdate {
-                        finalized_block_number: None,
-                        validator_details: Some(validator_details.clone()),
-                        validator_details_update: None
-                    });
+                    let _ = sink.send(&ValidatorDetailsUpdate::get_initial(plan, &validator_details));
                     validator_details
                 };
                 let mut bus_receiver = bus.lock().unwrap().add_rx();
-                let data_connection = data_connection.clone();
+                let realtime_store = realtime_store.clone();
                 let validator_storage_key_prefix =  format!(
                     "subvt:{}:validators:active:validator:{}",
                     CONFIG.substrate.chain, account_id,
                 );
                 std::thread::spawn(move || {
+                    // keep the permit for the lifetime of the subscription
+                    let _permit = permit;
+                    let mut maybe_last_push_block_number: Option<u64> = None;
                     loop {
                         if let Ok(update) = bus_receiver.recv() {
                             match update {
                                 BusEvent::NewFinalizedBlock(finalized_block_number) => {
+                                    // the diff is against the last pushed details, so it
+                                    // includes the changes of the skipped blocks
+                                    let is_push_due = match maybe_last_push_block_number {
+                                        Some(last_push_block_number) => {
+                                            finalized_block_number
+                                                >= last_push_block_number + min_push_interval_blocks
+                                        }
+                                        None => true,
+                                    };
+                                    if !is_push_due {
+                                        continue;
+                                    }
                                     let hash = {
                                         let mut hasher = DefaultHasher::new();
                                         validator_details.hash(&mut hasher);
@@ -129,15 +254,24 @@ impl ValidatorDetailsServer {
                                         "{}:hash",
                                         validator_storage_key_prefix,
                                     );
-                                    let mut data_connection = data_connection.write().unwrap();
-                                    let db_hash: u64 = redis::cmd("GET")
-                                        .arg(validator_hash_key)
-                                        .query(&mut *data_connection)
-                                        .unwrap();
+                                    let db_hash = match realtime_store
+                                        .get(&validator_hash_key)
+                                        .and_then(|maybe_hash| Ok(maybe_hash.unwrap_or_default().parse::<u64>()?))
+                                    {
+                                        Ok(db_hash) => db_hash,
+                                        Err(error) => {
+                                            error!(
+                                                "Error while fetching validator hash for storage key {}: {:?}",
+                                                validator_storage_key_prefix,
+                                                error
+                                            );
+                                            return;
+                                        }
+                                    };
                                     let update = if hash != db_hash {
-                                        let validator_json_string_result = redis::cmd("GET")
-                                            .arg(&validator_storage_key_prefix)
-                                            .query::<String>(&mut *data_connection);
+                                        let validator_json_string_result = realtime_store
+                                            .get(&validator_storage_key_prefix)
+                                            .and_then(|maybe_json_string| maybe_json_string.context("Validator not found."));
                                         let validator_json_string = match validator_json_string_result {
                                             Ok(validator_json_string) => validator_json_string,
                                             Err(error) => {
@@ -162,18 +296,18 @@ impl ValidatorDetailsServer {
                                                 return;
                                             }
                                         };
-                                        let update = ValidatorDetailsUpdate {
-                                            finalized_block_number: Some(finalized_block_number),
-                                            validator_details: None,
-                                            validator_details_update: Some(validator_details.get_diff(&db_validator_details)),
-                                        };
+                                        let update = ValidatorDetailsUpdate::get_change(
+                                            plan,
+                                            finalized_block_number,
+                                            &validator_details,
+                                            &db_validator_details,
+                                        );
                                         validator_details = db_validator_details;
                                         update
                                     } else {
                                         ValidatorDetailsUpdate {
                                             finalized_block_number: Some(finalized_block_number),
-                                            validator_details: None,
-                                            validator_details_update: None
+                                            ..Default::default()
                                         }
                                     };
                                     let send_result = sink.send(&update);
@@ -183,6 +317,7 @@ impl ValidatorDetailsServer {
                                     } else {
                                         debug!("Published update for {}.", account_id);
                                     }
+                                    maybe_last_push_block_number = Some(finalized_block_number);
                                 }
                                 BusEvent::Error => {
                                     return;
@@ -203,33 +338,41 @@ impl Service for ValidatorDetailsServer {
     async fn run(&'static self) -> anyhow::Result<()> {
         let mut last_finalized_block_number = 0;
         let bus = Arc::new(Mutex::new(Bus::new(100)));
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str()).context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
-        let mut pub_sub_connection = redis_client.get_connection()?;
-        let mut pub_sub = pub_sub_connection.as_pubsub();
-        pub_sub.subscribe(format!(
-            "subvt:{}:validators:publish:finalized_block_number",
-            CONFIG.substrate.chain
-        ))?;
+        let authorizer = Arc::new(SubscriptionAuthorizer::new(&CONFIG).await?);
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        let subscription = realtime_store
+            .subscribe(&format!(
+                "subvt:{}:validators:publish:finalized_block_number",
+                CONFIG.substrate.chain
+            ))?
+            .coalesce(CONFIG.realtime.coalesce_block_notifications);
         let server_stop_handle = ValidatorDetailsServer::run_rpc_server(
             &CONFIG.rpc.host,
             CONFIG.rpc.validator_details_port,
-            &redis_client,
+            &realtime_store,
             bus.clone(),
+            authorizer,
+            SubstrateClient::new(&CONFIG).await?,
         )
         .await?;
         let error: anyhow::Error = loop {
-            let message = pub_sub.get_message();
-            if let Err(error) = message {
-                break error.into();
-            }
-            let payload = message.unwrap().get_payload();
-            if let Err(error) = payload {
-                break error.into();
+            let finalized_block_number = match subscription.next_block_number() {
+                Ok(finalized_block_number) => finalized_block_number,
+                Err(error) => break error,
+            };
+            let skipped_block_count = subscription.take_skipped_block_count();
+            if skipped_block_count > 0 {
+                analytics::record("realtime:skipped_blocks", skipped_block_count);
+                if let Err(error) = realtime_store.increment(
+                    &get_skipped_block_counter_key(
+                        &CONFIG.substrate.chain,
+                        "validator_details_server",
+                    ),
+                    skipped_block_count,
+                ) {
+                    warn!("Cannot count the skipped blocks: {:?}", error);
+                }
             }
-            let finalized_block_number: u64 = payload.unwrap();
             if last_finalized_block_number == finalized_block_number {
                 warn!(
                     "Skip duplicate finalized block #{}.",
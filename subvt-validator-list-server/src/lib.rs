@@ -4,7 +4,24 @@
 //!
 //! Supports two RPC methods: `subscribe_validator_list` and `unsubscribe_validator_list`.
 //! Gives the complete list at first connection, then publishes only the changed validators' fields
-//! after each update from `subvt-validator-list-updater`.
+//! after each update from `subvt-validator-list-updater`. Each update has its finalized block
+//! number and the finalized block number of the preceding update, so that a client can detect
+//! the updates it has missed. A reconnecting client can pass the finalized block number of the
+//! last update it has applied as the subscription parameter, and receives only the updates it
+//! has missed instead of the complete list, if the block is still in the recent update history
+//! of the server. The history is kept in memory, so a restarted server sends the complete list.
+//!
+//! Also supports `subscribe_validator_list_stats` and `unsubscribe_validator_list_stats` for
+//! the clients that only need the aggregate numbers of the list (validator counts, stake and
+//! average commission), which get published after each update.
+//!
+//! Only the validator summaries are kept in memory. The details of a validator get read from the
+//! real-time store only when its summary hash changes, to compute the diff of its summary.
+//!
+//! When subscription authentication is enabled, both subscription methods accept a session
+//! token of the user as the last (optional) parameter, see `subscription` in
+//! `subvt-service-common`. The list consists of the validator summaries, so it's served the
+//! same way for all subscription plans.
 use anyhow::Context;
 use async_trait::async_trait;
 use bus::Bus;
@@ -12,15 +29,19 @@ use clap::{App, Arg};
 use jsonrpsee::ws_server::{RpcModule, WsServerBuilder, WsServerHandle};
 use lazy_static::lazy_static;
 use log::{debug, error, warn};
-use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
+use std::collections::{hash_map::DefaultHasher, HashMap, HashSet, VecDeque};
 use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, RwLock};
 use subvt_config::Config;
-use subvt_service_common::Service;
+use subvt_persistence::realtime::{get_realtime_store, get_skipped_block_counter_key};
+use subvt_service_common::subscription::SubscriptionAuthorizer;
+use subvt_service_common::{analytics, Service};
 use subvt_types::{
     crypto::AccountId,
-    subvt::{ValidatorDetails, ValidatorDetailsDiff, ValidatorListUpdate, ValidatorSummary},
+    subvt::{
+        NetworkMeta, ValidatorDetails, ValidatorListStats, ValidatorListUpdate, ValidatorSummary,
+    },
 };
 
 lazy_static! {
@@ -30,9 +51,48 @@ lazy_static! {
 #[derive(Clone, Debug)]
 pub enum BusEvent {
     Update(ValidatorListUpdate),
+    Stats(ValidatorListStats),
     Error,
 }
 
+/// Recently published updates, kept to serve the missed updates to reconnecting clients.
+#[derive(Default)]
+pub struct UpdateHistory {
+    maybe_finalized_block_number: Option<u64>,
+    is_provisional: bool,
+    /// Set by `subvt-validator-list-updater` on start, sent with the first message of each
+    /// subscription.
+    network_meta: Option<NetworkMeta>,
+    /// Oldest first.
+    updates: VecDeque<ValidatorListUpdate>,
+}
+
+impl UpdateHistory {
+    fn push(&mut self, finalized_block_number: u64, update: &mut ValidatorListUpdate) {
+        self.is_provisional = update.is_provisional;
+        update.previous_finalized_block_number = self
+            .maybe_finalized_block_number
+            .replace(finalized_block_number);
+        self.updates.push_back(update.clone());
+        while self.updates.len() > CONFIG.realtime.history_block_depth as usize {
+            self.updates.pop_front();
+        }
+    }
+
+    /// Gets the updates published after the update for the given block, `None` if the block
+    /// is not in the history.
+    fn get_updates_after(&self, block_number: u64) -> Option<Vec<ValidatorListUpdate>> {
+        if self.maybe_finalized_block_number == Some(block_number) {
+            return Some(Vec::new());
+        }
+        let start_index = self
+            .updates
+            .iter()
+            .position(|update| update.previous_finalized_block_number == Some(block_number))?;
+        Some(self.updates.iter().skip(start_index).cloned().collect())
+    }
+}
+
 #[derive(Default)]
 pub struct ValidatorListServer;
 
@@ -40,48 +100,147 @@ impl ValidatorListServer {
     pub async fn run_rpc_server(
         host: &str,
         port: u16,
-        validator_map: &Arc<RwLock<HashMap<AccountId, ValidatorDetails>>>,
+        validator_map: &Arc<RwLock<HashMap<AccountId, ValidatorSummary>>>,
+        history: &Arc<RwLock<UpdateHistory>>,
         bus: &Arc<Mutex<Bus<BusEvent>>>,
+        authorizer: &Arc<SubscriptionAuthorizer>,
     ) -> anyhow::Result<WsServerHandle> {
         let rpc_ws_server = WsServerBuilder::default()
             .max_request_body_size(u32::MAX)
             .build(format!("{}:{}", host, port))
             .await?;
         let mut rpc_module = RpcModule::new(());
+        let validator_map_for_stats = validator_map.clone();
+        let history_for_stats = history.clone();
+        let bus_for_stats = bus.clone();
+        let authorizer_for_stats = authorizer.clone();
         let validator_map = validator_map.clone();
+        let history = history.clone();
         let bus = bus.clone();
+        let authorizer = authorizer.clone();
         rpc_module.register_subscription(
             "subscribe_validator_list",
             "subscribe_validator_list",
             "unsubscribe_validator_list",
-            move |_params, mut sink, _| {
-                debug!("New subscription.");
-                let mut bus_receiver = bus.lock().unwrap().add_rx();
-                {
-                    let validator_summaries: Vec<ValidatorSummary> = {
-                        let validator_map = validator_map.read().unwrap();
-                        validator_map.iter().map(|value| value.1.into()).collect()
-                    };
-                    let update = ValidatorListUpdate {
-                        insert: validator_summaries,
+            move |params, mut sink, _| {
+                let mut params = params.sequence();
+                let maybe_last_applied_block_number: Option<u64> = params.optional_next()?;
+                let maybe_token: Option<String> = params.optional_next()?;
+                let permit = match authorizer.authorize_blocking(maybe_token.as_deref()) {
+                    Ok(permit) => permit,
+                    Err(message) => {
+                        let _ = sink.send(&message);
+                        return Err(jsonrpsee_core::error::Error::Custom(message));
+                    }
+                };
+                debug!(
+                    "New subscription. Last applied block: {:?}.",
+                    maybe_last_applied_block_number
+                );
+                analytics::record("subscription:validator_list", 1);
+                // the bus is locked while reading the history, so that no update gets
+                // published between the history and the bus receiver
+                let (mut bus_receiver, initial_updates) = {
+                    let mut bus = bus.lock().unwrap();
+                    let history = history.read().unwrap();
+                    let maybe_missed_updates = maybe_last_applied_block_number
+                        .and_then(|block_number| history.get_updates_after(block_number));
+                    let latest_update = ValidatorListUpdate {
+                        finalized_block_number: history.maybe_finalized_block_number,
+                        is_provisional: history.is_provisional,
                         ..Default::default()
                     };
-                    let _ = sink.send(&update);
+                    let mut initial_updates = match maybe_missed_updates {
+                        Some(missed_updates) if !missed_updates.is_empty() => {
+                            debug!("Send {} missed update(s).", missed_updates.len());
+                            missed_updates
+                        }
+                        // nothing missed, the network metadata gets sent in an empty update
+                        Some(_) => vec![latest_update],
+                        None => {
+                            let validator_map = validator_map.read().unwrap();
+                            vec![ValidatorListUpdate {
+                                insert: validator_map.values().cloned().collect(),
+                                ..latest_update
+                            }]
+                        }
+                    };
+                    initial_updates[0].network_meta = history.network_meta.clone();
+                    (bus.add_rx(), initial_updates)
+                };
+                for update in &initial_updates {
+                    let _ = sink.send(update);
                 }
-                std::thread::spawn(move || loop {
-                    if let Ok(update) = bus_receiver.recv() {
-                        match update {
-                            BusEvent::Update(update) => {
-                                let send_result = sink.send(&update);
-                                if let Err(error) = send_result {
-                                    debug!("Subscription closed. {:?}", error);
+                std::thread::spawn(move || {
+                    // keep the permit for the lifetime of the subscription
+                    let _permit = permit;
+                    loop {
+                        if let Ok(update) = bus_receiver.recv() {
+                            match update {
+                                BusEvent::Update(update) => {
+                                    let send_result = sink.send(&update);
+                                    if let Err(error) = send_result {
+                                        debug!("Subscription closed. {:?}", error);
+                                        return;
+                                    } else {
+                                        debug!("Published diff.");
+                                    }
+                                }
+                                BusEvent::Stats(_) => (),
+                                BusEvent::Error => {
                                     return;
-                                } else {
-                                    debug!("Published diff.");
                                 }
                             }
-                            BusEvent::Error => {
-                                return;
+                        }
+                    }
+                });
+                Ok(())
+            },
+        )?;
+        rpc_module.register_subscription(
+            "subscribe_validator_list_stats",
+            "subscribe_validator_list_stats",
+            "unsubscribe_validator_list_stats",
+            move |params, mut sink, _| {
+                let maybe_token: Option<String> = params.sequence().optional_next()?;
+                let permit = match authorizer_for_stats.authorize_blocking(maybe_token.as_deref()) {
+                    Ok(permit) => permit,
+                    Err(message) => {
+                        let _ = sink.send(&message);
+                        return Err(jsonrpsee_core::error::Error::Custom(message));
+                    }
+                };
+                debug!("New stats subscription.");
+                analytics::record("subscription:validator_list_stats", 1);
+                let (mut bus_receiver, stats) = {
+                    let mut bus = bus_for_stats.lock().unwrap();
+                    let history = history_for_stats.read().unwrap();
+                    let validator_map = validator_map_for_stats.read().unwrap();
+                    let stats = ValidatorListStats::from_validators(
+                        history.maybe_finalized_block_number,
+                        validator_map.values(),
+                    );
+                    (bus.add_rx(), stats)
+                };
+                let _ = sink.send(&stats);
+                std::thread::spawn(move || {
+                    // keep the permit for the lifetime of the subscription
+                    let _permit = permit;
+                    loop {
+                        if let Ok(update) = bus_receiver.recv() {
+                            match update {
+                                BusEvent::Stats(stats) => {
+                                    if let Err(error) = sink.send(&stats) {
+                                        debug!("Stats subscription closed. {:?}", error);
+                                        return;
+                                    } else {
+                                        debug!("Published stats.");
+                                    }
+                                }
+                                BusEvent::Update(_) => (),
+                                BusEvent::Error => {
+                                    return;
+                                }
                             }
                         }
                     }
@@ -106,20 +265,19 @@ impl Service for ValidatorListServer {
             .get_matches();
         let is_active_list = !matches.is_present("inactive");
         let mut last_finalized_block_number = 0;
+        let mut last_is_provisional = false;
         let bus = Arc::new(Mutex::new(Bus::new(100)));
-        let validator_map = Arc::new(RwLock::new(HashMap::<AccountId, ValidatorDetails>::new()));
+        let validator_map = Arc::new(RwLock::new(HashMap::<AccountId, ValidatorSummary>::new()));
+        let history = Arc::new(RwLock::new(UpdateHistory::default()));
+        let authorizer = Arc::new(SubscriptionAuthorizer::new(&CONFIG).await?);
 
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str()).context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
-        let mut pub_sub_connection = redis_client.get_connection()?;
-        let mut pub_sub = pub_sub_connection.as_pubsub();
-        pub_sub.subscribe(format!(
-            "subvt:{}:validators:publish:finalized_block_number",
-            CONFIG.substrate.chain
-        ))?;
-        let mut data_connection = redis_client.get_connection()?;
+        let realtime_store = get_realtime_store(&CONFIG)?;
+        let subscription = realtime_store
+            .subscribe(&format!(
+                "subvt:{}:validators:publish:finalized_block_number",
+                CONFIG.substrate.chain
+            ))?
+            .coalesce(CONFIG.realtime.coalesce_block_notifications);
         let server_stop_handle = ValidatorListServer::run_rpc_server(
             &CONFIG.rpc.host,
             if is_active_list {
@@ -128,21 +286,51 @@ impl Service for ValidatorListServer {
                 CONFIG.rpc.inactive_validator_list_port
             },
             &validator_map,
+            &history,
             &bus,
+            &authorizer,
         )
         .await?;
 
         let error: anyhow::Error = 'outer: loop {
-            let message = pub_sub.get_message();
-            if let Err(error) = message {
-                break error.into();
+            let finalized_block_number = match subscription.next_block_number() {
+                Ok(finalized_block_number) => finalized_block_number,
+                Err(error) => break error,
+            };
+            let skipped_block_count = subscription.take_skipped_block_count();
+            if skipped_block_count > 0 {
+                analytics::record("realtime:skipped_blocks", skipped_block_count);
+                if let Err(error) = realtime_store.increment(
+                    &get_skipped_block_counter_key(
+                        &CONFIG.substrate.chain,
+                        if is_active_list {
+                            "active_validator_list_server"
+                        } else {
+                            "inactive_validator_list_server"
+                        },
+                    ),
+                    skipped_block_count,
+                ) {
+                    warn!("Cannot count the skipped blocks: {:?}", error);
+                }
             }
-            let payload = message.unwrap().get_payload();
-            if let Err(error) = payload {
-                break error.into();
+            if history.read().unwrap().network_meta.is_none() {
+                if let Some(network_meta_json) =
+                    realtime_store.get(&format!("subvt:{}:network_meta", CONFIG.substrate.chain))?
+                {
+                    history.write().unwrap().network_meta =
+                        Some(serde_json::from_str(&network_meta_json)?);
+                }
             }
-            let finalized_block_number: u64 = payload.unwrap();
-            if last_finalized_block_number == finalized_block_number {
+            // the provisional list of a block gets replaced by the list of the same block
+            let is_provisional = realtime_store
+                .get(&format!(
+                    "subvt:{}:validators:{}:is_provisional",
+                    CONFIG.substrate.chain, finalized_block_number
+                ))?
+                .map(|is_provisional| is_provisional == "true")
+                .unwrap_or(false);
+            if last_finalized_block_number == finalized_block_number && !last_is_provisional {
                 warn!(
                     "Skip duplicate finalized block #{}.",
                     finalized_block_number
@@ -156,16 +344,16 @@ impl Service for ValidatorListServer {
                 finalized_block_number,
                 if is_active_list { "active" } else { "inactive" }
             );
-            let validator_account_ids: HashSet<String> = redis::cmd("SMEMBERS")
-                .arg(format!("{}:account_id_set", prefix))
-                .query(&mut data_connection)
-                .context("Can't read validator account ids from Redis.")?;
+            let validator_account_ids: HashSet<String> = realtime_store
+                .get_set_members(&format!("{}:account_id_set", prefix))
+                .context("Can't read validator account ids from the real-time store.")?;
             debug!(
                 "Got {} validator account ids. Checking for changes...",
                 validator_account_ids.len()
             );
             let mut update = ValidatorListUpdate {
                 finalized_block_number: Some(finalized_block_number),
+                is_provisional,
                 ..Default::default()
             };
             {
@@ -184,72 +372,99 @@ impl Service for ValidatorListServer {
                     validator_map.remove(remove_id);
                 }
             }
-            let mut new_validators: Vec<ValidatorDetails> = Vec::new();
-            let mut validator_updates: Vec<ValidatorDetailsDiff> = Vec::new();
+            let mut changed_validators: Vec<ValidatorSummary> = Vec::new();
             {
                 // update/insert
                 let validator_map = validator_map.read().unwrap();
-                for validator_account_id in validator_account_ids {
-                    let validator_account_id = AccountId::from_str(&validator_account_id).unwrap();
-                    let prefix = format!("{}:validator:{}", prefix, validator_account_id);
-                    if let Some(validator) = validator_map.get(&validator_account_id) {
-                        // check hash, if different, fetch, calculate and add to list
-                        let summary_hash = {
-                            let mut hasher = DefaultHasher::new();
-                            ValidatorSummary::from(validator).hash(&mut hasher);
-                            hasher.finish()
-                        };
-                        let db_summary_hash: u64 = redis::cmd("GET")
-                            .arg(format!("{}:summary_hash", prefix))
-                            .query(&mut data_connection)
-                            .context("Can't read validator summary hash from Redis.")?;
-                        if summary_hash != db_summary_hash {
-                            debug!("Summary hash changed for {}.", validator_account_id);
-                            let validator_json_string: String = redis::cmd("GET")
-                                .arg(prefix)
-                                .query(&mut data_connection)
-                                .context("Can't read validator JSON string (1) from Redis.")?;
-                            let db_validator: ValidatorDetails =
-                                serde_json::from_str(&validator_json_string)?;
-                            let db_validator_summary: ValidatorSummary =
-                                ValidatorSummary::from(&db_validator);
-                            let validator_summary: ValidatorSummary = validator.into();
-                            update
-                                .update
-                                .push(validator_summary.get_diff(&db_validator_summary));
-                            validator_updates.push(validator.get_diff(&db_validator));
+                let validator_account_ids: Vec<AccountId> = validator_account_ids
+                    .iter()
+                    .map(|validator_account_id| AccountId::from_str(validator_account_id).unwrap())
+                    .collect();
+                let (existing_account_ids, new_account_ids): (Vec<AccountId>, Vec<AccountId>) =
+                    validator_account_ids
+                        .into_iter()
+                        .partition(|validator_account_id| {
+                            validator_map.contains_key(validator_account_id)
+                        });
+                let get_validator_prefix = |validator_account_id: &AccountId| {
+                    format!("{}:validator:{}", prefix, validator_account_id)
+                };
+                // read the summary hashes of the existing validators in a single batch,
+                // and find out the changed ones
+                let summary_hash_keys: Vec<String> = existing_account_ids
+                    .iter()
+                    .map(|validator_account_id| {
+                        format!(
+                            "{}:summary_hash",
+                            get_validator_prefix(validator_account_id)
+                        )
+                    })
+                    .collect();
+                let db_summary_hashes = realtime_store.get_multiple(&summary_hash_keys)?;
+                let mut changed_account_ids: Vec<AccountId> = Vec::new();
+                for (validator_account_id, maybe_db_summary_hash) in
+                    existing_account_ids.into_iter().zip(db_summary_hashes)
+                {
+                    let validator_summary = validator_map.get(&validator_account_id).unwrap();
+                    let summary_hash = {
+                        let mut hasher = DefaultHasher::new();
+                        validator_summary.hash(&mut hasher);
+                        hasher.finish()
+                    };
+                    let db_summary_hash: u64 = maybe_db_summary_hash
+                        .context("Can't read validator summary hash from the real-time store.")?
+                        .parse()?;
+                    if summary_hash != db_summary_hash {
+                        debug!("Summary hash changed for {}.", validator_account_id);
+                        changed_account_ids.push(validator_account_id);
+                    }
+                }
+                // read the details of the changed and new validators in a single batch
+                let validator_keys: Vec<String> = changed_account_ids
+                    .iter()
+                    .chain(new_account_ids.iter())
+                    .map(get_validator_prefix)
+                    .collect();
+                let mut validator_json_strings =
+                    realtime_store.get_multiple(&validator_keys)?.into_iter();
+                for validator_account_id in &changed_account_ids {
+                    let validator_summary = validator_map.get(validator_account_id).unwrap();
+                    let validator_json_string = validator_json_strings.next().flatten().context(
+                        "Can't read validator JSON string (1) from the real-time store.",
+                    )?;
+                    let db_validator: ValidatorDetails =
+                        serde_json::from_str(&validator_json_string)?;
+                    let db_validator_summary = ValidatorSummary::from(&db_validator);
+                    update
+                        .update
+                        .push(validator_summary.get_diff(&db_validator_summary));
+                    changed_validators.push(db_validator_summary);
+                }
+                for validator_account_id in &new_account_ids {
+                    let validator_json_string =
+                        validator_json_strings.next().flatten().context(format!(
+                            "Can't read validator JSON string (2) :: {}",
+                            get_validator_prefix(validator_account_id)
+                        ))?;
+                    let validator_deser_result: serde_json::error::Result<ValidatorDetails> =
+                        serde_json::from_str(&validator_json_string);
+                    match validator_deser_result {
+                        Ok(validator) => {
+                            update.insert.push(ValidatorSummary::from(&validator));
                         }
-                    } else {
-                        let validator_json_string: String = redis::cmd("GET")
-                            .arg(&prefix)
-                            .query(&mut data_connection)
-                            .context(format!(
-                                "Can't read validator JSON string (2) from Redis :: {}",
-                                &prefix
-                            ))?;
-                        let validator_deser_result: serde_json::error::Result<ValidatorDetails> =
-                            serde_json::from_str(&validator_json_string);
-                        match validator_deser_result {
-                            Ok(validator) => {
-                                let validator_summary = ValidatorSummary::from(&validator);
-                                update.insert.push(validator_summary);
-                                new_validators.push(validator);
-                            }
-                            Err(error) => {
-                                break 'outer error.into();
-                            }
+                        Err(error) => {
+                            break 'outer error.into();
                         }
                     }
                 }
             }
             {
                 let mut validator_map = validator_map.write().unwrap();
-                for diff in validator_updates {
-                    let validator = validator_map.get_mut(&diff.account.id).unwrap();
-                    validator.apply_diff(&diff);
-                }
-                for validator in new_validators {
-                    validator_map.insert(validator.account.id.clone(), validator);
+                for validator_summary in changed_validators
+                    .into_iter()
+                    .chain(update.insert.iter().cloned())
+                {
+                    validator_map.insert(validator_summary.account_id.clone(), validator_summary);
                 }
             }
             debug!(
@@ -259,11 +474,24 @@ impl Service for ValidatorListServer {
                 update.update.len(),
             );
             {
+                let stats = ValidatorListStats::from_validators(
+                    Some(finalized_block_number),
+                    validator_map.read().unwrap().values(),
+                );
                 let mut bus = bus.lock().unwrap();
+                history
+                    .write()
+                    .unwrap()
+                    .push(finalized_block_number, &mut update);
                 bus.broadcast(BusEvent::Update(update));
-                debug!("Update published to the bus.");
+                bus.broadcast(BusEvent::Stats(stats));
+                debug!(
+                    "Update of block #{} and stats published to the bus.",
+                    finalized_block_number
+                );
             }
             last_finalized_block_number = finalized_block_number;
+            last_is_provisional = is_provisional;
         };
         error!("{:?}", error);
         {
@@ -276,3 +504,91 @@ impl Service for ValidatorListServer {
         Err(error)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{UpdateHistory, CONFIG};
+    use subvt_types::subvt::ValidatorListUpdate;
+
+    fn push_updates(history: &mut UpdateHistory, block_numbers: std::ops::RangeInclusive<u64>) {
+        for block_number in block_numbers {
+            let mut update = ValidatorListUpdate {
+                finalized_block_number: Some(block_number),
+                ..Default::default()
+            };
+            history.push(block_number, &mut update);
+        }
+    }
+
+    fn get_block_numbers(updates: &[ValidatorListUpdate]) -> Vec<u64> {
+        updates
+            .iter()
+            .map(|update| update.finalized_block_number.unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_replay_from_latest_block() {
+        let mut history = UpdateHistory::default();
+        push_updates(&mut history, 10..=12);
+        let updates = history.get_updates_after(12).unwrap();
+        assert!(updates.is_empty());
+    }
+
+    #[test]
+    fn test_replay_from_mid_history_block() {
+        let mut history = UpdateHistory::default();
+        push_updates(&mut history, 10..=12);
+        let updates = history.get_updates_after(10).unwrap();
+        assert_eq!(get_block_numbers(&updates), vec![11, 12]);
+        assert_eq!(
+            updates
+                .iter()
+                .map(|update| update.previous_finalized_block_number.unwrap())
+                .collect::<Vec<u64>>(),
+            vec![10, 11]
+        );
+        let updates = history.get_updates_after(11).unwrap();
+        assert_eq!(get_block_numbers(&updates), vec![12]);
+    }
+
+    #[test]
+    fn test_replay_from_evicted_block() {
+        let depth = CONFIG.realtime.history_block_depth;
+        let mut history = UpdateHistory::default();
+        push_updates(&mut history, 100..=(100 + depth + 1));
+        // update of block #101 is evicted, so the updates after block #100 are not available
+        assert!(history.get_updates_after(100).is_none());
+        // never seen
+        assert!(history.get_updates_after(99).is_none());
+        assert!(history.get_updates_after(100 + depth + 2).is_none());
+        // oldest update in the history is the one after block #101
+        let updates = history.get_updates_after(101).unwrap();
+        assert_eq!(
+            get_block_numbers(&updates),
+            (102..=(100 + depth + 1)).collect::<Vec<u64>>()
+        );
+    }
+
+    #[test]
+    fn test_history_block_depth() {
+        let depth = CONFIG.realtime.history_block_depth;
+        let mut history = UpdateHistory::default();
+        push_updates(&mut history, 1..=depth);
+        assert_eq!(history.updates.len() as u64, depth);
+        push_updates(&mut history, (depth + 1)..=(depth * 3));
+        assert_eq!(history.updates.len() as u64, depth);
+        assert_eq!(
+            history
+                .updates
+                .back()
+                .unwrap()
+                .previous_finalized_block_number,
+            Some(depth * 3 - 1)
+        );
+        assert_eq!(
+            history.get_updates_after(depth * 2).unwrap().len() as u64,
+            depth
+        );
+    }
+}
@@ -1,55 +1,127 @@
-//! Updates the Redis database with the complete validator list after every block.
-//! Subscribes to the new blocks using the Substrate client in `subvt-substrate-client`.
+//! Updates the real-time store (Redis by default) with the complete validator list after every block.
+//! Subscribes to the new blocks using the Substrate client in `subvt-substrate-client`. The field
+//! groups that are expensive to compute get refreshed with the cadence configured in the
+//! `validator_list_updater_cadence` configuration section. On start, a provisional list gets
+//! published from the latest indexed era until the list of the first finalized block is ready.
+//! Starts over with a new client after a runtime upgrade.
 use anyhow::Context;
 use async_lock::RwLock;
 use async_trait::async_trait;
+use futures::StreamExt;
 use lazy_static::lazy_static;
-use log::{debug, error, trace};
-use redis::Pipeline;
-use std::collections::{hash_map::DefaultHasher, HashSet};
+use log::{debug, error, info, trace, warn};
+use std::collections::{hash_map::DefaultHasher, HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use subvt_config::Config;
+use std::time::Instant;
+use subvt_config::{Config, FieldGroupCadenceConfig};
 use subvt_persistence::postgres::network::PostgreSQLNetworkStorage;
-use subvt_service_common::Service;
+use subvt_persistence::realtime::{
+    get_realtime_store, IncompleteWriteError, RealtimeStore, RealtimeWriteBatch,
+};
+use subvt_service_common::{analytics, Service};
 use subvt_substrate_client::SubstrateClient;
-use subvt_types::substrate::{BlockHeader, Era};
-use subvt_types::subvt::{ValidatorDetails, ValidatorSummary};
+use subvt_types::crypto::AccountId;
+use subvt_types::rdb::ValidatorInfo;
+use subvt_types::substrate::{Balance, BlockHeader, Era};
+use subvt_types::subvt::{
+    AnnouncedCommissionChange, MissedPrimarySlots, NetworkMeta, SessionRewardPoints,
+    ValidatorActivity, ValidatorDetails, ValidatorLocation, ValidatorPreferenceChange,
+    ValidatorRiskFlags, ValidatorSummary,
+};
 
 lazy_static! {
     static ref CONFIG: Config = Config::default();
 }
 
-const HISTORY_BLOCK_DEPTH: u64 = 3;
+/// Keeps the preference change history of each validator, so that it gets fetched
+/// from the database only when a new `validate` extrinsic gets indexed for the validator.
+#[derive(Default)]
+struct PreferenceChangeCache {
+    last_validate_extrinsic_id: u32,
+    preference_changes: HashMap<AccountId, Vec<ValidatorPreferenceChange>>,
+}
+
+/// Last refresh of a field group, checked against the group's cadence configuration.
+#[derive(Default)]
+struct FieldGroupRefresh {
+    maybe_last_refresh: Option<(u64, Instant)>,
+}
+
+impl FieldGroupRefresh {
+    fn is_due(&self, cadence: &FieldGroupCadenceConfig, block_number: u64) -> bool {
+        match self.maybe_last_refresh {
+            Some((last_block_number, last_refresh_instant)) => {
+                (cadence.block_count > 0
+                    && block_number.saturating_sub(last_block_number) >= cadence.block_count as u64)
+                    || (cadence.seconds > 0
+                        && last_refresh_instant.elapsed().as_secs() >= cadence.seconds)
+            }
+            None => true,
+        }
+    }
+
+    fn set_refreshed(&mut self, block_number: u64) {
+        self.maybe_last_refresh = Some((block_number, Instant::now()));
+    }
+}
+
+/// Reward points of the validators in the last completed session, computed once per session.
+/// Carried over when the `FieldGroupCache` gets reset at the start of an era, so that the
+/// zero-point session counts continue across the eras.
+#[derive(Default)]
+struct SessionRewardPointsCache {
+    session_index: Option<u64>,
+    reward_points: HashMap<AccountId, SessionRewardPoints>,
+}
+
+/// Keeps the last values of the field groups that are refreshed with their own cadence
+/// (see `ValidatorListUpdaterCadenceConfig`), so that they can be carried over to the
+/// validator list of the blocks in between. Gets reset at the start of every era,
+/// except for the session reward points.
+#[derive(Default)]
+struct FieldGroupCache {
+    era_index: Option<u32>,
+    db_info_refresh: FieldGroupRefresh,
+    validator_infos: HashMap<AccountId, ValidatorInfo>,
+    validator_locations: HashMap<AccountId, ValidatorLocation>,
+    average_payout_lags: HashMap<AccountId, u32>,
+    announced_commission_changes_refresh: FieldGroupRefresh,
+    announced_commission_changes: HashMap<AccountId, AnnouncedCommissionChange>,
+    risk_flags_refresh: FieldGroupRefresh,
+    recently_slashed_account_ids: HashSet<AccountId>,
+    checked_controller_account_ids: HashSet<AccountId>,
+    killed_controller_account_ids: HashSet<AccountId>,
+    missed_primary_slots_refresh: FieldGroupRefresh,
+    missed_primary_slots: HashMap<AccountId, MissedPrimarySlots>,
+    recent_activity_refresh: FieldGroupRefresh,
+    recent_activities: HashMap<AccountId, Vec<ValidatorActivity>>,
+    session_reward_points: SessionRewardPointsCache,
+}
 
 #[derive(Default)]
 pub struct ValidatorListUpdater;
 
 impl ValidatorListUpdater {
-    async fn update_redis(
+    async fn update_realtime_store(
+        realtime_store: &dyn RealtimeStore,
         active_era: &Era,
         processed_block_numbers: &Arc<RwLock<Vec<u64>>>,
         finalized_block_number: u64,
         validators: &[ValidatorDetails],
+        is_provisional: bool,
     ) -> anyhow::Result<()> {
-        // get redis connection
-        let redis_client = redis::Client::open(CONFIG.redis.url.as_str())?;
-        let mut redis_connection = redis_client.get_connection().context(format!(
-            "Cannot connect to Redis at URL {}.",
-            CONFIG.redis.url
-        ))?;
         let prefix = format!(
             "subvt:{}:validators:{}",
             CONFIG.substrate.chain, finalized_block_number
         );
-        // prepare first command pipeline
-        let mut redis_cmd_pipeline = Pipeline::new();
+        let mut batch = RealtimeWriteBatch::default();
         // delete history
         {
-            debug!("Clean Redis history.");
+            debug!("Clean real-time store history.");
             let mut processed_block_numbers = processed_block_numbers.write().await;
             let to_delete: Vec<u64> = processed_block_numbers
                 .iter()
@@ -57,20 +129,15 @@ impl ValidatorListUpdater {
                 .take(
                     processed_block_numbers
                         .len()
-                        .saturating_sub(HISTORY_BLOCK_DEPTH as usize),
+                        .saturating_sub(CONFIG.realtime.history_block_depth as usize),
                 )
                 .collect();
             for delete in to_delete {
-                let keys: Vec<String> = redis::cmd("KEYS")
-                    .arg(format!(
-                        "subvt:{}:validators:{}:*",
-                        CONFIG.substrate.chain, delete
-                    ))
-                    .query(&mut redis_connection)?;
-                debug!("Delete {} records for block #{}.", keys.len(), delete);
-                for key in keys {
-                    redis_cmd_pipeline.cmd("DEL").arg(key);
-                }
+                debug!("Delete records for block #{}.", delete);
+                batch.delete_with_prefix(&format!(
+                    "subvt:{}:validators:{}:",
+                    CONFIG.substrate.chain, delete
+                ));
                 processed_block_numbers.remove(0);
             }
         }
@@ -94,20 +161,20 @@ impl ValidatorListUpdater {
                 }
             })
             .collect();
-        redis_cmd_pipeline
-            .cmd("SADD")
-            .arg(format!("{}:active:{}", prefix, "account_id_set"))
-            .arg(active_account_ids);
-        redis_cmd_pipeline
-            .cmd("SADD")
-            .arg(format!("{}:inactive:{}", prefix, "account_id_set"))
-            .arg(inactive_account_ids);
-        // each validator
-        redis_cmd_pipeline.cmd("MSET");
+        batch.add_to_set(
+            &format!("{}:active:{}", prefix, "account_id_set"),
+            active_account_ids,
+        );
+        batch.add_to_set(
+            &format!("{}:inactive:{}", prefix, "account_id_set"),
+            inactive_account_ids,
+        );
         // set era
-        redis_cmd_pipeline
-            .arg(format!("{}:active_era", prefix))
-            .arg(serde_json::to_string(active_era)?);
+        batch.set(
+            &format!("{}:active_era", prefix),
+            serde_json::to_string(active_era)?,
+        );
+        batch.set(&format!("{}:is_provisional", prefix), is_provisional);
         // set validator details
         for validator in validators {
             let validator_prefix = format!(
@@ -133,34 +200,615 @@ impl ValidatorListUpdater {
                 hasher.finish()
             };
             let validator_json_string = serde_json::to_string(validator)?;
-            redis_cmd_pipeline
-                .arg(format!("{}:hash", validator_prefix))
-                .arg(hash)
-                .arg(format!("{}:summary_hash", validator_prefix))
-                .arg(summary_hash)
-                .arg(validator_prefix)
-                .arg(validator_json_string);
+            batch
+                .set(&format!("{}:hash", validator_prefix), hash)
+                .set(&format!("{}:summary_hash", validator_prefix), summary_hash)
+                .set(&validator_prefix, validator_json_string);
         }
+        // keep track of the latest block, for the tools that inspect the real-time store
+        batch.set(
+            &format!(
+                "subvt:{}:validators:finalized_block_number",
+                CONFIG.substrate.chain
+            ),
+            finalized_block_number,
+        );
         // publish event
-        redis_cmd_pipeline
-            .cmd("PUBLISH")
-            .arg(format!(
+        batch.publish_block_number(
+            &format!(
                 "subvt:{}:validators:publish:finalized_block_number",
                 CONFIG.substrate.chain
-            ))
-            .arg(finalized_block_number);
-        redis_cmd_pipeline
-            .query(&mut redis_connection)
-            .context("Error while setting Redis validators.")?;
+            ),
+            finalized_block_number,
+        );
+        if let Err(error) = realtime_store.write(&batch) {
+            // not published, but the written keys should get deleted with the history
+            if error.downcast_ref::<IncompleteWriteError>().is_some() {
+                analytics::record("realtime:incomplete_write", 1);
+                processed_block_numbers
+                    .write()
+                    .await
+                    .push(finalized_block_number);
+            }
+            return Err(error.context("Error while setting real-time store validators."));
+        }
         let mut processed_block_numbers = processed_block_numbers.write().await;
         processed_block_numbers.push(finalized_block_number);
         Ok(())
     }
 
+    async fn update_preference_changes(
+        postgres: &PostgreSQLNetworkStorage,
+        preference_change_cache: &Arc<RwLock<PreferenceChangeCache>>,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        let mut cache = preference_change_cache.write().await;
+        let mut last_validate_extrinsic_id = cache.last_validate_extrinsic_id;
+        let changed_account_ids: HashSet<AccountId> = {
+            let mut changed_account_ids = HashSet::new();
+            for (id, account_id) in postgres
+                .get_validate_extrinsic_stash_account_ids_after_id(last_validate_extrinsic_id)
+                .await?
+            {
+                last_validate_extrinsic_id = last_validate_extrinsic_id.max(id);
+                changed_account_ids.insert(account_id);
+            }
+            changed_account_ids
+        };
+        for validator in validators.iter_mut() {
+            if changed_account_ids.contains(&validator.account.id)
+                || !cache.preference_changes.contains_key(&validator.account.id)
+            {
+                let preference_changes = postgres
+                    .get_validator_preference_changes(
+                        &validator.account.id,
+                        CONFIG.validator_list_updater.preference_change_history_size,
+                    )
+                    .await?;
+                cache
+                    .preference_changes
+                    .insert(validator.account.id.clone(), preference_changes);
+            }
+            if let Some(preference_changes) = cache.preference_changes.get(&validator.account.id) {
+                validator.preference_changes = preference_changes.clone();
+            }
+        }
+        // advance only after all the changed validators have been refreshed
+        cache.last_validate_extrinsic_id = last_validate_extrinsic_id;
+        Ok(())
+    }
+
+    /// Sets the fields from the relational database. Validators that are not in the cache
+    /// (e.g. new validators) get fetched even if the field group is not due for a refresh.
+    async fn update_db_info(
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut FieldGroupCache,
+        finalized_block_number: u64,
+        finalized_block_hash: &str,
+        active_era: &Era,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        if cache.db_info_refresh.is_due(
+            &CONFIG.validator_list_updater_cadence.db_info,
+            finalized_block_number,
+        ) {
+            debug!("Refresh relational database fields.");
+            cache.validator_infos.clear();
+            // locations are resolved from the Telemetry and 1KV data
+            cache.validator_locations =
+                if CONFIG.is_telemetry_enabled() || CONFIG.is_onekv_enabled() {
+                    postgres.get_validator_locations().await?
+                } else {
+                    HashMap::new()
+                };
+            cache.average_payout_lags = postgres
+                .get_average_payout_lags(
+                    active_era.index,
+                    CONFIG.validator_list_updater.payout_lag_era_count,
+                )
+                .await?;
+            cache.db_info_refresh.set_refreshed(finalized_block_number);
+        }
+        for validator in validators.iter_mut() {
+            if !cache.validator_infos.contains_key(&validator.account.id) {
+                let db_validator_info = postgres
+                    .get_validator_info(
+                        finalized_block_hash,
+                        &validator.account.id,
+                        validator.is_active,
+                        active_era.index,
+                    )
+                    .await?;
+                cache
+                    .validator_infos
+                    .insert(validator.account.id.clone(), db_validator_info);
+            }
+            if let Some(db_validator_info) = cache.validator_infos.get(&validator.account.id) {
+                validator.account.discovered_at = db_validator_info.discovered_at;
+                validator.account.killed_at = db_validator_info.killed_at;
+                validator.slash_count = db_validator_info.slash_count;
+                validator.offline_offence_count = db_validator_info.offline_offence_count;
+                validator.active_era_count = db_validator_info.active_era_count;
+                validator.inactive_era_count = db_validator_info.inactive_era_count;
+                validator.total_reward_points = db_validator_info.total_reward_points;
+                validator.unclaimed_era_indices = db_validator_info.unclaimed_era_indices.clone();
+                validator.blocks_authored = db_validator_info.blocks_authored;
+                validator.reward_points = db_validator_info.reward_points;
+                validator.heartbeat_received = db_validator_info.heartbeat_received;
+                if CONFIG.is_onekv_enabled() {
+                    validator.onekv_candidate_record_id =
+                        db_validator_info.onekv_candidate_record_id;
+                    validator.onekv_rank = db_validator_info.onekv_rank;
+                    validator.onekv_is_valid = db_validator_info.onekv_is_valid;
+                }
+            }
+            validator.location = cache
+                .validator_locations
+                .get(&validator.account.id)
+                .cloned();
+            validator.avg_payout_lag_eras_thousandths = cache
+                .average_payout_lags
+                .get(&validator.account.id)
+                .cloned();
+        }
+        Ok(())
+    }
+
+    /// Sets the latest commission change announcement of each validator, unless the on-chain
+    /// commission has changed after the announcement or is already the announced commission.
+    /// Has to run after the preference changes are updated.
+    async fn update_announced_commission_changes(
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut FieldGroupCache,
+        finalized_block_number: u64,
+        active_era: &Era,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        if cache.announced_commission_changes_refresh.is_due(
+            &CONFIG
+                .validator_list_updater_cadence
+                .announced_commission_changes,
+            finalized_block_number,
+        ) {
+            cache.announced_commission_changes = postgres
+                .get_announced_commission_changes(
+                    active_era.index,
+                    CONFIG
+                        .validator_list_updater
+                        .commission_change_announcement_max_age_days,
+                )
+                .await?;
+            cache
+                .announced_commission_changes_refresh
+                .set_refreshed(finalized_block_number);
+        }
+        for validator in validators.iter_mut() {
+            let announcement = match cache
+                .announced_commission_changes
+                .get(&validator.account.id)
+            {
+                Some(announcement) => announcement,
+                None => {
+                    validator.announced_commission_change = None;
+                    continue;
+                }
+            };
+            let last_commission_change_timestamp = validator
+                .preference_changes
+                .iter()
+                .find(|change| {
+                    change.previous_commission_per_billion != Some(change.commission_per_billion)
+                })
+                .and_then(|change| change.timestamp);
+            let is_superseded = last_commission_change_timestamp
+                .map(|timestamp| timestamp >= announcement.announced_at)
+                .unwrap_or(false);
+            validator.announced_commission_change = if is_superseded
+                || announcement.commission_per_billion
+                    == validator.preferences.commission_per_billion
+            {
+                None
+            } else {
+                Some(announcement.clone())
+            };
+        }
+        Ok(())
+    }
+
+    /// Probability of a Poisson-distributed variable with the given mean being at most `k`.
+    /// Approximates the binomial distribution of the primary slot claims, which have a small
+    /// per-slot probability.
+    fn get_poisson_cdf(mean: f64, k: u32) -> f64 {
+        let mut term = (-mean).exp();
+        let mut sum = term;
+        for i in 1..=k {
+            term *= mean / i as f64;
+            sum += term;
+        }
+        sum.min(1.0)
+    }
+
+    /// Sets the missed primary slots of the active validators from the cache, which gets
+    /// refreshed with the field group cadence.
+    async fn update_missed_primary_slots(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut FieldGroupCache,
+        finalized_block_number: u64,
+        active_era: &Era,
+        finalized_block_hash: &str,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        if cache.missed_primary_slots_refresh.is_due(
+            &CONFIG.validator_list_updater_cadence.missed_primary_slots,
+            finalized_block_number,
+        ) {
+            cache.missed_primary_slots = ValidatorListUpdater::get_missed_primary_slots(
+                client,
+                postgres,
+                active_era,
+                finalized_block_hash,
+                validators,
+            )
+            .await?;
+            cache
+                .missed_primary_slots_refresh
+                .set_refreshed(finalized_block_number);
+        }
+        for validator in validators.iter_mut() {
+            validator.missed_primary_slots = cache
+                .missed_primary_slots
+                .get(&validator.account.id)
+                .cloned();
+        }
+        Ok(())
+    }
+
+    /// Sets the heartbeat status of the session validators from the `ImOnline` storage, which
+    /// also counts the blocks authored in the session. The indexed heartbeat events are kept as
+    /// a fallback when the storage cannot be read.
+    async fn update_heartbeats(
+        client: &SubstrateClient,
+        finalized_block_hash: &str,
+        validators: &mut [ValidatorDetails],
+    ) {
+        let heartbeats = match client.get_session_heartbeats(finalized_block_hash).await {
+            Ok(heartbeats) => heartbeats,
+            Err(error) => {
+                warn!(
+                    "Cannot get session heartbeats, using the indexed heartbeats: {:?}",
+                    error
+                );
+                return;
+            }
+        };
+        for validator in validators.iter_mut() {
+            if let Some(heartbeat_received) = heartbeats.get(&validator.account.id) {
+                validator.heartbeat_received = Some(*heartbeat_received);
+            }
+        }
+    }
+
+    /// Sets the reward points of the validators that were active in the last completed session.
+    /// The points get computed once per session, as soon as the first blocks of the last and the
+    /// current session are indexed.
+    async fn update_session_reward_points(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut SessionRewardPointsCache,
+        finalized_block_hash: &str,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        let current_session_index = client
+            .get_current_session_index(finalized_block_hash)
+            .await? as u64;
+        if current_session_index > 0 && cache.session_index != Some(current_session_index - 1) {
+            ValidatorListUpdater::refresh_session_reward_points(
+                client,
+                postgres,
+                cache,
+                current_session_index - 1,
+            )
+            .await?;
+        }
+        for validator in validators.iter_mut() {
+            validator.last_session_reward_points =
+                cache.reward_points.get(&validator.account.id).cloned();
+        }
+        Ok(())
+    }
+
+    /// Computes the reward points earned by the session validators in the session from the
+    /// `ErasRewardPoints` of the session's era at the first blocks of the session and the next
+    /// one. The zero-point session counts continue from the cached previous session. No-op if
+    /// any of the blocks is not indexed yet.
+    async fn refresh_session_reward_points(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut SessionRewardPointsCache,
+        session_index: u64,
+    ) -> anyhow::Result<()> {
+        let (start_block_hash, era_index) = match postgres
+            .get_session_first_block(session_index as u32)
+            .await?
+        {
+            Some(block) => block,
+            None => return Ok(()),
+        };
+        let end_block_hash = match postgres
+            .get_session_first_block(session_index as u32 + 1)
+            .await?
+        {
+            Some((block_hash, _)) => block_hash,
+            None => return Ok(()),
+        };
+        let reward_points = client
+            .get_era_reward_points_delta(era_index, &start_block_hash, &end_block_hash)
+            .await?;
+        let session_validator_account_ids = client
+            .get_active_validator_account_ids(&start_block_hash)
+            .await?;
+        let is_consecutive = cache.session_index.map(|index| index + 1) == Some(session_index);
+        let mut session_reward_points = HashMap::new();
+        for account_id in session_validator_account_ids {
+            let validator_reward_points = reward_points.get(&account_id).cloned().unwrap_or(0);
+            let zero_point_session_count = if validator_reward_points > 0 {
+                0
+            } else if is_consecutive {
+                cache
+                    .reward_points
+                    .get(&account_id)
+                    .map(|last| last.zero_point_session_count)
+                    .unwrap_or(0)
+                    + 1
+            } else {
+                1
+            };
+            session_reward_points.insert(
+                account_id,
+                SessionRewardPoints {
+                    session_index,
+                    reward_points: validator_reward_points,
+                    zero_point_session_count,
+                },
+            );
+        }
+        debug!(
+            "Got the reward points of {} validators for session #{}.",
+            session_reward_points.len(),
+            session_index,
+        );
+        *cache = SessionRewardPointsCache {
+            session_index: Some(session_index),
+            reward_points: session_reward_points,
+        };
+        Ok(())
+    }
+
+    async fn update_recent_activity(
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut FieldGroupCache,
+        finalized_block_number: u64,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        if cache.recent_activity_refresh.is_due(
+            &CONFIG.validator_list_updater_cadence.recent_activity,
+            finalized_block_number,
+        ) {
+            let account_ids: Vec<AccountId> = validators
+                .iter()
+                .map(|validator| validator.account.id.clone())
+                .collect();
+            cache.recent_activities = postgres
+                .get_validator_activities(
+                    &account_ids,
+                    CONFIG.validator_list_updater.recent_activity_size,
+                )
+                .await?;
+            cache
+                .recent_activity_refresh
+                .set_refreshed(finalized_block_number);
+        }
+        for validator in validators.iter_mut() {
+            validator.recent_activity = cache
+                .recent_activities
+                .get(&validator.account.id)
+                .cloned()
+                .unwrap_or_default();
+        }
+        Ok(())
+    }
+
+    /// Computes the expected and actual primary slot claims of the active validators in the
+    /// last completed session. The current session is not used, so that the metric doesn't
+    /// change with every block. Skipped in the first session of an era, since the last
+    /// session had a different active validator set.
+    async fn get_missed_primary_slots(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        active_era: &Era,
+        finalized_block_hash: &str,
+        validators: &[ValidatorDetails],
+    ) -> anyhow::Result<HashMap<AccountId, MissedPrimarySlots>> {
+        let mut missed_primary_slots = HashMap::new();
+        let current_session_index = client.get_current_epoch_index(finalized_block_hash).await?;
+        if current_session_index == 0 {
+            return Ok(missed_primary_slots);
+        }
+        let session_index = current_session_index - 1;
+        let slot_claims = match postgres.get_session_slot_claims(session_index).await? {
+            Some(slot_claims) if slot_claims.era_index == active_era.index => slot_claims,
+            _ => return Ok(missed_primary_slots),
+        };
+        let active_validator_count = validators
+            .iter()
+            .filter(|validator| validator.is_active)
+            .count();
+        if active_validator_count == 0 {
+            return Ok(missed_primary_slots);
+        }
+        let elapsed_slot_count = slot_claims.last_slot_number - slot_claims.first_slot_number + 1;
+        let primary_slot_probability = 1.0
+            - (1.0 - CONFIG.validator_list_updater.babe_primary_slot_probability)
+                .powf(1.0 / active_validator_count as f64);
+        let expected_primary_slot_count = elapsed_slot_count as f64 * primary_slot_probability;
+        for validator in validators.iter().filter(|validator| validator.is_active) {
+            let (primary_block_count, secondary_block_count) = slot_claims
+                .validator_block_counts
+                .get(&validator.account.id)
+                .cloned()
+                .unwrap_or((0, 0));
+            let bad_luck_probability = ValidatorListUpdater::get_poisson_cdf(
+                expected_primary_slot_count,
+                primary_block_count,
+            );
+            missed_primary_slots.insert(
+                validator.account.id.clone(),
+                MissedPrimarySlots {
+                    session_index,
+                    elapsed_slot_count,
+                    expected_primary_slot_count_thousandths: (expected_primary_slot_count * 1000.0)
+                        .round()
+                        as u64,
+                    primary_block_count,
+                    secondary_block_count,
+                    missed_primary_slot_count: (expected_primary_slot_count.floor() as u32)
+                        .saturating_sub(primary_block_count),
+                    bad_luck_probability_per_billion: (bad_luck_probability * 1_000_000_000.0)
+                        .round() as u32,
+                    // secondary blocks prove that the validator was online
+                    is_likely_offline: primary_block_count + secondary_block_count == 0
+                        && bad_luck_probability
+                            < CONFIG
+                                .validator_list_updater
+                                .missed_primary_slot_bad_luck_probability_threshold,
+                },
+            );
+        }
+        Ok(missed_primary_slots)
+    }
+
+    /// The recent slash and inactive controller flags come from the relational database and
+    /// get refreshed with the field group cadence, or when there is a new controller account.
+    async fn update_risk_flags(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        cache: &mut FieldGroupCache,
+        finalized_block_number: u64,
+        active_era: &Era,
+        validators: &mut [ValidatorDetails],
+    ) -> anyhow::Result<()> {
+        let controller_low_balance_threshold = client.get_constants().existential_deposit
+            * CONFIG
+                .validator_list_updater
+                .controller_low_balance_existential_deposit_multiple as Balance;
+        let has_new_controller = validators.iter().any(|validator| {
+            !cache
+                .checked_controller_account_ids
+                .contains(&validator.controller_account_id)
+        });
+        if has_new_controller
+            || cache.risk_flags_refresh.is_due(
+                &CONFIG.validator_list_updater_cadence.risk_flags,
+                finalized_block_number,
+            )
+        {
+            cache.recently_slashed_account_ids = postgres
+                .get_validator_account_ids_slashed_since_era(
+                    active_era
+                        .index
+                        .saturating_sub(CONFIG.validator_list_updater.recent_slash_era_count),
+                )
+                .await?
+                .into_iter()
+                .collect();
+            let controller_account_ids: Vec<AccountId> = validators
+                .iter()
+                .map(|validator| validator.controller_account_id.clone())
+                .collect();
+            cache.killed_controller_account_ids = postgres
+                .get_killed_account_ids(&controller_account_ids)
+                .await?
+                .into_iter()
+                .collect();
+            cache.checked_controller_account_ids = controller_account_ids.into_iter().collect();
+            cache
+                .risk_flags_refresh
+                .set_refreshed(finalized_block_number);
+        }
+        for validator in validators.iter_mut() {
+            validator.risk_flags = ValidatorRiskFlags {
+                commission_100_percent: validator.preferences.commission_per_billion
+                    >= 1_000_000_000,
+                blocks_nominations: validator.preferences.blocks_nominations,
+                no_identity: validator.account.identity.is_none()
+                    && validator.account.parent.is_none(),
+                slashed_recently: cache
+                    .recently_slashed_account_ids
+                    .contains(&validator.account.id),
+                oversubscribed: validator.oversubscribed,
+                inactive_controller: cache
+                    .killed_controller_account_ids
+                    .contains(&validator.controller_account_id),
+                controller_low_balance: validator.controller_fee_spendable_balance
+                    < controller_low_balance_threshold,
+            };
+        }
+        Ok(())
+    }
+
+    /// Publishes the provisional validator list reconstructed from the latest indexed era (see
+    /// `get_provisional_validator_list`) for the last indexed block, so that the list servers
+    /// have a best-effort list to serve until the list of the first finalized block is ready.
+    /// Needed when the real-time store doesn't persist its data, e.g. Redis without AOF.
+    async fn publish_provisional_validator_list(
+        postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
+        processed_block_numbers: &Arc<RwLock<Vec<u64>>>,
+    ) -> anyhow::Result<()> {
+        let block_number = match postgres.get_processed_block_height().await? {
+            block_number if block_number >= 0 => block_number as u64,
+            _ => {
+                debug!("No indexed blocks. Skip provisional validator list.");
+                return Ok(());
+            }
+        };
+        let (active_era, mut validators) = match postgres.get_provisional_validator_list().await? {
+            Some(era_and_validators) => era_and_validators,
+            None => {
+                debug!("No indexed eras. Skip provisional validator list.");
+                return Ok(());
+            }
+        };
+        let validator_locations = postgres.get_validator_locations().await?;
+        for validator in validators.iter_mut() {
+            validator.location = validator_locations.get(&validator.account.id).cloned();
+        }
+        ValidatorListUpdater::update_realtime_store(
+            realtime_store,
+            &active_era,
+            processed_block_numbers,
+            block_number,
+            &validators,
+            true,
+        )
+        .await?;
+        debug!(
+            "Published provisional list of {} validators of era {} for block #{}.",
+            validators.len(),
+            active_era.index,
+            block_number,
+        );
+        Ok(())
+    }
+
     async fn fetch_and_update_validator_list(
         client: &SubstrateClient,
         postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
         processed_block_numbers: &Arc<RwLock<Vec<u64>>>,
+        preference_change_cache: &Arc<RwLock<PreferenceChangeCache>>,
+        field_group_cache: &Arc<RwLock<FieldGroupCache>>,
         finalized_block_header: &BlockHeader,
     ) -> anyhow::Result<Vec<ValidatorDetails>> {
         let finalized_block_number = finalized_block_header
@@ -179,43 +827,142 @@ impl ValidatorListUpdater {
             .context("Error while getting validators.")?;
         // enrich data with data from the relational database
         debug!("Get RDB content.");
-        for validator in validators.iter_mut() {
-            let db_validator_info = postgres
-                .get_validator_info(
-                    &finalized_block_hash,
-                    &validator.account.id,
-                    validator.is_active,
-                    active_era.index,
-                )
-                .await?;
-            validator.account.discovered_at = db_validator_info.discovered_at;
-            validator.account.killed_at = db_validator_info.killed_at;
-            validator.slash_count = db_validator_info.slash_count;
-            validator.offline_offence_count = db_validator_info.offline_offence_count;
-            validator.active_era_count = db_validator_info.active_era_count;
-            validator.inactive_era_count = db_validator_info.inactive_era_count;
-            validator.total_reward_points = db_validator_info.total_reward_points;
-            validator.unclaimed_era_indices = db_validator_info.unclaimed_era_indices.clone();
-            validator.blocks_authored = db_validator_info.blocks_authored;
-            validator.reward_points = db_validator_info.reward_points;
-            validator.heartbeat_received = db_validator_info.heartbeat_received;
-            validator.onekv_candidate_record_id = db_validator_info.onekv_candidate_record_id;
-            validator.onekv_rank = db_validator_info.onekv_rank;
-            validator.onekv_is_valid = db_validator_info.onekv_is_valid;
+        let mut field_group_cache = field_group_cache.write().await;
+        if field_group_cache.era_index != Some(active_era.index) {
+            debug!("New era. Reset field group cache.");
+            *field_group_cache = FieldGroupCache {
+                era_index: Some(active_era.index),
+                session_reward_points: std::mem::take(&mut field_group_cache.session_reward_points),
+                ..Default::default()
+            };
         }
-        debug!("Got RDB content. Update Redis.");
+        ValidatorListUpdater::update_db_info(
+            postgres,
+            &mut field_group_cache,
+            finalized_block_number,
+            &finalized_block_hash,
+            &active_era,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_heartbeats(client, &finalized_block_hash, &mut validators)
+            .await;
+        ValidatorListUpdater::update_preference_changes(
+            postgres,
+            preference_change_cache,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_announced_commission_changes(
+            postgres,
+            &mut field_group_cache,
+            finalized_block_number,
+            &active_era,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_risk_flags(
+            client,
+            postgres,
+            &mut field_group_cache,
+            finalized_block_number,
+            &active_era,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_missed_primary_slots(
+            client,
+            postgres,
+            &mut field_group_cache,
+            finalized_block_number,
+            &active_era,
+            &finalized_block_hash,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_session_reward_points(
+            client,
+            postgres,
+            &mut field_group_cache.session_reward_points,
+            &finalized_block_hash,
+            &mut validators,
+        )
+        .await?;
+        ValidatorListUpdater::update_recent_activity(
+            postgres,
+            &mut field_group_cache,
+            finalized_block_number,
+            &mut validators,
+        )
+        .await?;
+        debug!("Got RDB content. Update real-time store.");
         let start = std::time::Instant::now();
-        ValidatorListUpdater::update_redis(
+        ValidatorListUpdater::update_realtime_store(
+            realtime_store,
             &active_era,
             processed_block_numbers,
             finalized_block_number,
             &validators,
+            false,
         )
         .await?;
         let elapsed = start.elapsed();
-        debug!("Redis updated. Took {} ms.", elapsed.as_millis());
+        debug!("Real-time store updated. Took {} ms.", elapsed.as_millis());
         Ok(validators)
     }
+
+    /// Updates the validator lists of the finalized blocks that got skipped while the previous
+    /// update was in progress, in order, up to the given finalized block (exclusive). Nothing to
+    /// catch up with before the first update, or if there are more than
+    /// `max_catch_up_block_count` skipped blocks.
+    async fn catch_up_with_skipped_blocks(
+        client: &SubstrateClient,
+        postgres: &PostgreSQLNetworkStorage,
+        realtime_store: &dyn RealtimeStore,
+        processed_block_numbers: &Arc<RwLock<Vec<u64>>>,
+        preference_change_cache: &Arc<RwLock<PreferenceChangeCache>>,
+        field_group_cache: &Arc<RwLock<FieldGroupCache>>,
+        finalized_block_number: u64,
+    ) -> anyhow::Result<()> {
+        let last_processed_block_number = match processed_block_numbers.read().await.last() {
+            Some(block_number) => *block_number,
+            None => return Ok(()),
+        };
+        if last_processed_block_number + 1 >= finalized_block_number {
+            return Ok(());
+        }
+        let skipped_block_count = finalized_block_number - last_processed_block_number - 1;
+        if skipped_block_count > CONFIG.validator_list_updater.max_catch_up_block_count {
+            warn!(
+                "{} blocks skipped since block #{}. Skip to finalized block #{}.",
+                skipped_block_count, last_processed_block_number, finalized_block_number,
+            );
+            return Ok(());
+        }
+        debug!(
+            "Catch up with {} skipped blocks before finalized block #{}.",
+            skipped_block_count, finalized_block_number,
+        );
+        let mut block_header_stream =
+            Box::pin(client.get_block_header_stream(
+                last_processed_block_number + 1,
+                finalized_block_number - 1,
+            ));
+        while let Some(block_header_result) = block_header_stream.next().await {
+            let (_, block_header) = block_header_result?;
+            ValidatorListUpdater::fetch_and_update_validator_list(
+                client,
+                postgres,
+                realtime_store,
+                processed_block_numbers,
+                preference_change_cache,
+                field_group_cache,
+                &block_header,
+            )
+            .await?;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait(?Send)]
@@ -228,42 +975,106 @@ impl Service for ValidatorListUpdater {
             let substrate_client = Arc::new(SubstrateClient::new(&CONFIG).await?);
             let is_busy = Arc::new(AtomicBool::new(false));
             let processed_block_numbers: Arc<RwLock<Vec<u64>>> = Arc::new(RwLock::new(Vec::new()));
-            // clean Redis history
+            let preference_change_cache: Arc<RwLock<PreferenceChangeCache>> =
+                Arc::new(RwLock::new(PreferenceChangeCache::default()));
+            let field_group_cache: Arc<RwLock<FieldGroupCache>> =
+                Arc::new(RwLock::new(FieldGroupCache::default()));
+            let realtime_store = get_realtime_store(&CONFIG)?;
+            // clean real-time store history
+            {
+                debug!("Clean real-time store history.");
+                let mut batch = RealtimeWriteBatch::default();
+                batch.delete_with_prefix(&format!("subvt:{}:", CONFIG.substrate.chain));
+                realtime_store.write(&batch)?;
+            }
+            // formatting properties for the clients of the list servers
+            {
+                let mut batch = RealtimeWriteBatch::default();
+                batch.set(
+                    &format!("subvt:{}:network_meta", CONFIG.substrate.chain),
+                    serde_json::to_string(&NetworkMeta::from(
+                        &substrate_client.get_chain_descriptor(),
+                    ))?,
+                );
+                realtime_store.write(&batch)?;
+            }
+            if let Err(error) = ValidatorListUpdater::publish_provisional_validator_list(
+                &postgres,
+                realtime_store.as_ref(),
+                &processed_block_numbers,
+            )
+            .await
             {
-                debug!("Clean Redis history.");
-                let redis_client = redis::Client::open(CONFIG.redis.url.as_str())?;
-                let mut connection = redis_client.get_connection().context(format!(
-                    "Cannot connect to Redis at URL {}.",
-                    CONFIG.redis.url
+                error!("Cannot publish provisional validator list: {:?}", error);
+            }
+            // a refresh request (e.g. from `subvt-cli`) makes the next update rebuild
+            // the cached data from scratch
+            let is_refresh_requested = Arc::new(AtomicBool::new(false));
+            {
+                let refresh_subscription = realtime_store.subscribe(&format!(
+                    "subvt:{}:validators:command:refresh",
+                    CONFIG.substrate.chain
                 ))?;
-                let keys: Vec<String> = redis::cmd("KEYS")
-                    .arg(format!("subvt:{}:*", CONFIG.substrate.chain))
-                    .query(&mut connection)?;
-                let mut redis_cmd_pipeline = Pipeline::new();
-                for key in keys {
-                    redis_cmd_pipeline.cmd("DEL").arg(key);
-                }
-                redis_cmd_pipeline.query(&mut connection)?;
+                let is_refresh_requested = is_refresh_requested.clone();
+                std::thread::spawn(move || loop {
+                    match refresh_subscription.next_block_number() {
+                        Ok(_) => {
+                            debug!("Refresh requested.");
+                            is_refresh_requested.store(true, Ordering::SeqCst);
+                        }
+                        Err(error) => {
+                            error!("Refresh request subscription exited: {:?}", error);
+                            return;
+                        }
+                    }
+                });
             }
-            substrate_client.subscribe_to_finalized_blocks(|finalized_block_header| {
+            let block_subscription = substrate_client.subscribe_to_finalized_blocks(|finalized_block_header| {
                 let finalized_block_number = match finalized_block_header.get_number() {
                     Ok(block_number) => block_number,
                     Err(_) => return error!("Cannot get block number for header: {:?}", finalized_block_header)
                 };
                 if is_busy.load(Ordering::SeqCst) {
-                    trace!("Busy processing a past block. Skip block #{}.", finalized_block_number);
+                    trace!("Busy processing a past block. Skip block #{} for now.", finalized_block_number);
                     return;
                 }
                 is_busy.store(true, Ordering::SeqCst);
                 let processed_block_numbers = processed_block_numbers.clone();
+                let preference_change_cache = preference_change_cache.clone();
+                let field_group_cache = field_group_cache.clone();
                 let substrate_client = Arc::clone(&substrate_client);
                 let postgres = postgres.clone();
+                let realtime_store = realtime_store.clone();
                 let is_busy = Arc::clone(&is_busy);
+                let is_refresh_requested = Arc::clone(&is_refresh_requested);
                 tokio::spawn(async move {
+                    if is_refresh_requested.swap(false, Ordering::SeqCst) {
+                        debug!("Reset preference change and field group caches for refresh.");
+                        *preference_change_cache.write().await = PreferenceChangeCache::default();
+                        *field_group_cache.write().await = FieldGroupCache::default();
+                    }
+                    if let Err(error) = ValidatorListUpdater::catch_up_with_skipped_blocks(
+                        &substrate_client,
+                        &postgres,
+                        realtime_store.as_ref(),
+                        &processed_block_numbers,
+                        &preference_change_cache,
+                        &field_group_cache,
+                        finalized_block_number,
+                    ).await {
+                        error!("{:?}", error);
+                        error!(
+                            "Cannot catch up with the skipped blocks before block #{}.",
+                            finalized_block_number,
+                        );
+                    }
                     let update_result = ValidatorListUpdater::fetch_and_update_validator_list(
                         &substrate_client,
                         &postgres,
+                        realtime_store.as_ref(),
                         &processed_block_numbers,
+                        &preference_change_cache,
+                        &field_group_cache,
                         &finalized_block_header,
                     ).await;
                     if let Err(error) = update_result {
@@ -275,7 +1086,18 @@ impl Service for ValidatorListUpdater {
                     }
                     is_busy.store(false, Ordering::SeqCst);
                 });
-            }).await?;
+            });
+            // the validator list gets rebuilt from scratch with the new metadata
+            tokio::select! {
+                subscription_result = block_subscription => subscription_result?,
+                runtime_upgrade_result = substrate_client.wait_for_runtime_upgrade() => {
+                    info!(
+                        "Runtime upgraded to #{}. Will reconnect with the new metadata.",
+                        runtime_upgrade_result?.spec_version,
+                    );
+                    continue;
+                }
+            }
             let delay_seconds = CONFIG.common.recovery_retry_seconds;
             error!(
                 "New block subscription exited. Will refresh connection and subscription after {} seconds.",